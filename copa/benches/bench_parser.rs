@@ -0,0 +1,120 @@
+extern crate copa;
+extern crate criterion;
+
+use copa::{Params, Parser, Perform};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+/// Captured PTY stream, the same used by the parser tests.
+static DEMO: &[u8] = include_bytes!("../tests/demo.vte");
+
+/// Counts dispatched actions so the parser work can't be optimized away.
+#[derive(Default)]
+struct Counter {
+    actions: usize,
+}
+
+impl Perform for Counter {
+    fn print(&mut self, _c: char) {
+        self.actions += 1;
+    }
+
+    fn execute(&mut self, _byte: u8) {
+        self.actions += 1;
+    }
+
+    fn hook(&mut self, _params: &Params, _intermediates: &[u8], _ignore: bool, _c: char) {
+        self.actions += 1;
+    }
+
+    fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {
+        self.actions += 1;
+    }
+
+    fn csi_dispatch(
+        &mut self,
+        _params: &Params,
+        _intermediates: &[u8],
+        _ignore: bool,
+        _c: char,
+    ) {
+        self.actions += 1;
+    }
+
+    fn esc_dispatch(&mut self, _intermediates: &[u8], _ignore: bool, _byte: u8) {
+        self.actions += 1;
+    }
+}
+
+// Streams below mimic the vtebench benchmarks output for a 80x24 terminal.
+
+fn dense_cells() -> Vec<u8> {
+    let mut stream = Vec::new();
+    for frame in 0..64u32 {
+        stream.extend_from_slice(b"\x1b[H");
+        for line in 0..24u32 {
+            for column in 0..80u32 {
+                let color = (frame + line + column) % 256;
+                stream.extend_from_slice(
+                    format!("\x1b[38;5;{color};48;5;{}m\x1b[1;3;4m", 255 - color)
+                        .as_bytes(),
+                );
+                stream.push(b'A' + (column % 26) as u8);
+            }
+        }
+    }
+    stream
+}
+
+fn scrolling() -> Vec<u8> {
+    let mut stream = Vec::new();
+    for line in 0..20_000u32 {
+        stream.extend_from_slice(format!("line {line} y\r\n").as_bytes());
+    }
+    stream
+}
+
+fn unicode() -> Vec<u8> {
+    let mut stream = Vec::new();
+    for _ in 0..4_000 {
+        stream.extend_from_slice("Ｒｉｏ 🦀 ação ñ 日本語 ✓ ∑\r\n".as_bytes());
+    }
+    stream
+}
+
+fn cursor_motion() -> Vec<u8> {
+    let mut stream = Vec::new();
+    for step in 0..40_000u32 {
+        let (line, column) = (step % 24 + 1, step % 80 + 1);
+        stream.extend_from_slice(format!("\x1b[{line};{column}Hx").as_bytes());
+    }
+    stream
+}
+
+fn bench_parser(c: &mut Criterion) {
+    let streams = [
+        ("demo", DEMO.to_vec()),
+        ("dense_cells", dense_cells()),
+        ("scrolling", scrolling()),
+        ("unicode", unicode()),
+        ("cursor_motion", cursor_motion()),
+    ];
+
+    let mut group = c.benchmark_group("bench_parser");
+    for (name, stream) in streams.iter() {
+        group.throughput(Throughput::Bytes(stream.len() as u64));
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                let mut parser = Parser::new();
+                let mut counter = Counter::default();
+                for byte in stream.iter() {
+                    parser.advance(&mut counter, *byte);
+                }
+                black_box(counter.actions)
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parser);
+criterion_main!(benches);
@@ -0,0 +1,168 @@
+//! Entry points of the fuzz targets, kept out of the `fuzz_target!` bodies so
+//! a crashing input can be replayed from a test or a debugger:
+//!
+//! ```sh
+//! cargo +nightly fuzz run csi_params
+//! cargo +nightly fuzz run osc_strings -- -max_len=4096
+//! ```
+//!
+//! Each target wraps the input into the sequence it covers, feeds it through
+//! the parser and checks the bounds the parser promises to its `Perform`:
+//! at most 32 CSI or DCS params, 2 intermediates and 16 OSC params over a
+//! 1024 bytes buffer. A cancel and a printable byte follow, the parser must
+//! be back in the ground state to print it whatever the input left behind.
+
+use copa::{Params, Parser, Perform};
+
+const MAX_PARAMS: usize = 32;
+const MAX_INTERMEDIATES: usize = 2;
+const MAX_OSC_PARAMS: usize = 16;
+const MAX_OSC_RAW: usize = 1024;
+
+/// Checks the actions dispatched by the parser.
+#[derive(Default)]
+struct Checker {
+    last_print: Option<char>,
+    hooked: bool,
+}
+
+impl Checker {
+    fn check_params(params: &Params, intermediates: &[u8]) {
+        assert!(params.len() <= MAX_PARAMS, "{} params", params.len());
+        assert!(params.iter().map(|param| param.len()).sum::<usize>() <= MAX_PARAMS);
+        assert!(intermediates.len() <= MAX_INTERMEDIATES);
+    }
+}
+
+impl Perform for Checker {
+    fn print(&mut self, c: char) {
+        self.last_print = Some(c);
+    }
+
+    fn execute(&mut self, _byte: u8) {
+        self.last_print = None;
+    }
+
+    fn hook(&mut self, params: &Params, intermediates: &[u8], _ignore: bool, _c: char) {
+        Checker::check_params(params, intermediates);
+        assert!(!self.hooked, "hooked twice without unhook");
+        self.hooked = true;
+        self.last_print = None;
+    }
+
+    fn put(&mut self, _byte: u8) {
+        assert!(self.hooked, "put outside of a DCS");
+    }
+
+    fn unhook(&mut self) {
+        assert!(self.hooked, "unhook without hook");
+        self.hooked = false;
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) {
+        assert!(
+            params.len() <= MAX_OSC_PARAMS,
+            "{} osc params",
+            params.len()
+        );
+        assert!(params.iter().map(|param| param.len()).sum::<usize>() <= MAX_OSC_RAW);
+        self.last_print = None;
+    }
+
+    fn csi_dispatch(
+        &mut self,
+        params: &Params,
+        intermediates: &[u8],
+        _ignore: bool,
+        _c: char,
+    ) {
+        Checker::check_params(params, intermediates);
+        self.last_print = None;
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], _ignore: bool, _byte: u8) {
+        assert!(intermediates.len() <= MAX_INTERMEDIATES);
+        self.last_print = None;
+    }
+}
+
+/// Feed `input` and check the parser recovers once it is over.
+fn feed(input: impl IntoIterator<Item = u8>) {
+    let mut parser = Parser::new();
+    let mut checker = Checker::default();
+    for byte in input {
+        parser.advance(&mut checker, byte);
+    }
+
+    // CAN aborts any sequence, an incomplete UTF-8 character is printed as the
+    // replacement character instead.
+    for byte in [0x18, b'x'] {
+        parser.advance(&mut checker, byte);
+    }
+    assert_eq!(checker.last_print, Some('x'), "parser did not recover");
+}
+
+/// Any bytes.
+pub fn parser(data: &[u8]) {
+    feed(data.iter().copied());
+}
+
+/// CSI sequences with the input mapped to digits and separators, the first
+/// byte picks the private marker and the final byte.
+pub fn csi_params(data: &[u8]) {
+    const PARAMS: &[u8] = b"0123456789;;;:";
+    let Some((&first, params)) = data.split_first() else {
+        return;
+    };
+
+    let mut input = b"\x1b[".to_vec();
+    if first & 0x80 != 0 {
+        input.push(b"?<=>"[(first & 0x3) as usize]);
+    }
+    input.extend(
+        params
+            .iter()
+            .map(|byte| PARAMS[*byte as usize % PARAMS.len()]),
+    );
+    if first & 0x40 != 0 {
+        input.push(b" !\"$'*"[(first as usize >> 2) % 6]);
+    }
+    input.push(0x40 + (first & 0x3f).min(0x3e));
+    feed(input);
+}
+
+/// OSC strings, terminated by BEL or ST after the first byte.
+pub fn osc_strings(data: &[u8]) {
+    let Some((&first, string)) = data.split_first() else {
+        return;
+    };
+
+    let mut input = b"\x1b]".to_vec();
+    input.extend_from_slice(string);
+    if first & 1 == 0 {
+        input.push(0x07);
+    } else {
+        input.extend_from_slice(b"\x1b\\");
+    }
+    feed(input);
+}
+
+/// DCS and APC payloads: sixel images, terminfo and setting queries, tmux
+/// control mode and kitty graphics commands, picked by the first byte.
+pub fn dcs_payloads(data: &[u8]) {
+    let Some((&first, payload)) = data.split_first() else {
+        return;
+    };
+
+    let introducer: &[u8] = match first % 5 {
+        0 => b"\x1bP0;1;0q",
+        1 => b"\x1bP+q",
+        2 => b"\x1bP$q",
+        3 => b"\x1bP1000p",
+        _ => b"\x1b_G",
+    };
+    let mut input = introducer.to_vec();
+    input.extend_from_slice(payload);
+    input.extend_from_slice(b"\x1b\\");
+    feed(input);
+}
@@ -50,6 +50,10 @@ use definitions::{unpack, Action, State};
 const MAX_INTERMEDIATES: usize = 2;
 const MAX_OSC_PARAMS: usize = 16;
 const MAX_OSC_RAW: usize = 1024;
+/// Bytes kept of an OSC string without the `no_std` buffer, the rest is dropped
+/// so a never terminated string can't take all the memory.
+#[cfg(not(feature = "no_std"))]
+const MAX_OSC_RAW_GROWABLE: usize = 0x10_0000;
 
 struct VtUtf8Receiver<'a, P: Perform>(&'a mut P, &'a mut State);
 
@@ -270,6 +274,12 @@ impl<const OSC_RAW_BUF_SIZE: usize> Parser<OSC_RAW_BUF_SIZE> {
                         return;
                     }
                 }
+                #[cfg(not(feature = "no_std"))]
+                {
+                    if self.osc_raw.len() >= MAX_OSC_RAW_GROWABLE {
+                        return;
+                    }
+                }
 
                 let idx = self.osc_raw.len();
 
@@ -727,6 +737,28 @@ mod tests {
         }
     }
 
+    #[cfg(not(feature = "no_std"))]
+    #[test]
+    fn exceed_max_growable_buffer_size() {
+        let mut dispatcher = Dispatcher::default();
+        let mut parser = Parser::new();
+
+        for byte in b"\x1b]52;" {
+            parser.advance(&mut dispatcher, *byte);
+        }
+        for _ in 0..MAX_OSC_RAW_GROWABLE + 100 {
+            parser.advance(&mut dispatcher, b'a');
+        }
+        parser.advance(&mut dispatcher, b'\x07');
+
+        match &dispatcher.dispatched[..] {
+            [Sequence::Osc(params, _)] => {
+                assert_eq!(params[1].len(), MAX_OSC_RAW_GROWABLE - params[0].len());
+            }
+            _ => panic!("expected osc sequence"),
+        }
+    }
+
     #[test]
     fn parse_csi_max_params() {
         // This will build a list of repeating '1;'s
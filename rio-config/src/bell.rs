@@ -0,0 +1,90 @@
+use crate::colors::{deserialize_to_arr, ColorArray};
+use crate::defaults::*;
+use serde::{Deserialize, Serialize};
+
+/// Curve followed by the visual bell as it fades out.
+#[derive(Default, Clone, Serialize, Deserialize, Copy, Debug, PartialEq)]
+pub enum BellAnimation {
+    Linear,
+    EaseOut,
+    EaseOutSine,
+    EaseOutQuad,
+    EaseOutCubic,
+    EaseOutQuart,
+    EaseOutQuint,
+    #[default]
+    EaseOutExpo,
+    EaseOutCirc,
+}
+
+impl BellAnimation {
+    /// Progress of the fade for `t` in `0.0..=1.0`, from 0.0 to 1.0.
+    pub fn ease(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            BellAnimation::Linear => t,
+            // Gentler than the quadratic curve, close to CSS `ease-out`.
+            BellAnimation::EaseOut => 1.0 - (1.0 - t).powf(1.7),
+            BellAnimation::EaseOutSine => (t * std::f32::consts::FRAC_PI_2).sin(),
+            BellAnimation::EaseOutQuad => 1.0 - (1.0 - t).powi(2),
+            BellAnimation::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+            BellAnimation::EaseOutQuart => 1.0 - (1.0 - t).powi(4),
+            BellAnimation::EaseOutQuint => 1.0 - (1.0 - t).powi(5),
+            BellAnimation::EaseOutExpo if t >= 1.0 => 1.0,
+            BellAnimation::EaseOutExpo => 1.0 - 2f32.powf(-10.0 * t),
+            BellAnimation::EaseOutCirc => (1.0 - (1.0 - t).powi(2)).sqrt(),
+        }
+    }
+}
+
+#[derive(PartialEq, Serialize, Deserialize, Clone, Debug)]
+pub struct Bell {
+    #[serde(default = "BellAnimation::default")]
+    pub animation: BellAnimation,
+    /// Duration of the flash in milliseconds, zero disables it.
+    #[serde(default = "u64::default")]
+    pub duration: u64,
+    #[serde(
+        default = "default_bell_color",
+        deserialize_with = "deserialize_to_arr"
+    )]
+    pub color: ColorArray,
+}
+
+impl Default for Bell {
+    fn default() -> Bell {
+        Bell {
+            animation: BellAnimation::default(),
+            duration: 0,
+            color: default_bell_color(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bell_animation_ease() {
+        let animations = [
+            BellAnimation::Linear,
+            BellAnimation::EaseOut,
+            BellAnimation::EaseOutSine,
+            BellAnimation::EaseOutQuad,
+            BellAnimation::EaseOutCubic,
+            BellAnimation::EaseOutQuart,
+            BellAnimation::EaseOutQuint,
+            BellAnimation::EaseOutExpo,
+            BellAnimation::EaseOutCirc,
+        ];
+        for animation in animations {
+            assert_eq!(animation.ease(0.0), 0.0, "{animation:?}");
+            assert_eq!(animation.ease(1.0), 1.0, "{animation:?}");
+            assert_eq!(animation.ease(2.0), 1.0, "{animation:?}");
+            // Ease out curves are ahead of the linear one.
+            assert!(animation.ease(0.5) >= 0.5, "{animation:?}");
+        }
+        assert_eq!(BellAnimation::EaseOutQuad.ease(0.5), 0.75);
+    }
+}
@@ -24,7 +24,61 @@ pub type KeyBindings = Vec<KeyBinding>;
 
 #[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Bindings {
+    #[serde(default = "Vec::default")]
     pub keys: KeyBindings,
+    #[serde(default = "Option::default")]
+    pub leader: Option<Leader>,
+    #[serde(default = "Vec::default")]
+    pub mouse: MouseBindings,
+}
+
+// Examples:
+// { button = "middle", action = "PasteSelection" }
+// { button = "back", action = "ScrollToPreviousPrompt" }
+// { button = "left", with = "control", clicks = 2, action = "SelectLastCommandOutput" }
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct MouseBinding {
+    /// `left`, `middle`, `right`, `back`, `forward` or the number of the button.
+    pub button: String,
+    #[serde(default = "String::default")]
+    pub with: String,
+    /// Clicks in a row the binding applies to, any when unset.
+    #[serde(default = "Option::default")]
+    pub clicks: Option<u8>,
+    #[serde(default = "String::default")]
+    pub action: String,
+    #[serde(default = "String::default")]
+    pub mode: String,
+}
+
+pub type MouseBindings = Vec<MouseBinding>;
+
+/// Key pressed before the bindings with `leader` in `with`, like the prefix
+/// of tmux. It is written to the terminal when pressed twice.
+// leader = { key = "a", with = "control", timeout = 1000 }
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Leader {
+    pub key: String,
+    #[serde(default = "String::default")]
+    pub with: String,
+    /// Milliseconds the leader waits for the next key.
+    #[serde(default = "default_leader_timeout")]
+    pub timeout: u64,
+}
+
+#[inline]
+fn default_leader_timeout() -> u64 {
+    1000
+}
+
+/// System-wide key combination that shows or hides the windows.
+// { key = "F12", with = "control | shift" }
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct GlobalHotkey {
+    pub key: String,
+    #[serde(default = "String::default")]
+    pub with: String,
 }
 
 #[cfg(test)]
@@ -157,4 +211,54 @@ mod tests {
         assert_eq!(decoded.bindings.keys[5].action.to_owned(), "selectprevtab");
         assert!(decoded.bindings.keys[5].text.to_owned().is_empty());
     }
+
+    #[test]
+    fn test_leader() {
+        let content = r#"
+            [bindings]
+            leader = { key = 'a', with = 'control' }
+            keys = [
+                { key = 'c', with = 'leader', action = 'CreateTab' },
+            ]
+        "#;
+
+        let decoded = toml::from_str::<Root>(content).unwrap();
+        let leader = decoded.bindings.leader.unwrap();
+        assert_eq!(leader.key, "a");
+        assert_eq!(leader.with, "control");
+        assert_eq!(leader.timeout, 1000);
+        assert_eq!(decoded.bindings.keys[0].with, "leader");
+
+        let content = r#"
+            [bindings]
+            leader = { key = 'space', with = 'control | shift', timeout = 500 }
+        "#;
+
+        let decoded = toml::from_str::<Root>(content).unwrap();
+        assert_eq!(decoded.bindings.leader.unwrap().timeout, 500);
+        assert!(decoded.bindings.keys.is_empty());
+    }
+
+    #[test]
+    fn test_mouse_bindings() {
+        let content = r#"
+            [bindings]
+            mouse = [
+                { button = 'middle', action = 'PasteSelection' },
+                { button = 'left', with = 'control', clicks = 2, action = 'Copy', mode = '~alt' },
+            ]
+        "#;
+
+        let decoded = toml::from_str::<Root>(content).unwrap();
+        let [middle, left] = &decoded.bindings.mouse[..] else {
+            panic!("two bindings expected");
+        };
+        assert_eq!(middle.button, "middle");
+        assert_eq!(middle.clicks, None);
+        assert!(middle.with.is_empty());
+        assert_eq!(left.with, "control");
+        assert_eq!(left.clicks, Some(2));
+        assert_eq!(left.mode, "~alt");
+        assert!(decoded.bindings.keys.is_empty());
+    }
 }
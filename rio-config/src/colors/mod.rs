@@ -11,7 +11,7 @@ pub type ColorWGPU = wgpu::Color;
 pub type ColorArray = [f32; 4];
 pub type ColorComposition = (ColorArray, ColorWGPU);
 
-#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct ColorRgb {
     pub r: u8,
     pub g: u8,
@@ -21,9 +21,9 @@ pub struct ColorRgb {
 impl ColorRgb {
     pub fn from_color_arr(arr: ColorArray) -> ColorRgb {
         ColorRgb {
-            r: (arr[0] * 255.0) as u8,
-            g: (arr[1] * 255.0) as u8,
-            b: (arr[2] * 255.0) as u8,
+            r: (arr[0] * 255.0).round() as u8,
+            g: (arr[1] * 255.0).round() as u8,
+            b: (arr[2] * 255.0).round() as u8,
         }
     }
 
@@ -46,7 +46,7 @@ pub enum Format {
     SRGB0_1,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum AnsiColor {
     Named(NamedColor),
     Spec(ColorRgb),
@@ -84,6 +84,14 @@ pub struct Colors {
     pub tabs_active: ColorArray,
     #[serde(default = "defaults::cursor", deserialize_with = "deserialize_to_arr")]
     pub cursor: ColorArray,
+    /// Color of the text under a block cursor, by default the text takes the
+    /// background of its square so it looks inverted.
+    #[serde(
+        default = "Option::default",
+        deserialize_with = "deserialize_to_optional_arr",
+        rename = "cursor-text"
+    )]
+    pub cursor_text: Option<ColorArray>,
 
     #[serde(default = "defaults::black", deserialize_with = "deserialize_to_arr")]
     pub black: ColorArray,
@@ -93,6 +101,20 @@ pub struct Colors {
     pub magenta: ColorArray,
     #[serde(default = "defaults::tabs", deserialize_with = "deserialize_to_arr")]
     pub tabs: ColorArray,
+    /// Text of the tabs in the tab bar, `tabs-active` by default.
+    #[serde(
+        default = "Option::default",
+        deserialize_with = "deserialize_to_optional_arr",
+        rename = "tabs-foreground"
+    )]
+    pub tabs_foreground: Option<ColorArray>,
+    /// Text of the current tab in the tab bar, `tabs` by default.
+    #[serde(
+        default = "Option::default",
+        deserialize_with = "deserialize_to_optional_arr",
+        rename = "tabs-active-foreground"
+    )]
+    pub tabs_active_foreground: Option<ColorArray>,
     #[serde(default = "defaults::white", deserialize_with = "deserialize_to_arr")]
     pub white: ColorArray,
     #[serde(
@@ -228,10 +250,13 @@ impl Default for Colors {
             yellow: defaults::yellow(),
             tabs_active: defaults::tabs_active(),
             cursor: defaults::cursor(),
+            cursor_text: None,
             black: defaults::black(),
             cyan: defaults::cyan(),
             magenta: defaults::magenta(),
             tabs: defaults::tabs(),
+            tabs_foreground: None,
+            tabs_active_foreground: None,
             white: defaults::white(),
             dim_black: defaults::dim_black(),
             dim_blue: defaults::dim_blue(),
@@ -269,7 +294,7 @@ pub fn hex_to_color_wgpu(s: &str) -> ColorWGPU {
         .to_wgpu()
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub enum NamedColor {
     /// Black.
     Black = 0,
@@ -577,6 +602,15 @@ where
     }
 }
 
+pub fn deserialize_to_optional_arr<'de, D>(
+    deserializer: D,
+) -> Result<Option<ColorArray>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    deserialize_to_arr(deserializer).map(Some)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
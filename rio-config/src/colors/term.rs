@@ -1,4 +1,4 @@
-use crate::colors::{ColorArray, ColorBuilder, ColorRgb, Format};
+use crate::colors::{ColorArray, ColorBuilder, ColorRgb, Colors, Format};
 use std::ops::{Index, IndexMut};
 
 use crate::colors::defaults;
@@ -45,6 +45,48 @@ impl Default for TermColors {
     }
 }
 
+impl TermColors {
+    /// Colors of `theme` taking the place of the configured ones, like the
+    /// colors set by a program.
+    pub fn from_theme(theme: &Colors) -> TermColors {
+        let mut colors = TermColors::default();
+        for (name, color) in [
+            (NamedColor::Black, theme.black),
+            (NamedColor::Red, theme.red),
+            (NamedColor::Green, theme.green),
+            (NamedColor::Yellow, theme.yellow),
+            (NamedColor::Blue, theme.blue),
+            (NamedColor::Magenta, theme.magenta),
+            (NamedColor::Cyan, theme.cyan),
+            (NamedColor::White, theme.white),
+            (NamedColor::LightBlack, theme.light_black),
+            (NamedColor::LightRed, theme.light_red),
+            (NamedColor::LightGreen, theme.light_green),
+            (NamedColor::LightYellow, theme.light_yellow),
+            (NamedColor::LightBlue, theme.light_blue),
+            (NamedColor::LightMagenta, theme.light_magenta),
+            (NamedColor::LightCyan, theme.light_cyan),
+            (NamedColor::LightWhite, theme.light_white),
+            (NamedColor::Foreground, theme.foreground),
+            (NamedColor::Background, theme.background.0),
+            (NamedColor::Cursor, theme.cursor),
+        ] {
+            colors[name] = Some(color);
+        }
+        colors
+    }
+
+    /// Colors of `self`, with the ones it does not set taken from `base`.
+    pub fn or(mut self, base: &TermColors) -> TermColors {
+        for (color, base) in self.0.iter_mut().zip(base.0.iter()) {
+            if color.is_none() {
+                *color = *base;
+            }
+        }
+        self
+    }
+}
+
 impl Index<usize> for TermColors {
     type Output = Option<ColorArray>;
 
@@ -197,3 +239,28 @@ impl IndexMut<NamedColor> for List {
         &mut self.0[idx as usize]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_under_term_colors() {
+        let theme = Colors {
+            red: [1., 0., 0., 1.],
+            ..Colors::default()
+        };
+        let palette = TermColors::from_theme(&theme);
+        assert_eq!(palette[NamedColor::Red], Some([1., 0., 0., 1.]));
+        assert_eq!(palette[NamedColor::Background], Some(theme.background.0));
+        assert_eq!(palette[NamedColor::DimRed], None);
+
+        // Colors set by programs win over the theme.
+        let mut colors = TermColors::default();
+        colors[NamedColor::Red] = Some([0., 1., 0., 1.]);
+        let colors = colors.or(&palette);
+        assert_eq!(colors[NamedColor::Red], Some([0., 1., 0., 1.]));
+        assert_eq!(colors[NamedColor::Blue], Some(theme.blue));
+        assert_eq!(colors[100], None);
+    }
+}
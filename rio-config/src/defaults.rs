@@ -1,3 +1,6 @@
+use crate::colors::ColorArray;
+use crate::window::Decorations;
+
 pub fn default_env_vars() -> Vec<String> {
     vec![]
 }
@@ -18,6 +21,10 @@ pub fn default_line_height() -> f32 {
     1.0
 }
 
+pub fn default_render_scale() -> f32 {
+    1.0
+}
+
 pub fn default_shell() -> crate::Shell {
     #[cfg(not(target_os = "windows"))]
     {
@@ -64,6 +71,120 @@ pub fn default_log_level() -> String {
     String::from("OFF")
 }
 
+pub fn default_include_padding() -> bool {
+    true
+}
+
+#[inline]
+pub fn default_clipboard_history_enabled() -> bool {
+    true
+}
+
+#[inline]
+pub fn default_clipboard_history_size() -> usize {
+    20
+}
+
+#[inline]
+pub fn default_syntax_highlighting() -> bool {
+    true
+}
+
+#[inline]
+pub fn default_arabic_shaping() -> bool {
+    true
+}
+
+#[inline]
+pub fn default_shell_integration() -> bool {
+    true
+}
+
+#[inline]
+pub fn default_join_wrapped_lines() -> bool {
+    true
+}
+
+#[inline]
+pub fn default_monitor_interval() -> u64 {
+    10
+}
+
+#[inline]
+pub fn default_memory_scrollback() -> usize {
+    512
+}
+
+#[inline]
+pub fn default_memory_images() -> usize {
+    256
+}
+
+#[inline]
+pub fn default_memory_glyph_atlas() -> usize {
+    64
+}
+
+#[inline]
+pub fn default_scroll_multiplier() -> f64 {
+    3.0
+}
+
+#[inline]
+pub fn default_scroll_precision_multiplier() -> f64 {
+    1.0
+}
+
+#[inline]
+pub fn default_alternate_scroll_arrows() -> usize {
+    1
+}
+
+pub fn default_quick_select_patterns() -> Vec<String> {
+    vec![
+        // Urls
+        String::from(r#"https?://[^\s"'<>()]+"#),
+        // Paths
+        String::from(r"(?:~|\.{1,2}|[\w.\-]+)?(?:/[\w.\-@~+]+)+/?"),
+        // IPv4 addresses, with an optional port
+        String::from(r"\b\d{1,3}(?:\.\d{1,3}){3}(?::\d+)?\b"),
+        // Hashes and container ids
+        String::from(r"\b[0-9a-f]{7,64}\b"),
+    ]
+}
+
+pub fn default_blinking_text_enabled() -> bool {
+    true
+}
+
+pub fn default_bold_font() -> bool {
+    true
+}
+
+pub fn default_dim_multiplier() -> f32 {
+    0.66
+}
+
+pub fn default_window_decorations() -> Decorations {
+    #[cfg(target_os = "macos")]
+    {
+        Decorations::Transparent
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        Decorations::Enabled
+    }
+}
+
+pub fn default_tab_title_format() -> String {
+    String::from("{index}.{title}")
+}
+
+pub fn default_bell_color() -> ColorArray {
+    [1.0, 1.0, 1.0, 1.0]
+}
+
 pub fn default_cursor() -> char {
     '▇'
 }
@@ -80,6 +201,10 @@ pub fn default_window_height() -> i32 {
     400
 }
 
+pub fn default_word_separators() -> String {
+    String::from(",│`|:\"' ()[]{}<>\t")
+}
+
 pub fn default_config_file_content() -> String {
     r#"
 # Cursor
@@ -95,6 +220,147 @@ cursor = '▇'
 #
 blinking-cursor = false
 
+# Blinking text
+#
+# Text written with the blink attribute (SGR 5) blinks at the cursor
+# blinking interval.
+#
+# • enabled - set to false to draw blinking text as regular text.
+#   Default: true
+#
+# • timeout - seconds after which blinking text stops blinking and stays
+#   visible, 0 keeps it blinking.
+#   Default: 0
+#
+# Example
+#   [blinking-text]
+#   enabled = true
+#   timeout = 10
+
+# Text attributes
+#
+# How bold (SGR 1) and dim (SGR 2) text is colored and drawn.
+#
+# • bold-as-bright - draw bold text using colors 0-7 with their bright
+#   variants 8-15.
+#   Default: false
+#
+# • bold-font - use the bold font for bold text, disable it to show bold
+#   text only through bright colors.
+#   Default: true
+#
+# • dim-multiplier - brightness of dim text with colors that have no dim
+#   variant in the theme, like RGB and 256 palette colors.
+#   Default: 0.66
+#
+# Example
+#   [text-attributes]
+#   bold-as-bright = true
+#   bold-font = false
+#   dim-multiplier = 0.66
+
+# Bell
+#
+# Visual bell flashed over the window when the terminal rings the bell (BEL),
+# it fades out following the animation curve.
+#
+# • animation - Linear, EaseOut, EaseOutSine, EaseOutQuad, EaseOutCubic,
+#   EaseOutQuart, EaseOutQuint, EaseOutExpo or EaseOutCirc.
+#   Default: EaseOutExpo
+#
+# • duration - length of the flash in milliseconds, zero disables it.
+#   Default: 0
+#
+# • color - color of the flash.
+#   Default: '#FFFFFF'
+#
+# Example
+#   [bell]
+#   animation = 'EaseOutExpo'
+#   duration = 100
+#   color = '#FFFFFF'
+
+# Global hotkey
+#
+# System-wide key combination that shows and focuses the Rio windows, or
+# hides them when one of them is focused. It uses the key binding names
+# for the modifiers and X11 key names like "F12", "space" or "grave".
+#
+# Supported on X11, macOS and Windows, Wayland compositors do not allow
+# applications to grab keys for the whole session. The hotkey is grabbed
+# again when the configuration changes.
+#
+# Example
+#   global-hotkey = { key = "F12", with = "control | shift" }
+
+# Profiles
+#
+# Named launch profiles, each one can set the shell, the working
+# directory, extra environment variables, the theme and the tab title.
+# Start Rio with a profile using `rio --profile work`, or open a tab with
+# it from a key binding with the `CreateTabWithProfile(work)` action. The
+# theme colors the whole window launched with `--profile`, and only the
+# tabs opened with the action otherwise. `output-log` logs the output of
+# its tabs, see Output log, and `shell-integration` overrides the option
+# of the same name.
+#
+# Example
+#   [[profiles]]
+#   name = "work"
+#   shell = { program = "/bin/zsh", args = ["--login"] }
+#   working-dir = "/home/me/work"
+#   env-vars = ["AWS_PROFILE=work"]
+#   theme = "lucario"
+#   title = "work"
+#   output-log = true
+
+# Remote profile
+#
+# Profile applied to a window while the program of its current tab is an
+# SSH client (`ssh` or `mosh-client`) or its shell reports a working
+# directory on another host with OSC 7, and removed once it is over. Only
+# the theme and `shell-integration` of the profile are used, a theme with
+# another background tells remote sessions apart. The program is checked
+# when the tab titles are updated, every few seconds, which is not
+# available on Windows.
+#
+# Example
+#   remote-profile = "remote"
+#
+#   [[profiles]]
+#   name = "remote"
+#   theme = "dracula"
+#   shell-integration = false
+
+# Shell integration
+#
+# Actions relying on the prompt marks of the shell integration (OSC 133):
+# ScrollToPreviousPrompt, ScrollToNextPrompt, SelectLastCommandOutput,
+# CopyLastCommandOutput, PageLastCommandOutput and RerunLastCommand. They
+# are ignored when false, profiles can change it. Default is true.
+#
+# Example
+#   shell-integration = true
+
+# Workspace rules
+#
+# Overrides applied to a window focused on a workspace or an output of
+# sway or Hyprland, like a larger font on an external monitor. Rules
+# without `workspace` or `output` match any, every matching rule is
+# applied in order. A window keeps the rules of the workspace it was
+# last focused on. Each rule can set the `theme` and the `font-size`.
+#
+# Requires Rio to be built with the `wayland-ipc` feature.
+#
+# Example
+#   [[workspace-rules]]
+#   output = "HDMI-A-1"
+#   font-size = 20.0
+#
+#   [[workspace-rules]]
+#   workspace = "3"
+#   theme = "lucario"
+
 # Ignore theme selection foreground color
 #
 # Default is false
@@ -106,9 +372,83 @@ blinking-cursor = false
 # Set WGPU rendering performance
 # High: Adapter that has the highest performance. This is often a discrete GPU.
 # Low: Adapter that uses the least possible power. This is often an integrated GPU.
+# Auto: Low while running on battery power and High otherwise, the power
+# source is checked every few seconds and the adapter picked at launch is kept.
+#
+# Low power also presents with Fifo, halves the cursor blinking rate,
+# coalesces output driven redraws into larger batches and leaves out the
+# visual bell fading and the smooth scrolling.
+# It can be overridden at launch with `rio --performance low`.
 #
 performance = "High"
 
+# Color space
+#
+# Srgb: colors of the config and themes are sRGB (default).
+# DisplayP3: colors are read as Display P3, for wide gamut monitors.
+#
+# With DisplayP3 Rio renders into an extended range surface when the
+# GPU offers one (Rgba16Float), so colors out of the sRGB gamut are
+# shown as they are. Other surfaces get the colors mapped into sRGB,
+# keeping their hue instead of clipping them. Screenshots are only
+# supported on sRGB surfaces. Changes require a restart.
+#
+# Example
+#   color-space = "DisplayP3"
+
+# Color filter
+#
+# Accessibility filter applied to the colors of the terminal.
+# None: colors are shown as they are (default).
+# Protanopia, Deuteranopia and Tritanopia: simulate how people lacking
+# red, green or blue cones see the colors, to check a theme.
+# HighContrast: pushes colors away from the middle gray.
+#
+# Filters can also be toggled with the `ToggleColorFilter(name)` action,
+# background images are not filtered.
+#
+# Example
+#   color-filter = "Deuteranopia"
+
+# Reduce motion
+#
+# Auto: follows the reduced motion preference of the system (default).
+# Always: leaves the animations out.
+# Never: keeps the animations whatever the system preference.
+#
+# With reduced motion the cursor does not blink, the visual bell is shown
+# without fading out and scrolling by a page or to a prompt ignores
+# `animation-duration`. The system preference is checked in the background
+# every few seconds and applied to the open windows once it changes:
+# "Reduce motion" on macOS, animation effects on Windows, `enable-animations`
+# on GNOME and the animation speed on KDE.
+#
+# Example
+#   reduce-motion = "Always"
+
+# Render scale
+#
+# Fraction of the window resolution the terminal is drawn at, the frame
+# is upscaled when presented. Values below 1.0 shade fewer pixels, which
+# helps weak GPUs driving large 4K/5K fullscreen windows at the cost of
+# softer text. It goes from 0.25 to 1.0 (default), screenshots are always
+# taken at the full resolution.
+#
+# Example
+#   render-scale = 0.75
+
+# Scale factor
+#
+# Scale factor of the windows, "auto" (default) uses the one reported by
+# the system. On X11 sessions reporting 1.0, `Xft.dpi` from the X
+# resources is used, then the GNOME `scaling-factor` setting, so HiDPI
+# screens without a scale configured for X11 aren't drawn tiny. A number
+# forces the scale factor, the font size and the padding are multiplied
+# by it.
+#
+# Example
+#   scale-factor = 2.0
+
 # Theme
 #
 # It makes Rio look for the specified theme in the themes folder
@@ -146,11 +486,18 @@ performance = "High"
 #     - "Maximized" window is created with maximized
 #     - "Fullscreen" window is created with fullscreen
 #
+# • decorations - define the window decorations
+#     - "Enabled" (default on Linux, BSD and Windows) titlebar and borders
+#     - "Disabled" borderless window, drag the area above the terminal to move it
+#     - "Transparent" (default on MacOS) titlebar drawn over the terminal
+#     - "Buttonless" (MacOS only) transparent titlebar without buttons
+#
 # Example
 #   [window]
 #   width = 600
 #   height = 400
 #   mode = "Windowed"
+#   decorations = "Enabled"
 
 # Background configuration
 #
@@ -232,6 +579,15 @@ performance = "High"
 # "color-automation" - Set a specific color for the tab whenever a specific program is running.
 # "macos-hide-window-buttons" - (MacOS only) Hide window buttons
 #
+# TopTab and BottomTab tab bar:
+# "hide-if-single" - Hide the tab bar while there is a single tab, its space
+#   is kept so the terminal does not resize when a tab is opened.
+# "equal-tab-width" - Split the bar width equally between the tabs.
+# "min-tab-width" - Minimum width of each tab.
+# "title-format" - Tab title template with the {index}, {program}, {title}
+#   and {cwd} variables, {title} falls back to the program name.
+#   Default: "{index}.{title}"
+#
 # Example
 #   [navigation]
 #   mode = "CollapsedTab"
@@ -239,6 +595,10 @@ performance = "High"
 #   use-current-path = false
 #   color-automation = []
 #   macos-hide-window-buttons = false
+#   hide-if-single = false
+#   equal-tab-width = false
+#   min-tab-width = 0
+#   title-format = "{index}.{title}"
 
 # Shell
 #
@@ -326,6 +686,371 @@ performance = "High"
 #     # Bytes[27, 91, 53, 126] is equivalent to "\x1b[5~"
 #     { key = "home", with = "super | shift", bytes = [27, 91, 53, 126] }
 #   ]
+#
+# • leader - key pressed before the bindings with `leader` in `with`, like
+#   the tmux prefix. `timeout` is the time in milliseconds to press the next
+#   key, 1000 by default.
+#
+#   [bindings]
+#   leader = { key = "a", with = "control", timeout = 1000 }
+#   keys = [
+#     { key = "c", with = "leader", action = "CreateTab" },
+#   ]
+#
+# • mouse - bindings of mouse buttons, `clicks` picks a double or triple
+#   click.
+#
+#   [bindings]
+#   mouse = [
+#     { button = "back", action = "ScrollToPreviousPrompt" },
+#     { button = "left", with = "control", clicks = 2, action = "Copy" },
+#   ]
+
+# Selection
+#
+# • word-separators - characters that delimit a word on double click.
+#   Default: ",│`|:\"' ()[]{}<>\t"
+#
+# • smart-shell-tokens - treat shell quoted strings and `--long-options`
+#   as a single word on double click.
+#   Default: false
+#
+# • reveal-concealed - copy text hidden with the conceal attribute (SGR 8),
+#   it is copied as blanks otherwise. Concealed text is never displayed.
+#   Default: false
+#
+# • trim-trailing-whitespace - remove the spaces and tabs ending each copied
+#   line. Block selections keep the spaces aligning their columns.
+#   Default: false
+#
+# • join-wrapped-lines - copy a line the terminal wrapped because it was
+#   longer than the window as one line. Lines are copied as displayed, with
+#   a line break where they wrapped, when disabled.
+#   Default: true
+#
+# • invert - draw selected squares with their foreground and background
+#   swapped instead of the selection-foreground and selection-background
+#   colors.
+#   Default: false
+#
+# Example
+#   [selection]
+#   word-separators = ",│`|:\"' ()[]{}<>\t"
+#   smart-shell-tokens = false
+#   reveal-concealed = false
+#   trim-trailing-whitespace = false
+#   join-wrapped-lines = true
+#   invert = false
+
+# Paste
+#
+# • confirm-unsafe - ask for confirmation before pasting text with
+#   multiple lines or control characters into a shell prompt.
+#   Requires shell integration reporting prompts with OSC 133.
+#   Default: false
+#
+# • trim-trailing-newline - remove trailing newlines from pasted text.
+#   Default: false
+#
+# • convert-crlf - convert CRLF line endings into LF before pasting.
+#   Default: false
+#
+# Example
+#   [paste]
+#   confirm-unsafe = false
+#   trim-trailing-newline = false
+#   convert-crlf = false
+
+# Clipboard history
+#
+# Text copied to the clipboard is kept in memory, the ClipboardHistory
+# action picks an entry to paste.
+#
+# • enabled - disable to keep no copies, for privacy.
+#   Default: true
+#
+# • size - number of entries kept, the oldest is dropped first.
+#   Default: 20
+#
+# Example
+#   [clipboard-history]
+#   enabled = true
+#   size = 20
+
+# Output log
+#
+# Copy what the programs of a tab print to a file, for keeping a record
+# of server sessions. The ToggleOutputLog action starts and stops the
+# log of the current tab, `enabled` starts one for every new tab.
+#
+# • enabled - log every new tab.
+#   Default: false
+#
+# • path - file to write, `%Y`, `%m`, `%d`, `%H`, `%M` and `%S` are
+#   replaced by the date and time the log starts and `~/` by the home
+#   directory, relative paths are inside the `logs` folder of the state
+#   directory. A number is added when the file exists.
+#   Default: "%Y-%m-%d_%H-%M-%S.log"
+#
+# • format - "plain" keeps the text without escape sequences, lines
+#   redrawn after a carriage return keep their last state. "raw" keeps
+#   the bytes as read, colors included, to replay with `cat`.
+#   Default: "plain"
+#
+# Output is written to the file within a second and when the log stops.
+#
+# Example
+#   [output-log]
+#   enabled = false
+#   path = "~/logs/rio/%Y-%m-%d_%H-%M-%S.log"
+#   format = "plain"
+
+# Watch
+#
+# Rules matching terminal output with a regex as it arrives.
+#
+# • regex - regular expression tested against each line.
+#
+# • action - what to do with a match:
+#   "highlight" paints the background of the match with `color`,
+#   "notify" requests the user attention for the window and
+#   "run" spawns `command`. They fire once per line, a line redrawn in
+#   place does not fire them again.
+#   Default: "highlight"
+#
+# • color - background color used by "highlight".
+#
+# • command - program (followed by arguments) used by "run".
+#
+# Example
+#   [watch]
+#   rules = [
+#     { regex = "ERROR", color = '#FF0000' },
+#     { regex = "compilation finished", action = "notify" },
+#   ]
+
+# Screenshot
+#
+# Used by the Screenshot and CopyScreenshot actions, the selection is
+# captured instead of the whole screen when there is one.
+#
+# • include-padding - keep the window padding around the terminal grid.
+#   Default: true
+#
+# • directory - where Screenshot saves PNG files.
+#   Default: the pictures directory, or the home directory
+#
+# Example
+#   [screenshot]
+#   include-padding = true
+#   directory = "~/Pictures"
+
+# Low latency
+#
+# Renders as soon as the shell echoes typed input instead of waiting for the
+# next frame and presents with Mailbox/Immediate when the GPU supports it.
+# It trades power usage and possible tearing for lower input latency.
+#
+# To measure the time from a key press until the frame showing its echo is
+# presented, set `measure-input-latency` under `[developer]`, results are
+# logged at the INFO level. The GPU time of the frame is read from timestamp
+# queries where supported (Vulkan and DX12), the time until the GPU finished
+# the frame is logged otherwise.
+#
+# Example
+#   low-latency = true
+#
+#   [developer]
+#   measure-input-latency = true
+
+# Deferred resize
+#
+# While the window is being resized the previous grid is drawn as it is,
+# letterboxed or clipped, and the grid is only computed again once no resize
+# event arrived for 100ms. It avoids reflowing the scrollback and the shell
+# redrawing its prompt on every resize event. Default is false.
+#
+# Example
+#   deferred-resize = true
+
+# Remote control
+#
+# Lets scripts and editors drive Rio through a unix socket, its path is
+# exported to the shells as `RIO_SOCKET`. Requests are JSON objects written
+# one per line and each one is answered by a line like
+# `{"ok":true,"data":"..."}`. Only the commands listed in `allow` are run,
+# remote control is disabled when the list is empty (default). The list is
+# read when Rio starts and remote control is not available on Windows.
+#
+# • get-text - text of the focused terminal, `"scrollback": true` includes
+#   the scrollback.
+#   {"cmd":"get-text"}
+#
+# • send-text - write text to the focused terminal as if it was typed.
+#   {"cmd":"send-text","text":"ls\n"}
+#
+# • new-window - open a new window.
+#   {"cmd":"new-window"}
+#
+# • set-colors - change the foreground, background or cursor color of the
+#   focused terminal, written as `rgb:rr/gg/bb` or `#rrggbb`.
+#   {"cmd":"set-colors","foreground":"rgb:ff/ff/ff","background":"rgb:00/00/00"}
+#
+# • resize - resize the focused window to a number of columns and lines.
+#   {"cmd":"resize","columns":120,"lines":40}
+#
+# • rename-tab - name a tab of the focused window, the current one unless
+#   `tab` is given. An empty name restores the title set by the program.
+#   {"cmd":"rename-tab","tab":0,"name":"logs"}
+#
+# • clear-screen - move the visible lines of the focused terminal to the
+#   scrollback, like `CSI H CSI 2 J`, and ask the shell to redraw.
+#   {"cmd":"clear-screen"}
+#
+# • clear-scrollback - drop the scrollback of the focused terminal, like
+#   `CSI 3 J`.
+#   {"cmd":"clear-scrollback"}
+#
+# Requests can also be sent with `rio remote '<request>'`.
+#
+# Example
+#   [remote-control]
+#   allow = ["get-text", "send-text"]
+
+# Pager
+#
+# Actions `ViewLastCommandOutput` and `ViewScrollback` show text in a pager
+# drawn over the terminal. It scrolls with the arrows, j/k, PageUp/PageDown,
+# space/b, d/u, g/G and the mouse wheel, `/` searches and n/N move to the
+# next or previous match, q or Escape closes it. Strings, numbers, comments,
+# common keywords and diff lines are colored unless `syntax-highlighting` is
+# disabled. Default is true.
+#
+# Example
+#   [pager]
+#   syntax-highlighting = true
+
+# Quick select
+#
+# Action `QuickSelect` lists the paths, urls, IP addresses, hashes and
+# container ids found in the last 1000 lines of output, the most recent
+# first. Typing filters the list with a fuzzy match, the arrows move the
+# selection and Enter inserts the selected token at the prompt. `patterns`
+# replaces the regexes used to find the tokens.
+#
+# Example
+#   [quick-select]
+#   patterns = ["https?://\\S+", "\\b[0-9a-f]{7,40}\\b", "JIRA-\\d+"]
+
+# Monitor
+#
+# Actions `ToggleMonitorActivity` and `ToggleMonitorSilence` watch the output
+# of the current tab. The activity monitor fires when new output comes after
+# `interval` seconds without any, the silence monitor when nothing was
+# written for `interval` seconds, handy to follow builds and long jobs from
+# another tab. Tabs with a fired monitor are marked in the tab bar until they
+# are selected, `notify` also requests the user attention for the window.
+# Default interval is 10 seconds and notify is false.
+#
+# Example
+#   [monitor]
+#   interval = 30
+#   notify = true
+
+# Scroll
+#
+# Speed of the scrollback with the mouse wheel. `multiplier` is the number of
+# lines scrolled for each line of a wheel, `precision-multiplier` scales the
+# pixel deltas sent by trackpads and high-resolution wheels, where 1.0 moves
+# the text along with the fingers. Fractions of a line are kept until they add
+# up to a full line. `natural-scrolling` reverses the direction reported by
+# the system, for when it can't be changed for Rio alone.
+#
+# The actions moving by a page, half a page or to a prompt slide the text to
+# its new place over `animation-duration` milliseconds, paced by
+# `animation-easing`: "linear", "ease-out" or "ease-in-out".
+#
+# Default multiplier is 3.0, precision-multiplier is 1.0,
+# natural-scrolling is false, animation-duration is 0, moving at once, and
+# animation-easing is "ease-out".
+#
+# Example
+#   [scroll]
+#   multiplier = 5.0
+#   precision-multiplier = 2.0
+#   natural-scrolling = true
+#   animation-duration = 150
+#   animation-easing = "ease-out"
+
+# Alternate scroll
+#
+# Programs on the alternate screen (less, vim, man) have no scrollback, when
+# they don't read the mouse themselves the wheel is sent to them as up and
+# down arrow keys instead. `arrows` is the number of arrow key presses sent
+# for each scrolled line and `disabled-programs` lists foreground programs
+# where the wheel does nothing instead, like menus that would move their
+# selection. Holding Shift also skips the translation, and programs can turn
+# it off with the alternate scroll mode (DECSET 1007).
+# Default arrows is 1 and no program is disabled.
+#
+# Example
+#   [alternate-scroll]
+#   arrows = 3
+#   disabled-programs = ["htop", "mc"]
+
+# BiDi
+#
+# Draws right-to-left text (Hebrew, Arabic) in visual order with the Unicode
+# BiDi algorithm, lines keep their left-to-right layout and only the
+# right-to-left runs inside them are reversed. Selection and the cursor follow
+# the reordered characters. It is disabled by default because some TUIs
+# already reorder text themselves or expect pure left-to-right rendering.
+#
+# Example
+#   bidi = true
+
+# Arabic shaping
+#
+# Arabic letters are drawn with their contextual forms (isolated, initial,
+# medial and final) and lam-alef as a ligature, so Arabic text is legible.
+# The font, or a fallback font, needs the Arabic Presentation Forms-B glyphs.
+# Set it to false to draw the letters unshaped. Default is true.
+#
+# Example
+#   arabic-shaping = false
+
+# Unicode version
+#
+# Width tables used to count the cells taken by characters. By default the
+# latest Unicode version is used, set an older version to match the wcwidth
+# of a remote system, for example `8` for hosts where emoji take a single cell
+# or `10` for hosts without the emoji added in Unicode 11 and later. Versions
+# from 8 to 13 are known, later versions use the latest tables.
+#
+# Example
+#   unicode-version = 9
+
+# Memory
+#
+# Budgets in MiB for the scrollback of all the terminals, the images uploaded
+# to the GPU and the glyph texture caches of all the windows, checked every
+# few seconds while there is output. Over the scrollback budget the oldest
+# lines of the terminals with the largest scrollback are dropped, and a glyph
+# cache that grew past its budget is shrunk once the glyphs on screen fit in a
+# smaller one. Images are only counted, the background image is the only one
+# drawn for now. The `ShowGlyphAtlas` view shows the usage. Zero disables a
+# budget. Default scrollback is 512, images is 256 and glyph-atlas is 64.
+#
+# With `compress-scrollback` the history older than the last 1000 lines is
+# packed into compressed chunks and unpacked when scrolling into it. Search,
+# selection, vi mode, prompt jumps and hints only reach the lines that are not
+# compressed, so it is off by default.
+#
+# Example
+#   [memory]
+#   scrollback = 128
+#   glyph-atlas = 32
+#   compress-scrollback = true
 
 # Log level
 #
@@ -334,5 +1059,28 @@ performance = "High"
 # Example
 #   [developer]
 #   log-level = "OFF"
+
+# Damage flash
+#
+# Tints the cells the terminal marked as changed since the previous frame,
+# fading out in half a second, to check the damage tracking. Lines are
+# tinted from their leftmost to their rightmost changed cell, the whole grid
+# when everything changed. Default is false.
+#
+# Example
+#   [developer]
+#   show-damage = true
+
+# Stats overlay
+#
+# Shows, in the top right corner, the frames drawn in the last second, the
+# time from a key press to the first frame drawn after it, the output parsed
+# per second by the tabs of the window and the frames dropped while the
+# terminal was busy. The ToggleStats action shows and hides it, with
+# `enable-fps-counter` it is shown from the start. Default is false.
+#
+# Example
+#   [developer]
+#   enable-fps-counter = true
 "#.to_string()
 }
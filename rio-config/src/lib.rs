@@ -1,14 +1,27 @@
+pub mod bell;
 pub mod bindings;
 pub mod colors;
 pub mod defaults;
+pub mod merge;
 pub mod navigation;
+pub mod paths;
+pub mod profile;
+pub mod selection;
 pub mod theme;
+pub mod validate;
+pub mod watch;
 pub mod window;
+pub mod workspace;
 
-use crate::bindings::Bindings;
+use crate::bell::Bell;
+use crate::bindings::{Bindings, GlobalHotkey};
 use crate::defaults::*;
 use crate::navigation::Navigation;
+use crate::profile::{Profile, RemoteProfile};
+use crate::selection::Selection;
+use crate::watch::Watch;
 use crate::window::{Background, Window};
+use crate::workspace::WorkspaceRule;
 use colors::Colors;
 use log::warn;
 use serde::{Deserialize, Serialize};
@@ -28,6 +41,8 @@ pub enum Performance {
     #[default]
     High,
     Low,
+    /// Low while running on battery power, High otherwise.
+    Auto,
 }
 
 impl std::fmt::Display for Performance {
@@ -39,6 +54,109 @@ impl std::fmt::Display for Performance {
             Performance::Low => {
                 write!(f, "Low")
             }
+            Performance::Auto => {
+                write!(f, "Auto")
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for Performance {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "high" => Ok(Performance::High),
+            "low" => Ok(Performance::Low),
+            "auto" => Ok(Performance::Auto),
+            _ => Err(format!("unknown performance \"{s}\"")),
+        }
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    /// Wide gamut colors, rendered on extended range surfaces.
+    DisplayP3,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub enum ColorFilter {
+    #[default]
+    None,
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+    HighContrast,
+}
+
+/// Whether animations are left out, `Auto` follows the preference of the
+/// system.
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub enum ReduceMotion {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl std::str::FromStr for ColorFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(ColorFilter::None),
+            "protanopia" => Ok(ColorFilter::Protanopia),
+            "deuteranopia" => Ok(ColorFilter::Deuteranopia),
+            "tritanopia" => Ok(ColorFilter::Tritanopia),
+            "highcontrast" => Ok(ColorFilter::HighContrast),
+            _ => Err(format!("unknown color filter \"{s}\"")),
+        }
+    }
+}
+
+/// Scale factor of the windows, `auto` follows the one reported by the system.
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(try_from = "ScaleFactorValue", into = "ScaleFactorValue")]
+pub enum ScaleFactor {
+    #[default]
+    Auto,
+    Fixed(f32),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ScaleFactorValue {
+    Name(String),
+    Value(f32),
+}
+
+impl TryFrom<ScaleFactorValue> for ScaleFactor {
+    type Error = String;
+
+    fn try_from(value: ScaleFactorValue) -> Result<Self, Self::Error> {
+        match value {
+            ScaleFactorValue::Name(name) if name.eq_ignore_ascii_case("auto") => {
+                Ok(ScaleFactor::Auto)
+            }
+            ScaleFactorValue::Value(value) if value > 0. => Ok(ScaleFactor::Fixed(value)),
+            ScaleFactorValue::Name(name) => {
+                Err(format!("unknown scale factor \"{name}\""))
+            }
+            ScaleFactorValue::Value(value) => {
+                Err(format!("scale factor {value} is not positive"))
+            }
+        }
+    }
+}
+
+impl From<ScaleFactor> for ScaleFactorValue {
+    fn from(scale_factor: ScaleFactor) -> Self {
+        match scale_factor {
+            ScaleFactor::Auto => ScaleFactorValue::Name(String::from("auto")),
+            ScaleFactor::Fixed(value) => ScaleFactorValue::Value(value),
         }
     }
 }
@@ -55,6 +173,11 @@ pub struct Developer {
     pub enable_fps_counter: bool,
     #[serde(default = "default_log_level", rename = "log-level")]
     pub log_level: String,
+    #[serde(default = "bool::default", rename = "measure-input-latency")]
+    pub measure_input_latency: bool,
+    /// Tint the cells damaged since the previous frame.
+    #[serde(default = "bool::default", rename = "show-damage")]
+    pub show_damage: bool,
 }
 
 impl Default for Developer {
@@ -62,6 +185,277 @@ impl Default for Developer {
         Developer {
             log_level: default_log_level(),
             enable_fps_counter: false,
+            measure_input_latency: false,
+            show_damage: false,
+        }
+    }
+}
+
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Paste {
+    #[serde(default = "bool::default", rename = "confirm-unsafe")]
+    pub confirm_unsafe: bool,
+    #[serde(default = "bool::default", rename = "trim-trailing-newline")]
+    pub trim_trailing_newline: bool,
+    #[serde(default = "bool::default", rename = "convert-crlf")]
+    pub convert_crlf: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ClipboardHistory {
+    #[serde(default = "default_clipboard_history_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_clipboard_history_size")]
+    pub size: usize,
+}
+
+impl ClipboardHistory {
+    /// Number of copies to keep, none once disabled.
+    pub fn capacity(&self) -> usize {
+        if self.enabled {
+            self.size
+        } else {
+            0
+        }
+    }
+}
+
+impl Default for ClipboardHistory {
+    fn default() -> ClipboardHistory {
+        ClipboardHistory {
+            enabled: default_clipboard_history_enabled(),
+            size: default_clipboard_history_size(),
+        }
+    }
+}
+
+/// What an output log keeps of the bytes the program writes.
+#[derive(Default, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputLogFormat {
+    /// Text without escape sequences.
+    #[default]
+    Plain,
+    /// Bytes as read, replayable with `cat`.
+    Raw,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct OutputLog {
+    /// Log the output of every new tab.
+    #[serde(default = "bool::default")]
+    pub enabled: bool,
+    /// Path of the log files, `%Y`, `%m`, `%d`, `%H`, `%M` and `%S` are
+    /// replaced by the time the log starts. Relative paths are inside the
+    /// `logs` folder of the state directory.
+    #[serde(default = "Option::default")]
+    pub path: Option<String>,
+    #[serde(default = "OutputLogFormat::default")]
+    pub format: OutputLogFormat,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Pager {
+    #[serde(
+        default = "default_syntax_highlighting",
+        rename = "syntax-highlighting"
+    )]
+    pub syntax_highlighting: bool,
+}
+
+impl Default for Pager {
+    fn default() -> Pager {
+        Pager {
+            syntax_highlighting: default_syntax_highlighting(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct QuickSelect {
+    #[serde(default = "default_quick_select_patterns")]
+    pub patterns: Vec<String>,
+}
+
+impl Default for QuickSelect {
+    fn default() -> QuickSelect {
+        QuickSelect {
+            patterns: default_quick_select_patterns(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Monitor {
+    /// Seconds without output before the activity and silence monitors fire.
+    #[serde(default = "default_monitor_interval")]
+    pub interval: u64,
+    /// Request the user attention when a monitor fires.
+    #[serde(default = "bool::default")]
+    pub notify: bool,
+}
+
+impl Default for Monitor {
+    fn default() -> Monitor {
+        Monitor {
+            interval: default_monitor_interval(),
+            notify: false,
+        }
+    }
+}
+
+/// Budgets in MiB, zero for no limit.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Memory {
+    /// Pack the scrollback far from the viewport into compressed chunks.
+    #[serde(default = "bool::default", rename = "compress-scrollback")]
+    pub compress_scrollback: bool,
+    /// Scrollback of all the terminals, compressed lines included.
+    #[serde(default = "default_memory_scrollback")]
+    pub scrollback: usize,
+    /// Images uploaded to the GPU, like the background image.
+    #[serde(default = "default_memory_images")]
+    pub images: usize,
+    /// Glyph texture caches of all the windows.
+    #[serde(default = "default_memory_glyph_atlas", rename = "glyph-atlas")]
+    pub glyph_atlas: usize,
+}
+
+impl Default for Memory {
+    fn default() -> Memory {
+        Memory {
+            compress_scrollback: false,
+            scrollback: default_memory_scrollback(),
+            images: default_memory_images(),
+            glyph_atlas: default_memory_glyph_atlas(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Scroll {
+    /// Lines scrolled in the history for each line of a mouse wheel.
+    #[serde(default = "default_scroll_multiplier")]
+    pub multiplier: f64,
+    /// Same for the pixel deltas of trackpads and high-resolution wheels.
+    #[serde(
+        default = "default_scroll_precision_multiplier",
+        rename = "precision-multiplier"
+    )]
+    pub precision_multiplier: f64,
+    /// Reverse the scroll direction reported by the system.
+    #[serde(default = "bool::default", rename = "natural-scrolling")]
+    pub natural_scrolling: bool,
+    /// Milliseconds taken to move the viewport by a page or to a prompt,
+    /// zero moves it at once.
+    #[serde(default = "u64::default", rename = "animation-duration")]
+    pub animation_duration: u64,
+    #[serde(default = "Easing::default", rename = "animation-easing")]
+    pub animation_easing: Easing,
+}
+
+/// Pace of an animation over its duration.
+#[derive(Default, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Easing {
+    Linear,
+    /// Fast at first, slowing down at the end.
+    #[default]
+    EaseOut,
+    /// Slow at both ends.
+    EaseInOut,
+}
+
+impl Default for Scroll {
+    fn default() -> Scroll {
+        Scroll {
+            multiplier: default_scroll_multiplier(),
+            precision_multiplier: default_scroll_precision_multiplier(),
+            natural_scrolling: false,
+            animation_duration: 0,
+            animation_easing: Easing::default(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct AlternateScroll {
+    /// Arrow key presses sent for each line scrolled.
+    #[serde(default = "default_alternate_scroll_arrows")]
+    pub arrows: usize,
+    /// Foreground programs where the mouse wheel is never turned into arrows.
+    #[serde(default = "Vec::default", rename = "disabled-programs")]
+    pub disabled_programs: Vec<String>,
+}
+
+impl Default for AlternateScroll {
+    fn default() -> AlternateScroll {
+        AlternateScroll {
+            arrows: default_alternate_scroll_arrows(),
+            disabled_programs: vec![],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Screenshot {
+    #[serde(default = "default_include_padding", rename = "include-padding")]
+    pub include_padding: bool,
+    #[serde(default = "Option::default")]
+    pub directory: Option<String>,
+}
+
+impl Default for Screenshot {
+    fn default() -> Screenshot {
+        Screenshot {
+            include_padding: default_include_padding(),
+            directory: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct RemoteControl {
+    /// Commands accepted over the remote control socket, the socket is not
+    /// created when it is empty.
+    #[serde(default = "Vec::default")]
+    pub allow: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct BlinkingText {
+    #[serde(default = "default_blinking_text_enabled")]
+    pub enabled: bool,
+    /// Seconds after which blinking text stops blinking, zero never stops.
+    #[serde(default = "u64::default")]
+    pub timeout: u64,
+}
+
+impl Default for BlinkingText {
+    fn default() -> BlinkingText {
+        BlinkingText {
+            enabled: default_blinking_text_enabled(),
+            timeout: 0,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TextAttributes {
+    #[serde(default = "bool::default", rename = "bold-as-bright")]
+    pub bold_as_bright: bool,
+    #[serde(default = "default_bold_font", rename = "bold-font")]
+    pub bold_font: bool,
+    #[serde(default = "default_dim_multiplier", rename = "dim-multiplier")]
+    pub dim_multiplier: f32,
+}
+
+impl Default for TextAttributes {
+    fn default() -> TextAttributes {
+        TextAttributes {
+            bold_as_bright: false,
+            bold_font: default_bold_font(),
+            dim_multiplier: default_dim_multiplier(),
         }
     }
 }
@@ -70,6 +464,14 @@ impl Default for Developer {
 pub struct Config {
     #[serde(default = "bool::default", rename = "blinking-cursor")]
     pub blinking_cursor: bool,
+    #[serde(default = "BlinkingText::default", rename = "blinking-text")]
+    pub blinking_text: BlinkingText,
+    #[serde(default = "TextAttributes::default", rename = "text-attributes")]
+    pub text_attributes: TextAttributes,
+    #[serde(default = "Bell::default")]
+    pub bell: Bell,
+    #[serde(default = "Option::default", rename = "global-hotkey")]
+    pub global_hotkey: Option<GlobalHotkey>,
     #[serde(default = "Navigation::default")]
     pub navigation: Navigation,
     #[serde(default = "Window::default")]
@@ -78,10 +480,30 @@ pub struct Config {
     pub background: Background,
     #[serde(default = "Performance::default")]
     pub performance: Performance,
+    #[serde(default = "ColorSpace::default", rename = "color-space")]
+    pub color_space: ColorSpace,
+    #[serde(default = "ColorFilter::default", rename = "color-filter")]
+    pub color_filter: ColorFilter,
+    #[serde(default = "ReduceMotion::default", rename = "reduce-motion")]
+    pub reduce_motion: ReduceMotion,
+    #[serde(default = "default_render_scale", rename = "render-scale")]
+    pub render_scale: f32,
+    #[serde(default = "ScaleFactor::default", rename = "scale-factor")]
+    pub scale_factor: ScaleFactor,
     #[serde(default = "default_shell")]
     pub shell: Shell,
     #[serde(default = "bool::default", rename = "disable-unfocused-render")]
     pub disable_unfocused_render: bool,
+    #[serde(default = "bool::default", rename = "low-latency")]
+    pub low_latency: bool,
+    #[serde(default = "bool::default", rename = "deferred-resize")]
+    pub deferred_resize: bool,
+    #[serde(default = "bool::default")]
+    pub bidi: bool,
+    #[serde(default = "default_arabic_shaping", rename = "arabic-shaping")]
+    pub arabic_shaping: bool,
+    #[serde(default = "Option::default", rename = "unicode-version")]
+    pub unicode_version: Option<u8>,
     #[serde(default = "default_use_fork", rename = "use-fork")]
     pub use_fork: bool,
     #[serde(default = "default_working_dir", rename = "working-dir")]
@@ -116,22 +538,53 @@ pub struct Config {
     pub bindings: bindings::Bindings,
     #[serde(default = "bool::default", rename = "ignore-selection-fg-color")]
     pub ignore_selection_fg_color: bool,
+    #[serde(default = "Selection::default")]
+    pub selection: Selection,
+    #[serde(default = "Paste::default")]
+    pub paste: Paste,
+    #[serde(default = "ClipboardHistory::default", rename = "clipboard-history")]
+    pub clipboard_history: ClipboardHistory,
+    #[serde(default = "OutputLog::default", rename = "output-log")]
+    pub output_log: OutputLog,
+    #[serde(default = "Watch::default")]
+    pub watch: Watch,
+    #[serde(default = "Screenshot::default")]
+    pub screenshot: Screenshot,
+    #[serde(default = "RemoteControl::default", rename = "remote-control")]
+    pub remote_control: RemoteControl,
+    #[serde(default = "Pager::default")]
+    pub pager: Pager,
+    #[serde(default = "QuickSelect::default", rename = "quick-select")]
+    pub quick_select: QuickSelect,
+    #[serde(default = "Monitor::default")]
+    pub monitor: Monitor,
+    #[serde(default = "Scroll::default")]
+    pub scroll: Scroll,
+    #[serde(default = "Memory::default")]
+    pub memory: Memory,
+    #[serde(default = "AlternateScroll::default", rename = "alternate-scroll")]
+    pub alternate_scroll: AlternateScroll,
+    #[serde(default = "Vec::default")]
+    pub profiles: Vec<Profile>,
+    /// Profile selected with `--profile`.
+    #[serde(skip)]
+    pub profile: Option<String>,
+    /// Files included by the config file, watched along with it.
+    #[serde(skip)]
+    pub includes: Vec<std::path::PathBuf>,
+    /// Profile applied to a window while its current tab runs an SSH client.
+    #[serde(default = "Option::default", rename = "remote-profile")]
+    pub remote_profile: Option<String>,
+    /// Actions relying on the marks of the shell integration (OSC 133).
+    #[serde(default = "default_shell_integration", rename = "shell-integration")]
+    pub shell_integration: bool,
+    #[serde(default = "Vec::default", rename = "workspace-rules")]
+    pub workspace_rules: Vec<WorkspaceRule>,
 }
 
-#[cfg(not(target_os = "windows"))]
 #[inline]
 pub fn config_dir_path() -> String {
-    let base_dir_buffer = dirs::home_dir().unwrap();
-    let home = base_dir_buffer.to_str().unwrap_or_default();
-    format!("{home}/.config/rio")
-}
-
-#[cfg(target_os = "windows")]
-#[inline]
-pub fn config_dir_path() -> String {
-    let base_dir_buffer = dirs::home_dir().unwrap();
-    let home = base_dir_buffer.to_str().unwrap_or_default();
-    format!("{home}/AppData/Local/rio")
+    paths::config_dir().to_string_lossy().to_string()
 }
 
 #[inline]
@@ -161,7 +614,11 @@ impl Config {
     fn load_from_path_without_fallback(path: &str) -> Result<Self, String> {
         if std::path::Path::new(path).exists() {
             let content = std::fs::read_to_string(path).unwrap();
-            match toml::from_str::<Config>(&content) {
+            let directory = std::path::Path::new(path)
+                .parent()
+                .and_then(|directory| directory.to_str())
+                .unwrap_or_default();
+            match Config::from_content(&content, directory) {
                 Ok(mut decoded) => {
                     let theme = &decoded.theme;
                     if theme.is_empty() {
@@ -212,7 +669,7 @@ impl Config {
 
                     Ok(decoded)
                 }
-                Err(err_message) => Err(format!("error parsing: {:?}", err_message)),
+                Err(err_message) => Err(format!("error parsing: {err_message}")),
             }
         } else {
             Err(String::from("filepath does not exists"))
@@ -231,6 +688,116 @@ impl Config {
         }
     }
 
+    /// Deserialize a config file, merging its includes and overrides.
+    fn from_content(content: &str, directory: &str) -> Result<Config, String> {
+        // Parsed as it is first, so errors point to their line.
+        let config = toml::from_str::<Config>(content).map_err(|err| err.to_string())?;
+        let mut includes = vec![];
+        let mut config = match merge::resolve(
+            content,
+            std::path::Path::new(directory),
+            &mut includes,
+        )? {
+            Some(value) => value.try_into::<Config>().map_err(|err| err.to_string())?,
+            None => config,
+        };
+        config.includes = includes;
+        Ok(config)
+    }
+
+    /// Find a profile by name, ignoring the case.
+    pub fn find_profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Apply the profile `name` on top of the config.
+    pub fn apply_profile(&mut self, name: &str) -> Result<(), String> {
+        let profile = self
+            .find_profile(name)
+            .cloned()
+            .ok_or_else(|| format!("profile `{name}` not found"))?;
+
+        if let Some(shell) = profile.shell {
+            self.shell = shell;
+            self.use_fork = false;
+        }
+        if profile.working_dir.is_some() {
+            self.working_dir = profile.working_dir;
+        }
+        self.env_vars.extend(profile.env_vars);
+        if let Some(shell_integration) = profile.shell_integration {
+            self.shell_integration = shell_integration;
+        }
+        if let Some(theme) = profile.theme {
+            self.apply_theme(theme)?;
+        }
+
+        self.profile = Some(profile.name);
+        Ok(())
+    }
+
+    /// Theme colors and shell integration of the remote profile, its shell,
+    /// working directory and environment are left out since tabs are not
+    /// spawned for it.
+    pub fn find_remote_profile(&self) -> Result<Option<RemoteProfile>, String> {
+        let Some(name) = &self.remote_profile else {
+            return Ok(None);
+        };
+        let profile = self
+            .find_profile(name)
+            .ok_or_else(|| format!("profile `{name}` not found"))?;
+
+        Ok(Some(RemoteProfile {
+            colors: profile
+                .theme
+                .as_deref()
+                .map(Config::theme_colors)
+                .transpose()?,
+            shell_integration: profile.shell_integration,
+        }))
+    }
+
+    fn apply_theme(&mut self, theme: String) -> Result<(), String> {
+        self.colors = Config::theme_colors(&theme)?;
+        self.adaptive_colors = None;
+        self.theme = theme;
+        Ok(())
+    }
+
+    /// Colors of `theme`, a file of the themes folder.
+    pub fn theme_colors(theme: &str) -> Result<Colors, String> {
+        let path = format!("{}/themes/{theme}.toml", config_dir_path());
+        Ok(Config::load_theme(&path)?.colors)
+    }
+
+    /// Indexes of the workspace rules matching `workspace` on `output`.
+    pub fn workspace_rules_for(&self, workspace: &str, output: &str) -> Vec<usize> {
+        self.workspace_rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.matches(workspace, output))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Apply the workspace rules at `rules` in order, later ones win.
+    pub fn apply_workspace_rules(&mut self, rules: &[usize]) -> Result<(), String> {
+        for index in rules {
+            let Some(rule) = self.workspace_rules.get(*index).cloned() else {
+                continue;
+            };
+            if let Some(font_size) = rule.font_size {
+                self.fonts.size = font_size;
+            }
+            if let Some(theme) = rule.theme {
+                self.apply_theme(theme)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn to_string(&self) -> Result<String, toml::ser::Error> {
         toml::to_string(self)
     }
@@ -240,7 +807,7 @@ impl Config {
         let path = format!("{config_path_str}/config.toml");
         if std::path::Path::new(&path).exists() {
             let content = std::fs::read_to_string(path).unwrap();
-            match toml::from_str::<Config>(&content) {
+            match Config::from_content(&content, &config_path_str) {
                 Ok(mut decoded) => {
                     let theme = &decoded.theme;
                     if theme.is_empty() {
@@ -271,7 +838,7 @@ impl Config {
         let path = format!("{config_path_str}/config.toml");
         if std::path::Path::new(&path).exists() {
             let content = std::fs::read_to_string(path).unwrap();
-            match toml::from_str::<Config>(&content) {
+            match Config::from_content(&content, &config_path_str) {
                 Ok(mut decoded) => {
                     let theme = &decoded.theme;
                     let theme_path = format!("{config_path_str}/themes");
@@ -326,9 +893,7 @@ impl Config {
 
                     Ok(decoded)
                 }
-                Err(err_message) => {
-                    Err(ConfigError::ErrLoadingConfig(err_message.to_string()))
-                }
+                Err(err_message) => Err(ConfigError::ErrLoadingConfig(err_message)),
             }
         } else {
             Err(ConfigError::PathNotFound)
@@ -340,6 +905,10 @@ impl Default for Config {
     fn default() -> Self {
         Config {
             blinking_cursor: false,
+            blinking_text: BlinkingText::default(),
+            text_attributes: TextAttributes::default(),
+            bell: Bell::default(),
+            global_hotkey: None,
             adaptive_theme: None,
             adaptive_colors: None,
             background: Background::default(),
@@ -348,6 +917,11 @@ impl Default for Config {
             cursor: default_cursor(),
             developer: Developer::default(),
             disable_unfocused_render: false,
+            low_latency: false,
+            bidi: false,
+            arabic_shaping: default_arabic_shaping(),
+            unicode_version: None,
+            deferred_resize: false,
             env_vars: default_env_vars(),
             fonts: SugarloafFonts::default(),
             line_height: default_line_height(),
@@ -355,12 +929,36 @@ impl Default for Config {
             option_as_alt: default_option_as_alt(),
             padding_x: default_padding_x(),
             performance: Performance::default(),
+            color_space: ColorSpace::default(),
+            color_filter: ColorFilter::default(),
+            reduce_motion: ReduceMotion::default(),
+            render_scale: default_render_scale(),
+            scale_factor: ScaleFactor::default(),
             shell: default_shell(),
             theme: default_theme(),
             use_fork: default_use_fork(),
             window: Window::default(),
             working_dir: default_working_dir(),
             ignore_selection_fg_color: false,
+            selection: Selection::default(),
+            paste: Paste::default(),
+            clipboard_history: ClipboardHistory::default(),
+            output_log: OutputLog::default(),
+            watch: Watch::default(),
+            screenshot: Screenshot::default(),
+            remote_control: RemoteControl::default(),
+            pager: Pager::default(),
+            quick_select: QuickSelect::default(),
+            monitor: Monitor::default(),
+            scroll: Scroll::default(),
+            memory: Memory::default(),
+            alternate_scroll: AlternateScroll::default(),
+            profiles: Vec::new(),
+            profile: None,
+            includes: vec![],
+            remote_profile: None,
+            shell_integration: default_shell_integration(),
+            workspace_rules: Vec::new(),
         }
     }
 }
@@ -451,6 +1049,10 @@ mod tests {
         assert_eq!(result.fonts, SugarloafFonts::default());
         assert_eq!(result.shell, default_shell());
         assert!(!result.disable_unfocused_render);
+        assert!(!result.low_latency);
+        assert!(!result.bidi);
+        assert!(result.arabic_shaping);
+        assert_eq!(result.unicode_version, None);
         assert_eq!(result.use_fork, default_use_fork());
         assert_eq!(result.line_height, default_line_height());
 
@@ -459,6 +1061,36 @@ mod tests {
         // Developer
         assert_eq!(result.developer, Developer::default());
         assert_eq!(result.bindings, Bindings::default());
+        assert_eq!(result.selection, Selection::default());
+        assert_eq!(result.paste, Paste::default());
+        assert!(result.clipboard_history.enabled);
+        assert_eq!(result.clipboard_history.size, 20);
+        assert_eq!(result.output_log, OutputLog::default());
+        assert_eq!(result.watch, Watch::default());
+        assert_eq!(result.screenshot, Screenshot::default());
+        assert_eq!(result.blinking_text, BlinkingText::default());
+        assert_eq!(result.text_attributes, TextAttributes::default());
+        assert_eq!(result.bell, Bell::default());
+        assert_eq!(result.global_hotkey, None);
+        assert_eq!(result.window.decorations, default_window_decorations());
+        assert!(result.profiles.is_empty());
+        assert!(result.workspace_rules.is_empty());
+        assert_eq!(result.color_space, ColorSpace::Srgb);
+        assert_eq!(result.color_filter, ColorFilter::None);
+        assert_eq!(result.render_scale, 1.0);
+        assert_eq!(result.scale_factor, ScaleFactor::Auto);
+        assert!(!result.deferred_resize);
+        assert!(result.remote_control.allow.is_empty());
+        assert!(result.pager.syntax_highlighting);
+        assert_eq!(result.quick_select.patterns.len(), 4);
+        assert_eq!(result.monitor.interval, 10);
+        assert!(!result.monitor.notify);
+        assert_eq!(result.scroll.multiplier, 3.0);
+        assert_eq!(result.scroll.precision_multiplier, 1.0);
+        assert!(!result.scroll.natural_scrolling);
+        assert_eq!(result.memory, Memory::default());
+        assert_eq!(result.alternate_scroll.arrows, 1);
+        assert!(result.alternate_scroll.disabled_programs.is_empty());
     }
 
     #[test]
@@ -487,6 +1119,21 @@ mod tests {
         assert_eq!(result.colors.cursor, colors::defaults::cursor());
     }
 
+    #[test]
+    fn test_change_config_performance_auto() {
+        let result = create_temporary_config(
+            "change-performance-auto",
+            r#"
+            performance = "Auto"
+        "#,
+        );
+
+        assert_eq!(result.performance, Performance::Auto);
+        assert_eq!("low".parse::<Performance>(), Ok(Performance::Low));
+        assert_eq!("AUTO".parse::<Performance>(), Ok(Performance::Auto));
+        assert!("medium".parse::<Performance>().is_err());
+    }
+
     #[test]
     fn test_change_config_performance() {
         let result = create_temporary_config(
@@ -718,6 +1365,7 @@ mod tests {
         assert_eq!(result.colors.foreground, [0.0, 0.0, 0.0, 1.0]);
         assert_eq!(result.colors.tabs_active, colors::defaults::tabs_active());
         assert_eq!(result.colors.cursor, colors::defaults::cursor());
+        assert_eq!(result.colors.cursor_text, None);
     }
 
     #[test]
@@ -732,6 +1380,7 @@ mod tests {
             selection-foreground = '#222222'
             foreground       = '#F8F8F2'
             cursor           = '#E6DB74'
+            cursor-text      = '#000000'
             black            = '#FFFFFF'
             blue             = '#030303'
             cyan             = '#030303'
@@ -739,6 +1388,7 @@ mod tests {
             magenta          = '#030303'
             red              = '#030303'
             tabs             = '#030303'
+            tabs-foreground  = '#000000'
             white            = '#000000'
             yellow           = '#030303'
             dim-black        = '#030303'
@@ -772,6 +1422,7 @@ mod tests {
         assert_eq!(result.colors.background.0, hex_to_color_arr("#2B3E50"));
         assert_eq!(result.colors.background.1, hex_to_color_wgpu("#2B3E50"));
         assert_eq!(result.colors.cursor, hex_to_color_arr("#E6DB74"));
+        assert_eq!(result.colors.cursor_text, Some([0.0, 0.0, 0.0, 1.0]));
         assert_eq!(result.colors.foreground, hex_to_color_arr("#F8F8F2"));
         assert_eq!(result.colors.tabs_active, hex_to_color_arr("#E6DB74"));
         assert_eq!(result.colors.black, hex_to_color_arr("#FFFFFF"));
@@ -781,6 +1432,8 @@ mod tests {
         assert_eq!(result.colors.magenta, hex_to_color_arr("#030303"));
         assert_eq!(result.colors.red, hex_to_color_arr("#030303"));
         assert_eq!(result.colors.tabs, hex_to_color_arr("#030303"));
+        assert_eq!(result.colors.tabs_foreground, Some([0.0, 0.0, 0.0, 1.0]));
+        assert_eq!(result.colors.tabs_active_foreground, None);
         assert_eq!(result.colors.white, hex_to_color_arr("#000000"));
         assert_eq!(result.colors.yellow, hex_to_color_arr("#030303"));
         assert_eq!(result.colors.dim_black, hex_to_color_arr("#030303"));
@@ -847,6 +1500,23 @@ mod tests {
         assert_eq!(result.shell.args, ["--hello"]);
     }
 
+    #[test]
+    fn test_change_low_latency() {
+        let result = create_temporary_config(
+            "change-low-latency",
+            r#"
+            low-latency = true
+
+            [developer]
+            measure-input-latency = true
+        "#,
+        );
+
+        assert!(result.low_latency);
+        assert!(result.developer.measure_input_latency);
+        assert!(!result.developer.enable_fps_counter);
+    }
+
     #[test]
     fn test_change_developer() {
         let result = create_temporary_config(
@@ -857,6 +1527,7 @@ mod tests {
             [developer]
             enable-fps-counter = true
             log-level = "INFO"
+            show-damage = true
         "#,
         );
 
@@ -864,6 +1535,8 @@ mod tests {
         // Developer
         assert_eq!(result.developer.log_level, String::from("INFO"));
         assert!(result.developer.enable_fps_counter);
+        assert!(result.developer.show_damage);
+        assert!(!result.developer.measure_input_latency);
 
         // Colors
         assert_eq!(result.colors.background, colors::defaults::background());
@@ -871,4 +1544,547 @@ mod tests {
         assert_eq!(result.colors.tabs_active, colors::defaults::tabs_active());
         assert_eq!(result.colors.cursor, colors::defaults::cursor());
     }
+
+    #[test]
+    fn test_change_selection() {
+        let result = create_temporary_config(
+            "change-selection",
+            r#"
+            [selection]
+            word-separators = " ,;"
+            smart-shell-tokens = true
+            reveal-concealed = true
+            trim-trailing-whitespace = true
+            join-wrapped-lines = false
+            invert = true
+        "#,
+        );
+
+        assert_eq!(result.selection.word_separators, String::from(" ,;"));
+        assert!(result.selection.smart_shell_tokens);
+        assert!(result.selection.reveal_concealed);
+        assert!(result.selection.trim_trailing_whitespace);
+        assert!(!result.selection.join_wrapped_lines);
+        assert!(result.selection.invert);
+    }
+
+    #[test]
+    fn test_change_paste() {
+        let result = create_temporary_config(
+            "change-paste",
+            r#"
+            [paste]
+            confirm-unsafe = true
+            convert-crlf = true
+        "#,
+        );
+
+        assert!(result.paste.confirm_unsafe);
+        assert!(!result.paste.trim_trailing_newline);
+        assert!(result.paste.convert_crlf);
+    }
+
+    #[test]
+    fn test_change_clipboard_history() {
+        let result = create_temporary_config(
+            "change-clipboard-history",
+            r#"
+            [clipboard-history]
+            enabled = false
+            size = 5
+        "#,
+        );
+
+        assert!(!result.clipboard_history.enabled);
+        assert_eq!(result.clipboard_history.size, 5);
+        assert_eq!(result.clipboard_history.capacity(), 0);
+    }
+
+    #[test]
+    fn test_change_output_log() {
+        let result = create_temporary_config(
+            "change-output-log",
+            r#"
+            [output-log]
+            enabled = true
+            path = "~/logs/%Y-%m-%d.log"
+            format = "raw"
+        "#,
+        );
+
+        assert!(result.output_log.enabled);
+        assert_eq!(
+            result.output_log.path,
+            Some(String::from("~/logs/%Y-%m-%d.log"))
+        );
+        assert_eq!(result.output_log.format, OutputLogFormat::Raw);
+    }
+
+    #[test]
+    fn test_change_watch() {
+        let result = create_temporary_config(
+            "change-watch",
+            r#"
+            [watch]
+            rules = [
+                { regex = "ERROR", color = '#FF0000' },
+                { regex = "compilation finished", action = "notify" },
+                { regex = "panicked", action = "run", command = "say panic" },
+            ]
+        "#,
+        );
+
+        let rules = &result.watch.rules;
+        assert_eq!(rules.len(), 3);
+        assert_eq!(rules[0].action, watch::WatchAction::Highlight);
+        assert_eq!(rules[0].color, hex_to_color_arr("#FF0000"));
+        assert_eq!(rules[1].action, watch::WatchAction::Notify);
+        assert_eq!(rules[1].color, colors::defaults::dim_yellow());
+        assert_eq!(rules[2].action, watch::WatchAction::Run);
+        assert_eq!(rules[2].command, String::from("say panic"));
+    }
+
+    #[test]
+    fn test_change_screenshot() {
+        let result = create_temporary_config(
+            "change-screenshot",
+            r#"
+            [screenshot]
+            include-padding = false
+            directory = "/tmp/screenshots"
+        "#,
+        );
+
+        assert!(!result.screenshot.include_padding);
+        assert_eq!(
+            result.screenshot.directory,
+            Some(String::from("/tmp/screenshots"))
+        );
+    }
+
+    #[test]
+    fn test_change_blinking_text() {
+        let result = create_temporary_config(
+            "change-blinking-text",
+            r#"
+            [blinking-text]
+            timeout = 10
+        "#,
+        );
+
+        assert!(result.blinking_text.enabled);
+        assert_eq!(result.blinking_text.timeout, 10);
+    }
+
+    #[test]
+    fn test_change_text_attributes() {
+        let result = create_temporary_config(
+            "change-text-attributes",
+            r#"
+            [text-attributes]
+            bold-as-bright = true
+            dim-multiplier = 0.5
+        "#,
+        );
+
+        assert!(result.text_attributes.bold_as_bright);
+        assert!(result.text_attributes.bold_font);
+        assert_eq!(result.text_attributes.dim_multiplier, 0.5);
+    }
+
+    #[test]
+    fn test_change_bell() {
+        let result = create_temporary_config(
+            "change-bell",
+            r#"
+            [bell]
+            animation = 'Linear'
+            duration = 150
+            color = '#FF0000'
+        "#,
+        );
+
+        assert_eq!(result.bell.animation, bell::BellAnimation::Linear);
+        assert_eq!(result.bell.duration, 150);
+        assert_eq!(result.bell.color, [1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_change_window_decorations() {
+        let result = create_temporary_config(
+            "change-window-decorations",
+            r#"
+            [window]
+            decorations = 'Disabled'
+        "#,
+        );
+
+        assert_eq!(result.window.decorations, window::Decorations::Disabled);
+        assert_eq!(result.window.width, default_window_width());
+    }
+
+    #[test]
+    fn test_change_global_hotkey() {
+        let result = create_temporary_config(
+            "change-global-hotkey",
+            r#"
+            global-hotkey = { key = "F12", with = "control | shift" }
+        "#,
+        );
+
+        assert_eq!(
+            result.global_hotkey,
+            Some(GlobalHotkey {
+                key: String::from("F12"),
+                with: String::from("control | shift"),
+            })
+        );
+    }
+
+    #[test]
+    fn test_change_with_override() {
+        let result = create_temporary_config(
+            "change-with-override",
+            &format!(
+                r#"
+            theme = "base"
+            padding-x = 2.0
+
+            [[override]]
+            os = "{}"
+            theme = "override"
+            [override.window]
+            width = 900
+        "#,
+                std::env::consts::OS
+            ),
+        );
+
+        assert_eq!(result.theme, "override");
+        assert_eq!(result.padding_x, 2.0);
+        assert_eq!(result.window.width, 900);
+        assert_eq!(result.window.height, default_window_height());
+    }
+
+    #[test]
+    fn test_apply_profile() {
+        let mut result = create_temporary_config(
+            "apply-profile",
+            r#"
+            env-vars = ["A=1"]
+
+            [[profiles]]
+            name = "Work"
+            shell = { program = "/bin/zsh", args = ["--login"] }
+            env-vars = ["B=2"]
+        "#,
+        );
+
+        assert!(result.apply_profile("missing").is_err());
+        result.apply_profile("work").unwrap();
+        assert_eq!(result.shell.program, "/bin/zsh");
+        assert!(!result.use_fork);
+        assert_eq!(result.working_dir, default_working_dir());
+        assert_eq!(
+            result.env_vars,
+            vec![String::from("A=1"), String::from("B=2")]
+        );
+        assert_eq!(result.profile, Some(String::from("Work")));
+    }
+
+    #[test]
+    fn test_find_remote_profile() {
+        let mut result = create_temporary_config(
+            "find-remote-profile",
+            r#"
+            remote-profile = "remote"
+
+            [[profiles]]
+            name = "remote"
+            shell = { program = "/bin/zsh", args = [] }
+            shell-integration = false
+        "#,
+        );
+
+        // Only the look and the shell integration come from the profile.
+        assert_eq!(
+            result.find_remote_profile().unwrap(),
+            Some(RemoteProfile {
+                colors: None,
+                shell_integration: Some(false),
+            })
+        );
+
+        result.remote_profile = Some(String::from("missing"));
+        assert!(result.find_remote_profile().is_err());
+        result.remote_profile = None;
+        assert_eq!(result.find_remote_profile(), Ok(None));
+    }
+
+    #[test]
+    fn test_apply_workspace_rules() {
+        let mut result = create_temporary_config(
+            "apply-workspace-rules",
+            r#"
+            [[workspace-rules]]
+            output = "HDMI-A-1"
+            font-size = 20.0
+
+            [[workspace-rules]]
+            workspace = "3"
+            font-size = 12.0
+
+            [[workspace-rules]]
+            workspace = "4"
+            theme = "missing"
+        "#,
+        );
+
+        assert!(result.workspace_rules_for("1", "eDP-1").is_empty());
+        assert!(result.apply_workspace_rules(&[3]).is_ok());
+
+        let rules = result.workspace_rules_for("3", "HDMI-A-1");
+        assert_eq!(rules, vec![0, 1]);
+        result.apply_workspace_rules(&rules).unwrap();
+        assert_eq!(result.fonts.size, 12.0);
+
+        let rules = result.workspace_rules_for("4", "eDP-1");
+        assert!(result.apply_workspace_rules(&rules).is_err());
+    }
+
+    #[test]
+    fn test_change_color_space() {
+        let result = create_temporary_config(
+            "change-color-space",
+            r#"
+            color-space = "DisplayP3"
+        "#,
+        );
+
+        assert_eq!(result.color_space, ColorSpace::DisplayP3);
+        assert_eq!(result.performance, Performance::default());
+    }
+
+    #[test]
+    fn test_change_color_filter() {
+        let result = create_temporary_config(
+            "change-color-filter",
+            r#"
+            color-filter = "Deuteranopia"
+        "#,
+        );
+
+        assert_eq!(result.color_filter, ColorFilter::Deuteranopia);
+        assert_eq!(
+            "HighContrast".parse::<ColorFilter>(),
+            Ok(ColorFilter::HighContrast)
+        );
+        assert!("sepia".parse::<ColorFilter>().is_err());
+    }
+
+    #[test]
+    fn test_change_reduce_motion() {
+        let result = create_temporary_config(
+            "change-reduce-motion",
+            r#"
+            reduce-motion = "Always"
+        "#,
+        );
+
+        assert_eq!(result.reduce_motion, ReduceMotion::Always);
+        assert_eq!(Config::default().reduce_motion, ReduceMotion::Auto);
+    }
+
+    #[test]
+    fn test_change_render_scale() {
+        let result = create_temporary_config(
+            "change-render-scale",
+            r#"
+            render-scale = 0.75
+        "#,
+        );
+
+        assert_eq!(result.render_scale, 0.75);
+        assert_eq!(result.color_filter, ColorFilter::None);
+    }
+
+    #[test]
+    fn test_change_scale_factor() {
+        let result = create_temporary_config(
+            "change-scale-factor",
+            r#"
+            scale-factor = 1.5
+        "#,
+        );
+        assert_eq!(result.scale_factor, ScaleFactor::Fixed(1.5));
+
+        let result = create_temporary_config(
+            "change-scale-factor-auto",
+            r#"
+            scale-factor = "Auto"
+        "#,
+        );
+        assert_eq!(result.scale_factor, ScaleFactor::Auto);
+
+        for value in ["0.0", "\"large\""] {
+            let content = format!("scale-factor = {value}");
+            assert!(toml::from_str::<Config>(&content).is_err());
+        }
+    }
+
+    #[test]
+    fn test_change_deferred_resize() {
+        let result = create_temporary_config(
+            "change-deferred-resize",
+            r#"
+            deferred-resize = true
+        "#,
+        );
+
+        assert!(result.deferred_resize);
+        assert!(!result.low_latency);
+    }
+
+    #[test]
+    fn test_change_remote_control() {
+        let result = create_temporary_config(
+            "change-remote-control",
+            r#"
+            [remote-control]
+            allow = ["get-text", "send-text"]
+        "#,
+        );
+
+        assert_eq!(
+            result.remote_control.allow,
+            vec![String::from("get-text"), String::from("send-text")]
+        );
+    }
+
+    #[test]
+    fn test_change_pager() {
+        let result = create_temporary_config(
+            "change-pager",
+            r#"
+            [pager]
+            syntax-highlighting = false
+        "#,
+        );
+
+        assert!(!result.pager.syntax_highlighting);
+    }
+
+    #[test]
+    fn test_change_quick_select() {
+        let result = create_temporary_config(
+            "change-quick-select",
+            r#"
+            [quick-select]
+            patterns = ["JIRA-\\d+"]
+        "#,
+        );
+
+        assert_eq!(
+            result.quick_select.patterns,
+            vec![String::from(r"JIRA-\d+")]
+        );
+    }
+
+    #[test]
+    fn test_change_monitor() {
+        let result = create_temporary_config(
+            "change-monitor",
+            r#"
+            [monitor]
+            interval = 30
+            notify = true
+        "#,
+        );
+
+        assert_eq!(result.monitor.interval, 30);
+        assert!(result.monitor.notify);
+    }
+
+    #[test]
+    fn test_change_scroll() {
+        let result = create_temporary_config(
+            "change-scroll",
+            r#"
+            [scroll]
+            multiplier = 5.0
+            precision-multiplier = 0.5
+            natural-scrolling = true
+            animation-duration = 150
+            animation-easing = "ease-in-out"
+        "#,
+        );
+
+        assert_eq!(result.scroll.multiplier, 5.0);
+        assert_eq!(result.scroll.precision_multiplier, 0.5);
+        assert!(result.scroll.natural_scrolling);
+        assert_eq!(result.scroll.animation_duration, 150);
+        assert_eq!(result.scroll.animation_easing, Easing::EaseInOut);
+    }
+
+    #[test]
+    fn test_change_memory() {
+        let result = create_temporary_config(
+            "change-memory",
+            r#"
+            [memory]
+            scrollback = 128
+            glyph-atlas = 0
+            compress-scrollback = true
+        "#,
+        );
+
+        assert!(result.memory.compress_scrollback);
+        assert_eq!(result.memory.scrollback, 128);
+        assert_eq!(result.memory.images, default_memory_images());
+        assert_eq!(result.memory.glyph_atlas, 0);
+    }
+
+    #[test]
+    fn test_change_alternate_scroll() {
+        let result = create_temporary_config(
+            "change-alternate-scroll",
+            r#"
+            [alternate-scroll]
+            arrows = 3
+            disabled-programs = ["htop", "mc"]
+        "#,
+        );
+
+        assert_eq!(result.alternate_scroll.arrows, 3);
+        assert_eq!(
+            result.alternate_scroll.disabled_programs,
+            vec![String::from("htop"), String::from("mc")]
+        );
+    }
+
+    #[test]
+    fn test_change_bidi() {
+        let result = create_temporary_config(
+            "change-bidi",
+            r#"
+            bidi = true
+            arabic-shaping = false
+        "#,
+        );
+
+        assert!(result.bidi);
+        assert!(!result.arabic_shaping);
+    }
+
+    #[test]
+    fn test_change_unicode_version() {
+        let result = create_temporary_config(
+            "change-unicode-version",
+            r#"
+            unicode-version = 8
+        "#,
+        );
+
+        assert_eq!(result.unicode_version, Some(8));
+    }
 }
@@ -0,0 +1,283 @@
+// Includes and conditional overrides of the config file.
+//
+// `include` lists files merged before the file itself, relative paths are
+// resolved from the directory of the including file. `[[override]]` sections
+// are merged after it, in order, when all their conditions match:
+//
+//     include = ["colors.toml", "~/dotfiles/rio/shared.toml"]
+//
+//     [[override]]
+//     os = "macos"
+//     hostname = "work-laptop"
+//     env = "SSH_CONNECTION"
+//     [override.window]
+//     width = 800
+//
+// `os` is compared with `std::env::consts::OS`, `env` is either a variable
+// name that has to be set or `NAME=value`. Tables are merged key by key,
+// everything else is replaced by the last value.
+
+use log::warn;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use toml::{Table, Value};
+
+const INCLUDE: &str = "include";
+const OVERRIDE: &str = "override";
+const MAX_DEPTH: usize = 8;
+
+/// Resolve the includes and overrides of `content`, `None` when it has none
+/// and can be deserialized as it is. The paths of the included files, found
+/// or not, are pushed to `includes`.
+pub fn resolve(
+    content: &str,
+    directory: &Path,
+    includes: &mut Vec<PathBuf>,
+) -> Result<Option<Value>, String> {
+    let table: Table = toml::from_str(content).map_err(|err| err.to_string())?;
+    if !table.contains_key(INCLUDE) && !table.contains_key(OVERRIDE) {
+        return Ok(None);
+    }
+
+    resolve_table(table, directory, 0, includes).map(|table| Some(Value::Table(table)))
+}
+
+fn resolve_table(
+    mut table: Table,
+    directory: &Path,
+    depth: usize,
+    paths: &mut Vec<PathBuf>,
+) -> Result<Table, String> {
+    if depth > MAX_DEPTH {
+        return Err(String::from("includes are nested too deeply"));
+    }
+
+    let includes = match table.remove(INCLUDE) {
+        None => Vec::new(),
+        Some(Value::String(path)) => vec![path],
+        Some(Value::Array(paths)) => paths
+            .into_iter()
+            .map(|path| match path {
+                Value::String(path) => Ok(path),
+                _ => Err(String::from("`include` only accepts paths")),
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(String::from("`include` only accepts paths")),
+    };
+    let overrides = match table.remove(OVERRIDE) {
+        None => Vec::new(),
+        Some(Value::Array(overrides)) => overrides,
+        Some(_) => return Err(String::from("`override` is declared with [[override]]")),
+    };
+
+    let mut merged = Table::new();
+    for include in includes {
+        let path = expand(&include, directory);
+        paths.push(path.clone());
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            // Files that only exist on some machines are skipped.
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                warn!("included config {} not found", path.display());
+                continue;
+            }
+            Err(err) => return Err(format!("{}: {err}", path.display())),
+        };
+        let included: Table = toml::from_str(&content)
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+        let parent = path.parent().unwrap_or(directory);
+        merge(
+            &mut merged,
+            resolve_table(included, parent, depth + 1, paths)?,
+        );
+    }
+
+    merge(&mut merged, table);
+
+    for section in overrides {
+        let Value::Table(mut section) = section else {
+            return Err(String::from("`override` is declared with [[override]]"));
+        };
+        if matches(&mut section)? {
+            merge(&mut merged, section);
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Merge `other` into `table`, nested tables are merged and any other value
+/// is replaced.
+fn merge(table: &mut Table, other: Table) {
+    for (key, value) in other {
+        match (table.get_mut(&key), value) {
+            (Some(Value::Table(existing)), Value::Table(value)) => merge(existing, value),
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Check and remove the conditions of an override section.
+fn matches(section: &mut Table) -> Result<bool, String> {
+    let mut condition = |key: &str| match section.remove(key) {
+        None => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value)),
+        Some(_) => Err(format!("override condition `{key}` has to be a string")),
+    };
+    let os = condition("os")?;
+    let hostname = condition("hostname")?;
+    let env = condition("env")?;
+
+    let os = os.map_or(true, |os| os.eq_ignore_ascii_case(std::env::consts::OS));
+    let hostname = hostname.map_or(true, |name| is_current_host(&name));
+    let env = env.map_or(true, |env| match env.split_once('=') {
+        Some((name, value)) => std::env::var(name).map_or(false, |var| var == value),
+        None => std::env::var_os(env).map_or(false, |var| !var.is_empty()),
+    });
+
+    Ok(os && hostname && env)
+}
+
+/// Whether `name` is the name of this machine, with or without its domain.
+pub fn is_current_host(name: &str) -> bool {
+    current_hostname().map_or(false, |current| {
+        let short = current.split('.').next().unwrap_or_default();
+        name.eq_ignore_ascii_case(current) || name.eq_ignore_ascii_case(short)
+    })
+}
+
+fn current_hostname() -> Option<&'static str> {
+    static HOSTNAME: OnceLock<Option<String>> = OnceLock::new();
+    HOSTNAME
+        .get_or_init(|| {
+            let hostname = system_hostname()?;
+            Some(hostname.trim().to_string()).filter(|hostname| !hostname.is_empty())
+        })
+        .as_deref()
+}
+
+#[cfg(unix)]
+fn system_hostname() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    let result = unsafe {
+        libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len())
+    };
+    if result != 0 {
+        return None;
+    }
+    let length = buffer.iter().position(|byte| *byte == 0)?;
+    String::from_utf8(buffer[..length].to_vec()).ok()
+}
+
+#[cfg(not(unix))]
+fn system_hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
+
+fn expand(path: &str, directory: &Path) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => directory.join(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let mut table: Table =
+            toml::from_str("theme = \"a\"\n[window]\nwidth = 1\nheight = 2\n").unwrap();
+        let other: Table =
+            toml::from_str("env-vars = []\n[window]\nwidth = 3\n").unwrap();
+        merge(&mut table, other);
+
+        let expected: Table = toml::from_str(
+            "theme = \"a\"\nenv-vars = []\n[window]\nwidth = 3\nheight = 2\n",
+        )
+        .unwrap();
+        assert_eq!(table, expected);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_current_host() {
+        let hostname = current_hostname().unwrap();
+        assert!(is_current_host(hostname));
+        assert!(is_current_host(&hostname.to_uppercase()));
+        assert!(!is_current_host("rio-merge-test-another-host"));
+    }
+
+    #[test]
+    fn test_resolve_without_includes() {
+        let directory = std::env::temp_dir();
+        assert_eq!(
+            resolve("theme = \"a\"", &directory, &mut vec![]).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolve_overrides() {
+        let content = format!(
+            "theme = \"base\"\n\
+             padding-x = 1.0\n\
+             \n\
+             [[override]]\n\
+             os = \"{}\"\n\
+             theme = \"os\"\n\
+             \n\
+             [[override]]\n\
+             os = \"another-os\"\n\
+             padding-x = 2.0\n\
+             \n\
+             [[override]]\n\
+             env = \"RIO_MERGE_TEST_UNSET_VARIABLE\"\n\
+             padding-x = 3.0\n",
+            std::env::consts::OS
+        );
+        let value = resolve(&content, &std::env::temp_dir(), &mut vec![])
+            .unwrap()
+            .unwrap();
+        let expected: Value =
+            toml::from_str("theme = \"os\"\npadding-x = 1.0\n").unwrap();
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn test_resolve_includes() {
+        let directory =
+            std::env::temp_dir().join(format!("rio-test-include-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(
+            directory.join("shared.toml"),
+            "include = \"nested.toml\"\ntheme = \"shared\"\n[window]\nwidth = 100\n",
+        )
+        .unwrap();
+        std::fs::write(directory.join("nested.toml"), "padding-x = 4.0\n").unwrap();
+
+        let content = "include = [\"shared.toml\", \"missing.toml\"]\n\
+                       theme = \"local\"\n";
+        let mut includes = vec![];
+        let value = resolve(content, &directory, &mut includes)
+            .unwrap()
+            .unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(
+            includes,
+            vec![
+                directory.join("shared.toml"),
+                directory.join("nested.toml"),
+                directory.join("missing.toml"),
+            ]
+        );
+
+        let expected: Value =
+            toml::from_str("padding-x = 4.0\ntheme = \"local\"\n[window]\nwidth = 100\n")
+                .unwrap();
+        assert_eq!(value, expected);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::colors::{deserialize_to_arr, ColorArray};
+use crate::defaults::default_tab_title_format;
 use serde::{Deserialize, Serialize};
 
 #[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
@@ -92,7 +93,7 @@ pub struct ColorAutomation {
     pub color: ColorArray,
 }
 
-#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Navigation {
     #[serde(default = "NavigationMode::default")]
     pub mode: NavigationMode,
@@ -118,6 +119,51 @@ pub struct Navigation {
         skip_serializing
     )]
     pub macos_hide_window_buttons: bool,
+    /// Hide the tab bar while the window has a single tab.
+    #[serde(default = "bool::default", rename = "hide-if-single")]
+    pub hide_if_single: bool,
+    /// Split the tab bar width equally between the tabs.
+    #[serde(default = "bool::default", rename = "equal-tab-width")]
+    pub equal_tab_width: bool,
+    #[serde(default = "f32::default", rename = "min-tab-width")]
+    pub min_tab_width: f32,
+    /// Template of the tab titles, see `format_tab_title`.
+    #[serde(default = "default_tab_title_format", rename = "title-format")]
+    pub title_format: String,
+}
+
+impl Default for Navigation {
+    fn default() -> Navigation {
+        Navigation {
+            mode: NavigationMode::default(),
+            color_automation: Vec::default(),
+            clickable: false,
+            use_current_path: false,
+            use_terminal_title: false,
+            macos_hide_window_buttons: false,
+            hide_if_single: false,
+            equal_tab_width: false,
+            min_tab_width: 0.0,
+            title_format: default_tab_title_format(),
+        }
+    }
+}
+
+/// Expand the `{index}`, `{program}`, `{title}` and `{cwd}` variables of a
+/// tab title template, `{title}` falls back to the program name.
+pub fn format_tab_title(
+    format: &str,
+    index: usize,
+    program: &str,
+    title: &str,
+    cwd: &str,
+) -> String {
+    let title = if title.is_empty() { program } else { title };
+    format
+        .replace("{index}", &index.to_string())
+        .replace("{program}", program)
+        .replace("{title}", title)
+        .replace("{cwd}", cwd)
 }
 
 impl Navigation {
@@ -163,7 +209,7 @@ impl Navigation {
 #[cfg(test)]
 mod tests {
     use crate::colors::hex_to_color_arr;
-    use crate::navigation::{Navigation, NavigationMode};
+    use crate::navigation::{format_tab_title, Navigation, NavigationMode};
     use serde::Deserialize;
 
     #[derive(Debug, Clone, Deserialize, PartialEq)]
@@ -287,4 +333,43 @@ mod tests {
             hex_to_color_arr("#333333")
         );
     }
+
+    #[test]
+    fn test_tab_bar() {
+        let content = r#"
+            [navigation]
+            mode = 'BottomTab'
+            hide-if-single = true
+            equal-tab-width = true
+            min-tab-width = 80
+            title-format = '{index}: {program} {cwd}'
+        "#;
+
+        let decoded = toml::from_str::<Root>(content).unwrap();
+        assert_eq!(decoded.navigation.mode, NavigationMode::BottomTab);
+        assert!(decoded.navigation.hide_if_single);
+        assert!(decoded.navigation.equal_tab_width);
+        assert_eq!(decoded.navigation.min_tab_width, 80.0);
+        assert_eq!(decoded.navigation.title_format, "{index}: {program} {cwd}");
+
+        let defaults = Navigation::default();
+        assert!(!defaults.hide_if_single);
+        assert_eq!(defaults.title_format, "{index}.{title}");
+    }
+
+    #[test]
+    fn test_format_tab_title() {
+        assert_eq!(
+            format_tab_title("{index}.{title}", 2, "zsh", "", "~"),
+            "2.zsh"
+        );
+        assert_eq!(
+            format_tab_title("{index}.{title}", 2, "zsh", "vim main.rs", "~"),
+            "2.vim main.rs"
+        );
+        assert_eq!(
+            format_tab_title("{program} in {cwd}", 1, "cargo", "", "~/rio"),
+            "cargo in ~/rio"
+        );
+    }
 }
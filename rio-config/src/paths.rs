@@ -0,0 +1,210 @@
+// Directories of the files Rio reads and writes. On Linux and BSD they follow
+// the XDG base directory specification, so they move with `XDG_CONFIG_HOME`,
+// `XDG_CACHE_HOME`, `XDG_STATE_HOME` and `XDG_RUNTIME_DIR`. Each one can also
+// be moved on its own, on every platform, by a `RIO_*` variable that takes
+// precedence:
+//
+// • RIO_CONFIG_HOME - configuration, themes and included files.
+// • RIO_CACHE_HOME - files that can be created again when removed.
+// • RIO_STATE_HOME - crash reports and what is kept across restarts.
+// • RIO_RUNTIME_DIR - sockets, gone once the user logs out.
+//
+// Directories are not created here, the code writing into one does it. The
+// runtime directory holds sockets and files other users must not open, it is
+// created with `create_private_dir`.
+
+use std::path::{Path, PathBuf};
+
+/// Variable pointing to `directory` when it is an absolute path, relative
+/// paths are invalid for the specification and ignored.
+fn env_dir(variable: &str) -> Option<PathBuf> {
+    std::env::var_os(variable)
+        .map(PathBuf::from)
+        .filter(|directory| directory.is_absolute())
+}
+
+fn home_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_default()
+}
+
+/// Configuration directory: `~/.config/rio` on Linux, BSD and MacOS and
+/// `AppData\Local\rio` on Windows.
+pub fn config_dir() -> PathBuf {
+    env_dir("RIO_CONFIG_HOME").unwrap_or_else(platform_config_dir)
+}
+
+#[cfg(not(windows))]
+fn platform_config_dir() -> PathBuf {
+    env_dir("XDG_CONFIG_HOME")
+        .unwrap_or_else(|| home_dir().join(".config"))
+        .join("rio")
+}
+
+#[cfg(windows)]
+fn platform_config_dir() -> PathBuf {
+    home_dir().join("AppData").join("Local").join("rio")
+}
+
+/// Cache directory: `~/.cache/rio`, `~/Library/Caches/rio` on MacOS and
+/// `AppData\Local\rio\cache` on Windows.
+pub fn cache_dir() -> PathBuf {
+    env_dir("RIO_CACHE_HOME").unwrap_or_else(platform_cache_dir)
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn platform_cache_dir() -> PathBuf {
+    env_dir("XDG_CACHE_HOME")
+        .unwrap_or_else(|| home_dir().join(".cache"))
+        .join("rio")
+}
+
+#[cfg(target_os = "macos")]
+fn platform_cache_dir() -> PathBuf {
+    home_dir().join("Library").join("Caches").join("rio")
+}
+
+#[cfg(windows)]
+fn platform_cache_dir() -> PathBuf {
+    platform_config_dir().join("cache")
+}
+
+/// State directory: `~/.local/state/rio`, `~/Library/Application
+/// Support/rio` on MacOS and `AppData\Local\rio\state` on Windows.
+pub fn state_dir() -> PathBuf {
+    env_dir("RIO_STATE_HOME").unwrap_or_else(platform_state_dir)
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn platform_state_dir() -> PathBuf {
+    env_dir("XDG_STATE_HOME")
+        .unwrap_or_else(|| home_dir().join(".local").join("state"))
+        .join("rio")
+}
+
+#[cfg(target_os = "macos")]
+fn platform_state_dir() -> PathBuf {
+    home_dir()
+        .join("Library")
+        .join("Application Support")
+        .join("rio")
+}
+
+#[cfg(windows)]
+fn platform_state_dir() -> PathBuf {
+    platform_config_dir().join("state")
+}
+
+/// Runtime directory: `$XDG_RUNTIME_DIR/rio`, otherwise a directory of the
+/// user in the temporary one.
+pub fn runtime_dir() -> PathBuf {
+    env_dir("RIO_RUNTIME_DIR")
+        .or_else(|| env_dir("XDG_RUNTIME_DIR").map(|runtime| runtime.join("rio")))
+        .unwrap_or_else(platform_runtime_dir)
+}
+
+// The temporary directory is shared with the other users.
+#[cfg(unix)]
+fn platform_runtime_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("rio-{}", unsafe { libc::getuid() }))
+}
+
+#[cfg(not(unix))]
+fn platform_runtime_dir() -> PathBuf {
+    std::env::temp_dir().join("rio")
+}
+
+/// Create `directory` only accessible by the user, failing when it already
+/// exists and belongs to someone else or is a link.
+#[cfg(unix)]
+pub fn create_private_dir(directory: &Path) -> std::io::Result<()> {
+    use std::io::{Error, ErrorKind};
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    if let Some(parent) = directory.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    match std::fs::DirBuilder::new().mode(0o700).create(directory) {
+        Err(err) if err.kind() != ErrorKind::AlreadyExists => return Err(err),
+        _ => (),
+    }
+
+    let metadata = std::fs::symlink_metadata(directory)?;
+    if !metadata.is_dir() || metadata.uid() != unsafe { libc::getuid() } {
+        return Err(Error::new(
+            ErrorKind::PermissionDenied,
+            format!("{} is not a directory of the user", directory.display()),
+        ));
+    }
+    // Created by a previous version with the default permissions.
+    if metadata.mode() & 0o077 != 0 {
+        std::fs::set_permissions(directory, std::fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+/// Create `directory`, the temporary directory belongs to the user.
+#[cfg(not(unix))]
+pub fn create_private_dir(directory: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(directory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The variables are shared by the tests running at the same time, they
+    // are only changed here.
+    #[test]
+    fn test_dirs() {
+        std::env::set_var("RIO_CACHE_HOME", "/var/cache/rio-test");
+        assert_eq!(cache_dir(), PathBuf::from("/var/cache/rio-test"));
+        std::env::set_var("RIO_CACHE_HOME", "relative/cache");
+        assert_eq!(cache_dir(), platform_cache_dir());
+        std::env::remove_var("RIO_CACHE_HOME");
+
+        std::env::set_var("RIO_STATE_HOME", "/var/lib/rio-test");
+        assert_eq!(state_dir(), PathBuf::from("/var/lib/rio-test"));
+        std::env::remove_var("RIO_STATE_HOME");
+
+        #[cfg(not(any(target_os = "macos", windows)))]
+        {
+            std::env::set_var("XDG_STATE_HOME", "/home/rio-test/state");
+            assert_eq!(state_dir(), PathBuf::from("/home/rio-test/state/rio"));
+            std::env::set_var("XDG_STATE_HOME", "state");
+            assert_eq!(
+                state_dir(),
+                home_dir().join(".local").join("state").join("rio")
+            );
+            std::env::remove_var("XDG_STATE_HOME");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_private_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let directory = std::env::temp_dir()
+            .join(format!("rio-private-dir-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        let mode = |directory: &Path| {
+            std::fs::metadata(directory).unwrap().permissions().mode() & 0o777
+        };
+
+        create_private_dir(&directory).unwrap();
+        assert_eq!(mode(&directory), 0o700);
+
+        std::fs::set_permissions(&directory, std::fs::Permissions::from_mode(0o755))
+            .unwrap();
+        create_private_dir(&directory).unwrap();
+        assert_eq!(mode(&directory), 0o700);
+
+        let link = directory.with_extension("link");
+        let _ = std::fs::remove_file(&link);
+        std::os::unix::fs::symlink(&directory, &link).unwrap();
+        assert!(create_private_dir(&link).is_err());
+
+        std::fs::remove_file(&link).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,118 @@
+use crate::colors::Colors;
+use crate::Shell;
+use serde::{Deserialize, Serialize};
+
+// Example:
+// [[profiles]]
+// name = "work"
+// shell = { program = "/bin/zsh", args = ["--login"] }
+// working-dir = "/home/me/work"
+// env-vars = ["AWS_PROFILE=work"]
+// theme = "lucario"
+// title = "work"
+
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default = "Option::default")]
+    pub shell: Option<Shell>,
+    #[serde(default = "Option::default", rename = "working-dir")]
+    pub working_dir: Option<String>,
+    #[serde(default = "Vec::default", rename = "env-vars")]
+    pub env_vars: Vec<String>,
+    /// Theme used by windows launched with the profile.
+    #[serde(default = "Option::default")]
+    pub theme: Option<String>,
+    /// Tab title, takes the place of `{title}` in the title format.
+    #[serde(default = "Option::default")]
+    pub title: Option<String>,
+    /// Log the output of the tabs opened with the profile.
+    #[serde(default = "bool::default", rename = "output-log")]
+    pub output_log: bool,
+    /// Keep the actions relying on the shell integration, see
+    /// `shell-integration` of the config.
+    #[serde(default = "Option::default", rename = "shell-integration")]
+    pub shell_integration: Option<bool>,
+}
+
+/// What a window takes from the remote profile while its current tab is
+/// connected to another host.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RemoteProfile {
+    pub colors: Option<Colors>,
+    pub shell_integration: Option<bool>,
+}
+
+impl Profile {
+    /// Environment variables as key and value pairs, entries without `=` are
+    /// ignored.
+    pub fn env(&self) -> Vec<(String, String)> {
+        self.env_vars
+            .iter()
+            .filter_map(|var| var.split_once('='))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::profile::Profile;
+    use crate::Shell;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct Root {
+        #[serde(default = "Vec::default")]
+        profiles: Vec<Profile>,
+    }
+
+    #[test]
+    fn test_profiles() {
+        let content = r#"
+            [[profiles]]
+            name = "work"
+            shell = { program = "/bin/zsh", args = ["--login"] }
+            working-dir = "/tmp"
+            env-vars = ["AWS_PROFILE=work", "INVALID", "URL=a=b"]
+            title = "work"
+            output-log = true
+            shell-integration = false
+
+            [[profiles]]
+            name = "plain"
+        "#;
+
+        let decoded = toml::from_str::<Root>(content).unwrap();
+        assert_eq!(decoded.profiles.len(), 2);
+
+        let work = &decoded.profiles[0];
+        assert_eq!(
+            work.shell,
+            Some(Shell {
+                program: String::from("/bin/zsh"),
+                args: vec![String::from("--login")],
+            })
+        );
+        assert_eq!(work.working_dir, Some(String::from("/tmp")));
+        assert_eq!(work.title, Some(String::from("work")));
+        assert_eq!(work.theme, None);
+        assert!(work.output_log);
+        assert_eq!(work.shell_integration, Some(false));
+        assert_eq!(
+            work.env(),
+            vec![
+                (String::from("AWS_PROFILE"), String::from("work")),
+                (String::from("URL"), String::from("a=b")),
+            ]
+        );
+
+        assert_eq!(
+            decoded.profiles[1],
+            Profile {
+                name: String::from("plain"),
+                ..Profile::default()
+            }
+        );
+    }
+}
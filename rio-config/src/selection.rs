@@ -0,0 +1,31 @@
+use crate::defaults::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(PartialEq, Serialize, Deserialize, Clone, Debug)]
+pub struct Selection {
+    #[serde(default = "default_word_separators", rename = "word-separators")]
+    pub word_separators: String,
+    #[serde(default = "bool::default", rename = "smart-shell-tokens")]
+    pub smart_shell_tokens: bool,
+    #[serde(default = "bool::default", rename = "reveal-concealed")]
+    pub reveal_concealed: bool,
+    #[serde(default = "bool::default", rename = "trim-trailing-whitespace")]
+    pub trim_trailing_whitespace: bool,
+    #[serde(default = "default_join_wrapped_lines", rename = "join-wrapped-lines")]
+    pub join_wrapped_lines: bool,
+    #[serde(default = "bool::default")]
+    pub invert: bool,
+}
+
+impl Default for Selection {
+    fn default() -> Selection {
+        Selection {
+            word_separators: default_word_separators(),
+            smart_shell_tokens: false,
+            reveal_concealed: false,
+            trim_trailing_whitespace: false,
+            join_wrapped_lines: default_join_wrapped_lines(),
+            invert: false,
+        }
+    }
+}
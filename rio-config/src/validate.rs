@@ -0,0 +1,243 @@
+// Checks a configuration file without loading it, unknown keys are reported
+// with the closest known key and type errors with the line they happened in.
+
+use crate::Config;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// One based line of the problem, when it could be located.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Validate the content of a config file, an empty list means it is valid.
+pub fn validate(content: &str) -> Vec<Diagnostic> {
+    let mut ignored: Vec<Vec<String>> = Vec::new();
+    let deserializer = toml::Deserializer::new(content);
+    let result: Result<Config, _> = serde_ignored::deserialize(deserializer, |path| {
+        let path = segments(&path);
+        // Resolved before deserializing, see `merge`.
+        if !matches!(
+            path.first().map(String::as_str),
+            Some("include" | "override")
+        ) {
+            ignored.push(path);
+        }
+    });
+
+    if let Err(err) = result {
+        return vec![Diagnostic {
+            line: err.span().map(|span| line_of_offset(content, span.start)),
+            message: err.message().trim().to_string(),
+        }];
+    }
+
+    let defaults = toml::Value::try_from(Config::default()).ok();
+    let written = toml::from_str::<toml::Value>(content).ok();
+    ignored
+        .iter()
+        .filter_map(|path| {
+            let (key, parent) = path.split_last()?;
+            let mut message = format!("unknown key `{}`", path.join("."));
+
+            // Keys known at the same level, either from the defaults or
+            // written by the user and accepted.
+            let mut candidates = keys_at(defaults.as_ref(), parent);
+            candidates.extend(keys_at(written.as_ref(), parent).into_iter().filter(
+                |candidate| {
+                    let mut full = parent.to_vec();
+                    full.push(candidate.clone());
+                    !ignored.contains(&full)
+                },
+            ));
+            if let Some(suggestion) = suggest(key, &candidates) {
+                message.push_str(&format!(", did you mean `{suggestion}`?"));
+            }
+
+            Some(Diagnostic {
+                line: line_of_key(content, path),
+                message,
+            })
+        })
+        .collect()
+}
+
+fn segments(path: &serde_ignored::Path) -> Vec<String> {
+    use serde_ignored::Path;
+    match path {
+        Path::Root => Vec::new(),
+        Path::Seq { parent, index } => {
+            let mut segments = segments(parent);
+            segments.push(index.to_string());
+            segments
+        }
+        Path::Map { parent, key } => {
+            let mut segments = segments(parent);
+            segments.push(key.clone());
+            segments
+        }
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => segments(parent),
+    }
+}
+
+fn keys_at(value: Option<&toml::Value>, path: &[String]) -> Vec<String> {
+    let mut value = value;
+    for segment in path {
+        value = match value {
+            Some(toml::Value::Table(table)) => table.get(segment),
+            Some(toml::Value::Array(array)) => {
+                segment.parse::<usize>().ok().and_then(|i| array.get(i))
+            }
+            _ => None,
+        };
+    }
+
+    match value {
+        Some(toml::Value::Table(table)) => table.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Closest candidate to `key`, if it is close enough to be a typo.
+fn suggest<'a>(key: &str, candidates: &'a [String]) -> Option<&'a str> {
+    let threshold = (key.chars().count() / 3).max(2);
+    candidates
+        .iter()
+        .map(|candidate| (distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+/// Levenshtein distance between two strings.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn line_of_offset(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+/// Find the line defining `path`, as a `key = ...` line inside its table
+/// or as a `[table]` header.
+fn line_of_key(content: &str, path: &[String]) -> Option<usize> {
+    let split = |keys: &str| -> Vec<String> {
+        keys.split('.')
+            .map(|key| key.trim().trim_matches('"').to_string())
+            .collect()
+    };
+
+    let mut table: Vec<String> = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.trim_start_matches('[');
+            let header = header.split(']').next().unwrap_or_default();
+            table = split(header);
+            if table == path {
+                return Some(number + 1);
+            }
+            continue;
+        }
+
+        if let Some((keys, _)) = line.split_once('=') {
+            let mut full = table.clone();
+            full.extend(split(keys));
+            // Unknown keys nested in an inline table point to its line.
+            if path.starts_with(&full) {
+                return Some(number + 1);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_valid_config() {
+        assert!(validate("").is_empty());
+        assert!(
+            validate("include = \"shared.toml\"\n[[override]]\nos = \"linux\"\n")
+                .is_empty()
+        );
+        assert!(
+            validate("theme = \"dracula\"\n[navigation]\nmode = \"Plain\"\n").is_empty()
+        );
+    }
+
+    #[test]
+    fn test_validate_unknown_keys() {
+        let content = "theme = \"dracula\"\n\
+                       them = \"lucario\"\n\
+                       \n\
+                       [navigation]\n\
+                       # a comment\n\
+                       moed = \"Plain\"\n\
+                       unrelated-key = true\n";
+        let diagnostics = validate(content);
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic {
+                    line: Some(2),
+                    message: String::from("unknown key `them`, did you mean `theme`?"),
+                },
+                Diagnostic {
+                    line: Some(6),
+                    message: String::from(
+                        "unknown key `navigation.moed`, did you mean `mode`?"
+                    ),
+                },
+                Diagnostic {
+                    line: Some(7),
+                    message: String::from("unknown key `navigation.unrelated-key`"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_type_error() {
+        let diagnostics = validate("theme = \"dracula\"\n\n[window]\nwidth = \"wide\"\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, Some(4));
+        assert!(diagnostics[0].message.contains("expected i32"));
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance("theme", "theme"), 0);
+        assert_eq!(distance("them", "theme"), 1);
+        assert_eq!(distance("moed", "mode"), 2);
+        assert_eq!(suggest("blink", &[String::from("bell")]), None);
+    }
+}
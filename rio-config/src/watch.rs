@@ -0,0 +1,34 @@
+use crate::colors::{deserialize_to_arr, ColorArray};
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchAction {
+    /// Paint the background of every match.
+    #[default]
+    Highlight,
+    /// Request the user attention for the window.
+    Notify,
+    /// Run `command` whenever a line matches.
+    Run,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct WatchRule {
+    pub regex: String,
+    #[serde(default = "WatchAction::default")]
+    pub action: WatchAction,
+    #[serde(
+        deserialize_with = "deserialize_to_arr",
+        default = "crate::colors::defaults::dim_yellow"
+    )]
+    pub color: ColorArray,
+    #[serde(default = "String::default")]
+    pub command: String,
+}
+
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Watch {
+    #[serde(default = "Vec::default")]
+    pub rules: Vec<WatchRule>,
+}
@@ -11,6 +11,17 @@ pub enum WindowMode {
     Windowed,
 }
 
+#[derive(Clone, Serialize, Deserialize, Copy, Debug, PartialEq)]
+pub enum Decorations {
+    Enabled,
+    /// Borderless window, moved by dragging the area above the terminal.
+    Disabled,
+    /// Titlebar drawn over the terminal content (macOS only).
+    Transparent,
+    /// Transparent titlebar without the window buttons (macOS only).
+    Buttonless,
+}
+
 #[derive(PartialEq, Serialize, Deserialize, Clone, Debug)]
 pub struct Window {
     #[serde(default = "default_window_width")]
@@ -19,6 +30,8 @@ pub struct Window {
     pub height: i32,
     #[serde(default = "WindowMode::default")]
     pub mode: WindowMode,
+    #[serde(default = "default_window_decorations")]
+    pub decorations: Decorations,
 }
 
 impl Default for Window {
@@ -27,6 +40,7 @@ impl Default for Window {
             width: default_window_width(),
             height: default_window_height(),
             mode: WindowMode::default(),
+            decorations: default_window_decorations(),
         }
     }
 }
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+// Example:
+// [[workspace-rules]]
+// output = "HDMI-A-1"
+// workspace = "3"
+// theme = "lucario"
+// font-size = 20.0
+
+/// Overrides applied to windows on a compositor workspace or output, rules
+/// without `workspace` or `output` match any.
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct WorkspaceRule {
+    #[serde(default = "Option::default")]
+    pub workspace: Option<String>,
+    #[serde(default = "Option::default")]
+    pub output: Option<String>,
+    #[serde(default = "Option::default")]
+    pub theme: Option<String>,
+    #[serde(default = "Option::default", rename = "font-size")]
+    pub font_size: Option<f32>,
+}
+
+impl WorkspaceRule {
+    pub fn matches(&self, workspace: &str, output: &str) -> bool {
+        self.workspace
+            .as_ref()
+            .map_or(true, |name| name == workspace)
+            && self.output.as_ref().map_or(true, |name| name == output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::workspace::WorkspaceRule;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct Root {
+        #[serde(default = "Vec::default", rename = "workspace-rules")]
+        workspace_rules: Vec<WorkspaceRule>,
+    }
+
+    #[test]
+    fn test_workspace_rules() {
+        let content = r#"
+            [[workspace-rules]]
+            output = "HDMI-A-1"
+            font-size = 20.0
+
+            [[workspace-rules]]
+            workspace = "3"
+            output = "eDP-1"
+            theme = "lucario"
+        "#;
+
+        let decoded = toml::from_str::<Root>(content).unwrap();
+        let [monitor, workspace] = &decoded.workspace_rules[..] else {
+            panic!("two rules expected");
+        };
+        assert_eq!(monitor.font_size, Some(20.0));
+        assert_eq!(monitor.theme, None);
+        assert!(monitor.matches("1", "HDMI-A-1"));
+        assert!(monitor.matches("3", "HDMI-A-1"));
+        assert!(!monitor.matches("1", "eDP-1"));
+
+        assert_eq!(workspace.theme, Some(String::from("lucario")));
+        assert!(workspace.matches("3", "eDP-1"));
+        assert!(!workspace.matches("3", "HDMI-A-1"));
+        assert!(!workspace.matches("1", "eDP-1"));
+        assert!(WorkspaceRule::default().matches("any", "output"));
+    }
+}
@@ -0,0 +1,383 @@
+// Accessibility tree published with AccessKit, so screen readers (VoiceOver,
+// NVDA, Orca) can read the terminal.
+//
+// The window is the root, its only child is a read-only terminal node with
+// one inline text box per visible line and the text selection placed at the
+// cursor. The tree is republished whenever the visible text or the cursor
+// changes, adapters only build it while an assistive technology is listening.
+
+use crate::crosswords::grid::row::Row;
+use crate::crosswords::pos::Pos;
+use crate::crosswords::square::{Flags, Square};
+use accesskit::{
+    ActionHandler, ActionRequest, NodeBuilder, NodeClassSet, NodeId, Role, TextPosition,
+    TextSelection, Tree, TreeUpdate,
+};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use winit::window::Window;
+
+#[cfg(target_os = "macos")]
+type Adapter = accesskit_macos::SubclassingAdapter;
+#[cfg(windows)]
+type Adapter = accesskit_windows::SubclassingAdapter;
+#[cfg(not(any(target_os = "macos", windows)))]
+type Adapter = accesskit_unix::Adapter;
+
+const WINDOW_ID: NodeId = NodeId(0);
+const TERMINAL_ID: NodeId = NodeId(1);
+// Lines are numbered from here, in the order they are displayed.
+const FIRST_LINE_ID: u64 = 2;
+
+/// Visible text and cursor, as given to the assistive technologies.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Content {
+    // A character is what the cursor moves over: a wide character and its
+    // spacer are one, combining characters stay with their base.
+    lines: Vec<Vec<String>>,
+    // Line and character index of the cursor.
+    cursor: (usize, usize),
+}
+
+impl Content {
+    fn new(rows: &[Row<Square>], cursor: Pos) -> Content {
+        let cursor_line = usize::try_from(cursor.row.0).ok();
+        let mut cursor_index = 0;
+
+        let lines = rows
+            .iter()
+            .enumerate()
+            .map(|(line, row)| {
+                let has_cursor = cursor_line == Some(line);
+                // Trailing blanks are dropped, unless the cursor is over them.
+                let mut end = row.inner.iter().rposition(|square| square.c != ' ');
+                if has_cursor {
+                    end = end.max(Some(cursor.col.0.min(row.len().saturating_sub(1))));
+                }
+
+                let mut characters = Vec::new();
+                for (column, square) in row.inner.iter().enumerate() {
+                    if end.map_or(true, |end| column > end) {
+                        break;
+                    }
+                    if has_cursor && column == cursor.col.0 {
+                        cursor_index = characters.len();
+                    }
+                    if square.flags.intersects(
+                        Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER,
+                    ) {
+                        continue;
+                    }
+
+                    let mut character = String::from(square.c);
+                    if let Some(zerowidth) = square.zerowidth() {
+                        character.extend(zerowidth);
+                    }
+                    characters.push(character);
+                }
+                characters
+            })
+            .collect();
+
+        Content {
+            lines,
+            cursor: (cursor_line.unwrap_or_default(), cursor_index),
+        }
+    }
+}
+
+/// Word lengths in characters, a word takes the blanks after it and the
+/// blanks at the start of a line are a word of their own.
+fn word_lengths(characters: &[String]) -> Vec<u8> {
+    let mut lengths = Vec::new();
+    let mut length: u8 = 0;
+    let mut previous_is_blank = false;
+    for character in characters {
+        let is_blank = character.chars().all(char::is_whitespace);
+        if (previous_is_blank && !is_blank && length > 0) || length == u8::MAX {
+            lengths.push(length);
+            length = 0;
+        }
+        length += 1;
+        previous_is_blank = is_blank;
+    }
+    if length > 0 {
+        lengths.push(length);
+    }
+    lengths
+}
+
+fn tree(content: &Content) -> TreeUpdate {
+    let mut classes = NodeClassSet::lock_global();
+    let mut nodes = Vec::with_capacity(content.lines.len() + 2);
+    let mut children = Vec::with_capacity(content.lines.len());
+    let last_line = content.lines.len().saturating_sub(1);
+
+    for (index, characters) in content.lines.iter().enumerate() {
+        let mut characters = characters.to_owned();
+        if index != last_line {
+            characters.push(String::from("\n"));
+        }
+
+        let mut line = NodeBuilder::new(Role::InlineTextBox);
+        line.set_character_lengths(
+            characters
+                .iter()
+                .map(|character| character.len().min(u8::MAX as usize) as u8)
+                .collect::<Vec<u8>>(),
+        );
+        line.set_word_lengths(word_lengths(&characters));
+        line.set_value(characters.concat());
+
+        let id = NodeId(FIRST_LINE_ID + index as u64);
+        nodes.push((id, line.build(&mut classes)));
+        children.push(id);
+    }
+
+    let mut terminal = NodeBuilder::new(Role::Terminal);
+    terminal.set_name("Terminal");
+    terminal.set_read_only();
+    if !content.lines.is_empty() {
+        let (line, character_index) = content.cursor;
+        let position = TextPosition {
+            node: NodeId(FIRST_LINE_ID + line.min(last_line) as u64),
+            character_index,
+        };
+        terminal.set_text_selection(TextSelection {
+            anchor: position,
+            focus: position,
+        });
+    }
+    terminal.set_children(children);
+    nodes.push((TERMINAL_ID, terminal.build(&mut classes)));
+
+    let mut window = NodeBuilder::new(Role::Window);
+    window.set_name("Rio");
+    window.set_children(vec![TERMINAL_ID]);
+    nodes.push((WINDOW_ID, window.build(&mut classes)));
+
+    let mut tree = Tree::new(WINDOW_ID);
+    tree.app_name = Some(String::from("Rio"));
+    TreeUpdate {
+        nodes,
+        tree: Some(tree),
+        focus: TERMINAL_ID,
+    }
+}
+
+// The grid is read only, requests from the assistive technologies (focus,
+// moving the selection) are ignored.
+struct IgnoredActions;
+
+impl ActionHandler for IgnoredActions {
+    fn do_action(&mut self, _request: ActionRequest) {}
+}
+
+pub struct Accessibility {
+    adapter: Option<Adapter>,
+    content: Arc<Mutex<Content>>,
+}
+
+impl Accessibility {
+    /// Must be created before the window is shown.
+    pub fn new(window: &Window) -> Accessibility {
+        let content = Arc::new(Mutex::new(Content::default()));
+        let source = {
+            let content = content.clone();
+            move || tree(&content.lock())
+        };
+
+        Accessibility {
+            adapter: create_adapter(window, source),
+            content,
+        }
+    }
+
+    /// Publish the visible rows and the cursor, if they changed.
+    pub fn update(&self, rows: &[Row<Square>], cursor: Pos) {
+        let adapter = match &self.adapter {
+            Some(adapter) => adapter,
+            None => return,
+        };
+
+        let content = Content::new(rows, cursor);
+        {
+            let mut current = self.content.lock();
+            if *current == content {
+                return;
+            }
+            *current = content;
+        }
+
+        let update = || tree(&self.content.lock());
+        #[cfg(any(target_os = "macos", windows))]
+        if let Some(events) = adapter.update_if_active(update) {
+            events.raise();
+        }
+        #[cfg(not(any(target_os = "macos", windows)))]
+        adapter.update(update());
+    }
+
+    pub fn set_focused(&self, #[allow(unused)] focused: bool) {
+        #[cfg(target_os = "macos")]
+        if let Some(events) = self
+            .adapter
+            .as_ref()
+            .and_then(|adapter| adapter.update_view_focus_state(focused))
+        {
+            events.raise();
+        }
+        #[cfg(not(any(target_os = "macos", windows)))]
+        if let Some(adapter) = &self.adapter {
+            adapter.update_window_focus_state(focused);
+        }
+    }
+
+    /// Window bounds for AT-SPI, other platforms read them from the window.
+    pub fn set_bounds(&self, #[allow(unused)] window: &Window) {
+        #[cfg(not(any(target_os = "macos", windows)))]
+        if let Some(adapter) = &self.adapter {
+            use accesskit::Rect;
+
+            let outer_position = window.outer_position().unwrap_or_default();
+            let outer_size = window.outer_size();
+            let inner_position = window.inner_position().unwrap_or_default();
+            let inner_size = window.inner_size();
+            adapter.set_root_window_bounds(
+                Rect::from_origin_size(
+                    (outer_position.x as f64, outer_position.y as f64),
+                    (outer_size.width as f64, outer_size.height as f64),
+                ),
+                Rect::from_origin_size(
+                    (inner_position.x as f64, inner_position.y as f64),
+                    (inner_size.width as f64, inner_size.height as f64),
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn create_adapter(
+    window: &Window,
+    source: impl 'static + FnOnce() -> TreeUpdate,
+) -> Option<Adapter> {
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+    match window.raw_window_handle() {
+        RawWindowHandle::AppKit(handle) => Some(unsafe {
+            Adapter::new(handle.ns_view, source, Box::new(IgnoredActions))
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(windows)]
+fn create_adapter(
+    window: &Window,
+    source: impl 'static + FnOnce() -> TreeUpdate,
+) -> Option<Adapter> {
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+    match window.raw_window_handle() {
+        RawWindowHandle::Win32(handle) => Some(Adapter::new(
+            accesskit_windows::HWND(handle.hwnd as isize),
+            source,
+            Box::new(IgnoredActions),
+        )),
+        _ => None,
+    }
+}
+
+// Only connects when the accessibility bus is enabled.
+#[cfg(not(any(target_os = "macos", windows)))]
+fn create_adapter(
+    window: &Window,
+    source: impl 'static + FnOnce() -> TreeUpdate,
+) -> Option<Adapter> {
+    Adapter::new(source, window.has_focus(), Box::new(IgnoredActions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crosswords::pos::{Column, Line};
+
+    fn row(text: &str, columns: usize) -> Row<Square> {
+        let mut row = Row::<Square>::new(columns);
+        for (column, character) in text.chars().enumerate() {
+            row[Column(column)].c = character;
+        }
+        row
+    }
+
+    #[test]
+    fn test_content() {
+        let mut wide = row("中  b", 6);
+        wide[Column(1)].c = ' ';
+        wide[Column(1)].flags.insert(Flags::WIDE_CHAR_SPACER);
+        let rows = vec![row("ls -la", 10), wide, row("", 10)];
+
+        let content = Content::new(&rows, Pos::new(Line(2), Column(3)));
+        assert_eq!(content.lines[0].concat(), "ls -la");
+        assert_eq!(content.lines[1], vec!["中", " ", "b"]);
+        // Blanks are kept up to the cursor.
+        assert_eq!(content.lines[2].concat(), "    ");
+        assert_eq!(content.cursor, (2, 3));
+
+        // Index of the character, not of the column.
+        let content = Content::new(&rows, Pos::new(Line(1), Column(3)));
+        assert_eq!(content.cursor, (1, 2));
+        assert!(content.lines[2].is_empty());
+    }
+
+    #[test]
+    fn test_word_lengths() {
+        let characters =
+            |text: &str| -> Vec<String> { text.chars().map(String::from).collect() };
+        assert_eq!(word_lengths(&characters("ls -la\n")), vec![3, 4]);
+        assert_eq!(word_lengths(&characters("  cd  ..")), vec![2, 4, 2]);
+        assert!(word_lengths(&[]).is_empty());
+        assert_eq!(word_lengths(&characters(&"=".repeat(300))), vec![255, 45]);
+    }
+
+    #[test]
+    fn test_tree() {
+        let rows = vec![row("$ echo é", 10), row("é", 10)];
+        let update = tree(&Content::new(&rows, Pos::new(Line(1), Column(1))));
+
+        assert_eq!(update.focus, TERMINAL_ID);
+        assert_eq!(update.tree.as_ref().map(|tree| tree.root), Some(WINDOW_ID));
+
+        let node = |id: NodeId| {
+            update
+                .nodes
+                .iter()
+                .find(|(node, _)| *node == id)
+                .map(|(_, node)| node)
+                .unwrap()
+        };
+        let first = node(NodeId(FIRST_LINE_ID));
+        assert_eq!(first.value(), Some("$ echo é\n"));
+        assert_eq!(first.character_lengths(), &[1, 1, 1, 1, 1, 1, 1, 2, 1]);
+        assert_eq!(first.word_lengths(), &[2, 5, 2]);
+
+        let terminal = node(TERMINAL_ID);
+        assert_eq!(terminal.role(), Role::Terminal);
+        assert_eq!(
+            terminal.children(),
+            &[NodeId(FIRST_LINE_ID), NodeId(FIRST_LINE_ID + 1)]
+        );
+        let position = TextPosition {
+            node: NodeId(FIRST_LINE_ID + 1),
+            character_index: 1,
+        };
+        assert_eq!(
+            terminal.text_selection(),
+            Some(&TextSelection {
+                anchor: position,
+                focus: position,
+            })
+        );
+    }
+}
@@ -55,6 +55,44 @@ pub enum LineClearMode {
     All,
 }
 
+/// Semantic prompt marks reported by the shell integration (OSC 133).
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum PromptMark {
+    /// Start of the prompt.
+    PromptStart,
+    /// End of the prompt and start of the command typed by the user.
+    CommandStart,
+    /// Command was executed, start of its output.
+    CommandExecuted,
+    /// Command has finished with an optional exit code.
+    CommandFinished(Option<i32>),
+}
+
+/// State of the progress reported with ConEmu's `OSC 9 ; 4`.
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+pub enum ProgressState {
+    #[default]
+    Hidden,
+    Normal,
+    Error,
+    Indeterminate,
+    Paused,
+}
+
+/// Progress of the program running in a terminal, `value` is a percent.
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+pub struct Progress {
+    pub state: ProgressState,
+    pub value: u8,
+}
+
+impl Progress {
+    #[inline]
+    pub fn is_visible(&self) -> bool {
+        self.state != ProgressState::Hidden
+    }
+}
+
 bitflags! {
     /// A set of [`kitty keyboard protocol'] modes.
     ///
@@ -23,12 +23,30 @@ pub enum Mode {
     /// * `CSI 4 h` change to insert mode
     /// * `CSI 4 l` reset to replacement mode
     Insert = 4,
+    /// ?5
+    ///
+    /// DECSCNM, reverse video swapping the default foreground and background
+    /// of the whole screen.
+    ReverseVideo = 5,
     /// ?6
     Origin = 6,
     /// ?7
     LineWrap = 7,
     /// ?12
     BlinkingCursor = 12,
+    /// ?45
+    ///
+    /// Reverse wraparound, backspace in the first column moves to the end of
+    /// the previous line.
+    ReverseWrap = 45,
+    /// ?66
+    ///
+    /// DECNKM, the numeric keypad sends application sequences like `ESC =`.
+    ApplicationKeypad = 66,
+    /// ?69
+    ///
+    /// DECLRMM, allows DECSLRM (`CSI Pl ; Pr s`) to set left and right margins.
+    LeftRightMargin = 69,
     /// 20
     ///
     /// NB This is actually a private mode. We should consider adding a second
@@ -56,6 +74,11 @@ pub enum Mode {
     SwapScreenAndSetRestoreCursor = 1049,
     /// ?2004
     BracketedPaste = 2004,
+    /// ?2027
+    ///
+    /// Grapheme clustering, emoji sequences joined by ZWJ, flags, skin tones
+    /// and emoji presentation selectors take the cells of a single emoji.
+    GraphemeClustering = 2027,
 }
 
 impl Mode {
@@ -71,10 +94,14 @@ impl Mode {
             Some(match num {
                 1 => Mode::CursorKeys,
                 3 => Mode::Column,
+                5 => Mode::ReverseVideo,
                 6 => Mode::Origin,
                 7 => Mode::LineWrap,
                 12 => Mode::BlinkingCursor,
                 25 => Mode::ShowCursor,
+                45 => Mode::ReverseWrap,
+                66 => Mode::ApplicationKeypad,
+                69 => Mode::LeftRightMargin,
                 1000 => Mode::ReportMouseClicks,
                 1002 => Mode::ReportSquareMouseMotion,
                 1003 => Mode::ReportAllMouseMotion,
@@ -85,6 +112,7 @@ impl Mode {
                 1042 => Mode::UrgencyHints,
                 1049 => Mode::SwapScreenAndSetRestoreCursor,
                 2004 => Mode::BracketedPaste,
+                2027 => Mode::GraphemeClustering,
                 _ => {
                     warn!("[unimplemented] primitive mode: {}", num);
                     return None;
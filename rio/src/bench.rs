@@ -0,0 +1,470 @@
+// `rio bench` feeds terminal output through the parser and the renderer without
+// a window, the way the PTY reader and the redraws would, and reports the
+// parsing throughput and the frame times. Besides the built-in workloads,
+// captures taken with `script` or recorded by the `ToggleRecording` action can
+// be given as arguments to measure a slow program the way it ran.
+
+use crate::ansi::CursorShape;
+use crate::crosswords::{Crosswords, MIN_COLUMNS, MIN_LINES};
+use crate::event::VoidListener;
+use crate::performer::handler::ParserProcessor;
+use crate::performer::recorder::{Cast, CastEvent};
+use crate::screen::constants;
+use crate::screen::state::State;
+use rio_config::Config;
+use std::fmt::Write;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use sugarloaf::font::loader::Database;
+use sugarloaf::layout::SugarloafLayout;
+use sugarloaf::Sugarloaf;
+use winit::window::WindowId;
+
+/// Size of the offscreen frames, in physical pixels.
+const WIDTH: f32 = 1280.;
+const HEIGHT: f32 = 800.;
+
+pub struct Workload {
+    pub name: String,
+    pub cast: Cast,
+}
+
+fn workload(name: &str, frames: Vec<String>) -> Workload {
+    let events = frames
+        .into_iter()
+        .map(|frame| CastEvent {
+            time: 0.0,
+            output: frame.into_bytes(),
+        })
+        .collect();
+    Workload {
+        name: name.to_string(),
+        cast: Cast {
+            width: 0,
+            height: 0,
+            idle_time_limit: None,
+            events,
+        },
+    }
+}
+
+/// Plausible line of source code, changing with `index`.
+fn source_line(index: usize) -> String {
+    match index % 5 {
+        0 => format!("fn handler_{index}(value: usize) -> Option<usize> {{"),
+        1 => format!("    let name = \"item-{index}\";"),
+        2 => format!("    if value > {index} {{ return None; }}"),
+        3 => String::from("    Some(value * 2)"),
+        _ => String::from("}"),
+    }
+}
+
+/// Line of `source_line` cut to `width` and colored the way an editor would.
+fn highlighted_line(index: usize, width: usize) -> String {
+    let line: String = source_line(index).chars().take(width).collect();
+    match line.split_once(' ') {
+        Some((keyword, rest)) if !keyword.is_empty() => {
+            format!("\x1b[35m{keyword}\x1b[0m \x1b[32m{rest}\x1b[0m")
+        }
+        _ => format!("\x1b[37m{line}\x1b[0m"),
+    }
+}
+
+/// `text` cut or padded to `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    format!("{:<width$}", text.chars().take(width).collect::<String>())
+}
+
+/// Compiler output with colored statuses, warnings and a progress bar
+/// redrawn in place, a few crates per frame.
+pub fn cargo_build(crates: usize) -> Workload {
+    let mut frames = vec![];
+    let mut frame = String::new();
+    for index in 0..crates {
+        // Progress bar of the previous line is cleared before printing.
+        let _ = write!(
+            frame,
+            "\x1b[K\x1b[1m\x1b[32m   Compiling\x1b[0m crate-{index} v0.{}.{}\r\n",
+            index % 10,
+            index % 7
+        );
+        if index % 7 == 0 {
+            let _ = write!(
+                frame,
+                "\x1b[1m\x1b[33mwarning\x1b[0m\x1b[1m: unused variable: `value`\x1b[0m\r\n\
+                 \x1b[1m\x1b[34m  -->\x1b[0m src/lib.rs:{index}:9\r\n\
+                 \x1b[1m\x1b[34m   |\x1b[0m\r\n\
+                 \x1b[1m\x1b[34m{index:<3}|\x1b[0m {}\r\n\r\n",
+                source_line(index)
+            );
+        }
+        let done = (index + 1) * 40 / crates;
+        let _ = write!(
+            frame,
+            "\x1b[1m\x1b[36m    Building\x1b[0m [{}>{}] {}/{crates}: crate-{index}\r",
+            "=".repeat(done),
+            " ".repeat(40 - done),
+            index + 1
+        );
+        if index % 4 == 3 {
+            frames.push(std::mem::take(&mut frame));
+        }
+    }
+    frames.push(frame);
+    workload("cargo-build", frames)
+}
+
+/// Editor on the alternate screen scrolling a file one line per frame inside a
+/// scroll region, with the status line and the cursor updated every time.
+pub fn vim_scrolling(columns: usize, lines: usize, steps: usize) -> Workload {
+    let region = lines.saturating_sub(1).max(1);
+    let mut frames = vec![];
+
+    let mut frame = String::from("\x1b[?1049h\x1b[H\x1b[2J");
+    for row in 0..region {
+        let _ = write!(
+            frame,
+            "\x1b[{};1H\x1b[33m{:>4}\x1b[0m {}",
+            row + 1,
+            row + 1,
+            highlighted_line(row, columns.saturating_sub(5))
+        );
+    }
+    frames.push(frame);
+
+    for step in 0..steps {
+        let line = region + step;
+        let mut frame = String::new();
+        let _ = write!(
+            frame,
+            "\x1b[?25l\x1b[1;{region}r\x1b[{region};1H\n\x1b[r\
+             \x1b[{region};1H\x1b[33m{:>4}\x1b[0m {}\x1b[K",
+            line + 1,
+            highlighted_line(line, columns.saturating_sub(5))
+        );
+        let status = format!(
+            "src/main.rs [+]  {}:1  {}%",
+            line + 1,
+            line * 100 / (region + steps)
+        );
+        let _ = write!(
+            frame,
+            "\x1b[{lines};1H\x1b[7m{}\x1b[0m\x1b[{region};6H\x1b[?25h",
+            fit(&status, columns)
+        );
+        frames.push(frame);
+    }
+
+    frames.push(String::from("\x1b[?1049l"));
+    workload("vim-scrolling", frames)
+}
+
+/// Two panes side by side with a status bar. Panes of a vertical split can't
+/// use scroll regions, so the multiplexer redraws every row of the pane that
+/// changed: a log in the left one every frame, a process list in the right one
+/// every few frames.
+pub fn tmux_splits(columns: usize, lines: usize, steps: usize) -> Workload {
+    let rows = lines.saturating_sub(1).max(1);
+    let left = columns.saturating_sub(1) / 2;
+    let right = columns.saturating_sub(left + 1);
+    let mut frames = vec![];
+
+    let mut frame = String::from("\x1b[H\x1b[2J");
+    for row in 1..=rows {
+        let _ = write!(frame, "\x1b[{row};{}H\x1b[32m│\x1b[0m", left + 1);
+    }
+    frames.push(frame);
+
+    for step in 0..steps {
+        let mut frame = String::from("\x1b[?25l");
+        for row in 0..rows {
+            let index = (step + row).saturating_sub(rows);
+            let text = if step + row < rows {
+                String::new()
+            } else {
+                format!("[{index:>5}] GET /api/items/{index} 200 {}ms", index % 90)
+            };
+            let _ = write!(frame, "\x1b[{};1H{}", row + 1, fit(&text, left));
+        }
+
+        if step % 4 == 0 {
+            let _ = write!(
+                frame,
+                "\x1b[1;{}H\x1b[7m{}\x1b[0m",
+                left + 2,
+                fit("  PID USER     %CPU COMMAND", right)
+            );
+            for row in 1..rows {
+                let cpu = (row * 7 + step) % 100;
+                let text = format!("{:>5} user     {cpu:>4} worker-{row}", 1000 + row);
+                let color = if cpu > 80 { 31 } else { 39 };
+                let _ = write!(
+                    frame,
+                    "\x1b[{};{}H\x1b[{color}m{}\x1b[0m",
+                    row + 1,
+                    left + 2,
+                    fit(&text, right)
+                );
+            }
+        }
+
+        let status = format!("[0] 0:shell*  1:logs-  {:02}:{:02}", step / 60, step % 60);
+        let _ = write!(
+            frame,
+            "\x1b[{lines};1H\x1b[30;42m{}\x1b[0m\x1b[{rows};1H\x1b[?25h",
+            fit(&status, columns)
+        );
+        frames.push(frame);
+    }
+    workload("tmux-splits", frames)
+}
+
+/// Built-in workloads sized for a terminal of `columns` by `lines`.
+pub fn builtin(columns: usize, lines: usize) -> Vec<Workload> {
+    vec![
+        cargo_build(2000),
+        vim_scrolling(columns, lines, 600),
+        tmux_splits(columns, lines, 600),
+    ]
+}
+
+/// Capture from a file, asciicast recordings are replayed event by event and
+/// anything else as a raw PTY byte stream.
+fn load(path: &str) -> std::io::Result<Workload> {
+    let cast = if path.ends_with(".cast") {
+        Cast::load(Path::new(path))?
+    } else {
+        Cast::from_raw(&std::fs::read(path)?)
+    };
+    let name = Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+    Ok(Workload { name, cast })
+}
+
+/// Value below which `percent` of the sorted `values` fall, nearest rank.
+fn percentile(sorted: &[Duration], percent: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (percent / 100. * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+pub struct Report {
+    pub name: String,
+    pub bytes: usize,
+    /// Parsing and taking the snapshot drawn, as the PTY reader does.
+    pub parse: Duration,
+    /// Building and drawing each frame, sorted.
+    pub frames: Vec<Duration>,
+}
+
+impl Report {
+    fn row(&self) -> String {
+        let megabytes = self.bytes as f64 / (1024. * 1024.);
+        let render: Duration = self.frames.iter().sum();
+        let milliseconds = |duration: Duration| duration.as_secs_f64() * 1000.;
+        format!(
+            "{:<16} {:>9.2} {:>7} {:>11.2} {:>11.2} {:>8.2} {:>8.2} {:>8.2} {:>8.2}",
+            self.name,
+            megabytes,
+            self.frames.len(),
+            megabytes / self.parse.as_secs_f64().max(f64::EPSILON),
+            megabytes / (self.parse + render).as_secs_f64().max(f64::EPSILON),
+            milliseconds(percentile(&self.frames, 50.)),
+            milliseconds(percentile(&self.frames, 95.)),
+            milliseconds(percentile(&self.frames, 99.)),
+            milliseconds(self.frames.last().copied().unwrap_or_default()),
+        )
+    }
+}
+
+const HEADER: &str =
+    "workload               MiB  frames parse MiB/s total MiB/s   p50 ms   p95 ms   p99 ms   max ms";
+
+/// Feed every event of the workload into a new terminal and draw a frame after
+/// each one.
+fn run_workload(
+    workload: &Workload,
+    sugarloaf: &mut Sugarloaf,
+    state: &mut State,
+) -> Report {
+    let mut terminal = Crosswords::new(
+        sugarloaf.layout.columns,
+        sugarloaf.layout.lines,
+        CursorShape::Block,
+        VoidListener {},
+        WindowId::from(0),
+    );
+    let mut parser = ParserProcessor::new();
+    let mut report = Report {
+        name: workload.name.to_owned(),
+        bytes: 0,
+        parse: Duration::ZERO,
+        frames: Vec::with_capacity(workload.cast.events.len()),
+    };
+
+    for event in &workload.cast.events {
+        let start = Instant::now();
+        for byte in &event.output {
+            parser.advance(&mut terminal, *byte);
+        }
+        let snapshot = terminal.snapshot();
+        report.parse += start.elapsed();
+        report.bytes += event.output.len();
+
+        let start = Instant::now();
+        state.set_term_colors(snapshot.colors);
+        state.prepare_rows(
+            &snapshot.rows,
+            snapshot.cursor,
+            sugarloaf,
+            snapshot.display_offset as i32,
+            snapshot.blinking_cursor,
+        );
+        sugarloaf.render();
+        report.frames.push(start.elapsed());
+    }
+    parser.stop_sync(&mut terminal);
+
+    report.frames.sort_unstable();
+    report
+}
+
+/// Run the built-in workloads, or the captures in `paths`, returning the
+/// process exit code.
+pub async fn run(paths: &[String]) -> i32 {
+    let config = match Config::try_load() {
+        Ok(config) => config,
+        Err(_) => Config::default(),
+    };
+
+    let layout = SugarloafLayout::new(
+        WIDTH,
+        HEIGHT,
+        (config.padding_x, constants::PADDING_Y, 0.),
+        1.,
+        config.fonts.size,
+        config.line_height,
+        (MIN_COLUMNS, MIN_LINES),
+    );
+    let mut font_database = Database::new();
+    font_database.load_system_fonts();
+    let mut sugarloaf = match Sugarloaf::new_headless(
+        wgpu::PowerPreference::HighPerformance,
+        config.fonts.to_owned(),
+        layout,
+        Some(&font_database),
+    )
+    .await
+    {
+        Some(Ok(sugarloaf)) => sugarloaf,
+        Some(Err(with_errors)) => with_errors.instance,
+        None => {
+            eprintln!("no GPU adapter available");
+            return 1;
+        }
+    };
+    let config = Rc::new(config);
+    let mut state = State::new(&config, None);
+    sugarloaf.set_background_color(state.window_background());
+    sugarloaf.calculate_bounds();
+
+    let workloads = if paths.is_empty() {
+        builtin(sugarloaf.layout.columns, sugarloaf.layout.lines)
+    } else {
+        let mut workloads = vec![];
+        for path in paths {
+            match load(path) {
+                Ok(workload) => workloads.push(workload),
+                Err(err) => {
+                    eprintln!("{path}: {err}");
+                    return 1;
+                }
+            }
+        }
+        workloads
+    };
+
+    let info = &sugarloaf.ctx.adapter_info;
+    println!(
+        "{} ({:?}), {}x{} cells\n{HEADER}",
+        info.name, info.backend, sugarloaf.layout.columns, sugarloaf.layout.lines
+    );
+    for workload in &workloads {
+        println!(
+            "{}",
+            run_workload(workload, &mut sugarloaf, &mut state).row()
+        );
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crosswords::pos::{Column, Line};
+    use crate::performer::playback;
+
+    fn screen(workload: &Workload, columns: usize, lines: usize) -> Vec<String> {
+        let mut terminal = Crosswords::new(
+            columns,
+            lines,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        playback::replay(&workload.cast, &mut terminal);
+        (0..lines as i32)
+            .map(|line| {
+                (0..columns)
+                    .map(|column| terminal.grid[Line(line)][Column(column)].c)
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_workloads() {
+        let cargo = cargo_build(10);
+        assert_eq!(cargo.cast.events.len(), 3);
+        let lines = screen(&cargo, 80, 6);
+        assert_eq!(lines[4], "   Compiling crate-9 v0.9.2");
+        assert!(
+            lines[5].starts_with("    Building [")
+                && lines[5].ends_with("10/10: crate-9")
+        );
+
+        let vim = vim_scrolling(40, 5, 10);
+        let lines = screen(&vim, 40, 5);
+        // Back on the primary screen once done.
+        assert!(lines.iter().all(|line| line.is_empty()));
+        let mut scrolled = vim_scrolling(40, 5, 10);
+        scrolled.cast.events.pop();
+        let lines = screen(&scrolled, 40, 5);
+        assert_eq!(lines[0], "  11 fn handler_10(value: usize) -> Opti");
+        assert_eq!(lines[3], "  14     Some(value * 2)");
+        assert!(lines[4].starts_with("src/main.rs [+]  14:1"));
+
+        let tmux = tmux_splits(21, 4, 5);
+        let lines = screen(&tmux, 21, 4);
+        assert_eq!(lines[0], "[    1] GE│  PID USER");
+        assert_eq!(lines[2], "[    3] GE│ 1002 user");
+        assert_eq!(lines[3], "[0] 0:shell*  1:logs-");
+    }
+
+    #[test]
+    fn test_percentile() {
+        let frames: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&frames, 50.), Duration::from_millis(50));
+        assert_eq!(percentile(&frames, 99.), Duration::from_millis(99));
+        assert_eq!(percentile(&frames, 100.), Duration::from_millis(100));
+        assert_eq!(percentile(&frames, 0.), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 50.), Duration::ZERO);
+    }
+}
@@ -1,8 +1,8 @@
 // cli.rs was retired originally from https://github.com/alacritty/alacritty/blob/e35e5ad14fce8456afdd89f2b392b9924bb27471/alacritty/src/cli.rs
 // which is licensed under Apache 2.0 license.
 
-use clap::{Args, Parser, ValueHint};
-use rio_config::Shell;
+use clap::{Args, Parser, Subcommand, ValueHint};
+use rio_config::{ConfigError, Performance, Shell};
 use serde::{Deserialize, Serialize};
 
 #[derive(Parser, Default, Debug)]
@@ -11,6 +11,62 @@ pub struct Options {
     /// Options which can be passed via IPC.
     #[clap(flatten)]
     pub window_options: WindowOptions,
+
+    /// Launch with the shell, directory, environment and theme of a profile.
+    #[clap(long)]
+    pub profile: Option<String>,
+
+    /// Start with the default configuration, ignoring the config file.
+    #[clap(long)]
+    pub safe_mode: bool,
+
+    /// Override the configured rendering performance (high, low or auto).
+    #[clap(long)]
+    pub performance: Option<Performance>,
+
+    /// Log level (off, error, warn, info, debug or trace), overrides the config.
+    #[clap(long)]
+    pub log_level: Option<String>,
+
+    /// Write the logs into a file instead of the standard output.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub log_file: Option<String>,
+
+    /// Print timing milestones from launch until the first frame.
+    #[clap(long)]
+    pub measure_startup: bool,
+
+    /// Save the terminal as a PNG image once the command exits.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub screenshot: Option<String>,
+
+    /// Run without a window or display, requires --screenshot.
+    #[clap(long, requires = "screenshot")]
+    pub headless: bool,
+
+    /// Play an asciicast v2 recording instead of starting a shell.
+    #[clap(long, value_hint = ValueHint::FilePath, conflicts_with = "replay")]
+    pub play: Option<String>,
+
+    /// Feed a raw PTY byte stream into the terminal instead of starting a shell.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub replay: Option<String>,
+
+    /// Print the GPU adapters, surface capabilities, displays and renderer
+    /// settings, then exit.
+    #[clap(long)]
+    pub diagnostics: bool,
+
+    /// Draw colors, attributes and glyphs to check the rendering.
+    #[clap(long, conflicts_with_all = ["play", "replay"])]
+    pub test_pattern: bool,
+
+    /// Speed multiplier for --play and --replay, 0 plays without delays.
+    #[clap(long, default_value_t = 1.0)]
+    pub speed: f64,
+
+    #[clap(subcommand)]
+    pub subcommand: Option<Subcommands>,
 }
 
 impl Options {
@@ -19,6 +75,128 @@ impl Options {
     }
 }
 
+#[derive(Subcommand, Debug)]
+pub enum Subcommands {
+    /// Inspect the configuration file.
+    #[clap(subcommand)]
+    Config(ConfigCommand),
+    /// Send a JSON request to the Rio instance at $RIO_SOCKET and print the
+    /// response.
+    Remote {
+        /// Request like '{"cmd":"get-text"}'.
+        request: String,
+    },
+    /// Replay captured output through the parser and the renderer without a
+    /// window and report the throughput and the frame times.
+    #[clap(hide = true)]
+    Bench {
+        /// Asciicast recordings or raw PTY captures, the built-in workloads
+        /// when empty.
+        #[clap(value_hint = ValueHint::FilePath)]
+        paths: Vec<String>,
+    },
+}
+
+/// Send a remote control request, returning the process exit code.
+#[cfg(unix)]
+pub fn send_remote_request(request: &str) -> i32 {
+    let path = match std::env::var_os(crate::remote::SOCKET_ENV) {
+        Some(path) => std::path::PathBuf::from(path),
+        None => {
+            eprintln!(
+                "{} is not set, enable remote-control in the config",
+                crate::remote::SOCKET_ENV
+            );
+            return 1;
+        }
+    };
+
+    match crate::remote::send(&path, request) {
+        Ok(response) => {
+            println!("{response}");
+            if response.starts_with(r#"{"ok":true"#) {
+                0
+            } else {
+                1
+            }
+        }
+        Err(err) => {
+            eprintln!("{}: {err}", path.display());
+            1
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn send_remote_request(_request: &str) -> i32 {
+    eprintln!("remote control is not supported on this platform");
+    1
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Check the config file for unknown keys and invalid values.
+    Validate {
+        /// File to check instead of the user config file.
+        #[clap(value_hint = ValueHint::FilePath)]
+        path: Option<String>,
+    },
+    /// Print the effective configuration, including the defaults.
+    Show,
+}
+
+impl ConfigCommand {
+    /// Run the command, returning the process exit code.
+    pub fn run(&self) -> i32 {
+        match self {
+            ConfigCommand::Validate { path } => {
+                let path = path.clone().unwrap_or_else(rio_config::config_file_path);
+                let content = match std::fs::read_to_string(&path) {
+                    Ok(content) => content,
+                    Err(err) => {
+                        eprintln!("{path}: {err}");
+                        return 1;
+                    }
+                };
+
+                let diagnostics = rio_config::validate::validate(&content);
+                for diagnostic in &diagnostics {
+                    eprintln!("{path}: {diagnostic}");
+                }
+                if diagnostics.is_empty() {
+                    println!("{path}: ok");
+                    0
+                } else {
+                    1
+                }
+            }
+            ConfigCommand::Show => {
+                let config = match rio_config::Config::try_load() {
+                    Ok(config) => config,
+                    Err(ConfigError::PathNotFound) => rio_config::Config::default(),
+                    Err(
+                        ConfigError::ErrLoadingConfig(message)
+                        | ConfigError::ErrLoadingTheme(message),
+                    ) => {
+                        eprintln!("failed to load the configuration: {message}");
+                        return 1;
+                    }
+                };
+                match config.to_string() {
+                    Ok(content) => {
+                        print!("{content}");
+                        0
+                    }
+                    Err(err) => {
+                        eprintln!("failed to serialize the configuration: {err}");
+                        1
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Args, Default, Clone, Debug, PartialEq, Eq)]
 pub struct WindowOptions {
     /// Terminal options which can be passed via IPC.
@@ -2,6 +2,8 @@
 // which is licensed under Apache 2.0 license.
 
 use log::warn;
+use std::collections::VecDeque;
+use std::sync::Mutex;
 
 use winit::window::raw_window_handle::RawDisplayHandle;
 
@@ -21,6 +23,48 @@ use copypasta::x11_clipboard::{Primary as X11SelectionClipboard, X11ClipboardCon
 use copypasta::ClipboardContext;
 use copypasta::ClipboardProvider;
 
+/// Text copied to the clipboard from any window.
+static HISTORY: Mutex<History> = Mutex::new(History::new());
+
+/// Recent copies, the most recent first and without duplicates.
+struct History {
+    entries: VecDeque<String>,
+    size: usize,
+}
+
+impl History {
+    const fn new() -> History {
+        History {
+            entries: VecDeque::new(),
+            size: 0,
+        }
+    }
+
+    fn push(&mut self, text: &str) {
+        if self.size == 0 || text.is_empty() {
+            return;
+        }
+        self.entries.retain(|entry| entry != text);
+        self.entries.push_front(text.to_string());
+        self.entries.truncate(self.size);
+    }
+
+    fn set_size(&mut self, size: usize) {
+        self.size = size;
+        self.entries.truncate(size);
+    }
+}
+
+/// Number of copies kept, none when it is zero and the ones kept are dropped.
+pub fn set_history_size(size: usize) {
+    HISTORY.lock().unwrap().set_size(size);
+}
+
+/// Recent copies, the most recent first.
+pub fn history() -> Vec<String> {
+    HISTORY.lock().unwrap().entries.iter().cloned().collect()
+}
+
 pub struct Clipboard {
     clipboard: Box<dyn ClipboardProvider>,
     selection: Option<Box<dyn ClipboardProvider>>,
@@ -83,7 +127,12 @@ impl Clipboard {
             _ => &mut self.clipboard,
         };
 
-        clipboard.set_contents(text.into()).unwrap_or_else(|err| {
+        let text = text.into();
+        if ty == ClipboardType::Clipboard {
+            HISTORY.lock().unwrap().push(&text);
+        }
+
+        clipboard.set_contents(text).unwrap_or_else(|err| {
             warn!("Unable to store text in clipboard: {}", err);
         });
     }
@@ -103,3 +152,26 @@ impl Clipboard {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history() {
+        let mut history = History::new();
+        history.push("ignored");
+        assert!(history.entries.is_empty());
+
+        history.set_size(3);
+        for text in ["one", "two", "", "three", "one", "four"] {
+            history.push(text);
+        }
+        assert_eq!(history.entries, ["four", "one", "three"]);
+
+        history.set_size(1);
+        assert_eq!(history.entries, ["four"]);
+        history.set_size(0);
+        assert!(history.entries.is_empty());
+    }
+}
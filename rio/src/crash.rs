@@ -0,0 +1,220 @@
+// Crash reports and safe mode.
+//
+// The panic hook writes a report with the backtrace, a hash of the config
+// file and the GPU adapter into `directory()`. A marker file is created at
+// launch and removed once the first frame is presented, if it is still there
+// on the next launch the previous one crashed before drawing anything and Rio
+// starts in safe mode, with the default configuration.
+
+use std::backtrace::Backtrace;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::panic::{self, PanicInfo};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const STARTUP_MARKER: &str = "starting";
+const REPORT_PREFIX: &str = "rio-crash-";
+
+static ADAPTER: Mutex<Option<String>> = Mutex::new(None);
+
+/// Directory of the crash reports.
+pub fn directory() -> PathBuf {
+    rio_config::paths::state_dir().join("crashes")
+}
+
+/// Record the GPU adapter for the crash reports.
+pub fn set_adapter(info: &wgpu::AdapterInfo) {
+    if let Ok(mut adapter) = ADAPTER.lock() {
+        *adapter = Some(format!(
+            "{} ({:?}, {:?}, driver {} {})",
+            info.name, info.backend, info.device_type, info.driver, info.driver_info
+        ));
+    }
+}
+
+/// Write a crash report on panic, before running the previous hook.
+pub fn attach_handler() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let config = std::fs::read(rio_config::config_file_path()).ok();
+        let adapter = ADAPTER.lock().ok().and_then(|adapter| adapter.clone());
+        let content = report(info, config.as_deref(), adapter.as_deref());
+
+        match write_report(&directory(), &content) {
+            Ok(path) => eprintln!(
+                "Rio crashed, a report was written to {}\n\
+                 Start Rio with --safe-mode to use the default configuration.",
+                path.display()
+            ),
+            Err(err) => eprintln!("unable to write the crash report: {err}"),
+        }
+
+        previous(info);
+    }));
+}
+
+fn report(info: &PanicInfo, config: Option<&[u8]>, adapter: Option<&str>) -> String {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("unknown panic"));
+    let location = info
+        .location()
+        .map(|location| location.to_string())
+        .unwrap_or_default();
+    let thread = std::thread::current()
+        .name()
+        .unwrap_or("unnamed")
+        .to_string();
+
+    format_report(
+        &message,
+        &location,
+        &thread,
+        config,
+        adapter,
+        &Backtrace::force_capture().to_string(),
+    )
+}
+
+fn format_report(
+    message: &str,
+    location: &str,
+    thread: &str,
+    config: Option<&[u8]>,
+    adapter: Option<&str>,
+    backtrace: &str,
+) -> String {
+    let config = match config {
+        Some(content) => {
+            let mut hasher = DefaultHasher::new();
+            content.hash(&mut hasher);
+            format!("{:016x}", hasher.finish())
+        }
+        None => String::from("none"),
+    };
+
+    format!(
+        "Rio {} crashed on {} {}\n\n\
+         panic: {message}\n\
+         location: {location}\n\
+         thread: {thread}\n\
+         config: {config}\n\
+         adapter: {}\n\n\
+         backtrace:\n{backtrace}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        adapter.unwrap_or("unknown"),
+    )
+}
+
+fn write_report(directory: &Path, content: &str) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(directory)?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    let path = directory.join(format!("{REPORT_PREFIX}{timestamp}.txt"));
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// Most recent crash report.
+pub fn latest_report() -> Option<PathBuf> {
+    latest_report_in(&directory())
+}
+
+fn latest_report_in(directory: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(directory)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.starts_with(REPORT_PREFIX))
+        })
+        // Reports are named after their timestamp.
+        .max()
+}
+
+/// Create the startup marker, returns whether the previous launch crashed
+/// before presenting its first frame.
+pub fn begin_startup() -> bool {
+    begin_startup_in(&directory())
+}
+
+fn begin_startup_in(directory: &Path) -> bool {
+    let marker = directory.join(STARTUP_MARKER);
+    let crashed = marker.exists();
+    if let Err(err) =
+        std::fs::create_dir_all(directory).and_then(|_| std::fs::write(&marker, ""))
+    {
+        log::warn!("unable to create the startup marker: {err}");
+    }
+    crashed
+}
+
+/// Remove the startup marker once the first frame is presented.
+pub fn end_startup() {
+    let _ = std::fs::remove_file(directory().join(STARTUP_MARKER));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_report() {
+        let report = format_report(
+            "index out of bounds",
+            "src/screen/mod.rs:10:5",
+            "main",
+            Some(b"theme = 'dracula'"),
+            None,
+            "0: rioterm::main",
+        );
+
+        assert!(report.starts_with(&format!("Rio {} crashed", env!("CARGO_PKG_VERSION"))));
+        assert!(report.contains("panic: index out of bounds\n"));
+        assert!(report.contains("location: src/screen/mod.rs:10:5\n"));
+        assert!(report.contains("thread: main\n"));
+        assert!(report.contains("adapter: unknown\n"));
+        assert!(report.ends_with("backtrace:\n0: rioterm::main\n"));
+
+        // The config is identified by a hash, its content is never written.
+        assert!(!report.contains("dracula"));
+        let other = format_report("", "", "", Some(b"theme = 'lucario'"), None, "");
+        let config = |report: &str| {
+            report
+                .lines()
+                .find(|line| line.starts_with("config: "))
+                .map(String::from)
+        };
+        assert_ne!(config(&report), config(&other));
+    }
+
+    #[test]
+    fn test_startup_marker_and_reports() {
+        let directory =
+            std::env::temp_dir().join(format!("rio-test-crash-{}", std::process::id()));
+
+        assert!(!begin_startup_in(&directory));
+        // Still there, as if the previous launch crashed.
+        assert!(begin_startup_in(&directory));
+
+        assert_eq!(latest_report_in(&directory), None);
+        std::fs::write(directory.join("rio-crash-100.txt"), "").unwrap();
+        std::fs::write(directory.join("rio-crash-200.txt"), "").unwrap();
+        assert_eq!(
+            latest_report_in(&directory),
+            Some(directory.join("rio-crash-200.txt"))
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,200 @@
+// Arabic letters change their form with the letters they join to. Fonts are
+// drawn glyph by glyph without a shaper, so the letters are replaced by their
+// contextual forms from the Arabic Presentation Forms-B block before drawing,
+// the grid keeps the letters as they were written.
+
+const TATWEEL: char = '\u{0640}';
+const LAM: char = '\u{0644}';
+
+/// Letters with their first presentation form and number of forms, two for
+/// letters only joining the previous one (isolated, final) and four for
+/// letters joining both sides (isolated, final, initial, medial).
+const FORMS: [(char, u32, u8); 36] = [
+    ('\u{0621}', 0xFE80, 1),
+    ('\u{0622}', 0xFE81, 2),
+    ('\u{0623}', 0xFE83, 2),
+    ('\u{0624}', 0xFE85, 2),
+    ('\u{0625}', 0xFE87, 2),
+    ('\u{0626}', 0xFE89, 4),
+    ('\u{0627}', 0xFE8D, 2),
+    ('\u{0628}', 0xFE8F, 4),
+    ('\u{0629}', 0xFE93, 2),
+    ('\u{062A}', 0xFE95, 4),
+    ('\u{062B}', 0xFE99, 4),
+    ('\u{062C}', 0xFE9D, 4),
+    ('\u{062D}', 0xFEA1, 4),
+    ('\u{062E}', 0xFEA5, 4),
+    ('\u{062F}', 0xFEA9, 2),
+    ('\u{0630}', 0xFEAB, 2),
+    ('\u{0631}', 0xFEAD, 2),
+    ('\u{0632}', 0xFEAF, 2),
+    ('\u{0633}', 0xFEB1, 4),
+    ('\u{0634}', 0xFEB5, 4),
+    ('\u{0635}', 0xFEB9, 4),
+    ('\u{0636}', 0xFEBD, 4),
+    ('\u{0637}', 0xFEC1, 4),
+    ('\u{0638}', 0xFEC5, 4),
+    ('\u{0639}', 0xFEC9, 4),
+    ('\u{063A}', 0xFECD, 4),
+    ('\u{0641}', 0xFED1, 4),
+    ('\u{0642}', 0xFED5, 4),
+    ('\u{0643}', 0xFED9, 4),
+    ('\u{0644}', 0xFEDD, 4),
+    ('\u{0645}', 0xFEE1, 4),
+    ('\u{0646}', 0xFEE5, 4),
+    ('\u{0647}', 0xFEE9, 4),
+    ('\u{0648}', 0xFEED, 2),
+    ('\u{0649}', 0xFEEF, 2),
+    ('\u{064A}', 0xFEF1, 4),
+];
+
+/// Lam followed by one of these alefs is drawn as a single ligature, from
+/// its isolated form (the final form comes next).
+const LAM_ALEF: [(char, u32); 4] = [
+    ('\u{0622}', 0xFEF5),
+    ('\u{0623}', 0xFEF7),
+    ('\u{0625}', 0xFEF9),
+    ('\u{0627}', 0xFEFB),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Form {
+    Isolated,
+    Final,
+    Initial,
+    Medial,
+}
+
+#[inline]
+fn forms(c: char) -> Option<(u32, u8)> {
+    FORMS
+        .iter()
+        .find(|(letter, _, _)| *letter == c)
+        .map(|(_, first, count)| (*first, *count))
+}
+
+/// Marks drawn over the letters, they don't break the joining.
+#[inline]
+fn is_transparent(c: char) -> bool {
+    matches!(c, '\u{0610}'..='\u{061A}' | '\u{064B}'..='\u{065F}' | '\u{0670}')
+}
+
+#[inline]
+fn joins_next(c: char) -> bool {
+    c == TATWEEL || matches!(forms(c), Some((_, 4)))
+}
+
+#[inline]
+fn joins_previous(c: char) -> bool {
+    c == TATWEEL || matches!(forms(c), Some((_, 2 | 4)))
+}
+
+#[inline]
+pub fn has_arabic(chars: &[char]) -> bool {
+    chars.iter().any(|c| ('\u{0600}'..='\u{06FF}').contains(c))
+}
+
+/// Replace the Arabic letters of a line, in logical order, by their
+/// contextual forms. A lam-alef pair becomes one ligature and the alef is
+/// replaced by a space so the columns don't move.
+pub fn shape(chars: &mut [char]) {
+    if !has_arabic(chars) {
+        return;
+    }
+
+    let letters: Vec<char> = chars.to_vec();
+    let neighbour = |index: usize, step: isize| -> Option<char> {
+        let mut index = index as isize + step;
+        while index >= 0 && (index as usize) < letters.len() {
+            let c = letters[index as usize];
+            if !is_transparent(c) {
+                return Some(c);
+            }
+            index += step;
+        }
+        None
+    };
+
+    let mut index = 0;
+    while index < letters.len() {
+        let c = letters[index];
+        let (first, count) = match forms(c) {
+            Some(forms) => forms,
+            None => {
+                index += 1;
+                continue;
+            }
+        };
+
+        let after_joining = neighbour(index, -1).is_some_and(joins_next);
+        let next = neighbour(index, 1);
+
+        if c == LAM {
+            let alef = index + 1;
+            let ligature = LAM_ALEF.iter().find(|(letter, _)| Some(*letter) == next);
+            if let (Some((_, ligature)), Some(true)) =
+                (ligature, letters.get(alef).map(|c| !is_transparent(*c)))
+            {
+                let form = if after_joining { 1 } else { 0 };
+                chars[index] = char::from_u32(ligature + form).unwrap_or(c);
+                chars[alef] = ' ';
+                index += 2;
+                continue;
+            }
+        }
+
+        let before_joining = count == 4 && next.is_some_and(joins_previous);
+        let form = match (after_joining && count > 1, before_joining) {
+            (true, true) => Form::Medial,
+            (true, false) => Form::Final,
+            (false, true) => Form::Initial,
+            (false, false) => Form::Isolated,
+        };
+        chars[index] = char::from_u32(first + form as u32).unwrap_or(c);
+        index += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shaped(text: &str) -> String {
+        let mut chars: Vec<char> = text.chars().collect();
+        shape(&mut chars);
+        chars.into_iter().collect()
+    }
+
+    #[test]
+    fn test_shape() {
+        assert_eq!(shaped("echo"), "echo");
+
+        // Beh alone, then beh-beh-beh as initial, medial and final.
+        assert_eq!(shaped("\u{0628}"), "\u{FE8F}");
+        assert_eq!(
+            shaped("\u{0628}\u{0628}\u{0628}"),
+            "\u{FE91}\u{FE92}\u{FE90}"
+        );
+        // Dal doesn't join the next letter, the beh after it is isolated.
+        assert_eq!(
+            shaped("\u{0628}\u{062F}\u{0628}"),
+            "\u{FE91}\u{FEAA}\u{FE8F}"
+        );
+        // Marks are kept and don't break the joining.
+        assert_eq!(
+            shaped("\u{0628}\u{064E}\u{0628}"),
+            "\u{FE91}\u{064E}\u{FE90}"
+        );
+        // Spaces and latin letters break it.
+        assert_eq!(shaped("\u{0628} \u{0628}x"), "\u{FE8F} \u{FE8F}x");
+    }
+
+    #[test]
+    fn test_lam_alef() {
+        // "la" alone and after a joining beh.
+        assert_eq!(shaped("\u{0644}\u{0627}"), "\u{FEFB} ");
+        assert_eq!(shaped("\u{0628}\u{0644}\u{0627}"), "\u{FE91}\u{FEFC} ");
+        // Lam followed by another letter.
+        assert_eq!(shaped("\u{0644}\u{0645}"), "\u{FEDF}\u{FEE2}");
+    }
+}
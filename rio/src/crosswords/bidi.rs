@@ -0,0 +1,160 @@
+// Bidirectional text stays in the grid in logical order, as applications
+// wrote it, and rows are only reordered to be drawn. Every row is its own
+// left-to-right paragraph so lines keep their alignment, right-to-left runs
+// (Hebrew, Arabic) inside it are reversed by the Unicode BiDi algorithm.
+
+use crate::crosswords::grid::row::Row;
+use crate::crosswords::square::{Flags, Square};
+use unicode_bidi::{Level, ParagraphBidiInfo};
+
+/// First code point of the right-to-left scripts, rows below it are drawn as
+/// they are without running the algorithm.
+const FIRST_RTL_CHAR: char = '\u{0590}';
+
+/// Columns of the drawn squares, wide char spacers are left out.
+fn drawn_columns(row: &Row<Square>) -> Vec<usize> {
+    row.inner
+        .iter()
+        .enumerate()
+        .filter(|(_, square)| !square.flags.contains(Flags::WIDE_CHAR_SPACER))
+        .map(|(column, _)| column)
+        .collect()
+}
+
+/// Visual order of the drawn squares of a row: for each position, the index
+/// of the drawn square shown there and whether it is in a right-to-left run.
+/// Returns None for rows without right-to-left text.
+pub fn visual_order(row: &Row<Square>) -> Option<Vec<(usize, bool)>> {
+    if row.inner.iter().all(|square| square.c < FIRST_RTL_CHAR) {
+        return None;
+    }
+
+    let text: String = drawn_columns(row)
+        .into_iter()
+        .map(|column| row.inner[column].c)
+        .collect();
+    let info = ParagraphBidiInfo::new(&text, Some(Level::ltr()));
+    if !info.has_rtl() {
+        return None;
+    }
+
+    let levels = info.reordered_levels_per_char(0..text.len());
+    let order = ParagraphBidiInfo::reorder_visual(&levels);
+    Some(
+        order
+            .into_iter()
+            .map(|index| (index, levels[index].is_rtl()))
+            .collect(),
+    )
+}
+
+/// Grid column of the square drawn at `visual_column`.
+pub fn logical_column(row: &Row<Square>, visual_column: usize) -> usize {
+    let order = match visual_order(row) {
+        Some(order) => order,
+        None => return visual_column,
+    };
+
+    let columns = drawn_columns(row);
+    let mut position = 0;
+    for (index, _) in order {
+        let column = columns[index];
+        let width = if row.inner[column].flags.contains(Flags::WIDE_CHAR) {
+            2
+        } else {
+            1
+        };
+        if visual_column < position + width {
+            return column + (visual_column - position);
+        }
+        position += width;
+    }
+    visual_column
+}
+
+/// Mirrored glyph of paired characters drawn in a right-to-left run.
+#[inline]
+pub fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(text: &str) -> Row<Square> {
+        let mut row: Row<Square> = Row::new(text.chars().count());
+        for (column, c) in text.chars().enumerate() {
+            row.inner[column].c = c;
+        }
+        row
+    }
+
+    fn visual(row: &Row<Square>) -> String {
+        let columns = drawn_columns(row);
+        match visual_order(row) {
+            Some(order) => order
+                .into_iter()
+                .map(|(index, rtl)| {
+                    let c = row.inner[columns[index]].c;
+                    if rtl {
+                        mirror(c)
+                    } else {
+                        c
+                    }
+                })
+                .collect(),
+            None => columns
+                .into_iter()
+                .map(|column| row.inner[column].c)
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_visual_order() {
+        assert_eq!(visual_order(&row("echo hello")), None);
+
+        // Hebrew "shalom" is reversed, the latin text around it is not.
+        assert_eq!(visual(&row("say שלום now")), "say םולש now");
+        // Numbers keep their direction inside a right-to-left run.
+        assert_eq!(visual(&row("שלום 123")), "123 םולש");
+        assert_eq!(visual(&row("א(ב)")), "(ב)א");
+    }
+
+    #[test]
+    fn test_logical_column() {
+        let row = row("ab אבג cd");
+        assert_eq!(logical_column(&row, 0), 0);
+        assert_eq!(logical_column(&row, 3), 5);
+        assert_eq!(logical_column(&row, 5), 3);
+        assert_eq!(logical_column(&row, 7), 7);
+
+        let ltr = super::tests::row("abc");
+        assert_eq!(logical_column(&ltr, 2), 2);
+    }
+
+    #[test]
+    fn test_wide_chars() {
+        let mut row = row("אב中  x");
+        row.inner[2].flags.insert(Flags::WIDE_CHAR);
+        row.inner[3].flags.insert(Flags::WIDE_CHAR_SPACER);
+
+        assert_eq!(visual(&row), "בא中 x");
+        assert_eq!(logical_column(&row, 2), 2);
+        assert_eq!(logical_column(&row, 3), 3);
+        assert_eq!(logical_column(&row, 0), 1);
+    }
+}
@@ -0,0 +1,212 @@
+// Conformance suite in the spirit of vttest and esctest.
+//
+// Each file in `conformance/` holds scripted cases that are fed through the
+// parser into a headless terminal, the visible grid and the cursor are then
+// compared against the expected state:
+//
+//     test cup-is-clamped
+//     size 10x3
+//     input \e[5;20HX
+//     cursor 2 9
+//     screen
+//     |          |
+//     |          |
+//     |         X|
+//
+// `input` lines are concatenated and understand the `\e`, `\r`, `\n`, `\t`,
+// `\b`, `\\` and `\xNN` escapes. `cursor` takes the line and the column, both zero
+// based, and `screen` is followed by one `|`-delimited row per screen line.
+// Lines starting with `#` are comments.
+
+use crate::ansi::CursorShape;
+use crate::crosswords::pos::{Column, Line};
+use crate::crosswords::Crosswords;
+use crate::event::VoidListener;
+use crate::performer::playback;
+use crate::performer::recorder::Cast;
+use std::path::Path;
+use winit::window::WindowId;
+
+#[derive(Debug, Default)]
+struct Case {
+    name: String,
+    columns: usize,
+    lines: usize,
+    input: Vec<u8>,
+    cursor: Option<(usize, usize)>,
+    screen: Vec<String>,
+}
+
+impl Case {
+    /// Run the case, returning a description of every mismatch.
+    fn run(&self) -> Vec<String> {
+        let mut terminal = Crosswords::new(
+            self.columns,
+            self.lines,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        playback::replay(&Cast::from_raw(&self.input), &mut terminal);
+
+        let mut failures = Vec::new();
+        for (line, expected) in self.screen.iter().enumerate() {
+            // Tabs are kept in the cells they skipped but render as spaces.
+            let actual: String = (0..self.columns)
+                .map(
+                    |column| match terminal.grid[Line(line as i32)][Column(column)].c {
+                        '\t' => ' ',
+                        c => c,
+                    },
+                )
+                .collect();
+            if &actual != expected {
+                failures.push(format!(
+                    "{}: line {line} is |{actual}|, expected |{expected}|",
+                    self.name
+                ));
+            }
+        }
+
+        if let Some((line, column)) = self.cursor {
+            let pos = terminal.grid.cursor.pos;
+            if (pos.row.0 as usize, pos.col.0) != (line, column) {
+                failures.push(format!(
+                    "{}: cursor is at {} {}, expected {line} {column}",
+                    self.name, pos.row.0, pos.col.0
+                ));
+            }
+        }
+
+        failures
+    }
+}
+
+fn parse(content: &str) -> Result<Vec<Case>, String> {
+    let mut cases: Vec<Case> = Vec::new();
+    let mut in_screen = false;
+
+    for (number, line) in content.lines().enumerate() {
+        let error = |message: &str| format!("line {}: {message}", number + 1);
+
+        if line.starts_with('#') || line.trim().is_empty() {
+            in_screen = false;
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix("test ") {
+            cases.push(Case {
+                name: name.trim().to_string(),
+                ..Case::default()
+            });
+            in_screen = false;
+            continue;
+        }
+
+        let case = cases
+            .last_mut()
+            .ok_or_else(|| error("expected a `test` line first"))?;
+
+        if in_screen {
+            let row = line
+                .strip_prefix('|')
+                .and_then(|row| row.strip_suffix('|'))
+                .ok_or_else(|| error("screen rows are delimited by `|`"))?;
+            if row.chars().count() != case.columns {
+                return Err(error("screen row does not match the size"));
+            }
+            case.screen.push(row.to_string());
+            continue;
+        }
+
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match keyword {
+            "size" => {
+                let (columns, lines) = rest
+                    .split_once('x')
+                    .and_then(|(c, l)| Some((c.parse().ok()?, l.parse().ok()?)))
+                    .ok_or_else(|| error("size is `<columns>x<lines>`"))?;
+                case.columns = columns;
+                case.lines = lines;
+            }
+            "input" => case.input.extend(unescape(rest).map_err(|e| error(&e))?),
+            "cursor" => {
+                let mut values = rest.split_whitespace().map(str::parse);
+                match (values.next(), values.next()) {
+                    (Some(Ok(line)), Some(Ok(column))) => {
+                        case.cursor = Some((line, column))
+                    }
+                    _ => return Err(error("cursor is `<line> <column>`")),
+                }
+            }
+            "screen" => in_screen = true,
+            _ => return Err(error(&format!("unknown keyword `{keyword}`"))),
+        }
+    }
+
+    Ok(cases)
+}
+
+fn unescape(input: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+
+        match chars.next() {
+            Some('e') => bytes.push(0x1b),
+            Some('r') => bytes.push(b'\r'),
+            Some('n') => bytes.push(b'\n'),
+            Some('t') => bytes.push(b'\t'),
+            Some('b') => bytes.push(0x08),
+            Some('\\') => bytes.push(b'\\'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                let byte = u8::from_str_radix(&hex, 16)
+                    .map_err(|_| format!("invalid escape `\\x{hex}`"))?;
+                bytes.push(byte);
+            }
+            other => return Err(format!("invalid escape `\\{}`", other.unwrap_or(' '))),
+        }
+    }
+    Ok(bytes)
+}
+
+#[test]
+fn test_conformance() {
+    let directory =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src/crosswords/conformance");
+    let mut files: Vec<_> = std::fs::read_dir(&directory)
+        .unwrap()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().map_or(false, |ext| ext == "vt"))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty());
+
+    let mut failures = Vec::new();
+    for file in files {
+        let content = std::fs::read_to_string(&file).unwrap();
+        let name = file.file_name().unwrap().to_string_lossy().to_string();
+        let cases = parse(&content).unwrap_or_else(|err| panic!("{name}: {err}"));
+        for case in cases {
+            failures.extend(case.run().into_iter().map(|f| format!("{name}: {f}")));
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn test_unescape() {
+    assert_eq!(
+        unescape("a\\e[m\\r\\n\\x07\\\\").unwrap(),
+        b"a\x1b[m\r\n\x07\\"
+    );
+    assert!(unescape("\\q").is_err());
+    assert!(unescape("\\xZZ").is_err());
+}
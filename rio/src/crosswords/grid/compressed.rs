@@ -0,0 +1,221 @@
+// Scrollback rows far from the viewport are packed into compressed chunks.
+//
+// Each chunk keeps a palette with the distinct cell styles (everything except the
+// character) found in its rows, cells are then packed as runs of a repeated
+// character and palette index using varints. Chunks are unpacked back into the
+// grid when the user scrolls into them.
+
+use super::row::Row;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::mem::size_of;
+
+/// Number of history lines kept uncompressed above the viewport.
+pub const HOT_HISTORY_LINES: usize = 1_000;
+
+/// Number of rows packed together in a chunk.
+pub const CHUNK_LINES: usize = 256;
+
+/// Cells that can be stored in compressed scrollback.
+pub trait Compressible: Clone + Eq + Hash {
+    /// Split the cell into its character and its style.
+    fn split(&self) -> (char, Self);
+
+    /// Build a cell back from its character and style.
+    fn join(c: char, style: &Self) -> Self;
+}
+
+#[derive(Debug, Clone)]
+struct Chunk<T> {
+    lines: usize,
+    bytes: Vec<u8>,
+    styles: Vec<T>,
+}
+
+/// Compressed history, ordered from the oldest chunk to the newest one.
+#[derive(Debug, Clone)]
+pub struct CompressedHistory<T> {
+    chunks: VecDeque<Chunk<T>>,
+    lines: usize,
+}
+
+impl<T> Default for CompressedHistory<T> {
+    fn default() -> Self {
+        Self {
+            chunks: VecDeque::new(),
+            lines: 0,
+        }
+    }
+}
+
+impl<T> CompressedHistory<T> {
+    /// Number of lines stored.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.lines
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.lines == 0
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.lines = 0;
+    }
+
+    /// Drop the oldest chunks until at most `max_lines` are stored.
+    pub fn truncate(&mut self, max_lines: usize) {
+        while self.lines > max_lines {
+            match self.chunks.pop_front() {
+                Some(chunk) => self.lines -= chunk.lines,
+                None => break,
+            }
+        }
+    }
+
+    /// Bytes taken by the chunks, packed cells and style palettes.
+    pub fn memory(&self) -> usize {
+        self.chunks
+            .iter()
+            .map(|chunk| chunk.bytes.len() + chunk.styles.len() * size_of::<T>())
+            .sum()
+    }
+
+    /// Drop the oldest chunk, returns the number of lines dropped.
+    pub fn pop_oldest(&mut self) -> Option<usize> {
+        let chunk = self.chunks.pop_front()?;
+        self.lines -= chunk.lines;
+        Some(chunk.lines)
+    }
+}
+
+impl<T: Compressible + Default> CompressedHistory<T> {
+    /// Pack `rows`, ordered from the oldest to the newest, as the newest chunk.
+    pub fn push(&mut self, rows: &[&Row<T>]) {
+        let mut styles: Vec<T> = Vec::new();
+        let mut palette: HashMap<T, usize> = HashMap::new();
+        let mut raw = Vec::new();
+        let mut last_style = 0;
+
+        for row in rows {
+            write_varint(&mut raw, row.len() as u32);
+
+            let mut run: Option<(char, usize, u32)> = None;
+            for cell in &row.inner {
+                let (c, style) = cell.split();
+
+                // Consecutive cells usually share the same style.
+                let index = if styles.get(last_style) == Some(&style) {
+                    last_style
+                } else {
+                    *palette.entry(style).or_insert_with_key(|style| {
+                        styles.push(style.clone());
+                        styles.len() - 1
+                    })
+                };
+                last_style = index;
+
+                run = match run {
+                    Some((run_c, run_index, count))
+                        if run_c == c && run_index == index =>
+                    {
+                        Some((c, index, count + 1))
+                    }
+                    Some(previous) => {
+                        write_run(&mut raw, previous);
+                        Some((c, index, 1))
+                    }
+                    None => Some((c, index, 1)),
+                };
+            }
+
+            if let Some(run) = run {
+                write_run(&mut raw, run);
+            }
+        }
+
+        raw.shrink_to_fit();
+        styles.shrink_to_fit();
+        self.lines += rows.len();
+        self.chunks.push_back(Chunk {
+            lines: rows.len(),
+            bytes: raw,
+            styles,
+        });
+    }
+
+    /// Size in bytes of the packed cells.
+    #[allow(dead_code)]
+    pub fn packed_size(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.bytes.len()).sum()
+    }
+
+    /// Unpack the newest chunk, rows are ordered from the oldest to the newest
+    /// and fitted to `columns`. The grid unpacks every chunk before its width
+    /// changes, so rows only need padding when they were packed shorter.
+    pub fn pop(&mut self, columns: usize) -> Option<Vec<Row<T>>> {
+        let chunk = self.chunks.pop_back()?;
+        self.lines -= chunk.lines;
+
+        let mut bytes = chunk.bytes.as_slice();
+        let mut rows = Vec::with_capacity(chunk.lines);
+        for _ in 0..chunk.lines {
+            let len = read_varint(&mut bytes) as usize;
+            let mut cells = Vec::with_capacity(len.max(columns));
+            while cells.len() < len && !bytes.is_empty() {
+                let count = read_varint(&mut bytes) as usize;
+                let c = char::from_u32(read_varint(&mut bytes)).unwrap_or(' ');
+                let cell = match chunk.styles.get(read_varint(&mut bytes) as usize) {
+                    Some(style) => T::join(c, style),
+                    None => T::default(),
+                };
+                let count = count.min(len - cells.len());
+                cells.resize(cells.len() + count, cell);
+            }
+
+            cells.truncate(columns);
+            cells.resize_with(columns, T::default);
+            let occ = cells.len();
+            rows.push(Row::from_vec(cells, occ));
+        }
+
+        Some(rows)
+    }
+}
+
+#[inline]
+fn write_run(buf: &mut Vec<u8>, (c, index, count): (char, usize, u32)) {
+    write_varint(buf, count);
+    write_varint(buf, c as u32);
+    write_varint(buf, index as u32);
+}
+
+#[inline]
+fn write_varint(buf: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+#[inline]
+fn read_varint(bytes: &mut &[u8]) -> u32 {
+    let mut value = 0u32;
+    let mut shift = 0;
+    while let Some((&byte, rest)) = bytes.split_first() {
+        *bytes = rest;
+        value |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+        if shift >= u32::BITS {
+            break;
+        }
+    }
+    value
+}
@@ -2,6 +2,7 @@
 // https://github.com/alacritty/alacritty/blob/e35e5ad14fce8456afdd89f2b392b9924bb27471/alacritty_terminal/src/grid/mod.rs
 // which is licensed under Apache 2.0 license.
 
+pub mod compressed;
 pub mod resize;
 pub mod row;
 pub mod storage;
@@ -14,8 +15,10 @@ use crate::crosswords::square::Flags;
 use crate::crosswords::square::ResetDiscriminant;
 use crate::crosswords::Cursor;
 use crate::crosswords::{Column, Line};
+use compressed::{CompressedHistory, Compressible, CHUNK_LINES, HOT_HISTORY_LINES};
 use row::Row;
 use std::cmp::{max, min};
+use std::mem::size_of;
 use std::ops::{Bound, Deref, Index, IndexMut, Range, RangeBounds};
 use storage::Storage;
 
@@ -63,16 +66,29 @@ pub struct Grid<T> {
     /// updates this offset accordingly.
     display_offset: usize,
 
+    /// Lines written since the display was scrolled away from the bottom.
+    new_lines: usize,
+
     /// Maximum number of lines in history.
     max_scroll_limit: usize,
+
+    /// History lines older than the uncompressed part of `raw`.
+    compressed: CompressedHistory<T>,
+
+    /// Whether old history lines are compressed. Search, selection, vi mode
+    /// and prompt marks only see the uncompressed lines.
+    compress: bool,
 }
 
-impl<T: GridSquare + Default + PartialEq + Clone> Grid<T> {
+impl<T: GridSquare + Default + PartialEq + Clone + Compressible> Grid<T> {
     pub fn new(lines: usize, columns: usize, max_scroll_limit: usize) -> Grid<T> {
         Grid {
             raw: Storage::with_capacity(lines, columns),
+            compressed: CompressedHistory::default(),
+            compress: false,
             max_scroll_limit,
             display_offset: 0,
+            new_lines: 0,
             saved_cursor: Cursor::default(),
             cursor: Cursor::default(),
             lines,
@@ -87,25 +103,122 @@ impl<T: GridSquare + Default + PartialEq + Clone> Grid<T> {
         if current_history_size > history_size {
             self.raw.shrink_lines(current_history_size - history_size);
         }
+        self.compressed
+            .truncate(history_size.saturating_sub(self.history_size()));
         self.display_offset = min(self.display_offset, history_size);
         self.max_scroll_limit = history_size;
     }
 
+    /// Bytes taken by the scrollback, the lines in the viewport are not
+    /// counted.
+    pub fn history_memory(&self) -> usize {
+        self.history_size() * self.columns * size_of::<T>() + self.compressed.memory()
+    }
+
+    /// Drop the oldest history lines until `bytes` are freed or the history is
+    /// empty, compressed chunks go first. Returns the bytes freed.
+    pub fn evict_history(&mut self, bytes: usize) -> usize {
+        let before = self.history_memory();
+        let target = before.saturating_sub(bytes);
+        while self.history_memory() > target && self.compressed.pop_oldest().is_some() {}
+
+        let line = (self.columns * size_of::<T>()).max(1);
+        let excess = self.history_memory().saturating_sub(target);
+        let count = min(excess.div_ceil(line), self.history_size());
+        if count != 0 {
+            self.raw.shrink_lines(count);
+            // Rows kept around for reuse are released too.
+            self.raw.truncate();
+        }
+        self.display_offset = min(self.display_offset, self.history_size());
+
+        before - self.history_memory()
+    }
+
     pub fn scroll_display(&mut self, scroll: Scroll) {
-        self.display_offset = match scroll {
+        let history_size = self.history_size() + self.compressed.len();
+        let display_offset = match scroll {
             Scroll::Delta(count) => min(
                 max((self.display_offset as i32) + count, 0) as usize,
-                self.history_size(),
+                history_size,
             ),
-            Scroll::PageUp => min(self.display_offset + self.lines, self.history_size()),
+            Scroll::PageUp => min(self.display_offset + self.lines, history_size),
             Scroll::PageDown => self.display_offset.saturating_sub(self.lines),
-            Scroll::Top => self.history_size(),
+            Scroll::Top => history_size,
             Scroll::Bottom => 0,
         };
+
+        self.decompress_history(display_offset);
+        self.display_offset = min(display_offset, self.history_size());
+        if self.display_offset == 0 {
+            self.new_lines = 0;
+        }
+    }
+
+    /// Compress the old history lines, or unpack them all when disabled.
+    pub fn set_compression(&mut self, compress: bool) {
+        self.compress = compress;
+        if compress {
+            self.compress_history();
+        } else {
+            self.decompress_history(usize::MAX);
+        }
+    }
+
+    /// Unpack the whole compressed history, before the rows are reflowed to
+    /// another width.
+    pub fn decompress_all(&mut self) {
+        self.decompress_history(usize::MAX);
+    }
+
+    /// Unpack compressed history until `display_offset` is reachable.
+    fn decompress_history(&mut self, display_offset: usize) {
+        while display_offset > self.history_size() {
+            let rows = match self.compressed.pop(self.columns) {
+                Some(rows) => rows,
+                None => break,
+            };
+
+            let top = self.history_size();
+            self.raw.initialize(rows.len(), self.columns);
+            for (i, row) in rows.into_iter().rev().enumerate() {
+                self.raw[Line(-((top + i + 1) as i32))] = row;
+            }
+        }
+    }
+
+    /// Pack the oldest history lines once the uncompressed history grows past
+    /// its limit, lines in the viewport are always kept uncompressed.
+    fn compress_history(&mut self) {
+        while self.compress {
+            let top = self.history_size();
+            if top < HOT_HISTORY_LINES + CHUNK_LINES
+                || top - CHUNK_LINES < self.display_offset + self.lines
+            {
+                return;
+            }
+
+            let rows: Vec<&Row<T>> = (0..CHUNK_LINES)
+                .map(|i| &self.raw[Line(-((top - i) as i32))])
+                .collect();
+            self.compressed.push(&rows);
+            self.raw.shrink_lines(CHUNK_LINES);
+        }
     }
 
     fn increase_scroll_limit(&mut self, count: usize) {
-        let count = min(count, self.max_scroll_limit - self.history_size());
+        // Make room by dropping the oldest compressed lines first.
+        if !self.compressed.is_empty() {
+            let hot = self.history_size() + count;
+            self.compressed
+                .truncate(self.max_scroll_limit.saturating_sub(hot));
+        }
+
+        let count = min(
+            count,
+            self.max_scroll_limit
+                .saturating_sub(self.history_size() + self.compressed.len()),
+        );
         if count != 0 {
             self.raw.initialize(count, self.columns);
         }
@@ -204,6 +317,9 @@ impl<T: GridSquare + Default + PartialEq + Clone> Grid<T> {
         if self.display_offset != 0 {
             self.display_offset =
                 min(self.display_offset + positions, self.max_scroll_limit);
+            self.new_lines += positions;
+        } else {
+            self.new_lines = 0;
         }
 
         // Create scrollback for the new lines.
@@ -234,6 +350,8 @@ impl<T: GridSquare + Default + PartialEq + Clone> Grid<T> {
         for i in (region.end.0..(screen_lines as i32)).rev().map(Line::from) {
             self.raw.swap(i, i - positions);
         }
+
+        self.compress_history();
     }
 
     pub fn clear_viewport<D>(&mut self)
@@ -314,6 +432,7 @@ impl<T> Grid<T> {
     pub fn clear_history(&mut self) {
         // Explicitly purge all lines from history.
         self.raw.shrink_lines(self.history_size());
+        self.compressed.clear();
 
         // Reset display offset.
         self.display_offset = 0;
@@ -377,6 +496,17 @@ impl<T> Grid<T> {
         self.display_offset
     }
 
+    /// Lines written below the display since it was scrolled up, zero when
+    /// it is at the bottom.
+    #[inline]
+    pub fn new_lines(&self) -> usize {
+        if self.display_offset == 0 {
+            0
+        } else {
+            self.new_lines
+        }
+    }
+
     #[inline]
     pub fn cursor_cell(&mut self) -> &mut T {
         let point = self.cursor.pos;
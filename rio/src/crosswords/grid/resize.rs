@@ -2,6 +2,7 @@
 // https://github.com/alacritty/alacritty/blob/e35e5ad14fce8456afdd89f2b392b9924bb27471/alacritty_terminal/src/grid/resize.rs
 // which is licensed under Apache 2.0 license.
 
+use crate::crosswords::grid::compressed::Compressible;
 use crate::crosswords::grid::{Dimensions, Grid, GridSquare};
 use crate::crosswords::pos::{Boundary, Column, Line};
 use crate::crosswords::square::Flags;
@@ -10,13 +11,19 @@ use crate::crosswords::Row;
 use std::cmp::{max, min, Ordering};
 use std::mem;
 
-impl<T: GridSquare + Default + PartialEq + Clone> Grid<T> {
+impl<T: GridSquare + Default + PartialEq + Clone + Compressible> Grid<T> {
     /// Resize the grid's width and/or height.
     pub fn resize<D>(&mut self, reflow: bool, lines: usize, columns: usize)
     where
         T: ResetDiscriminant<D>,
         D: PartialEq,
     {
+        // Compressed rows are packed at the current width, they are reflowed
+        // with the rest of the history and compressed again later.
+        if self.columns != columns {
+            self.decompress_all();
+        }
+
         // Use empty template cell for resetting cells due to resize.
         let template = mem::take(&mut self.cursor.template);
 
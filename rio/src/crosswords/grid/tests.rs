@@ -24,6 +24,16 @@ impl GridSquare for usize {
     }
 }
 
+impl Compressible for usize {
+    fn split(&self) -> (char, Self) {
+        (' ', *self)
+    }
+
+    fn join(_c: char, style: &Self) -> Self {
+        *style
+    }
+}
+
 // Scroll up moves lines upward.
 #[test]
 fn scroll_up() {
@@ -120,6 +130,28 @@ fn scroll_down_with_history() {
     assert_eq!(grid[Line(9)].occ, 1);
 }
 
+#[test]
+fn new_lines() {
+    let mut grid = Grid::<usize>::new(10, 1, 100);
+    let region = Line(0)..Line(10);
+    grid.scroll_up::<usize>(&region, 5);
+    assert_eq!(grid.new_lines(), 0);
+
+    grid.scroll_display(Scroll::Delta(2));
+    grid.scroll_up::<usize>(&region, 3);
+    grid.scroll_up::<usize>(&region, 1);
+    assert_eq!(grid.display_offset(), 6);
+    assert_eq!(grid.new_lines(), 4);
+
+    grid.scroll_display(Scroll::Delta(-3));
+    assert_eq!(grid.new_lines(), 4);
+    grid.scroll_display(Scroll::Bottom);
+    assert_eq!(grid.new_lines(), 0);
+
+    grid.scroll_display(Scroll::Delta(1));
+    assert_eq!(grid.new_lines(), 0);
+}
+
 // Test that GridIterator works.
 #[test]
 fn test_iter() {
@@ -366,3 +398,132 @@ fn wrap_cell(c: char) -> Square {
     cell.flags.insert(Flags::WRAPLINE);
     cell
 }
+
+// Old history is compressed and unpacked again when scrolled into.
+#[test]
+fn compressed_history() {
+    let lines = 5;
+    let mut grid = Grid::<Square>::new(lines, 3, 10_000);
+    grid.set_compression(true);
+    let total = 4_000;
+
+    for i in 0..total {
+        let c = char::from_u32('a' as u32 + (i % 26) as u32).unwrap();
+        grid[Line(lines as i32 - 1)][Column(0)].c = c;
+        grid.scroll_up(&(Line(0)..Line(lines as i32)), 1);
+    }
+
+    let history = total;
+    assert!(grid.history_size() < HOT_HISTORY_LINES + CHUNK_LINES);
+    assert_eq!(grid.history_size() + grid.compressed.len(), history);
+
+    // Packed rows take a fraction of their uncompressed size.
+    let uncompressed = grid.compressed.len() * 3 * std::mem::size_of::<Square>();
+    assert!(grid.compressed.packed_size() * 10 < uncompressed);
+
+    grid.scroll_display(Scroll::Top);
+    assert_eq!(grid.display_offset(), history);
+    assert_eq!(grid.history_size(), history);
+    assert!(grid.compressed.is_empty());
+
+    // Row written at step `i` ends up `total - i` lines above the bottom line.
+    for i in 0..total {
+        let expected = char::from_u32('a' as u32 + (i % 26) as u32).unwrap();
+        let line = Line(lines as i32 - 1 - (total - i) as i32);
+        assert_eq!(grid[line][Column(0)].c, expected);
+    }
+}
+
+// History stays searchable unless compression is asked for, turning it off
+// unpacks everything.
+#[test]
+fn compression_is_opt_in() {
+    let lines = 5;
+    let mut grid = Grid::<Square>::new(lines, 3, 10_000);
+    for _ in 0..2_000 {
+        grid.scroll_up(&(Line(0)..Line(lines as i32)), 1);
+    }
+    assert_eq!(grid.history_size(), 2_000);
+    assert!(grid.compressed.is_empty());
+
+    grid.set_compression(true);
+    assert!(!grid.compressed.is_empty());
+    assert_eq!(grid.history_size() + grid.compressed.len(), 2_000);
+
+    grid.set_compression(false);
+    assert!(grid.compressed.is_empty());
+    assert_eq!(grid.history_size(), 2_000);
+}
+
+// Compressed rows are reflowed like the rest of the history on resize.
+#[test]
+fn resize_compressed_history() {
+    let lines = 2;
+    let mut grids = [
+        Grid::<Square>::new(lines, 6, 10_000),
+        Grid::<Square>::new(lines, 6, 10_000),
+    ];
+    grids[0].set_compression(true);
+    for grid in &mut grids {
+        for _ in 0..HOT_HISTORY_LINES + 2 * CHUNK_LINES {
+            for (column, c) in "abcdef".chars().enumerate() {
+                grid[Line(lines as i32 - 1)][Column(column)].c = c;
+            }
+            grid.scroll_up(&(Line(0)..Line(lines as i32)), 1);
+        }
+    }
+    assert!(!grids[0].compressed.is_empty());
+
+    // Shrinking splits every line in two rows, nothing is cut.
+    for grid in &mut grids {
+        grid.resize(true, lines, 3);
+    }
+    let [compressed, plain] = &grids;
+    assert!(compressed.compressed.is_empty());
+    assert_eq!(compressed.history_size(), plain.history_size());
+    for line in -(plain.history_size() as i32)..lines as i32 {
+        assert_eq!(compressed[Line(line)], plain[Line(line)]);
+    }
+    let oldest = (-(plain.history_size() as i32)..0)
+        .find(|line| compressed[Line(*line)][Column(0)].c == 'a')
+        .unwrap();
+    assert_eq!(compressed[Line(oldest + 1)][Column(0)].c, 'd');
+}
+
+// Eviction drops the oldest compressed chunks before uncompressed lines.
+#[test]
+fn evict_history() {
+    let lines = 5;
+    let mut grid = Grid::<Square>::new(lines, 3, 10_000);
+    grid.set_compression(true);
+    for i in 0..2_000 {
+        grid[Line(lines as i32 - 1)][Column(0)].c =
+            char::from_u32('a' as u32 + (i % 26)).unwrap();
+        grid.scroll_up(&(Line(0)..Line(lines as i32)), 1);
+    }
+    let line = 3 * std::mem::size_of::<Square>();
+    let hot = grid.history_size();
+    assert!(!grid.compressed.is_empty());
+    assert_eq!(grid.history_memory(), hot * line + grid.compressed.memory());
+
+    // A single byte is enough to drop the oldest chunk.
+    let compressed = grid.compressed.len();
+    assert!(grid.evict_history(1) > 0);
+    assert_eq!(grid.compressed.len(), compressed - CHUNK_LINES);
+    assert_eq!(grid.history_size(), hot);
+
+    // Once the chunks are gone uncompressed lines follow, newest kept.
+    grid.scroll_display(Scroll::Top);
+    let hot = grid.history_size();
+    let newest = grid[Line(-1)][Column(0)].c;
+    let freed = grid.evict_history(grid.compressed.memory() + 10 * line);
+    assert!(freed >= 10 * line);
+    assert!(grid.compressed.is_empty());
+    assert_eq!(grid.history_size(), hot - 10);
+    assert_eq!(grid.display_offset(), hot - 10);
+    assert_eq!(grid[Line(-1)][Column(0)].c, newest);
+
+    assert_eq!(grid.evict_history(usize::MAX), (hot - 10) * line);
+    assert_eq!(grid.history_memory(), 0);
+    assert_eq!(grid.display_offset(), 0);
+}
@@ -14,21 +14,31 @@
 // which is licensed under Apache 2.0 license.
 */
 
+pub mod arabic;
 pub mod attr;
+pub mod bidi;
+#[cfg(test)]
+mod conformance;
 pub mod grid;
 pub mod pos;
+pub mod snapshot;
 pub mod square;
 pub mod vi_mode;
+pub mod watch;
+pub mod width;
 
 use crate::ansi::{
     mode::Mode as AnsiMode, ClearMode, CursorShape, KeyboardModes,
-    KeyboardModesApplyBehavior, LineClearMode, TabulationClearMode,
+    KeyboardModesApplyBehavior, LineClearMode, Progress, ProgressState, PromptMark,
+    TabulationClearMode,
 };
 use crate::clipboard::ClipboardType;
 use crate::crosswords::grid::{BidirectionalIterator, Dimensions, Grid, Scroll};
 use crate::event::{EventListener, RioEvent};
 use crate::performer::handler::Handler;
+use crate::performer::monitor::MonitorKind;
 use crate::selection::{Selection, SelectionRange, SelectionType};
+use crate::tmux;
 use attr::*;
 use base64::{engine::general_purpose, Engine as _};
 use bitflags::bitflags;
@@ -37,18 +47,16 @@ use log::{debug, info, warn};
 use pos::{
     Boundary, CharsetIndex, Column, Cursor, CursorState, Direction, Line, Pos, Side,
 };
-use rio_config::colors::{
-    self,
-    term::{List, TermColors},
-    AnsiColor, ColorRgb,
-};
+use rio_config::colors::{self, term::TermColors, AnsiColor, ColorRgb};
+use rio_config::watch::WatchAction;
+use snapshot::{SharedSnapshot, Snapshot};
 use square::{Hyperlink, LineLength, Square};
 use std::mem;
 use std::ops::{Index, IndexMut, Range};
 use std::option::Option;
 use std::ptr;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
-use unicode_width::UnicodeWidthChar;
 use vi_mode::{ViModeCursor, ViMotion};
 use winit::window::WindowId;
 
@@ -57,6 +65,18 @@ pub type NamedColor = colors::NamedColor;
 pub const MIN_COLUMNS: usize = 2;
 pub const MIN_LINES: usize = 1;
 const BRACKET_PAIRS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+const EMOJI_PRESENTATION_SELECTOR: char = '\u{FE0F}';
+
+#[inline]
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+#[inline]
+fn is_emoji_modifier(c: char) -> bool {
+    ('\u{1F3FB}'..='\u{1F3FF}').contains(&c)
+}
 
 bitflags! {
     #[derive(Debug, Copy, Clone)]
@@ -86,6 +106,10 @@ bitflags! {
         const KEYBOARD_REPORT_ALTERNATE_KEYS   = 0b0001_0000_0000_0000_0000_0000;
         const KEYBOARD_REPORT_ALL_KEYS_AS_ESC  = 0b0010_0000_0000_0000_0000_0000;
         const KEYBOARD_REPORT_ASSOCIATED_TEXT  = 0b0100_0000_0000_0000_0000_0000;
+        const LEFT_RIGHT_MARGIN                = 0b1000_0000_0000_0000_0000_0000;
+        const REVERSE_WRAP                     = 0b0001_0000_0000_0000_0000_0000_0000;
+        const REVERSE_VIDEO                    = 0b0010_0000_0000_0000_0000_0000_0000;
+        const GRAPHEME_CLUSTERING              = 0b0100_0000_0000_0000_0000_0000_0000;
         const KEYBOARD_PROTOCOL = Self::KEYBOARD_DISAMBIGUATE_ESC_CODES.bits()
                                 | Self::KEYBOARD_REPORT_EVENT_TYPES.bits()
                                 | Self::KEYBOARD_REPORT_ALTERNATE_KEYS.bits()
@@ -163,12 +187,18 @@ impl LineDamageBounds {
     }
 
     #[inline]
-    #[allow(dead_code)]
     pub fn is_damaged(&self) -> bool {
         self.left <= self.right
     }
 }
 
+/// Lines changed since the damage was last taken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TermDamage {
+    Full,
+    Partial(Vec<LineDamageBounds>),
+}
+
 #[derive(Debug, Clone)]
 struct TermDamageState {
     /// Hint whether terminal should be damaged entirely regardless of the actual damage changes.
@@ -341,18 +371,48 @@ pub struct Crosswords<U>
 where
     U: EventListener,
 {
-    active_charset: CharsetIndex,
+    // Charset used for the next character only, set by SS2 and SS3.
+    single_shift: Option<CharsetIndex>,
     mode: Mode,
+    // Copy of `mode` readable without locking the terminal.
+    shared_mode: Arc<AtomicU32>,
+    // Last snapshot of the visible rows, drawn when the terminal is locked.
+    shared_snapshot: SharedSnapshot,
+    // Bytes of output parsed so far, readable without locking the terminal.
+    parsed_bytes: Arc<AtomicU64>,
     pub vi_mode_cursor: ViModeCursor,
     semantic_escape_chars: String,
+    pub smart_selection: bool,
+    /// Copy concealed (SGR 8) text instead of blanks.
+    pub reveal_concealed: bool,
+    /// Leave out the blanks ending the copied lines.
+    pub trim_trailing_whitespace: bool,
+    /// Copy wrapped rows as one line.
+    pub join_wrapped_lines: bool,
+    pub watch_rules: Vec<watch::WatchRule>,
+    /// Lines scrolled off the top of the screen, numbers the lines for the
+    /// watch rules.
+    scrolled_lines: u64,
+    /// Monitor that fired since the tab was last looked at.
+    pub monitor_alert: Option<MonitorKind>,
+    /// Progress reported by the program with OSC 9 ; 4.
+    pub progress: Progress,
+    /// Host of the working directory reported by the shell with OSC 7, when
+    /// it is another machine.
+    pub remote_host: Option<String>,
+    /// Unicode version of the width tables, the latest when None.
+    pub unicode_version: Option<u8>,
     pub grid: Grid<Square>,
     inactive_grid: Grid<Square>,
     scroll_region: Range<Line>,
+    // Left and right margins set by DECSLRM.
+    margins: Range<Column>,
     tabs: TabStops,
     event_proxy: U,
     pub selection: Option<Selection>,
-    #[allow(dead_code)]
-    colors: List,
+    // Colors changed by OSC 4, 10, 11 and 12, they take precedence over the
+    // configured colors.
+    colors: TermColors,
     pub title: String,
     damage: TermDamageState,
     pub cursor_shape: CursorShape,
@@ -381,25 +441,38 @@ impl<U: EventListener> Crosswords<U> {
 
         let scroll_region = Line(0)..Line(rows as i32);
         let semantic_escape_chars = String::from(",│`|:\"' ()[]{}<>\t");
-        let term_colors = TermColors::default();
-        let colors = List::from(&term_colors);
+        let mode = Mode::SHOW_CURSOR
+            | Mode::LINE_WRAP
+            | Mode::ALTERNATE_SCROLL
+            | Mode::URGENCY_HINTS;
 
         Crosswords {
             vi_mode_cursor: ViModeCursor::new(grid.cursor.pos),
             semantic_escape_chars,
+            smart_selection: false,
+            reveal_concealed: false,
+            trim_trailing_whitespace: false,
+            join_wrapped_lines: true,
+            watch_rules: Vec::new(),
+            scrolled_lines: 0,
+            monitor_alert: None,
+            progress: Progress::default(),
+            remote_host: None,
+            unicode_version: None,
             selection: None,
             grid,
             inactive_grid: alt,
-            active_charset: CharsetIndex::default(),
+            single_shift: None,
             scroll_region,
+            margins: Column(0)..Column(cols),
             event_proxy,
-            colors,
+            colors: TermColors::default(),
             title: String::from(""),
             tabs: TabStops::new(cols),
-            mode: Mode::SHOW_CURSOR
-                | Mode::LINE_WRAP
-                | Mode::ALTERNATE_SCROLL
-                | Mode::URGENCY_HINTS,
+            mode,
+            shared_mode: Arc::new(AtomicU32::new(mode.bits())),
+            shared_snapshot: SharedSnapshot::default(),
+            parsed_bytes: Arc::new(AtomicU64::new(0)),
             damage: TermDamageState::new(cols, rows),
             default_cursor_shape: cursor_shape,
             cursor_shape,
@@ -415,11 +488,36 @@ impl<U: EventListener> Crosswords<U> {
         self.damage.is_fully_damaged = true;
     }
 
-    #[allow(dead_code)]
     pub fn reset_damage(&mut self) {
         self.damage.reset(self.grid.columns());
     }
 
+    /// Damage since the previous call, it starts again undamaged. When the
+    /// cursor moved, the cells it left and moved to are damaged as well.
+    pub fn take_damage(&mut self) -> TermDamage {
+        let cursor = self.grid.cursor.pos;
+        let last_cursor = std::mem::replace(&mut self.damage.last_cursor, cursor);
+        if last_cursor != cursor {
+            self.damage.damage_point(last_cursor);
+            self.damage_cursor();
+        }
+
+        let damage = if self.damage.is_fully_damaged {
+            TermDamage::Full
+        } else {
+            TermDamage::Partial(
+                self.damage
+                    .lines
+                    .iter()
+                    .filter(|line| line.is_damaged())
+                    .copied()
+                    .collect(),
+            )
+        };
+        self.reset_damage();
+        damage
+    }
+
     pub fn display_offset(&mut self) -> usize {
         self.grid.display_offset()
     }
@@ -428,6 +526,12 @@ impl<U: EventListener> Crosswords<U> {
         self.clear_screen(ClearMode::Saved);
     }
 
+    /// Same as `CSI H CSI 2 J`, the visible lines are pushed to the history.
+    pub fn clear_viewport(&mut self) {
+        self.goto(Line(0), Column(0));
+        self.clear_screen(ClearMode::All);
+    }
+
     #[inline]
     pub fn scroll_display(&mut self, scroll: Scroll) {
         let old_display_offset = self.grid.display_offset();
@@ -455,7 +559,7 @@ impl<U: EventListener> Crosswords<U> {
         self.grid.bottommost_line()
     }
 
-    pub fn colors(&self) -> List {
+    pub fn colors(&self) -> TermColors {
         self.colors
     }
 
@@ -509,8 +613,9 @@ impl<U: EventListener> Crosswords<U> {
         self.vi_mode_cursor.pos.col =
             std::cmp::min(vi_pos.pos.col, self.grid.last_column());
 
-        // Reset scrolling region.
+        // Reset scrolling region and margins.
         self.scroll_region = Line(0)..Line(self.grid.screen_lines() as i32);
+        self.margins = Column(0)..Column(self.grid.columns());
 
         // Resize damage information.
         self.damage.resize(num_cols, num_lines);
@@ -626,11 +731,181 @@ impl<U: EventListener> Crosswords<U> {
         pos
     }
 
+    /// Whether the shell integration reported the cursor is at a command prompt.
+    #[inline]
+    pub fn is_at_prompt(&self) -> bool {
+        self.grid
+            .cursor
+            .template
+            .flags
+            .contains(square::Flags::COMMAND_INPUT)
+    }
+
+    /// Lines where a shell prompt starts (OSC 133), from the oldest one.
+    pub fn prompt_lines(&self) -> Vec<Line> {
+        let mut lines = Vec::new();
+        let mut previous_has_prompt = false;
+        for line in self.grid.topmost_line().0..=self.grid.bottommost_line().0 {
+            let line = Line(line);
+            let has_prompt = self.grid[line]
+                .inner
+                .iter()
+                .any(|square| square.flags.contains(square::Flags::PROMPT));
+            if has_prompt && !previous_has_prompt {
+                lines.push(line);
+            }
+            previous_has_prompt = has_prompt;
+        }
+        lines
+    }
+
+    /// Scroll the display so `line` is at the top of the viewport, or as
+    /// close as it can be.
+    pub fn scroll_to_line(&mut self, line: Line) {
+        let display_offset = std::cmp::max(-line.0, 0);
+        let delta = display_offset - self.grid.display_offset() as i32;
+        self.scroll_display(Scroll::Delta(delta));
+    }
+
+    /// Scroll to the prompt above, or below, the top of the viewport.
+    /// Returns false when there is none.
+    pub fn scroll_to_prompt(&mut self, next: bool) -> bool {
+        let top = Line(-(self.grid.display_offset() as i32));
+        let prompts = self.prompt_lines();
+        let target = if next {
+            prompts.into_iter().find(|line| *line > top)
+        } else {
+            prompts.into_iter().rev().find(|line| *line < top)
+        };
+
+        match target {
+            Some(line) => {
+                self.scroll_to_line(line);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Find the lines holding the output of the most recent command, based on
+    /// the semantic zones reported by the shell integration.
+    pub fn last_command_output(&self) -> Option<(Pos, Pos)> {
+        let has_zone = |line: Line| {
+            self.grid[line]
+                .inner
+                .iter()
+                .any(|square| square.flags.intersects(square::Flags::SEMANTIC_ZONES))
+        };
+
+        let topmost_line = self.grid.topmost_line();
+        let mut line = self.grid.cursor.pos.row;
+
+        // Skip the current prompt.
+        while line >= topmost_line && has_zone(line) {
+            line -= 1;
+        }
+
+        let end = line;
+        while line >= topmost_line && !has_zone(line) {
+            line -= 1;
+        }
+
+        // Output is only valid when preceded by the prompt that produced it.
+        if line < topmost_line || end <= line {
+            return None;
+        }
+
+        Some((
+            Pos::new(line + 1, Column(0)),
+            Pos::new(end, self.grid.last_column()),
+        ))
+    }
+
+    /// Text typed in the prompt of the most recent command.
+    pub fn last_command(&self) -> Option<String> {
+        let mut iter = self.grid.iter_from(self.grid.cursor.pos);
+        let mut command = Vec::new();
+
+        // Skip the current prompt and then the output until the command input.
+        let mut cell = iter.prev();
+        while cell
+            .as_ref()
+            .is_some_and(|square| square.flags.intersects(square::Flags::SEMANTIC_ZONES))
+        {
+            cell = iter.prev();
+        }
+        while cell
+            .as_ref()
+            .is_some_and(|square| !square.flags.contains(square::Flags::COMMAND_INPUT))
+        {
+            cell = iter.prev();
+        }
+
+        while let Some(square) = cell {
+            if !square.flags.contains(square::Flags::COMMAND_INPUT) {
+                break;
+            }
+
+            if !square.flags.intersects(
+                square::Flags::WIDE_CHAR_SPACER | square::Flags::LEADING_WIDE_CHAR_SPACER,
+            ) {
+                command.push(square.c);
+            }
+            cell = iter.prev();
+        }
+
+        let command: String = command.into_iter().rev().collect();
+        let command = command.trim();
+        if command.is_empty() {
+            return None;
+        }
+
+        Some(command.to_owned())
+    }
+
+    /// Trigger the notify and run watch rules matching the cursor line.
+    fn trigger_watch_rules(&mut self) {
+        if self
+            .watch_rules
+            .iter()
+            .all(|rule| rule.action == WatchAction::Highlight)
+        {
+            return;
+        }
+
+        let line = self.scrolled_lines + self.grid.cursor.pos.row.0 as u64;
+        let text = watch::line_text(&self.grid[self.grid.cursor.pos.row]);
+        for rule in self.watch_rules.iter_mut() {
+            if rule.last_fired_line == Some(line) || !rule.regex.is_match(&text) {
+                continue;
+            }
+
+            let event = match rule.action {
+                WatchAction::Highlight => continue,
+                WatchAction::Notify => RioEvent::RequestAttention,
+                WatchAction::Run => RioEvent::RunCommand(rule.command.to_owned()),
+            };
+            rule.last_fired_line = Some(line);
+            self.event_proxy.send_event(event, self.window_id);
+        }
+    }
+
     #[inline]
     pub fn semantic_escape_chars(&self) -> &str {
         &self.semantic_escape_chars
     }
 
+    /// Compress the scrollback far from the viewport, or unpack it.
+    pub fn set_compress_scrollback(&mut self, compress: bool) {
+        self.grid.set_compression(compress);
+        self.inactive_grid.set_compression(compress);
+    }
+
+    #[inline]
+    pub fn set_semantic_escape_chars(&mut self, semantic_escape_chars: &str) {
+        self.semantic_escape_chars = semantic_escape_chars.to_owned();
+    }
+
     #[inline]
     pub fn wrapline(&mut self) {
         if !self.mode.contains(Mode::LINE_WRAP) {
@@ -642,6 +917,7 @@ impl<U: EventListener> Crosswords<U> {
             .flags
             .insert(square::Flags::WRAPLINE);
 
+        let col = self.line_start();
         if self.grid.cursor.pos.row + 1 >= self.scroll_region.end {
             self.linefeed();
         } else {
@@ -649,7 +925,7 @@ impl<U: EventListener> Crosswords<U> {
             self.grid.cursor.pos.row += 1;
         }
 
-        self.grid.cursor.pos.col = Column(0);
+        self.grid.cursor.pos.col = col;
         self.grid.cursor.should_wrap = false;
         self.damage_cursor();
     }
@@ -660,6 +936,26 @@ impl<U: EventListener> Crosswords<U> {
             .saturating_sub(self.grid.screen_lines())
     }
 
+    /// Bytes taken by the scrollback of both grids.
+    pub fn scrollback_memory(&self) -> usize {
+        self.grid.history_memory() + self.inactive_grid.history_memory()
+    }
+
+    /// Drop the oldest scrollback lines until `bytes` are freed, returns the
+    /// bytes freed.
+    pub fn evict_scrollback(&mut self, bytes: usize) -> usize {
+        let offset = self.grid.display_offset();
+        let freed = self.grid.evict_history(bytes);
+        let freed = freed
+            + self
+                .inactive_grid
+                .evict_history(bytes.saturating_sub(freed));
+        if self.grid.display_offset() != offset {
+            self.mark_fully_damaged();
+        }
+        freed
+    }
+
     #[inline]
     fn damage_cursor(&mut self) {
         // The normal cursor coordinates are always in viewport.
@@ -682,6 +978,11 @@ impl<U: EventListener> Crosswords<U> {
 
         let region = origin..self.scroll_region.end;
 
+        if self.has_horizontal_margins() {
+            self.scroll_margins_down(region, lines);
+            return;
+        }
+
         // Scroll selection.
         self.selection = self
             .selection
@@ -710,6 +1011,15 @@ impl<U: EventListener> Crosswords<U> {
 
         let region = origin..self.scroll_region.end;
 
+        if self.has_horizontal_margins() {
+            self.scroll_margins_up(region, lines);
+            return;
+        }
+
+        if origin == Line(0) {
+            self.scrolled_lines += lines as u64;
+        }
+
         // Scroll selection.
         self.selection = self
             .selection
@@ -732,6 +1042,234 @@ impl<U: EventListener> Crosswords<U> {
         self.mark_fully_damaged();
     }
 
+    /// Whether DECSLRM narrowed the margins, scrolling then only moves the
+    /// cells between them.
+    #[inline]
+    fn has_horizontal_margins(&self) -> bool {
+        self.margins.start > 0 || self.margins.end < self.grid.columns()
+    }
+
+    #[inline]
+    fn cursor_in_margins(&self) -> bool {
+        self.margins.contains(&self.grid.cursor.pos.col)
+    }
+
+    /// Column where a carriage return or a wrap moves the cursor to.
+    #[inline]
+    fn line_start(&self) -> Column {
+        if self.grid.cursor.pos.col >= self.margins.start {
+            self.margins.start
+        } else {
+            Column(0)
+        }
+    }
+
+    /// Move the cursor to an absolute position already within bounds.
+    #[inline]
+    fn set_cursor(&mut self, line: Line, col: Column) {
+        self.damage_cursor();
+        self.grid.cursor.pos.row = line;
+        self.grid.cursor.pos.col = col;
+        self.damage_cursor();
+        self.grid.cursor.should_wrap = false;
+    }
+
+    /// Line `lines` above the cursor, stopping at the top of the scrolling region
+    /// when the cursor is inside it.
+    #[inline]
+    fn line_above(&self, lines: usize) -> Line {
+        let row = self.grid.cursor.pos.row;
+        let top = if row >= self.scroll_region.start {
+            self.scroll_region.start
+        } else {
+            Line(0)
+        };
+        std::cmp::max(row - lines, top)
+    }
+
+    /// Line `lines` below the cursor, stopping at the bottom of the scrolling
+    /// region when the cursor is inside it.
+    #[inline]
+    fn line_below(&self, lines: usize) -> Line {
+        let row = self.grid.cursor.pos.row;
+        let bottom = if row < self.scroll_region.end {
+            self.scroll_region.end - 1
+        } else {
+            self.grid.bottommost_line()
+        };
+        std::cmp::min(row + lines, bottom)
+    }
+
+    /// Column after the last one the cursor can write to, the right margin when
+    /// the cursor is inside the margins.
+    #[inline]
+    /// DECRPM reply for a mode, 1 when set, 2 when reset and 0 when the mode
+    /// is not recognized.
+    fn mode_report(&self, private: bool, number: u16) -> String {
+        let intermediate = if private { Some(&b'?') } else { None };
+        let flag = match AnsiMode::from_primitive(intermediate, number) {
+            Some(AnsiMode::CursorKeys) => Some(Mode::APP_CURSOR),
+            Some(AnsiMode::Insert) => Some(Mode::INSERT),
+            Some(AnsiMode::ReverseVideo) => Some(Mode::REVERSE_VIDEO),
+            Some(AnsiMode::Origin) => Some(Mode::ORIGIN),
+            Some(AnsiMode::LineWrap) => Some(Mode::LINE_WRAP),
+            Some(AnsiMode::ReverseWrap) => Some(Mode::REVERSE_WRAP),
+            Some(AnsiMode::ApplicationKeypad) => Some(Mode::APP_KEYPAD),
+            Some(AnsiMode::LeftRightMargin) => Some(Mode::LEFT_RIGHT_MARGIN),
+            Some(AnsiMode::LineFeedNewLine) => Some(Mode::LINE_FEED_NEW_LINE),
+            Some(AnsiMode::ShowCursor) => Some(Mode::SHOW_CURSOR),
+            Some(AnsiMode::ReportMouseClicks) => Some(Mode::MOUSE_REPORT_CLICK),
+            Some(AnsiMode::ReportSquareMouseMotion) => Some(Mode::MOUSE_DRAG),
+            Some(AnsiMode::ReportAllMouseMotion) => Some(Mode::MOUSE_MOTION),
+            Some(AnsiMode::ReportFocusInOut) => Some(Mode::FOCUS_IN_OUT),
+            Some(AnsiMode::Utf8Mouse) => Some(Mode::UTF8_MOUSE),
+            Some(AnsiMode::SgrMouse) => Some(Mode::SGR_MOUSE),
+            Some(AnsiMode::AlternateScroll) => Some(Mode::ALTERNATE_SCROLL),
+            Some(AnsiMode::UrgencyHints) => Some(Mode::URGENCY_HINTS),
+            Some(AnsiMode::SwapScreenAndSetRestoreCursor) => Some(Mode::ALT_SCREEN),
+            Some(AnsiMode::BracketedPaste) => Some(Mode::BRACKETED_PASTE),
+            Some(AnsiMode::GraphemeClustering) => Some(Mode::GRAPHEME_CLUSTERING),
+            // 132 columns are never used.
+            Some(AnsiMode::Column) => Some(Mode::NONE),
+            Some(AnsiMode::BlinkingCursor) | None => None,
+        };
+
+        let state = match flag {
+            Some(flag) if !flag.is_empty() && self.mode.contains(flag) => 1,
+            Some(_) => 2,
+            None => 0,
+        };
+        let prefix = if private { "?" } else { "" };
+        format!("\x1b[{prefix}{number};{state}$y")
+    }
+
+    /// Square holding the last character written before the cursor, on the
+    /// cursor line.
+    fn previous_square(&self) -> Option<Pos> {
+        let mut pos = self.grid.cursor.pos;
+        if !self.grid.cursor.should_wrap {
+            if pos.col == 0 {
+                return None;
+            }
+            pos.col -= 1;
+        }
+
+        if self.grid[pos.row][pos.col]
+            .flags
+            .contains(square::Flags::WIDE_CHAR_SPACER)
+        {
+            pos.col = Column(pos.col.saturating_sub(1));
+        }
+        Some(pos)
+    }
+
+    /// With grapheme clustering, add `c` to the emoji written before the
+    /// cursor when it continues its cluster. Returns false when `c` starts a
+    /// new one.
+    fn extend_grapheme_cluster(&mut self, c: char) -> bool {
+        let pos = match self.previous_square() {
+            Some(pos) => pos,
+            None => return false,
+        };
+
+        let square = &self.grid[pos.row][pos.col];
+        let is_wide = square.flags.contains(square::Flags::WIDE_CHAR);
+        let last = square
+            .zerowidth()
+            .and_then(|zerowidth| zerowidth.last())
+            .copied()
+            .unwrap_or(square.c);
+        let widens = c == EMOJI_PRESENTATION_SELECTOR
+            || (is_regional_indicator(c)
+                && is_regional_indicator(square.c)
+                && square.zerowidth().is_none());
+        let joins =
+            last == ZERO_WIDTH_JOINER || widens || (is_emoji_modifier(c) && is_wide);
+        if !joins {
+            return false;
+        }
+
+        self.grid[pos.row][pos.col].push_zerowidth(c);
+        if widens && !is_wide {
+            self.widen_square(pos);
+        }
+        true
+    }
+
+    /// Make the narrow square right before the cursor wide, the cursor moves
+    /// over its spacer.
+    fn widen_square(&mut self, pos: Pos) {
+        let columns = self.line_end().0;
+        if self.grid.cursor.should_wrap || self.grid.cursor.pos.col != pos.col + 1 {
+            return;
+        }
+
+        self.grid[pos.row][pos.col]
+            .flags
+            .insert(square::Flags::WIDE_CHAR);
+        self.grid
+            .cursor
+            .template
+            .flags
+            .insert(square::Flags::WIDE_CHAR_SPACER);
+        self.write_at_cursor(' ');
+        self.grid
+            .cursor
+            .template
+            .flags
+            .remove(square::Flags::WIDE_CHAR_SPACER);
+
+        if self.grid.cursor.pos.col + 1 < columns {
+            self.grid.cursor.pos.col += 1;
+        } else if self.mode.contains(Mode::LINE_WRAP) {
+            self.grid.cursor.should_wrap = true;
+        }
+    }
+
+    fn line_end(&self) -> Column {
+        if self.grid.cursor.pos.col < self.margins.end {
+            self.margins.end
+        } else {
+            Column(self.grid.columns())
+        }
+    }
+
+    fn scroll_margins_up(&mut self, region: Range<Line>, lines: usize) {
+        let lines = std::cmp::min(lines, (region.end - region.start).0 as usize) as i32;
+        for line in region.start.0..region.end.0 - lines {
+            for col in self.margins.start.0..self.margins.end.0 {
+                self.grid[Line(line)][Column(col)] =
+                    self.grid[Line(line + lines)][Column(col)].clone();
+            }
+        }
+        self.clear_margins(region.end - lines..region.end);
+    }
+
+    fn scroll_margins_down(&mut self, region: Range<Line>, lines: usize) {
+        let lines = std::cmp::min(lines, (region.end - region.start).0 as usize) as i32;
+        for line in (region.start.0 + lines..region.end.0).rev() {
+            for col in self.margins.start.0..self.margins.end.0 {
+                self.grid[Line(line)][Column(col)] =
+                    self.grid[Line(line - lines)][Column(col)].clone();
+            }
+        }
+        self.clear_margins(region.start..region.start + lines);
+    }
+
+    fn clear_margins(&mut self, lines: Range<Line>) {
+        let bg = self.grid.cursor.template.bg;
+        let margins = self.margins.clone();
+        for line in lines.start.0..lines.end.0 {
+            for cell in &mut self.grid[Line(line)][margins.clone()] {
+                *cell = bg.into();
+            }
+        }
+
+        let range = lines.start..=lines.end - 1;
+        self.selection = self.selection.take().filter(|s| !s.intersects_range(range));
+        self.mark_fully_damaged();
+    }
+
     #[inline]
     pub fn bracket_search(&self, point: Pos) -> Option<Pos> {
         let start_char = self.grid[point].c;
@@ -833,7 +1371,6 @@ impl<U: EventListener> Crosswords<U> {
 
     #[inline(always)]
     pub fn write_at_cursor(&mut self, c: char) {
-        let c = self.grid.cursor.charsets[self.active_charset].map(c);
         let fg = self.grid.cursor.template.fg;
         let bg = self.grid.cursor.template.bg;
         let flags = self.grid.cursor.template.flags;
@@ -897,7 +1434,9 @@ impl<U: EventListener> Crosswords<U> {
         U: EventListener,
     {
         // Setting 132 column font makes no sense, but run the other side effects.
-        // Clear scrolling region.
+        // Clear scrolling region and margins.
+        self.margins = Column(0)..Column(self.grid.columns());
+        self.mode.remove(Mode::LEFT_RIGHT_MARGIN);
         self.set_scrolling_region(1, None);
 
         // Clear grid.
@@ -909,6 +1448,57 @@ impl<U: EventListener> Crosswords<U> {
         self.mode
     }
 
+    /// Handle to the terminal mode that can be read without the terminal lock.
+    #[inline]
+    pub fn shared_mode(&self) -> Arc<AtomicU32> {
+        self.shared_mode.clone()
+    }
+
+    #[inline]
+    pub fn shared_snapshot(&self) -> SharedSnapshot {
+        self.shared_snapshot.clone()
+    }
+
+    /// Handle to the count of bytes parsed, for the throughput of the stats.
+    #[inline]
+    pub fn shared_parsed_bytes(&self) -> Arc<AtomicU64> {
+        self.parsed_bytes.clone()
+    }
+
+    #[inline]
+    pub fn count_parsed_bytes(&self, bytes: usize) {
+        self.parsed_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Copy of what is drawn from the terminal.
+    pub fn snapshot(&mut self) -> Snapshot {
+        Snapshot {
+            rows: self.visible_rows(),
+            cursor: self.cursor(),
+            display_offset: self.display_offset(),
+            new_lines: self.grid.new_lines(),
+            blinking_cursor: self.blinking_cursor,
+            reverse_video: self.mode.contains(Mode::REVERSE_VIDEO),
+            colors: self.colors,
+            remote_host: self.remote_host.is_some(),
+            parsed_bytes: self.parsed_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Take a snapshot and make it the one drawn while the terminal is locked.
+    #[inline]
+    pub fn publish_snapshot(&mut self) -> Arc<Snapshot> {
+        let snapshot = Arc::new(self.snapshot());
+        self.shared_snapshot.store(snapshot.clone());
+        snapshot
+    }
+
+    /// Update the shared copy of the terminal mode.
+    #[inline]
+    pub fn publish_mode(&self) {
+        self.shared_mode.store(self.mode.bits(), Ordering::Relaxed);
+    }
+
     #[inline]
     pub fn cursor(&mut self) -> CursorState {
         let mut content = self.cursor_shape;
@@ -977,39 +1567,109 @@ impl<U: EventListener> Crosswords<U> {
         let selection_range = self.selection.as_ref().and_then(|s| s.to_range(self))?;
         let SelectionRange { start, end, .. } = selection_range;
 
-        let mut res = String::new();
-
-        match self.selection.as_ref() {
+        let res = match self.selection.as_ref() {
             Some(Selection {
                 ty: SelectionType::Block,
                 ..
             }) => {
-                for line in (start.row.0..end.row.0).map(Line::from) {
-                    res += self
-                        .line_to_string(line, start.col..end.col, start.col.0 != 0)
-                        .trim_end();
-                    res += "\n";
-                }
-
-                res += self
-                    .line_to_string(end.row, start.col..end.col, true)
-                    .trim_end();
+                let rows: Vec<String> = self
+                    .block_cells(start, end)
+                    .into_iter()
+                    .map(|cells| cells.concat())
+                    .collect();
+                rows.join("\n")
             }
             Some(Selection {
                 ty: SelectionType::Lines,
                 ..
-            }) => {
-                res = self.bounds_to_string(start, end) + "\n";
-            }
-            _ => {
-                res = self.bounds_to_string(start, end);
-            }
-        }
+            }) => self.copied_text(start, end) + "\n",
+            _ => self.copied_text(start, end),
+        };
 
         Some(res)
     }
 
+    /// Selection as tab separated values, the columns are split where every
+    /// line of the selection is blank.
+    pub fn selection_to_tsv(&self) -> Option<String> {
+        let SelectionRange {
+            start,
+            end,
+            is_block,
+        } = self.selection.as_ref().and_then(|s| s.to_range(self))?;
+
+        let rows = if is_block {
+            self.block_cells(start, end)
+        } else {
+            self.bounds_to_string(start, end)
+                .lines()
+                .map(|line| {
+                    line.chars()
+                        .map(|c| if c == '\t' { ' ' } else { c }.to_string())
+                        .collect()
+                })
+                .collect()
+        };
+        Some(tsv(&rows))
+    }
+
+    /// Text of each cell of a block, one row per line. Every cell gives one
+    /// column of text, the second half of wide chars is empty and what does
+    /// not fit in the block is blank, so the rows stay aligned.
+    fn block_cells(&self, start: Pos, end: Pos) -> Vec<Vec<String>> {
+        (start.row.0..=end.row.0)
+            .map(|line| {
+                let grid_line = &self.grid[Line(line)];
+                (start.col.0..=end.col.0)
+                    .map(|column| {
+                        let cell = &grid_line[Column(column)];
+                        if cell.flags.contains(square::Flags::WIDE_CHAR_SPACER) {
+                            return if column == start.col.0 {
+                                String::from(" ")
+                            } else {
+                                String::new()
+                            };
+                        }
+                        if cell.flags.contains(square::Flags::LEADING_WIDE_CHAR_SPACER)
+                            || (cell.flags.contains(square::Flags::WIDE_CHAR)
+                                && column == end.col.0)
+                            || (cell.flags.contains(square::Flags::HIDDEN)
+                                && !self.reveal_concealed)
+                            || cell.c == '\t'
+                        {
+                            return String::from(" ");
+                        }
+
+                        let mut text = String::from(cell.c);
+                        text.extend(cell.zerowidth().into_iter().flatten());
+                        text
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Text between `start` and `end` as copied, following the
+    /// `trim_trailing_whitespace` and `join_wrapped_lines` options.
+    fn copied_text(&self, start: Pos, end: Pos) -> String {
+        let text = self.text_between(start, end, self.join_wrapped_lines);
+        if !self.trim_trailing_whitespace {
+            return text;
+        }
+
+        text.split('\n')
+            .map(|line| line.trim_end_matches([' ', '\t']))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     pub fn bounds_to_string(&self, start: Pos, end: Pos) -> String {
+        self.text_between(start, end, true)
+    }
+
+    /// Text between `start` and `end`, rows that wrapped are joined with the
+    /// next one when `join_wrapped` is set and end with a newline otherwise.
+    fn text_between(&self, start: Pos, end: Pos, join_wrapped: bool) -> String {
         let mut res = String::new();
 
         for line in (start.row.0..=end.row.0).map(Line::from) {
@@ -1025,11 +1685,27 @@ impl<U: EventListener> Crosswords<U> {
             };
 
             res += &self.line_to_string(line, start_col..end_col, line == end.row);
+            if !join_wrapped && line != end.row && !res.ends_with('\n') {
+                res.push('\n');
+            }
         }
 
         res.strip_suffix('\n').map(str::to_owned).unwrap_or(res)
     }
 
+    /// Index of the line holding `line` in the text of the whole grid, as
+    /// written by `bounds_to_string`, where wrapped rows are joined.
+    pub fn text_line(&self, line: Line) -> usize {
+        let last_column = self.grid.last_column();
+        (self.grid.topmost_line().0..line.0)
+            .filter(|row| {
+                !self.grid[Line(*row)][last_column]
+                    .flags
+                    .contains(square::Flags::WRAPLINE)
+            })
+            .count()
+    }
+
     /// Convert a single line in the grid to a String.
     fn line_to_string(
         &self,
@@ -1067,7 +1743,9 @@ impl<U: EventListener> Crosswords<U> {
                 tab_mode = true;
             }
 
-            if !cell.flags.intersects(
+            if cell.flags.contains(square::Flags::HIDDEN) && !self.reveal_concealed {
+                text.push(' ');
+            } else if !cell.flags.intersects(
                 square::Flags::WIDE_CHAR_SPACER | square::Flags::LEADING_WIDE_CHAR_SPACER,
             ) {
                 // Push cells primary character.
@@ -1103,31 +1781,117 @@ impl<U: EventListener> Crosswords<U> {
         text
     }
 
-    #[inline]
-    fn set_keyboard_mode(&mut self, mode: Mode, apply: KeyboardModesApplyBehavior) {
-        // println!("{:?}", mode);
-        let active_mode = self.mode & Mode::KEYBOARD_PROTOCOL;
-        self.mode &= !Mode::KEYBOARD_PROTOCOL;
-        let new_mode = match apply {
-            KeyboardModesApplyBehavior::Replace => mode,
-            KeyboardModesApplyBehavior::Union => active_mode.union(mode),
-            KeyboardModesApplyBehavior::Difference => active_mode.difference(mode),
-        };
-        info!("Setting keyboard mode to {new_mode:?}");
-        self.mode |= new_mode;
-    }
-
-    /// Find the beginning of the current line across linewraps.
-    pub fn row_search_left(&self, mut point: Pos) -> Pos {
-        while point.row > self.grid.topmost_line()
-            && self.grid[point.row - 1i32][self.grid.last_column()]
-                .flags
-                .contains(square::Flags::WRAPLINE)
-        {
-            point.row -= 1;
-        }
+    /// Collect the selected squares with their attributes, one entry per line
+    /// of output. Wrapped lines are joined and wide char spacers are skipped.
+    pub fn selection_to_squares(&self) -> Option<Vec<Vec<Square>>> {
+        let SelectionRange {
+            start,
+            end,
+            is_block,
+        } = self.selection.as_ref().and_then(|s| s.to_range(self))?;
 
-        point.col = Column(0);
+        let spacers =
+            square::Flags::WIDE_CHAR_SPACER | square::Flags::LEADING_WIDE_CHAR_SPACER;
+        let mut lines = vec![Vec::new()];
+        for line in (start.row.0..=end.row.0).map(Line::from) {
+            let start_col = if is_block || line == start.row {
+                start.col
+            } else {
+                Column(0)
+            };
+            let end_col = if is_block || line == end.row {
+                end.col
+            } else {
+                self.grid.last_column()
+            };
+
+            let grid_line = &self.grid[line];
+            let line_length = std::cmp::min(grid_line.line_length(), end_col + 1);
+            if let Some(squares) = lines.last_mut() {
+                for column in (start_col.0..line_length.0).map(Column::from) {
+                    if !grid_line[column].flags.intersects(spacers) {
+                        let mut square = grid_line[column].clone();
+                        if square.flags.contains(square::Flags::HIDDEN) {
+                            if self.reveal_concealed {
+                                square.flags.remove(square::Flags::HIDDEN);
+                            } else {
+                                square.c = ' ';
+                            }
+                        }
+                        squares.push(square);
+                    }
+                }
+            }
+
+            let is_wrapped = grid_line[self.grid.last_column()]
+                .flags
+                .contains(square::Flags::WRAPLINE);
+            if line != end.row && (is_block || !is_wrapped) {
+                lines.push(Vec::new());
+            }
+        }
+
+        Some(lines)
+    }
+
+    /// Convert the selection to text with SGR escape sequences preserving
+    /// colors and text attributes.
+    pub fn selection_to_ansi(&self) -> Option<String> {
+        let lines = self.selection_to_squares()?;
+
+        let mut res = String::new();
+        for (index, squares) in lines.iter().enumerate() {
+            if index > 0 {
+                res.push('\n');
+            }
+
+            let mut current = String::new();
+            for square in squares {
+                let sgr = square_to_sgr(square);
+                if sgr != current {
+                    res += &format!("\x1b[0{sgr}m");
+                    current = sgr;
+                }
+
+                res.push(square.c);
+                for c in square.zerowidth().into_iter().flatten() {
+                    res.push(*c);
+                }
+            }
+
+            if !current.is_empty() {
+                res += "\x1b[0m";
+            }
+        }
+
+        Some(res)
+    }
+
+    #[inline]
+    fn set_keyboard_mode(&mut self, mode: Mode, apply: KeyboardModesApplyBehavior) {
+        // println!("{:?}", mode);
+        let active_mode = self.mode & Mode::KEYBOARD_PROTOCOL;
+        self.mode &= !Mode::KEYBOARD_PROTOCOL;
+        let new_mode = match apply {
+            KeyboardModesApplyBehavior::Replace => mode,
+            KeyboardModesApplyBehavior::Union => active_mode.union(mode),
+            KeyboardModesApplyBehavior::Difference => active_mode.difference(mode),
+        };
+        info!("Setting keyboard mode to {new_mode:?}");
+        self.mode |= new_mode;
+    }
+
+    /// Find the beginning of the current line across linewraps.
+    pub fn row_search_left(&self, mut point: Pos) -> Pos {
+        while point.row > self.grid.topmost_line()
+            && self.grid[point.row - 1i32][self.grid.last_column()]
+                .flags
+                .contains(square::Flags::WRAPLINE)
+        {
+            point.row -= 1;
+        }
+
+        point.col = Column(0);
 
         point
     }
@@ -1160,6 +1924,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
             }
             AnsiMode::ShowCursor => self.mode.insert(Mode::SHOW_CURSOR),
             AnsiMode::CursorKeys => self.mode.insert(Mode::APP_CURSOR),
+            AnsiMode::ApplicationKeypad => self.mode.insert(Mode::APP_KEYPAD),
             // Mouse protocols are mutually exclusive.
             AnsiMode::ReportMouseClicks => {
                 self.mode.remove(Mode::MOUSE_MODE);
@@ -1193,9 +1958,19 @@ impl<U: EventListener> Handler for Crosswords<U> {
             AnsiMode::AlternateScroll => self.mode.insert(Mode::ALTERNATE_SCROLL),
             AnsiMode::LineWrap => self.mode.insert(Mode::LINE_WRAP),
             AnsiMode::LineFeedNewLine => self.mode.insert(Mode::LINE_FEED_NEW_LINE),
-            AnsiMode::Origin => self.mode.insert(Mode::ORIGIN),
+            AnsiMode::Origin => {
+                self.mode.insert(Mode::ORIGIN);
+                self.goto(Line(0), Column(0));
+            }
+            AnsiMode::LeftRightMargin => self.mode.insert(Mode::LEFT_RIGHT_MARGIN),
+            AnsiMode::ReverseWrap => self.mode.insert(Mode::REVERSE_WRAP),
+            AnsiMode::ReverseVideo => {
+                self.mode.insert(Mode::REVERSE_VIDEO);
+                self.mark_fully_damaged();
+            }
             AnsiMode::Column => self.deccolm(),
             AnsiMode::Insert => self.mode.insert(Mode::INSERT),
+            AnsiMode::GraphemeClustering => self.mode.insert(Mode::GRAPHEME_CLUSTERING),
             AnsiMode::BlinkingCursor => {
                 self.blinking_cursor = true;
                 self.event_proxy
@@ -1237,6 +2012,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
             }
             AnsiMode::ShowCursor => self.mode.remove(Mode::SHOW_CURSOR),
             AnsiMode::CursorKeys => self.mode.remove(Mode::APP_CURSOR),
+            AnsiMode::ApplicationKeypad => self.mode.remove(Mode::APP_KEYPAD),
             AnsiMode::ReportMouseClicks => {
                 self.mode.remove(Mode::MOUSE_REPORT_CLICK);
                 self.event_proxy
@@ -1259,12 +2035,25 @@ impl<U: EventListener> Handler for Crosswords<U> {
             AnsiMode::AlternateScroll => self.mode.remove(Mode::ALTERNATE_SCROLL),
             AnsiMode::LineWrap => self.mode.remove(Mode::LINE_WRAP),
             AnsiMode::LineFeedNewLine => self.mode.remove(Mode::LINE_FEED_NEW_LINE),
-            AnsiMode::Origin => self.mode.remove(Mode::ORIGIN),
+            AnsiMode::Origin => {
+                self.mode.remove(Mode::ORIGIN);
+                self.goto(Line(0), Column(0));
+            }
+            AnsiMode::ReverseWrap => self.mode.remove(Mode::REVERSE_WRAP),
+            AnsiMode::ReverseVideo => {
+                self.mode.remove(Mode::REVERSE_VIDEO);
+                self.mark_fully_damaged();
+            }
+            AnsiMode::LeftRightMargin => {
+                self.mode.remove(Mode::LEFT_RIGHT_MARGIN);
+                self.margins = Column(0)..Column(self.grid.columns());
+            }
             AnsiMode::Column => self.deccolm(),
             AnsiMode::Insert => {
                 self.mode.remove(Mode::INSERT);
                 self.mark_fully_damaged();
             }
+            AnsiMode::GraphemeClustering => self.mode.remove(Mode::GRAPHEME_CLUSTERING),
             AnsiMode::BlinkingCursor => {
                 // TODO: Update it
                 // self.blinking_cursor = false;
@@ -1281,24 +2070,32 @@ impl<U: EventListener> Handler for Crosswords<U> {
         } else {
             (Line(0), self.grid.bottommost_line())
         };
+        let (x_offset, max_x) = if self.mode.contains(Mode::ORIGIN) {
+            (self.margins.start, self.margins.end - 1)
+        } else {
+            (Column(0), self.grid.last_column())
+        };
 
-        self.damage_cursor();
-        self.grid.cursor.pos.row =
-            std::cmp::max(std::cmp::min(line + y_offset, max_y), Line(0));
-        self.grid.cursor.pos.col = std::cmp::min(col, self.grid.last_column());
-        self.damage_cursor();
-        self.grid.cursor.should_wrap = false;
+        self.set_cursor(
+            std::cmp::max(std::cmp::min(line + y_offset, max_y), Line(0)),
+            std::cmp::min(col + x_offset, max_x),
+        );
     }
 
     #[inline]
     fn set_active_charset(&mut self, index: CharsetIndex) {
-        self.active_charset = index;
+        self.grid.cursor.active_charset = index;
+    }
+
+    #[inline]
+    fn single_shift(&mut self, index: CharsetIndex) {
+        self.single_shift = Some(index);
     }
 
     #[inline]
     fn move_forward(&mut self, cols: Column) {
         let last_column =
-            std::cmp::min(self.grid.cursor.pos.col + cols, self.grid.last_column());
+            std::cmp::min(self.grid.cursor.pos.col + cols, self.line_end() - 1);
 
         let cursor_line = self.grid.cursor.pos.row.0 as usize;
         self.damage
@@ -1310,7 +2107,10 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline]
     fn move_backward(&mut self, cols: Column) {
-        let column = self.grid.cursor.pos.col.saturating_sub(cols.0);
+        let column = std::cmp::max(
+            self.grid.cursor.pos.col.saturating_sub(cols.0),
+            self.line_start().0,
+        );
 
         let cursor_line = self.grid.cursor.pos.row.0 as usize;
         self.damage
@@ -1325,9 +2125,11 @@ impl<U: EventListener> Handler for Crosswords<U> {
         self.damage_cursor();
 
         let old_col = self.grid.cursor.pos.col.0;
+        let start = self.line_start();
         for _ in 0..count {
-            let mut col = self.grid.cursor.pos.col;
-            for i in (0..(col.0)).rev() {
+            // Stop at the left margin when there is no tab stop before it.
+            let mut col = start;
+            for i in (start.0..self.grid.cursor.pos.col.0).rev() {
                 if self.tabs[Column(i)] {
                     col = Column(i);
                     break;
@@ -1335,6 +2137,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
             }
             self.grid.cursor.pos.col = col;
         }
+        self.grid.cursor.should_wrap = false;
 
         let line = self.grid.cursor.pos.row.0 as usize;
         self.damage
@@ -1343,12 +2146,26 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline]
     fn goto_line(&mut self, line: Line) {
-        self.goto(line, self.grid.cursor.pos.col)
+        let (y_offset, max_y) = if self.mode.contains(Mode::ORIGIN) {
+            (self.scroll_region.start, self.scroll_region.end - 1)
+        } else {
+            (Line(0), self.grid.bottommost_line())
+        };
+
+        let line = std::cmp::max(std::cmp::min(line + y_offset, max_y), Line(0));
+        self.set_cursor(line, self.grid.cursor.pos.col);
     }
 
     #[inline]
     fn goto_col(&mut self, col: Column) {
-        self.goto(self.grid.cursor.pos.row, col)
+        let (x_offset, max_x) = if self.mode.contains(Mode::ORIGIN) {
+            (self.margins.start, self.margins.end - 1)
+        } else {
+            (Column(0), self.grid.last_column())
+        };
+
+        let col = std::cmp::min(col + x_offset, max_x);
+        self.set_cursor(self.grid.cursor.pos.row, col);
     }
 
     #[inline]
@@ -1366,22 +2183,26 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline]
     fn move_up(&mut self, rows: usize) {
-        self.goto(self.grid.cursor.pos.row - rows, self.grid.cursor.pos.col)
+        let line = self.line_above(rows);
+        self.set_cursor(line, self.grid.cursor.pos.col)
     }
 
     #[inline]
     fn move_down(&mut self, rows: usize) {
-        self.goto(self.grid.cursor.pos.row + rows, self.grid.cursor.pos.col)
+        let line = self.line_below(rows);
+        self.set_cursor(line, self.grid.cursor.pos.col)
     }
 
     #[inline]
     fn move_down_and_cr(&mut self, rows: usize) {
-        self.goto(self.grid.cursor.pos.row + rows, Column(0))
+        let line = self.line_below(rows);
+        self.set_cursor(line, self.line_start())
     }
 
     #[inline]
     fn move_up_and_cr(&mut self, lines: usize) {
-        self.goto(self.grid.cursor.pos.row - lines, Column(0))
+        let line = self.line_above(lines);
+        self.set_cursor(line, self.line_start())
     }
 
     #[inline]
@@ -1395,8 +2216,9 @@ impl<U: EventListener> Handler for Crosswords<U> {
         let origin = self.grid.cursor.pos.row;
         let lines = std::cmp::min(self.grid.screen_lines() - origin.0 as usize, lines);
 
-        if lines > 0 && self.scroll_region.contains(&origin) {
+        if lines > 0 && self.scroll_region.contains(&origin) && self.cursor_in_margins() {
             self.scroll_up_relative(origin, lines);
+            self.carriage_return();
         }
     }
 
@@ -1444,30 +2266,28 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline]
     fn delete_chars(&mut self, count: usize) {
-        let columns = self.grid.columns();
-        let cursor = &self.grid.cursor;
-        let bg = cursor.template.bg;
+        if !self.cursor_in_margins() {
+            return;
+        }
 
-        // Ensure deleting within terminal bounds.
-        let count = std::cmp::min(count, columns);
+        let bg = self.grid.cursor.template.bg;
+        let start = self.grid.cursor.pos.col.0;
+        let end = self.margins.end.0;
 
-        let start = cursor.pos.col.0;
-        let end = std::cmp::min(start + count, columns - 1);
-        let num_cells = columns - end;
+        // Ensure deleting within the margins.
+        let count = std::cmp::min(count, end - start);
 
-        let line = cursor.pos.row;
+        let line = self.grid.cursor.pos.row;
         self.damage
-            .damage_line(line.0 as usize, 0, self.grid.columns() - 1);
+            .damage_line(line.0 as usize, start, self.grid.columns() - 1);
         let row = &mut self.grid[line][..];
 
-        for offset in 0..num_cells {
-            row.swap(start + offset, end + offset);
+        for col in start..end - count {
+            row.swap(col, col + count);
         }
 
-        // Clear last `count` cells in the row. If deleting 1 char, need to delete
-        // 1 cell.
-        let end = columns - count;
-        for cell in &mut row[end..] {
+        // Clear last `count` cells before the right margin.
+        for cell in &mut row[end - count..end] {
             *cell = bg.into();
         }
     }
@@ -1481,45 +2301,52 @@ impl<U: EventListener> Handler for Crosswords<U> {
     #[inline]
     fn insert_blank_lines(&mut self, lines: usize) {
         let origin = self.grid.cursor.pos.row;
-        if self.scroll_region.contains(&origin) {
+        if self.scroll_region.contains(&origin) && self.cursor_in_margins() {
             self.scroll_down_relative(origin, lines);
+            self.carriage_return();
         }
     }
 
     #[inline]
     fn insert_blank(&mut self, count: usize) {
-        let cursor = &self.grid.cursor;
-        let bg = cursor.template.bg;
+        if !self.cursor_in_margins() {
+            return;
+        }
 
-        // Ensure inserting within terminal bounds
-        let count = std::cmp::min(count, self.grid.columns() - cursor.pos.col.0);
+        let bg = self.grid.cursor.template.bg;
+        let source = self.grid.cursor.pos.col.0;
+        let end = self.margins.end.0;
 
-        let source = cursor.pos.col;
-        let destination = cursor.pos.col.0 + count;
-        let num_cells = self.grid.columns() - destination;
+        // Ensure inserting within the margins.
+        let count = std::cmp::min(count, end - source);
+        let destination = source + count;
 
-        let line = cursor.pos.row;
+        let line = self.grid.cursor.pos.row;
         self.damage
-            .damage_line(line.0 as usize, 0, self.grid.columns() - 1);
+            .damage_line(line.0 as usize, source, self.grid.columns() - 1);
 
         let row = &mut self.grid[line][..];
 
-        for offset in (0..num_cells).rev() {
-            row.swap(destination + offset, source.0 + offset);
+        for offset in (0..end - destination).rev() {
+            row.swap(destination + offset, source + offset);
         }
 
-        // Squares were just moved out toward the end of the line;
+        // Squares were just moved out toward the right margin;
         // fill in between source and dest with blanks.
-        for cell in &mut row[source.0..destination] {
+        for cell in &mut row[source..destination] {
             *cell = bg.into();
         }
     }
 
     #[inline]
     fn reverse_index(&mut self) {
+        self.grid.cursor.should_wrap = false;
+
         // If cursor is at the top.
         if self.grid.cursor.pos.row == self.scroll_region.start {
-            self.scroll_down(1);
+            if self.cursor_in_margins() {
+                self.scroll_down(1);
+            }
         } else {
             self.damage_cursor();
             self.grid.cursor.pos.row =
@@ -1533,16 +2360,19 @@ impl<U: EventListener> Handler for Crosswords<U> {
         if self.mode.contains(Mode::ALT_SCREEN) {
             std::mem::swap(&mut self.grid, &mut self.inactive_grid);
         }
-        self.active_charset = Default::default();
+        self.single_shift = None;
+        self.colors = TermColors::default();
         self.cursor_shape = self.default_cursor_shape;
         self.grid.reset();
         self.inactive_grid.reset();
         self.scroll_region = Line(0)..Line(self.grid.screen_lines() as i32);
+        self.margins = Column(0)..Column(self.grid.columns());
         self.tabs = TabStops::new(self.grid.columns());
         self.title_stack = Vec::new();
         self.keyboard_mode_stack = Vec::new();
         self.title = String::from("");
         self.selection = None;
+        self.set_progress(ProgressState::Hidden, None);
         self.vi_mode_cursor = Default::default();
         self.keyboard_mode_stack = Default::default();
         self.inactive_keyboard_mode_stack = Default::default();
@@ -1556,6 +2386,38 @@ impl<U: EventListener> Handler for Crosswords<U> {
         self.mark_fully_damaged();
     }
 
+    #[inline]
+    fn report_mode(&mut self, private: bool, mode: u16) {
+        let text = self.mode_report(private, mode);
+        self.event_proxy
+            .send_event(RioEvent::PtyWrite(text), self.window_id);
+    }
+
+    #[inline]
+    fn soft_reset(&mut self) {
+        self.mode.insert(Mode::SHOW_CURSOR);
+        self.mode.remove(
+            Mode::INSERT
+                | Mode::ORIGIN
+                | Mode::APP_CURSOR
+                | Mode::APP_KEYPAD
+                | Mode::LEFT_RIGHT_MARGIN
+                | Mode::REVERSE_WRAP,
+        );
+        self.scroll_region = Line(0)..Line(self.grid.screen_lines() as i32);
+        self.margins = Column(0)..Column(self.grid.columns());
+        self.single_shift = None;
+        self.terminal_attribute(Attr::Reset);
+
+        let cursor = &mut self.grid.cursor;
+        cursor.charsets = Default::default();
+        cursor.active_charset = Default::default();
+
+        // The saved cursor goes back to the home position with default state.
+        self.grid.saved_cursor = Default::default();
+        self.damage_cursor();
+    }
+
     #[inline]
     fn terminal_attribute(&mut self, attr: Attr) {
         let cursor = &mut self.grid.cursor;
@@ -1566,7 +2428,8 @@ impl<U: EventListener> Handler for Crosswords<U> {
             Attr::Reset => {
                 cursor.template.fg = AnsiColor::Named(NamedColor::Foreground);
                 cursor.template.bg = AnsiColor::Named(NamedColor::Background);
-                cursor.template.flags = square::Flags::empty();
+                // Semantic zones are driven by the shell integration, not by SGR.
+                cursor.template.flags &= square::Flags::SEMANTIC_ZONES;
                 cursor.template.set_underline_color(None);
             }
             Attr::Reverse => cursor.template.flags.insert(square::Flags::INVERSE),
@@ -1614,11 +2477,12 @@ impl<U: EventListener> Handler for Crosswords<U> {
             }
             Attr::Hidden => cursor.template.flags.insert(square::Flags::HIDDEN),
             Attr::CancelHidden => cursor.template.flags.remove(square::Flags::HIDDEN),
+            Attr::BlinkSlow | Attr::BlinkFast => {
+                cursor.template.flags.insert(square::Flags::BLINK)
+            }
+            Attr::CancelBlink => cursor.template.flags.remove(square::Flags::BLINK),
             Attr::Strike => cursor.template.flags.insert(square::Flags::STRIKEOUT),
             Attr::CancelStrike => cursor.template.flags.remove(square::Flags::STRIKEOUT),
-            _ => {
-                warn!("Term got unhandled attr: {:?}", attr);
-            }
         }
     }
 
@@ -1691,7 +2555,19 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline(never)]
     fn input(&mut self, c: char) {
-        let width = match c.width() {
+        let charset = self
+            .single_shift
+            .take()
+            .unwrap_or(self.grid.cursor.active_charset);
+        let c = self.grid.cursor.charsets[charset].map(c);
+
+        if self.mode.contains(Mode::GRAPHEME_CLUSTERING)
+            && self.extend_grapheme_cluster(c)
+        {
+            return;
+        }
+
+        let width = match width::char_width(c, self.unicode_version) {
             Some(width) => width,
             None => return,
         };
@@ -1721,9 +2597,10 @@ impl<U: EventListener> Handler for Crosswords<U> {
             self.wrapline();
         }
 
-        let columns = self.grid.columns();
-        if self.mode.contains(Mode::INSERT) && self.grid.cursor.pos.col + width < columns
-        {
+        let columns = self.line_end().0;
+        let shifted = self.mode.contains(Mode::INSERT)
+            && self.grid.cursor.pos.col + width < columns;
+        if shifted {
             let line = self.grid.cursor.pos.row;
             let col = self.grid.cursor.pos.col;
             let row = &mut self.grid[line][..];
@@ -1752,8 +2629,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
                         .remove(square::Flags::LEADING_WIDE_CHAR_SPACER);
                     self.wrapline();
                 } else {
-                    // Prevent out of bounds crash when linewrapping is disabled.
-                    self.grid.cursor.should_wrap = true;
+                    // The glyph does not fit and cannot wrap, drop it.
                     return;
                 }
             }
@@ -1785,9 +2661,20 @@ impl<U: EventListener> Handler for Crosswords<U> {
                 .remove(square::Flags::WIDE_CHAR_SPACER);
         }
 
+        // Cells written, up to the end of the line when the rest moved.
+        let column = self.grid.cursor.pos.col.0;
+        let right = if shifted { columns - 1 } else { column };
+        self.damage.damage_line(
+            self.grid.cursor.pos.row.0 as usize,
+            (column + 1).saturating_sub(width),
+            right,
+        );
+
         if self.grid.cursor.pos.col + 1 < columns {
             self.grid.cursor.pos.col += 1;
-        } else {
+        } else if self.mode.contains(Mode::LINE_WRAP) {
+            // Pending wrap, the cursor stays in the last column until the next
+            // character. Without auto-wrap the next character overwrites it.
             self.grid.cursor.should_wrap = true;
         }
     }
@@ -1890,7 +2777,25 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline]
     fn backspace(&mut self) {
-        if self.grid.cursor.pos.col > Column(0) {
+        let start = self.line_start();
+
+        // Reverse wraparound moves to the end of the previous line.
+        if self.grid.cursor.pos.col == start
+            && self.mode.contains(Mode::REVERSE_WRAP | Mode::LINE_WRAP)
+        {
+            let top = if self.grid.cursor.pos.row >= self.scroll_region.start {
+                self.scroll_region.start
+            } else {
+                Line(0)
+            };
+            if self.grid.cursor.pos.row > top {
+                let col = self.line_end() - 1;
+                self.set_cursor(self.grid.cursor.pos.row - 1, col);
+            }
+            return;
+        }
+
+        if self.grid.cursor.pos.col > start {
             let line = self.grid.cursor.pos.row.0 as usize;
             let column = self.grid.cursor.pos.col.0;
             self.grid.cursor.pos.col -= 1;
@@ -1910,7 +2815,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
                 let cursor = self.grid.cursor.pos;
 
                 // If clearing more than one line.
-                if cursor.row > 1 {
+                if cursor.row > 0 {
                     // Fully clear all lines before the current line.
                     self.grid.reset_region(..cursor.row);
                 }
@@ -1991,9 +2896,15 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline]
     fn linefeed(&mut self) {
+        self.trigger_watch_rules();
+
+        self.grid.cursor.should_wrap = false;
+
         let next = self.grid.cursor.pos.row + 1;
         if next == self.scroll_region.end {
-            self.scroll_up_relative(self.scroll_region.start, 1);
+            if self.cursor_in_margins() {
+                self.scroll_up_relative(self.scroll_region.start, 1);
+            }
         } else if next < self.grid.screen_lines() {
             self.damage_cursor();
             self.grid.cursor.pos.row += 1;
@@ -2011,30 +2922,78 @@ impl<U: EventListener> Handler for Crosswords<U> {
         self.grid.cursor.template.set_hyperlink(hyperlink);
     }
 
+    #[inline]
+    fn prompt_mark(&mut self, mark: PromptMark) {
+        log::trace!("Setting prompt mark {:?}", mark);
+        let flags = &mut self.grid.cursor.template.flags;
+        flags.remove(square::Flags::SEMANTIC_ZONES);
+        match mark {
+            PromptMark::PromptStart => flags.insert(square::Flags::PROMPT),
+            PromptMark::CommandStart => flags.insert(square::Flags::COMMAND_INPUT),
+            PromptMark::CommandExecuted => (),
+            // A program that exited early can leave its progress behind.
+            PromptMark::CommandFinished(_) => {
+                self.set_progress(ProgressState::Hidden, None)
+            }
+        }
+    }
+
+    fn set_progress(&mut self, state: ProgressState, value: Option<u8>) {
+        let value = match state {
+            ProgressState::Hidden | ProgressState::Indeterminate => 0,
+            ProgressState::Normal => value.unwrap_or_default(),
+            ProgressState::Error | ProgressState::Paused => {
+                value.unwrap_or(self.progress.value)
+            }
+        };
+        let progress = Progress { state, value };
+        if progress != self.progress {
+            self.progress = progress;
+            self.event_proxy
+                .send_event(RioEvent::Progress, self.window_id);
+        }
+    }
+
+    fn set_working_directory(&mut self, host: &str, _path: &str) {
+        let is_local = host.is_empty()
+            || host.eq_ignore_ascii_case("localhost")
+            || rio_config::merge::is_current_host(host);
+        self.remote_host = (!is_local).then(|| host.to_owned());
+    }
+
     /// Set the indexed color value.
     #[inline]
-    fn set_color(&mut self, _index: usize, _color: ColorRgb) {
+    fn set_color(&mut self, index: usize, color: ColorRgb) {
+        if index >= colors::term::COUNT {
+            return;
+        }
+
         // Damage terminal if the color changed and it's not the cursor.
-        // if index != NamedColor::Cursor as usize && self.colors[index] != Some(color) {
-        // self.mark_fully_damaged();
-        // }
+        let color = Some(color.to_arr());
+        if index != NamedColor::Cursor as usize && self.colors[index] != color {
+            self.mark_fully_damaged();
+        }
 
-        // self.colors[index] = Some(color);
+        self.colors[index] = color;
     }
 
     #[inline]
-    fn reset_color(&mut self, _index: usize) {
+    fn reset_color(&mut self, index: usize) {
+        if index >= colors::term::COUNT {
+            return;
+        }
+
         // Damage terminal if the color changed and it's not the cursor.
-        // if index != NamedColor::Cursor as usize && self.colors[index].is_some() {
-        // self.mark_fully_damaged();
-        // }
+        if index != NamedColor::Cursor as usize && self.colors[index].is_some() {
+            self.mark_fully_damaged();
+        }
 
-        // self.colors[index] = None;
+        self.colors[index] = None;
     }
 
     #[inline]
     fn bell(&mut self) {
-        warn!("[unimplemented] Bell");
+        self.event_proxy.send_event(RioEvent::Bell, self.window_id);
     }
 
     #[inline]
@@ -2072,17 +3031,18 @@ impl<U: EventListener> Handler for Crosswords<U> {
             return;
         }
 
-        while self.grid.cursor.pos.col < self.grid.columns() && count != 0 {
+        let end = self.line_end();
+        while self.grid.cursor.pos.col < end && count != 0 {
             count -= 1;
 
-            let c = self.grid.cursor.charsets[self.active_charset].map('\t');
+            let c = self.grid.cursor.charsets[self.grid.cursor.active_charset].map('\t');
             let cell = self.grid.cursor_square();
             if cell.c == ' ' {
                 cell.c = c;
             }
 
             loop {
-                if (self.grid.cursor.pos.col + 1) == self.grid.columns() {
+                if (self.grid.cursor.pos.col + 1) == end {
                     break;
                 }
 
@@ -2097,7 +3057,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline]
     fn carriage_return(&mut self) {
-        let new_col = 0;
+        let new_col = self.line_start().0;
         let row = self.grid.cursor.pos.row.0 as usize;
         self.damage
             .damage_line(row, new_col, self.grid.cursor.pos.col.0);
@@ -2107,18 +3067,44 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline]
     fn move_forward_tabs(&mut self, count: u16) {
-        log::trace!("[unimplemented] Moving forward {} tabs", count);
+        self.damage_cursor();
+
+        // Unlike HT, CHT only moves the cursor and leaves the cells untouched.
+        let old_col = self.grid.cursor.pos.col.0;
+        let last = self.line_end() - 1;
+        for _ in 0..count {
+            let mut col = last;
+            for i in self.grid.cursor.pos.col.0 + 1..=last.0 {
+                if self.tabs[Column(i)] {
+                    col = Column(i);
+                    break;
+                }
+            }
+            self.grid.cursor.pos.col = col;
+        }
+        self.grid.cursor.should_wrap = false;
+
+        let line = self.grid.cursor.pos.row.0 as usize;
+        self.damage
+            .damage_line(line, old_col, self.grid.cursor.pos.col.0);
+    }
+
+    #[inline]
+    fn reset_tab_stops(&mut self) {
+        self.tabs = TabStops::new(self.grid.columns());
     }
 
     #[inline]
     fn save_cursor_position(&mut self) {
         self.grid.saved_cursor = self.grid.cursor.clone();
+        self.grid.saved_cursor.origin = self.mode.contains(Mode::ORIGIN);
     }
 
     #[inline]
     fn restore_cursor_position(&mut self) {
         self.damage_cursor();
         self.grid.cursor = self.grid.saved_cursor.clone();
+        self.mode.set(Mode::ORIGIN, self.grid.cursor.origin);
         self.damage_cursor();
     }
 
@@ -2172,6 +3158,26 @@ impl<U: EventListener> Handler for Crosswords<U> {
         self.goto(Line(0), Column(0));
     }
 
+    #[inline]
+    fn set_left_right_margins(&mut self, left: usize, right: Option<usize>) {
+        if !self.mode.contains(Mode::LEFT_RIGHT_MARGIN) {
+            self.save_cursor_position();
+            return;
+        }
+
+        let columns = self.grid.columns();
+        let right = std::cmp::min(right.unwrap_or(columns), columns);
+        if left >= right {
+            warn!("Invalid margins: ({};{})", left, right);
+            return;
+        }
+
+        debug!("Setting margins: ({};{})", left, right);
+
+        self.margins = Column(left - 1)..Column(right);
+        self.goto(Line(0), Column(0));
+    }
+
     #[inline]
     fn text_area_size_pixels(&mut self) {
         debug!("text_area_size_pixels");
@@ -2182,6 +3188,13 @@ impl<U: EventListener> Handler for Crosswords<U> {
         // })));
     }
 
+    /// The event loop treats the current tab as the one running tmux.
+    #[inline]
+    fn tmux_control(&mut self, control: tmux::Control) {
+        self.event_proxy
+            .send_event(RioEvent::TmuxControl(control), self.window_id);
+    }
+
     #[inline]
     fn text_area_size_chars(&mut self) {
         let text = format!(
@@ -2195,6 +3208,100 @@ impl<U: EventListener> Handler for Crosswords<U> {
     }
 }
 
+/// Join the fields of `rows` with tabs. A field spans the columns between
+/// two columns that are blank in every row.
+fn tsv(rows: &[Vec<String>]) -> String {
+    let width = rows.iter().map(Vec::len).max().unwrap_or_default();
+    let is_gutter = |column: usize| {
+        rows.iter()
+            .all(|row| row.get(column).map_or(true, |cell| cell == " "))
+    };
+
+    let mut fields = vec![];
+    let mut field_start = None;
+    for column in 0..=width {
+        match (field_start, column < width && !is_gutter(column)) {
+            (None, true) => field_start = Some(column),
+            (Some(start), false) => {
+                fields.push(start..column);
+                field_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    rows.iter()
+        .map(|row| {
+            fields
+                .iter()
+                .map(|field| {
+                    let end = field.end.min(row.len());
+                    let start = field.start.min(end);
+                    row[start..end].concat().trim().to_string()
+                })
+                .collect::<Vec<_>>()
+                .join("\t")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Build the SGR parameters (prefixed by `;`) describing a square attributes.
+fn square_to_sgr(square: &Square) -> String {
+    let mut sgr = String::new();
+    let flags = square.flags;
+    for (flag, param) in [
+        (square::Flags::BOLD, "1"),
+        (square::Flags::DIM, "2"),
+        (square::Flags::ITALIC, "3"),
+        (square::Flags::UNDERLINE, "4"),
+        (square::Flags::DOUBLE_UNDERLINE, "21"),
+        (square::Flags::UNDERCURL, "4:3"),
+        (square::Flags::DOTTED_UNDERLINE, "4:4"),
+        (square::Flags::DASHED_UNDERLINE, "4:5"),
+        (square::Flags::INVERSE, "7"),
+        (square::Flags::HIDDEN, "8"),
+        (square::Flags::STRIKEOUT, "9"),
+    ] {
+        if flags.contains(flag) {
+            sgr.push(';');
+            sgr.push_str(param);
+        }
+    }
+
+    if let Some(fg) = color_to_sgr(square.fg, 30) {
+        sgr += &fg;
+    }
+    if let Some(bg) = color_to_sgr(square.bg, 40) {
+        sgr += &bg;
+    }
+
+    sgr
+}
+
+/// Convert a color into SGR parameters, `base` is 30 for foreground
+/// and 40 for background.
+fn color_to_sgr(color: AnsiColor, base: usize) -> Option<String> {
+    match color {
+        AnsiColor::Named(named) => {
+            let index = named as usize;
+            let dim_black = NamedColor::DimBlack as usize;
+            match index {
+                0..=7 => Some(format!(";{}", base + index)),
+                8..=15 => Some(format!(";{}", base + 60 + index - 8)),
+                _ if (dim_black..dim_black + 8).contains(&index) => {
+                    Some(format!(";{}", base + index - dim_black))
+                }
+                _ => None,
+            }
+        }
+        AnsiColor::Indexed(index) => Some(format!(";{};5;{}", base + 8, index)),
+        AnsiColor::Spec(rgb) => {
+            Some(format!(";{};2;{};{};{}", base + 8, rgb.r, rgb.g, rgb.b))
+        }
+    }
+}
+
 /// Terminal test helpers.
 #[cfg(test)]
 pub mod test {
@@ -2235,6 +3342,7 @@ mod tests {
     use crate::crosswords::pos::{Column, Line, Pos, Side};
     use crate::crosswords::test::CrosswordsSize;
     use crate::event::VoidListener;
+    use crate::performer::handler::ParserProcessor;
     use winit::window::WindowId;
 
     #[test]
@@ -2486,7 +3594,8 @@ mod tests {
                 Side::Right,
             );
         }
-        assert_eq!(term.selection_to_string(), Some(String::from("\na\na\na")));
+        // Blank lines are padded to the width of the block.
+        assert_eq!(term.selection_to_string(), Some(String::from(" \na\na\na")));
 
         // The first column.
         if let Some(s) = term.selection.as_mut() {
@@ -2500,7 +3609,7 @@ mod tests {
         }
         assert_eq!(
             term.selection_to_string(),
-            Some(String::from("\n\"aa\n\"a\n\"aa"))
+            Some(String::from("   \n\"aa\n\"a \n\"aa"))
         );
 
         // The last column.
@@ -2515,8 +3624,750 @@ mod tests {
         }
         assert_eq!(
             term.selection_to_string(),
-            Some(String::from("\na\"\na\"\na"))
+            Some(String::from("  \na\"\na\"\na "))
+        );
+    }
+
+    #[test]
+    fn copy_options_trim_and_split_wrapped_lines() {
+        let mut term =
+            Crosswords::new(5, 4, CursorShape::Block, VoidListener {}, WindowId::from(0));
+        for c in "abcdefg".chars() {
+            term.input(c);
+        }
+        term.carriage_return();
+        term.linefeed();
+        term.input('x');
+        term.grid[Line(2)][Column(1)].c = '\t';
+
+        let mut selection = Selection::new(
+            SelectionType::Simple,
+            Pos::new(Line(0), Column(0)),
+            Side::Left,
+        );
+        selection.update(Pos::new(Line(2), Column(4)), Side::Right);
+        term.selection = Some(selection);
+        assert_eq!(
+            term.selection_to_string(),
+            Some(String::from("abcdefg\nx\t"))
+        );
+
+        term.trim_trailing_whitespace = true;
+        assert_eq!(term.selection_to_string(), Some(String::from("abcdefg\nx")));
+
+        term.join_wrapped_lines = false;
+        assert_eq!(
+            term.selection_to_string(),
+            Some(String::from("abcde\nfg\nx"))
+        );
+
+        // Other text of the terminal is left as it is.
+        assert_eq!(
+            term.bounds_to_string(
+                Pos::new(Line(0), Column(0)),
+                Pos::new(Line(2), Column(4))
+            ),
+            "abcdefg\nx\t"
+        );
+    }
+
+    #[test]
+    fn block_selection_keeps_wide_chars_aligned() {
+        let mut term =
+            Crosswords::new(5, 3, CursorShape::Block, VoidListener {}, WindowId::from(0));
+        for c in "漢xy".chars() {
+            term.input(c);
+        }
+        term.carriage_return();
+        term.linefeed();
+        for c in "abcd".chars() {
+            term.input(c);
+        }
+
+        let mut selection = Selection::new(
+            SelectionType::Block,
+            Pos::new(Line(0), Column(0)),
+            Side::Left,
+        );
+        selection.update(Pos::new(Line(1), Column(2)), Side::Right);
+        term.selection = Some(selection);
+        assert_eq!(term.selection_to_string(), Some(String::from("漢x\nabc")));
+
+        // Halves of wide chars are left blank.
+        let mut selection = Selection::new(
+            SelectionType::Block,
+            Pos::new(Line(0), Column(1)),
+            Side::Left,
+        );
+        selection.update(Pos::new(Line(1), Column(3)), Side::Right);
+        term.selection = Some(selection);
+        assert_eq!(term.selection_to_string(), Some(String::from(" xy\nbcd")));
+    }
+
+    #[test]
+    fn tsv_selection_splits_columns() {
+        let mut term = Crosswords::new(
+            12,
+            3,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        for c in "ab  1   x".chars() {
+            term.input(c);
+        }
+        term.carriage_return();
+        term.linefeed();
+        for c in "c   22  yy".chars() {
+            term.input(c);
+        }
+
+        let mut selection = Selection::new(
+            SelectionType::Block,
+            Pos::new(Line(0), Column(0)),
+            Side::Left,
+        );
+        selection.update(Pos::new(Line(1), Column(11)), Side::Right);
+        term.selection = Some(selection);
+        assert_eq!(
+            term.selection_to_tsv(),
+            Some(String::from("ab\t1\tx\nc\t22\tyy"))
+        );
+
+        let mut selection = Selection::new(
+            SelectionType::Simple,
+            Pos::new(Line(0), Column(0)),
+            Side::Left,
+        );
+        selection.update(Pos::new(Line(1), Column(11)), Side::Right);
+        term.selection = Some(selection);
+        assert_eq!(
+            term.selection_to_tsv(),
+            Some(String::from("ab\t1\tx\nc\t22\tyy"))
+        );
+    }
+
+    #[test]
+    fn ansi_selection_works() {
+        let size = CrosswordsSize::new(5, 2);
+        let mut term = Crosswords::new(
+            size.columns,
+            size.screen_lines,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        let grid = &mut term.grid;
+        for i in 0..5 {
+            grid[Line(0)][Column(i)].c = 'a';
+        }
+        grid[Line(0)][Column(1)].flags.insert(square::Flags::BOLD);
+        grid[Line(0)][Column(1)].fg = AnsiColor::Named(NamedColor::Red);
+        grid[Line(0)][Column(2)].bg = AnsiColor::Indexed(120);
+        grid[Line(1)][Column(0)].c = 'b';
+        grid[Line(1)][Column(0)].fg = AnsiColor::Spec(ColorRgb { r: 1, g: 2, b: 3 });
+
+        term.selection = Some(Selection::new(
+            SelectionType::Lines,
+            Pos {
+                row: Line(0),
+                col: Column(0),
+            },
+            Side::Left,
+        ));
+        if let Some(s) = term.selection.as_mut() {
+            s.update(
+                Pos {
+                    row: Line(1),
+                    col: Column(0),
+                },
+                Side::Right,
+            );
+        }
+
+        assert_eq!(
+            term.selection_to_ansi(),
+            Some(String::from(
+                "a\x1b[0;1;31ma\x1b[0;48;5;120ma\x1b[0maa\n\x1b[0;38;2;1;2;3mb\x1b[0m"
+            ))
+        );
+    }
+
+    #[test]
+    fn concealed_selection() {
+        let size = CrosswordsSize::new(5, 1);
+        let mut term = Crosswords::new(
+            size.columns,
+            size.screen_lines,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        for (i, c) in "abcde".chars().enumerate() {
+            term.grid[Line(0)][Column(i)].c = c;
+        }
+        term.grid[Line(0)][Column(1)]
+            .flags
+            .insert(square::Flags::HIDDEN);
+        term.grid[Line(0)][Column(2)]
+            .flags
+            .insert(square::Flags::HIDDEN);
+
+        term.selection = Some(Selection::new(
+            SelectionType::Lines,
+            Pos {
+                row: Line(0),
+                col: Column(0),
+            },
+            Side::Left,
+        ));
+        assert_eq!(term.selection_to_string(), Some(String::from("a  de\n")));
+        assert_eq!(
+            term.selection_to_ansi(),
+            Some(String::from("a\x1b[0;8m  \x1b[0mde"))
+        );
+
+        term.reveal_concealed = true;
+        assert_eq!(term.selection_to_string(), Some(String::from("abcde\n")));
+        assert_eq!(term.selection_to_ansi(), Some(String::from("abcde")));
+    }
+
+    #[test]
+    fn prompt_marks_track_semantic_zones() {
+        let size = CrosswordsSize::new(10, 2);
+        let mut term = Crosswords::new(
+            size.columns,
+            size.screen_lines,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        assert!(!term.is_at_prompt());
+
+        term.prompt_mark(PromptMark::PromptStart);
+        term.input('$');
+        term.prompt_mark(PromptMark::CommandStart);
+        // SGR reset must not leave the command input zone.
+        term.terminal_attribute(Attr::Reset);
+        term.input('l');
+        assert!(term.is_at_prompt());
+
+        term.prompt_mark(PromptMark::CommandExecuted);
+        term.input('o');
+        assert!(!term.is_at_prompt());
+
+        let row = &term.grid[Line(0)];
+        assert!(row[Column(0)].flags.contains(square::Flags::PROMPT));
+        assert!(row[Column(1)].flags.contains(square::Flags::COMMAND_INPUT));
+        assert!(!row[Column(2)]
+            .flags
+            .intersects(square::Flags::SEMANTIC_ZONES));
+    }
+
+    #[test]
+    fn scroll_to_prompt() {
+        let size = CrosswordsSize::new(10, 3);
+        let mut term = Crosswords::new(
+            size.columns,
+            size.screen_lines,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        assert!(!term.scroll_to_prompt(false));
+
+        // Three commands with two lines of output each.
+        for _ in 0..3 {
+            term.prompt_mark(PromptMark::PromptStart);
+            term.input('$');
+            term.prompt_mark(PromptMark::CommandStart);
+            term.input('l');
+            term.prompt_mark(PromptMark::CommandExecuted);
+            for _ in 0..2 {
+                term.newline();
+                term.carriage_return();
+                term.input('o');
+            }
+            term.newline();
+            term.carriage_return();
+        }
+        term.prompt_mark(PromptMark::PromptStart);
+        term.input('$');
+
+        let prompts = term.prompt_lines();
+        assert_eq!(prompts, vec![Line(-7), Line(-4), Line(-1), Line(2)]);
+
+        assert!(term.scroll_to_prompt(false));
+        assert_eq!(term.grid.display_offset(), 1);
+        assert!(term.scroll_to_prompt(false));
+        assert_eq!(term.grid.display_offset(), 4);
+        assert!(term.scroll_to_prompt(false));
+        assert_eq!(term.grid.display_offset(), 7);
+        assert!(!term.scroll_to_prompt(false));
+
+        assert!(term.scroll_to_prompt(true));
+        assert_eq!(term.grid.display_offset(), 4);
+        term.scroll_to_line(Line(2));
+        assert_eq!(term.grid.display_offset(), 0);
+    }
+
+    #[test]
+    fn publish_snapshot() {
+        let mut term =
+            Crosswords::new(5, 2, CursorShape::Block, VoidListener {}, WindowId::from(0));
+        let shared = term.shared_snapshot();
+        assert!(shared.load().is_none());
+
+        term.input('a');
+        term.publish_snapshot();
+        // Later writes only show up in the next snapshot.
+        term.input('b');
+
+        let snapshot = shared.load().unwrap();
+        assert_eq!(snapshot.rows.len(), 2);
+        assert_eq!(snapshot.rows[0][Column(0)].c, 'a');
+        assert_eq!(snapshot.rows[0][Column(1)].c, ' ');
+        assert_eq!(snapshot.cursor.pos.col, Column(1));
+        assert_eq!(snapshot.display_offset, 0);
+
+        term.publish_snapshot();
+        assert_eq!(shared.load().unwrap().rows[0][Column(1)].c, 'b');
+    }
+
+    #[test]
+    fn last_command_and_output_from_prompt_marks() {
+        let size = CrosswordsSize::new(10, 6);
+        let mut term = Crosswords::new(
+            size.columns,
+            size.screen_lines,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        assert_eq!(term.last_command_output(), None);
+        assert_eq!(term.last_command(), None);
+
+        term.prompt_mark(PromptMark::PromptStart);
+        term.input('$');
+        term.prompt_mark(PromptMark::CommandStart);
+        for c in "ls".chars() {
+            term.input(c);
+        }
+        term.prompt_mark(PromptMark::CommandExecuted);
+        term.carriage_return();
+        term.linefeed();
+        term.input('a');
+        term.carriage_return();
+        term.linefeed();
+        term.input('b');
+        term.carriage_return();
+        term.linefeed();
+        term.prompt_mark(PromptMark::CommandFinished(Some(0)));
+        term.prompt_mark(PromptMark::PromptStart);
+        term.input('$');
+        term.prompt_mark(PromptMark::CommandStart);
+
+        let (start, end) = term.last_command_output().unwrap();
+        assert_eq!(start, Pos::new(Line(1), Column(0)));
+        assert_eq!(end, Pos::new(Line(2), Column(9)));
+        assert_eq!(term.bounds_to_string(start, end), "a\nb");
+        assert_eq!(term.last_command(), Some(String::from("ls")));
+    }
+
+    #[test]
+    fn text_line_joins_wrapped_rows() {
+        let mut term =
+            Crosswords::new(5, 4, CursorShape::Block, VoidListener {}, WindowId::from(0));
+        for c in "abcdefg".chars() {
+            term.input(c);
+        }
+        term.carriage_return();
+        term.linefeed();
+        term.input('x');
+
+        assert_eq!(term.text_line(Line(0)), 0);
+        assert_eq!(term.text_line(Line(1)), 0);
+        assert_eq!(term.text_line(Line(2)), 1);
+        let text = term
+            .bounds_to_string(Pos::new(Line(0), Column(0)), Pos::new(Line(3), Column(4)));
+        assert_eq!(text.lines().nth(1), Some("x"));
+    }
+
+    #[test]
+    fn dynamic_colors() {
+        let mut term = Crosswords::new(
+            10,
+            2,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        let mut parser = ParserProcessor::new();
+        for byte in b"\x1b]11;rgb:ff/80/00\x07\x1b]10;#0000ff\x1b\\" {
+            parser.advance(&mut term, *byte);
+        }
+
+        let colors = term.colors();
+        let orange = ColorRgb {
+            r: 255,
+            g: 128,
+            b: 0,
+        };
+        assert_eq!(colors[NamedColor::Background], Some(orange.to_arr()));
+        assert_eq!(
+            colors[NamedColor::Foreground],
+            Some(ColorRgb { r: 0, g: 0, b: 255 }.to_arr())
+        );
+        assert_eq!(ColorRgb::from_color_arr(orange.to_arr()), orange);
+
+        for byte in b"\x1b]110\x07" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.colors()[NamedColor::Foreground], None);
+
+        term.reset_state();
+        assert_eq!(term.colors()[NamedColor::Background], None);
+    }
+
+    #[test]
+    fn palette_colors() {
+        let new_term = || {
+            Crosswords::new(
+                10,
+                2,
+                CursorShape::Block,
+                VoidListener {},
+                WindowId::from(0),
+            )
+        };
+        let mut term = new_term();
+        let other = new_term();
+        let mut parser = ParserProcessor::new();
+        for byte in b"\x1b]4;1;rgb:01/02/03;200;#040506\x07" {
+            parser.advance(&mut term, *byte);
+        }
+
+        let colors = term.colors();
+        assert_eq!(colors[1], Some(ColorRgb { r: 1, g: 2, b: 3 }.to_arr()));
+        assert_eq!(colors[200], Some(ColorRgb { r: 4, g: 5, b: 6 }.to_arr()));
+        // Palettes belong to each terminal.
+        assert_eq!(other.colors()[1], None);
+
+        for byte in b"\x1b]104;1\x07" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.colors()[1], None);
+        assert!(term.colors()[200].is_some());
+
+        for byte in b"\x1b]104\x07" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.colors()[200], None);
+    }
+
+    #[test]
+    fn shared_mode_is_published() {
+        let size = CrosswordsSize::new(10, 2);
+        let mut term = Crosswords::new(
+            size.columns,
+            size.screen_lines,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        let shared_mode = term.shared_mode();
+        assert_eq!(shared_mode.load(Ordering::Relaxed), term.mode().bits());
+
+        term.set_mode(AnsiMode::BracketedPaste);
+        assert!(
+            !Mode::from_bits_truncate(shared_mode.load(Ordering::Relaxed))
+                .contains(Mode::BRACKETED_PASTE)
+        );
+
+        term.publish_mode();
+        assert!(
+            Mode::from_bits_truncate(shared_mode.load(Ordering::Relaxed))
+                .contains(Mode::BRACKETED_PASTE)
+        );
+    }
+
+    #[test]
+    fn grapheme_clustering() {
+        let mut term = Crosswords::new(
+            10,
+            2,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        let mut parser = ParserProcessor::new();
+        let family = "\u{1F468}\u{200D}\u{1F469}";
+
+        // Without the mode each emoji takes its own cells.
+        for byte in family.as_bytes() {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.grid.cursor.pos.col, Column(4));
+
+        term.reset_state();
+        for byte in b"\x1b[?2027h" {
+            parser.advance(&mut term, *byte);
+        }
+        for byte in family.as_bytes() {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.grid.cursor.pos.col, Column(2));
+        assert_eq!(
+            term.grid[Line(0)][Column(0)].zerowidth(),
+            Some(&['\u{200D}', '\u{1F469}'][..])
+        );
+
+        // Flags and emoji presentation of narrow symbols are wide.
+        for byte in "\u{1F1EB}\u{1F1F7}\u{2764}\u{FE0F}x".as_bytes() {
+            parser.advance(&mut term, *byte);
+        }
+        assert!(term.grid[Line(0)][Column(2)]
+            .flags
+            .contains(square::Flags::WIDE_CHAR));
+        assert!(term.grid[Line(0)][Column(4)]
+            .flags
+            .contains(square::Flags::WIDE_CHAR));
+        assert_eq!(term.grid[Line(0)][Column(6)].c, 'x');
+    }
+
+    #[test]
+    fn mode_report() {
+        let mut term = Crosswords::new(
+            10,
+            2,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        assert_eq!(term.mode_report(true, 2027), "\x1b[?2027;2$y");
+        term.set_mode(AnsiMode::GraphemeClustering);
+        assert_eq!(term.mode_report(true, 2027), "\x1b[?2027;1$y");
+        assert_eq!(term.mode_report(true, 7), "\x1b[?7;1$y");
+        assert_eq!(term.mode_report(false, 4), "\x1b[4;2$y");
+        assert_eq!(term.mode_report(true, 3), "\x1b[?3;2$y");
+        assert_eq!(term.mode_report(true, 9999), "\x1b[?9999;0$y");
+    }
+
+    #[test]
+    fn take_damage() {
+        let mut term = Crosswords::new(
+            10,
+            3,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        let mut parser = ParserProcessor::new();
+        assert_eq!(term.take_damage(), TermDamage::Full);
+        assert_eq!(term.take_damage(), TermDamage::Partial(vec![]));
+
+        for byte in b"ab\r\n  c" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(
+            term.take_damage(),
+            TermDamage::Partial(vec![
+                LineDamageBounds {
+                    line: 0,
+                    left: 0,
+                    right: 2,
+                },
+                LineDamageBounds {
+                    line: 1,
+                    left: 0,
+                    right: 3,
+                },
+            ])
+        );
+
+        for byte in b"\x1b[2J" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.take_damage(), TermDamage::Full);
+    }
+
+    #[test]
+    fn clear_viewport_and_scrollback() {
+        let mut term =
+            Crosswords::new(5, 3, CursorShape::Block, VoidListener {}, WindowId::from(0));
+        let mut parser = ParserProcessor::new();
+        for byte in b"1\r\n2\r\n3\r\n4\r\n5" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.history_size(), 2);
+
+        term.selection = Some(Selection::new(
+            SelectionType::Simple,
+            Pos::new(Line(1), Column(0)),
+            Side::Left,
+        ));
+        term.reset_damage();
+        term.clear_viewport();
+        assert_eq!(term.history_size(), 5);
+        assert_eq!(term.grid[Line(-1)][Column(0)].c, '5');
+        assert_eq!(term.grid.cursor.pos, Pos::new(Line(0), Column(0)));
+        assert!(term.selection.is_none());
+        assert!(term.damage.is_fully_damaged);
+
+        term.scroll_display(Scroll::Delta(2));
+        assert_eq!(term.display_offset(), 2);
+        term.reset_damage();
+        for byte in b"\x1b[3J" {
+            parser.advance(&mut term, *byte);
+        }
+        assert_eq!(term.history_size(), 0);
+        assert_eq!(term.display_offset(), 0);
+        assert!(term.damage.is_fully_damaged);
+    }
+
+    #[test]
+    fn progress_report() {
+        let mut term = Crosswords::new(
+            10,
+            2,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        let mut parser = ParserProcessor::new();
+        let mut report = |term: &mut Crosswords<VoidListener>, sequence: &[u8]| {
+            for byte in sequence {
+                parser.advance(term, *byte);
+            }
+            term.progress
+        };
+
+        let progress = report(&mut term, b"\x1b]9;4;1;42\x07");
+        assert_eq!(
+            progress,
+            Progress {
+                state: ProgressState::Normal,
+                value: 42
+            }
+        );
+        // Error and paused keep the value when none is given.
+        let progress = report(&mut term, b"\x1b]9;4;2\x1b\\");
+        assert_eq!(progress.state, ProgressState::Error);
+        assert_eq!(progress.value, 42);
+        let progress = report(&mut term, b"\x1b]9;4;4;250\x07");
+        assert_eq!(progress.state, ProgressState::Paused);
+        assert_eq!(progress.value, 100);
+        let progress = report(&mut term, b"\x1b]9;4;3\x07");
+        assert_eq!(progress.state, ProgressState::Indeterminate);
+        let progress = report(&mut term, b"\x1b]9;4;7;10\x07");
+        assert_eq!(progress.state, ProgressState::Indeterminate);
+        assert!(!report(&mut term, b"\x1b]9;4;0\x07").is_visible());
+
+        report(&mut term, b"\x1b]9;4;1;5\x07");
+        // The shell reports the command ended.
+        assert!(!report(&mut term, b"\x1b]133;D;0\x07").is_visible());
+    }
+
+    #[test]
+    fn watch_rules_fire_once_per_line() {
+        #[derive(Clone, Default)]
+        struct Commands(std::rc::Rc<std::cell::RefCell<Vec<String>>>);
+
+        impl EventListener for Commands {
+            fn send_event(&self, event: RioEvent, _id: WindowId) {
+                if let RioEvent::RunCommand(command) = event {
+                    self.0.borrow_mut().push(command);
+                }
+            }
+        }
+
+        let commands = Commands::default();
+        let mut term = Crosswords::new(
+            10,
+            3,
+            CursorShape::Block,
+            commands.clone(),
+            WindowId::from(0),
+        );
+        term.watch_rules = watch::compile(&[rio_config::watch::WatchRule {
+            regex: String::from("error"),
+            action: WatchAction::Run,
+            color: [1.0; 4],
+            command: String::from("notify"),
+        }]);
+        let mut parser = ParserProcessor::new();
+        let mut output = |term: &mut Crosswords<Commands>, sequence: &[u8]| {
+            for byte in sequence {
+                parser.advance(term, *byte);
+            }
+            commands.0.borrow().len()
+        };
+
+        assert_eq!(output(&mut term, b"error\r\n"), 1);
+        // The same line redrawn in place.
+        assert_eq!(output(&mut term, b"\x1b[A\rerror\r\n"), 1);
+        assert_eq!(output(&mut term, b"error\r\n"), 2);
+        // Scrolled lines keep their numbers.
+        assert_eq!(output(&mut term, b"ok\r\nerror\r\n"), 3);
+        assert_eq!(output(&mut term, b"\x1b[A\rerror\r\n"), 3);
+    }
+
+    #[test]
+    fn working_directory_host() {
+        let mut term = Crosswords::new(
+            10,
+            2,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        let mut parser = ParserProcessor::new();
+        let mut report = |term: &mut Crosswords<VoidListener>, sequence: &[u8]| {
+            for byte in sequence {
+                parser.advance(term, *byte);
+            }
+            term.remote_host.clone()
+        };
+
+        assert_eq!(
+            report(&mut term, b"\x1b]7;file://build-server.example/home/me\x07"),
+            Some(String::from("build-server.example"))
+        );
+        assert_eq!(report(&mut term, b"\x1b]7;file:///home/me\x1b\\"), None);
+        report(&mut term, b"\x1b]7;file://build-server.example/home/me\x07");
+        assert_eq!(report(&mut term, b"\x1b]7;file://localhost/tmp\x07"), None);
+        // Not a file URL.
+        report(&mut term, b"\x1b]7;file://build-server.example/home/me\x07");
+        assert!(report(&mut term, b"\x1b]7;https://example.com\x07").is_some());
+    }
+
+    #[test]
+    fn keypad_and_cursor_key_modes() {
+        let mut term = Crosswords::new(
+            10,
+            2,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
         );
+        assert_eq!(term.mode_report(true, 66), "\x1b[?66;2$y");
+
+        term.set_mode(AnsiMode::ApplicationKeypad);
+        term.set_mode(AnsiMode::CursorKeys);
+        assert!(term.mode().contains(Mode::APP_KEYPAD | Mode::APP_CURSOR));
+        assert_eq!(term.mode_report(true, 66), "\x1b[?66;1$y");
+        assert_eq!(term.mode_report(true, 1), "\x1b[?1;1$y");
+
+        // DECNKM and DECKPNM share the mode.
+        term.unset_keypad_application_mode();
+        assert_eq!(term.mode_report(true, 66), "\x1b[?66;2$y");
+        term.set_keypad_application_mode();
+        term.unset_mode(AnsiMode::ApplicationKeypad);
+        assert!(!term.mode().contains(Mode::APP_KEYPAD));
+
+        term.set_keypad_application_mode();
+        term.reset_state();
+        assert!(!term.mode().intersects(Mode::APP_KEYPAD | Mode::APP_CURSOR));
     }
 
     #[test]
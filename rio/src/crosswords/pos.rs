@@ -36,6 +36,12 @@ pub struct Cursor<T> {
     /// Currently configured graphic character sets.
     pub charsets: Charsets,
 
+    /// Character set invoked into GL.
+    pub active_charset: CharsetIndex,
+
+    /// Origin mode, only tracked by the saved cursor so DECRC can restore it.
+    pub origin: bool,
+
     /// Tracks if the next call to input will need to first handle wrapping.
     pub should_wrap: bool,
 }
@@ -62,7 +68,10 @@ impl CursorState {
 pub enum StandardCharset {
     #[default]
     Ascii,
+    /// DEC Special Graphics, used to draw boxes.
     SpecialCharacterAndLineDrawing,
+    /// United Kingdom, ASCII with the pound sign in place of `#`.
+    UnitedKingdom,
 }
 
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
@@ -100,6 +109,10 @@ impl StandardCharset {
     pub fn map(self, c: char) -> char {
         match self {
             StandardCharset::Ascii => c,
+            StandardCharset::UnitedKingdom => match c {
+                '#' => '£',
+                _ => c,
+            },
             StandardCharset::SpecialCharacterAndLineDrawing => match c {
                 '_' => ' ',
                 '`' => '◆',
@@ -0,0 +1,46 @@
+// Frames are drawn from a snapshot of the visible part of the terminal. The
+// PTY reader publishes one after every batch it parses, while it still holds
+// the terminal, so a frame never shows a batch half applied. When output keeps
+// the terminal locked the renderer draws the last snapshot instead of waiting
+// for the lock, swapping it only takes a pointer.
+
+use crate::crosswords::grid::row::Row;
+use crate::crosswords::pos::CursorState;
+use crate::crosswords::square::Square;
+use parking_lot::Mutex;
+use rio_config::colors::term::TermColors;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub rows: Vec<Row<Square>>,
+    pub cursor: CursorState,
+    pub display_offset: usize,
+    /// Lines written below the viewport while scrolled up.
+    pub new_lines: usize,
+    pub blinking_cursor: bool,
+    pub reverse_video: bool,
+    pub colors: TermColors,
+    /// Whether the shell reported a working directory on another host.
+    pub remote_host: bool,
+    /// Bytes of output parsed when the snapshot was taken, tells the frames
+    /// showing the echo of some input.
+    pub parsed_bytes: u64,
+}
+
+/// Last snapshot published for a terminal, shared between the PTY reader and
+/// the renderer.
+#[derive(Debug, Clone, Default)]
+pub struct SharedSnapshot(Arc<Mutex<Option<Arc<Snapshot>>>>);
+
+impl SharedSnapshot {
+    #[inline]
+    pub fn store(&self, snapshot: Arc<Snapshot>) {
+        *self.0.lock() = Some(snapshot);
+    }
+
+    #[inline]
+    pub fn load(&self) -> Option<Arc<Snapshot>> {
+        self.0.lock().clone()
+    }
+}
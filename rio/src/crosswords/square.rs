@@ -1,6 +1,7 @@
 // square.rs was originally taken from Alacritty as cell.rs https://github.com/alacritty/alacritty/blob/e35e5ad14fce8456afdd89f2b392b9924bb27471/alacritty_terminal/src/term/cell.rs
 // which is licensed under Apache 2.0 license.
 
+use crate::crosswords::grid::compressed::Compressible;
 use crate::crosswords::grid::GridSquare;
 use crate::crosswords::Column;
 use crate::crosswords::Row;
@@ -10,28 +11,34 @@ use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
 bitflags! {
-    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-    pub struct Flags: u16 {
-        const INVERSE                   = 0b0000_0000_0000_0001;
-        const BOLD                      = 0b0000_0000_0000_0010;
-        const ITALIC                    = 0b0000_0000_0000_0100;
-        const BOLD_ITALIC               = 0b0000_0000_0000_0110;
-        const UNDERLINE                 = 0b0000_0000_0000_1000;
-        const WRAPLINE                  = 0b0000_0000_0001_0000;
-        const WIDE_CHAR                 = 0b0000_0000_0010_0000;
-        const WIDE_CHAR_SPACER          = 0b0000_0000_0100_0000;
-        const DIM                       = 0b0000_0000_1000_0000;
-        const DIM_BOLD                  = 0b0000_0000_1000_0010;
-        const HIDDEN                    = 0b0000_0001_0000_0000;
-        const STRIKEOUT                 = 0b0000_0010_0000_0000;
-        const LEADING_WIDE_CHAR_SPACER  = 0b0000_0100_0000_0000;
-        const DOUBLE_UNDERLINE          = 0b0000_1000_0000_0000;
-        const UNDERCURL                 = 0b0001_0000_0000_0000;
-        const DOTTED_UNDERLINE          = 0b0010_0000_0000_0000;
-        const DASHED_UNDERLINE          = 0b0100_0000_0000_0000;
+    #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+    pub struct Flags: u32 {
+        const INVERSE                   = 0b0000_0000_0000_0000_0001;
+        const BOLD                      = 0b0000_0000_0000_0000_0010;
+        const ITALIC                    = 0b0000_0000_0000_0000_0100;
+        const BOLD_ITALIC               = 0b0000_0000_0000_0000_0110;
+        const UNDERLINE                 = 0b0000_0000_0000_0000_1000;
+        const WRAPLINE                  = 0b0000_0000_0000_0001_0000;
+        const WIDE_CHAR                 = 0b0000_0000_0000_0010_0000;
+        const WIDE_CHAR_SPACER          = 0b0000_0000_0000_0100_0000;
+        const DIM                       = 0b0000_0000_0000_1000_0000;
+        const DIM_BOLD                  = 0b0000_0000_0000_1000_0010;
+        const HIDDEN                    = 0b0000_0000_0001_0000_0000;
+        const STRIKEOUT                 = 0b0000_0000_0010_0000_0000;
+        const LEADING_WIDE_CHAR_SPACER  = 0b0000_0000_0100_0000_0000;
+        const DOUBLE_UNDERLINE          = 0b0000_0000_1000_0000_0000;
+        const UNDERCURL                 = 0b0000_0001_0000_0000_0000;
+        const DOTTED_UNDERLINE          = 0b0000_0010_0000_0000_0000;
+        const DASHED_UNDERLINE          = 0b0000_0100_0000_0000_0000;
         const ALL_UNDERLINES            = Self::UNDERLINE.bits() | Self::DOUBLE_UNDERLINE.bits()
                                         | Self::UNDERCURL.bits() | Self::DOTTED_UNDERLINE.bits()
                                         | Self::DASHED_UNDERLINE.bits();
+        /// Square written as part of the shell prompt (OSC 133).
+        const PROMPT                    = 0b0000_1000_0000_0000_0000;
+        /// Square written as part of the command typed in the prompt (OSC 133).
+        const COMMAND_INPUT             = 0b0001_0000_0000_0000_0000;
+        const SEMANTIC_ZONES            = Self::PROMPT.bits() | Self::COMMAND_INPUT.bits();
+        const BLINK                     = 0b0010_0000_0000_0000_0000;
     }
 }
 
@@ -92,7 +99,7 @@ impl HyperlinkInner {
 /// This storage is reserved for cell attributes which are rarely set. This allows reducing the
 /// allocation required ahead of time for every cell, with some additional overhead when the extra
 /// storage is actually required.
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Hash)]
 pub struct CellExtra {
     zerowidth: Vec<char>,
     underline_color: Option<rio_config::colors::AnsiColor>,
@@ -101,7 +108,7 @@ pub struct CellExtra {
 }
 
 /// Content and attributes of a single cell in the terminal grid.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Square {
     pub c: char,
     pub fg: AnsiColor,
@@ -223,6 +230,22 @@ impl GridSquare for Square {
     }
 }
 
+impl Compressible for Square {
+    #[inline]
+    fn split(&self) -> (char, Self) {
+        let style = Square {
+            c: ' ',
+            ..self.clone()
+        };
+        (self.c, style)
+    }
+
+    #[inline]
+    fn join(c: char, style: &Self) -> Self {
+        Square { c, ..style.clone() }
+    }
+}
+
 pub trait LineLength {
     /// Calculate the occupied line length.
     fn line_length(&self) -> Column;
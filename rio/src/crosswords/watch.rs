@@ -0,0 +1,134 @@
+// Watch rules match terminal output with a regex, the matches can be
+// highlighted when rendered or trigger an action when a line is completed.
+
+use crate::crosswords::grid::row::Row;
+use crate::crosswords::square::{Flags, Square};
+use regex::Regex;
+use rio_config::colors::ColorArray;
+use rio_config::watch::WatchAction;
+
+#[derive(Debug, Clone)]
+pub struct WatchRule {
+    pub regex: Regex,
+    pub action: WatchAction,
+    pub color: ColorArray,
+    pub command: String,
+    /// Line the rule last fired on, counted from the first line of output,
+    /// a line redrawn in place does not fire it again.
+    pub last_fired_line: Option<u64>,
+}
+
+/// Compile the configured rules, skipping (and logging) invalid regexes.
+pub fn compile(rules: &[rio_config::watch::WatchRule]) -> Vec<WatchRule> {
+    rules
+        .iter()
+        .filter_map(|rule| match Regex::new(&rule.regex) {
+            Ok(regex) => Some(WatchRule {
+                regex,
+                action: rule.action,
+                color: rule.color,
+                command: rule.command.to_owned(),
+                last_fired_line: None,
+            }),
+            Err(err) => {
+                log::error!("invalid watch rule regex {:?}: {err}", rule.regex);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Text of the row along with the column of each character byte offset.
+fn row_text(row: &Row<Square>) -> (String, Vec<(usize, usize)>) {
+    let mut text = String::new();
+    let mut offsets = Vec::new();
+    for (column, square) in row.inner.iter().enumerate() {
+        if square
+            .flags
+            .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+        {
+            continue;
+        }
+
+        offsets.push((text.len(), column));
+        text.push(square.c);
+    }
+
+    (text, offsets)
+}
+
+/// Text of the row without trailing blank cells.
+pub fn line_text(row: &Row<Square>) -> String {
+    let (text, _) = row_text(row);
+    text.trim_end().to_owned()
+}
+
+/// Highlight color for each column of the row, last matching rule wins.
+pub fn highlights(rules: &[WatchRule], row: &Row<Square>) -> Vec<Option<ColorArray>> {
+    let mut colors = vec![None; row.len()];
+    let mut text = None;
+
+    for rule in rules
+        .iter()
+        .filter(|rule| rule.action == WatchAction::Highlight)
+    {
+        let (text, offsets) = text.get_or_insert_with(|| row_text(row));
+        for found in rule.regex.find_iter(text) {
+            if found.is_empty() {
+                continue;
+            }
+
+            for (offset, column) in offsets.iter() {
+                if *offset >= found.start() && *offset < found.end() {
+                    colors[*column] = Some(rule.color);
+                }
+            }
+        }
+    }
+
+    colors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(regex: &str, action: WatchAction) -> rio_config::watch::WatchRule {
+        rio_config::watch::WatchRule {
+            regex: regex.to_owned(),
+            action,
+            color: [1.0, 0.0, 0.0, 1.0],
+            command: String::new(),
+        }
+    }
+
+    #[test]
+    fn invalid_rules_are_skipped() {
+        let rules = compile(&[
+            rule("ERROR", WatchAction::Highlight),
+            rule("(", WatchAction::Notify),
+        ]);
+        assert_eq!(rules.len(), 1);
+    }
+
+    #[test]
+    fn highlights_matched_columns() {
+        let rules = compile(&[
+            rule("ERR", WatchAction::Highlight),
+            rule("a", WatchAction::Notify),
+        ]);
+        let mut row: Row<Square> = Row::new(8);
+        for (column, c) in "a ERR b".chars().enumerate() {
+            row.inner[column].c = c;
+        }
+
+        let colors = highlights(&rules, &row);
+        let highlighted: Vec<usize> = colors
+            .iter()
+            .enumerate()
+            .filter_map(|(column, color)| color.map(|_| column))
+            .collect();
+        assert_eq!(highlighted, vec![2, 3, 4]);
+        assert_eq!(line_text(&row), "a ERR b");
+    }
+}
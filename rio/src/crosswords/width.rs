@@ -0,0 +1,162 @@
+// Character widths follow the latest Unicode version by default. Programs on
+// older systems count cells with their own wcwidth tables, where emoji added
+// or made wide since then take a single cell, so the terminal can be told to
+// use the widths of an older version to keep their screens aligned.
+
+use unicode_width::UnicodeWidthChar;
+
+/// Wide characters along with the Unicode version they became wide in,
+/// before it they are narrow or unassigned (drawn narrow).
+const WIDE_SINCE: &[(u8, char, char)] = &[
+    // Emoji with default emoji presentation, neutral before Unicode 9.
+    (9, '\u{231A}', '\u{231B}'),
+    (9, '\u{23E9}', '\u{23EC}'),
+    (9, '\u{23F0}', '\u{23F0}'),
+    (9, '\u{23F3}', '\u{23F3}'),
+    (9, '\u{25FD}', '\u{25FE}'),
+    (9, '\u{2614}', '\u{2615}'),
+    (9, '\u{2648}', '\u{2653}'),
+    (9, '\u{267F}', '\u{267F}'),
+    (9, '\u{2693}', '\u{2693}'),
+    (9, '\u{26A1}', '\u{26A1}'),
+    (9, '\u{26AA}', '\u{26AB}'),
+    (9, '\u{26BD}', '\u{26BE}'),
+    (9, '\u{26C4}', '\u{26C5}'),
+    (9, '\u{26CE}', '\u{26CE}'),
+    (9, '\u{26D4}', '\u{26D4}'),
+    (9, '\u{26EA}', '\u{26EA}'),
+    (9, '\u{26F2}', '\u{26F3}'),
+    (9, '\u{26F5}', '\u{26F5}'),
+    (9, '\u{26FA}', '\u{26FA}'),
+    (9, '\u{26FD}', '\u{26FD}'),
+    (9, '\u{2705}', '\u{2705}'),
+    (9, '\u{270A}', '\u{270B}'),
+    (9, '\u{2728}', '\u{2728}'),
+    (9, '\u{274C}', '\u{274C}'),
+    (9, '\u{274E}', '\u{274E}'),
+    (9, '\u{2753}', '\u{2755}'),
+    (9, '\u{2757}', '\u{2757}'),
+    (9, '\u{2795}', '\u{2797}'),
+    (9, '\u{27B0}', '\u{27B0}'),
+    (9, '\u{27BF}', '\u{27BF}'),
+    (9, '\u{2B1B}', '\u{2B1C}'),
+    (9, '\u{2B50}', '\u{2B50}'),
+    (9, '\u{2B55}', '\u{2B55}'),
+    (9, '\u{1F004}', '\u{1F004}'),
+    (9, '\u{1F0CF}', '\u{1F0CF}'),
+    (9, '\u{1F18E}', '\u{1F18E}'),
+    (9, '\u{1F191}', '\u{1F19A}'),
+    (9, '\u{1F300}', '\u{1F320}'),
+    (9, '\u{1F32D}', '\u{1F335}'),
+    (9, '\u{1F337}', '\u{1F37C}'),
+    (9, '\u{1F37E}', '\u{1F393}'),
+    (9, '\u{1F3A0}', '\u{1F3CA}'),
+    (9, '\u{1F3CF}', '\u{1F3D3}'),
+    (9, '\u{1F3E0}', '\u{1F3F0}'),
+    (9, '\u{1F3F4}', '\u{1F3F4}'),
+    (9, '\u{1F3F8}', '\u{1F43E}'),
+    (9, '\u{1F440}', '\u{1F440}'),
+    (9, '\u{1F442}', '\u{1F4FC}'),
+    (9, '\u{1F4FF}', '\u{1F53D}'),
+    (9, '\u{1F54B}', '\u{1F54E}'),
+    (9, '\u{1F550}', '\u{1F567}'),
+    (9, '\u{1F57A}', '\u{1F57A}'),
+    (9, '\u{1F595}', '\u{1F596}'),
+    (9, '\u{1F5A4}', '\u{1F5A4}'),
+    (9, '\u{1F5FB}', '\u{1F64F}'),
+    (9, '\u{1F680}', '\u{1F6C5}'),
+    (9, '\u{1F6CC}', '\u{1F6CC}'),
+    (9, '\u{1F6D0}', '\u{1F6D2}'),
+    (9, '\u{1F6EB}', '\u{1F6EC}'),
+    (9, '\u{1F6F4}', '\u{1F6F6}'),
+    (9, '\u{1F910}', '\u{1F91E}'),
+    (9, '\u{1F920}', '\u{1F927}'),
+    (9, '\u{1F930}', '\u{1F930}'),
+    (9, '\u{1F933}', '\u{1F93E}'),
+    (9, '\u{1F940}', '\u{1F94B}'),
+    (9, '\u{1F950}', '\u{1F95E}'),
+    (9, '\u{1F980}', '\u{1F991}'),
+    (9, '\u{1F9C0}', '\u{1F9C0}'),
+    // Emoji added since.
+    (10, '\u{1F6F7}', '\u{1F6F8}'),
+    (10, '\u{1F91F}', '\u{1F91F}'),
+    (10, '\u{1F928}', '\u{1F92F}'),
+    (10, '\u{1F931}', '\u{1F932}'),
+    (10, '\u{1F94C}', '\u{1F94C}'),
+    (10, '\u{1F95F}', '\u{1F96B}'),
+    (10, '\u{1F992}', '\u{1F997}'),
+    (10, '\u{1F9D0}', '\u{1F9E6}'),
+    (11, '\u{1F6F9}', '\u{1F6F9}'),
+    (11, '\u{1F94D}', '\u{1F94F}'),
+    (11, '\u{1F96C}', '\u{1F970}'),
+    (11, '\u{1F973}', '\u{1F976}'),
+    (11, '\u{1F97A}', '\u{1F97A}'),
+    (11, '\u{1F97C}', '\u{1F97F}'),
+    (11, '\u{1F998}', '\u{1F9A2}'),
+    (11, '\u{1F9B0}', '\u{1F9B9}'),
+    (11, '\u{1F9C1}', '\u{1F9C2}'),
+    (11, '\u{1F9E7}', '\u{1F9FF}'),
+    (12, '\u{1F6D5}', '\u{1F6D5}'),
+    (12, '\u{1F6FA}', '\u{1F6FA}'),
+    (12, '\u{1F7E0}', '\u{1F7EB}'),
+    (12, '\u{1F90D}', '\u{1F90F}'),
+    (12, '\u{1F93F}', '\u{1F93F}'),
+    (12, '\u{1F971}', '\u{1F971}'),
+    (12, '\u{1F97B}', '\u{1F97B}'),
+    (12, '\u{1F9A5}', '\u{1F9AA}'),
+    (12, '\u{1F9AE}', '\u{1F9AF}'),
+    (12, '\u{1F9BA}', '\u{1F9BF}'),
+    (12, '\u{1F9C3}', '\u{1F9CA}'),
+    (12, '\u{1F9CD}', '\u{1F9CF}'),
+    (12, '\u{1FA70}', '\u{1FA73}'),
+    (12, '\u{1FA78}', '\u{1FA7A}'),
+    (12, '\u{1FA80}', '\u{1FA82}'),
+    (12, '\u{1FA90}', '\u{1FA95}'),
+    (13, '\u{1F6D6}', '\u{1F6D7}'),
+    (13, '\u{1F6FB}', '\u{1F6FC}'),
+    (13, '\u{1F90C}', '\u{1F90C}'),
+    (13, '\u{1F972}', '\u{1F972}'),
+    (13, '\u{1FA96}', '\u{1FAA8}'),
+];
+
+/// Number of cells taken by `c` with the tables of Unicode `version`, the
+/// latest one when it is None.
+#[inline]
+pub fn char_width(c: char, version: Option<u8>) -> Option<usize> {
+    let width = c.width()?;
+    let version = match version {
+        Some(version) if width == 2 => version,
+        _ => return Some(width),
+    };
+
+    let narrow = WIDE_SINCE
+        .iter()
+        .any(|(since, start, end)| version < *since && (*start..=*end).contains(&c));
+    Some(if narrow { 1 } else { width })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_char_width() {
+        // Latest tables.
+        assert_eq!(char_width('a', None), Some(1));
+        assert_eq!(char_width('⌚', None), Some(2));
+        assert_eq!(char_width('🥰', None), Some(2));
+        assert_eq!(char_width('\u{200D}', None), Some(0));
+
+        // Emoji presentation became wide in Unicode 9.
+        assert_eq!(char_width('⌚', Some(8)), Some(1));
+        assert_eq!(char_width('😀', Some(8)), Some(1));
+        assert_eq!(char_width('😀', Some(9)), Some(2));
+        // 🥰 was added in Unicode 11.
+        assert_eq!(char_width('🥰', Some(10)), Some(1));
+        assert_eq!(char_width('🥰', Some(11)), Some(2));
+
+        // East asian wide characters never changed.
+        assert_eq!(char_width('中', Some(8)), Some(2));
+        assert_eq!(char_width('a', Some(8)), Some(1));
+    }
+}
@@ -0,0 +1,254 @@
+// Report printed by `rio --diagnostics` with the GPU adapters, the surface
+// capabilities, the displays and the renderer settings resolved from the
+// config, meant to be attached to rendering bug reports.
+
+use crate::platform::power::{self, is_low_power};
+use rio_config::Config;
+use std::fmt::Write;
+use winit::window::Window;
+
+pub async fn report(window: &Window, config: &Config) -> String {
+    power::check();
+    let mut report = String::new();
+    let _ = writeln!(
+        report,
+        "Rio {} on {} {}",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+
+    report.push_str("\nRenderer settings\n");
+    report.push_str(&settings(config));
+
+    report.push_str("\nDisplays\n");
+    for monitor in window.available_monitors() {
+        let size = monitor.size();
+        let refresh_rate = monitor
+            .refresh_rate_millihertz()
+            .map(|rate| format!("{:.2}Hz", rate as f64 / 1000.))
+            .unwrap_or_else(|| String::from("unknown refresh rate"));
+        let _ = writeln!(
+            report,
+            "  {}: {}x{}, scale {}, {refresh_rate}",
+            monitor.name().unwrap_or_else(|| String::from("unnamed")),
+            size.width,
+            size.height,
+            monitor.scale_factor()
+        );
+    }
+
+    let backends = wgpu::util::backend_bits_from_env().unwrap_or(wgpu::Backends::all());
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends,
+        ..Default::default()
+    });
+
+    report.push_str("\nAdapters\n");
+    for adapter in instance.enumerate_adapters(backends) {
+        let _ = writeln!(report, "  {}", adapter_info(&adapter.get_info()));
+    }
+
+    let surface = match unsafe { instance.create_surface(window) } {
+        Ok(surface) => surface,
+        Err(err) => {
+            let _ = writeln!(report, "\nUnable to create a surface: {err}");
+            return report;
+        }
+    };
+    let power_preference = if is_low_power(config.performance) {
+        wgpu::PowerPreference::LowPower
+    } else {
+        wgpu::PowerPreference::HighPerformance
+    };
+    let adapter = match instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference,
+            compatible_surface: Some(&surface),
+            force_fallback_adapter: false,
+        })
+        .await
+    {
+        Some(adapter) => adapter,
+        None => {
+            report.push_str("\nNo adapter is compatible with the surface\n");
+            return report;
+        }
+    };
+
+    let _ = writeln!(
+        report,
+        "\nSelected adapter ({power_preference:?})\n  {}",
+        adapter_info(&adapter.get_info())
+    );
+
+    let capabilities = surface.get_capabilities(&adapter);
+    let _ = writeln!(report, "\nSurface");
+    let _ = writeln!(report, "  formats: {:?}", capabilities.formats);
+    let _ = writeln!(report, "  present modes: {:?}", capabilities.present_modes);
+    let _ = writeln!(report, "  alpha modes: {:?}", capabilities.alpha_modes);
+
+    let limits = adapter.limits();
+    let _ = writeln!(report, "\nLimits");
+    let _ = writeln!(
+        report,
+        "  max texture dimension 2d: {}",
+        limits.max_texture_dimension_2d
+    );
+    let _ = writeln!(report, "  max buffer size: {}", limits.max_buffer_size);
+    let _ = writeln!(
+        report,
+        "  max uniform buffer binding size: {}",
+        limits.max_uniform_buffer_binding_size
+    );
+    let _ = writeln!(report, "  max bind groups: {}", limits.max_bind_groups);
+    let _ = writeln!(
+        report,
+        "  max vertex buffers: {}",
+        limits.max_vertex_buffers
+    );
+
+    let _ = writeln!(report, "\nFeatures\n  {:?}", adapter.features());
+    let downlevel = adapter.get_downlevel_capabilities();
+    let _ = writeln!(
+        report,
+        "\nDownlevel\n  shader model: {:?}\n  flags: {:?}",
+        downlevel.shader_model, downlevel.flags
+    );
+
+    report
+}
+
+fn adapter_info(info: &wgpu::AdapterInfo) -> String {
+    format!(
+        "{} ({:?}, {:?}, vendor {:#06x}, device {:#06x}, driver {} {})",
+        info.name,
+        info.backend,
+        info.device_type,
+        info.vendor,
+        info.device,
+        info.driver,
+        info.driver_info
+    )
+}
+
+fn settings(config: &Config) -> String {
+    let mut settings = String::new();
+    let _ = writeln!(
+        settings,
+        "  performance: {} (low power: {})",
+        config.performance,
+        is_low_power(config.performance)
+    );
+    let _ = writeln!(settings, "  low latency: {}", config.low_latency);
+    let _ = writeln!(
+        settings,
+        "  color space: {:?}, filter {:?}",
+        config.color_space, config.color_filter
+    );
+    let _ = writeln!(settings, "  render scale: {}", config.render_scale);
+    let _ = writeln!(
+        settings,
+        "  disable unfocused render: {}",
+        config.disable_unfocused_render
+    );
+    let _ = writeln!(
+        settings,
+        "  WGPU_BACKEND: {}",
+        std::env::var("WGPU_BACKEND").unwrap_or_else(|_| String::from("unset"))
+    );
+    let _ = writeln!(
+        settings,
+        "  font: {} {}, line height {}, padding x {}",
+        config.fonts.regular.family,
+        config.fonts.size,
+        config.line_height,
+        config.padding_x
+    );
+    let _ = writeln!(
+        settings,
+        "  window: {}x{}, background opacity {}, decorations {:?}",
+        config.window.width,
+        config.window.height,
+        config.background.opacity,
+        config.window.decorations
+    );
+    let _ = writeln!(settings, "  navigation: {:?}", config.navigation.mode);
+    settings
+}
+
+/// Escape sequences drawing colors, attributes and glyphs that are commonly
+/// rendered wrong, played with `rio --test-pattern`.
+pub fn test_pattern() -> Vec<u8> {
+    let mut pattern = String::from("\x1b[2J\x1b[H");
+
+    pattern.push_str("ANSI colors\r\n");
+    for color in 0..16 {
+        let _ = write!(pattern, "\x1b[48;5;{color}m {color:>2} ");
+    }
+    pattern.push_str("\x1b[0m\r\n\r\n256 colors\r\n");
+    for color in 16..256 {
+        let _ = write!(pattern, "\x1b[48;5;{color}m ");
+        if (color - 16) % 36 == 35 {
+            pattern.push_str("\x1b[0m\r\n");
+        }
+    }
+
+    pattern.push_str("\r\nTrue color\r\n");
+    for step in 0..64 {
+        let value = step * 4;
+        let _ = write!(pattern, "\x1b[48;2;{value};0;{}m ", 255 - value);
+    }
+    pattern.push_str("\x1b[0m\r\n\r\n");
+
+    pattern.push_str(
+        "\x1b[1mbold\x1b[0m \x1b[2mdim\x1b[0m \x1b[3mitalic\x1b[0m \
+         \x1b[4munderline\x1b[0m \x1b[4:3mcurly\x1b[0m \x1b[7minverse\x1b[0m \
+         \x1b[9mstrikethrough\x1b[0m\r\n\r\n",
+    );
+    pattern.push_str("┌─┬─┐ ╔═╦═╗ ▁▂▃▄▅▆▇█ ░▒▓ ⠿⣿\r\n");
+    pattern.push_str("└─┴─┘ ╚═╩═╝ 中文 日本語 한국어 🦀 🎨\r\n");
+    pattern.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::CursorShape;
+    use crate::crosswords::pos::{Column, Line};
+    use crate::crosswords::Crosswords;
+    use crate::event::VoidListener;
+    use crate::performer::playback;
+    use crate::performer::recorder::Cast;
+    use winit::window::WindowId;
+
+    #[test]
+    fn test_settings() {
+        let settings = settings(&Config::default());
+        assert!(settings.contains("  performance: High (low power: false)\n"));
+        assert!(settings.contains("  low latency: false\n"));
+    }
+
+    #[test]
+    fn test_test_pattern() {
+        let mut terminal = Crosswords::new(
+            80,
+            24,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        playback::replay(&Cast::from_raw(&test_pattern()), &mut terminal);
+
+        let line = |line: i32| -> String {
+            (0..80)
+                .map(|column| terminal.grid[Line(line)][Column(column)].c)
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        };
+        assert_eq!(line(0), "ANSI colors");
+        assert_eq!(line(3), "256 colors");
+        assert!(line(14).starts_with("bold dim italic underline"));
+    }
+}
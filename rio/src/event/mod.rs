@@ -2,7 +2,12 @@ pub mod sync;
 
 use crate::clipboard::ClipboardType;
 use crate::crosswords::grid::Scroll;
+use crate::performer::monitor::Monitor;
+use crate::performer::output_log::OutputLog;
+use crate::performer::recorder::Recorder;
+use crate::remote;
 use crate::router::ErrorReport;
+use crate::tmux;
 use rio_config::colors::ColorRgb;
 use std::borrow::Cow;
 use std::fmt::Debug;
@@ -21,6 +26,19 @@ pub enum Msg {
     Shutdown,
 
     Resize(WinsizeBuilder),
+
+    /// Record the PTY output, replacing any previous recording.
+    StartRecording(Recorder),
+
+    StopRecording,
+
+    /// Log the PTY output, replacing any previous log.
+    StartOutputLog(Box<OutputLog>),
+
+    StopOutputLog,
+
+    /// Replace the activity or silence monitor of the terminal.
+    Monitor(Option<Monitor>),
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -35,11 +53,18 @@ pub enum ClickState {
 pub enum RioEvent {
     PrepareRender(u64),
     Render,
+    /// The window stopped being resized, the grids waiting for the new size
+    /// are recomputed.
+    FinishResize,
+    /// Tick of the scrolling of a selection dragged over an edge.
+    SelectionScrolling,
     Scroll(Scroll),
     ToggleFullScreen,
     Minimize(bool),
     Hide,
     HideOtherApplications,
+    /// Show and focus the windows, or hide them when one is focused.
+    ToggleVisibility,
     UpdateConfig,
     CreateWindow,
     CloseWindow,
@@ -52,6 +77,39 @@ pub enum RioEvent {
 
     ReportToAssistant(ErrorReport),
 
+    /// Pasted text is waiting for the user confirmation.
+    ConfirmPaste,
+
+    /// Text is ready to be shown in the built-in pager.
+    OpenPager,
+
+    /// Recent output tokens are ready to be picked.
+    OpenQuickSelect,
+
+    /// The name of the current tab is being edited.
+    OpenRenameTab,
+
+    /// The context menu is shown over the terminal.
+    OpenContextMenu,
+
+    /// Debug view of the glyph texture cache.
+    OpenGlyphAtlas,
+
+    /// An activity or silence monitor of a tab has fired.
+    MonitorAlert,
+
+    /// The progress reported by the program of a tab has changed.
+    Progress,
+
+    /// The current tab started or stopped running an SSH client.
+    RemoteSession(bool),
+
+    /// The system switched between battery and external power.
+    PowerSource,
+
+    /// The reduced motion preference of the system has changed.
+    ReduceMotion,
+
     /// Grid has changed possibly requiring a mouse cursor shape change.
     MouseCursorDirty,
 
@@ -97,6 +155,22 @@ pub enum RioEvent {
     /// Terminal bell ring.
     Bell,
 
+    /// Request the user attention, sent by watch rules.
+    RequestAttention,
+
+    /// Spawn a program (followed by arguments), sent by watch rules.
+    RunCommand(String),
+
+    /// Command received over the remote control socket.
+    RemoteControl(remote::Request),
+
+    /// Data of a tmux control mode session.
+    TmuxControl(tmux::Control),
+
+    /// The compositor focused another workspace or output.
+    #[cfg(all(feature = "wayland-ipc", not(any(target_os = "macos", windows))))]
+    Workspace(crate::platform::compositor::Workspace),
+
     /// Shutdown request.
     Exit,
 }
@@ -114,6 +188,7 @@ impl Debug for RioEvent {
             RioEvent::Title(title) => write!(f, "Title({title})"),
             RioEvent::Minimize(cond) => write!(f, "Minimize({cond})"),
             RioEvent::Hide => write!(f, "Hide)"),
+            RioEvent::ToggleVisibility => write!(f, "ToggleVisibility"),
             RioEvent::HideOtherApplications => write!(f, "HideOtherApplications)"),
             RioEvent::CursorBlinkingChange => write!(f, "CursorBlinkingChange"),
             RioEvent::MouseCursorDirty => write!(f, "MouseCursorDirty"),
@@ -121,8 +196,16 @@ impl Debug for RioEvent {
             RioEvent::Wakeup => write!(f, "Wakeup"),
             RioEvent::PrepareRender(millis) => write!(f, "PrepareRender({millis})"),
             RioEvent::Render => write!(f, "Render"),
+            RioEvent::FinishResize => write!(f, "FinishResize"),
+            RioEvent::SelectionScrolling => write!(f, "SelectionScrolling"),
             RioEvent::Scroll(scroll) => write!(f, "Scroll {scroll:?}"),
             RioEvent::Bell => write!(f, "Bell"),
+            RioEvent::RequestAttention => write!(f, "RequestAttention"),
+            RioEvent::RunCommand(command) => write!(f, "RunCommand({command})"),
+            RioEvent::RemoteControl(request) => write!(f, "RemoteControl({request:?})"),
+            RioEvent::TmuxControl(control) => write!(f, "TmuxControl({control:?})"),
+            #[cfg(all(feature = "wayland-ipc", not(any(target_os = "macos", windows))))]
+            RioEvent::Workspace(workspace) => write!(f, "Workspace({workspace:?})"),
             RioEvent::Exit => write!(f, "Exit"),
             RioEvent::CreateWindow => write!(f, "CreateWindow"),
             RioEvent::CloseWindow => write!(f, "CloseWindow"),
@@ -139,6 +222,17 @@ impl Debug for RioEvent {
                 write!(f, "ReportToAssistant({})", error_report.report)
             }
             RioEvent::ToggleFullScreen => write!(f, "FullScreen"),
+            RioEvent::ConfirmPaste => write!(f, "ConfirmPaste"),
+            RioEvent::OpenPager => write!(f, "OpenPager"),
+            RioEvent::OpenQuickSelect => write!(f, "OpenQuickSelect"),
+            RioEvent::OpenRenameTab => write!(f, "OpenRenameTab"),
+            RioEvent::OpenContextMenu => write!(f, "OpenContextMenu"),
+            RioEvent::OpenGlyphAtlas => write!(f, "OpenGlyphAtlas"),
+            RioEvent::MonitorAlert => write!(f, "MonitorAlert"),
+            RioEvent::Progress => write!(f, "Progress"),
+            RioEvent::RemoteSession(remote) => write!(f, "RemoteSession({remote})"),
+            RioEvent::PowerSource => write!(f, "PowerSource"),
+            RioEvent::ReduceMotion => write!(f, "ReduceMotion"),
         }
     }
 }
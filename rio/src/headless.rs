@@ -0,0 +1,174 @@
+// `rio --headless --screenshot` runs the command, or plays the recording, in a
+// terminal that is never shown and draws it with an offscreen renderer once it
+// is over, so no window or display server is needed. Like `rio bench` there is
+// no event loop, the PTY and the playback are followed from this thread.
+
+use crate::crosswords::pos::CursorState;
+use crate::crosswords::{Crosswords, MIN_COLUMNS, MIN_LINES};
+use crate::event::sync::FairMutex;
+use crate::event::{EventListener, RioEvent};
+use crate::performer::{playback, Machine};
+use crate::screen::constants;
+use crate::screen::messenger::Messenger;
+use crate::screen::state::State;
+use rio_config::Config;
+use std::borrow::Cow;
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::sync::Arc;
+use sugarloaf::font::loader::Database;
+use sugarloaf::layout::SugarloafLayout;
+use sugarloaf::Sugarloaf;
+#[cfg(target_os = "windows")]
+use teletypewriter::create_pty;
+#[cfg(not(target_os = "windows"))]
+use teletypewriter::create_pty_with_spawn;
+use winit::window::WindowId;
+
+/// Forwards the terminal events that need an answer to the headless run.
+#[derive(Clone)]
+struct Listener {
+    sender: mpsc::Sender<RioEvent>,
+}
+
+impl EventListener for Listener {
+    fn send_event(&self, event: RioEvent, _id: WindowId) {
+        if matches!(event, RioEvent::Exit | RioEvent::PtyWrite(_)) {
+            let _ = self.sender.send(event);
+        }
+    }
+}
+
+/// Run the command, or the pending playback, until it is over and save the
+/// terminal to `path`, returning the process exit code.
+pub async fn run(config: Config, path: &str) -> i32 {
+    let layout = SugarloafLayout::new(
+        config.window.width as f32,
+        config.window.height as f32,
+        (config.padding_x, constants::PADDING_Y, 0.),
+        1.,
+        config.fonts.size,
+        config.line_height,
+        (MIN_COLUMNS, MIN_LINES),
+    );
+    let mut font_database = Database::new();
+    font_database.load_system_fonts();
+    let mut sugarloaf = match Sugarloaf::new_headless(
+        wgpu::PowerPreference::HighPerformance,
+        config.fonts.to_owned(),
+        layout,
+        Some(&font_database),
+    )
+    .await
+    {
+        Some(Ok(sugarloaf)) => sugarloaf,
+        Some(Err(with_errors)) => with_errors.instance,
+        None => {
+            eprintln!("no GPU adapter available");
+            return 1;
+        }
+    };
+    let config = Rc::new(config);
+    let mut state = State::new(&config, None);
+    sugarloaf.set_background_color(state.window_background());
+    sugarloaf.calculate_bounds();
+
+    let (columns, lines) = (sugarloaf.layout.columns, sugarloaf.layout.lines);
+    let (sender, receiver) = mpsc::channel();
+    let listener = Listener { sender };
+    let terminal = Arc::new(FairMutex::new(Crosswords::new(
+        columns,
+        lines,
+        CursorState::new(config.cursor).content,
+        listener.clone(),
+        WindowId::from(0),
+    )));
+
+    if let Some(playback) = playback::take_pending() {
+        // Nobody watches the playback, the recording is fed at once.
+        playback::replay(&playback.cast, &mut *terminal.lock());
+    } else if let Err(err) =
+        run_command(&config, &terminal, listener, &receiver, &sugarloaf)
+    {
+        eprintln!("unable to run the command: {err}");
+        return 1;
+    }
+
+    let snapshot = terminal.lock().snapshot();
+    state.set_term_colors(snapshot.colors);
+    state.prepare_rows(
+        &snapshot.rows,
+        snapshot.cursor,
+        &mut sugarloaf,
+        snapshot.display_offset as i32,
+        false,
+    );
+    let frame = match sugarloaf.render_to_image() {
+        Some(frame) => frame,
+        None => {
+            eprintln!("unable to read back the frame");
+            return 1;
+        }
+    };
+    match image::save_buffer(
+        path,
+        &frame.pixels,
+        frame.width,
+        frame.height,
+        image::ColorType::Rgba8,
+    ) {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("unable to save {path}: {err}");
+            1
+        }
+    }
+}
+
+/// Start the configured shell in a PTY sized like the offscreen frame and
+/// wait for it to exit, answering the queries it writes meanwhile.
+fn run_command(
+    config: &Config,
+    terminal: &Arc<FairMutex<Crosswords<Listener>>>,
+    listener: Listener,
+    receiver: &mpsc::Receiver<RioEvent>,
+    sugarloaf: &Sugarloaf,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let layout = &sugarloaf.layout;
+    let (columns, lines) = (layout.columns as u16, layout.lines as u16);
+
+    #[cfg(not(target_os = "windows"))]
+    let pty = create_pty_with_spawn(
+        &Cow::Borrowed(&config.shell.program),
+        config.shell.args.clone(),
+        &[],
+        &config.working_dir,
+        columns,
+        lines,
+    )?;
+    #[cfg(target_os = "windows")]
+    let pty = create_pty(
+        &Cow::Borrowed(&config.shell.program),
+        config.shell.args.clone(),
+        &[],
+        &config.working_dir,
+        columns,
+        lines,
+    );
+
+    let machine = Machine::new(Arc::clone(terminal), pty, listener, WindowId::from(0))?;
+    let mut messenger = Messenger::new(machine.channel());
+    machine.spawn();
+    let _ =
+        messenger.send_resize(layout.width as u16, layout.height as u16, columns, lines);
+
+    // The terminal and the machine hold senders, only the exit ends the wait.
+    while let Ok(event) = receiver.recv() {
+        match event {
+            RioEvent::PtyWrite(text) => messenger.send_bytes(text.into_bytes()),
+            RioEvent::Exit => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -1,6 +1,51 @@
-use log::{Metadata, Record};
+use log::{LevelFilter, Metadata, Record};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-pub struct Logger;
+pub struct Logger {
+    // Logs go to the standard output unless a file is set.
+    file: Mutex<Option<File>>,
+    // Level given by --log-level, it wins over the config.
+    cli_level: Mutex<Option<LevelFilter>>,
+}
+
+static LOGGER: Logger = Logger::new();
+
+impl Logger {
+    pub const fn new() -> Self {
+        Logger {
+            file: Mutex::new(None),
+            cli_level: Mutex::new(None),
+        }
+    }
+
+    fn format(record: &Record, colored: bool) -> String {
+        if colored {
+            return format!(
+                "\x1b[35m[{}]\x1b[0m \x1b[34m{}\x1b[0m {}\0",
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        format!(
+            "{}.{:03} [{}] {} {}",
+            timestamp.as_secs(),
+            timestamp.subsec_millis(),
+            record.level(),
+            record.target(),
+            record.args()
+        )
+    }
+}
 
 impl log::Log for Logger {
     fn enabled(&self, _metadata: &Metadata) -> bool {
@@ -16,14 +61,109 @@ impl log::Log for Logger {
         //     println!("{}", record.level());
         // }
 
-        let line = format!(
-            "\x1b[35m[{}]\x1b[0m \x1b[34m{}\x1b[0m {}\0",
-            record.level(),
-            record.target(),
-            record.args()
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                let _ = writeln!(file, "{}", Logger::format(record, false));
+                return;
+            }
+        }
+
+        println!("{}", Logger::format(record, true));
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+/// Level from the command line, `RIO_LOG_LEVEL` or the config, in this order.
+fn filter_level(config_level: &str, cli_level: Option<LevelFilter>) -> LevelFilter {
+    if let Some(level) = cli_level {
+        return level;
+    }
+
+    let filter_level = LevelFilter::from_str(config_level).unwrap_or(LevelFilter::Off);
+    match std::env::var("RIO_LOG_LEVEL") {
+        Ok(data) if !data.is_empty() => {
+            LevelFilter::from_str(&data).unwrap_or(filter_level)
+        }
+        _ => filter_level,
+    }
+}
+
+/// Install the logger, writing into `file` when given.
+pub fn init(
+    config_level: &str,
+    cli_level: Option<&str>,
+    file: Option<&Path>,
+) -> Result<(), String> {
+    let cli_level = match cli_level {
+        Some(level) => Some(
+            LevelFilter::from_str(level)
+                .map_err(|_| format!("invalid log level `{level}`"))?,
+        ),
+        None => None,
+    };
+
+    if let Some(path) = file {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|err| format!("unable to open {}: {err}", path.display()))?;
+        if let Ok(mut current) = LOGGER.file.lock() {
+            *current = Some(file);
+        }
+    }
+
+    if let Ok(mut current) = LOGGER.cli_level.lock() {
+        *current = cli_level;
+    }
+
+    log::set_logger(&LOGGER).map_err(|err| err.to_string())?;
+    log::set_max_level(filter_level(config_level, cli_level));
+    Ok(())
+}
+
+/// Apply the log level of a reloaded config.
+pub fn set_config_level(config_level: &str) {
+    let cli_level = LOGGER.cli_level.lock().ok().and_then(|level| *level);
+    log::set_max_level(filter_level(config_level, cli_level));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_level() {
+        assert_eq!(
+            filter_level("info", Some(LevelFilter::Trace)),
+            LevelFilter::Trace
         );
-        println!("{line}");
+        if std::env::var_os("RIO_LOG_LEVEL").is_none() {
+            assert_eq!(filter_level("warn", None), LevelFilter::Warn);
+            assert_eq!(filter_level("invalid", None), LevelFilter::Off);
+        }
     }
 
-    fn flush(&self) {}
+    #[test]
+    fn test_format() {
+        let record = Record::builder()
+            .args(format_args!("frame dropped"))
+            .level(log::Level::Warn)
+            .target("sugarloaf")
+            .build();
+
+        assert_eq!(
+            Logger::format(&record, true),
+            "\x1b[35m[WARN]\x1b[0m \x1b[34msugarloaf\x1b[0m frame dropped\0"
+        );
+        let line = Logger::format(&record, false);
+        assert!(line.ends_with(" [WARN] sugarloaf frame dropped"), "{line}");
+    }
 }
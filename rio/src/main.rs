@@ -4,29 +4,37 @@
 // See https://msdn.microsoft.com/en-us/library/4cc7ya5b.aspx for more details.
 #![windows_subsystem = "windows"]
 
+mod accessibility;
 mod ansi;
+mod bench;
 mod cli;
 mod clipboard;
+mod crash;
 mod crosswords;
+mod diagnostics;
 mod event;
+mod headless;
 mod ime;
 mod logger;
+mod memory;
 #[cfg(windows)]
 mod panic;
 mod performer;
 mod platform;
+mod remote;
 mod router;
 mod scheduler;
 mod screen;
 mod selection;
 mod sequencer;
+mod startup;
+mod tmux;
 mod ui;
 mod watch;
 use crate::event::EventP;
 use crate::sequencer::Sequencer;
-use log::{info, LevelFilter, SetLoggerError};
-use logger::Logger;
-use std::str::FromStr;
+use log::info;
+use std::path::Path;
 
 #[cfg(windows)]
 use windows_sys::Win32::System::Console::{
@@ -52,6 +60,9 @@ pub fn setup_environment_variables(config: &rio_config::Config) {
     std::env::set_var("TERM_PROGRAM_VERSION", env!("CARGO_PKG_VERSION"));
 
     std::env::set_var("COLORTERM", "truecolor");
+    // On X11 and Wayland the activation token is read and removed once the
+    // first window is created.
+    #[cfg(any(target_os = "macos", windows))]
     std::env::remove_var("DESKTOP_STARTUP_ID");
     #[cfg(target_os = "macos")]
     {
@@ -72,21 +83,6 @@ pub fn setup_environment_variables(config: &rio_config::Config) {
     }
 }
 
-static LOGGER: Logger = Logger;
-
-fn setup_logs_by_filter_level(log_level: &str) -> Result<(), SetLoggerError> {
-    let mut filter_level = LevelFilter::from_str(log_level).unwrap_or(LevelFilter::Off);
-
-    if let Ok(data) = std::env::var("RIO_LOG_LEVEL") {
-        if !data.is_empty() {
-            filter_level = LevelFilter::from_str(&data).unwrap_or(filter_level);
-        }
-    }
-
-    info!("[setup_logs_by_filter_level] log_level: {log_level}");
-    log::set_logger(&LOGGER).map(|()| log::set_max_level(filter_level))
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(windows)]
@@ -102,19 +98,50 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Load command line options.
     let options = cli::Options::new();
+    match &options.subcommand {
+        Some(cli::Subcommands::Config(command)) => std::process::exit(command.run()),
+        Some(cli::Subcommands::Remote { request }) => {
+            std::process::exit(cli::send_remote_request(request))
+        }
+        Some(cli::Subcommands::Bench { paths }) => {
+            std::process::exit(bench::run(paths).await)
+        }
+        None => (),
+    }
+    startup::init(options.measure_startup);
+
+    crash::attach_handler();
+    let crashed_on_startup = crash::begin_startup();
+    let safe_mode = options.safe_mode || crashed_on_startup;
 
     let mut config_error: Option<rio_config::ConfigError> = None;
-    let mut config = match rio_config::Config::try_load() {
-        Ok(config) => config,
-        Err(error) => {
-            config_error = Some(error);
-            rio_config::Config::default()
+    let mut config = if safe_mode {
+        rio_config::Config::default()
+    } else {
+        match rio_config::Config::try_load() {
+            Ok(config) => config,
+            Err(error) => {
+                config_error = Some(error);
+                rio_config::Config::default()
+            }
         }
     };
 
-    let setup_logs = setup_logs_by_filter_level(&config.developer.log_level);
-    if setup_logs.is_err() {
-        println!("unable to configure log level");
+    if let Err(err) = logger::init(
+        &config.developer.log_level,
+        options.log_level.as_deref(),
+        options.log_file.as_deref().map(Path::new),
+    ) {
+        eprintln!("unable to configure logs: {err}");
+    }
+    info!("[logger] log_level: {}", config.developer.log_level);
+
+    if let Some(profile) = options.profile.as_ref().filter(|_| !safe_mode) {
+        if let Err(err) = config.apply_profile(profile) {
+            eprintln!("unable to apply profile: {err}");
+            crash::end_startup();
+            std::process::exit(1);
+        }
     }
 
     if let Some(command) = options.window_options.terminal_options.command() {
@@ -126,6 +153,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         config.working_dir = Some(working_dir_cli);
     }
 
+    if let Some(performance) = options.performance {
+        config.performance = performance;
+    }
+
+    let playback = match (&options.play, &options.replay) {
+        (Some(path), _) => Some(performer::recorder::Cast::load(Path::new(path))),
+        (_, Some(path)) => Some(
+            std::fs::read(path).map(|bytes| performer::recorder::Cast::from_raw(&bytes)),
+        ),
+        _ if options.test_pattern => Some(Ok(performer::recorder::Cast::from_raw(
+            &diagnostics::test_pattern(),
+        ))),
+        _ => None,
+    };
+    if let Some(cast) = playback {
+        match cast {
+            Ok(cast) => performer::playback::set_pending(performer::playback::Playback {
+                cast,
+                speed: options.speed.max(0.0),
+                exit_on_end: options.screenshot.is_some(),
+            }),
+            Err(err) => {
+                eprintln!("unable to load recording: {err}");
+                crash::end_startup();
+                std::process::exit(1);
+            }
+        }
+    }
+
     #[cfg(target_os = "linux")]
     {
         // If running inside a flatpak sandbox.
@@ -136,14 +192,43 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     setup_environment_variables(&config);
+    startup::milestone("config loaded");
+
+    if let Some(screenshot) = options.screenshot.as_ref().filter(|_| options.headless) {
+        crash::end_startup();
+        std::process::exit(headless::run(config, screenshot).await);
+    }
 
     let window_event_loop =
         winit::event_loop::EventLoopBuilder::<EventP>::with_user_event()
             .build()
             .unwrap();
 
+    if options.diagnostics {
+        // The report is not a launch of the terminal, a failure must not
+        // start the next one in safe mode.
+        crash::end_startup();
+        let window = winit::window::WindowBuilder::new()
+            .with_visible(false)
+            .build(&window_event_loop)?;
+        print!("{}", diagnostics::report(&window, &config).await);
+        return Ok(());
+    }
+
     let mut sequencer = Sequencer::new(config, config_error);
+    if safe_mode {
+        let report = crashed_on_startup
+            .then(crash::latest_report)
+            .flatten()
+            .map(|path| path.display().to_string());
+        sequencer.set_safe_mode(report);
+    }
+    if let Some(screenshot) = options.screenshot {
+        sequencer.set_screenshot(screenshot);
+    }
     let _ = sequencer.run(window_event_loop).await;
+    #[cfg(unix)]
+    let _ = std::fs::remove_file(remote::socket_path());
 
     #[cfg(windows)]
     unsafe {
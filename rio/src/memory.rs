@@ -0,0 +1,227 @@
+// Memory accounting of the scrollback, images and glyph texture caches of all
+// the windows against the budgets of the `[memory]` section. Usage is measured
+// every few seconds after events were processed, terminals busy writing output
+// are skipped until the next check.
+//
+// Going over the scrollback budget drops the oldest lines of the terminals with
+// the largest scrollback first, a terminal with a short history keeps it while
+// another one holds most of the budget. Glyph caches are shrunk when the glyphs
+// they hold fit in a smaller texture, images are only counted.
+
+use crate::router::Route;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use winit::window::WindowId;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+const MIB: usize = 1024 * 1024;
+
+/// Bytes taken by each kind of storage.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Usage {
+    pub scrollback: usize,
+    pub images: usize,
+    pub glyph_atlas: usize,
+}
+
+/// Usage and evictions of the last check, shown by the glyph atlas view.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Report {
+    pub usage: Usage,
+    pub budget: Usage,
+    pub evicted_scrollback: usize,
+    pub shrunk_atlases: usize,
+}
+
+fn mib(bytes: usize) -> String {
+    format!("{:.1}", bytes as f64 / MIB as f64)
+}
+
+fn budget(bytes: usize) -> String {
+    if bytes == 0 {
+        String::from("unlimited")
+    } else {
+        format!("{} MiB", bytes / MIB)
+    }
+}
+
+impl Report {
+    pub fn summary(&self) -> String {
+        format!(
+            "Memory: scrollback {} of {}, images {} of {}, glyph atlas {} of {}, {} MiB of scrollback evicted, {} atlases shrunk",
+            mib(self.usage.scrollback),
+            budget(self.budget.scrollback),
+            mib(self.usage.images),
+            budget(self.budget.images),
+            mib(self.usage.glyph_atlas),
+            budget(self.budget.glyph_atlas),
+            mib(self.evicted_scrollback),
+            self.shrunk_atlases,
+        )
+    }
+}
+
+/// Bytes to evict from each scrollback of `usages` to fit in `budget`, the
+/// largest ones are brought down to a common level first.
+pub fn scrollback_evictions(usages: &[usize], budget: usize) -> Vec<usize> {
+    let total: usize = usages.iter().sum();
+    if budget == 0 || total <= budget {
+        return vec![0; usages.len()];
+    }
+
+    let mut sorted = usages.to_vec();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+    let mut rest = total;
+    let mut level = 0;
+    for (capped, usage) in sorted.iter().enumerate() {
+        rest -= usage;
+        if rest > budget {
+            continue;
+        }
+        // The `capped + 1` largest ones share what the others leave.
+        level = (budget - rest) / (capped + 1);
+        if sorted.get(capped + 1).map_or(true, |next| level >= *next) {
+            break;
+        }
+    }
+
+    usages
+        .iter()
+        .map(|usage| usage.saturating_sub(level))
+        .collect()
+}
+
+#[derive(Default)]
+pub struct Accountant {
+    last_check: Option<Instant>,
+    evicted_scrollback: usize,
+    shrunk_atlases: usize,
+}
+
+impl Accountant {
+    /// Measure the usage of every window and evict what goes over `config`,
+    /// at most once every `CHECK_INTERVAL`.
+    pub fn check(
+        &mut self,
+        config: &rio_config::Memory,
+        routes: &mut HashMap<WindowId, Route>,
+    ) {
+        let now = Instant::now();
+        if self
+            .last_check
+            .is_some_and(|last_check| now.duration_since(last_check) < CHECK_INTERVAL)
+        {
+            return;
+        }
+        self.last_check = Some(now);
+
+        let budget = Usage {
+            scrollback: config.scrollback * MIB,
+            images: config.images * MIB,
+            glyph_atlas: config.glyph_atlas * MIB,
+        };
+
+        let mut terminals = Vec::new();
+        let mut usages = Vec::new();
+        for route in routes.values() {
+            for context in route.window.screen.context_manager.contexts() {
+                if let Some(terminal) = context.terminal.try_lock_unfair() {
+                    usages.push(terminal.scrollback_memory());
+                    terminals.push(context.terminal.clone());
+                }
+            }
+        }
+        let mut usage = Usage {
+            scrollback: usages.iter().sum(),
+            ..Usage::default()
+        };
+        for (terminal, bytes) in terminals
+            .iter()
+            .zip(scrollback_evictions(&usages, budget.scrollback))
+        {
+            if bytes == 0 {
+                continue;
+            }
+            if let Some(mut terminal) = terminal.try_lock_unfair() {
+                let freed = terminal.evict_scrollback(bytes);
+                usage.scrollback -= freed;
+                self.evicted_scrollback += freed;
+            }
+        }
+
+        // Each window gets the same share of the glyph atlas budget.
+        let windows = routes.len().max(1);
+        for route in routes.values_mut() {
+            let sugarloaf = &mut route.window.screen.sugarloaf;
+            usage.images += sugarloaf.image_memory();
+            usage.glyph_atlas += sugarloaf.glyph_atlas_memory();
+        }
+        if budget.glyph_atlas != 0 && usage.glyph_atlas > budget.glyph_atlas {
+            for route in routes.values_mut() {
+                let sugarloaf = &mut route.window.screen.sugarloaf;
+                if sugarloaf.shrink_glyph_atlas(budget.glyph_atlas / windows) {
+                    self.shrunk_atlases += 1;
+                }
+            }
+        }
+        let report = Report {
+            usage,
+            budget,
+            evicted_scrollback: self.evicted_scrollback,
+            shrunk_atlases: self.shrunk_atlases,
+        };
+        for route in routes.values_mut() {
+            route.window.screen.memory = report.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrollback_evictions() {
+        // Within the budget, or no budget at all.
+        assert_eq!(scrollback_evictions(&[10, 20], 30), vec![0, 0]);
+        assert_eq!(scrollback_evictions(&[10, 20], 0), vec![0, 0]);
+        assert_eq!(scrollback_evictions(&[], 10), Vec::<usize>::new());
+
+        // The largest scrollback gives up lines alone while it is above the
+        // others.
+        assert_eq!(scrollback_evictions(&[10, 100, 20], 100), vec![0, 30, 0]);
+
+        // Then they are brought down together.
+        assert_eq!(scrollback_evictions(&[60, 100, 20], 100), vec![20, 60, 0]);
+        assert_eq!(scrollback_evictions(&[50, 50, 50], 30), vec![40, 40, 40]);
+
+        for (usages, budget) in [(vec![7, 3, 11, 5], 13), (vec![1, 1, 1], 2)] {
+            let evictions = scrollback_evictions(&usages, budget);
+            let left: usize = usages.iter().zip(&evictions).map(|(u, e)| u - e).sum();
+            assert!(left <= budget);
+        }
+    }
+
+    #[test]
+    fn test_summary() {
+        let report = Report {
+            usage: Usage {
+                scrollback: 3 * MIB / 2,
+                images: 0,
+                glyph_atlas: MIB / 4,
+            },
+            budget: Usage {
+                scrollback: 512 * MIB,
+                images: 0,
+                glyph_atlas: 64 * MIB,
+            },
+            evicted_scrollback: 2 * MIB,
+            shrunk_atlases: 1,
+        };
+        assert_eq!(
+            report.summary(),
+            "Memory: scrollback 1.5 of 512 MiB, images 0.0 of unlimited, glyph atlas 0.2 of 64 MiB, 2.0 MiB of scrollback evicted, 1 atlases shrunk"
+        );
+    }
+}
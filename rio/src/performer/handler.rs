@@ -1,7 +1,10 @@
 use crate::ansi::CursorShape;
-use crate::ansi::{mode::Mode, KeyboardModes, KeyboardModesApplyBehavior};
+use crate::ansi::{
+    mode::Mode, KeyboardModes, KeyboardModesApplyBehavior, ProgressState, PromptMark,
+};
 use crate::crosswords::pos::{CharsetIndex, Column, Line, StandardCharset};
 use crate::crosswords::square::Hyperlink;
+use crate::tmux;
 use cursor_icon::CursorIcon;
 use log::{debug, warn};
 use rio_config::colors::{AnsiColor, ColorRgb, NamedColor};
@@ -31,7 +34,7 @@ use copa::{Params, ParamsIter};
 // const SYNC_END_ESCAPE_START: [u8; SYNC_ESCAPE_START_LEN] =
 //     [b'\x1b', b'P', b'=', b'2', b's'];
 
-fn xparse_color(color: &[u8]) -> Option<ColorRgb> {
+pub(crate) fn xparse_color(color: &[u8]) -> Option<ColorRgb> {
     if !color.is_empty() && color[0] == b'#' {
         parse_legacy_color(&color[1..])
     } else if color.len() >= 4 && &color[..4] == b"rgb:" {
@@ -43,12 +46,9 @@ fn xparse_color(color: &[u8]) -> Option<ColorRgb> {
 
 /// Parse colors in `rgb:r(rrr)/g(ggg)/b(bbb)` format.
 fn parse_rgb_color(color: &[u8]) -> Option<ColorRgb> {
-    let colors = std::str::from_utf8(color)
-        .ok()?
-        .split('/')
-        .collect::<Vec<_>>();
-
-    if colors.len() != 3 {
+    let mut colors = std::str::from_utf8(color).ok()?.split('/');
+    let (r, g, b) = (colors.next()?, colors.next()?, colors.next()?);
+    if colors.next().is_some() {
         return None;
     }
 
@@ -64,9 +64,9 @@ fn parse_rgb_color(color: &[u8]) -> Option<ColorRgb> {
     };
 
     Some(ColorRgb {
-        r: scale(colors[0])?,
-        g: scale(colors[1])?,
-        b: scale(colors[2])?,
+        r: scale(r)?,
+        g: scale(g)?,
+        b: scale(b)?,
     })
 }
 
@@ -232,6 +232,9 @@ pub trait Handler {
     /// Move forward `count` tabs.
     fn move_forward_tabs(&mut self, _count: u16) {}
 
+    /// Reset tab stops to every 8 columns.
+    fn reset_tab_stops(&mut self) {}
+
     /// Save current cursor position.
     fn save_cursor_position(&mut self) {}
 
@@ -250,6 +253,12 @@ pub trait Handler {
     /// Reset terminal state.
     fn reset_state(&mut self) {}
 
+    /// DECSTR, reset modes, margins and attributes keeping the screen content.
+    fn soft_reset(&mut self) {}
+
+    /// DECRQM, report whether a mode is set.
+    fn report_mode(&mut self, _private: bool, _mode: u16) {}
+
     /// Reverse Index.
     ///
     /// Move the active position to the same horizontal position on the
@@ -269,6 +278,12 @@ pub trait Handler {
     /// DECSTBM - Set the terminal scrolling region.
     fn set_scrolling_region(&mut self, _top: usize, _bottom: Option<usize>) {}
 
+    /// DECSLRM, which shares `CSI s` with saving the cursor (SCOSC) when left and
+    /// right margins are disabled.
+    fn set_left_right_margins(&mut self, _left: usize, _right: Option<usize>) {
+        self.save_cursor_position();
+    }
+
     /// DECKPAM - Set keypad to applications mode (ESCape instead of digits).
     fn set_keypad_application_mode(&mut self) {}
 
@@ -287,6 +302,9 @@ pub trait Handler {
     /// later be 'invoked' by `set_active_charset`.
     fn configure_charset(&mut self, _: CharsetIndex, _: StandardCharset) {}
 
+    /// Use the charset at `index` for the next character only (SS2, SS3).
+    fn single_shift(&mut self, _: CharsetIndex) {}
+
     /// Set an indexed color value.
     fn set_color(&mut self, _: usize, _: ColorRgb) {}
 
@@ -320,6 +338,16 @@ pub trait Handler {
     /// Set hyperlink.
     fn set_hyperlink(&mut self, _: Option<Hyperlink>) {}
 
+    /// Mark the semantic zone of the shell integration (OSC 133).
+    fn prompt_mark(&mut self, _mark: PromptMark) {}
+
+    /// Report the progress of the program (OSC 9 ; 4), without a value the
+    /// error and paused states keep the current one.
+    fn set_progress(&mut self, _state: ProgressState, _value: Option<u8>) {}
+
+    /// Report the host and the working directory of the shell (OSC 7).
+    fn set_working_directory(&mut self, _host: &str, _path: &str) {}
+
     /// Set mouse cursor icon.
     fn set_mouse_cursor_icon(&mut self, _: CursorIcon) {}
 
@@ -333,6 +361,9 @@ pub trait Handler {
     /// keyboard mode stack.
     fn pop_keyboard_modes(&mut self, _to_pop: u16) {}
 
+    /// Start, line or end of a tmux control mode session.
+    fn tmux_control(&mut self, _: tmux::Control) {}
+
     /// Set the [`keyboard mode`] using the given [`behavior`].
     ///
     /// [`keyboard mode`]: crate::ansi::KeyboardModes
@@ -352,8 +383,18 @@ struct ProcessorState {
 
     /// State for synchronized terminal updates.
     sync_state: SyncState,
+
+    /// Last hyperlink with an explicit id, reused while the same link is
+    /// reopened to avoid allocating it again.
+    hyperlink: Option<Hyperlink>,
+
+    /// Line being read while tmux is in control mode.
+    tmux_line: Option<Vec<u8>>,
 }
 
+/// Maximum length of a tmux control mode line (1MiB), the rest is dropped.
+pub(super) const MAX_TMUX_LINE: usize = 0x10_0000;
+
 /// Maximum number of bytes read in one synchronized update (2MiB).
 const SYNC_BUFFER_SIZE: usize = 0x20_0000;
 
@@ -516,19 +557,55 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
         ignore: bool,
         action: char,
     ) {
+        // tmux -CC enters the control mode with DCS 1000 p.
+        if action == 'p'
+            && intermediates.is_empty()
+            && params.iter().next() == Some(&[1000][..])
+        {
+            self.state.tmux_line = Some(Vec::new());
+            self.handler.tmux_control(tmux::Control::Start);
+            return;
+        }
+
         debug!(
             "[unhandled hook] params={:?}, ints: {:?}, ignore: {:?}, action: {:?}",
             params, intermediates, ignore, action
         );
     }
 
-    fn put(&mut self, _byte: u8) {
-        debug!("[put] {_byte:02x}");
+    fn put(&mut self, byte: u8) {
+        let line = match &mut self.state.tmux_line {
+            Some(line) => line,
+            None => {
+                debug!("[put] {byte:02x}");
+                return;
+            }
+        };
+
+        match byte {
+            b'\n' => {
+                let text = String::from_utf8_lossy(line).into_owned();
+                line.clear();
+                self.handler.tmux_control(tmux::Control::Line(text));
+            }
+            b'\r' => (),
+            _ if line.len() >= MAX_TMUX_LINE => (),
+            _ => line.push(byte),
+        }
     }
 
     #[inline]
     fn unhook(&mut self) {
-        debug!("[unhandled unhook]");
+        match self.state.tmux_line.take() {
+            Some(line) => {
+                if !line.is_empty() {
+                    let text = String::from_utf8_lossy(&line).into_owned();
+                    self.handler.tmux_control(tmux::Control::Line(text));
+                }
+                self.handler.tmux_control(tmux::Control::End);
+            }
+            None => debug!("[unhandled unhook]"),
+        }
     }
 
     fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
@@ -618,7 +695,51 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
                     .find_map(|kv| kv.strip_prefix(b"id="))
                     .and_then(|kv| std::str::from_utf8(kv).ok());
 
-                self.handler.set_hyperlink(Some(Hyperlink::new(id, uri)));
+                let hyperlink = match (&self.state.hyperlink, id) {
+                    (Some(last), Some(id)) if last.id() == id && last.uri() == uri => {
+                        last.clone()
+                    }
+                    _ => {
+                        let hyperlink = Hyperlink::new(id, uri);
+                        if id.is_some() {
+                            self.state.hyperlink = Some(hyperlink.clone());
+                        }
+                        hyperlink
+                    }
+                };
+
+                self.handler.set_hyperlink(Some(hyperlink));
+            }
+
+            // Working directory of the shell, as a `file://host/path` URL.
+            b"7" if params.len() >= 2 => {
+                let url = params[1..].join(&b';');
+                let Some(location) = std::str::from_utf8(&url)
+                    .ok()
+                    .and_then(|url| url.strip_prefix("file://"))
+                else {
+                    return unhandled(params);
+                };
+                let (host, path) =
+                    location.split_at(location.find('/').unwrap_or(location.len()));
+                self.handler.set_working_directory(host, path);
+            }
+
+            // ConEmu progress report.
+            b"9" if params.len() >= 3 && params[1] == b"4" => {
+                let state = match params[2] {
+                    b"0" => ProgressState::Hidden,
+                    b"1" => ProgressState::Normal,
+                    b"2" => ProgressState::Error,
+                    b"3" => ProgressState::Indeterminate,
+                    b"4" => ProgressState::Paused,
+                    _ => return unhandled(params),
+                };
+                let value = params
+                    .get(3)
+                    .and_then(|value| parse_number(value))
+                    .map(|value| value.min(100));
+                self.handler.set_progress(state, value);
             }
 
             b"10" | b"11" | b"12" => {
@@ -714,6 +835,22 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
                 }
             }
 
+            // Shell integration prompt marks.
+            b"133" if params.len() >= 2 => match params[1] {
+                b"A" => self.handler.prompt_mark(PromptMark::PromptStart),
+                b"B" => self.handler.prompt_mark(PromptMark::CommandStart),
+                b"C" => self.handler.prompt_mark(PromptMark::CommandExecuted),
+                b"D" => {
+                    let exit_code = params
+                        .get(2)
+                        .and_then(|code| std::str::from_utf8(code).ok())
+                        .and_then(|code| code.parse().ok());
+                    self.handler
+                        .prompt_mark(PromptMark::CommandFinished(exit_code));
+                }
+                _ => unhandled(params),
+            },
+
             // Reset foreground color.
             b"110" => self.handler.reset_color(NamedColor::Foreground as usize),
 
@@ -747,7 +884,8 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
             }};
         }
 
-        if should_ignore || intermediates.len() > 1 {
+        // Private mode requests (DECRQM) are the only sequences with two.
+        if should_ignore || (intermediates.len() > 1 && intermediates != [b'?', b'$']) {
             return;
         }
 
@@ -788,7 +926,7 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
             ('g', []) => {
                 let mode = match next_param_or(0) {
                     0 => TabulationClearMode::Current,
-                    3 => TabulationClearMode::All,
+                    3 | 5 => TabulationClearMode::All,
                     _ => {
                         csi_unhandled!();
                         return;
@@ -859,16 +997,17 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
                 if params.is_empty() {
                     handler.terminal_attribute(Attr::Reset);
                 } else {
-                    for attr in attrs_from_sgr_parameters(&mut params_iter) {
-                        match attr {
-                            Some(attr) => handler.terminal_attribute(attr),
-                            None => csi_unhandled!(),
-                        }
-                    }
+                    attrs_from_sgr_parameters(&mut params_iter, |attr| match attr {
+                        Some(attr) => handler.terminal_attribute(attr),
+                        None => csi_unhandled!(),
+                    });
                 }
             }
             ('n', []) => handler.device_status(next_param_or(0) as usize),
             ('P', []) => handler.delete_chars(next_param_or(1) as usize),
+            ('p', [b'!']) => handler.soft_reset(),
+            ('p', [b'$']) => handler.report_mode(false, next_param_or(0)),
+            ('p', [b'?', b'$']) => handler.report_mode(true, next_param_or(0)),
             ('q', [b' ']) => {
                 // DECSCUSR (CSI Ps SP q) -- Set Cursor Style.
                 let cursor_style_id = next_param_or(0);
@@ -895,7 +1034,15 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
                 handler.set_scrolling_region(top, bottom);
             }
             ('S', []) => handler.scroll_up(next_param_or(1) as usize),
-            ('s', []) => handler.save_cursor_position(),
+            ('s', []) => {
+                let left = next_param_or(1) as usize;
+                let right = params_iter
+                    .next()
+                    .map(|param| param[0] as usize)
+                    .filter(|&param| param != 0);
+
+                handler.set_left_right_margins(left, right);
+            }
             ('T', []) => handler.scroll_down(next_param_or(1) as usize),
             ('t', []) => match next_param_or(1) as usize {
                 14 => handler.text_area_size_pixels(),
@@ -925,6 +1072,14 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
             }
             ('u', []) => handler.restore_cursor_position(),
             ('X', []) => handler.erase_chars(Column(next_param_or(1) as usize)),
+            ('W', []) => match next_param_or(0) {
+                // CTC -- Cursor Tabulation Control.
+                0 => handler.set_horizontal_tabstop(),
+                2 => handler.clear_tabs(TabulationClearMode::Current),
+                5 => handler.clear_tabs(TabulationClearMode::All),
+                _ => csi_unhandled!(),
+            },
+            ('W', [b'?']) if next_param_or(0) == 5 => handler.reset_tab_stops(),
             ('Z', []) => handler.move_backward_tabs(next_param_or(1)),
             _ => csi_unhandled!(),
         };
@@ -960,6 +1115,9 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
             (b'B', intermediates) => {
                 configure_charset!(StandardCharset::Ascii, intermediates)
             }
+            (b'A', intermediates) => {
+                configure_charset!(StandardCharset::UnitedKingdom, intermediates)
+            }
             (b'D', []) => self.handler.linefeed(),
             (b'E', []) => {
                 self.handler.linefeed();
@@ -967,6 +1125,10 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
             }
             (b'H', []) => self.handler.set_horizontal_tabstop(),
             (b'M', []) => self.handler.reverse_index(),
+            (b'N', []) => self.handler.single_shift(CharsetIndex::G2),
+            (b'O', []) => self.handler.single_shift(CharsetIndex::G3),
+            (b'n', []) => self.handler.set_active_charset(CharsetIndex::G2),
+            (b'o', []) => self.handler.set_active_charset(CharsetIndex::G3),
             (b'Z', []) => self.handler.identify_terminal(None),
             (b'c', []) => self.handler.reset_state(),
             (b'0', intermediates) => {
@@ -987,10 +1149,12 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
     }
 }
 
+/// Apply each SGR attribute as it is parsed, without collecting them.
 #[inline]
-fn attrs_from_sgr_parameters(params: &mut ParamsIter<'_>) -> Vec<Option<Attr>> {
-    let mut attrs = Vec::with_capacity(params.size_hint().0);
-
+fn attrs_from_sgr_parameters<F>(params: &mut ParamsIter<'_>, mut apply: F)
+where
+    F: FnMut(Option<Attr>),
+{
     while let Some(param) = params.next() {
         let attr = match param {
             [0] => Some(Attr::Reset),
@@ -1070,8 +1234,6 @@ fn attrs_from_sgr_parameters(params: &mut ParamsIter<'_>) -> Vec<Option<Attr>> {
             [107] => Some(Attr::Background(AnsiColor::Named(NamedColor::LightWhite))),
             _ => None,
         };
-        attrs.push(attr);
+        apply(attr);
     }
-
-    attrs
 }
@@ -0,0 +1,170 @@
+// Malformed output fed through the parser and the handlers of a terminal. The
+// parser itself is fuzzed by the targets in `copa/fuzz`, rio being a binary
+// crate its handlers are covered here instead: sequences are generated from a
+// seed, mixing extreme and missing parameters, unknown OSC commands, truncated
+// DCS strings and random bytes, over grids as small as the window allows.
+
+use super::handler::{Handler, ParserProcessor, MAX_TMUX_LINE};
+use crate::ansi::CursorShape;
+use crate::crosswords::pos::{Column, Line};
+use crate::crosswords::{Crosswords, MIN_COLUMNS, MIN_LINES};
+use crate::event::VoidListener;
+use crate::tmux;
+use winit::window::WindowId;
+
+/// Xorshift generator, the same seed gives the same input.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, count: usize) -> usize {
+        (self.next() % count as u64) as usize
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+fn param(rng: &mut Rng) -> String {
+    match rng.below(6) {
+        0 => String::new(),
+        1 => String::from("0"),
+        2 => String::from("65535"),
+        3 => String::from("99999999999"),
+        4 => rng.below(300).to_string(),
+        _ => format!("{}:{}:{}", rng.below(10), rng.below(300), rng.below(300)),
+    }
+}
+
+fn sequence(rng: &mut Rng) -> Vec<u8> {
+    let mut sequence = String::new();
+    match rng.below(5) {
+        0 | 1 => {
+            sequence.push_str("\x1b[");
+            if rng.below(3) == 0 {
+                sequence.push(*rng.pick(&['?', '>', '<', '=']));
+            }
+            for index in 0..rng.below(40) {
+                if index > 0 {
+                    sequence.push(';');
+                }
+                sequence.push_str(&param(rng));
+            }
+            if rng.below(4) == 0 {
+                sequence.push(*rng.pick(&[' ', '$', '!', '"', '\'', '*']));
+            }
+            sequence.push((0x40 + rng.below(0x3f) as u8) as char);
+        }
+        2 => {
+            let command = rng.pick(&[
+                0, 1, 2, 4, 7, 8, 9, 10, 11, 12, 52, 99, 104, 110, 111, 112, 133, 777,
+                1337,
+            ]);
+            sequence.push_str(&format!("\x1b]{command}"));
+            for _ in 0..rng.below(6) {
+                sequence.push(';');
+                let argument = match rng.below(6) {
+                    0 => String::from("rgb:ff/00/zz"),
+                    1 => String::from("?"),
+                    2 => param(rng),
+                    3 => String::from("c;aGVsbG8="),
+                    4 => String::from("id=x:y=1"),
+                    _ => String::from("file://host/tmp/a b"),
+                };
+                sequence.push_str(&argument);
+            }
+            // Left unterminated sometimes.
+            let terminator = *rng.pick(&["\x07", "\x1b\\", ""]);
+            sequence.push_str(terminator);
+        }
+        3 => {
+            sequence.push_str("\x1bP");
+            for index in 0..rng.below(5) {
+                if index > 0 {
+                    sequence.push(';');
+                }
+                sequence.push_str(&param(rng));
+            }
+            sequence.push(*rng.pick(&['q', 'p', '$', '+', '|']));
+            for _ in 0..rng.below(50) {
+                sequence.push((0x20 + rng.below(0x60) as u8) as char);
+            }
+            if rng.below(2) == 0 {
+                sequence.push('\n');
+            }
+            sequence.push_str("\x1b\\");
+        }
+        _ => {
+            let mut bytes = sequence.into_bytes();
+            for _ in 0..rng.below(20) {
+                bytes.push(rng.next() as u8);
+            }
+            return bytes;
+        }
+    }
+    sequence.into_bytes()
+}
+
+#[test]
+fn test_malformed_sequences() {
+    let sizes = [(MIN_COLUMNS, MIN_LINES), (3, 2), (2, 5), (80, 24)];
+    for seed in 1..400u64 {
+        let mut rng = Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        let (columns, lines) = *rng.pick(&sizes);
+        let mut terminal = Crosswords::new(
+            columns,
+            lines,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        let mut parser = ParserProcessor::new();
+        for _ in 0..200 {
+            for byte in sequence(&mut rng) {
+                parser.advance(&mut terminal, byte);
+            }
+        }
+        parser.stop_sync(&mut terminal);
+
+        // Still usable afterwards.
+        for byte in b"\x18\x1bc\x1b[?1049lok" {
+            parser.advance(&mut terminal, *byte);
+        }
+        let text: String = (0..2)
+            .map(|column| terminal.grid[Line(0)][Column(column)].c)
+            .collect();
+        assert_eq!(text, "ok", "seed {seed}");
+    }
+}
+
+#[derive(Default)]
+struct TmuxLines(Vec<usize>);
+
+impl Handler for TmuxLines {
+    fn tmux_control(&mut self, control: tmux::Control) {
+        if let tmux::Control::Line(line) = control {
+            self.0.push(line.len());
+        }
+    }
+}
+
+#[test]
+fn test_tmux_line_is_bounded() {
+    let mut handler = TmuxLines::default();
+    let mut parser = ParserProcessor::new();
+    let input = b"\x1bP1000p"
+        .iter()
+        .chain(std::iter::repeat(&b'a').take(MAX_TMUX_LINE * 2))
+        .chain(b"\nnext\n\x1b\\");
+    for byte in input {
+        parser.advance(&mut handler, *byte);
+    }
+    assert_eq!(handler.0, vec![MAX_TMUX_LINE, 4]);
+}
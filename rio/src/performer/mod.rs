@@ -1,4 +1,10 @@
 pub mod handler;
+#[cfg(test)]
+mod malformed;
+pub mod monitor;
+pub mod output_log;
+pub mod playback;
+pub mod recorder;
 
 use crate::crosswords::Crosswords;
 use crate::event::sync::FairMutex;
@@ -48,6 +54,9 @@ pub struct State {
     write_list: VecDeque<Cow<'static, [u8]>>,
     writing: Option<Writing>,
     parser: handler::ParserProcessor,
+    recorder: Option<recorder::Recorder>,
+    output_log: Option<output_log::OutputLog>,
+    monitor: Option<monitor::Monitor>,
 }
 
 impl State {
@@ -77,6 +86,43 @@ impl State {
     fn set_current(&mut self, new: Option<Writing>) {
         self.writing = new;
     }
+
+    #[inline]
+    fn record(&mut self, bytes: &[u8]) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(err) = recorder.output(bytes) {
+                error!("unable to record session: {err}");
+                self.recorder = None;
+            }
+        }
+        if let Some(log) = &mut self.output_log {
+            if let Err(err) = log.output(bytes) {
+                error!("unable to log output to {}: {err}", log.path.display());
+                self.output_log = None;
+            }
+        }
+    }
+
+    /// Write out the output log once it waited long enough.
+    fn flush_output_log(&mut self, now: Instant) {
+        let Some(log) = &mut self.output_log else {
+            return;
+        };
+        if log.flush_deadline().is_some_and(|deadline| deadline <= now) {
+            if let Err(err) = log.flush(false) {
+                error!("unable to log output to {}: {err}", log.path.display());
+                self.output_log = None;
+            }
+        }
+    }
+
+    fn stop_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            if let Err(err) = recorder.finish() {
+                error!("unable to save recording: {err}");
+            }
+        }
+    }
 }
 
 struct Writing {
@@ -139,6 +185,7 @@ where
     fn pty_read(&mut self, state: &mut State, buf: &mut [u8]) -> io::Result<()> {
         let mut unprocessed = 0;
         let mut processed = 0;
+        let mut alert = false;
 
         // Reserve the next terminal lock for PTY reading.
         let _terminal_lease = Some(self.terminal.lease());
@@ -149,7 +196,10 @@ where
             match self.pty.reader().read(&mut buf[unprocessed..]) {
                 // This is received on Windows/macOS when no more data is readable from the PTY.
                 Ok(0) if unprocessed == 0 => break,
-                Ok(got) => unprocessed += got,
+                Ok(got) => {
+                    state.record(&buf[unprocessed..unprocessed + got]);
+                    unprocessed += got;
+                }
                 Err(err) => match err.kind() {
                     ErrorKind::Interrupted | ErrorKind::WouldBlock => {
                         // Go back to mio if we're caught up on parsing and the PTY would block.
@@ -174,10 +224,25 @@ where
                 }),
             };
 
+            // Input handling reads the mode without waiting for this lock, so
+            // publish it before parsing (it may have been changed by the UI)
+            // and after parsing.
+            terminal.publish_mode();
+
             // Parse the incoming bytes.
             for byte in &buf[..unprocessed] {
                 state.parser.advance(&mut **terminal, *byte);
             }
+            terminal.count_parsed_bytes(unprocessed);
+
+            if let Some(monitor) = &mut state.monitor {
+                if monitor.output(Instant::now()) {
+                    terminal.monitor_alert = Some(monitor.kind);
+                    alert = true;
+                }
+            }
+
+            terminal.publish_mode();
 
             processed += unprocessed;
             unprocessed = 0;
@@ -188,15 +253,36 @@ where
             }
         }
 
+        // Frames drawn while the next batch is parsed show this one.
+        if let Some(terminal) = &mut terminal {
+            terminal.publish_snapshot();
+        }
+
         // Queue terminal redraw unless all processed bytes were synchronized.
         if state.parser.sync_bytes_count() < processed && processed > 0 {
             self.event_proxy
                 .send_event(RioEvent::Wakeup, self.window_id);
         }
 
+        if alert {
+            self.event_proxy
+                .send_event(RioEvent::MonitorAlert, self.window_id);
+        }
+
         Ok(())
     }
 
+    /// Flag the terminal once the silence monitor fires.
+    fn check_silence(&mut self, state: &mut State) {
+        if let Some(monitor) = &mut state.monitor {
+            if monitor.check(Instant::now()) {
+                self.terminal.lock().monitor_alert = Some(monitor.kind);
+                self.event_proxy
+                    .send_event(RioEvent::MonitorAlert, self.window_id);
+            }
+        }
+    }
+
     fn should_keep_alive(&mut self, state: &mut State) -> bool {
         while let Ok(msg) = self.receiver.try_recv() {
             match msg {
@@ -204,8 +290,19 @@ where
                     state.write_list.push_back(input);
                 }
                 Msg::Resize(window_size) => {
+                    if let Some(recorder) = &mut state.recorder {
+                        let _ = recorder.resize(window_size.cols, window_size.rows);
+                    }
                     let _ = self.pty.set_winsize(window_size);
                 }
+                Msg::StartRecording(recorder) => {
+                    state.stop_recording();
+                    state.recorder = Some(recorder);
+                }
+                Msg::StopRecording => state.stop_recording(),
+                Msg::StartOutputLog(log) => state.output_log = Some(*log),
+                Msg::StopOutputLog => state.output_log = None,
+                Msg::Monitor(monitor) => state.monitor = monitor,
                 Msg::Shutdown => return false,
             }
         }
@@ -292,9 +389,20 @@ where
 
             'event_loop: loop {
                 // Wakeup the event loop when a synchronized update timeout was reached.
-                let sync_timeout = state.parser.sync_timeout();
-                let timeout =
-                    sync_timeout.map(|st| st.saturating_duration_since(Instant::now()));
+                let sync_timeout = state.parser.sync_timeout().copied();
+                let silence_deadline = state
+                    .monitor
+                    .as_ref()
+                    .and_then(|monitor| monitor.deadline());
+                let flush_deadline = state
+                    .output_log
+                    .as_ref()
+                    .and_then(|log| log.flush_deadline());
+                let timeout = [sync_timeout, silence_deadline, flush_deadline]
+                    .into_iter()
+                    .flatten()
+                    .min()
+                    .map(|deadline| deadline.saturating_duration_since(Instant::now()));
 
                 if let Err(err) = self.poll.poll(&mut events, timeout) {
                     match err.kind() {
@@ -303,11 +411,18 @@ where
                     }
                 }
 
+                self.check_silence(&mut state);
+                state.flush_output_log(Instant::now());
+
                 // Handle synchronized update timeout.
                 if events.is_empty() {
-                    state.parser.stop_sync(&mut *self.terminal.lock());
-                    self.event_proxy
-                        .send_event(RioEvent::Wakeup, self.window_id);
+                    if sync_timeout
+                        .is_some_and(|sync_timeout| sync_timeout <= Instant::now())
+                    {
+                        state.parser.stop_sync(&mut *self.terminal.lock());
+                        self.event_proxy
+                            .send_event(RioEvent::Wakeup, self.window_id);
+                    }
                     continue;
                 }
 
@@ -323,15 +438,10 @@ where
                             if let Some(teletypewriter::ChildEvent::Exited) =
                                 self.pty.next_child_event()
                             {
-                                // In the future allow configure exit
-                                // if self.hold {
-                                //     With hold enabled, make sure the PTY is drained.
-                                //     let _ = self.pty_read(&mut state, &mut buf);
-                                // } else {
-                                //     // Without hold, shutdown the terminal.
-                                //     self.terminal.lock().exit();
-                                // }
-
+                                // The last output can still be unread when the
+                                // exit is reported, screenshots taken on exit
+                                // need it.
+                                let _ = self.pty_read(&mut state, &mut buf);
                                 self.terminal.lock().exit();
                                 self.event_proxy
                                     .send_event(RioEvent::Wakeup, self.window_id);
@@ -388,6 +498,8 @@ where
                     .unwrap();
             }
 
+            state.stop_recording();
+
             // The evented instances are not dropped here so deregister them explicitly.
             let _ = self.poll.deregister(&self.receiver);
             let _ = self.pty.deregister(&self.poll);
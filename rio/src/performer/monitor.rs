@@ -0,0 +1,95 @@
+// Activity and silence monitors of a terminal. They run on the PTY reader
+// thread, which sees every output and can wake up when nothing came for a
+// while, and flag the terminal for the tab bar once they fire.
+
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorKind {
+    /// New output after `interval` without any.
+    Activity,
+    /// No output for `interval`.
+    Silence,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Monitor {
+    pub kind: MonitorKind,
+    interval: Duration,
+    last_output: Instant,
+    /// Silence already reported, until the next output.
+    silent: bool,
+}
+
+impl Monitor {
+    pub fn new(kind: MonitorKind, interval: Duration) -> Monitor {
+        Monitor {
+            kind,
+            interval,
+            last_output: Instant::now(),
+            silent: false,
+        }
+    }
+
+    /// Register output received at `now`, returns true when the activity
+    /// monitor fires.
+    pub fn output(&mut self, now: Instant) -> bool {
+        let quiet_for = now.saturating_duration_since(self.last_output);
+        self.last_output = now;
+        self.silent = false;
+        self.kind == MonitorKind::Activity && quiet_for >= self.interval
+    }
+
+    /// When the silence monitor fires if no output comes before.
+    pub fn deadline(&self) -> Option<Instant> {
+        if self.kind == MonitorKind::Silence && !self.silent {
+            Some(self.last_output + self.interval)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true once when the silence monitor fires.
+    pub fn check(&mut self, now: Instant) -> bool {
+        match self.deadline() {
+            Some(deadline) if deadline <= now => {
+                self.silent = true;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activity() {
+        let start = Instant::now();
+        let mut monitor = Monitor::new(MonitorKind::Activity, Duration::from_secs(10));
+        assert_eq!(monitor.deadline(), None);
+        assert!(!monitor.output(start + Duration::from_secs(3)));
+        assert!(!monitor.output(start + Duration::from_secs(12)));
+        assert!(monitor.output(start + Duration::from_secs(30)));
+        assert!(!monitor.output(start + Duration::from_secs(31)));
+        assert!(!monitor.check(start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_silence() {
+        let start = Instant::now();
+        let mut monitor = Monitor::new(MonitorKind::Silence, Duration::from_secs(10));
+        assert!(!monitor.output(start));
+        assert_eq!(monitor.deadline(), Some(start + Duration::from_secs(10)));
+        assert!(!monitor.check(start + Duration::from_secs(5)));
+        assert!(monitor.check(start + Duration::from_secs(10)));
+        // Reported once until new output comes.
+        assert_eq!(monitor.deadline(), None);
+        assert!(!monitor.check(start + Duration::from_secs(20)));
+
+        monitor.output(start + Duration::from_secs(25));
+        assert!(monitor.check(start + Duration::from_secs(35)));
+    }
+}
@@ -0,0 +1,378 @@
+// Output logs, a copy of what the programs of a tab print written to a file
+// as it is read from the PTY. Raw logs keep the bytes untouched, plain logs
+// go through a parser of their own that keeps the text and leaves the escape
+// sequences out, the terminal state is of no use there.
+
+use rio_config::OutputLogFormat;
+use std::fs::{DirBuilder, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Longest time logged output waits in memory before being written out.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct OutputLog {
+    pub path: PathBuf,
+    writer: BufWriter<File>,
+    plain: Option<(copa::Parser, Text)>,
+    /// First output buffered since the last flush.
+    unflushed_since: Option<Instant>,
+}
+
+impl std::fmt::Debug for OutputLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("OutputLog")
+            .field("path", &self.path)
+            .field("plain", &self.plain.is_some())
+            .finish()
+    }
+}
+
+impl OutputLog {
+    /// Create a new file at `path`, or next to it with a number added when it
+    /// exists already, logs are never overwritten. The output can hold
+    /// anything typed or shown in the tab, only the user may read it.
+    pub fn create(path: &Path, format: OutputLogFormat) -> io::Result<OutputLog> {
+        #[cfg(unix)]
+        use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+
+        if let Some(parent) = path.parent() {
+            let mut builder = DirBuilder::new();
+            builder.recursive(true);
+            #[cfg(unix)]
+            builder.mode(0o700);
+            builder.create(parent)?;
+        }
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let mut candidate = path.to_path_buf();
+        let mut count = 0;
+        let file = loop {
+            match options.open(&candidate) {
+                Ok(file) => break file,
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists && count < 100 => {
+                    count += 1;
+                    candidate = numbered(path, count);
+                }
+                Err(err) => return Err(err),
+            }
+        };
+
+        let plain = match format {
+            OutputLogFormat::Plain => Some((copa::Parser::new(), Text::default())),
+            OutputLogFormat::Raw => None,
+        };
+        Ok(OutputLog {
+            path: candidate,
+            writer: BufWriter::new(file),
+            plain,
+            unflushed_since: None,
+        })
+    }
+
+    /// Log bytes read from the PTY, they are written out by [`flush`] at the
+    /// latest once [`flush_deadline`] is over.
+    ///
+    /// [`flush`]: OutputLog::flush
+    /// [`flush_deadline`]: OutputLog::flush_deadline
+    pub fn output(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match &mut self.plain {
+            Some((parser, text)) => {
+                for byte in bytes {
+                    parser.advance(text, *byte);
+                }
+                self.writer.write_all(text.take_lines().as_bytes())?;
+            }
+            None => self.writer.write_all(bytes)?,
+        }
+        self.unflushed_since.get_or_insert_with(Instant::now);
+        Ok(())
+    }
+
+    /// When the buffered output has to be written out.
+    #[inline]
+    pub fn flush_deadline(&self) -> Option<Instant> {
+        self.unflushed_since.map(|since| since + FLUSH_INTERVAL)
+    }
+
+    /// Write out the buffered output, the last line of plain logs waits for
+    /// its end unless `finish` is set.
+    pub fn flush(&mut self, finish: bool) -> io::Result<()> {
+        if finish {
+            if let Some((_, text)) = &mut self.plain {
+                self.writer.write_all(text.take_all().as_bytes())?;
+            }
+        }
+        self.unflushed_since = None;
+        self.writer.flush()
+    }
+}
+
+impl Drop for OutputLog {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush(true) {
+            log::error!("unable to log output to {}: {err}", self.path.display());
+        }
+    }
+}
+
+/// Text printed by the program, with the line feeds and tabs. Text after a
+/// carriage return starts the line over, the way progress bars redraw it.
+#[derive(Default)]
+struct Text {
+    text: String,
+    /// A carriage return was the last thing printed.
+    returned: bool,
+}
+
+impl Text {
+    #[inline]
+    fn line_start(&self) -> usize {
+        self.text.rfind('\n').map_or(0, |index| index + 1)
+    }
+
+    fn push(&mut self, c: char) {
+        if std::mem::take(&mut self.returned) {
+            self.text.truncate(self.line_start());
+        }
+        self.text.push(c);
+    }
+
+    /// Take the complete lines, the last one can still be started over.
+    fn take_lines(&mut self) -> String {
+        let rest = self.text.split_off(self.line_start());
+        std::mem::replace(&mut self.text, rest)
+    }
+
+    fn take_all(&mut self) -> String {
+        std::mem::take(&mut self.text)
+    }
+}
+
+impl copa::Perform for Text {
+    fn print(&mut self, c: char) {
+        self.push(c);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            b'\n' => {
+                self.returned = false;
+                self.text.push('\n');
+            }
+            b'\r' => self.returned = true,
+            b'\t' => self.push('\t'),
+            _ => {}
+        }
+    }
+}
+
+/// `path` with `-count` before its extension.
+fn numbered(path: &Path, count: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{stem}-{count}.{}", extension.to_string_lossy()),
+        None => format!("{stem}-{count}"),
+    };
+    path.with_file_name(name)
+}
+
+/// Path of a log starting now, from the `path` of the config.
+pub fn new_path(template: Option<&str>) -> PathBuf {
+    let directory = rio_config::paths::state_dir().join("logs");
+    let template = template.unwrap_or("%Y-%m-%d_%H-%M-%S.log");
+    let path = expand(template, &now());
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => directory.join(path),
+    }
+}
+
+/// Calendar date and time, local on Unix and UTC elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DateTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+/// Replace the `strftime` fields of `template` supported in paths.
+fn expand(template: &str, time: &DateTime) -> String {
+    let mut path = String::with_capacity(template.len());
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            path.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => path.push_str(&format!("{:04}", time.year)),
+            Some('m') => path.push_str(&format!("{:02}", time.month)),
+            Some('d') => path.push_str(&format!("{:02}", time.day)),
+            Some('H') => path.push_str(&format!("{:02}", time.hour)),
+            Some('M') => path.push_str(&format!("{:02}", time.minute)),
+            Some('S') => path.push_str(&format!("{:02}", time.second)),
+            Some('%') => path.push('%'),
+            Some(other) => {
+                path.push('%');
+                path.push(other);
+            }
+            None => path.push('%'),
+        }
+    }
+    path
+}
+
+fn seconds_since_epoch() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn now() -> DateTime {
+    let seconds = seconds_since_epoch() as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&seconds, &mut tm) }.is_null() {
+        return utc(seconds as i64);
+    }
+    DateTime {
+        year: tm.tm_year as i64 + 1900,
+        month: tm.tm_mon as u32 + 1,
+        day: tm.tm_mday as u32,
+        hour: tm.tm_hour as u32,
+        minute: tm.tm_min as u32,
+        second: tm.tm_sec as u32,
+    }
+}
+
+#[cfg(not(unix))]
+fn now() -> DateTime {
+    utc(seconds_since_epoch())
+}
+
+/// Date and time `seconds` after the epoch in UTC, the days are converted
+/// with the civil calendar algorithm of Howard Hinnant.
+fn utc(seconds: i64) -> DateTime {
+    let days = seconds.div_euclid(86400);
+    let time = seconds.rem_euclid(86400) as u32;
+
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    DateTime {
+        year,
+        month,
+        day,
+        hour: time / 3600,
+        minute: time / 60 % 60,
+        second: time % 60,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let time = DateTime {
+            year: 2023,
+            month: 9,
+            day: 4,
+            hour: 7,
+            minute: 5,
+            second: 30,
+        };
+        assert_eq!(
+            expand("%Y-%m-%d_%H-%M-%S.log", &time),
+            "2023-09-04_07-05-30.log"
+        );
+        assert_eq!(expand("100%% %q %", &time), "100% %q %");
+    }
+
+    #[test]
+    fn test_utc() {
+        assert_eq!(
+            utc(0),
+            DateTime {
+                year: 1970,
+                month: 1,
+                day: 1,
+                hour: 0,
+                minute: 0,
+                second: 0,
+            }
+        );
+        assert_eq!(
+            utc(1709210096),
+            DateTime {
+                year: 2024,
+                month: 2,
+                day: 29,
+                hour: 12,
+                minute: 34,
+                second: 56,
+            }
+        );
+    }
+
+    #[test]
+    fn test_plain_output() {
+        let path = std::env::temp_dir().join("rio-test-output-log.log");
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(numbered(&path, 1));
+
+        let mut log = OutputLog::create(&path, OutputLogFormat::Plain).unwrap();
+        log.output(b"\x1b[1;31mred\x1b[0m\r\n\x1b]0;title\x07a\tb")
+            .unwrap();
+        // Sequences split between two reads are still left out.
+        log.output(b"\x1b[3").unwrap();
+        log.output(b"2mc\xc3").unwrap();
+        log.output(b"\xa9\n").unwrap();
+        // Progress redrawn after a carriage return keeps its last state.
+        log.output(b"10%\r50%\r").unwrap();
+        log.output(b"100%\r\ndone").unwrap();
+        log.flush(false).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "red\na\tbcé\n100%\n"
+        );
+        drop(log);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "red\na\tbcé\n100%\ndone"
+        );
+
+        let raw = OutputLog::create(&path, OutputLogFormat::Raw).unwrap();
+        assert_eq!(raw.path, numbered(&path, 1));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&raw.path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(numbered(&path, 1));
+    }
+}
@@ -0,0 +1,137 @@
+// Playback of recorded sessions, the recorded output is fed through the parser
+// as if it was read from a PTY. It is also used to reproduce rendering bugs from
+// a captured byte stream and by tests to check the grid after a given input.
+
+use super::handler::{Handler, ParserProcessor};
+use super::recorder::Cast;
+use super::spawn_named;
+use crate::crosswords::Crosswords;
+use crate::event::sync::FairMutex;
+use crate::event::{EventListener, RioEvent};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use winit::window::WindowId;
+
+pub struct Playback {
+    pub cast: Cast,
+    /// Speed multiplier, zero plays without waiting between events.
+    pub speed: f64,
+    /// Close the terminal once the playback is over.
+    pub exit_on_end: bool,
+}
+
+static PENDING: Mutex<Option<Playback>> = Mutex::new(None);
+
+/// Queue a playback to be played by the first terminal.
+pub fn set_pending(playback: Playback) {
+    if let Ok(mut pending) = PENDING.lock() {
+        *pending = Some(playback);
+    }
+}
+
+pub fn take_pending() -> Option<Playback> {
+    PENDING.lock().ok().and_then(|mut pending| pending.take())
+}
+
+/// Feed the whole recording into `handler` without waiting.
+pub fn replay<H: Handler>(cast: &Cast, handler: &mut H) {
+    let mut parser = ParserProcessor::new();
+    for event in &cast.events {
+        for byte in &event.output {
+            parser.advance(handler, *byte);
+        }
+    }
+    parser.stop_sync(handler);
+}
+
+/// Play the recording into `terminal` following its timing.
+pub fn spawn<U>(
+    playback: Playback,
+    terminal: Arc<FairMutex<Crosswords<U>>>,
+    event_proxy: U,
+    window_id: WindowId,
+) where
+    U: EventListener + Send + 'static,
+{
+    spawn_named("playback", move || {
+        let Playback {
+            cast,
+            speed,
+            exit_on_end,
+        } = playback;
+        let mut parser = ParserProcessor::new();
+        let started_at = Instant::now();
+        let mut clock = 0.0;
+        let mut previous = 0.0;
+
+        for event in cast.events {
+            let mut delay = (event.time - previous).max(0.0);
+            previous = event.time;
+            if let Some(limit) = cast.idle_time_limit {
+                delay = delay.min(limit);
+            }
+
+            if speed > 0.0 {
+                clock += delay / speed;
+                let target = started_at + Duration::from_secs_f64(clock);
+                if let Some(wait) = target.checked_duration_since(Instant::now()) {
+                    std::thread::sleep(wait);
+                }
+            }
+
+            let mut terminal = terminal.lock();
+            for byte in &event.output {
+                parser.advance(&mut *terminal, *byte);
+            }
+            terminal.publish_mode();
+            drop(terminal);
+
+            event_proxy.send_event(RioEvent::Wakeup, window_id);
+        }
+
+        // Flush a synchronized update left open by the recording.
+        if parser.sync_bytes_count() > 0 {
+            parser.stop_sync(&mut *terminal.lock());
+            event_proxy.send_event(RioEvent::Wakeup, window_id);
+        }
+
+        if exit_on_end {
+            event_proxy.send_event(RioEvent::Exit, window_id);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::CursorShape;
+    use crate::crosswords::pos::{Column, Line};
+    use crate::event::VoidListener;
+
+    #[test]
+    fn test_replay_raw_stream() {
+        let mut terminal = Crosswords::new(
+            10,
+            3,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+        );
+
+        // Write, move the cursor back, overwrite and erase the end of the line
+        // inside a synchronized update that is never closed.
+        let cast = Cast::from_raw(b"\x1b[?2026hhello\r\nworld\x1b[1;2HE\x1b[2;4H\x1b[K");
+        replay(&cast, &mut terminal);
+
+        let line = |line: i32| -> String {
+            (0..10)
+                .map(|column| terminal.grid[Line(line)][Column(column)].c)
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        };
+        assert_eq!(line(0), "hEllo");
+        assert_eq!(line(1), "wor");
+        assert_eq!(line(2), "");
+    }
+}
@@ -0,0 +1,280 @@
+// Session recording in the asciicast v2 format, see
+// https://docs.asciinema.org/manual/asciicast/v2/
+//
+// The file starts with a JSON header line followed by one JSON array per event
+// holding the time in seconds since the recording started, the event code and
+// its data: `[0.248, "o", "hello\r\n"]` for output and `[1.5, "r", "80x24"]`
+// for resizes.
+
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug)]
+pub struct Recorder {
+    writer: BufWriter<File>,
+    started_at: Instant,
+    // Trailing bytes of an UTF-8 sequence split between two reads.
+    pending: Vec<u8>,
+}
+
+impl Recorder {
+    /// Create the file at `path` and write the asciicast header.
+    pub fn create(path: &Path, columns: usize, lines: usize) -> io::Result<Recorder> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let header = serde_json::json!({
+            "version": 2,
+            "width": columns,
+            "height": lines,
+            "timestamp": timestamp,
+            "env": {
+                "TERM": std::env::var("TERM").unwrap_or_else(|_| String::from("rio")),
+                "SHELL": std::env::var("SHELL").unwrap_or_default(),
+            },
+        });
+        writeln!(writer, "{header}")?;
+
+        Ok(Recorder {
+            writer,
+            started_at: Instant::now(),
+            pending: Vec::new(),
+        })
+    }
+
+    /// Record bytes read from the PTY.
+    pub fn output(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let mut data = std::mem::take(&mut self.pending);
+        data.extend_from_slice(bytes);
+
+        let mut text = String::with_capacity(data.len());
+        let mut rest = data.as_slice();
+        while !rest.is_empty() {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    rest = &[];
+                }
+                Err(err) => {
+                    let (valid, invalid) = rest.split_at(err.valid_up_to());
+                    text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    match err.error_len() {
+                        Some(len) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            rest = &invalid[len..];
+                        }
+                        // Incomplete sequence, wait for the next read.
+                        None => {
+                            self.pending = invalid.to_vec();
+                            rest = &[];
+                        }
+                    }
+                }
+            }
+        }
+
+        if text.is_empty() {
+            return Ok(());
+        }
+        self.event("o", &text)
+    }
+
+    /// Record a terminal resize.
+    pub fn resize(&mut self, columns: u16, lines: u16) -> io::Result<()> {
+        self.event("r", &format!("{columns}x{lines}"))
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn event(&mut self, code: &str, data: &str) -> io::Result<()> {
+        // Microseconds are enough precision and keep the file small.
+        let time = (self.started_at.elapsed().as_secs_f64() * 1e6).round() / 1e6;
+        let event = serde_json::to_string(&(time, code, data))?;
+        writeln!(self.writer, "{event}")
+    }
+}
+
+/// Path for a new recording, inside the videos directory or the home directory.
+pub fn new_path() -> PathBuf {
+    let directory = dirs::video_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_default();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    directory.join(format!("rio-recording-{timestamp}.cast"))
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+    width: usize,
+    height: usize,
+    idle_time_limit: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CastEvent {
+    /// Seconds since the start of the recording.
+    pub time: f64,
+    pub output: Vec<u8>,
+}
+
+/// Recording loaded from an asciicast v2 file, only output events are kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cast {
+    pub width: usize,
+    pub height: usize,
+    /// Maximum pause between two events, in seconds.
+    pub idle_time_limit: Option<f64>,
+    pub events: Vec<CastEvent>,
+}
+
+/// Bytes per event when playing a raw byte stream.
+const RAW_CHUNK_SIZE: usize = 4096;
+
+/// Seconds between events of a raw byte stream, about one frame.
+const RAW_CHUNK_INTERVAL: f64 = 1.0 / 60.0;
+
+impl Cast {
+    pub fn load(path: &Path) -> io::Result<Cast> {
+        Cast::parse(BufReader::new(File::open(path)?))
+    }
+
+    /// Raw PTY byte stream, as captured by `script` or a PTY logger. It has no
+    /// timing so it is split into chunks played one per frame.
+    pub fn from_raw(bytes: &[u8]) -> Cast {
+        let events = bytes
+            .chunks(RAW_CHUNK_SIZE)
+            .enumerate()
+            .map(|(index, chunk)| CastEvent {
+                time: index as f64 * RAW_CHUNK_INTERVAL,
+                output: chunk.to_vec(),
+            })
+            .collect();
+
+        Cast {
+            width: 0,
+            height: 0,
+            idle_time_limit: None,
+            events,
+        }
+    }
+
+    pub fn parse<R: BufRead>(reader: R) -> io::Result<Cast> {
+        let mut lines = reader.lines();
+        let header = match lines.next() {
+            Some(line) => serde_json::from_str::<Header>(&line?)?,
+            None => return Err(invalid_data("empty asciicast file")),
+        };
+        if header.version != 2 {
+            return Err(invalid_data(&format!(
+                "unsupported asciicast version {}",
+                header.version
+            )));
+        }
+
+        let mut events = Vec::new();
+        for line in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (time, code, data): (f64, String, String) = serde_json::from_str(&line)?;
+            if code == "o" {
+                events.push(CastEvent {
+                    time,
+                    output: data.into_bytes(),
+                });
+            }
+        }
+
+        Ok(Cast {
+            width: header.width,
+            height: header.height,
+            idle_time_limit: header.idle_time_limit,
+            events,
+        })
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("rio-test-recording-{}.cast", std::process::id()));
+
+        let mut recorder = Recorder::create(&path, 80, 24).unwrap();
+        recorder.output(b"hello\r\n").unwrap();
+        // "é" split between two reads.
+        recorder.output(&[b'a', 0xc3]).unwrap();
+        recorder.output(&[0xa9, b'\x1b', b'[', b'm']).unwrap();
+        recorder.resize(100, 30).unwrap();
+        recorder.output(&[0xff]).unwrap();
+        recorder.finish().unwrap();
+
+        let cast = Cast::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((cast.width, cast.height), (80, 24));
+        assert_eq!(cast.idle_time_limit, None);
+        let outputs: Vec<String> = cast
+            .events
+            .iter()
+            .map(|event| String::from_utf8(event.output.clone()).unwrap())
+            .collect();
+        assert_eq!(outputs, vec!["hello\r\n", "a", "é\x1b[m", "\u{FFFD}"]);
+        assert!(cast.events.windows(2).all(|w| w[0].time <= w[1].time));
+    }
+
+    #[test]
+    fn test_parse_cast() {
+        let content =
+            "{\"version\": 2, \"width\": 10, \"height\": 5, \"idle_time_limit\": 1.5}\n\
+             [0.5, \"o\", \"ls\\r\\n\"]\n\
+             [0.7, \"i\", \"q\"]\n\
+             \n\
+             [1.25, \"o\", \"\\u001b[1mbold\"]\n";
+        let cast = Cast::parse(content.as_bytes()).unwrap();
+        assert_eq!((cast.width, cast.height), (10, 5));
+        assert_eq!(cast.idle_time_limit, Some(1.5));
+        assert_eq!(
+            cast.events,
+            vec![
+                CastEvent {
+                    time: 0.5,
+                    output: b"ls\r\n".to_vec(),
+                },
+                CastEvent {
+                    time: 1.25,
+                    output: b"\x1b[1mbold".to_vec(),
+                },
+            ]
+        );
+
+        let cast = Cast::from_raw(&[b'a'; RAW_CHUNK_SIZE + 1]);
+        assert_eq!(cast.events.len(), 2);
+        assert_eq!(cast.events[1].output, b"a");
+        assert_eq!(cast.events[1].time, RAW_CHUNK_INTERVAL);
+
+        let err = Cast::parse("{\"version\": 1, \"width\": 1, \"height\": 1}".as_bytes())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
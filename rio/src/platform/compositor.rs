@@ -0,0 +1,289 @@
+// Follows the focused workspace and output of sway or Hyprland over their IPC
+// sockets, so `workspace-rules` can be applied to the windows focused there.
+// A window keeps the rules of the workspace it was last focused on.
+//
+// sway speaks the i3 IPC protocol: messages are the "i3-ipc" magic followed by
+// the payload length and the message type as native endian u32, then a JSON
+// payload. Hyprland writes one `event>>data` line per event on its second
+// socket and answers `j/` prefixed queries with JSON on the first one.
+
+use crate::event::{EventListener, RioEvent};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use winit::window::WindowId;
+
+const I3_MAGIC: &[u8] = b"i3-ipc";
+const I3_GET_WORKSPACES: u32 = 1;
+const I3_SUBSCRIBE: u32 = 2;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Workspace {
+    pub name: String,
+    pub output: String,
+}
+
+/// Focused workspace and the workspace each window was last focused on.
+#[derive(Default)]
+pub struct Workspaces {
+    pub current: Option<Workspace>,
+    windows: HashMap<WindowId, Workspace>,
+}
+
+impl Workspaces {
+    /// Record the focused window as being on the current workspace, returns
+    /// the workspace it was on before and the current one if it moved.
+    pub fn focus(
+        &mut self,
+        window_id: WindowId,
+    ) -> Option<(Option<Workspace>, Workspace)> {
+        let current = self.current.clone()?;
+        let previous = self.windows.insert(window_id, current.clone());
+        if previous.as_ref() == Some(&current) {
+            return None;
+        }
+        Some((previous, current))
+    }
+
+    pub fn windows(&self) -> impl Iterator<Item = (&WindowId, &Workspace)> {
+        self.windows.iter()
+    }
+}
+
+/// Start following the compositor the session runs on, if it is supported.
+pub fn listen<T: EventListener + Send + 'static>(event_proxy: T) {
+    let listener = if let Some(socket) = std::env::var_os("SWAYSOCK") {
+        let socket = PathBuf::from(socket);
+        std::thread::Builder::new()
+            .name(String::from("sway ipc"))
+            .spawn(move || {
+                if let Err(err) = follow_sway(&socket, &event_proxy) {
+                    log::warn!("sway ipc stopped: {err}");
+                }
+            })
+    } else if let Some(directory) = hyprland_directory() {
+        std::thread::Builder::new()
+            .name(String::from("hyprland ipc"))
+            .spawn(move || {
+                if let Err(err) = follow_hyprland(&directory, &event_proxy) {
+                    log::warn!("hyprland ipc stopped: {err}");
+                }
+            })
+    } else {
+        log::info!("no supported compositor found, workspace rules are ignored");
+        return;
+    };
+
+    if let Err(err) = listener {
+        log::error!("unable to follow the compositor workspaces: {err}");
+    }
+}
+
+fn send<T: EventListener>(event_proxy: &T, workspace: Workspace) {
+    event_proxy.send_event(RioEvent::Workspace(workspace), WindowId::from(0));
+}
+
+fn i3_message(kind: u32, payload: &[u8]) -> Vec<u8> {
+    let mut message = I3_MAGIC.to_vec();
+    message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+    message.extend_from_slice(&kind.to_ne_bytes());
+    message.extend_from_slice(payload);
+    message
+}
+
+/// Read a message, returns its type and payload.
+fn read_i3_message(stream: &mut impl Read) -> std::io::Result<(u32, Vec<u8>)> {
+    let mut header = [0; 14];
+    stream.read_exact(&mut header)?;
+    if &header[..6] != I3_MAGIC {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "invalid i3 ipc magic",
+        ));
+    }
+    let length = u32::from_ne_bytes(header[6..10].try_into().unwrap());
+    let kind = u32::from_ne_bytes(header[10..14].try_into().unwrap());
+    let mut payload = vec![0; length as usize];
+    stream.read_exact(&mut payload)?;
+    Ok((kind, payload))
+}
+
+/// The focused workspace of a GET_WORKSPACES reply.
+fn sway_focused(payload: &[u8]) -> Option<Workspace> {
+    let workspaces: Vec<serde_json::Value> = serde_json::from_slice(payload).ok()?;
+    let focused = workspaces
+        .iter()
+        .find(|workspace| workspace["focused"].as_bool() == Some(true))?;
+    Some(Workspace {
+        name: focused["name"].as_str()?.to_string(),
+        output: focused["output"].as_str()?.to_string(),
+    })
+}
+
+fn follow_sway<T: EventListener>(
+    socket: &PathBuf,
+    event_proxy: &T,
+) -> std::io::Result<()> {
+    // Replies and events would interleave on a single connection.
+    let mut query = UnixStream::connect(socket)?;
+    let mut events = UnixStream::connect(socket)?;
+    events.write_all(&i3_message(I3_SUBSCRIBE, br#"["workspace","output"]"#))?;
+
+    let mut current = None;
+    loop {
+        query.write_all(&i3_message(I3_GET_WORKSPACES, b""))?;
+        let (_, payload) = read_i3_message(&mut query)?;
+        let focused = sway_focused(&payload);
+        if let Some(workspace) = focused.as_ref().filter(|_| focused != current) {
+            send(event_proxy, workspace.clone());
+            current = focused;
+        }
+
+        // Anything on the events connection, the subscribe reply included,
+        // asks for the focused workspace again.
+        read_i3_message(&mut events)?;
+    }
+}
+
+fn hyprland_directory() -> Option<PathBuf> {
+    let signature = std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE")?;
+    let runtime = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|runtime| PathBuf::from(runtime).join("hypr").join(&signature));
+    // Older versions keep their sockets in /tmp.
+    [runtime, Some(PathBuf::from("/tmp/hypr").join(&signature))]
+        .into_iter()
+        .flatten()
+        .find(|directory| directory.join(".socket2.sock").exists())
+}
+
+/// The workspace of an `activeworkspace` query.
+fn hyprland_active(payload: &[u8]) -> Option<Workspace> {
+    let workspace: serde_json::Value = serde_json::from_slice(payload).ok()?;
+    Some(Workspace {
+        name: workspace["name"].as_str()?.to_string(),
+        output: workspace["monitor"].as_str()?.to_string(),
+    })
+}
+
+/// Update `workspace` from an event line, returns `true` if it changed.
+fn hyprland_event(line: &str, workspace: &mut Workspace) -> bool {
+    let Some((event, data)) = line.split_once(">>") else {
+        return false;
+    };
+    let previous = workspace.clone();
+    match event {
+        "workspace" => workspace.name = data.to_string(),
+        "focusedmon" => {
+            if let Some((output, name)) = data.split_once(',') {
+                workspace.output = output.to_string();
+                workspace.name = name.to_string();
+            }
+        }
+        _ => {}
+    }
+    *workspace != previous
+}
+
+fn follow_hyprland<T: EventListener>(
+    directory: &std::path::Path,
+    event_proxy: &T,
+) -> std::io::Result<()> {
+    let mut query = UnixStream::connect(directory.join(".socket.sock"))?;
+    query.write_all(b"j/activeworkspace")?;
+    let mut payload = Vec::new();
+    query.read_to_end(&mut payload)?;
+
+    let mut workspace = hyprland_active(&payload).unwrap_or_default();
+    if workspace != Workspace::default() {
+        send(event_proxy, workspace.clone());
+    }
+
+    let events = UnixStream::connect(directory.join(".socket2.sock"))?;
+    for line in BufReader::new(events).lines() {
+        if hyprland_event(&line?, &mut workspace) {
+            send(event_proxy, workspace.clone());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspaces() {
+        let window = WindowId::from(1);
+        let first = Workspace {
+            name: String::from("1"),
+            output: String::from("eDP-1"),
+        };
+        let mut workspaces = Workspaces::default();
+        assert_eq!(workspaces.focus(window), None);
+
+        workspaces.current = Some(first.clone());
+        assert_eq!(workspaces.focus(window), Some((None, first.clone())));
+        assert_eq!(workspaces.focus(window), None);
+
+        let second = Workspace {
+            name: String::from("2"),
+            ..first.clone()
+        };
+        workspaces.current = Some(second.clone());
+        assert_eq!(
+            workspaces.focus(window),
+            Some((Some(first), second.clone()))
+        );
+        assert_eq!(workspaces.windows().count(), 1);
+    }
+
+    #[test]
+    fn test_i3_message() {
+        let message = i3_message(I3_SUBSCRIBE, b"[]");
+        let (kind, payload) = read_i3_message(&mut message.as_slice()).unwrap();
+        assert_eq!(kind, I3_SUBSCRIBE);
+        assert_eq!(payload, b"[]");
+        assert!(read_i3_message(&mut &b"i3-ipX\0\0\0\0\0\0\0\0"[..]).is_err());
+    }
+
+    #[test]
+    fn test_sway_focused() {
+        let payload = br#"[
+            {"num": 1, "name": "1", "focused": false, "output": "eDP-1"},
+            {"num": 3, "name": "3: web", "focused": true, "output": "HDMI-A-1"}
+        ]"#;
+        assert_eq!(
+            sway_focused(payload),
+            Some(Workspace {
+                name: String::from("3: web"),
+                output: String::from("HDMI-A-1"),
+            })
+        );
+        assert_eq!(sway_focused(b"[]"), None);
+        assert_eq!(sway_focused(b"{"), None);
+    }
+
+    #[test]
+    fn test_hyprland() {
+        let mut workspace = hyprland_active(
+            br#"{"id": 1, "name": "1", "monitor": "eDP-1", "windows": 2}"#,
+        )
+        .unwrap();
+        assert_eq!(workspace.output, "eDP-1");
+
+        assert!(hyprland_event("workspace>>2", &mut workspace));
+        assert_eq!(workspace.name, "2");
+        assert!(!hyprland_event("workspace>>2", &mut workspace));
+        assert!(!hyprland_event("activewindow>>rio,~", &mut workspace));
+
+        assert!(hyprland_event("focusedmon>>HDMI-A-1,5", &mut workspace));
+        assert_eq!(
+            workspace,
+            Workspace {
+                name: String::from("5"),
+                output: String::from("HDMI-A-1"),
+            }
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use rio_config::ScaleFactor;
+
+/// Resolves the configured scale factor against the one `reported` by winit,
+/// `system` is only asked when the reported one might be a missing setting.
+pub fn resolve(
+    scale_factor: ScaleFactor,
+    reported: f64,
+    system: impl FnOnce() -> Option<f64>,
+) -> f64 {
+    match scale_factor {
+        ScaleFactor::Fixed(value) => value as f64,
+        ScaleFactor::Auto if reported == 1.0 => system().unwrap_or(reported),
+        ScaleFactor::Auto => reported,
+    }
+}
+
+/// Scale factor set for X11 sessions outside of winit's reach: `Xft.dpi`
+/// from the X resources, then the GNOME `scaling-factor`.
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn x11_scale_factor() -> Option<f64> {
+    let output = |program: &str, args: &[&str]| {
+        std::process::Command::new(program)
+            .args(args)
+            .output()
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+    };
+
+    output("xrdb", &["-query"])
+        .and_then(|resources| xft_dpi(&resources))
+        .map(|dpi| dpi / 96.)
+        .or_else(|| {
+            output(
+                "gsettings",
+                &["get", "org.gnome.desktop.interface", "scaling-factor"],
+            )
+            .and_then(|value| gsettings_scaling_factor(&value))
+        })
+        .filter(|scale| *scale > 1.0)
+}
+
+/// `Xft.dpi` of the output of `xrdb -query`.
+#[cfg_attr(any(target_os = "macos", windows), allow(dead_code))]
+fn xft_dpi(resources: &str) -> Option<f64> {
+    resources.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        if name.trim() != "Xft.dpi" {
+            return None;
+        }
+        value.trim().parse::<f64>().ok().filter(|dpi| *dpi > 0.)
+    })
+}
+
+/// Value printed by `gsettings get`, like `uint32 2`. Zero picks the scale
+/// from the monitor, which is what winit reported already.
+#[cfg_attr(any(target_os = "macos", windows), allow(dead_code))]
+fn gsettings_scaling_factor(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let value = value.strip_prefix("uint32 ").unwrap_or(value);
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|scale| *scale > 0)
+        .map(f64::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let system = || Some(2.0);
+        assert_eq!(resolve(ScaleFactor::Auto, 1.0, system), 2.0);
+        assert_eq!(resolve(ScaleFactor::Auto, 1.0, || None), 1.0);
+        assert_eq!(resolve(ScaleFactor::Auto, 1.5, || panic!("not asked")), 1.5);
+        assert_eq!(resolve(ScaleFactor::Fixed(1.25), 2.0, system), 1.25);
+    }
+
+    #[test]
+    fn test_xft_dpi() {
+        let resources = "Xcursor.size:\t24\nXft.antialias:\t1\nXft.dpi:\t192\n";
+        assert_eq!(xft_dpi(resources), Some(192.));
+        assert_eq!(xft_dpi("Xft.dpi:\tlarge\n"), None);
+        assert_eq!(xft_dpi("Xft.dpi2:\t144\n"), None);
+        assert_eq!(xft_dpi(""), None);
+    }
+
+    #[test]
+    fn test_gsettings_scaling_factor() {
+        assert_eq!(gsettings_scaling_factor("uint32 2\n"), Some(2.));
+        assert_eq!(gsettings_scaling_factor("uint32 0\n"), None);
+        assert_eq!(gsettings_scaling_factor(""), None);
+    }
+}
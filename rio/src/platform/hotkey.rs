@@ -0,0 +1,576 @@
+// System-wide hotkey that shows or hides the windows. X11 grabs the key on
+// the root window, macOS registers a Carbon hot key and Windows a thread hot
+// key. On Wayland it is up to the compositor.
+
+use crate::event::{EventProxy, RioEvent, RioEventType};
+use rio_config::bindings::GlobalHotkey;
+use winit::keyboard::ModifiersState;
+
+/// Registered hotkey, the key is released once dropped.
+pub struct Hotkey {
+    release: Option<Box<dyn FnOnce()>>,
+}
+
+impl Drop for Hotkey {
+    fn drop(&mut self) {
+        if let Some(release) = self.release.take() {
+            release();
+        }
+    }
+}
+
+/// Parse the `with` field of the hotkey, using the key bindings names.
+pub fn modifiers(with: &str) -> Result<ModifiersState, String> {
+    let mut res = ModifiersState::empty();
+    for modifier in with.split('|') {
+        match modifier.trim().to_lowercase().as_str() {
+            "command" | "super" => res.insert(ModifiersState::SUPER),
+            "shift" => res.insert(ModifiersState::SHIFT),
+            "alt" | "option" => res.insert(ModifiersState::ALT),
+            "control" => res.insert(ModifiersState::CONTROL),
+            "none" | "" => (),
+            other => return Err(format!("unknown modifier `{other}`")),
+        }
+    }
+    Ok(res)
+}
+
+/// Grab `hotkey` and send `RioEvent::ToggleVisibility` whenever it is pressed.
+pub fn register(
+    hotkey: &GlobalHotkey,
+    event_proxy: EventProxy,
+) -> Result<Hotkey, String> {
+    let modifiers = modifiers(&hotkey.with)?;
+
+    #[cfg(target_os = "macos")]
+    {
+        macos::register(&hotkey.key, modifiers, event_proxy)
+    }
+
+    #[cfg(windows)]
+    {
+        windows::register(&hotkey.key, modifiers, event_proxy)
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    {
+        #[cfg(feature = "x11")]
+        {
+            if std::env::var_os("WAYLAND_DISPLAY").is_none() {
+                return x11::register(hotkey.key.to_owned(), modifiers, event_proxy);
+            }
+        }
+
+        let _ = (modifiers, event_proxy);
+        Err(String::from("global hotkeys are not supported on Wayland"))
+    }
+}
+
+fn toggle_visibility(event_proxy: &EventProxy) {
+    event_proxy.send_event(
+        RioEventType::Rio(RioEvent::ToggleVisibility),
+        winit::window::WindowId::from(0),
+    );
+}
+
+/// Key code of the named key in `table`, letters and digits are looked up by
+/// their character.
+#[cfg(any(target_os = "macos", windows))]
+fn key_code(key: &str, table: &[(&str, u32)]) -> Option<u32> {
+    table
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|(_, code)| *code)
+}
+
+#[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
+mod x11 {
+    use super::{toggle_visibility, Hotkey};
+    use crate::event::EventProxy;
+    use crate::performer::spawn_named;
+    use std::ffi::CString;
+    use std::mem::MaybeUninit;
+    use std::os::raw::c_int;
+    use std::ptr;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use winit::keyboard::ModifiersState;
+    use x11_dl::xlib;
+
+    // Set by the error handler when the key is already grabbed by another
+    // client, the handler is installed only while grabbing.
+    static BAD_ACCESS: AtomicBool = AtomicBool::new(false);
+
+    unsafe extern "C" fn catch_bad_access(
+        _display: *mut xlib::Display,
+        event: *mut xlib::XErrorEvent,
+    ) -> c_int {
+        if (*event).error_code == xlib::BadAccess {
+            BAD_ACCESS.store(true, Ordering::Relaxed);
+        }
+        0
+    }
+
+    pub fn register(
+        key: String,
+        modifiers: ModifiersState,
+        event_proxy: EventProxy,
+    ) -> Result<Hotkey, String> {
+        let xlib = xlib::Xlib::open().map_err(|err| err.to_string())?;
+        let name = CString::new(key.as_str()).map_err(|err| err.to_string())?;
+
+        let mut mask = 0;
+        if modifiers.shift_key() {
+            mask |= xlib::ShiftMask;
+        }
+        if modifiers.control_key() {
+            mask |= xlib::ControlMask;
+        }
+        if modifiers.alt_key() {
+            mask |= xlib::Mod1Mask;
+        }
+        if modifiers.super_key() {
+            mask |= xlib::Mod4Mask;
+        }
+
+        // The connection is owned by the thread, Xlib calls are not shared
+        // with the one used by winit.
+        let display = unsafe { (xlib.XOpenDisplay)(ptr::null()) };
+        if display.is_null() {
+            return Err(String::from("unable to open the X11 display"));
+        }
+
+        let keysym = unsafe { (xlib.XStringToKeysym)(name.as_ptr()) };
+        let keycode = unsafe { (xlib.XKeysymToKeycode)(display, keysym) };
+        if keysym == 0 || keycode == 0 {
+            unsafe { (xlib.XCloseDisplay)(display) };
+            return Err(format!("unknown key `{key}`"));
+        }
+
+        // Grab the combination with Caps Lock and Num Lock in any state. The
+        // errors are only reported once the server processed the requests.
+        let root = unsafe { (xlib.XDefaultRootWindow)(display) };
+        BAD_ACCESS.store(false, Ordering::Relaxed);
+        let previous = unsafe { (xlib.XSetErrorHandler)(Some(catch_bad_access)) };
+        for locks in [
+            0,
+            xlib::LockMask,
+            xlib::Mod2Mask,
+            xlib::LockMask | xlib::Mod2Mask,
+        ] {
+            unsafe {
+                (xlib.XGrabKey)(
+                    display,
+                    keycode as i32,
+                    mask | locks,
+                    root,
+                    xlib::False,
+                    xlib::GrabModeAsync,
+                    xlib::GrabModeAsync,
+                );
+            }
+        }
+        unsafe {
+            (xlib.XSync)(display, xlib::False);
+            (xlib.XSetErrorHandler)(previous);
+        }
+        if BAD_ACCESS.load(Ordering::Relaxed) {
+            unsafe { (xlib.XCloseDisplay)(display) };
+            return Err(format!("`{key}` is already grabbed by another application"));
+        }
+
+        // Written to once the hotkey is dropped, to stop waiting for events.
+        let mut pipe = [0; 2];
+        if unsafe { libc::pipe(pipe.as_mut_ptr()) } != 0 {
+            unsafe { (xlib.XCloseDisplay)(display) };
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        let [stop_reader, stop_writer] = pipe;
+
+        // Raw pointers are not Send, the display is only used by the thread.
+        let display = display as usize;
+        let thread = spawn_named("global hotkey", move || {
+            let display = display as *mut xlib::Display;
+            let mut fds = [
+                libc::pollfd {
+                    fd: unsafe { (xlib.XConnectionNumber)(display) },
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+                libc::pollfd {
+                    fd: stop_reader,
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+            ];
+            let mut event = MaybeUninit::<xlib::XEvent>::uninit();
+            loop {
+                while unsafe { (xlib.XPending)(display) } > 0 {
+                    unsafe { (xlib.XNextEvent)(display, event.as_mut_ptr()) };
+                    if unsafe { event.assume_init_ref().get_type() } == xlib::KeyPress {
+                        toggle_visibility(&event_proxy);
+                    }
+                }
+
+                if unsafe { libc::poll(fds.as_mut_ptr(), 2, -1) } < 0 {
+                    if std::io::Error::last_os_error().kind()
+                        == std::io::ErrorKind::Interrupted
+                    {
+                        continue;
+                    }
+                    break;
+                }
+                if fds[1].revents != 0 {
+                    break;
+                }
+            }
+
+            // Closing the connection releases the grabs.
+            unsafe {
+                (xlib.XCloseDisplay)(display);
+                libc::close(stop_reader);
+            }
+        });
+
+        Ok(Hotkey {
+            release: Some(Box::new(move || {
+                unsafe {
+                    libc::write(stop_writer, [0u8].as_ptr().cast(), 1);
+                    libc::close(stop_writer);
+                }
+                // The key is grabbed again right away when the config changes.
+                let _ = thread.join();
+            })),
+        })
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::{key_code, toggle_visibility, Hotkey};
+    use crate::event::EventProxy;
+    use std::ffi::c_void;
+    use std::ptr;
+    use winit::keyboard::ModifiersState;
+
+    type OSStatus = i32;
+    type EventHandlerUPP =
+        extern "C" fn(*mut c_void, *mut c_void, *mut c_void) -> OSStatus;
+
+    #[repr(C)]
+    struct EventTypeSpec {
+        event_class: u32,
+        event_kind: u32,
+    }
+
+    #[repr(C)]
+    struct EventHotKeyID {
+        signature: u32,
+        id: u32,
+    }
+
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        fn GetApplicationEventTarget() -> *mut c_void;
+        fn InstallEventHandler(
+            target: *mut c_void,
+            handler: EventHandlerUPP,
+            num_types: u32,
+            list: *const EventTypeSpec,
+            user_data: *mut c_void,
+            out_ref: *mut *mut c_void,
+        ) -> OSStatus;
+        fn RemoveEventHandler(handler: *mut c_void) -> OSStatus;
+        fn RegisterEventHotKey(
+            key_code: u32,
+            modifiers: u32,
+            id: EventHotKeyID,
+            target: *mut c_void,
+            options: u32,
+            out_ref: *mut *mut c_void,
+        ) -> OSStatus;
+        fn UnregisterEventHotKey(hotkey: *mut c_void) -> OSStatus;
+    }
+
+    // 'keyb' and kEventHotKeyPressed.
+    const EVENT_CLASS_KEYBOARD: u32 = 0x6b65_7962;
+    const EVENT_HOT_KEY_PRESSED: u32 = 5;
+    // 'rio ', identifies the hot key in the events.
+    const SIGNATURE: u32 = 0x7269_6f20;
+    // Returned by RegisterEventHotKey when another application has the key.
+    const EVENT_HOT_KEY_EXISTS_ERR: OSStatus = -9878;
+
+    const CMD_KEY: u32 = 1 << 8;
+    const SHIFT_KEY: u32 = 1 << 9;
+    const OPTION_KEY: u32 = 1 << 11;
+    const CONTROL_KEY: u32 = 1 << 12;
+
+    // Virtual key codes of the ANSI layout, from Events.h.
+    const KEY_CODES: &[(&str, u32)] = &[
+        ("a", 0x00),
+        ("s", 0x01),
+        ("d", 0x02),
+        ("f", 0x03),
+        ("h", 0x04),
+        ("g", 0x05),
+        ("z", 0x06),
+        ("x", 0x07),
+        ("c", 0x08),
+        ("v", 0x09),
+        ("b", 0x0b),
+        ("q", 0x0c),
+        ("w", 0x0d),
+        ("e", 0x0e),
+        ("r", 0x0f),
+        ("y", 0x10),
+        ("t", 0x11),
+        ("1", 0x12),
+        ("2", 0x13),
+        ("3", 0x14),
+        ("4", 0x15),
+        ("6", 0x16),
+        ("5", 0x17),
+        ("9", 0x19),
+        ("7", 0x1a),
+        ("8", 0x1c),
+        ("0", 0x1d),
+        ("o", 0x1f),
+        ("u", 0x20),
+        ("i", 0x22),
+        ("p", 0x23),
+        ("return", 0x24),
+        ("l", 0x25),
+        ("j", 0x26),
+        ("k", 0x28),
+        ("n", 0x2d),
+        ("m", 0x2e),
+        ("tab", 0x30),
+        ("space", 0x31),
+        ("grave", 0x32),
+        ("escape", 0x35),
+        ("f17", 0x40),
+        ("f18", 0x4f),
+        ("f19", 0x50),
+        ("f20", 0x5a),
+        ("f5", 0x60),
+        ("f6", 0x61),
+        ("f7", 0x62),
+        ("f3", 0x63),
+        ("f8", 0x64),
+        ("f9", 0x65),
+        ("f11", 0x67),
+        ("f13", 0x69),
+        ("f16", 0x6a),
+        ("f14", 0x6b),
+        ("f10", 0x6d),
+        ("f12", 0x6f),
+        ("f15", 0x71),
+        ("f4", 0x76),
+        ("f2", 0x78),
+        ("f1", 0x7a),
+    ];
+
+    extern "C" fn handle_hot_key(
+        _call: *mut c_void,
+        _event: *mut c_void,
+        user_data: *mut c_void,
+    ) -> OSStatus {
+        let event_proxy = unsafe { &*(user_data as *const EventProxy) };
+        toggle_visibility(event_proxy);
+        0
+    }
+
+    pub fn register(
+        key: &str,
+        modifiers: ModifiersState,
+        event_proxy: EventProxy,
+    ) -> Result<Hotkey, String> {
+        let code = key_code(key, KEY_CODES).ok_or(format!("unknown key `{key}`"))?;
+
+        let mut mask = 0;
+        if modifiers.shift_key() {
+            mask |= SHIFT_KEY;
+        }
+        if modifiers.control_key() {
+            mask |= CONTROL_KEY;
+        }
+        if modifiers.alt_key() {
+            mask |= OPTION_KEY;
+        }
+        if modifiers.super_key() {
+            mask |= CMD_KEY;
+        }
+
+        // Carbon events are dispatched on the main thread by the application
+        // run loop, the proxy is freed when the hotkey is released.
+        let user_data = Box::into_raw(Box::new(event_proxy));
+        let target = unsafe { GetApplicationEventTarget() };
+        let spec = EventTypeSpec {
+            event_class: EVENT_CLASS_KEYBOARD,
+            event_kind: EVENT_HOT_KEY_PRESSED,
+        };
+        let mut handler = ptr::null_mut();
+        let status = unsafe {
+            InstallEventHandler(
+                target,
+                handle_hot_key,
+                1,
+                &spec,
+                user_data.cast(),
+                &mut handler,
+            )
+        };
+        if status != 0 {
+            drop(unsafe { Box::from_raw(user_data) });
+            return Err(format!("unable to install the event handler ({status})"));
+        }
+
+        let mut hotkey = ptr::null_mut();
+        let id = EventHotKeyID {
+            signature: SIGNATURE,
+            id: 1,
+        };
+        let status =
+            unsafe { RegisterEventHotKey(code, mask, id, target, 0, &mut hotkey) };
+        if status != 0 {
+            unsafe { RemoveEventHandler(handler) };
+            drop(unsafe { Box::from_raw(user_data) });
+            return Err(if status == EVENT_HOT_KEY_EXISTS_ERR {
+                format!("`{key}` is already registered by another application")
+            } else {
+                format!("unable to register `{key}` ({status})")
+            });
+        }
+
+        Ok(Hotkey {
+            release: Some(Box::new(move || unsafe {
+                UnregisterEventHotKey(hotkey);
+                RemoveEventHandler(handler);
+                drop(Box::from_raw(user_data));
+            })),
+        })
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::{key_code, toggle_visibility, Hotkey};
+    use crate::event::EventProxy;
+    use crate::performer::spawn_named;
+    use std::sync::mpsc;
+    use windows_sys::Win32::Foundation::{GetLastError, ERROR_HOTKEY_ALREADY_REGISTERED};
+    use windows_sys::Win32::System::Threading::GetCurrentThreadId;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+        RegisterHotKey, UnregisterHotKey, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT,
+        MOD_WIN, VK_F1, VK_OEM_3,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetMessageW, PeekMessageW, PostThreadMessageW, MSG, PM_NOREMOVE, WM_HOTKEY,
+        WM_QUIT,
+    };
+    use winit::keyboard::ModifiersState;
+
+    const KEY_CODES: &[(&str, u32)] = &[
+        ("tab", 0x09),
+        ("return", 0x0d),
+        ("escape", 0x1b),
+        ("space", 0x20),
+        ("grave", VK_OEM_3 as u32),
+    ];
+
+    /// Letters and digits use their upper case character as virtual key.
+    fn virtual_key(key: &str) -> Option<u32> {
+        match key.as_bytes() {
+            [byte] if byte.is_ascii_alphanumeric() => {
+                Some(u32::from(byte.to_ascii_uppercase()))
+            }
+            [b'f' | b'F', number @ ..] if !number.is_empty() => {
+                let number: u32 = std::str::from_utf8(number).ok()?.parse().ok()?;
+                (1..=24)
+                    .contains(&number)
+                    .then(|| u32::from(VK_F1) + number - 1)
+            }
+            _ => key_code(key, KEY_CODES),
+        }
+    }
+
+    pub fn register(
+        key: &str,
+        modifiers: ModifiersState,
+        event_proxy: EventProxy,
+    ) -> Result<Hotkey, String> {
+        let code = virtual_key(key).ok_or(format!("unknown key `{key}`"))?;
+
+        let mut mask = MOD_NOREPEAT;
+        if modifiers.shift_key() {
+            mask |= MOD_SHIFT;
+        }
+        if modifiers.control_key() {
+            mask |= MOD_CONTROL;
+        }
+        if modifiers.alt_key() {
+            mask |= MOD_ALT;
+        }
+        if modifiers.super_key() {
+            mask |= MOD_WIN;
+        }
+
+        // A hot key without a window posts WM_HOTKEY to the thread that
+        // registered it, the thread has its own message loop so winit does
+        // not have to know about it.
+        let (sender, receiver) = mpsc::channel();
+        let key = key.to_owned();
+        let thread = spawn_named("global hotkey", move || {
+            let mut msg: MSG = unsafe { std::mem::zeroed() };
+            // Creates the message queue of the thread before it is posted to.
+            unsafe { PeekMessageW(&mut msg, 0, 0, 0, PM_NOREMOVE) };
+
+            if unsafe { RegisterHotKey(0, 1, mask, code) } == 0 {
+                let _ = sender.send(Err(
+                    if unsafe { GetLastError() } == ERROR_HOTKEY_ALREADY_REGISTERED {
+                        format!("`{key}` is already registered by another application")
+                    } else {
+                        std::io::Error::last_os_error().to_string()
+                    },
+                ));
+                return;
+            }
+            let _ = sender.send(Ok(unsafe { GetCurrentThreadId() }));
+
+            while unsafe { GetMessageW(&mut msg, 0, 0, 0) } > 0 {
+                if msg.message == WM_HOTKEY {
+                    toggle_visibility(&event_proxy);
+                }
+            }
+
+            unsafe { UnregisterHotKey(0, 1) };
+        });
+
+        let thread_id = receiver
+            .recv()
+            .map_err(|err| err.to_string())
+            .and_then(|res| res)?;
+
+        Ok(Hotkey {
+            release: Some(Box::new(move || {
+                unsafe { PostThreadMessageW(thread_id, WM_QUIT, 0, 0) };
+                let _ = thread.join();
+            })),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modifiers() {
+        assert_eq!(
+            modifiers("control | shift").unwrap(),
+            ModifiersState::CONTROL | ModifiersState::SHIFT
+        );
+        assert_eq!(modifiers("Super").unwrap(), ModifiersState::SUPER);
+        assert_eq!(modifiers("").unwrap(), ModifiersState::empty());
+        assert!(modifiers("hyper").is_err());
+    }
+}
@@ -1,2 +1,9 @@
+#[cfg(all(feature = "wayland-ipc", not(any(target_os = "macos", windows))))]
+pub mod compositor;
+pub mod dpi;
+pub mod hotkey;
 #[cfg(target_os = "macos")]
 pub mod macos;
+pub mod motion;
+pub mod power;
+pub mod taskbar;
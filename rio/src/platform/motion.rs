@@ -0,0 +1,174 @@
+use crate::event::{EventListener, RioEvent};
+use rio_config::ReduceMotion;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+use std::time::Duration;
+use winit::window::WindowId;
+
+/// How often the system preference is checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Whether the last check found the system asking for reduced motion.
+static PREFERS_REDUCED_MOTION: AtomicBool = AtomicBool::new(false);
+
+/// Animations played by the renderer, decided in one place from the config
+/// and the preference of the system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Animations {
+    /// The cursor blinks when the config and the program ask for it.
+    pub blinking_cursor: bool,
+    /// The visual bell fades out, it is shown whole until it ends otherwise.
+    pub bell_fade: bool,
+    /// Page and prompt actions slide the viewport to its new place.
+    pub smooth_scroll: bool,
+}
+
+impl Animations {
+    pub fn new(reduce_motion: ReduceMotion) -> Animations {
+        let reduced = reduces_motion(reduce_motion);
+        Animations {
+            blinking_cursor: !reduced,
+            bell_fade: !reduced,
+            smooth_scroll: !reduced,
+        }
+    }
+
+    /// Effects left out with the low power profile, the cursor keeps
+    /// blinking at a slower pace.
+    pub fn low_power(self) -> Animations {
+        Animations {
+            bell_fade: false,
+            smooth_scroll: false,
+            ..self
+        }
+    }
+}
+
+/// Resolves the configured reduce motion into whether animations are left
+/// out, `Auto` follows the system preference found by [`watch`].
+pub fn reduces_motion(reduce_motion: ReduceMotion) -> bool {
+    match reduce_motion {
+        ReduceMotion::Always => true,
+        ReduceMotion::Never => false,
+        ReduceMotion::Auto => PREFERS_REDUCED_MOTION.load(Ordering::Relaxed),
+    }
+}
+
+/// Check the system preference from a thread, `ReduceMotion` is sent once it
+/// changes. Only the first call starts the thread.
+pub fn watch<T: EventListener + Send + 'static>(event_proxy: T) {
+    static WATCH: Once = Once::new();
+    WATCH.call_once(|| {
+        let watcher = std::thread::Builder::new()
+            .name(String::from("reduce motion"))
+            .spawn(move || loop {
+                let reduced = prefers_reduced_motion();
+                if PREFERS_REDUCED_MOTION.swap(reduced, Ordering::Relaxed) != reduced {
+                    log::info!("system prefers reduced motion: {reduced}");
+                    event_proxy.send_event(RioEvent::ReduceMotion, WindowId::from(0));
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            });
+        if let Err(err) = watcher {
+            log::error!("unable to follow the reduced motion preference: {err}");
+        }
+    });
+}
+
+#[cfg(target_os = "macos")]
+fn prefers_reduced_motion() -> bool {
+    match std::process::Command::new("defaults")
+        .args(["read", "com.apple.universalaccess", "reduceMotion"])
+        .output()
+    {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).trim() == "1",
+        Err(_) => false,
+    }
+}
+
+#[cfg(windows)]
+fn prefers_reduced_motion() -> bool {
+    use windows_sys::Win32::Foundation::BOOL;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SPI_GETCLIENTAREAANIMATION,
+    };
+
+    let mut animations: BOOL = 1;
+    let read = unsafe {
+        SystemParametersInfoW(
+            SPI_GETCLIENTAREAANIMATION,
+            0,
+            &mut animations as *mut BOOL as *mut std::ffi::c_void,
+            0,
+        )
+    };
+    read != 0 && animations == 0
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn prefers_reduced_motion() -> bool {
+    // GNOME and the desktops following its settings.
+    if let Ok(output) = std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", "enable-animations"])
+        .output()
+    {
+        if String::from_utf8_lossy(&output.stdout).trim() == "false" {
+            return true;
+        }
+    }
+
+    let kdeglobals = dirs::config_dir()
+        .map(|directory| directory.join("kdeglobals"))
+        .and_then(|path| std::fs::read_to_string(path).ok());
+    kdeglobals.is_some_and(|kdeglobals| kde_disables_animations(&kdeglobals))
+}
+
+/// KDE turns animations off with a speed factor of zero.
+#[cfg(not(any(target_os = "macos", windows)))]
+fn kde_disables_animations(kdeglobals: &str) -> bool {
+    kdeglobals.lines().any(|line| {
+        line.trim()
+            .strip_prefix("AnimationDurationFactor=")
+            .and_then(|factor| factor.trim().parse::<f32>().ok())
+            == Some(0.)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduces_motion() {
+        assert!(reduces_motion(ReduceMotion::Always));
+        assert!(!reduces_motion(ReduceMotion::Never));
+        assert_eq!(
+            Animations::new(ReduceMotion::Always),
+            Animations {
+                blinking_cursor: false,
+                bell_fade: false,
+                smooth_scroll: false,
+            }
+        );
+        assert_eq!(
+            Animations::new(ReduceMotion::Never).low_power(),
+            Animations {
+                blinking_cursor: true,
+                bell_fade: false,
+                smooth_scroll: false,
+            }
+        );
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    #[test]
+    fn test_kde_disables_animations() {
+        assert!(kde_disables_animations(
+            "[KDE]\nAnimationDurationFactor=0\nLookAndFeelPackage=org.kde.breeze"
+        ));
+        assert!(!kde_disables_animations(
+            "[KDE]\nAnimationDurationFactor=0.5\n"
+        ));
+        assert!(!kde_disables_animations("[General]\nColorScheme=Breeze\n"));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::event::{EventListener, RioEvent};
+use rio_config::Performance;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+use std::time::Duration;
+use winit::window::WindowId;
+
+/// How often the power source is checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Whether the last check found the system on battery power.
+static ON_BATTERY: AtomicBool = AtomicBool::new(false);
+
+/// Resolves the configured performance into whether the low power profile
+/// should be used, `Auto` follows the power source found by [`watch`].
+pub fn is_low_power(performance: Performance) -> bool {
+    match performance {
+        Performance::High => false,
+        Performance::Low => true,
+        Performance::Auto => ON_BATTERY.load(Ordering::Relaxed),
+    }
+}
+
+/// Check the power source right away, for the reports that cannot wait for
+/// [`watch`].
+pub fn check() {
+    ON_BATTERY.store(is_on_battery(), Ordering::Relaxed);
+}
+
+/// Check the power source from a thread, `PowerSource` is sent once it
+/// changes. Only the first call starts the thread.
+pub fn watch<T: EventListener + Send + 'static>(event_proxy: T) {
+    static WATCH: Once = Once::new();
+    WATCH.call_once(|| {
+        let watcher = std::thread::Builder::new()
+            .name(String::from("power source"))
+            .spawn(move || loop {
+                let on_battery = is_on_battery();
+                if ON_BATTERY.swap(on_battery, Ordering::Relaxed) != on_battery {
+                    log::info!("running on battery power: {on_battery}");
+                    event_proxy.send_event(RioEvent::PowerSource, WindowId::from(0));
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            });
+        if let Err(err) = watcher {
+            log::error!("unable to follow the power source: {err}");
+        }
+    });
+}
+
+#[cfg(target_os = "macos")]
+fn is_on_battery() -> bool {
+    match std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+    {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).contains("'Battery Power'"),
+        Err(_) => false,
+    }
+}
+
+#[cfg(windows)]
+fn is_on_battery() -> bool {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return false;
+    }
+
+    // 0 is offline, 1 is online and 255 is unknown.
+    status.ACLineStatus == 0
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn is_on_battery() -> bool {
+    let entries = match std::fs::read_dir("/sys/class/power_supply") {
+        Ok(entries) => entries,
+        Err(_) => return false,
+    };
+
+    let mut has_battery = false;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let kind = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+        match kind.trim() {
+            "Mains" | "USB" => {
+                let online =
+                    std::fs::read_to_string(path.join("online")).unwrap_or_default();
+                if online.trim() == "1" {
+                    return false;
+                }
+            }
+            "Battery" => {
+                let status =
+                    std::fs::read_to_string(path.join("status")).unwrap_or_default();
+                has_battery |= status.trim() == "Discharging";
+            }
+            _ => {}
+        }
+    }
+
+    has_battery
+}
@@ -0,0 +1,188 @@
+// Progress reported with `OSC 9 ; 4` shown outside of the window: on the
+// taskbar button through ITaskbarList3 on Windows, and on Linux through the
+// Unity launcher API, a D-Bus signal followed by the KDE task manager, Dash
+// to Dock and Plank among others, with the `launcher-progress` feature.
+// macOS is left out.
+
+use crate::ansi::Progress;
+use winit::window::Window;
+
+#[cfg(windows)]
+pub fn set_progress(window: &Window, progress: Progress) {
+    use crate::ansi::ProgressState;
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+    use std::ffi::c_void;
+    use windows_sys::core::{GUID, HRESULT};
+    use windows_sys::Win32::Foundation::HWND;
+    use windows_sys::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED,
+    };
+    use windows_sys::Win32::UI::Shell::{
+        TaskbarList, TBPFLAG, TBPF_ERROR, TBPF_INDETERMINATE, TBPF_NOPROGRESS,
+        TBPF_NORMAL, TBPF_PAUSED,
+    };
+
+    const IID_ITASKBARLIST3: GUID =
+        GUID::from_u128(0xea1afb91_9e28_4b86_90e9_9e9f8a5eefaf);
+
+    // windows-sys has no COM interfaces, the methods that are not called
+    // are only there for the layout.
+    #[repr(C)]
+    struct TaskbarList3Vtbl {
+        _query_interface: usize,
+        _add_ref: usize,
+        release: unsafe extern "system" fn(*mut c_void) -> u32,
+        hr_init: unsafe extern "system" fn(*mut c_void) -> HRESULT,
+        _add_tab: usize,
+        _delete_tab: usize,
+        _activate_tab: usize,
+        _set_active_alt: usize,
+        _mark_fullscreen_window: usize,
+        set_progress_value:
+            unsafe extern "system" fn(*mut c_void, HWND, u64, u64) -> HRESULT,
+        set_progress_state:
+            unsafe extern "system" fn(*mut c_void, HWND, TBPFLAG) -> HRESULT,
+    }
+
+    let RawWindowHandle::Win32(handle) = window.raw_window_handle() else {
+        return;
+    };
+    let hwnd = handle.hwnd as HWND;
+    let state = match progress.state {
+        ProgressState::Hidden => TBPF_NOPROGRESS,
+        ProgressState::Normal => TBPF_NORMAL,
+        ProgressState::Error => TBPF_ERROR,
+        ProgressState::Indeterminate => TBPF_INDETERMINATE,
+        ProgressState::Paused => TBPF_PAUSED,
+    };
+
+    unsafe {
+        // Fails without harm when the thread has COM initialized already.
+        CoInitializeEx(std::ptr::null(), COINIT_APARTMENTTHREADED);
+
+        let mut taskbar: *mut c_void = std::ptr::null_mut();
+        let created = CoCreateInstance(
+            &TaskbarList,
+            std::ptr::null_mut(),
+            CLSCTX_INPROC_SERVER,
+            &IID_ITASKBARLIST3,
+            &mut taskbar,
+        );
+        if created < 0 || taskbar.is_null() {
+            log::warn!("unable to reach the taskbar: {created:#x}");
+            return;
+        }
+
+        let vtable = &**(taskbar as *mut *const TaskbarList3Vtbl);
+        if (vtable.hr_init)(taskbar) >= 0 {
+            (vtable.set_progress_state)(taskbar, hwnd, state);
+            if matches!(state, TBPF_NORMAL | TBPF_ERROR | TBPF_PAUSED) {
+                (vtable.set_progress_value)(taskbar, hwnd, progress.value as u64, 100);
+            }
+        }
+        (vtable.release)(taskbar);
+    }
+}
+
+/// Queue the update for the launcher thread, D-Bus calls block.
+#[cfg(all(feature = "launcher-progress", not(any(target_os = "macos", windows))))]
+pub fn set_progress(_window: &Window, progress: Progress) {
+    use std::sync::mpsc::{channel, Sender};
+    use std::sync::OnceLock;
+
+    static UPDATES: OnceLock<Sender<Progress>> = OnceLock::new();
+    let updates = UPDATES.get_or_init(|| {
+        let (sender, receiver) = channel();
+        crate::performer::spawn_named("launcher progress", move || {
+            send_updates(receiver)
+        });
+        sender
+    });
+    let _ = updates.send(progress);
+}
+
+#[cfg(all(feature = "launcher-progress", not(any(target_os = "macos", windows))))]
+fn send_updates(receiver: std::sync::mpsc::Receiver<Progress>) {
+    use zbus::blocking::Connection;
+
+    // Connected once, there is nothing to retry without a session bus.
+    let connection = match Connection::session() {
+        Ok(connection) => connection,
+        Err(err) => {
+            log::warn!("unable to show the progress in the launcher: {err}");
+            return;
+        }
+    };
+
+    while let Ok(mut progress) = receiver.recv() {
+        // Only the last of the updates sent meanwhile matters.
+        while let Ok(next) = receiver.try_recv() {
+            progress = next;
+        }
+
+        if let Err(err) = connection.emit_signal(
+            None::<&str>,
+            "/com/canonical/unity/launcherentry/rio",
+            "com.canonical.Unity.LauncherEntry",
+            "Update",
+            &("application://rio.desktop", launcher_properties(progress)),
+        ) {
+            log::warn!("unable to show the progress in the launcher: {err}");
+        }
+    }
+}
+
+#[cfg(any(
+    target_os = "macos",
+    all(not(feature = "launcher-progress"), not(windows))
+))]
+pub fn set_progress(_window: &Window, _progress: Progress) {}
+
+/// Properties of a Unity launcher entry update, the error state asks for
+/// attention.
+#[cfg(all(feature = "launcher-progress", not(any(target_os = "macos", windows))))]
+fn launcher_properties(
+    progress: Progress,
+) -> std::collections::HashMap<&'static str, zbus::zvariant::Value<'static>> {
+    use crate::ansi::ProgressState;
+    use zbus::zvariant::Value;
+
+    let value = match progress.state {
+        ProgressState::Indeterminate => 0.,
+        _ => progress.value as f64 / 100.,
+    };
+    std::collections::HashMap::from([
+        ("progress", Value::from(value)),
+        ("progress-visible", Value::from(progress.is_visible())),
+        (
+            "urgent",
+            Value::from(progress.state == ProgressState::Error),
+        ),
+    ])
+}
+
+#[cfg(all(
+    test,
+    feature = "launcher-progress",
+    not(any(target_os = "macos", windows))
+))]
+mod tests {
+    use super::*;
+    use crate::ansi::ProgressState;
+    use zbus::zvariant::Value;
+
+    #[test]
+    fn test_launcher_properties() {
+        let properties = launcher_properties(Progress {
+            state: ProgressState::Error,
+            value: 40,
+        });
+        assert_eq!(properties["progress"], Value::from(0.4));
+        assert_eq!(properties["progress-visible"], Value::from(true));
+        assert_eq!(properties["urgent"], Value::from(true));
+
+        let properties = launcher_properties(Progress::default());
+        assert_eq!(properties["progress-visible"], Value::from(false));
+        assert_eq!(properties["urgent"], Value::from(false));
+    }
+}
@@ -0,0 +1,375 @@
+// Remote control over a unix socket, similar to kitty's `kitten @`.
+//
+// Requests are JSON objects written one per line and every request is
+// answered with one JSON line. The listener thread only parses and filters
+// them by the `remote-control.allow` config, commands are run by the event
+// loop which sends the response back through the request.
+
+#[cfg(unix)]
+use crate::event::{EventListener, RioEvent};
+use crate::performer::handler::xparse_color;
+use rio_config::colors::{ColorRgb, NamedColor};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Formatter};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::mpsc;
+#[cfg(unix)]
+use std::time::Duration;
+
+/// Environment variable with the socket path, set for the shells.
+#[cfg(unix)]
+pub const SOCKET_ENV: &str = "RIO_SOCKET";
+
+/// Time given to the event loop to run a command.
+#[cfg(unix)]
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "cmd", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Command {
+    GetText {
+        #[serde(default)]
+        scrollback: bool,
+    },
+    SendText {
+        text: String,
+    },
+    NewWindow,
+    SetColors {
+        #[serde(default)]
+        foreground: Option<String>,
+        #[serde(default)]
+        background: Option<String>,
+        #[serde(default)]
+        cursor: Option<String>,
+    },
+    Resize {
+        columns: usize,
+        lines: usize,
+    },
+    RenameTab {
+        /// Index of the tab, the current one when missing.
+        #[serde(default)]
+        tab: Option<usize>,
+        name: String,
+    },
+    ClearScreen,
+    ClearScrollback,
+}
+
+impl Command {
+    /// Name used by the `cmd` field and the allowlist.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::GetText { .. } => "get-text",
+            Command::SendText { .. } => "send-text",
+            Command::NewWindow => "new-window",
+            Command::SetColors { .. } => "set-colors",
+            Command::Resize { .. } => "resize",
+            Command::RenameTab { .. } => "rename-tab",
+            Command::ClearScreen => "clear-screen",
+            Command::ClearScrollback => "clear-scrollback",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Response {
+    pub fn ok(data: Option<String>) -> Self {
+        Response {
+            ok: true,
+            data,
+            error: None,
+        }
+    }
+
+    pub fn error<S: Into<String>>(message: S) -> Self {
+        Response {
+            ok: false,
+            data: None,
+            error: Some(message.into()),
+        }
+    }
+}
+
+/// Command waiting to be run by the event loop.
+#[derive(Clone)]
+pub struct Request {
+    pub command: Command,
+    reply: mpsc::Sender<Response>,
+}
+
+impl Request {
+    pub fn reply(&self, response: Response) {
+        // The connection may have been closed meanwhile.
+        let _ = self.reply.send(response);
+    }
+}
+
+impl Debug for Request {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.command.name())
+    }
+}
+
+/// Parse a request line, commands missing from `allow` are refused.
+#[cfg_attr(not(unix), allow(dead_code))]
+pub fn parse(line: &str, allow: &[String]) -> Result<Command, String> {
+    let command: Command =
+        serde_json::from_str(line).map_err(|err| format!("invalid request: {err}"))?;
+    if !allow.iter().any(|allowed| allowed == command.name()) {
+        return Err(format!("command `{}` is not allowed", command.name()));
+    }
+    Ok(command)
+}
+
+/// Terminal color indexes and values of a `set-colors` command.
+pub fn color_changes(
+    foreground: &Option<String>,
+    background: &Option<String>,
+    cursor: &Option<String>,
+) -> Result<Vec<(usize, ColorRgb)>, String> {
+    let mut changes = Vec::new();
+    for (index, color) in [
+        (NamedColor::Foreground, foreground),
+        (NamedColor::Background, background),
+        (NamedColor::Cursor, cursor),
+    ] {
+        if let Some(color) = color {
+            let rgb = xparse_color(color.as_bytes())
+                .ok_or_else(|| format!("invalid color `{color}`"))?;
+            changes.push((index as usize, rgb));
+        }
+    }
+    Ok(changes)
+}
+
+/// Socket of this instance, inside the runtime directory when there is one.
+#[cfg(unix)]
+pub fn socket_path() -> PathBuf {
+    rio_config::paths::runtime_dir().join(format!("rio-{}.sock", std::process::id()))
+}
+
+/// Accept connections on `path` until Rio exits, requests are sent to the
+/// event loop through `event_proxy`.
+#[cfg(unix)]
+pub fn listen<T: EventListener + Clone + Send + 'static>(
+    path: PathBuf,
+    allow: Vec<String>,
+    event_proxy: T,
+) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixListener;
+
+    // Anyone able to connect can type into the terminals, the socket is
+    // bound in a directory the other users cannot open.
+    if let Some(directory) = path.parent() {
+        rio_config::paths::create_private_dir(directory)?;
+    }
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+
+    std::thread::Builder::new()
+        .name(String::from("remote control"))
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        log::warn!("remote control connection failed: {err}");
+                        continue;
+                    }
+                };
+                let allow = allow.clone();
+                let event_proxy = event_proxy.clone();
+                let _ = std::thread::Builder::new()
+                    .name(String::from("remote control connection"))
+                    .spawn(move || serve(stream, &allow, &event_proxy));
+            }
+        })?;
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn serve<T: EventListener>(
+    stream: std::os::unix::net::UnixStream,
+    allow: &[String],
+    event_proxy: &T,
+) {
+    use std::io::{BufRead, BufReader, Write};
+    use winit::window::WindowId;
+
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(_) => return,
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => return,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match parse(&line, allow) {
+            Ok(command) => {
+                let (reply, receiver) = mpsc::channel();
+                event_proxy.send_event(
+                    RioEvent::RemoteControl(Request { command, reply }),
+                    WindowId::from(0),
+                );
+                receiver
+                    .recv_timeout(RESPONSE_TIMEOUT)
+                    .unwrap_or_else(|_| Response::error("no response from Rio"))
+            }
+            Err(message) => Response::error(message),
+        };
+
+        let mut response = serde_json::to_string(&response).unwrap_or_default();
+        response.push('\n');
+        if writer.write_all(response.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+/// Send one request to the instance listening on `path` and return its
+/// response line.
+#[cfg(unix)]
+pub fn send(path: &std::path::Path, request: &str) -> std::io::Result<String> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(path)?;
+    stream.write_all(request.trim().as_bytes())?;
+    stream.write_all(b"\n")?;
+
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response)?;
+    Ok(response.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allow(commands: &[&str]) -> Vec<String> {
+        commands.iter().map(|command| command.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let all = allow(&[
+            "get-text",
+            "send-text",
+            "new-window",
+            "set-colors",
+            "resize",
+            "rename-tab",
+            "clear-screen",
+            "clear-scrollback",
+        ]);
+
+        assert_eq!(
+            parse(r#"{"cmd":"get-text"}"#, &all),
+            Ok(Command::GetText { scrollback: false })
+        );
+        assert_eq!(
+            parse(r#"{"cmd":"send-text","text":"ls\n"}"#, &all),
+            Ok(Command::SendText {
+                text: String::from("ls\n")
+            })
+        );
+        assert_eq!(
+            parse(r#"{"cmd":"new-window"}"#, &all),
+            Ok(Command::NewWindow)
+        );
+        assert_eq!(
+            parse(r#"{"cmd":"resize","columns":120,"lines":40}"#, &all),
+            Ok(Command::Resize {
+                columns: 120,
+                lines: 40
+            })
+        );
+        assert_eq!(
+            parse(r#"{"cmd":"rename-tab","name":"logs"}"#, &all),
+            Ok(Command::RenameTab {
+                tab: None,
+                name: String::from("logs")
+            })
+        );
+        assert_eq!(
+            parse(r#"{"cmd":"rename-tab","tab":1,"name":""}"#, &all),
+            Ok(Command::RenameTab {
+                tab: Some(1),
+                name: String::new()
+            })
+        );
+        assert_eq!(
+            parse(r#"{"cmd":"clear-screen"}"#, &all),
+            Ok(Command::ClearScreen)
+        );
+        assert_eq!(
+            parse(r#"{"cmd":"clear-scrollback"}"#, &all),
+            Ok(Command::ClearScrollback)
+        );
+        assert!(parse(r#"{"cmd":"resize","columns":120}"#, &all).is_err());
+        assert!(parse(r#"{"cmd":"close-window"}"#, &all).is_err());
+        assert!(parse("get-text", &all).is_err());
+    }
+
+    #[test]
+    fn test_parse_allowlist() {
+        let allowed = allow(&["get-text"]);
+        assert!(parse(r#"{"cmd":"get-text","scrollback":true}"#, &allowed).is_ok());
+        assert_eq!(
+            parse(r#"{"cmd":"send-text","text":"rm -rf ~"}"#, &allowed),
+            Err(String::from("command `send-text` is not allowed"))
+        );
+        assert!(parse(r#"{"cmd":"get-text"}"#, &[]).is_err());
+    }
+
+    #[test]
+    fn test_color_changes() {
+        let changes = color_changes(
+            &Some(String::from("#ff0000")),
+            &None,
+            &Some(String::from("rgb:00/ff/00")),
+        )
+        .unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                (256, ColorRgb { r: 255, g: 0, b: 0 }),
+                (258, ColorRgb { r: 0, g: 255, b: 0 }),
+            ]
+        );
+
+        assert!(color_changes(&None, &Some(String::from("red")), &None).is_err());
+    }
+
+    #[test]
+    fn test_response() {
+        assert_eq!(
+            serde_json::to_string(&Response::ok(Some(String::from("$ ls")))).unwrap(),
+            r#"{"ok":true,"data":"$ ls"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Response::error("no window")).unwrap(),
+            r#"{"ok":false,"error":"no window"}"#
+        );
+    }
+}
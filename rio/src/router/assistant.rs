@@ -51,6 +51,9 @@ pub enum AssistantReport {
     InvalidConfigurationFormat(String),
     // configuration invalid theme
     InvalidConfigurationTheme(String),
+    // started with the default configuration, with the crash report when the
+    // previous launch crashed
+    SafeMode(Option<String>),
 
     // reports that are ignored by AssistantReport
     IgnoredReport,
@@ -97,6 +100,15 @@ impl std::fmt::Display for AssistantReport {
             AssistantReport::InvalidConfigurationTheme(message) => {
                 write!(f, "Found an issue in the configured theme:\n\n{message}")
             }
+            AssistantReport::SafeMode(None) => {
+                write!(
+                    f,
+                    "Rio is running in safe mode with the default configuration"
+                )
+            }
+            AssistantReport::SafeMode(Some(report)) => {
+                write!(f, "Rio crashed while starting and is running in safe mode with the default configuration\n\nThe crash report was written to:\n{report}")
+            }
         }
     }
 }
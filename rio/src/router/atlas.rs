@@ -0,0 +1,150 @@
+// Debug view of the glyph texture cache. The texture is drawn as it is on the
+// GPU over the rows the cache packed glyphs in, the most recently used rows
+// stronger, so corrupted glyphs and a cache evicting rows all the time can be
+// told apart from a shaping or layout issue. The last memory check is shown
+// below the header.
+
+use crate::memory::Report;
+use rio_config::colors::Colors;
+use sugarloaf::components::rect::Rect;
+use sugarloaf::font::FONT_ID_REGULAR;
+use sugarloaf::{GlyphAtlas, Sugarloaf};
+
+/// Share of the texture taken by glyph rows.
+fn occupancy(atlas: &GlyphAtlas) -> f32 {
+    let area = atlas.width as f32 * atlas.height as f32;
+    if area == 0. {
+        return 0.;
+    }
+    let used: f32 = atlas
+        .rows
+        .iter()
+        .map(|row| row.width as f32 * row.height as f32)
+        .sum();
+    used / area
+}
+
+fn summary(atlas: &GlyphAtlas) -> String {
+    format!(
+        "Glyph atlas {}x{}, {} rows, {:.0}% used, {} rows ({} glyphs) evicted, {} repacks",
+        atlas.width,
+        atlas.height,
+        atlas.rows.len(),
+        occupancy(atlas) * 100.,
+        atlas.stats.evicted_rows,
+        atlas.stats.evicted_glyphs,
+        atlas.stats.repacks,
+    )
+}
+
+#[inline]
+pub fn screen(sugarloaf: &mut Sugarloaf, colors: &Colors, memory: &Report) {
+    let atlas = sugarloaf.glyph_atlas();
+    let layout = &sugarloaf.layout;
+    let width = layout.width / layout.scale_factor;
+    let height = layout.height / layout.scale_factor;
+    let line_height = layout.sugarheight * layout.line_height;
+    let font_size = layout.font_size;
+    let left = layout.margin.x;
+    let top = layout.margin.top_y;
+
+    // Largest size keeping the texture square pixels, below the header and
+    // the memory line.
+    let area_top = top + line_height * 3.;
+    let scale = ((width - left * 2.) / atlas.width.max(1) as f32)
+        .min((height - area_top - left) / atlas.height.max(1) as f32)
+        .max(0.);
+    let size = (atlas.width as f32 * scale, atlas.height as f32 * scale);
+
+    let mut rects = vec![
+        Rect {
+            position: [0., 0.],
+            color: colors.background.0,
+            size: [width * 2., layout.height * 2.],
+        },
+        Rect {
+            position: [0., top],
+            color: colors.tabs_active,
+            size: [width * 2., line_height * 2.],
+        },
+        Rect {
+            position: [left, area_top],
+            color: colors.tabs,
+            size: [size.0 * 2., size.1 * 2.],
+        },
+    ];
+    let count = atlas.rows.len() as f32;
+    for (index, row) in atlas.rows.iter().enumerate() {
+        let mut color = colors.selection_background;
+        color[3] = 0.2 + 0.6 * (index + 1) as f32 / count;
+        rects.push(Rect {
+            position: [left, area_top + row.top as f32 * scale],
+            color,
+            size: [
+                row.width as f32 * scale * 2.,
+                row.height as f32 * scale * 2.,
+            ],
+        });
+    }
+    sugarloaf.pile_rects(rects);
+
+    sugarloaf.text(
+        (left, top + line_height / 2.),
+        format!("{}, esc to close", summary(&atlas)),
+        FONT_ID_REGULAR,
+        font_size,
+        colors.background.0,
+        true,
+    );
+
+    sugarloaf.text(
+        (left, top + line_height * 1.5),
+        memory.summary(),
+        FONT_ID_REGULAR,
+        font_size,
+        colors.foreground,
+        true,
+    );
+
+    sugarloaf.show_glyph_atlas((left, area_top), size, colors.foreground);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sugarloaf::glyph::{CacheStats, RowUsage};
+
+    #[test]
+    fn test_summary() {
+        let row = |top, width, height| RowUsage {
+            top,
+            width,
+            height,
+            glyphs: 4,
+        };
+        let atlas = GlyphAtlas {
+            width: 64,
+            height: 64,
+            stats: CacheStats {
+                evicted_rows: 3,
+                evicted_glyphs: 20,
+                repacks: 1,
+            },
+            rows: vec![row(0, 64, 16), row(16, 32, 16)],
+        };
+
+        assert_eq!(occupancy(&atlas), 0.375);
+        assert_eq!(
+            summary(&atlas),
+            "Glyph atlas 64x64, 2 rows, 38% used, 3 rows (20 glyphs) evicted, 1 repacks"
+        );
+
+        let empty = GlyphAtlas {
+            width: 0,
+            height: 0,
+            stats: CacheStats::default(),
+            rows: vec![],
+        };
+        assert_eq!(occupancy(&empty), 0.);
+    }
+}
@@ -0,0 +1,281 @@
+use crate::screen::bindings::{Action, Program};
+use rio_config::colors::Colors;
+use sugarloaf::components::rect::Rect;
+use sugarloaf::font::FONT_ID_REGULAR;
+use sugarloaf::Sugarloaf;
+use winit::keyboard::Key;
+
+/// Url of `line` over the character at `column`.
+pub fn link_at(line: &str, column: usize) -> Option<String> {
+    let start = line.char_indices().nth(column).map(|(index, _)| index)?;
+    let url = regex::Regex::new(r#"https?://[^\s"'<>()]+"#).unwrap();
+    let link = url
+        .find_iter(line)
+        .find(|found| found.start() <= start && start < found.end())
+        .map(|found| found.as_str().to_string());
+    link
+}
+
+/// Program opening `link` in the default application.
+pub fn open_link(link: String) -> Program {
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let program = Program::WithArgs {
+        program: String::from("xdg-open"),
+        args: vec![link],
+    };
+    #[cfg(target_os = "macos")]
+    let program = Program::WithArgs {
+        program: String::from("open"),
+        args: vec![link],
+    };
+    #[cfg(windows)]
+    let program = Program::WithArgs {
+        program: String::from("cmd"),
+        args: vec![
+            String::from("/c"),
+            String::from("start"),
+            String::new(),
+            link,
+        ],
+    };
+    program
+}
+
+pub enum Input {
+    Pending,
+    Cancelled,
+    Chosen(Action),
+}
+
+pub struct Item {
+    pub label: &'static str,
+    pub action: Action,
+}
+
+impl Item {
+    pub fn new(label: &'static str, action: Action) -> Item {
+        Item { label, action }
+    }
+}
+
+/// Actions offered over the terminal, at the mouse or at the cursor.
+pub struct ContextMenu {
+    items: Vec<Item>,
+    selected: usize,
+    position: [f32; 2],
+    /// Left, top and width of the menu as last drawn and the height of an
+    /// item, the position is moved so the menu fits in the window.
+    bounds: Option<([f32; 3], f32)>,
+}
+
+impl ContextMenu {
+    pub fn new(items: Vec<Item>, position: [f32; 2]) -> ContextMenu {
+        ContextMenu {
+            items,
+            selected: 0,
+            position,
+            bounds: None,
+        }
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = match self.selected {
+            0 => self.items.len().saturating_sub(1),
+            selected => selected - 1,
+        };
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % self.items.len().max(1);
+    }
+
+    pub fn choose(&self) -> Input {
+        match self.items.get(self.selected) {
+            Some(item) => Input::Chosen(item.action.clone()),
+            None => Input::Cancelled,
+        }
+    }
+
+    pub fn key(&mut self, key: &Key) -> Input {
+        match key {
+            Key::Escape => return Input::Cancelled,
+            Key::Enter | Key::Space => return self.choose(),
+            Key::ArrowUp => self.previous(),
+            Key::ArrowDown | Key::Tab => self.next(),
+            Key::Home => self.selected = 0,
+            Key::End => self.selected = self.items.len().saturating_sub(1),
+            _ => {}
+        }
+        Input::Pending
+    }
+
+    /// Item at `x` and `y` in logical pixels.
+    fn item_at(&self, x: f32, y: f32) -> Option<usize> {
+        let ([left, top, width], item_height) = self.bounds?;
+        if x < left || x > left + width || y < top {
+            return None;
+        }
+        let index = ((y - top) / item_height) as usize;
+        (index < self.items.len()).then_some(index)
+    }
+
+    /// Select the item under the mouse, returns true if the selection changed.
+    pub fn hover(&mut self, x: f32, y: f32) -> bool {
+        match self.item_at(x, y) {
+            Some(index) if index != self.selected => {
+                self.selected = index;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Choose the item under the mouse, a click anywhere else closes the menu.
+    pub fn click(&mut self, x: f32, y: f32) -> Input {
+        match self.item_at(x, y) {
+            Some(index) => {
+                self.selected = index;
+                self.choose()
+            }
+            None => Input::Cancelled,
+        }
+    }
+}
+
+#[inline]
+pub fn screen(sugarloaf: &mut Sugarloaf, menu: &mut ContextMenu, colors: &Colors) {
+    let layout = &sugarloaf.layout;
+    let window_width = layout.width / layout.scale_factor;
+    let window_height = layout.height / layout.scale_factor;
+    let font_size = layout.font_size;
+    let sugarwidth = layout.sugarwidth;
+    let item_height = layout.scaled_sugarheight / layout.scale_factor * 1.4;
+
+    let longest = menu
+        .items
+        .iter()
+        .map(|item| item.label.chars().count())
+        .max()
+        .unwrap_or_default();
+    let width = (longest + 4) as f32 * sugarwidth;
+    let height = menu.items.len() as f32 * item_height;
+    let left = menu.position[0].min(window_width - width - 4.).max(0.);
+    let top = menu.position[1].min(window_height - height - 4.).max(0.);
+    menu.bounds = Some(([left, top, width], item_height));
+
+    sugarloaf.pile_overlay_rects(vec![
+        Rect {
+            position: [left - 1., top - 1.],
+            color: colors.tabs_active,
+            size: [width + 2., height + 2.],
+        },
+        Rect {
+            position: [left, top],
+            color: colors.tabs,
+            size: [width, height],
+        },
+        Rect {
+            position: [left, top + menu.selected as f32 * item_height],
+            color: colors.selection_background,
+            size: [width, item_height],
+        },
+    ]);
+
+    for (index, item) in menu.items.iter().enumerate() {
+        let color = if index == menu.selected {
+            colors.selection_foreground
+        } else {
+            colors.foreground
+        };
+        sugarloaf.overlay_text(
+            (
+                left + sugarwidth * 2.,
+                top + (index as f32 + 0.5) * item_height,
+            ),
+            item.label.to_string(),
+            FONT_ID_REGULAR,
+            font_size,
+            color,
+            true,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu() -> ContextMenu {
+        ContextMenu::new(
+            vec![
+                Item::new("Copy", Action::Copy),
+                Item::new("Paste", Action::Paste),
+                Item::new("Clear scrollback", Action::ClearHistory),
+            ],
+            [100., 100.],
+        )
+    }
+
+    #[test]
+    fn test_key() {
+        let mut menu = menu();
+        menu.key(&Key::ArrowUp);
+        assert!(matches!(
+            menu.key(&Key::Enter),
+            Input::Chosen(Action::ClearHistory)
+        ));
+        menu.key(&Key::ArrowDown);
+        menu.key(&Key::ArrowDown);
+        assert!(matches!(
+            menu.key(&Key::Enter),
+            Input::Chosen(Action::Paste)
+        ));
+        menu.key(&Key::Home);
+        assert!(matches!(menu.key(&Key::Space), Input::Chosen(Action::Copy)));
+        assert!(matches!(menu.key(&Key::Escape), Input::Cancelled));
+        assert!(matches!(
+            menu.key(&Key::Character("x".into())),
+            Input::Pending
+        ));
+    }
+
+    #[test]
+    fn test_link_at() {
+        let line = "docs at https://rio.example/docs (mirror http://a.example)";
+        assert_eq!(
+            link_at(line, 8),
+            Some(String::from("https://rio.example/docs"))
+        );
+        assert_eq!(
+            link_at(line, 31),
+            Some(String::from("https://rio.example/docs"))
+        );
+        assert_eq!(link_at(line, 32), None);
+        assert_eq!(link_at(line, 7), None);
+        assert_eq!(link_at(line, 50), Some(String::from("http://a.example")));
+        assert_eq!(
+            link_at("émoji http://x.example", 7),
+            Some(String::from("http://x.example"))
+        );
+        assert_eq!(link_at(line, 200), None);
+    }
+
+    #[test]
+    fn test_mouse() {
+        let mut menu = menu();
+        // Nothing is hit before the menu is drawn.
+        assert!(!menu.hover(110., 110.));
+
+        menu.bounds = Some(([100., 100., 80.], 20.));
+        assert!(menu.hover(110., 125.));
+        assert!(!menu.hover(110., 125.));
+        assert!(!menu.hover(90., 145.));
+        assert_eq!(menu.selected, 1);
+        assert!(matches!(
+            menu.click(170., 159.),
+            Input::Chosen(Action::ClearHistory)
+        ));
+        assert!(matches!(menu.click(110., 161.), Input::Cancelled));
+        assert!(matches!(menu.click(110., 99.), Input::Cancelled));
+    }
+}
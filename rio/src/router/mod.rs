@@ -1,10 +1,18 @@
 pub mod assistant;
+pub mod atlas;
+pub mod context_menu;
+pub mod pager;
+pub mod paste;
+pub mod quick_select;
+pub mod rename;
 pub mod settings;
+pub mod stats;
 pub mod welcome;
 
 use crate::event::EventProxy;
 use crate::screen::window::{configure_window, create_window_builder};
 use crate::screen::Screen;
+use crate::startup;
 use crate::EventP;
 use assistant::{Assistant, AssistantReport};
 use settings::Settings;
@@ -14,6 +22,7 @@ use std::rc::Rc;
 use sugarloaf::font::loader;
 use winit::event_loop::EventLoop;
 use winit::event_loop::EventLoopWindowTarget;
+use winit::window::ActivationToken;
 use winit::window::Window;
 use winit::window::WindowId;
 
@@ -119,6 +128,66 @@ impl Route {
             return true;
         }
 
+        if self.path == RoutePath::ConfirmPaste {
+            if is_enter {
+                self.window.screen.confirm_paste();
+                self.path = RoutePath::Terminal;
+            } else if key_event.logical_key == winit::keyboard::Key::Escape {
+                self.window.screen.cancel_paste();
+                self.path = RoutePath::Terminal;
+            }
+
+            return true;
+        }
+
+        if self.path == RoutePath::Pager {
+            if key_event.state == winit::event::ElementState::Pressed
+                && !self.window.screen.pager_key(key_event)
+            {
+                self.path = RoutePath::Terminal;
+            }
+
+            return true;
+        }
+
+        if self.path == RoutePath::QuickSelect {
+            if key_event.state == winit::event::ElementState::Pressed
+                && !self.window.screen.quick_select_key(key_event)
+            {
+                self.path = RoutePath::Terminal;
+            }
+
+            return true;
+        }
+
+        if self.path == RoutePath::RenameTab {
+            if key_event.state == winit::event::ElementState::Pressed
+                && !self.window.screen.rename_tab_key(&key_event.logical_key)
+            {
+                self.path = RoutePath::Terminal;
+            }
+
+            return true;
+        }
+
+        if self.path == RoutePath::ContextMenu {
+            if key_event.state == winit::event::ElementState::Pressed
+                && !self.window.screen.context_menu_key(&key_event.logical_key)
+            {
+                self.path = RoutePath::Terminal;
+            }
+
+            return true;
+        }
+
+        if self.path == RoutePath::GlyphAtlas {
+            if is_enter || key_event.logical_key == winit::keyboard::Key::Escape {
+                self.path = RoutePath::Terminal;
+            }
+
+            return true;
+        }
+
         if self.path == RoutePath::Welcome && is_enter {
             self.settings.create_file();
             self.path = RoutePath::Terminal;
@@ -137,6 +206,12 @@ pub enum RoutePath {
     #[allow(dead_code)]
     Settings,
     Welcome,
+    ConfirmPaste,
+    Pager,
+    QuickSelect,
+    RenameTab,
+    ContextMenu,
+    GlyphAtlas,
 }
 
 pub struct Router {
@@ -186,6 +261,7 @@ impl Router {
         event_loop: &EventLoopWindowTarget<EventP>,
         event_proxy: EventProxy,
         config: &Rc<rio_config::Config>,
+        activation_token: Option<ActivationToken>,
     ) {
         let window = RouteWindow::from_target(
             event_loop,
@@ -194,6 +270,7 @@ impl Router {
             &self.font_database,
             "Rio",
             None,
+            activation_token,
         );
         self.routes.insert(
             window.winit_window.id(),
@@ -222,6 +299,7 @@ impl Router {
             &self.font_database,
             "Rio",
             tab_id,
+            None,
         );
         self.routes.insert(
             window.winit_window.id(),
@@ -252,8 +330,30 @@ impl RouteWindow {
     ) -> Result<Self, Box<dyn Error>> {
         let proxy = event_loop.create_proxy();
         let event_proxy = EventProxy::new(proxy.clone());
-        let window_builder = create_window_builder("Rio", config, None);
+        #[allow(unused_mut)]
+        let mut window_builder =
+            create_window_builder("Rio", config, None).with_visible(false);
+
+        // Token of the launcher (desktop entry, `xdg-open`, another terminal)
+        // so the compositor lets the first window take the focus.
+        #[cfg(all(
+            any(feature = "x11", feature = "wayland"),
+            not(any(target_os = "macos", windows))
+        ))]
+        {
+            use winit::platform::startup_notify::{
+                reset_activation_token_env, EventLoopExtStartupNotify,
+                WindowBuilderExtStartupNotify,
+            };
+            if let Some(token) = event_loop.read_token_from_env() {
+                window_builder = window_builder.with_activation_token(token);
+            }
+            // Programs started from the terminal must not reuse it.
+            unsafe { reset_activation_token_env() };
+        }
+
         let winit_window = window_builder.build(event_loop).unwrap();
+        startup::milestone("window created");
         let winit_window = configure_window(winit_window, config);
 
         let mut screen =
@@ -264,6 +364,8 @@ impl RouteWindow {
             config.background.mode.is_image(),
             &config.background.image,
         );
+        // Shown once the accessibility adapter is attached.
+        winit_window.set_visible(true);
 
         Ok(Self {
             is_focused: false,
@@ -282,8 +384,22 @@ impl RouteWindow {
         font_database: &loader::Database,
         window_name: &str,
         tab_id: Option<String>,
+        #[allow(unused)] activation_token: Option<ActivationToken>,
     ) -> Self {
-        let window_builder = create_window_builder(window_name, config, tab_id.clone());
+        #[allow(unused_mut)]
+        let mut window_builder =
+            create_window_builder(window_name, config, tab_id.clone())
+                .with_visible(false);
+
+        #[cfg(all(
+            any(feature = "x11", feature = "wayland"),
+            not(any(target_os = "macos", windows))
+        ))]
+        if let Some(token) = activation_token {
+            use winit::platform::startup_notify::WindowBuilderExtStartupNotify;
+            window_builder = window_builder.with_activation_token(token);
+        }
+
         let winit_window = window_builder.build(event_loop).unwrap();
         let winit_window = configure_window(winit_window, config);
 
@@ -300,6 +416,7 @@ impl RouteWindow {
             config.background.mode.is_image(),
             &config.background.image,
         );
+        winit_window.set_visible(true);
 
         Self {
             is_focused: false,
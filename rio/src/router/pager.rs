@@ -0,0 +1,525 @@
+use rio_config::colors::{ColorArray, Colors};
+use std::ops::Range;
+use sugarloaf::components::rect::Rect;
+use sugarloaf::font::FONT_ID_REGULAR;
+use sugarloaf::Sugarloaf;
+use winit::keyboard::Key;
+
+const KEYWORDS: [&str; 40] = [
+    "as", "async", "await", "break", "case", "class", "const", "continue", "def", "elif",
+    "else", "enum", "export", "false", "fn", "for", "from", "func", "function", "if",
+    "impl", "import", "in", "let", "match", "mod", "None", "null", "pub", "return",
+    "self", "static", "struct", "switch", "true", "type", "use", "var", "while", "yield",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Token {
+    Text,
+    Keyword,
+    String,
+    Number,
+    Comment,
+    Added,
+    Removed,
+}
+
+/// Split a line into tokens, as ranges of characters. It only knows what
+/// most languages, diffs and logs share, there is no grammar per language.
+pub fn highlight(line: &str) -> Vec<(Token, std::ops::Range<usize>)> {
+    let chars: Vec<char> = line.chars().collect();
+    let whole_line = |token| vec![(token, 0..chars.len())];
+    if line.starts_with("+++") || line.starts_with("---") {
+        return whole_line(Token::Text);
+    }
+    if line.starts_with('+') {
+        return whole_line(Token::Added);
+    }
+    if line.starts_with('-') && !line.starts_with("--") {
+        return whole_line(Token::Removed);
+    }
+
+    let mut tokens: Vec<(Token, std::ops::Range<usize>)> = vec![];
+    let mut index = 0;
+    while index < chars.len() {
+        let start = index;
+        let c = chars[index];
+        let next = chars.get(index + 1).copied();
+        let after_space = index == 0 || chars[index - 1].is_whitespace();
+
+        let token = if (c == '/' && next == Some('/')) || (c == '#' && after_space) {
+            index = chars.len();
+            Token::Comment
+        } else if c == '"' || c == '\'' || c == '`' {
+            index += 1;
+            while index < chars.len() && chars[index] != c {
+                if chars[index] == '\\' {
+                    index += 1;
+                }
+                index += 1;
+            }
+            index = (index + 1).min(chars.len());
+            Token::String
+        } else if c.is_ascii_digit() && (index == 0 || !is_word(chars[index - 1])) {
+            while index < chars.len() && (is_word(chars[index]) || chars[index] == '.') {
+                index += 1;
+            }
+            Token::Number
+        } else if is_word(c) {
+            while index < chars.len() && is_word(chars[index]) {
+                index += 1;
+            }
+            let word: String = chars[start..index].iter().collect();
+            if KEYWORDS.contains(&word.as_str()) {
+                Token::Keyword
+            } else {
+                Token::Text
+            }
+        } else {
+            index += 1;
+            Token::Text
+        };
+
+        // Consecutive plain text is kept together.
+        match tokens.last_mut() {
+            Some((Token::Text, range)) if token == Token::Text => range.end = index,
+            _ => tokens.push((token, start..index)),
+        }
+    }
+    tokens
+}
+
+#[inline]
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Text shown by the built-in pager, over the terminal.
+pub struct Pager {
+    title: String,
+    lines: Vec<String>,
+    /// First line of the viewport.
+    top: usize,
+    /// Lines visible at once.
+    height: usize,
+    /// Search being typed after `/`.
+    input: Option<String>,
+    query: String,
+    /// Line of the match the viewport was last moved to.
+    current: Option<usize>,
+    /// Name and lines of the part of the text searched, all of it otherwise.
+    scope: Option<(String, Range<usize>)>,
+    syntax_highlighting: bool,
+}
+
+impl Pager {
+    pub fn new(title: &str, text: &str, syntax_highlighting: bool) -> Pager {
+        Pager {
+            title: title.to_string(),
+            lines: text
+                .lines()
+                .map(|line| line.replace('\t', "    "))
+                .collect(),
+            top: 0,
+            height: 1,
+            input: None,
+            query: String::new(),
+            current: None,
+            scope: None,
+            syntax_highlighting,
+        }
+    }
+
+    /// Set the number of visible lines.
+    pub fn set_height(&mut self, height: usize) {
+        self.height = height.max(1);
+        self.scroll(0);
+    }
+
+    pub fn scroll(&mut self, delta: isize) {
+        let last_top = self.lines.len().saturating_sub(self.height);
+        self.top = self.top.saturating_add_signed(delta).min(last_top);
+    }
+
+    /// Open the search prompt to match only `lines`, shown as `scope` in
+    /// the status.
+    pub fn search_in(&mut self, scope: &str, lines: Range<usize>) {
+        let lines = lines.start.min(self.lines.len())..lines.end.min(self.lines.len());
+        self.top = 0;
+        self.scroll(lines.start as isize);
+        self.scope = Some((scope.to_string(), lines));
+        self.current = None;
+        self.input = Some(String::new());
+    }
+
+    /// Lines that can match, the scope or the whole text.
+    fn searched(&self) -> Range<usize> {
+        match &self.scope {
+            Some((_, lines)) => lines.clone(),
+            None => 0..self.lines.len(),
+        }
+    }
+
+    /// Lines containing the query, ignoring case.
+    fn matches(&self) -> Vec<usize> {
+        if self.query.is_empty() {
+            return vec![];
+        }
+
+        let query = self.query.to_lowercase();
+        self.searched()
+            .filter(|index| self.lines[*index].to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// Move the viewport to the next line containing the query, from `start`
+    /// or before it when going `backward`.
+    fn find(&mut self, start: usize, backward: bool) {
+        let matches = self.matches();
+        let found = if backward {
+            matches.into_iter().rev().find(|line| *line < start)
+        } else {
+            matches.into_iter().find(|line| *line >= start)
+        };
+        if let Some(line) = found {
+            self.current = Some(line);
+            self.top = 0;
+            self.scroll(line as isize);
+        }
+    }
+
+    /// Handle a key press, returns false once the pager is closed.
+    /// Whether the search prompt is open.
+    #[inline]
+    pub fn is_searching(&self) -> bool {
+        self.input.is_some()
+    }
+
+    /// Search the text of the prompt and close it.
+    pub fn confirm_search(&mut self) {
+        self.query = self.input.take().unwrap_or_default();
+        self.current = None;
+        self.find(self.top, false);
+    }
+
+    #[inline]
+    pub fn cancel_search(&mut self) {
+        self.input = None;
+    }
+
+    pub fn key(&mut self, key: &Key) -> bool {
+        if let Some(input) = &mut self.input {
+            match key {
+                Key::Enter => self.confirm_search(),
+                Key::Escape => self.cancel_search(),
+                Key::Backspace => {
+                    input.pop();
+                }
+                Key::Space => input.push(' '),
+                Key::Character(text) => input.push_str(text),
+                _ => {}
+            }
+            return true;
+        }
+
+        let page = self.height as isize;
+        match key {
+            Key::Escape => return false,
+            Key::ArrowDown | Key::Enter => self.scroll(1),
+            Key::ArrowUp => self.scroll(-1),
+            Key::PageDown | Key::Space => self.scroll(page),
+            Key::PageUp => self.scroll(-page),
+            Key::Home => self.top = 0,
+            Key::End => self.scroll(self.lines.len() as isize),
+            Key::Character(text) => match text.as_str() {
+                "q" => return false,
+                "j" => self.scroll(1),
+                "k" => self.scroll(-1),
+                "f" => self.scroll(page),
+                "b" => self.scroll(-page),
+                "d" => self.scroll(page / 2),
+                "u" => self.scroll(-page / 2),
+                "g" => self.top = 0,
+                "G" => self.scroll(self.lines.len() as isize),
+                "/" => self.input = Some(String::new()),
+                "n" => self.find(self.current.map_or(self.top, |line| line + 1), false),
+                "N" => self.find(self.current.unwrap_or(self.top), true),
+                _ => {}
+            },
+            _ => {}
+        }
+        true
+    }
+
+    fn status(&self) -> String {
+        if let Some(input) = &self.input {
+            return format!("/{input}");
+        }
+
+        let bottom = (self.top + self.height).min(self.lines.len());
+        let mut status = format!(
+            "{} lines {}-{} of {}",
+            self.title,
+            self.top + 1,
+            bottom,
+            self.lines.len()
+        );
+        if !self.query.is_empty() {
+            status.push_str(&format!("  /{}", self.query));
+            let matches = self.matches();
+            let index = self
+                .current
+                .and_then(|current| matches.iter().position(|line| *line == current));
+            match index {
+                Some(index) => status.push_str(&format!(
+                    "  match {} of {}",
+                    index + 1,
+                    matches.len()
+                )),
+                None => status.push_str(&format!("  {} matches", matches.len())),
+            }
+            if let Some((scope, _)) = &self.scope {
+                status.push_str(&format!(" in {scope}"));
+            }
+        }
+        status.push_str("  (q to close, / to search, n/N next/previous match)");
+        status
+    }
+}
+
+/// Number of text lines fitting in the pager, a line is kept for the status.
+#[inline]
+pub fn height(sugarloaf: &Sugarloaf) -> usize {
+    let layout = &sugarloaf.layout;
+    let line = layout.sugarheight * layout.line_height;
+    let available = layout.height / layout.scale_factor - layout.margin.top_y;
+    ((available / line).floor() as usize).saturating_sub(1)
+}
+
+#[inline]
+pub fn screen(sugarloaf: &mut Sugarloaf, pager: &Pager, colors: &Colors) {
+    let layout = &sugarloaf.layout;
+    let width = layout.width / layout.scale_factor;
+    let cell_width = layout.sugarwidth;
+    let line_height = layout.sugarheight * layout.line_height;
+    let font_size = layout.font_size;
+    let left = layout.margin.x;
+    let top = layout.margin.top_y;
+
+    let color = |token: Token| -> ColorArray {
+        match token {
+            Token::Text => colors.foreground,
+            Token::Keyword => colors.blue,
+            Token::String => colors.green,
+            Token::Number => colors.magenta,
+            Token::Comment => colors.light_black,
+            Token::Added => colors.green,
+            Token::Removed => colors.red,
+        }
+    };
+
+    let query = pager.query.to_lowercase();
+    let searched = pager.searched();
+    let mut rects = vec![Rect {
+        position: [0., 0.],
+        color: colors.background.0,
+        size: [width * 2., layout.height * 2.],
+    }];
+    let rows = pager
+        .lines
+        .iter()
+        .enumerate()
+        .skip(pager.top)
+        .take(pager.height);
+    for (row, (index, line)) in rows.clone().enumerate() {
+        if !query.is_empty()
+            && searched.contains(&index)
+            && line.to_lowercase().contains(&query)
+        {
+            rects.push(Rect {
+                position: [0., top + row as f32 * line_height],
+                color: colors.selection_background,
+                size: [width * 2., line_height * 2.],
+            });
+        }
+    }
+    let status_y = top + pager.height as f32 * line_height;
+    rects.push(Rect {
+        position: [0., status_y],
+        color: colors.tabs_active,
+        size: [width * 2., line_height * 2.],
+    });
+    sugarloaf.pile_rects(rects);
+
+    for (row, (_, line)) in rows.enumerate() {
+        let y = top + (row as f32 + 0.5) * line_height;
+        if !pager.syntax_highlighting {
+            sugarloaf.text(
+                (left, y),
+                line.to_owned(),
+                FONT_ID_REGULAR,
+                font_size,
+                colors.foreground,
+                true,
+            );
+            continue;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        for (token, range) in highlight(line) {
+            sugarloaf.text(
+                (left + range.start as f32 * cell_width, y),
+                chars[range].iter().collect(),
+                FONT_ID_REGULAR,
+                font_size,
+                color(token),
+                true,
+            );
+        }
+    }
+
+    sugarloaf.text(
+        (left, status_y + line_height / 2.),
+        pager.status(),
+        FONT_ID_REGULAR,
+        font_size,
+        colors.background.0,
+        true,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pager(lines: usize, height: usize) -> Pager {
+        let text: Vec<String> = (0..lines).map(|line| format!("line {line}")).collect();
+        let mut pager = Pager::new("Scrollback", &text.join("\n"), false);
+        pager.set_height(height);
+        pager
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut pager = pager(100, 10);
+        assert!(pager.key(&Key::Character("j".into())));
+        assert_eq!(pager.top, 1);
+        pager.key(&Key::PageDown);
+        assert_eq!(pager.top, 11);
+        pager.key(&Key::Character("G".into()));
+        assert_eq!(pager.top, 90);
+        pager.key(&Key::ArrowDown);
+        assert_eq!(pager.top, 90);
+        pager.key(&Key::Home);
+        assert_eq!(pager.top, 0);
+        pager.key(&Key::ArrowUp);
+        assert_eq!(pager.top, 0);
+
+        // Shorter than the viewport.
+        let mut pager = super::tests::pager(3, 10);
+        pager.key(&Key::End);
+        assert_eq!(pager.top, 0);
+
+        assert!(!pager.key(&Key::Character("q".into())));
+        assert!(!pager.key(&Key::Escape));
+    }
+
+    #[test]
+    fn test_search() {
+        let mut pager = pager(100, 10);
+        pager.key(&Key::Character("/".into()));
+        for key in ["l", "i", "n", "e"] {
+            pager.key(&Key::Character(key.into()));
+        }
+        pager.key(&Key::Space);
+        pager.key(&Key::Character("4".into()));
+        assert_eq!(pager.status(), "/line 4");
+        pager.key(&Key::Enter);
+        assert_eq!(pager.top, 4);
+
+        pager.key(&Key::Character("n".into()));
+        assert_eq!(pager.top, 40);
+        pager.key(&Key::Character("n".into()));
+        assert_eq!(pager.top, 41);
+        pager.key(&Key::Character("N".into()));
+        assert_eq!(pager.top, 40);
+        pager.key(&Key::Character("N".into()));
+        assert_eq!(pager.top, 4);
+        // No more matches.
+        pager.key(&Key::Character("N".into()));
+        assert_eq!(pager.top, 4);
+
+        // Escape cancels the search being typed, not the pager.
+        pager.key(&Key::Character("/".into()));
+        assert!(pager.key(&Key::Escape));
+        assert!(pager.status().starts_with("Scrollback lines 5-14 of 100"));
+        assert!(pager
+            .status()
+            .starts_with("Scrollback lines 5-14 of 100  /line 4  match 1 of 11"));
+    }
+
+    #[test]
+    fn test_search_in() {
+        let mut pager = pager(100, 10);
+        pager.search_in("selection", 30..45);
+        assert!(pager.is_searching());
+        assert_eq!(pager.top, 30);
+        for key in ["l", "i", "n", "e"] {
+            pager.key(&Key::Character(key.into()));
+        }
+        pager.key(&Key::Space);
+        pager.key(&Key::Character("4".into()));
+        pager.key(&Key::Enter);
+        assert_eq!(pager.top, 40);
+        assert!(pager
+            .status()
+            .contains("/line 4  match 1 of 5 in selection"));
+
+        for _ in 0..10 {
+            pager.key(&Key::Character("n".into()));
+        }
+        assert_eq!(pager.current, Some(44));
+        assert!(pager.status().contains("match 5 of 5 in selection"));
+        // Line 4 is out of the scope.
+        for _ in 0..10 {
+            pager.key(&Key::Character("N".into()));
+        }
+        assert_eq!(pager.current, Some(40));
+
+        // Scrolling away keeps the current match.
+        pager.key(&Key::Character("g".into()));
+        assert!(pager.status().contains("match 1 of 5"));
+
+        pager.key(&Key::Character("/".into()));
+        pager.key(&Key::Character("none".into()));
+        pager.key(&Key::Enter);
+        assert!(pager.status().contains("/none  0 matches in selection"));
+    }
+
+    #[test]
+    fn test_highlight() {
+        assert_eq!(
+            highlight(r#"let name = "rio"; // comment"#),
+            vec![
+                (Token::Keyword, 0..3),
+                (Token::Text, 3..11),
+                (Token::String, 11..16),
+                (Token::Text, 16..18),
+                (Token::Comment, 18..28),
+            ]
+        );
+        assert_eq!(
+            highlight("x2 = 0x1F + 3.5"),
+            vec![
+                (Token::Text, 0..5),
+                (Token::Number, 5..9),
+                (Token::Text, 9..12),
+                (Token::Number, 12..15),
+            ]
+        );
+        assert_eq!(highlight("+ added"), vec![(Token::Added, 0..7)]);
+        assert_eq!(highlight("- removed"), vec![(Token::Removed, 0..9)]);
+        assert_eq!(highlight("--- a/file"), vec![(Token::Text, 0..10)]);
+        assert_eq!(highlight("#include"), vec![(Token::Comment, 0..8)]);
+        assert_eq!(highlight(r#""esc\"aped" x"#)[0], (Token::String, 0..11));
+    }
+}
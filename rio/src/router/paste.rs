@@ -0,0 +1,86 @@
+use sugarloaf::components::rect::Rect;
+use sugarloaf::font::FONT_ID_BUILTIN;
+use sugarloaf::Sugarloaf;
+
+// Maximum number of lines from the pasted text shown in the preview.
+const PREVIEW_MAX_LINES: usize = 10;
+
+#[inline]
+pub fn screen(sugarloaf: &mut Sugarloaf, content: &str) {
+    let blue = [0.1764706, 0.6039216, 1.0, 1.0];
+    let yellow = [0.9882353, 0.7294118, 0.15686275, 1.0];
+    let red = [1.0, 0.07058824, 0.38039216, 1.0];
+
+    let paste_background = vec![
+        Rect {
+            position: [0., 30.0],
+            color: blue,
+            size: [30., sugarloaf.layout.height],
+        },
+        Rect {
+            position: [15., sugarloaf.layout.margin.top_y + 40.],
+            color: yellow,
+            size: [30., sugarloaf.layout.height],
+        },
+        Rect {
+            position: [30., sugarloaf.layout.margin.top_y + 120.],
+            color: red,
+            size: [30., sugarloaf.layout.height],
+        },
+    ];
+
+    sugarloaf.pile_rects(paste_background);
+
+    sugarloaf.text(
+        (70., sugarloaf.layout.margin.top_y + 50.),
+        "Paste into the shell prompt?".to_string(),
+        FONT_ID_BUILTIN,
+        28.,
+        [1., 1., 1., 1.],
+        true,
+    );
+
+    sugarloaf.text(
+        (70., sugarloaf.layout.margin.top_y + 80.),
+        "(press enter to paste or escape to cancel)".to_string(),
+        FONT_ID_BUILTIN,
+        18.,
+        yellow,
+        true,
+    );
+
+    sugarloaf.text(
+        (70., sugarloaf.layout.margin.top_y + 170.),
+        preview(content),
+        FONT_ID_BUILTIN,
+        14.,
+        [1., 1., 1., 1.],
+        false,
+    );
+}
+
+/// Show the first lines of the text, making control characters visible.
+#[inline]
+fn preview(content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut preview = String::new();
+    for line in lines.iter().take(PREVIEW_MAX_LINES) {
+        for c in line.chars() {
+            if c.is_control() && c != '\t' {
+                preview.push_str(&format!("^{}", ((c as u8) ^ 0x40) as char));
+            } else {
+                preview.push(c);
+            }
+        }
+        preview.push('\n');
+    }
+
+    if lines.len() > PREVIEW_MAX_LINES {
+        preview.push_str(&format!(
+            "... {} more lines",
+            lines.len() - PREVIEW_MAX_LINES
+        ));
+    }
+
+    preview
+}
@@ -0,0 +1,306 @@
+use rio_config::colors::Colors;
+use sugarloaf::components::rect::Rect;
+use sugarloaf::font::FONT_ID_REGULAR;
+use sugarloaf::Sugarloaf;
+use winit::keyboard::Key;
+
+/// Compile the configured patterns, skipping (and logging) invalid regexes.
+pub fn compile(patterns: &[String]) -> Vec<regex::Regex> {
+    patterns
+        .iter()
+        .filter_map(|pattern| match regex::Regex::new(pattern) {
+            Ok(regex) => Some(regex),
+            Err(err) => {
+                log::error!("invalid quick select regex {pattern:?}: {err}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Tokens matched by `patterns` in `text`, the most recent first and without
+/// duplicates. When matches overlap the one starting first, or the longest,
+/// is kept so an url is not also offered as a path.
+pub fn tokens(text: &str, patterns: &[regex::Regex]) -> Vec<String> {
+    let mut tokens: Vec<String> = vec![];
+    for line in text.lines().rev() {
+        let mut found: Vec<(usize, usize)> = patterns
+            .iter()
+            .flat_map(|pattern| pattern.find_iter(line))
+            .map(|found| (found.start(), found.end()))
+            .collect();
+        found.sort_by_key(|(start, end)| (*start, std::cmp::Reverse(*end)));
+
+        let mut end_of_last = 0;
+        for (start, end) in found {
+            if start < end_of_last || start == end {
+                continue;
+            }
+            end_of_last = end;
+
+            let token = &line[start..end];
+            if !tokens.iter().any(|existent| existent == token) {
+                tokens.push(token.to_string());
+            }
+        }
+    }
+    tokens
+}
+
+/// Score of `candidate` for a fuzzy `query`, lower is better. Every query
+/// character must appear in order, gaps between them are penalized.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<usize> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut first = None;
+    for wanted in query.to_lowercase().chars() {
+        let found = candidate[position..].iter().position(|c| *c == wanted)?;
+        if first.is_some() {
+            score += found;
+        } else {
+            first = Some(position + found);
+        }
+        position += found + 1;
+    }
+    Some(score * 2 + first.unwrap_or_default())
+}
+
+pub enum Selection {
+    Pending,
+    Cancelled,
+    Chosen(String),
+}
+
+/// Recent output tokens offered to be inserted at the prompt.
+pub struct QuickSelect {
+    tokens: Vec<String>,
+    query: String,
+    selected: usize,
+}
+
+impl QuickSelect {
+    pub fn new(tokens: Vec<String>) -> QuickSelect {
+        QuickSelect {
+            tokens,
+            query: String::new(),
+            selected: 0,
+        }
+    }
+
+    /// Tokens matching the query, the best first.
+    pub fn filtered(&self) -> Vec<&String> {
+        let mut filtered: Vec<(usize, &String)> = self
+            .tokens
+            .iter()
+            .filter_map(|token| {
+                fuzzy_score(&self.query, token).map(|score| (score, token))
+            })
+            .collect();
+        // Stable, ties keep the most recent first.
+        filtered.sort_by_key(|(score, _)| *score);
+        filtered.into_iter().map(|(_, token)| token).collect()
+    }
+
+    /// The selected token, or nothing when none matches.
+    pub fn choose(&self) -> Selection {
+        match self.filtered().get(self.selected) {
+            Some(token) => Selection::Chosen(token.to_string()),
+            None => Selection::Cancelled,
+        }
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn next(&mut self) {
+        let last = self.filtered().len().saturating_sub(1);
+        self.selected = (self.selected + 1).min(last);
+    }
+
+    pub fn key(&mut self, key: &Key) -> Selection {
+        match key {
+            Key::Escape => return Selection::Cancelled,
+            Key::Enter => return self.choose(),
+            Key::ArrowUp => self.previous(),
+            Key::ArrowDown | Key::Tab => self.next(),
+            Key::Backspace => {
+                self.query.pop();
+                self.selected = 0;
+            }
+            Key::Space => {
+                self.query.push(' ');
+                self.selected = 0;
+            }
+            Key::Character(text) => {
+                self.query.push_str(text);
+                self.selected = 0;
+            }
+            _ => {}
+        }
+        Selection::Pending
+    }
+}
+
+/// First line of `token`, with the count of the other lines.
+fn label(token: &str) -> String {
+    let mut lines = token.trim_end_matches('\n').lines();
+    let first = lines.next().unwrap_or_default();
+    match lines.count() {
+        0 => first.to_string(),
+        1 => format!("{first}  (+1 line)"),
+        more => format!("{first}  (+{more} lines)"),
+    }
+}
+
+#[inline]
+pub fn screen(sugarloaf: &mut Sugarloaf, quick_select: &QuickSelect, colors: &Colors) {
+    let layout = &sugarloaf.layout;
+    let width = layout.width / layout.scale_factor;
+    let line_height = layout.sugarheight * layout.line_height;
+    let font_size = layout.font_size;
+    let left = layout.margin.x;
+    let top = layout.margin.top_y;
+    let rows = crate::router::pager::height(sugarloaf);
+
+    let filtered = quick_select.filtered();
+    // Keep the selected token visible.
+    let first = (quick_select.selected + 1).saturating_sub(rows);
+
+    sugarloaf.pile_rects(vec![
+        Rect {
+            position: [0., 0.],
+            color: colors.background.0,
+            size: [width * 2., layout.height * 2.],
+        },
+        Rect {
+            position: [0., top],
+            color: colors.tabs_active,
+            size: [width * 2., line_height * 2.],
+        },
+        Rect {
+            position: [
+                0.,
+                top + (quick_select.selected - first + 1) as f32 * line_height,
+            ],
+            color: colors.selection_background,
+            size: [width * 2., line_height * 2.],
+        },
+    ]);
+
+    sugarloaf.text(
+        (left, top + line_height / 2.),
+        format!(
+            "> {}  ({} of {}, enter to insert, esc to cancel)",
+            quick_select.query,
+            filtered.len(),
+            quick_select.tokens.len()
+        ),
+        FONT_ID_REGULAR,
+        font_size,
+        colors.background.0,
+        true,
+    );
+
+    for (row, token) in filtered.iter().skip(first).take(rows).enumerate() {
+        sugarloaf.text(
+            (left, top + (row as f32 + 1.5) * line_height),
+            label(token),
+            FONT_ID_REGULAR,
+            font_size,
+            colors.foreground,
+            true,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_patterns() -> Vec<regex::Regex> {
+        compile(&rio_config::Config::default().quick_select.patterns)
+    }
+
+    #[test]
+    fn test_tokens() {
+        let text = "\
+commit 3f2a9c1d8e7b6a5f4e3d2c1b0a9f8e7d6c5b4a3f
+modified: src/router/mod.rs
+Listening on 192.168.0.12:8080, see https://rio.example/docs
+CONTAINER ID   IMAGE
+4c01db0b339c   nginx
+modified: src/router/mod.rs";
+
+        assert_eq!(
+            tokens(text, &default_patterns()),
+            vec![
+                "src/router/mod.rs",
+                "4c01db0b339c",
+                "192.168.0.12:8080",
+                "https://rio.example/docs",
+                "3f2a9c1d8e7b6a5f4e3d2c1b0a9f8e7d6c5b4a3f",
+            ]
+        );
+
+        let custom = compile(&[String::from(r"TICKET-\d+"), String::from("(")]);
+        assert_eq!(custom.len(), 1);
+        assert_eq!(tokens("see TICKET-42", &custom), vec!["TICKET-42"]);
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "src/main.rs"), None);
+        assert_eq!(fuzzy_score("MAIN", "src/main.rs"), Some(4));
+        assert!(fuzzy_score("mr", "main.rs") > fuzzy_score("mr", "mr.rs"));
+    }
+
+    #[test]
+    fn test_label() {
+        assert_eq!(label("src/main.rs"), "src/main.rs");
+        assert_eq!(label("cargo build\n"), "cargo build");
+        assert_eq!(label("a\nb\n"), "a  (+1 line)");
+        assert_eq!(label("a\nb\nc"), "a  (+2 lines)");
+    }
+
+    #[test]
+    fn test_key() {
+        let mut quick_select = QuickSelect::new(vec![
+            String::from("src/main.rs"),
+            String::from("192.168.0.1"),
+            String::from("src/lib.rs"),
+        ]);
+
+        assert!(matches!(
+            quick_select.key(&Key::ArrowDown),
+            Selection::Pending
+        ));
+        match quick_select.key(&Key::Enter) {
+            Selection::Chosen(token) => assert_eq!(token, "192.168.0.1"),
+            _ => panic!("expected a token"),
+        }
+
+        for key in ["s", "r", "c"] {
+            quick_select.key(&Key::Character(key.into()));
+        }
+        assert_eq!(quick_select.filtered(), vec!["src/main.rs", "src/lib.rs"]);
+        quick_select.key(&Key::ArrowDown);
+        quick_select.key(&Key::ArrowDown);
+        match quick_select.key(&Key::Enter) {
+            Selection::Chosen(token) => assert_eq!(token, "src/lib.rs"),
+            _ => panic!("expected a token"),
+        }
+
+        quick_select.key(&Key::Character("z".into()));
+        assert!(matches!(
+            quick_select.key(&Key::Enter),
+            Selection::Cancelled
+        ));
+        assert!(matches!(
+            quick_select.key(&Key::Escape),
+            Selection::Cancelled
+        ));
+    }
+}
@@ -0,0 +1,112 @@
+use rio_config::colors::Colors;
+use sugarloaf::components::rect::Rect;
+use sugarloaf::font::FONT_ID_REGULAR;
+use sugarloaf::Sugarloaf;
+use winit::keyboard::Key;
+
+pub enum Input {
+    Pending,
+    Cancelled,
+    /// New name of the tab, None to go back to the titles set by programs.
+    Renamed(Option<String>),
+}
+
+/// Prompt asking for the name of the current tab.
+pub struct RenameTab {
+    name: String,
+}
+
+impl RenameTab {
+    pub fn new(name: Option<String>) -> RenameTab {
+        RenameTab {
+            name: name.unwrap_or_default(),
+        }
+    }
+
+    pub fn key(&mut self, key: &Key) -> Input {
+        match key {
+            Key::Escape => return Input::Cancelled,
+            Key::Enter => {
+                let name = self.name.trim();
+                return Input::Renamed(if name.is_empty() {
+                    None
+                } else {
+                    Some(name.to_string())
+                });
+            }
+            Key::Backspace => {
+                self.name.pop();
+            }
+            Key::Space => self.name.push(' '),
+            Key::Character(text) => self.name.push_str(text),
+            _ => {}
+        }
+        Input::Pending
+    }
+}
+
+#[inline]
+pub fn screen(sugarloaf: &mut Sugarloaf, rename: &RenameTab, colors: &Colors) {
+    let layout = &sugarloaf.layout;
+    let width = layout.width / layout.scale_factor;
+    let line_height = layout.sugarheight * layout.line_height;
+    let font_size = layout.font_size;
+    let left = layout.margin.x;
+    let top = layout.margin.top_y;
+
+    sugarloaf.pile_rects(vec![
+        Rect {
+            position: [0., 0.],
+            color: colors.background.0,
+            size: [width * 2., layout.height * 2.],
+        },
+        Rect {
+            position: [0., top],
+            color: colors.tabs_active,
+            size: [width * 2., line_height * 2.],
+        },
+    ]);
+
+    sugarloaf.text(
+        (left, top + line_height / 2.),
+        format!("Tab name: {}_", rename.name),
+        FONT_ID_REGULAR,
+        font_size,
+        colors.background.0,
+        true,
+    );
+
+    sugarloaf.text(
+        (left, top + line_height * 1.5),
+        String::from("enter to rename, an empty name restores the title, esc to cancel"),
+        FONT_ID_REGULAR,
+        font_size,
+        colors.foreground,
+        true,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key() {
+        let mut rename = RenameTab::new(Some(String::from("logs")));
+        assert!(matches!(rename.key(&Key::Backspace), Input::Pending));
+        rename.key(&Key::Space);
+        for key in ["a", "p", "i"] {
+            rename.key(&Key::Character(key.into()));
+        }
+        match rename.key(&Key::Enter) {
+            Input::Renamed(name) => assert_eq!(name, Some(String::from("log api"))),
+            _ => panic!("expected a name"),
+        }
+
+        let mut rename = RenameTab::new(Some(String::from("x")));
+        rename.key(&Key::Backspace);
+        rename.key(&Key::Space);
+        assert!(matches!(rename.key(&Key::Enter), Input::Renamed(None)));
+        assert!(matches!(rename.key(&Key::Escape), Input::Cancelled));
+    }
+}
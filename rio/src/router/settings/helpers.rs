@@ -43,7 +43,11 @@ pub fn config_to_settings(
     }
 
     {
-        let options = vec![String::from("High"), String::from("Low")];
+        let options = vec![
+            String::from("High"),
+            String::from("Low"),
+            String::from("Auto"),
+        ];
         let current_option: usize = options
             .iter()
             .position(|r| r == &config.performance.to_string())
@@ -284,12 +288,9 @@ pub fn settings_to_config(settings: &HashMap<usize, Setting>) -> rio_config::Con
 
     {
         if let Some(setting) = settings.get(&IDX_PERFORMANCE) {
-            let val = if setting.options[setting.current_option].to_lowercase() == *"low"
-            {
-                rio_config::Performance::Low
-            } else {
-                rio_config::Performance::High
-            };
+            let val = setting.options[setting.current_option]
+                .parse::<rio_config::Performance>()
+                .unwrap_or_default();
             current_config.performance = val;
         }
     }
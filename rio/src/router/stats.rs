@@ -0,0 +1,181 @@
+// Overlay with figures to compare configurations: frames drawn in the last
+// second, the time from a key press to the first frame drawn after it, the
+// output parsed per second by the tabs of the window and the frames dropped
+// because the terminal stayed locked by the PTY reader.
+
+use rio_config::colors::Colors;
+use std::time::{Duration, Instant};
+use sugarloaf::components::rect::Rect;
+use sugarloaf::font::FONT_ID_REGULAR;
+use sugarloaf::Sugarloaf;
+
+const SECOND: Duration = Duration::from_secs(1);
+
+pub struct Stats {
+    /// Start of the second being counted.
+    since: Instant,
+    frames: u32,
+    /// Bytes parsed when the second started.
+    parsed_since: u64,
+    fps: u32,
+    throughput: u64,
+    dropped: u64,
+    latency: Option<Duration>,
+}
+
+impl Stats {
+    pub fn new(parsed: u64) -> Stats {
+        Stats {
+            since: Instant::now(),
+            frames: 0,
+            parsed_since: parsed,
+            fps: 0,
+            throughput: 0,
+            dropped: 0,
+            latency: None,
+        }
+    }
+
+    /// Count a frame drawn at `now`, with `parsed` bytes parsed so far.
+    pub fn frame(&mut self, now: Instant, parsed: u64) {
+        self.frames += 1;
+        let elapsed = now.saturating_duration_since(self.since);
+        if elapsed < SECOND {
+            return;
+        }
+
+        let seconds = elapsed.as_secs_f64();
+        self.fps = (self.frames as f64 / seconds).round() as u32;
+        // The count goes down when a tab is closed.
+        self.throughput =
+            (parsed.saturating_sub(self.parsed_since) as f64 / seconds) as u64;
+        self.frames = 0;
+        self.parsed_since = parsed;
+        self.since = now;
+    }
+
+    /// Count a frame skipped while the terminal was busy.
+    #[inline]
+    pub fn drop_frame(&mut self) {
+        self.dropped += 1;
+    }
+
+    /// Time from a key press to a frame, smoothed so a single slow frame
+    /// does not hide the usual value.
+    pub fn input_latency(&mut self, latency: Duration) {
+        self.latency = Some(match self.latency {
+            Some(previous) => (previous * 3 + latency) / 4,
+            None => latency,
+        });
+    }
+
+    /// Time until the figures are due again, the overlay is drawn once a
+    /// second while nothing else is.
+    #[inline]
+    pub fn next_update(&self) -> Duration {
+        SECOND.saturating_sub(self.since.elapsed())
+    }
+
+    fn lines(&self) -> [String; 4] {
+        let latency = match self.latency {
+            Some(latency) => format!("{:.1} ms", latency.as_secs_f64() * 1000.),
+            None => String::from("-"),
+        };
+        [
+            format!("frames   {} fps", self.fps),
+            format!("latency  {latency}"),
+            format!("output   {}/s", human_bytes(self.throughput)),
+            format!("dropped  {}", self.dropped),
+        ]
+    }
+}
+
+/// `bytes` in B, KB or MB.
+fn human_bytes(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{bytes} B"),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.),
+    }
+}
+
+#[inline]
+pub fn screen(sugarloaf: &mut Sugarloaf, stats: &Stats, colors: &Colors) {
+    let layout = &sugarloaf.layout;
+    let window_width = layout.width / layout.scale_factor;
+    let top = layout.margin.top_y * 2. + 4.;
+    let font_size = layout.font_size;
+    let sugarwidth = layout.sugarwidth;
+    let line_height = layout.scaled_sugarheight / layout.scale_factor * 1.2;
+
+    let lines = stats.lines();
+    let longest = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or_default();
+    let width = (longest + 2) as f32 * sugarwidth;
+    let height = lines.len() as f32 * line_height;
+    let left = (window_width - width - 8.).max(0.);
+    let [r, g, b, _] = colors.tabs;
+
+    sugarloaf.pile_overlay_rects(vec![Rect {
+        position: [left, top],
+        color: [r, g, b, 0.85],
+        size: [width, height],
+    }]);
+    for (index, line) in lines.into_iter().enumerate() {
+        sugarloaf.overlay_text(
+            (left + sugarwidth, top + (index as f32 + 0.5) * line_height),
+            line,
+            FONT_ID_REGULAR,
+            font_size,
+            colors.foreground,
+            true,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame() {
+        let mut stats = Stats::new(100);
+        let start = stats.since;
+        for frame in 1..60 {
+            stats.frame(start + Duration::from_millis(frame * 16), 200);
+        }
+        // Nothing is reported before a second went by.
+        assert_eq!(stats.fps, 0);
+        stats.frame(start + Duration::from_secs(1), 2148);
+        assert_eq!(stats.fps, 60);
+        assert_eq!(stats.throughput, 2048);
+        assert_eq!(stats.lines()[2], "output   2.0 KB/s");
+
+        // A closed tab takes its bytes away from the count.
+        stats.frame(start + Duration::from_secs(2), 48);
+        assert_eq!(stats.fps, 1);
+        assert_eq!(stats.throughput, 0);
+    }
+
+    #[test]
+    fn test_input_latency() {
+        let mut stats = Stats::new(0);
+        assert_eq!(stats.lines()[1], "latency  -");
+        stats.input_latency(Duration::from_millis(8));
+        stats.input_latency(Duration::from_millis(16));
+        assert_eq!(stats.latency, Some(Duration::from_millis(10)));
+        assert_eq!(stats.lines()[1], "latency  10.0 ms");
+        stats.drop_frame();
+        assert_eq!(stats.lines()[3], "dropped  1");
+    }
+
+    #[test]
+    fn test_human_bytes() {
+        assert_eq!(human_bytes(0), "0 B");
+        assert_eq!(human_bytes(1536), "1.5 KB");
+        assert_eq!(human_bytes(3 * 1_048_576), "3.0 MB");
+    }
+}
@@ -5,8 +5,12 @@
 use crate::crosswords::vi_mode::ViMotion;
 use crate::crosswords::Mode;
 use bitflags::bitflags;
-use rio_config::bindings::KeyBinding as ConfigKeyBinding;
+use rio_config::bindings::{
+    KeyBinding as ConfigKeyBinding, Leader as ConfigLeader,
+    MouseBinding as ConfigMouseBinding,
+};
 use std::fmt::Debug;
+use std::time::Duration;
 use winit::event::MouseButton;
 use winit::keyboard::Key::*;
 use winit::keyboard::{Key, KeyCode, KeyLocation, ModifiersState};
@@ -103,8 +107,25 @@ pub enum BindingKey {
 pub type KeyBinding = Binding<BindingKey>;
 pub type KeyBindings = Vec<KeyBinding>;
 
+/// Mouse button, with the clicks in a row it applies to or `None` for any.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct MouseTrigger {
+    pub button: MouseButton,
+    pub clicks: Option<u8>,
+}
+
+impl MouseTrigger {
+    #[inline]
+    fn any(button: MouseButton) -> MouseTrigger {
+        MouseTrigger {
+            button,
+            clicks: None,
+        }
+    }
+}
+
 /// Bindings that are triggered by a mouse button.
-pub type MouseBinding = Binding<MouseButton>;
+pub type MouseBinding = Binding<MouseTrigger>;
 
 bitflags! {
     /// Modes available for key bindings.
@@ -116,6 +137,8 @@ bitflags! {
         const VI                  = 0b0000_1000;
         const DISAMBIGUATE_KEYS   = 0b0010_0000;
         const ALL_KEYS_AS_ESC     = 0b0100_0000;
+        /// The leader key was pressed.
+        const LEADER              = 0b1000_0000;
     }
 }
 
@@ -163,18 +186,30 @@ impl Program {
 
 impl From<String> for Action {
     fn from(action: String) -> Action {
+        // Arguments of these keep their case.
+        if let Some(action) = send_action(&action) {
+            return action;
+        }
+
         let action = action.to_lowercase();
 
         let action_from_string = match action.as_str() {
             "paste" => Some(Action::Paste),
             "quit" => Some(Action::Quit),
             "copy" => Some(Action::Copy),
-            "clearhistory" => Some(Action::ClearHistory),
+            "copyasansi" => Some(Action::CopyAsAnsi),
+            "copyastsv" => Some(Action::CopyAsTsv),
+            "clearhistory" | "clearscrollback" => Some(Action::ClearHistory),
+            "clearscreen" => Some(Action::ClearScreen),
             "resetfontsize" => Some(Action::ResetFontSize),
             "increasefontsize" => Some(Action::IncreaseFontSize),
             "decreasefontsize" => Some(Action::DecreaseFontSize),
             "createwindow" => Some(Action::WindowCreateNew),
             "createtab" => Some(Action::TabCreateNew),
+            "duplicatetab" => Some(Action::TabDuplicate),
+            "renametab" => Some(Action::TabRename),
+            "togglemonitoractivity" => Some(Action::ToggleMonitorActivity),
+            "togglemonitorsilence" => Some(Action::ToggleMonitorSilence),
             "closetab" => Some(Action::TabCloseCurrent),
             "openconfigeditor" => Some(Action::ConfigEditor),
             "selectprevtab" => Some(Action::SelectPrevTab),
@@ -185,7 +220,32 @@ impl From<String> for Action {
             "scrollhalfpagedown" => Some(Action::ScrollHalfPageDown),
             "scrolltotop" => Some(Action::ScrollToTop),
             "scrolltobottom" => Some(Action::ScrollToBottom),
+            "scrolltopreviousprompt" => Some(Action::ScrollToPreviousPrompt),
+            "scrolltonextprompt" => Some(Action::ScrollToNextPrompt),
             "togglevimode" => Some(Action::ToggleViMode),
+            "selectlastcommandoutput" => Some(Action::SelectLastCommandOutput),
+            "copylastcommandoutput" => Some(Action::CopyLastCommandOutput),
+            "pagelastcommandoutput" => Some(Action::PageLastCommandOutput),
+            "viewlastcommandoutput" => Some(Action::ViewLastCommandOutput),
+            "viewscrollback" => Some(Action::ViewScrollback),
+            "searchselection" => Some(Action::SearchSelection),
+            "searchlastcommandoutput" => Some(Action::SearchLastCommandOutput),
+            "quickselect" => Some(Action::QuickSelect),
+            "contextmenu" => Some(Action::ContextMenu),
+            "clipboardhistory" => Some(Action::ClipboardHistory),
+            "rerunlastcommand" => Some(Action::RerunLastCommand),
+            "screenshot" => Some(Action::Screenshot),
+            "copyscreenshot" => Some(Action::CopyScreenshot),
+            "togglerecording" => Some(Action::ToggleRecording),
+            "toggleoutputlog" => Some(Action::ToggleOutputLog),
+            "togglestats" => Some(Action::ToggleStats),
+            "showglyphatlas" => Some(Action::ShowGlyphAtlas),
+            "searchconfirm" => Some(Action::Search(SearchAction::Confirm)),
+            "searchcancel" => Some(Action::Search(SearchAction::Cancel)),
+            "hintchoose" => Some(Action::Hint(HintAction::Choose)),
+            "hintcancel" => Some(Action::Hint(HintAction::Cancel)),
+            "hintprevious" => Some(Action::Hint(HintAction::Previous)),
+            "hintnext" => Some(Action::Hint(HintAction::Next)),
             "none" => Some(Action::None),
             _ => None,
         };
@@ -203,6 +263,20 @@ impl From<String> for Action {
             }
         }
 
+        let re = regex::Regex::new(r"createtabwithprofile\(([^()]+)\)").unwrap();
+        if let Some(matched) = re.captures(&action).and_then(|capture| capture.get(1)) {
+            return Action::TabCreateWithProfile(matched.as_str().trim().to_string());
+        }
+
+        let re = regex::Regex::new(r"togglecolorfilter\(([^()]+)\)").unwrap();
+        if let Some(filter) = re
+            .captures(&action)
+            .and_then(|capture| capture.get(1))
+            .and_then(|matched| matched.as_str().trim().parse().ok())
+        {
+            return Action::ToggleColorFilter(filter);
+        }
+
         let re = regex::Regex::new(r"run\(([^()]+)\)").unwrap();
         for capture in re.captures_iter(&action) {
             if let Some(matched) = capture.get(1) {
@@ -240,11 +314,100 @@ impl From<String> for Action {
     }
 }
 
+/// Parses `SendText("...")` and `SendBytes([..])`.
+fn send_action(action: &str) -> Option<Action> {
+    let re = regex::Regex::new(r#"(?is)^\s*sendtext\(\s*"(.*)"\s*\)\s*$"#).unwrap();
+    if let Some(text) = re.captures(action).and_then(|capture| capture.get(1)) {
+        return match unescape(text.as_str()) {
+            Ok(text) => Some(Action::SendText(text)),
+            Err(err) => {
+                log::error!("invalid text in key binding action {action}: {err}");
+                Some(Action::None)
+            }
+        };
+    }
+
+    let re = regex::Regex::new(r"(?i)^\s*sendbytes\(\s*\[([^\[\]]*)\]\s*\)\s*$").unwrap();
+    let bytes = re.captures(action).and_then(|capture| capture.get(1))?;
+    let bytes = bytes
+        .as_str()
+        .split(',')
+        .map(str::trim)
+        .filter(|byte| !byte.is_empty())
+        .map(|byte| match byte.strip_prefix("0x") {
+            Some(hex) => u8::from_str_radix(hex, 16),
+            None => byte.parse(),
+        })
+        .collect::<Result<Vec<u8>, _>>();
+    match bytes {
+        Ok(bytes) => Some(Action::SendBytes(bytes)),
+        Err(err) => {
+            log::error!("invalid bytes in key binding action {action}: {err}");
+            Some(Action::None)
+        }
+    }
+}
+
+/// Resolves the escapes of a `SendText` string: `\n`, `\r`, `\t`, `\e`,
+/// `\0`, `\\`, `\"`, `\xHH` and `\u{HHHH}`.
+fn unescape(text: &str) -> Result<String, String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        let escaped = match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('e') => '\x1b',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                u8::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|byte| byte.is_ascii())
+                    .map(char::from)
+                    .ok_or_else(|| format!("invalid escape \\x{hex}"))?
+            }
+            Some('u') => {
+                let rest = chars.as_str();
+                let code = rest
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.split_once('}'))
+                    .map(|(code, _)| code)
+                    .ok_or("expected \\u{...}")?;
+                let escaped = u32::from_str_radix(code, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("invalid escape \\u{{{code}}}"))?;
+                chars = rest[code.len() + 2..].chars();
+                escaped
+            }
+            Some(other) => return Err(format!("unknown escape \\{other}")),
+            None => return Err(String::from("trailing \\")),
+        };
+        unescaped.push(escaped);
+    }
+    Ok(unescaped)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Action {
     /// Write an escape sequence.
     Esc(String),
 
+    /// Write text as if it was typed.
+    SendText(String),
+
+    /// Write bytes as if they were typed.
+    SendBytes(Vec<u8>),
+
     /// Run given command.
     #[allow(unused)]
     Run(Program),
@@ -263,12 +426,24 @@ pub enum Action {
     /// Perform mouse binding exclusive action.
     Mouse(MouseAction),
 
+    /// Perform search prompt exclusive action.
+    Search(SearchAction),
+
+    /// Perform quick select hints exclusive action.
+    Hint(HintAction),
+
     /// Paste contents of system clipboard.
     Paste,
 
     /// Store current selection into clipboard.
     Copy,
 
+    /// Store current selection into clipboard as text with ANSI escapes.
+    CopyAsAnsi,
+
+    /// Store current selection into clipboard as tab separated values.
+    CopyAsTsv,
+
     #[cfg(not(any(target_os = "macos", windows)))]
     #[allow(dead_code)]
     /// Store current selection into selection buffer.
@@ -304,9 +479,18 @@ pub enum Action {
     /// Scroll all the way to the bottom.
     ScrollToBottom,
 
+    /// Scroll to the shell prompt above the top of the viewport.
+    ScrollToPreviousPrompt,
+
+    /// Scroll to the shell prompt below the top of the viewport.
+    ScrollToNextPrompt,
+
     /// Clear the display buffer(s) to remove history.
     ClearHistory,
 
+    /// Move the visible lines to the history and ask the program to redraw.
+    ClearScreen,
+
     /// Hide the Rio window.
     #[allow(dead_code)]
     Hide,
@@ -340,6 +524,22 @@ pub enum Action {
     /// Create a new Rio tab.
     TabCreateNew,
 
+    /// Create a new Rio tab using a profile.
+    TabCreateWithProfile(String),
+
+    /// Create a new Rio tab with the shell, environment, profile and working
+    /// directory of the current one.
+    TabDuplicate,
+
+    /// Name the current tab, the name is shown instead of its title.
+    TabRename,
+
+    /// Mark the current tab when output comes after a while without any.
+    ToggleMonitorActivity,
+
+    /// Mark the current tab when no output came for a while.
+    ToggleMonitorSilence,
+
     /// Switch to next tab.
     SelectNextTab,
 
@@ -365,9 +565,67 @@ pub enum Action {
     /// Clear active selection.
     ClearSelection,
 
+    /// Select the output of the most recent command.
+    SelectLastCommandOutput,
+
+    /// Store the output of the most recent command into clipboard.
+    CopyLastCommandOutput,
+
+    /// Open the output of the most recent command in `$PAGER`.
+    PageLastCommandOutput,
+
+    /// Show the output of the most recent command in the built-in pager, or
+    /// the scrollback without shell integration.
+    ViewLastCommandOutput,
+
+    /// Show the scrollback in the built-in pager.
+    ViewScrollback,
+
+    /// Search the lines of the selection in the scrollback pager.
+    SearchSelection,
+
+    /// Search the output of the most recent command in the scrollback pager.
+    SearchLastCommandOutput,
+
+    /// Pick a path, url, address or hash from the recent output and insert
+    /// it at the prompt.
+    QuickSelect,
+
+    /// Pick one of the recent copies to paste.
+    ClipboardHistory,
+
+    /// Show the menu of the actions at hand, at the mouse when a mouse button
+    /// opens it and at the cursor otherwise.
+    ContextMenu,
+
+    /// Run the most recent command again.
+    RerunLastCommand,
+
+    /// Save the screen, or the selection, as a PNG file.
+    Screenshot,
+
+    /// Store the screen, or the selection, as an image into clipboard.
+    CopyScreenshot,
+
+    /// Start or stop recording the terminal output as an asciicast file.
+    ToggleRecording,
+
+    /// Start or stop copying the terminal output to a log file.
+    ToggleOutputLog,
+
+    /// Show or hide the overlay with the frame rate, the input latency and
+    /// the output throughput.
+    ToggleStats,
+
+    /// Show the glyph texture cache with the rows in use and evictions.
+    ShowGlyphAtlas,
+
     /// Toggle vi mode.
     ToggleViMode,
 
+    /// Apply an accessibility color filter, or remove it when it is applied.
+    ToggleColorFilter(rio_config::ColorFilter),
+
     /// Allow receiving char input.
     ReceiveChar,
 
@@ -379,6 +637,22 @@ pub enum Action {
     SelectLastTab,
 }
 
+impl Action {
+    /// Whether the action relies on the prompt marks of the shell integration.
+    pub fn uses_shell_integration(&self) -> bool {
+        matches!(
+            self,
+            Action::ScrollToPreviousPrompt
+                | Action::ScrollToNextPrompt
+                | Action::SelectLastCommandOutput
+                | Action::CopyLastCommandOutput
+                | Action::PageLastCommandOutput
+                | Action::SearchLastCommandOutput
+                | Action::RerunLastCommand
+        )
+    }
+}
+
 impl From<&'static str> for Action {
     fn from(s: &'static str) -> Action {
         Action::Esc(s.into())
@@ -467,11 +741,51 @@ macro_rules! trigger {
             location: KeyLocation::Standard,
         }
     }};
+    (KeyBinding, $key:expr, $location:expr) => {{
+        BindingKey::Keycode {
+            key: $key,
+            location: $location,
+        }
+    }};
     ($ty:ident, $key:expr,) => {{
         $key
     }};
 }
 
+/// Actions of the search prompt of the pager.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SearchAction {
+    /// Search the text typed.
+    Confirm,
+    /// Close the prompt.
+    Cancel,
+}
+
+impl From<SearchAction> for Action {
+    fn from(action: SearchAction) -> Self {
+        Self::Search(action)
+    }
+}
+
+/// Actions of the quick select hints.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HintAction {
+    /// Insert the selected hint.
+    Choose,
+    /// Close the hints.
+    Cancel,
+    /// Select the previous hint.
+    Previous,
+    /// Select the next hint.
+    Next,
+}
+
+impl From<HintAction> for Action {
+    fn from(action: HintAction) -> Self {
+        Self::Hint(action)
+    }
+}
+
 /// Mouse binding specific actions.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum MouseAction {
@@ -485,19 +799,84 @@ impl From<MouseAction> for Action {
     }
 }
 
-pub fn default_mouse_bindings() -> Vec<MouseBinding> {
-    bindings!(
+pub fn default_mouse_bindings(
+    unprocessed_config_mouse_bindings: Vec<ConfigMouseBinding>,
+) -> Vec<MouseBinding> {
+    let bindings = bindings!(
         MouseBinding;
-        MouseButton::Right;                            MouseAction::ExpandSelection;
-        MouseButton::Right,   ModifiersState::CONTROL; MouseAction::ExpandSelection;
-        MouseButton::Middle, ~BindingMode::VI;         Action::PasteSelection;
-    )
+        MouseTrigger::any(MouseButton::Right);                            MouseAction::ExpandSelection;
+        MouseTrigger::any(MouseButton::Right),   ModifiersState::CONTROL; Action::ContextMenu;
+        MouseTrigger::any(MouseButton::Middle), ~BindingMode::VI;         Action::PasteSelection;
+    );
+
+    config_mouse_bindings(unprocessed_config_mouse_bindings, bindings)
+}
+
+/// Mouse bindings of the configuration replace the default ones triggered the
+/// same way, and add up when they share a trigger.
+pub fn config_mouse_bindings(
+    config_mouse_bindings: Vec<ConfigMouseBinding>,
+    mut bindings: Vec<MouseBinding>,
+) -> Vec<MouseBinding> {
+    let mut defaults = bindings.len();
+    for config_binding in config_mouse_bindings {
+        match convert_mouse(config_binding) {
+            Ok(mouse_binding) => {
+                let count = bindings.len();
+                let mut index = 0;
+                bindings.retain(|binding| {
+                    index += 1;
+                    index > defaults || !binding.triggers_match(&mouse_binding)
+                });
+                defaults -= count - bindings.len();
+                log::info!("added a new mouse_binding: {:?}", mouse_binding);
+                bindings.push(mouse_binding)
+            }
+            Err(err_message) => {
+                log::error!("error loading a mouse binding: {:?}", err_message);
+            }
+        }
+    }
+    bindings
+}
+
+fn parse_button(button: &str) -> Result<MouseButton, String> {
+    match button.to_lowercase().as_str() {
+        "left" => Ok(MouseButton::Left),
+        "middle" => Ok(MouseButton::Middle),
+        "right" => Ok(MouseButton::Right),
+        "back" => Ok(MouseButton::Back),
+        "forward" => Ok(MouseButton::Forward),
+        other => other
+            .parse()
+            .map(MouseButton::Other)
+            .map_err(|_| format!("unable to find defined button {button}")),
+    }
+}
+
+fn convert_mouse(
+    config_mouse_binding: ConfigMouseBinding,
+) -> Result<MouseBinding, String> {
+    let button = parse_button(&config_mouse_binding.button)?;
+    let (mods, _) = parse_modifiers(&config_mouse_binding.with);
+    let modes = parse_mode(&config_mouse_binding.mode);
+
+    Ok(MouseBinding {
+        trigger: MouseTrigger {
+            button,
+            clicks: config_mouse_binding.clicks,
+        },
+        mods,
+        action: config_mouse_binding.action.into(),
+        mode: modes.mode,
+        notmode: modes.not_mode,
+    })
 }
 
 pub fn default_key_bindings(
     unprocessed_config_key_bindings: Vec<ConfigKeyBinding>,
     ignore_platform_key_bindings: bool,
-) -> Vec<KeyBinding> {
+) -> KeyTables {
     let mut bindings = bindings!(
         KeyBinding;
         Copy;  Action::Copy;
@@ -505,6 +884,7 @@ pub fn default_key_bindings(
         Paste, ~BindingMode::VI; Action::Paste;
         "l", ModifiersState::CONTROL; Action::ClearLogNotice;
         "l", ModifiersState::CONTROL; Action::ReceiveChar;
+        ContextMenu, ~BindingMode::VI; Action::ContextMenu;
         Tab,  ModifiersState::SHIFT, ~BindingMode::VI;
             Action::Esc("\x1b[Z".into());
         Home,     ModifiersState::SHIFT, ~BindingMode::ALT_SCREEN; Action::ScrollToTop;
@@ -543,6 +923,21 @@ pub fn default_key_bindings(
             Action::Esc("\x1bOD".into());
         ArrowLeft,  ~BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::ALL_KEYS_AS_ESC;
             Action::Esc("\x1b[D".into());
+        // Keypad keys, the arrows are the ones of a keypad without num lock.
+        ArrowUp    => KeyLocation::Numpad, +BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::ALL_KEYS_AS_ESC, ~BindingMode::DISAMBIGUATE_KEYS;
+            Action::Esc("\x1bOA".into());
+        ArrowDown  => KeyLocation::Numpad, +BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::ALL_KEYS_AS_ESC, ~BindingMode::DISAMBIGUATE_KEYS;
+            Action::Esc("\x1bOB".into());
+        ArrowRight => KeyLocation::Numpad, +BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::ALL_KEYS_AS_ESC, ~BindingMode::DISAMBIGUATE_KEYS;
+            Action::Esc("\x1bOC".into());
+        ArrowLeft  => KeyLocation::Numpad, +BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::ALL_KEYS_AS_ESC, ~BindingMode::DISAMBIGUATE_KEYS;
+            Action::Esc("\x1bOD".into());
+        Home       => KeyLocation::Numpad, +BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::ALL_KEYS_AS_ESC, ~BindingMode::DISAMBIGUATE_KEYS;
+            Action::Esc("\x1bOH".into());
+        End        => KeyLocation::Numpad, +BindingMode::APP_CURSOR, ~BindingMode::VI, ~BindingMode::ALL_KEYS_AS_ESC, ~BindingMode::DISAMBIGUATE_KEYS;
+            Action::Esc("\x1bOF".into());
+        Enter      => KeyLocation::Numpad, +BindingMode::APP_KEYPAD, ~BindingMode::VI, ~BindingMode::ALL_KEYS_AS_ESC, ~BindingMode::DISAMBIGUATE_KEYS;
+            Action::Esc("\x1bOM".into());
         Insert,     ~BindingMode::VI, ~BindingMode::ALL_KEYS_AS_ESC, ~BindingMode::DISAMBIGUATE_KEYS; Action::Esc("\x1b[2~".into());
         Delete,     ~BindingMode::VI, ~BindingMode::ALL_KEYS_AS_ESC, ~BindingMode::DISAMBIGUATE_KEYS; Action::Esc("\x1b[3~".into());
         PageUp,     ~BindingMode::VI, ~BindingMode::ALL_KEYS_AS_ESC, ~BindingMode::DISAMBIGUATE_KEYS; Action::Esc("\x1b[5~".into());
@@ -648,11 +1043,145 @@ pub fn default_key_bindings(
         ArrowDown,   ModifiersState::ALT, ~BindingMode::VI, ~BindingMode::ALL_KEYS_AS_ESC, ~BindingMode::DISAMBIGUATE_KEYS; Action::Esc("\x1b[1;3B".into());
     ));
 
+    // Application keypad (DECKPAM/DECNKM), from:
+    // https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h2-PC-Style-Function-Keys
+    for (key, final_byte) in [
+        ("0", 'p'),
+        ("1", 'q'),
+        ("2", 'r'),
+        ("3", 's'),
+        ("4", 't'),
+        ("5", 'u'),
+        ("6", 'v'),
+        ("7", 'w'),
+        ("8", 'x'),
+        ("9", 'y'),
+        ("*", 'j'),
+        ("+", 'k'),
+        (",", 'l'),
+        ("-", 'm'),
+        (".", 'n'),
+        ("/", 'o'),
+        ("=", 'X'),
+    ] {
+        bindings.extend(bindings!(
+            KeyBinding;
+            Character(key.into()) => KeyLocation::Numpad, +BindingMode::APP_KEYPAD, ~BindingMode::VI, ~BindingMode::ALL_KEYS_AS_ESC, ~BindingMode::DISAMBIGUATE_KEYS;
+                Action::Esc(format!("\x1bO{final_byte}"));
+        ));
+    }
+
     if !ignore_platform_key_bindings {
         bindings.extend(platform_key_bindings());
     }
 
-    config_key_bindings(unprocessed_config_key_bindings, bindings)
+    let (copy, normal) = bindings
+        .into_iter()
+        .partition(|binding| binding.mode.contains(BindingMode::VI));
+    let mut tables = KeyTables {
+        normal,
+        copy,
+        search: bindings!(
+            KeyBinding;
+            Enter; SearchAction::Confirm;
+            Escape; SearchAction::Cancel;
+        ),
+        hints: bindings!(
+            KeyBinding;
+            Enter; HintAction::Choose;
+            Escape; HintAction::Cancel;
+            ArrowUp; HintAction::Previous;
+            "p", ModifiersState::CONTROL; HintAction::Previous;
+            ArrowDown; HintAction::Next;
+            Tab; HintAction::Next;
+            "n", ModifiersState::CONTROL; HintAction::Next;
+        ),
+    };
+
+    for table in [
+        KeyTable::Normal,
+        KeyTable::Copy,
+        KeyTable::Search,
+        KeyTable::Hints,
+    ] {
+        let config_bindings = unprocessed_config_key_bindings
+            .iter()
+            .filter(|binding| key_table(&binding.mode) == table)
+            .cloned()
+            .collect();
+        let bindings = std::mem::take(tables.table_mut(table));
+        *tables.table_mut(table) = config_key_bindings(config_bindings, bindings);
+    }
+
+    tables
+}
+
+/// Tables of key bindings, the copy mode (vi mode), search and hints tables
+/// are stacked over the normal one while they are active.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyTable {
+    Normal,
+    Copy,
+    Search,
+    Hints,
+}
+
+/// Table a binding of the configuration goes to, from its `mode`.
+fn key_table(mode: &str) -> KeyTable {
+    let mut table = KeyTable::Normal;
+    for mode in mode.split('|') {
+        match mode.trim().to_lowercase().as_str() {
+            "vi" | "copy" => table = KeyTable::Copy,
+            "search" => return KeyTable::Search,
+            "hints" => return KeyTable::Hints,
+            _ => {}
+        }
+    }
+    table
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct KeyTables {
+    normal: KeyBindings,
+    copy: KeyBindings,
+    search: KeyBindings,
+    hints: KeyBindings,
+}
+
+impl KeyTables {
+    #[inline]
+    pub fn table(&self, table: KeyTable) -> &[KeyBinding] {
+        match table {
+            KeyTable::Normal => &self.normal,
+            KeyTable::Copy => &self.copy,
+            KeyTable::Search => &self.search,
+            KeyTable::Hints => &self.hints,
+        }
+    }
+
+    #[inline]
+    fn table_mut(&mut self, table: KeyTable) -> &mut KeyBindings {
+        match table {
+            KeyTable::Normal => &mut self.normal,
+            KeyTable::Copy => &mut self.copy,
+            KeyTable::Search => &mut self.search,
+            KeyTable::Hints => &mut self.hints,
+        }
+    }
+
+    /// Action of the first binding of `table` triggered by `input`.
+    pub fn action(
+        &self,
+        table: KeyTable,
+        mode: BindingMode,
+        mods: ModifiersState,
+        input: &BindingKey,
+    ) -> Option<&Action> {
+        self.table(table)
+            .iter()
+            .find(|binding| binding.is_triggered_by(mode.clone(), mods, input))
+            .map(|binding| &binding.action)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -661,15 +1190,15 @@ pub struct ModeWrapper {
     pub not_mode: BindingMode,
 }
 
-#[inline]
-fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
-    let (key, location) = if config_key_binding.key.chars().count() == 1 {
+/// Key of a binding in the configuration.
+fn parse_key(key: &str) -> Result<(Key, KeyLocation), String> {
+    let key = if key.chars().count() == 1 {
         (
-            Key::Character(config_key_binding.key.to_lowercase().into()),
+            Key::Character(key.to_lowercase().into()),
             KeyLocation::Standard,
         )
     } else {
-        match config_key_binding.key.to_lowercase().as_str() {
+        match key.to_lowercase().as_str() {
             "home" => (Key::Home, KeyLocation::Standard),
             "space" => (Key::Space, KeyLocation::Standard),
             "delete" => (Key::Delete, KeyLocation::Standard),
@@ -729,23 +1258,61 @@ fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
 
             // Special cases
             "tab" => (Key::Tab, KeyLocation::Standard),
+            "f1" => (Key::F1, KeyLocation::Standard),
+            "f2" => (Key::F2, KeyLocation::Standard),
+            "f3" => (Key::F3, KeyLocation::Standard),
+            "f4" => (Key::F4, KeyLocation::Standard),
+            "f5" => (Key::F5, KeyLocation::Standard),
+            "f6" => (Key::F6, KeyLocation::Standard),
+            "f7" => (Key::F7, KeyLocation::Standard),
+            "f8" => (Key::F8, KeyLocation::Standard),
+            "f9" => (Key::F9, KeyLocation::Standard),
+            "f10" => (Key::F10, KeyLocation::Standard),
+            "f11" => (Key::F11, KeyLocation::Standard),
+            "f12" => (Key::F12, KeyLocation::Standard),
+            "f13" => (Key::F13, KeyLocation::Standard),
+            "f14" => (Key::F14, KeyLocation::Standard),
+            "f15" => (Key::F15, KeyLocation::Standard),
+            "f16" => (Key::F16, KeyLocation::Standard),
+            "f17" => (Key::F17, KeyLocation::Standard),
+            "f18" => (Key::F18, KeyLocation::Standard),
+            "f19" => (Key::F19, KeyLocation::Standard),
+            "f20" => (Key::F20, KeyLocation::Standard),
+            "f21" => (Key::F21, KeyLocation::Standard),
+            "f22" => (Key::F22, KeyLocation::Standard),
+            "f23" => (Key::F23, KeyLocation::Standard),
+            "f24" => (Key::F24, KeyLocation::Standard),
             _ => return Err("Unable to find defined 'keycode'".to_string()),
         }
     };
 
-    let trigger = BindingKey::Keycode { key, location };
+    Ok(key)
+}
 
+/// Modifiers of a binding in the configuration, and whether it follows the
+/// leader key.
+fn parse_modifiers(with: &str) -> (ModifiersState, bool) {
     let mut res = ModifiersState::empty();
-    for modifier in config_key_binding.with.split('|') {
+    let mut leader = false;
+    for modifier in with.split('|') {
         match modifier.trim().to_lowercase().as_str() {
             "command" | "super" => res.insert(ModifiersState::SUPER),
             "shift" => res.insert(ModifiersState::SHIFT),
             "alt" | "option" => res.insert(ModifiersState::ALT),
             "control" => res.insert(ModifiersState::CONTROL),
+            "leader" => leader = true,
             "none" => (),
             _ => (),
         }
     }
+    (res, leader)
+}
+
+#[inline]
+fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
+    let (key, location) = parse_key(&config_key_binding.key)?;
+    let trigger = BindingKey::Keycode { key, location };
+    let (res, leader) = parse_modifiers(&config_key_binding.with);
 
     let mut action: Action = config_key_binding.action.into();
     if !config_key_binding.text.is_empty() {
@@ -758,12 +1325,26 @@ fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
         }
     }
 
+    let mut res_mode = parse_mode(&config_key_binding.mode);
+    res_mode.mode.set(BindingMode::LEADER, leader);
+
+    Ok(KeyBinding {
+        trigger,
+        mods: res,
+        action,
+        mode: res_mode.mode,
+        notmode: res_mode.not_mode,
+    })
+}
+
+/// Modes a binding of the configuration applies in, and doesn't.
+fn parse_mode(mode: &str) -> ModeWrapper {
     let mut res_mode = ModeWrapper {
         mode: BindingMode::empty(),
         not_mode: BindingMode::empty(),
     };
 
-    for modifier in config_key_binding.mode.split('|') {
+    for modifier in mode.split('|') {
         match modifier.trim().to_lowercase().as_str() {
             "appcursor" => res_mode.mode |= BindingMode::APP_CURSOR,
             "~appcursor" => res_mode.not_mode |= BindingMode::APP_CURSOR,
@@ -780,13 +1361,49 @@ fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
         }
     }
 
-    Ok(KeyBinding {
-        trigger,
-        mods: res,
-        action,
-        mode: res_mode.mode,
-        notmode: res_mode.not_mode,
-    })
+    res_mode
+}
+
+/// Key starting the bindings with `leader` in their modifiers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Leader {
+    pub trigger: BindingKey,
+    pub mods: ModifiersState,
+    pub timeout: Duration,
+    /// Shown while waiting for the next key.
+    pub label: String,
+}
+
+impl Leader {
+    pub fn new(config: &ConfigLeader) -> Result<Leader, String> {
+        let (key, location) = parse_key(&config.key)?;
+        let (mods, _) = parse_modifiers(&config.with);
+        let mut label = String::new();
+        for (modifier, name) in [
+            (ModifiersState::CONTROL, "control"),
+            (ModifiersState::ALT, "alt"),
+            (ModifiersState::SHIFT, "shift"),
+            (ModifiersState::SUPER, "super"),
+        ] {
+            if mods.contains(modifier) {
+                label.push_str(name);
+                label.push('+');
+            }
+        }
+        label.push_str(&config.key.to_lowercase());
+
+        Ok(Leader {
+            trigger: BindingKey::Keycode { key, location },
+            mods,
+            timeout: Duration::from_millis(config.timeout),
+            label,
+        })
+    }
+
+    #[inline]
+    pub fn is_triggered_by(&self, mods: ModifiersState, input: &BindingKey) -> bool {
+        self.trigger == *input && self.mods == mods
+    }
 }
 
 pub fn config_key_bindings(
@@ -797,35 +1414,34 @@ pub fn config_key_bindings(
         return bindings;
     }
 
+    // Bindings of the configuration replace the default ones triggered the
+    // same way, and add up when they share a trigger.
+    let mut defaults = bindings.len();
     for ckb in config_key_bindings {
         match convert(ckb) {
-            Ok(key_binding) => match key_binding.action {
-                Action::None | Action::ReceiveChar => {
-                    let mut found_idx = None;
-                    for (idx, binding) in bindings.iter().enumerate() {
-                        if binding.triggers_match(&key_binding) {
-                            found_idx = Some(idx);
-                            break;
-                        }
-                    }
-
-                    if let Some(idx) = found_idx {
-                        bindings.remove(idx);
-                        log::warn!(
-                            "overwritted a previous key_binding with new one: {:?}",
-                            key_binding
-                        );
-                    } else {
-                        log::info!("added a new key_binding: {:?}", key_binding);
-                    }
-
-                    bindings.push(key_binding)
-                }
-                _ => {
+            Ok(key_binding) => {
+                let count = bindings.len();
+                let mut index = 0;
+                bindings.retain(|binding| {
+                    index += 1;
+                    index > defaults
+                        || !binding.triggers_match(&key_binding)
+                        // Chords don't replace the bindings of their keys.
+                        || binding.mode.contains(BindingMode::LEADER)
+                            != key_binding.mode.contains(BindingMode::LEADER)
+                });
+                let replaced = count - bindings.len();
+                defaults -= replaced;
+                if replaced > 0 {
+                    log::warn!(
+                        "overwritted {replaced} previous key_binding with new one: {:?}",
+                        key_binding
+                    );
+                } else {
                     log::info!("added a new key_binding: {:?}", key_binding);
-                    bindings.push(key_binding)
                 }
-            },
+                bindings.push(key_binding)
+            }
             Err(err_message) => {
                 log::error!("error loading a key binding: {:?}", err_message);
             }
@@ -850,9 +1466,8 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
             Action::Esc("\x1bb".into());
         ArrowRight, ModifiersState::ALT,  ~BindingMode::VI, ~BindingMode::ALL_KEYS_AS_ESC, ~BindingMode::DISAMBIGUATE_KEYS;
             Action::Esc("\x1bf".into());
-        "k", ModifiersState::SUPER, ~BindingMode::VI;
-            Action::Esc("\x0c".into());
-        "k", ModifiersState::SUPER, ~BindingMode::VI;  Action::ClearHistory;
+        "k", ModifiersState::SUPER, ~BindingMode::VI; Action::ClearScreen;
+        "k", ModifiersState::SUPER, ~BindingMode::VI; Action::ClearHistory;
         "v", ModifiersState::SUPER, ~BindingMode::VI; Action::Paste;
         "f", ModifiersState::CONTROL | ModifiersState::SUPER; Action::ToggleFullscreen;
         "c", ModifiersState::SUPER; Action::Copy;
@@ -868,6 +1483,8 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
         "[", ModifiersState::SUPER | ModifiersState::SHIFT; Action::SelectPrevTab;
         "]", ModifiersState::SUPER | ModifiersState::SHIFT; Action::SelectNextTab;
         "w", ModifiersState::SUPER; Action::TabCloseCurrent;
+        ArrowUp, ModifiersState::SUPER, ~BindingMode::ALT_SCREEN; Action::ScrollToPreviousPrompt;
+        ArrowDown, ModifiersState::SUPER, ~BindingMode::ALT_SCREEN; Action::ScrollToNextPrompt;
         ",", ModifiersState::SUPER; Action::ConfigEditor;
         "1", ModifiersState::SUPER; Action::SelectTab(0);
         "2", ModifiersState::SUPER; Action::SelectTab(1);
@@ -905,6 +1522,10 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
         "]", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::SelectNextTab;
         "w", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::TabCloseCurrent;
         ",", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::ConfigEditor;
+        "z", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::ALT_SCREEN; Action::ScrollToPreviousPrompt;
+        "x", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::ALT_SCREEN; Action::ScrollToNextPrompt;
+        "k", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::VI; Action::ClearScreen;
+        "k", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::VI; Action::ClearHistory;
     )
 }
 
@@ -932,6 +1553,10 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
         "[", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::SelectPrevTab;
         "]", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::SelectNextTab;
         ",", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::ConfigEditor;
+        "z", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::ALT_SCREEN; Action::ScrollToPreviousPrompt;
+        "x", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::ALT_SCREEN; Action::ScrollToNextPrompt;
+        "k", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::VI; Action::ClearScreen;
+        "k", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::VI; Action::ClearHistory;
         // This is actually a Windows Powershell shortcut
         // https://github.com/alacritty/alacritty/issues/2930
         // https://github.com/raphamorim/rio/issues/220#issuecomment-1761651339
@@ -1220,4 +1845,320 @@ mod tests {
         assert_eq!(new_bindings.len(), 2);
         assert_eq!(new_bindings[1].action, Action::ReceiveChar);
     }
+
+    #[test]
+    fn bindings_keypad_modes() {
+        let bindings = default_key_bindings(vec![], true);
+        let action = |key: Key, location: KeyLocation, mode: BindingMode| {
+            let key = BindingKey::Keycode { key, location };
+            bindings
+                .action(KeyTable::Normal, mode, ModifiersState::empty(), &key)
+                .cloned()
+        };
+
+        let one = || Character("1".into());
+        assert_eq!(
+            action(one(), KeyLocation::Numpad, BindingMode::APP_KEYPAD),
+            Some(Action::Esc("\x1bOq".into()))
+        );
+        assert_eq!(
+            action(one(), KeyLocation::Numpad, BindingMode::empty()),
+            None
+        );
+        assert_eq!(
+            action(one(), KeyLocation::Standard, BindingMode::APP_KEYPAD),
+            None
+        );
+        assert_eq!(
+            action(Enter, KeyLocation::Numpad, BindingMode::APP_KEYPAD),
+            Some(Action::Esc("\x1bOM".into()))
+        );
+        // The kitty keyboard protocol encodes the keypad itself.
+        assert_eq!(
+            action(
+                one(),
+                KeyLocation::Numpad,
+                BindingMode::APP_KEYPAD | BindingMode::DISAMBIGUATE_KEYS
+            ),
+            None
+        );
+
+        assert_eq!(
+            action(ArrowUp, KeyLocation::Numpad, BindingMode::APP_CURSOR),
+            Some(Action::Esc("\x1bOA".into()))
+        );
+        assert_eq!(
+            action(ArrowUp, KeyLocation::Standard, BindingMode::APP_CURSOR),
+            Some(Action::Esc("\x1bOA".into()))
+        );
+        assert_eq!(
+            action(ArrowUp, KeyLocation::Standard, BindingMode::empty()),
+            Some(Action::Esc("\x1b[A".into()))
+        );
+    }
+
+    #[test]
+    fn send_actions() {
+        let action = |action: &str| Action::from(action.to_string());
+        assert_eq!(
+            action(r#"SendText("git Status\n")"#),
+            Action::SendText(String::from("git Status\n"))
+        );
+        assert_eq!(
+            action(r#"sendtext("\e[A \x1b \"\\ \u{1F980}\t")"#),
+            Action::SendText(String::from("\x1b[A \x1b \"\\ \u{1F980}\t"))
+        );
+        assert_eq!(action(r#"SendText("\q")"#), Action::None);
+        assert_eq!(action(r#"SendText("\xff")"#), Action::None);
+        assert_eq!(action(r#"SendText("\u{zz}")"#), Action::None);
+
+        assert_eq!(
+            action("SendBytes([27, 0x5b, 65])"),
+            Action::SendBytes(vec![27, 91, 65])
+        );
+        assert_eq!(action("SendBytes([255])"), Action::SendBytes(vec![255]));
+        assert_eq!(action("SendBytes([256])"), Action::None);
+        assert_eq!(action("SendBytes([])"), Action::SendBytes(vec![]));
+    }
+
+    #[test]
+    fn bindings_function_keys() {
+        let binding = convert(ConfigKeyBinding {
+            key: String::from("F12"),
+            action: String::from(r#"SendText("cargo test\n")"#),
+            with: String::from(""),
+            bytes: vec![],
+            text: String::from(""),
+            mode: String::from(""),
+        })
+        .unwrap();
+
+        assert_eq!(
+            binding.trigger,
+            BindingKey::Keycode {
+                key: F12,
+                location: KeyLocation::Standard
+            }
+        );
+        assert_eq!(
+            binding.action,
+            Action::SendText(String::from("cargo test\n"))
+        );
+    }
+
+    #[test]
+    fn bindings_leader() {
+        let leader = Leader::new(&ConfigLeader {
+            key: String::from("a"),
+            with: String::from("control"),
+            timeout: 1000,
+        })
+        .unwrap();
+        let a = BindingKey::Keycode {
+            key: Character("a".into()),
+            location: KeyLocation::Standard,
+        };
+        assert_eq!(leader.label, "control+a");
+        assert!(leader.is_triggered_by(ModifiersState::CONTROL, &a));
+        assert!(!leader.is_triggered_by(ModifiersState::empty(), &a));
+
+        let binding = convert(ConfigKeyBinding {
+            key: String::from("c"),
+            action: String::from("CreateTab"),
+            with: String::from("leader"),
+            bytes: vec![],
+            text: String::from(""),
+            mode: String::from(""),
+        })
+        .unwrap();
+        let c = BindingKey::Keycode {
+            key: Character("c".into()),
+            location: KeyLocation::Standard,
+        };
+        assert_eq!(binding.mode, BindingMode::LEADER);
+        assert!(!binding.is_triggered_by(
+            BindingMode::empty(),
+            ModifiersState::empty(),
+            &c
+        ));
+        assert!(binding.is_triggered_by(
+            BindingMode::LEADER,
+            ModifiersState::empty(),
+            &c
+        ));
+    }
+
+    #[test]
+    fn bindings_mouse() {
+        let config_binding =
+            |button: &str, with: &str, clicks: Option<u8>, action: &str| {
+                ConfigMouseBinding {
+                    button: String::from(button),
+                    with: String::from(with),
+                    clicks,
+                    action: String::from(action),
+                    mode: String::from("~vi"),
+                }
+            };
+        let trigger =
+            |button: MouseButton, clicks: Option<u8>| MouseTrigger { button, clicks };
+
+        let bindings = default_mouse_bindings(vec![
+            config_binding("middle", "", None, "Paste"),
+            config_binding("back", "", None, "ScrollToPreviousPrompt"),
+            config_binding("Forward", "", None, "ScrollToNextPrompt"),
+            config_binding("left", "control", Some(2), "Copy"),
+            config_binding("9", "", None, "SendText(\"\\e\")"),
+            config_binding("wheel", "", None, "Paste"),
+        ]);
+        let action = |mods: ModifiersState, input: &MouseTrigger| {
+            bindings
+                .iter()
+                .find(|binding| {
+                    binding.is_triggered_by(BindingMode::empty(), mods, input)
+                })
+                .map(|binding| &binding.action)
+        };
+
+        let none = ModifiersState::empty();
+        assert_eq!(
+            action(none, &trigger(MouseButton::Middle, None)),
+            Some(&Action::Paste)
+        );
+        assert_eq!(
+            bindings
+                .iter()
+                .filter(|binding| binding.trigger.button == MouseButton::Middle)
+                .count(),
+            1
+        );
+        assert_eq!(
+            action(none, &trigger(MouseButton::Back, None)),
+            Some(&Action::ScrollToPreviousPrompt)
+        );
+        assert_eq!(
+            action(none, &trigger(MouseButton::Forward, None)),
+            Some(&Action::ScrollToNextPrompt)
+        );
+        assert_eq!(
+            action(none, &trigger(MouseButton::Other(9), None)),
+            Some(&Action::SendText(String::from("\x1b")))
+        );
+        assert_eq!(
+            action(
+                ModifiersState::CONTROL,
+                &trigger(MouseButton::Left, Some(2))
+            ),
+            Some(&Action::Copy)
+        );
+        assert_eq!(
+            action(
+                ModifiersState::CONTROL,
+                &trigger(MouseButton::Left, Some(1))
+            ),
+            None
+        );
+        // Defaults without a configured replacement are kept.
+        assert_eq!(
+            action(ModifiersState::CONTROL, &trigger(MouseButton::Right, None)),
+            Some(&Action::ContextMenu)
+        );
+        assert!(!bindings
+            .iter()
+            .any(|binding| binding.action == Action::Paste
+                && !matches!(binding.trigger.button, MouseButton::Middle)));
+    }
+
+    #[test]
+    fn bindings_tables() {
+        let config_binding =
+            |key: &str, with: &str, action: &str, mode: &str| ConfigKeyBinding {
+                key: String::from(key),
+                action: String::from(action),
+                with: String::from(with),
+                bytes: vec![],
+                text: String::from(""),
+                mode: String::from(mode),
+            };
+        let key = |key: Key| BindingKey::Keycode {
+            key,
+            location: KeyLocation::Standard,
+        };
+
+        let tables = default_key_bindings(
+            vec![
+                config_binding("y", "", "Paste", "vi"),
+                config_binding("j", "control", "HintNext", "hints"),
+                config_binding("esc", "", "None", "search"),
+                config_binding("c", "control | shift", "Paste", ""),
+                config_binding("c", "control | shift", "Copy", ""),
+                config_binding("c", "leader | control | shift", "CreateTab", ""),
+            ],
+            true,
+        );
+        assert!(tables
+            .table(KeyTable::Copy)
+            .iter()
+            .all(|binding| binding.mode.contains(BindingMode::VI)));
+        assert!(tables
+            .table(KeyTable::Normal)
+            .iter()
+            .all(|binding| !binding.mode.contains(BindingMode::VI)));
+
+        // Every default binding of "y" in vi mode is replaced.
+        let y = key(Character("y".into()));
+        let copy: Vec<&Action> = tables
+            .table(KeyTable::Copy)
+            .iter()
+            .filter(|binding| {
+                binding.is_triggered_by(BindingMode::VI, ModifiersState::empty(), &y)
+            })
+            .map(|binding| &binding.action)
+            .collect();
+        assert_eq!(copy, vec![&Action::Paste]);
+
+        // Bindings of the configuration add up, chords keep them.
+        let c = key(Character("c".into()));
+        let mods = ModifiersState::CONTROL | ModifiersState::SHIFT;
+        let normal: Vec<&Action> = tables
+            .table(KeyTable::Normal)
+            .iter()
+            .filter(|binding| binding.is_triggered_by(BindingMode::empty(), mods, &c))
+            .map(|binding| &binding.action)
+            .collect();
+        assert_eq!(normal, vec![&Action::Paste, &Action::Copy]);
+        assert!(tables.table(KeyTable::Normal).iter().any(|binding| {
+            binding.mode.contains(BindingMode::LEADER)
+                && binding.is_triggered_by(BindingMode::LEADER, mods, &c)
+                && binding.action == Action::TabCreateNew
+        }));
+
+        assert_eq!(
+            tables.action(
+                KeyTable::Hints,
+                BindingMode::empty(),
+                ModifiersState::CONTROL,
+                &key(Character("j".into()))
+            ),
+            Some(&Action::Hint(HintAction::Next))
+        );
+        assert_eq!(
+            tables.action(
+                KeyTable::Hints,
+                BindingMode::empty(),
+                ModifiersState::empty(),
+                &key(Enter)
+            ),
+            Some(&Action::Hint(HintAction::Choose))
+        );
+        assert_eq!(
+            tables.action(
+                KeyTable::Search,
+                BindingMode::empty(),
+                ModifiersState::empty(),
+                &key(Escape)
+            ),
+            Some(&Action::None)
+        );
+    }
 }
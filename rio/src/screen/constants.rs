@@ -12,6 +12,9 @@ pub const PADDING_Y_WITH_SINGLE_NATIVE_TAB: f32 = 17.;
 #[cfg(target_os = "macos")]
 pub const PADDING_Y_WITH_MANY_NATIVE_TAB: f32 = 32.;
 
+/// Height of the progress bar drawn in a tab.
+pub const PROGRESS_HEIGHT: f32 = 2.;
+
 #[cfg(not(any(target_os = "macos")))]
 pub const INACTIVE_TAB_WIDTH_SIZE: f32 = 4.;
 
@@ -1,16 +1,24 @@
-use crate::ansi::CursorShape;
+use crate::ansi::{CursorShape, Progress};
 use crate::crosswords::pos::CursorState;
+use crate::crosswords::snapshot::SharedSnapshot;
+use crate::crosswords::watch::WatchRule;
 use crate::event::sync::FairMutex;
-use crate::event::{EventListener, RioEvent};
+use crate::event::{EventListener, Msg, RioEvent};
+use crate::performer::monitor::{Monitor, MonitorKind};
+use crate::performer::output_log::{self, OutputLog};
+use crate::performer::playback::{self, Playback};
 use crate::performer::Machine;
 use crate::router::assistant::AssistantReport::{FontsNotFound, InitializationError};
 use crate::router::assistant::{AssistantReportLevel, ErrorReport};
 use crate::screen::Crosswords;
 use crate::screen::Messenger;
+use rio_config::colors::term::TermColors;
+use rio_config::profile::Profile;
 use rio_config::Shell;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use sugarloaf::{font::SugarloafFont, SugarloafErrors};
@@ -23,13 +31,70 @@ use teletypewriter::{create_pty_with_fork, create_pty_with_spawn};
 
 const DEFAULT_CONTEXT_CAPACITY: usize = 20;
 
+/// Whether `program`, the name of a foreground process, connects to
+/// another host.
+fn is_ssh_client(program: &str) -> bool {
+    matches!(program, "ssh" | "mosh-client")
+}
+
+/// Send a new log file to the PTY reader of `messenger`, returns false when
+/// it could not be created.
+fn start_output_log(messenger: &Messenger, config: &rio_config::OutputLog) -> bool {
+    let path = output_log::new_path(config.path.as_deref());
+    match OutputLog::create(&path, config.format) {
+        Ok(log) => {
+            log::info!("logging output to {}", log.path.display());
+            messenger.start_output_log(log);
+            true
+        }
+        Err(err) => {
+            log::error!("unable to log output to {}: {err}", path.display());
+            false
+        }
+    }
+}
+
 pub struct Context<T: EventListener> {
     pub terminal: Arc<FairMutex<Crosswords<T>>>,
+    /// Terminal mode published by the PTY reader after each parsed batch.
+    pub mode: Arc<AtomicU32>,
+    /// Visible rows published by the PTY reader, see `Crosswords::snapshot`.
+    pub snapshot: SharedSnapshot,
+    /// Bytes of output parsed by the PTY reader.
+    pub parsed_bytes: Arc<AtomicU64>,
     pub messenger: Messenger,
+    /// Whether the PTY output is being recorded.
+    pub recording: bool,
+    /// Whether the PTY output is copied to a log file.
+    pub output_log: bool,
     #[cfg(not(target_os = "windows"))]
     pub main_fd: Arc<i32>,
     #[cfg(not(target_os = "windows"))]
     pub shell_pid: u32,
+    /// Title of the profile the tab was created with.
+    pub title: Option<String>,
+    /// Name given by the user, it takes the place of the titles set by
+    /// programs and profiles.
+    pub name: Option<String>,
+    /// Activity or silence monitor watching the output.
+    pub monitor: Option<MonitorKind>,
+    /// Shell and extra environment variables the tab was spawned with, empty
+    /// for tabs without a process of their own.
+    pub shell: Shell,
+    pub env: Vec<(String, String)>,
+    /// Colors of the profile theme of the tab, see `ContextManagerConfig`.
+    pub palette: TermColors,
+    /// File read by the program of the tab, removed with it.
+    pub temporary_file: Option<TemporaryFile>,
+}
+
+/// File removed once dropped.
+pub struct TemporaryFile(pub std::path::PathBuf);
+
+impl Drop for TemporaryFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
 }
 
 #[derive(Clone, Default)]
@@ -42,11 +107,33 @@ pub struct ContextManagerConfig {
     pub is_collapsed: bool,
     pub is_native: bool,
     pub should_update_titles: bool,
+    pub word_separators: String,
+    pub smart_selection: bool,
+    pub reveal_concealed: bool,
+    pub trim_trailing_whitespace: bool,
+    pub join_wrapped_lines: bool,
+    pub unicode_version: Option<u8>,
+    pub compress_scrollback: bool,
+    pub watch_rules: Vec<WatchRule>,
+    /// Output log of the new tabs, started when `enabled`.
+    pub output_log: rio_config::OutputLog,
+    /// Extra environment variables of the shell.
+    pub env: Vec<(String, String)>,
+    pub title: Option<String>,
+    pub profiles: Vec<Profile>,
+    /// Colors of the theme of the profile, drawn under the ones set by
+    /// programs.
+    pub palette: TermColors,
 }
 
 pub struct ContextManagerTitles {
     last_title_update: Instant,
-    pub titles: HashMap<usize, [String; 2]>,
+    /// Program, terminal title and working directory of each tab.
+    pub titles: HashMap<usize, [String; 3]>,
+    /// Tabs with an activity or silence monitor that fired.
+    pub alerts: HashSet<usize>,
+    /// Progress reported by the programs of the tabs showing one.
+    pub progress: HashMap<usize, Progress>,
     pub key: String,
 }
 
@@ -60,15 +147,23 @@ impl ContextManagerTitles {
         ContextManagerTitles {
             titles: HashMap::from([(
                 idx,
-                [program.to_owned(), terminal_title.to_owned()],
+                [program.to_owned(), terminal_title.to_owned(), String::new()],
             )]),
+            alerts: HashSet::new(),
+            progress: HashMap::new(),
             key: format!("{}{}{};", idx, program, terminal_title),
             last_title_update,
         }
     }
 
-    pub fn set_key_val(&mut self, idx: usize, program: String, terminal_title: String) {
-        self.titles.insert(idx, [program, terminal_title]);
+    pub fn set_key_val(
+        &mut self,
+        idx: usize,
+        program: String,
+        terminal_title: String,
+        path: String,
+    ) {
+        self.titles.insert(idx, [program, terminal_title, path]);
     }
 
     pub fn set_key(&mut self, key: String) {
@@ -76,6 +171,42 @@ impl ContextManagerTitles {
     }
 }
 
+/// Title set by the program of a tab and whether one of its monitors fired,
+/// looking at the current tab acknowledges it.
+fn take_title<T: EventListener>(
+    context: &Context<T>,
+    is_current: bool,
+) -> (String, bool) {
+    let mut terminal = context.terminal.lock();
+    if is_current {
+        terminal.monitor_alert = None;
+    }
+    (terminal.title.to_string(), terminal.monitor_alert.is_some())
+}
+
+/// Title of a tab: the name given by the user, then the one set by its
+/// program and then the title of its profile.
+fn context_title<T: EventListener>(
+    context: &Context<T>,
+    terminal_title: String,
+) -> String {
+    match (&context.name, &context.title) {
+        (Some(name), _) => name.to_owned(),
+        (None, Some(title)) if terminal_title.is_empty() => title.to_owned(),
+        _ => terminal_title,
+    }
+}
+
+/// Display `path` with the home directory replaced by `~`.
+#[cfg(not(target_os = "windows"))]
+fn shorten_home(path: &std::path::Path) -> String {
+    match dirs::home_dir().and_then(|home| path.strip_prefix(home).ok()) {
+        Some(rest) if rest.as_os_str().is_empty() => String::from("~"),
+        Some(rest) => format!("~/{}", rest.display()),
+        None => path.display().to_string(),
+    }
+}
+
 pub struct ContextManager<T: EventListener> {
     contexts: Vec<Context<T>>,
     current_index: usize,
@@ -90,16 +221,87 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
     #[inline]
     pub fn create_dead_context(event_proxy: T, window_id: WindowId) -> Context<T> {
         let terminal = Crosswords::new(1, 1, CursorShape::Block, event_proxy, window_id);
+        let mode = terminal.shared_mode();
+        let snapshot = terminal.shared_snapshot();
+        let parsed_bytes = terminal.shared_parsed_bytes();
         let terminal: Arc<FairMutex<Crosswords<T>>> = Arc::new(FairMutex::new(terminal));
         let (sender, _receiver) = corcovado::channel::channel();
 
         Context {
+            mode,
+            snapshot,
+            parsed_bytes,
             #[cfg(not(target_os = "windows"))]
             main_fd: Arc::new(-1),
             #[cfg(not(target_os = "windows"))]
             shell_pid: 1,
             messenger: Messenger::new(sender),
+            recording: false,
+            output_log: false,
             terminal,
+            title: None,
+            name: None,
+            monitor: None,
+            shell: Shell::default(),
+            env: vec![],
+            palette: TermColors::default(),
+            temporary_file: None,
+        }
+    }
+
+    /// Context without PTY that plays a recorded session.
+    #[inline]
+    pub fn create_playback_context(
+        playback: Playback,
+        cols_rows: (usize, usize),
+        cursor_state: (&CursorState, bool),
+        event_proxy: T,
+        window_id: WindowId,
+        config: &ContextManagerConfig,
+    ) -> Context<T> {
+        let mut terminal = Crosswords::new(
+            cols_rows.0,
+            cols_rows.1,
+            cursor_state.0.content,
+            event_proxy.clone(),
+            window_id,
+        );
+        terminal.blinking_cursor = cursor_state.1;
+        terminal.set_semantic_escape_chars(&config.word_separators);
+        terminal.smart_selection = config.smart_selection;
+        terminal.reveal_concealed = config.reveal_concealed;
+        terminal.trim_trailing_whitespace = config.trim_trailing_whitespace;
+        terminal.join_wrapped_lines = config.join_wrapped_lines;
+        terminal.unicode_version = config.unicode_version;
+        terminal.set_compress_scrollback(config.compress_scrollback);
+        let mode = terminal.shared_mode();
+        let snapshot = terminal.shared_snapshot();
+        let parsed_bytes = terminal.shared_parsed_bytes();
+        let terminal: Arc<FairMutex<Crosswords<T>>> = Arc::new(FairMutex::new(terminal));
+        playback::spawn(playback, Arc::clone(&terminal), event_proxy, window_id);
+
+        // Input is dropped, there is nothing to write to.
+        let (sender, _receiver) = corcovado::channel::channel();
+
+        Context {
+            mode,
+            snapshot,
+            parsed_bytes,
+            #[cfg(not(target_os = "windows"))]
+            main_fd: Arc::new(-1),
+            #[cfg(not(target_os = "windows"))]
+            shell_pid: 1,
+            messenger: Messenger::new(sender),
+            recording: false,
+            output_log: false,
+            terminal,
+            title: None,
+            name: None,
+            monitor: None,
+            shell: Shell::default(),
+            env: vec![],
+            palette: TermColors::default(),
+            temporary_file: None,
         }
     }
 
@@ -121,6 +323,17 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             window_id,
         );
         terminal.blinking_cursor = cursor_state.1;
+        terminal.set_semantic_escape_chars(&config.word_separators);
+        terminal.smart_selection = config.smart_selection;
+        terminal.reveal_concealed = config.reveal_concealed;
+        terminal.trim_trailing_whitespace = config.trim_trailing_whitespace;
+        terminal.join_wrapped_lines = config.join_wrapped_lines;
+        terminal.unicode_version = config.unicode_version;
+        terminal.set_compress_scrollback(config.compress_scrollback);
+        terminal.watch_rules = config.watch_rules.to_owned();
+        let mode = terminal.shared_mode();
+        let snapshot = terminal.shared_snapshot();
+        let parsed_bytes = terminal.shared_parsed_bytes();
         let terminal: Arc<FairMutex<Crosswords<T>>> = Arc::new(FairMutex::new(terminal));
 
         let pty;
@@ -144,6 +357,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
                 pty = match create_pty_with_spawn(
                     &Cow::Borrowed(&config.shell.program),
                     config.shell.args.clone(),
+                    &config.env,
                     &config.working_dir,
                     cols_rows.0 as u16,
                     cols_rows.1 as u16,
@@ -162,6 +376,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             pty = create_pty(
                 &Cow::Borrowed(&config.shell.program),
                 config.shell.args.clone(),
+                &config.env,
                 &config.working_dir,
                 cols_rows.0 as u16,
                 cols_rows.1 as u16,
@@ -185,6 +400,8 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         let height = dimensions.1 as u16;
         let _ =
             messenger.send_resize(width, height, cols_rows.0 as u16, cols_rows.1 as u16);
+        let output_log =
+            config.output_log.enabled && start_output_log(&messenger, &config.output_log);
 
         Ok(Context {
             #[cfg(not(target_os = "windows"))]
@@ -192,7 +409,19 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             #[cfg(not(target_os = "windows"))]
             shell_pid,
             messenger,
+            recording: false,
+            output_log,
             terminal,
+            mode,
+            snapshot,
+            parsed_bytes,
+            title: config.title.clone(),
+            name: None,
+            monitor: None,
+            shell: config.shell.clone(),
+            env: config.env.clone(),
+            palette: config.palette,
+            temporary_file: None,
         })
     }
 
@@ -206,27 +435,38 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         ctx_config: ContextManagerConfig,
         sugarloaf_errors: Option<SugarloafErrors>,
     ) -> Result<Self, Box<dyn Error>> {
-        let initial_context = match ContextManager::create_context(
-            (dimensions.0, dimensions.1),
-            (col_rows.0, col_rows.1),
-            cursor_state,
-            event_proxy.clone(),
-            window_id,
-            &ctx_config,
-        ) {
-            Ok(context) => context,
-            Err(err_message) => {
-                log::error!("{:?}", err_message);
-
-                event_proxy.send_event(
-                    RioEvent::ReportToAssistant(ErrorReport {
-                        report: InitializationError(err_message.to_string()),
-                        level: AssistantReportLevel::Error,
-                    }),
-                    window_id,
-                );
+        let initial_context = if let Some(playback) = playback::take_pending() {
+            ContextManager::create_playback_context(
+                playback,
+                (col_rows.0, col_rows.1),
+                cursor_state,
+                event_proxy.clone(),
+                window_id,
+                &ctx_config,
+            )
+        } else {
+            match ContextManager::create_context(
+                (dimensions.0, dimensions.1),
+                (col_rows.0, col_rows.1),
+                cursor_state,
+                event_proxy.clone(),
+                window_id,
+                &ctx_config,
+            ) {
+                Ok(context) => context,
+                Err(err_message) => {
+                    log::error!("{:?}", err_message);
+
+                    event_proxy.send_event(
+                        RioEvent::ReportToAssistant(ErrorReport {
+                            report: InitializationError(err_message.to_string()),
+                            level: AssistantReportLevel::Error,
+                        }),
+                        window_id,
+                    );
 
-                ContextManager::create_dead_context(event_proxy.clone(), window_id)
+                    ContextManager::create_dead_context(event_proxy.clone(), window_id)
+                }
             }
         };
 
@@ -276,6 +516,19 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             is_native: false,
             should_update_titles: false,
             use_current_path: false,
+            word_separators: rio_config::defaults::default_word_separators(),
+            smart_selection: false,
+            reveal_concealed: false,
+            trim_trailing_whitespace: false,
+            join_wrapped_lines: true,
+            unicode_version: None,
+            compress_scrollback: false,
+            watch_rules: vec![],
+            output_log: rio_config::OutputLog::default(),
+            env: vec![],
+            title: None,
+            profiles: vec![],
+            palette: TermColors::default(),
         };
         let initial_context = ContextManager::create_context(
             (100, 100),
@@ -320,6 +573,48 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         }
     }
 
+    #[inline]
+    pub fn report_remote_session(&self, remote: bool) {
+        self.event_proxy
+            .send_event(RioEvent::RemoteSession(remote), self.window_id);
+    }
+
+    #[inline]
+    pub fn request_paste_confirmation(&self) {
+        self.event_proxy
+            .send_event(RioEvent::ConfirmPaste, self.window_id);
+    }
+
+    #[inline]
+    pub fn open_pager(&self) {
+        self.event_proxy
+            .send_event(RioEvent::OpenPager, self.window_id);
+    }
+
+    #[inline]
+    pub fn open_rename_tab(&self) {
+        self.event_proxy
+            .send_event(RioEvent::OpenRenameTab, self.window_id);
+    }
+
+    #[inline]
+    pub fn open_context_menu(&self) {
+        self.event_proxy
+            .send_event(RioEvent::OpenContextMenu, self.window_id);
+    }
+
+    #[inline]
+    pub fn open_glyph_atlas(&self) {
+        self.event_proxy
+            .send_event(RioEvent::OpenGlyphAtlas, self.window_id);
+    }
+
+    #[inline]
+    pub fn open_quick_select(&self) {
+        self.event_proxy
+            .send_event(RioEvent::OpenQuickSelect, self.window_id);
+    }
+
     #[inline]
     pub fn create_new_window(&self) {
         self.event_proxy
@@ -412,24 +707,19 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             if self.titles.last_title_update.elapsed() > interval_time {
                 self.titles.last_title_update = Instant::now();
                 let mut id = String::from("");
+                self.titles.alerts.clear();
+                self.update_progress();
                 for (i, context) in self.contexts.iter_mut().enumerate() {
                     let program = teletypewriter::foreground_process_name(
                         *context.main_fd,
                         context.shell_pid,
                     );
 
-                    #[cfg(not(target_os = "macos"))]
-                    let terminal_title = String::from("");
-
-                    #[cfg(target_os = "macos")]
-                    #[allow(unused)]
-                    let mut terminal_title = String::from("");
-
-                    #[cfg(target_os = "macos")]
-                    {
-                        let terminal = context.terminal.lock();
-                        terminal_title = terminal.title.to_string();
-                        drop(terminal);
+                    let (terminal_title, alerted) =
+                        take_title(context, i == self.current_index);
+                    let terminal_title = context_title(context, terminal_title);
+                    if alerted {
+                        self.titles.alerts.insert(i);
                     }
 
                     if self.config.is_native {
@@ -443,9 +733,19 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
                             .send_event(RioEvent::Title(window_title), self.window_id);
                     }
 
-                    id =
-                        id.to_owned() + &(format!("{}{}{};", i, program, terminal_title));
-                    self.titles.set_key_val(i, program, terminal_title);
+                    let path = teletypewriter::foreground_process_path(
+                        *context.main_fd,
+                        context.shell_pid,
+                    )
+                    .map(|path| shorten_home(&path))
+                    .unwrap_or_default();
+
+                    id = id.to_owned()
+                        + &(format!(
+                            "{}{}{}{}{};",
+                            i, program, terminal_title, path, alerted
+                        ));
+                    self.titles.set_key_val(i, program, terminal_title, path);
                 }
                 self.titles.set_key(id);
             }
@@ -456,19 +756,111 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             if self.titles.last_title_update.elapsed() > Duration::from_secs(5) {
                 self.titles.last_title_update = Instant::now();
                 let mut id = String::from("");
+                self.titles.alerts.clear();
+                self.update_progress();
                 for (i, context) in self.contexts.iter_mut().enumerate() {
                     let program = self.config.shell.program.to_owned();
-                    let terminal_title = String::from("");
+                    let (terminal_title, alerted) =
+                        take_title(context, i == self.current_index);
+                    let terminal_title = context_title(context, terminal_title);
+                    if alerted {
+                        self.titles.alerts.insert(i);
+                    }
 
-                    id =
-                        id.to_owned() + &(format!("{}{}{};", i, program, terminal_title));
-                    self.titles.set_key_val(i, program, terminal_title);
+                    id = id.to_owned()
+                        + &(format!("{}{}{}{};", i, program, terminal_title, alerted));
+                    self.titles
+                        .set_key_val(i, program, terminal_title, String::new());
                 }
                 self.titles.set_key(id);
             }
         }
     }
 
+    /// Read the progress reported in every tab.
+    pub fn update_progress(&mut self) {
+        self.titles.progress = self
+            .contexts
+            .iter()
+            .enumerate()
+            .filter_map(|(index, context)| {
+                let progress = context.terminal.lock().progress;
+                progress.is_visible().then_some((index, progress))
+            })
+            .collect();
+    }
+
+    /// Progress shown for the window: the one of the current tab, otherwise
+    /// the first tab reporting one.
+    pub fn window_progress(&self) -> Progress {
+        let progress = &self.titles.progress;
+        progress
+            .get(&self.current_index)
+            .or_else(|| (0..self.contexts.len()).find_map(|index| progress.get(&index)))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Update the titles right away instead of at the next interval.
+    pub fn refresh_titles(&mut self) {
+        self.titles.last_title_update = Instant::now()
+            .checked_sub(Duration::from_secs(60))
+            .unwrap_or_else(Instant::now);
+        self.update_titles();
+    }
+
+    /// Whether the program of the current tab is an SSH client, as of the
+    /// last update of the titles.
+    pub fn is_remote(&self) -> bool {
+        self.titles
+            .titles
+            .get(&self.current_index)
+            .map_or(false, |[program, ..]| is_ssh_client(program))
+    }
+
+    /// Bytes of output parsed by all the tabs.
+    pub fn parsed_bytes(&self) -> u64 {
+        self.contexts
+            .iter()
+            .map(|context| context.parsed_bytes.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Watch the output of the current tab with `monitor`, or stop watching
+    /// it when None.
+    pub fn set_monitor(&mut self, monitor: Option<Monitor>) {
+        let context = self.current_mut();
+        context.monitor = monitor.map(|monitor| monitor.kind);
+        context.messenger.set_monitor(monitor);
+        context.terminal.lock().monitor_alert = None;
+    }
+
+    /// Start logging the output of the current tab, or stop when it is
+    /// being logged.
+    pub fn toggle_output_log(&mut self) {
+        let config = self.config.output_log.to_owned();
+        let context = self.current_mut();
+        if context.output_log {
+            context.messenger.stop_output_log();
+            context.output_log = false;
+        } else {
+            context.output_log = start_output_log(&context.messenger, &config);
+        }
+    }
+
+    /// Name the tab `index`, None goes back to the titles set by programs.
+    /// Returns false when there is no such tab.
+    pub fn rename_context(&mut self, index: usize, name: Option<String>) -> bool {
+        match self.contexts.get_mut(index) {
+            Some(context) => {
+                context.name = name;
+                self.refresh_titles();
+                true
+            }
+            None => false,
+        }
+    }
+
     #[inline]
     pub fn contexts(&self) -> &Vec<Context<T>> {
         &self.contexts
@@ -513,6 +905,13 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
 
         let index_to_remove = self.current_index;
 
+        // tmux closes the tab once the pane is gone.
+        let messenger = &self.contexts[index_to_remove].messenger;
+        if let Some(pane) = messenger.tmux_pane() {
+            messenger.send_tmux_command(&format!("kill-pane -t %{pane}"));
+            return;
+        }
+
         #[cfg(not(target_os = "windows"))]
         {
             // The reason why we don't use close context here is because it is unix is handled by
@@ -634,6 +1033,278 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             }
         }
     }
+
+    /// Create a context with the shell, working directory, environment and
+    /// title of the profile `name`.
+    #[inline]
+    pub fn add_context_with_profile(
+        &mut self,
+        name: &str,
+        dimensions: (u32, u32),
+        col_rows: (usize, usize),
+        cursor_state: (&CursorState, bool),
+    ) {
+        let profile = match self
+            .config
+            .profiles
+            .iter()
+            .find(|profile| profile.name.eq_ignore_ascii_case(name))
+        {
+            Some(profile) => profile.clone(),
+            None => {
+                log::warn!("profile {name} not found");
+                return;
+            }
+        };
+
+        // Native tabs are windows created from the config.
+        if self.config.is_native {
+            log::warn!("profiles are not supported with native tabs");
+            self.create_new_native_tab();
+            return;
+        }
+
+        if self.contexts.len() >= self.capacity {
+            return;
+        }
+
+        let mut config = self.config.clone();
+        if let Some(shell) = profile.shell.to_owned() {
+            config.shell = shell;
+            config.use_fork = false;
+        }
+        if profile.working_dir.is_some() {
+            config.working_dir = profile.working_dir.to_owned();
+        }
+        config.env.extend(profile.env());
+        if profile.title.is_some() {
+            config.title = profile.title;
+        }
+        config.output_log.enabled |= profile.output_log;
+        if let Some(theme) = &profile.theme {
+            match rio_config::Config::theme_colors(theme) {
+                Ok(colors) => config.palette = TermColors::from_theme(&colors),
+                Err(err) => log::warn!("unable to load theme {theme}: {err}"),
+            }
+        }
+        // The environment is only passed to spawned processes.
+        if !config.env.is_empty() {
+            config.use_fork = false;
+        }
+
+        match ContextManager::create_context(
+            dimensions,
+            col_rows,
+            cursor_state,
+            self.event_proxy.clone(),
+            self.window_id,
+            &config,
+        ) {
+            Ok(new_context) => {
+                self.contexts.push(new_context);
+                self.current_index = self.contexts.len() - 1;
+            }
+            Err(..) => {
+                log::error!("not able to create a new context");
+            }
+        }
+    }
+
+    /// Create a context like the current one: same shell, environment
+    /// variables and profile title, started in its working directory.
+    #[inline]
+    pub fn duplicate_current_context(
+        &mut self,
+        dimensions: (u32, u32),
+        col_rows: (usize, usize),
+        cursor_state: (&CursorState, bool),
+    ) {
+        if self.config.is_native {
+            log::warn!("duplicating tabs is not supported with native tabs");
+            self.create_new_native_tab();
+            return;
+        }
+
+        if self.contexts.len() >= self.capacity {
+            return;
+        }
+
+        let current = self.current();
+        let mut config = self.config.clone();
+        // Playback and tmux tabs have no shell, the configured one is used.
+        if !current.shell.program.is_empty() {
+            config.shell = current.shell.clone();
+            config.env = current.env.clone();
+            config.title = current.title.clone();
+        }
+        config.palette = current.palette;
+
+        #[cfg(not(target_os = "windows"))]
+        if let Ok(path) =
+            teletypewriter::foreground_process_path(*current.main_fd, current.shell_pid)
+        {
+            config.working_dir = Some(path.to_string_lossy().to_string());
+        }
+
+        // Forked processes would not receive the environment nor start in
+        // the working directory.
+        config.use_fork = false;
+
+        match ContextManager::create_context(
+            dimensions,
+            col_rows,
+            cursor_state,
+            self.event_proxy.clone(),
+            self.window_id,
+            &config,
+        ) {
+            Ok(new_context) => {
+                self.contexts.push(new_context);
+                self.current_index = self.contexts.len() - 1;
+            }
+            Err(..) => {
+                log::error!("not able to create a new context");
+            }
+        }
+    }
+
+    /// Create a context running `shell` instead of the configured shell,
+    /// returns it unless it could not be created.
+    #[inline]
+    pub fn add_context_with_shell(
+        &mut self,
+        shell: Shell,
+        dimensions: (u32, u32),
+        col_rows: (usize, usize),
+        cursor_state: (&CursorState, bool),
+    ) -> Option<&mut Context<T>> {
+        if self.contexts.len() >= self.capacity {
+            return None;
+        }
+
+        let mut config = self.config.clone();
+        config.shell = shell;
+        // Forked processes would not receive the program arguments.
+        config.use_fork = false;
+
+        match ContextManager::create_context(
+            dimensions,
+            col_rows,
+            cursor_state,
+            self.event_proxy.clone(),
+            self.window_id,
+            &config,
+        ) {
+            Ok(new_context) => {
+                self.contexts.push(new_context);
+                self.current_index = self.contexts.len() - 1;
+                self.contexts.last_mut()
+            }
+            Err(..) => {
+                log::error!("not able to create a new context");
+                None
+            }
+        }
+    }
+
+    /// Tab showing the pane `pane` of a tmux control mode session, its
+    /// output is parsed by the event loop and its input written to
+    /// `gateway` as tmux commands.
+    pub fn add_tmux_context(
+        &mut self,
+        pane: u32,
+        gateway: corcovado::channel::Sender<Msg>,
+        cols_rows: (usize, usize),
+        cursor_state: (&CursorState, bool),
+    ) {
+        if self.contexts.len() >= self.capacity || self.tmux_context(pane).is_some() {
+            return;
+        }
+
+        let mut terminal = Crosswords::new(
+            cols_rows.0,
+            cols_rows.1,
+            cursor_state.0.content,
+            self.event_proxy.clone(),
+            self.window_id,
+        );
+        terminal.blinking_cursor = cursor_state.1;
+        terminal.set_semantic_escape_chars(&self.config.word_separators);
+        terminal.smart_selection = self.config.smart_selection;
+        terminal.reveal_concealed = self.config.reveal_concealed;
+        terminal.trim_trailing_whitespace = self.config.trim_trailing_whitespace;
+        terminal.join_wrapped_lines = self.config.join_wrapped_lines;
+        terminal.unicode_version = self.config.unicode_version;
+        terminal.set_compress_scrollback(self.config.compress_scrollback);
+        let mode = terminal.shared_mode();
+        let snapshot = terminal.shared_snapshot();
+        let parsed_bytes = terminal.shared_parsed_bytes();
+
+        self.contexts.push(Context {
+            mode,
+            snapshot,
+            parsed_bytes,
+            #[cfg(not(target_os = "windows"))]
+            main_fd: Arc::new(-1),
+            // There is no process to kill or to read the title from.
+            #[cfg(not(target_os = "windows"))]
+            shell_pid: 0,
+            messenger: Messenger::for_tmux_pane(gateway, pane),
+            recording: false,
+            output_log: false,
+            terminal: Arc::new(FairMutex::new(terminal)),
+            title: None,
+            name: None,
+            monitor: None,
+            shell: Shell::default(),
+            env: vec![],
+            palette: TermColors::default(),
+            temporary_file: None,
+        });
+    }
+
+    #[inline]
+    pub fn tmux_context(&self, pane: u32) -> Option<&Context<T>> {
+        self.contexts
+            .iter()
+            .find(|context| context.messenger.tmux_pane() == Some(pane))
+    }
+
+    #[inline]
+    pub fn tmux_context_mut(&mut self, pane: u32) -> Option<&mut Context<T>> {
+        self.contexts
+            .iter_mut()
+            .find(|context| context.messenger.tmux_pane() == Some(pane))
+    }
+
+    #[inline]
+    pub fn select_tmux_context(&mut self, pane: u32) {
+        if let Some(index) = self
+            .contexts
+            .iter()
+            .position(|context| context.messenger.tmux_pane() == Some(pane))
+        {
+            self.current_index = index;
+        }
+    }
+
+    /// Remove the tab of a tmux pane, the tab running tmux always remains.
+    pub fn close_tmux_context(&mut self, pane: u32) {
+        let index = match self
+            .contexts
+            .iter()
+            .position(|context| context.messenger.tmux_pane() == Some(pane))
+        {
+            Some(index) => index,
+            None => return,
+        };
+
+        self.titles.titles.remove(&index);
+        self.contexts.remove(index);
+        if self.current_index > index || self.current_index >= self.contexts.len() {
+            self.current_index = self.current_index.saturating_sub(1);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -641,6 +1312,38 @@ pub mod test {
     use super::*;
     use crate::event::VoidListener;
 
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_shorten_home() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(shorten_home(&home), "~");
+        assert_eq!(shorten_home(&home.join("rio")), "~/rio");
+        assert_eq!(shorten_home(std::path::Path::new("/")), "/");
+    }
+
+    #[test]
+    fn test_is_remote() {
+        let mut context_manager =
+            ContextManager::start_with_capacity(5, VoidListener {}, WindowId::from(0))
+                .unwrap();
+        assert!(!context_manager.is_remote());
+        context_manager.titles.set_key_val(
+            0,
+            String::from("ssh"),
+            String::new(),
+            String::new(),
+        );
+        assert!(context_manager.is_remote());
+        context_manager.titles.set_key_val(
+            0,
+            String::from("sshd"),
+            String::new(),
+            String::new(),
+        );
+        assert!(!context_manager.is_remote());
+        assert!(is_ssh_client("mosh-client"));
+    }
+
     #[test]
     fn test_capacity() {
         let context_manager =
@@ -929,4 +1632,105 @@ pub mod test {
         context_manager.switch_to_next();
         assert_eq!(context_manager.current_index, 1);
     }
+
+    #[test]
+    fn test_tmux_contexts() {
+        let mut context_manager =
+            ContextManager::start_with_capacity(5, VoidListener {}, WindowId::from(0))
+                .unwrap();
+        let gateway = context_manager.current().messenger.channel();
+
+        for pane in [2, 5] {
+            context_manager.add_tmux_context(
+                pane,
+                gateway.clone(),
+                (40, 24),
+                (&CursorState::new('_'), false),
+            );
+        }
+        // A pane is only shown once.
+        context_manager.add_tmux_context(
+            5,
+            gateway.clone(),
+            (40, 24),
+            (&CursorState::new('_'), false),
+        );
+        assert_eq!(context_manager.len(), 3);
+
+        context_manager.select_tmux_context(5);
+        assert_eq!(context_manager.current_index, 2);
+        assert_eq!(context_manager.current().messenger.tmux_pane(), Some(5));
+
+        context_manager.close_tmux_context(2);
+        assert_eq!(context_manager.len(), 2);
+        assert_eq!(context_manager.current_index, 1);
+        assert!(context_manager.tmux_context(2).is_none());
+
+        context_manager.close_tmux_context(5);
+        assert_eq!(context_manager.current_index, 0);
+        assert_eq!(context_manager.current().messenger.tmux_pane(), None);
+    }
+
+    #[test]
+    fn test_duplicate_current_context() {
+        let mut context_manager =
+            ContextManager::start_with_capacity(3, VoidListener {}, WindowId::from(0))
+                .unwrap();
+        context_manager.contexts[0].env =
+            vec![(String::from("RIO_DUPLICATE"), String::from("1"))];
+        context_manager.contexts[0].title = Some(String::from("work"));
+
+        context_manager.duplicate_current_context(
+            (100, 100),
+            (1, 1),
+            (&CursorState::new('_'), false),
+        );
+        assert_eq!(context_manager.len(), 2);
+        assert_eq!(context_manager.current_index, 1);
+
+        let duplicated = context_manager.current();
+        assert_eq!(
+            duplicated.shell.program,
+            context_manager.contexts[0].shell.program
+        );
+        assert_eq!(duplicated.env, context_manager.contexts[0].env);
+        assert_eq!(duplicated.title, Some(String::from("work")));
+
+        context_manager.duplicate_current_context(
+            (100, 100),
+            (1, 1),
+            (&CursorState::new('_'), false),
+        );
+        context_manager.duplicate_current_context(
+            (100, 100),
+            (1, 1),
+            (&CursorState::new('_'), false),
+        );
+        assert_eq!(context_manager.len(), 3);
+    }
+
+    #[test]
+    fn test_rename_context() {
+        let mut context_manager =
+            ContextManager::start_with_capacity(3, VoidListener {}, WindowId::from(0))
+                .unwrap();
+        context_manager.contexts[0].title = Some(String::from("work"));
+        assert_eq!(
+            context_title(context_manager.current(), String::new()),
+            "work"
+        );
+        assert_eq!(
+            context_title(context_manager.current(), String::from("vim")),
+            "vim"
+        );
+
+        assert!(context_manager.rename_context(0, Some(String::from("api"))));
+        assert_eq!(
+            context_title(context_manager.current(), String::from("vim")),
+            "api"
+        );
+        assert!(context_manager.rename_context(0, None));
+        assert_eq!(context_manager.current().name, None);
+        assert!(!context_manager.rename_context(1, None));
+    }
 }
@@ -0,0 +1,177 @@
+// Keyboard layouts and composition.
+//
+// Dead keys and Compose sequences are resolved by winit through the xkb
+// compose tables on Linux and by the input method on macOS. Until a sequence
+// completes its keys come without text, they must not reach the shell as key
+// codes, only the composed text does. Bindings are matched against the key of
+// the current layout, then against the key at the same place on a US layout so
+// `control+shift+c` keeps working while a Cyrillic or Greek layout is active.
+
+use winit::keyboard::{Key, KeyCode};
+
+/// Tracks the dead key or Compose sequence in progress and the keys it took,
+/// so their releases are left out as well.
+#[derive(Debug, Default)]
+pub struct Composition {
+    composing: bool,
+    consumed: Vec<KeyCode>,
+}
+
+impl Composition {
+    /// Update the state with a key press, returns `true` when the key is part
+    /// of a sequence and should be left out.
+    pub fn press(
+        &mut self,
+        code: KeyCode,
+        logical_key: &Key,
+        text: Option<&str>,
+    ) -> bool {
+        let composing = match logical_key {
+            Key::Dead(_) | Key::Compose => true,
+            Key::Character(_) => self.composing && text.is_none(),
+            _ => false,
+        };
+        // Escape and such cancel the sequence without text.
+        let consumed = composing || (self.composing && text.is_none());
+        self.composing = composing;
+        if consumed && !self.consumed.contains(&code) {
+            self.consumed.push(code);
+        }
+        consumed
+    }
+
+    /// Returns `true` when the released key was taken by a sequence.
+    pub fn release(&mut self, code: KeyCode) -> bool {
+        let position = self.consumed.iter().position(|consumed| *consumed == code);
+        position
+            .map(|index| self.consumed.swap_remove(index))
+            .is_some()
+    }
+}
+
+/// Character of `code` on a US layout, for the keys whose character depends
+/// on the layout.
+pub fn latin_key(code: KeyCode) -> Option<&'static str> {
+    let key = match code {
+        KeyCode::KeyA => "a",
+        KeyCode::KeyB => "b",
+        KeyCode::KeyC => "c",
+        KeyCode::KeyD => "d",
+        KeyCode::KeyE => "e",
+        KeyCode::KeyF => "f",
+        KeyCode::KeyG => "g",
+        KeyCode::KeyH => "h",
+        KeyCode::KeyI => "i",
+        KeyCode::KeyJ => "j",
+        KeyCode::KeyK => "k",
+        KeyCode::KeyL => "l",
+        KeyCode::KeyM => "m",
+        KeyCode::KeyN => "n",
+        KeyCode::KeyO => "o",
+        KeyCode::KeyP => "p",
+        KeyCode::KeyQ => "q",
+        KeyCode::KeyR => "r",
+        KeyCode::KeyS => "s",
+        KeyCode::KeyT => "t",
+        KeyCode::KeyU => "u",
+        KeyCode::KeyV => "v",
+        KeyCode::KeyW => "w",
+        KeyCode::KeyX => "x",
+        KeyCode::KeyY => "y",
+        KeyCode::KeyZ => "z",
+        KeyCode::Digit0 => "0",
+        KeyCode::Digit1 => "1",
+        KeyCode::Digit2 => "2",
+        KeyCode::Digit3 => "3",
+        KeyCode::Digit4 => "4",
+        KeyCode::Digit5 => "5",
+        KeyCode::Digit6 => "6",
+        KeyCode::Digit7 => "7",
+        KeyCode::Digit8 => "8",
+        KeyCode::Digit9 => "9",
+        KeyCode::Backquote => "`",
+        KeyCode::Minus => "-",
+        KeyCode::Equal => "=",
+        KeyCode::BracketLeft => "[",
+        KeyCode::BracketRight => "]",
+        KeyCode::Backslash => "\\",
+        KeyCode::Semicolon => ";",
+        KeyCode::Quote => "'",
+        KeyCode::Comma => ",",
+        KeyCode::Period => ".",
+        KeyCode::Slash => "/",
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Key a binding is matched against when `key` of the current layout isn't
+/// the US one, `None` for the keys every layout shares.
+pub fn binding_fallback(key: &Key, code: KeyCode) -> Option<Key> {
+    match key {
+        Key::Character(c) if !c.is_ascii() => {
+            latin_key(code).map(|latin| Key::Character(latin.into()))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn character(c: &str) -> Key {
+        Key::Character(c.into())
+    }
+
+    #[test]
+    fn test_dead_key() {
+        let mut composition = Composition::default();
+        assert!(composition.press(KeyCode::BracketLeft, &Key::Dead(Some('´')), None));
+        assert!(composition.composing);
+        // The composed character comes with the second key.
+        assert!(!composition.press(KeyCode::KeyE, &character("e"), Some("é")));
+        assert!(!composition.composing);
+        assert!(!composition.press(KeyCode::KeyE, &character("e"), Some("e")));
+    }
+
+    #[test]
+    fn test_compose_sequence() {
+        let mut composition = Composition::default();
+        assert!(composition.press(KeyCode::AltRight, &Key::Compose, None));
+        assert!(composition.press(KeyCode::KeyO, &character("o"), None));
+        assert!(composition.composing);
+        assert!(!composition.press(KeyCode::KeyC, &character("c"), Some("©")));
+        assert!(!composition.composing);
+        assert!(composition.release(KeyCode::KeyO));
+        assert!(!composition.release(KeyCode::KeyO));
+        assert!(!composition.release(KeyCode::KeyC));
+    }
+
+    #[test]
+    fn test_cancelled_sequence() {
+        let mut composition = Composition::default();
+        assert!(composition.press(KeyCode::Backquote, &Key::Dead(Some('`')), None));
+        // Escape ends it without text, it is swallowed by the sequence.
+        assert!(composition.press(KeyCode::Escape, &Key::Escape, None));
+        assert!(!composition.composing);
+        assert!(composition.release(KeyCode::Escape));
+        assert!(!composition.press(KeyCode::Escape, &Key::Escape, None));
+        assert!(!composition.release(KeyCode::Escape));
+    }
+
+    #[test]
+    fn test_binding_fallback() {
+        assert_eq!(
+            binding_fallback(&character("с"), KeyCode::KeyC),
+            Some(character("c"))
+        );
+        assert_eq!(
+            binding_fallback(&character("ς"), KeyCode::KeyW),
+            Some(character("w"))
+        );
+        assert_eq!(binding_fallback(&character("c"), KeyCode::KeyC), None);
+        assert_eq!(binding_fallback(&Key::Enter, KeyCode::Enter), None);
+        assert_eq!(binding_fallback(&character("é"), KeyCode::F1), None);
+    }
+}
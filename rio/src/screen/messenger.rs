@@ -1,14 +1,44 @@
 use crate::event::Msg;
+use crate::performer::monitor::Monitor;
+use crate::performer::output_log::OutputLog;
+use crate::performer::recorder::Recorder;
+use crate::tmux;
 use std::borrow::Cow;
 use teletypewriter::WinsizeBuilder;
 
 pub struct Messenger {
     channel: corcovado::channel::Sender<Msg>,
+    /// Pane of a tmux control mode session, the input is written as tmux
+    /// commands to the tab running tmux.
+    tmux_pane: Option<u32>,
 }
 
 impl Messenger {
     pub fn new(channel: corcovado::channel::Sender<Msg>) -> Messenger {
-        Messenger { channel }
+        Messenger {
+            channel,
+            tmux_pane: None,
+        }
+    }
+
+    pub fn for_tmux_pane(
+        gateway: corcovado::channel::Sender<Msg>,
+        pane: u32,
+    ) -> Messenger {
+        Messenger {
+            channel: gateway,
+            tmux_pane: Some(pane),
+        }
+    }
+
+    #[inline]
+    pub fn channel(&self) -> corcovado::channel::Sender<Msg> {
+        self.channel.clone()
+    }
+
+    #[inline]
+    pub fn tmux_pane(&self) -> Option<u32> {
+        self.tmux_pane
     }
 
     #[inline]
@@ -24,6 +54,10 @@ impl Messenger {
             return;
         }
 
+        let bytes = match self.tmux_pane {
+            Some(pane) => Cow::Owned(tmux::send_keys(pane, &bytes).into_bytes()),
+            None => bytes,
+        };
         let _ = self.channel.send(Msg::Input(bytes));
     }
 
@@ -35,6 +69,11 @@ impl Messenger {
         cols: u16,
         rows: u16,
     ) -> Result<&str, String> {
+        // The size of tmux panes is decided by tmux.
+        if self.tmux_pane.is_some() {
+            return Ok("Resized");
+        }
+
         let new_size = WinsizeBuilder {
             rows,
             cols,
@@ -47,4 +86,52 @@ impl Messenger {
             Err(..) => Err("Error sending message".to_string()),
         }
     }
+
+    #[inline]
+    pub fn start_recording(&self, recorder: Recorder) {
+        if self.tmux_pane.is_some() {
+            return;
+        }
+        let _ = self.channel.send(Msg::StartRecording(recorder));
+    }
+
+    #[inline]
+    pub fn stop_recording(&self) {
+        if self.tmux_pane.is_some() {
+            return;
+        }
+        let _ = self.channel.send(Msg::StopRecording);
+    }
+
+    #[inline]
+    pub fn start_output_log(&self, log: OutputLog) {
+        if self.tmux_pane.is_some() {
+            return;
+        }
+        let _ = self.channel.send(Msg::StartOutputLog(Box::new(log)));
+    }
+
+    #[inline]
+    pub fn stop_output_log(&self) {
+        if self.tmux_pane.is_some() {
+            return;
+        }
+        let _ = self.channel.send(Msg::StopOutputLog);
+    }
+
+    #[inline]
+    pub fn set_monitor(&self, monitor: Option<Monitor>) {
+        if self.tmux_pane.is_some() {
+            return;
+        }
+        let _ = self.channel.send(Msg::Monitor(monitor));
+    }
+
+    /// Write a tmux command to the tab running tmux.
+    #[inline]
+    pub fn send_tmux_command(&self, command: &str) {
+        let _ = self
+            .channel
+            .send(Msg::Input(Cow::Owned(format!("{command}\n").into_bytes())));
+    }
 }
@@ -6,13 +6,16 @@
 // were retired from https://github.com/alacritty/alacritty/blob/c39c3c97f1a1213418c3629cc59a1d46e34070e0/alacritty/src/input.rs
 // which is licensed under Apache 2.0 license.
 
-mod bindings;
-mod constants;
+pub mod bindings;
+pub mod constants;
 mod context;
-mod messenger;
+mod keyboard;
+pub mod messenger;
 mod mouse;
 mod navigation;
-mod state;
+mod screenshot;
+mod smooth_scroll;
+pub mod state;
 pub mod window;
 
 use crate::crosswords::vi_mode::ViMotion;
@@ -26,32 +29,47 @@ use winit::event::Modifiers;
 use winit::event::MouseButton;
 use winit::window::raw_window_handle::HasRawDisplayHandle;
 // use winit::window::raw_window_handle::HasRawWindowHandle;
+use crate::accessibility::Accessibility;
+use crate::ansi::Progress;
 use crate::clipboard::{Clipboard, ClipboardType};
 use crate::crosswords::grid::Dimensions;
 use crate::crosswords::pos::Column;
 use crate::crosswords::{
     grid::Scroll,
-    pos::{Pos, Side},
-    Crosswords, Mode, MIN_COLUMNS, MIN_LINES,
+    pos::{Line, Pos, Side},
+    watch, Crosswords, Mode, MIN_COLUMNS, MIN_LINES,
 };
 use crate::event::{ClickState, EventProxy};
 use crate::ime::Ime;
+use crate::performer::handler::Handler;
+use crate::performer::monitor::{Monitor, MonitorKind};
+use crate::performer::recorder::{self, Recorder};
+use crate::platform::dpi;
+use crate::platform::power::is_low_power;
 use crate::router;
 #[cfg(target_os = "macos")]
 use crate::screen::constants::{DEADZONE_END_Y, DEADZONE_START_X, DEADZONE_START_Y};
 use crate::screen::{
-    bindings::{Action as Act, BindingKey, BindingMode, FontSizeAction},
+    bindings::{
+        Action as Act, BindingKey, BindingMode, FontSizeAction, HintAction, KeyTable,
+        MouseAction, MouseTrigger, SearchAction,
+    },
     context::ContextManager,
     mouse::{calculate_mouse_position, Mouse},
 };
 use crate::selection::{Selection, SelectionType};
+use crate::startup;
+use crate::tmux;
 use messenger::Messenger;
-use rio_config::colors::{term::List, ColorWGPU};
+use rio_config::colors::{term::TermColors, ColorRgb, ColorWGPU};
 use state::State;
 use std::cmp::max;
 use std::cmp::min;
+use std::collections::HashMap;
 use std::error::Error;
 use std::rc::Rc;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
 use sugarloaf::{layout::SugarloafLayout, Sugarloaf, SugarloafErrors};
 use winit::event::ElementState;
 #[cfg(target_os = "macos")]
@@ -59,12 +77,40 @@ use winit::keyboard::ModifiersKeyState;
 use winit::keyboard::{Key, KeyLocation, ModifiersState};
 use winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
 
+/// Longest time rendering is skipped while the PTY reader holds the terminal.
+const MAX_RENDER_SKIP: Duration = Duration::from_millis(50);
+
+/// Milliseconds between frames while glyphs or lines are still being built.
+const PENDING_FRAME_INTERVAL: u64 = 16;
+
+/// Milliseconds between the frames of a smooth scroll.
+const SMOOTH_SCROLL_INTERVAL: u64 = 8;
+
+/// Longest time waited for the echo of an input, the input latency is not
+/// measured for input without one.
+const INPUT_ECHO_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Cursor blinking interval in milliseconds.
+const BLINK_INTERVAL: u64 = 800;
+
+/// Cursor blinking interval in milliseconds with the low power profile.
+const LOW_POWER_BLINK_INTERVAL: u64 = 1600;
+
+/// Pager used to page command output when `$PAGER` is not set.
+#[cfg(not(target_os = "windows"))]
+const DEFAULT_PAGER: &str = "less";
+#[cfg(target_os = "windows")]
+const DEFAULT_PAGER: &str = "more";
+
 /// Minimum number of pixels at the bottom/top where selection scrolling is performed.
 const MIN_SELECTION_SCROLLING_HEIGHT: f32 = 5.;
 
 /// Number of pixels for increasing the selection scrolling speed factor by one.
 const SELECTION_SCROLLING_STEP: f32 = 10.;
 
+/// Lines of output searched for quick select tokens.
+const QUICK_SELECT_LINES: i32 = 1000;
+
 impl Dimensions for SugarloafLayout {
     #[inline]
     fn columns(&self) -> usize {
@@ -83,12 +129,66 @@ impl Dimensions for SugarloafLayout {
 }
 
 pub struct Screen {
-    bindings: bindings::KeyBindings,
+    bindings: bindings::KeyTables,
     mouse_bindings: Vec<MouseBinding>,
     clipboard: Clipboard,
+    paste_config: rio_config::Paste,
+    screenshot_config: rio_config::Screenshot,
+    pending_paste: Option<String>,
+    pager_config: rio_config::Pager,
+    monitor_config: rio_config::Monitor,
+    /// Progress last shown on the taskbar.
+    taskbar_progress: Progress,
+    alternate_scroll: rio_config::AlternateScroll,
+    scroll_config: rio_config::Scroll,
+    /// Viewport of a tab sliding to the place a page or prompt action moved
+    /// it to.
+    smooth_scroll: Option<(usize, smooth_scroll::SmoothScroll)>,
+    pager: Option<router::pager::Pager>,
+    quick_select_patterns: Vec<regex::Regex>,
+    quick_select: Option<router::quick_select::QuickSelect>,
+    rename_tab: Option<router::rename::RenameTab>,
+    context_menu: Option<router::context_menu::ContextMenu>,
+    /// Overlay with the frame rate, input latency and output throughput.
+    stats: Option<router::stats::Stats>,
+    last_render: Instant,
+    performance: rio_config::Performance,
+    low_power: bool,
+    low_latency: bool,
+    // Grid resized once the window stops being resized, see `finish_resize`.
+    deferred_resize: bool,
+    has_pending_resize: bool,
+    measure_input_latency: bool,
+    /// Tint the cells damaged since the previous frame.
+    show_damage: bool,
+    /// Switch to the remote profile while the current tab runs an SSH client.
+    has_remote_profile: bool,
+    /// Whether the current tab was running an SSH client at the last frame.
+    remote: bool,
+    /// Colors of the remote profile theme, drawn under the terminal colors.
+    remote_palette: TermColors,
+    remote_shell_integration: Option<bool>,
+    shell_integration: bool,
+    // Time a key press was written to the PTY and the bytes parsed by its tab
+    // then, only tracked when measuring the input latency.
+    pending_input: Option<(Instant, u64)>,
+    // Time the key press echoed by the frame being drawn was written.
+    input_frame: Option<Instant>,
+    tmux: Option<tmux::Session>,
+    // Last memory check, shown by the glyph atlas view.
+    pub memory: crate::memory::Report,
+    scale_factor: rio_config::ScaleFactor,
+    // Scale factor reported by winit and the one set for the X11 session.
+    reported_scale: f64,
+    system_scale: Option<f64>,
+    composition: keyboard::Composition,
+    leader: Option<bindings::Leader>,
+    // Time the leader key was pressed, while waiting for the next key.
+    leader_pressed: Option<Instant>,
     pub modifiers: Modifiers,
     pub mouse: Mouse,
     pub ime: Ime,
+    pub accessibility: Accessibility,
     pub state: State,
     pub sugarloaf: Sugarloaf,
     pub context_manager: context::ContextManager<EventProxy>,
@@ -102,14 +202,18 @@ impl Screen {
         font_database: &sugarloaf::font::loader::Database,
     ) -> Result<Screen, Box<dyn Error>> {
         let size = winit_window.inner_size();
-        let scale = winit_window.scale_factor();
         // let raw_window_handle = winit_window.raw_window_handle();
         let raw_display_handle = winit_window.raw_display_handle();
+        let reported_scale = winit_window.scale_factor();
+        let system_scale = system_scale_factor(&raw_display_handle, reported_scale);
+        let scale = dpi::resolve(config.scale_factor, reported_scale, || system_scale);
         let window_id = winit_window.id();
 
-        let power_preference: wgpu::PowerPreference = match config.performance {
-            rio_config::Performance::High => wgpu::PowerPreference::HighPerformance,
-            rio_config::Performance::Low => wgpu::PowerPreference::LowPower,
+        let low_power = is_low_power(config.performance);
+        let power_preference: wgpu::PowerPreference = if low_power {
+            wgpu::PowerPreference::LowPower
+        } else {
+            wgpu::PowerPreference::HighPerformance
         };
 
         let mut padding_y_bottom = 0.0;
@@ -122,7 +226,10 @@ impl Screen {
 
         #[cfg(not(target_os = "macos"))]
         {
-            if config.navigation.is_placed_on_top() {
+            // Borderless windows keep a strip above the terminal to be dragged.
+            if config.navigation.is_placed_on_top()
+                || config.window.decorations == rio_config::window::Decorations::Disabled
+            {
                 padding_y_top = constants::PADDING_Y_WITH_TAB_ON_TOP;
             }
         }
@@ -138,9 +245,17 @@ impl Screen {
         );
 
         let mut sugarloaf_errors: Option<SugarloafErrors> = None;
-        let sugarloaf: Sugarloaf = match Sugarloaf::new(
+        let color_space = match config.color_space {
+            rio_config::ColorSpace::Srgb => sugarloaf::context::ColorSpace::Srgb,
+            rio_config::ColorSpace::DisplayP3 => {
+                sugarloaf::context::ColorSpace::DisplayP3
+            }
+        };
+
+        let mut sugarloaf: Sugarloaf = match Sugarloaf::new(
             winit_window,
             power_preference,
+            color_space,
             config.fonts.to_owned(),
             sugarloaf_layout,
             Some(font_database),
@@ -153,16 +268,33 @@ impl Screen {
                 instance_with_errors.instance
             }
         };
+        startup::milestone("renderer initialized");
+        crate::crash::set_adapter(&sugarloaf.ctx.adapter_info);
+
+        if config.low_latency {
+            sugarloaf.ctx.set_low_latency(true);
+        }
+        set_frame_timing(&mut sugarloaf, config.developer.measure_input_latency);
+        sugarloaf.set_color_filter(color_filter(config.color_filter));
+        sugarloaf.set_render_scale(config.render_scale);
 
-        let state = State::new(config, winit_window.theme());
+        let mut state = State::new(config, winit_window.theme());
+        state.set_low_power(low_power);
 
         let clipboard = unsafe { Clipboard::new(raw_display_handle) };
+        crate::clipboard::set_history_size(config.clipboard_history.capacity());
 
+        let leader = config.bindings.leader.as_ref().and_then(|leader| {
+            bindings::Leader::new(leader)
+                .map_err(|err| log::error!("error loading the leader key: {err}"))
+                .ok()
+        });
         let bindings = bindings::default_key_bindings(
             config.bindings.keys.to_owned(),
             config.navigation.is_plain(),
         );
         let ime = Ime::new();
+        let accessibility = Accessibility::new(winit_window);
 
         let is_collapsed = config.navigation.is_collapsed_mode();
         let is_native = config.navigation.is_native();
@@ -175,9 +307,28 @@ impl Screen {
             is_collapsed,
             is_native,
             // When navigation is collapsed and does not contain any color rule
-            // does not make sense fetch for foreground process names
+            // does not make sense fetch for foreground process names, unless
+            // they tell when to switch to the remote profile
             should_update_titles: !(is_collapsed
-                && config.navigation.color_automation.is_empty()),
+                && config.navigation.color_automation.is_empty())
+                || config.remote_profile.is_some(),
+            word_separators: config.selection.word_separators.to_owned(),
+            smart_selection: config.selection.smart_shell_tokens,
+            reveal_concealed: config.selection.reveal_concealed,
+            trim_trailing_whitespace: config.selection.trim_trailing_whitespace,
+            join_wrapped_lines: config.selection.join_wrapped_lines,
+            unicode_version: config.unicode_version,
+            compress_scrollback: config.memory.compress_scrollback,
+            watch_rules: watch::compile(&config.watch.rules),
+            output_log: output_log_config(config),
+            env: vec![],
+            title: config
+                .profile
+                .as_ref()
+                .and_then(|name| config.find_profile(name))
+                .and_then(|profile| profile.title.to_owned()),
+            profiles: config.profiles.to_owned(),
+            palette: TermColors::default(),
         };
         let context_manager = context::ContextManager::start(
             (sugarloaf.layout.width_u32, sugarloaf.layout.height_u32),
@@ -189,19 +340,177 @@ impl Screen {
             sugarloaf_errors,
         )?;
 
+        let stats = config
+            .developer
+            .enable_fps_counter
+            .then(|| router::stats::Stats::new(context_manager.parsed_bytes()));
+
+        let (remote_palette, remote_shell_integration) = remote_profile(config);
+
         Ok(Screen {
-            mouse_bindings: bindings::default_mouse_bindings(),
+            mouse_bindings: bindings::default_mouse_bindings(
+                config.bindings.mouse.to_owned(),
+            ),
             modifiers: Modifiers::default(),
             context_manager,
             ime,
+            accessibility,
             sugarloaf,
             mouse: Mouse::default(),
             state,
             bindings,
             clipboard,
+            paste_config: config.paste.to_owned(),
+            screenshot_config: config.screenshot.to_owned(),
+            pending_paste: None,
+            pager_config: config.pager.to_owned(),
+            monitor_config: config.monitor.to_owned(),
+            taskbar_progress: Progress::default(),
+            alternate_scroll: config.alternate_scroll.to_owned(),
+            scroll_config: config.scroll.to_owned(),
+            smooth_scroll: None,
+            pager: None,
+            quick_select_patterns: router::quick_select::compile(
+                &config.quick_select.patterns,
+            ),
+            quick_select: None,
+            rename_tab: None,
+            context_menu: None,
+            stats,
+            last_render: Instant::now(),
+            performance: config.performance,
+            low_power,
+            low_latency: config.low_latency,
+            deferred_resize: config.deferred_resize,
+            has_pending_resize: false,
+            measure_input_latency: config.developer.measure_input_latency,
+            show_damage: config.developer.show_damage,
+            has_remote_profile: config.remote_profile.is_some(),
+            remote: false,
+            remote_palette,
+            remote_shell_integration,
+            shell_integration: config.shell_integration,
+            pending_input: None,
+            input_frame: None,
+            tmux: None,
+            memory: crate::memory::Report::default(),
+            scale_factor: config.scale_factor,
+            reported_scale,
+            system_scale,
+            composition: keyboard::Composition::default(),
+            leader,
+            leader_pressed: None,
         })
     }
 
+    /// Whether the mouse is above the terminal grid, where borderless
+    /// windows can be dragged.
+    #[inline]
+    pub fn is_drag_region(&self) -> bool {
+        let layout = &self.sugarloaf.layout;
+        (self.mouse.y as f32) < layout.margin.top_y * 2. * layout.scale_factor
+    }
+
+    /// Whether the mouse is over the pill counting the lines written below
+    /// the viewport.
+    #[inline]
+    pub fn is_new_lines_pill(&self) -> bool {
+        let scale = self.sugarloaf.layout.scale_factor;
+        let (x, y) = (self.mouse.x as f32 / scale, self.mouse.y as f32 / scale);
+        self.state
+            .new_lines_pill
+            .is_some_and(|[left, top, width, height]| {
+                x >= left && x <= left + width && y >= top && y <= top + height
+            })
+    }
+
+    /// Scroll to the prompt of the scrollback marker under the mouse, returns
+    /// false when there is none.
+    pub fn click_prompt_mark(&mut self) -> bool {
+        let scale = self.sugarloaf.layout.scale_factor;
+        let (x, y) = (self.mouse.x as f32 / scale, self.mouse.y as f32 / scale);
+        let line = self.state.prompt_mark_bounds.iter().find_map(
+            |([left, top, width, height], line)| {
+                (x >= *left && x <= left + width && y >= *top && y <= top + height)
+                    .then_some(*line)
+            },
+        );
+
+        match line {
+            Some(line) => {
+                self.ctx_mut()
+                    .current_mut()
+                    .terminal
+                    .lock()
+                    .scroll_to_line(line);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Clear the viewport like `CSI H CSI 2 J` would, then send a form feed
+    /// so the shell draws its prompt again.
+    pub fn clear_screen(&mut self) {
+        self.state.set_selection(None);
+        let current_context = self.context_manager.current_mut();
+        let mut terminal = current_context.terminal.lock();
+        terminal.scroll_display(Scroll::Bottom);
+        terminal.clear_viewport();
+        drop(terminal);
+        current_context.messenger.send_bytes(b"\x0c".to_vec());
+    }
+
+    /// Clear the scrollback like `CSI 3 J` would.
+    pub fn clear_history(&mut self) {
+        let mut terminal = self.context_manager.current_mut().terminal.lock();
+        terminal.clear_saved_history();
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        let mut terminal = self.context_manager.current_mut().terminal.lock();
+        terminal.scroll_display(Scroll::Bottom);
+
+        // Move vi mode cursor.
+        terminal.vi_mode_cursor.pos.row = terminal.grid.bottommost_line();
+
+        // Move to beginning twice, to always jump across linewraps.
+        terminal.vi_motion(ViMotion::FirstOccupied);
+        terminal.vi_motion(ViMotion::FirstOccupied);
+    }
+
+    #[inline]
+    pub fn ring_bell(&mut self) {
+        self.state.ring_bell();
+    }
+
+    #[inline]
+    pub fn is_low_power(&self) -> bool {
+        self.low_power
+    }
+
+    /// Switch to the profile of the current power source when the
+    /// performance follows it.
+    pub fn update_power_source(&mut self) {
+        self.set_low_power(is_low_power(self.performance));
+    }
+
+    pub fn update_reduce_motion(&mut self) {
+        self.state.update_reduce_motion();
+        self.state.set_low_power(self.low_power);
+    }
+
+    fn set_low_power(&mut self, low_power: bool) {
+        self.low_power = low_power;
+        self.state.set_low_power(low_power);
+        self.sugarloaf.ctx.set_low_power(low_power);
+    }
+
+    #[inline]
+    pub fn is_low_latency(&self) -> bool {
+        self.low_latency
+    }
+
     #[inline]
     pub fn ctx(&self) -> &ContextManager<EventProxy> {
         &self.context_manager
@@ -224,7 +533,7 @@ impl Screen {
 
     #[inline]
     pub fn mouse_position(&self, display_offset: usize) -> Pos {
-        calculate_mouse_position(
+        let mut pos = calculate_mouse_position(
             &self.mouse,
             display_offset,
             self.sugarloaf.layout.scale_factor,
@@ -235,7 +544,20 @@ impl Screen {
                 self.sugarloaf.layout.scaled_sugarwidth,
                 self.sugarloaf.layout.scaled_sugarheight,
             ),
-        )
+        );
+
+        // Rows are drawn reordered, the mouse is over the square drawn there.
+        if self.state.bidi {
+            let terminal = self.ctx().current().terminal.lock();
+            if pos.row >= terminal.grid.topmost_line()
+                && pos.row <= terminal.grid.bottommost_line()
+            {
+                let row = &terminal.grid[pos.row];
+                pos.col = Column(crate::crosswords::bidi::logical_column(row, pos.col.0));
+            }
+        }
+
+        pos
     }
 
     #[inline]
@@ -286,6 +608,13 @@ impl Screen {
             return;
         }
 
+        self.scale_factor = config.scale_factor;
+        let size = winit::dpi::PhysicalSize::new(
+            self.sugarloaf.layout.width_u32,
+            self.sugarloaf.layout.height_u32,
+        );
+        self.set_scale(self.resolved_scale(), size);
+
         let mut padding_y_bottom = 0.0;
         if config.navigation.is_placed_on_bottom() {
             padding_y_bottom += config.fonts.size
@@ -300,15 +629,66 @@ impl Screen {
 
         self.sugarloaf.layout.update();
         self.state = State::new(config, current_theme);
+        self.performance = config.performance;
+        self.set_low_power(is_low_power(config.performance));
+        self.low_latency = config.low_latency;
+        self.deferred_resize = config.deferred_resize;
+        self.measure_input_latency = config.developer.measure_input_latency;
+        set_frame_timing(&mut self.sugarloaf, self.measure_input_latency);
+        self.show_damage = config.developer.show_damage;
+        self.has_remote_profile = config.remote_profile.is_some();
+        self.remote &= self.has_remote_profile;
+        (self.remote_palette, self.remote_shell_integration) = remote_profile(config);
+        self.shell_integration = config.shell_integration;
+        if self.stats.is_some() != config.developer.enable_fps_counter {
+            self.toggle_stats();
+        }
+        self.sugarloaf.ctx.set_low_latency(config.low_latency);
+        self.sugarloaf
+            .set_color_filter(color_filter(config.color_filter))
+            .set_render_scale(config.render_scale);
 
+        let watch_rules = watch::compile(&config.watch.rules);
         for context in self.ctx().contexts() {
             let mut terminal = context.terminal.lock();
             let cursor = self.state.get_cursor_state_from_ref().content;
             terminal.cursor_shape = cursor;
             terminal.default_cursor_shape = cursor;
             terminal.blinking_cursor = config.blinking_cursor;
+            terminal.set_semantic_escape_chars(&config.selection.word_separators);
+            terminal.smart_selection = config.selection.smart_shell_tokens;
+            terminal.reveal_concealed = config.selection.reveal_concealed;
+            terminal.trim_trailing_whitespace = config.selection.trim_trailing_whitespace;
+            terminal.join_wrapped_lines = config.selection.join_wrapped_lines;
+            terminal.unicode_version = config.unicode_version;
+            terminal.set_compress_scrollback(config.memory.compress_scrollback);
+            terminal.watch_rules = watch_rules.to_owned();
         }
 
+        self.paste_config = config.paste.to_owned();
+        self.pager_config = config.pager.to_owned();
+        self.monitor_config = config.monitor.to_owned();
+        self.alternate_scroll = config.alternate_scroll.to_owned();
+        self.scroll_config = config.scroll.to_owned();
+        self.quick_select_patterns =
+            router::quick_select::compile(&config.quick_select.patterns);
+        crate::clipboard::set_history_size(config.clipboard_history.capacity());
+        self.screenshot_config = config.screenshot.to_owned();
+        self.context_manager.config.word_separators =
+            config.selection.word_separators.to_owned();
+        self.context_manager.config.smart_selection = config.selection.smart_shell_tokens;
+        self.context_manager.config.reveal_concealed = config.selection.reveal_concealed;
+        self.context_manager.config.trim_trailing_whitespace =
+            config.selection.trim_trailing_whitespace;
+        self.context_manager.config.join_wrapped_lines =
+            config.selection.join_wrapped_lines;
+        self.context_manager.config.unicode_version = config.unicode_version;
+        self.context_manager.config.compress_scrollback =
+            config.memory.compress_scrollback;
+        self.context_manager.config.watch_rules = watch_rules;
+        self.context_manager.config.profiles = config.profiles.to_owned();
+        self.context_manager.config.output_log = output_log_config(config);
+
         let width = self.sugarloaf.layout.width_u32 as u16;
         let height = self.sugarloaf.layout.height_u32 as u16;
         let columns = self.sugarloaf.layout.columns;
@@ -351,6 +731,15 @@ impl Screen {
 
     #[inline]
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) -> &mut Self {
+        if self.deferred_resize {
+            // The previous grid keeps its cell size, letterboxed or clipped by
+            // the new surface, instead of reflowing on every resize event.
+            self.sugarloaf
+                .resize_surface(new_size.width, new_size.height);
+            self.has_pending_resize = true;
+            return self;
+        }
+
         self.sugarloaf.resize(new_size.width, new_size.height);
 
         self.resize_all_contexts(
@@ -362,17 +751,75 @@ impl Screen {
         self
     }
 
+    #[inline]
+    pub fn has_pending_resize(&self) -> bool {
+        self.has_pending_resize
+    }
+
+    /// Compute the grid for the current surface size after a deferred
+    /// resize, returns whether there was one.
+    pub fn finish_resize(&mut self) -> bool {
+        if !self.has_pending_resize {
+            return false;
+        }
+
+        self.has_pending_resize = false;
+        self.sugarloaf.layout.update();
+        self.resize_all_contexts(
+            self.sugarloaf.layout.width_u32 as u16,
+            self.sugarloaf.layout.height_u32 as u16,
+            self.sugarloaf.layout.columns,
+            self.sugarloaf.layout.lines,
+        );
+        true
+    }
+
+    #[inline]
+    /// Follow the scale factor reported by winit, unless the configuration
+    /// overrides it.
+    pub fn set_reported_scale(
+        &mut self,
+        reported_scale: f64,
+        new_size: winit::dpi::PhysicalSize<u32>,
+    ) -> &mut Self {
+        self.reported_scale = reported_scale;
+        self.set_scale(self.resolved_scale(), new_size)
+    }
+
+    #[inline]
+    fn resolved_scale(&self) -> f32 {
+        dpi::resolve(self.scale_factor, self.reported_scale, || self.system_scale) as f32
+    }
+
     #[inline]
     pub fn set_scale(
         &mut self,
         new_scale: f32,
         new_size: winit::dpi::PhysicalSize<u32>,
     ) -> &mut Self {
+        if new_scale == self.sugarloaf.layout.scale_factor
+            && new_size.width == self.sugarloaf.layout.width_u32
+            && new_size.height == self.sugarloaf.layout.height_u32
+        {
+            return self;
+        }
+
+        // The text scale follows the scale factor, glyphs are rasterized again
+        // at the new size and the cell bounds have to be measured again before
+        // the grid is computed.
         self.sugarloaf
             .rescale(new_scale)
             .resize(new_size.width, new_size.height)
             .calculate_bounds();
+        self.sugarloaf.layout.update();
+        self.has_pending_resize = false;
 
+        self.resize_all_contexts(
+            new_size.width as u16,
+            new_size.height as u16,
+            self.sugarloaf.layout.columns,
+            self.sugarloaf.layout.lines,
+        );
         self
     }
 
@@ -395,6 +842,13 @@ impl Screen {
                 lines as u16,
             );
         }
+
+        if let Some(session) = &mut self.tmux {
+            if let tmux::Action::Command(command) = session.client.resize(columns, lines)
+            {
+                session.gateway.send_write(command.into_bytes());
+            }
+        }
     }
 
     #[inline]
@@ -432,19 +886,20 @@ impl Screen {
 
     #[inline]
     pub fn get_mode(&self) -> Mode {
-        let terminal = self.ctx().current().terminal.lock();
-        let mode = terminal.mode();
-        drop(terminal);
-        mode
+        let current = self.ctx().current();
+        // Don't wait for the PTY reader while it parses a batch of output.
+        match current.terminal.try_lock_unfair() {
+            Some(terminal) => terminal.mode(),
+            None => Mode::from_bits_truncate(current.mode.load(Ordering::Relaxed)),
+        }
     }
 
     #[inline]
-    #[allow(unused)]
-    pub fn colors(&mut self) -> List {
+    pub fn colors(&mut self) -> TermColors {
         let terminal = self.ctx().current().terminal.lock();
-        let mode = terminal.colors();
+        let colors = terminal.colors();
         drop(terminal);
-        mode
+        colors
     }
 
     #[inline]
@@ -457,6 +912,10 @@ impl Screen {
         let mods = self.modifiers.state();
 
         if key.state == ElementState::Released {
+            if self.composition.release(key.physical_key) {
+                return;
+            }
+
             if mode.contains(Mode::KEYBOARD_REPORT_EVENT_TYPES)
                 && !mode.contains(Mode::VI)
             {
@@ -477,267 +936,112 @@ impl Screen {
             return;
         }
 
-        let binding_mode = BindingMode::new(&mode);
-        let mut ignore_chars = None;
-
-        for i in 0..self.bindings.len() {
-            let binding = &self.bindings[i];
-
-            // When the logical key is some named key, use it, otherwise fallback to
-            // key without modifiers to account for bindings.
-            let logical_key = if matches!(key.logical_key, Key::Character(_)) {
-                key.key_without_modifiers()
-            } else {
-                key.logical_key.clone()
-            };
-
-            let key = match (&binding.trigger, logical_key) {
-                (BindingKey::Scancode(_), _) => BindingKey::Scancode(key.physical_key),
-                (_, code) => BindingKey::Keycode {
-                    key: code,
-                    location: key.location,
-                },
-            };
-
-            if binding.is_triggered_by(binding_mode.to_owned(), mods, &key) {
-                *ignore_chars.get_or_insert(true) &= binding.action != Act::ReceiveChar;
-
-                match &binding.action {
-                    #[cfg(unix)]
-                    Act::Run(program) => self.exec(program.program(), program.args()),
-                    Act::Esc(s) => {
-                        let current_context = self.context_manager.current_mut();
-                        self.state.set_selection(None);
-                        let mut terminal = current_context.terminal.lock();
-                        terminal.selection.take();
-                        terminal.scroll_display(Scroll::Bottom);
-                        drop(terminal);
-                        current_context.messenger.send_bytes(s.clone().into_bytes());
-                    }
-                    Act::Paste => {
-                        let content = self.clipboard.get(ClipboardType::Clipboard);
-                        self.paste(&content, true);
-                    }
-                    Act::ClearSelection => {
-                        self.clear_selection();
-                    }
-                    Act::PasteSelection => {
-                        let content = self.clipboard.get(ClipboardType::Selection);
-                        self.paste(&content, true);
-                    }
-                    Act::Copy => {
-                        self.copy_selection(ClipboardType::Clipboard);
-                    }
-                    Act::ToggleViMode => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.toggle_vi_mode();
-                        drop(terminal);
-                    }
-                    Act::ViMotion(motion) => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        if terminal.mode().contains(Mode::VI) {
-                            terminal.vi_motion(*motion);
-                        }
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::Vi(ViAction::CenterAroundViCursor) => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let display_offset = terminal.display_offset() as i32;
-                        let target =
-                            -display_offset + terminal.grid.screen_lines() as i32 / 2 - 1;
-                        let line = terminal.vi_mode_cursor.pos.row;
-                        let scroll_lines = target - line.0;
-
-                        terminal.scroll_display(Scroll::Delta(scroll_lines));
-                        drop(terminal);
-                    }
-                    Act::Vi(ViAction::ToggleNormalSelection) => {
-                        self.toggle_selection(SelectionType::Simple, Side::Left);
-                        self.render();
-                    }
-                    Act::Vi(ViAction::ToggleLineSelection) => {
-                        self.toggle_selection(SelectionType::Lines, Side::Left);
-                        self.render();
-                    }
-                    Act::Vi(ViAction::ToggleBlockSelection) => {
-                        self.toggle_selection(SelectionType::Block, Side::Left);
-                        self.render();
-                    }
-                    Act::Vi(ViAction::ToggleSemanticSelection) => {
-                        self.toggle_selection(SelectionType::Semantic, Side::Left);
-                        self.render();
-                    }
-                    Act::ConfigEditor => {
-                        self.context_manager.switch_to_settings();
-                    }
-                    Act::WindowCreateNew => {
-                        self.context_manager.create_new_window();
-                    }
-                    Act::TabCreateNew => {
-                        let redirect = true;
-
-                        self.context_manager.add_context(
-                            redirect,
-                            (
-                                self.sugarloaf.layout.width_u32,
-                                self.sugarloaf.layout.height_u32,
-                            ),
-                            (self.sugarloaf.layout.columns, self.sugarloaf.layout.lines),
-                            (
-                                &self.state.get_cursor_state_from_ref(),
-                                self.state.has_blinking_enabled,
-                            ),
-                        );
-
-                        self.render();
-                    }
-                    Act::TabCloseCurrent => {
-                        self.clear_selection();
-
-                        if self.context_manager.config.is_native {
-                            self.context_manager.close_current_window();
-                        } else {
-                            // Kill current context will trigger terminal.exit
-                            // then RioEvent::Exit and eventually try_close_existent_tab
-                            self.context_manager.kill_current_context();
-                        }
-                    }
-                    Act::Quit => {
-                        // TODO: Add it in event system
-                        std::process::exit(0);
-                    }
-                    Act::IncreaseFontSize => {
-                        self.change_font_size(FontSizeAction::Increase);
-                    }
-                    Act::DecreaseFontSize => {
-                        self.change_font_size(FontSizeAction::Decrease);
-                    }
-                    Act::ResetFontSize => {
-                        self.change_font_size(FontSizeAction::Reset);
-                    }
-                    Act::ScrollPageUp => {
-                        // Move vi mode cursor.
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let scroll_lines = terminal.grid.screen_lines() as i32;
-                        terminal.vi_mode_cursor =
-                            terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
-                        terminal.scroll_display(Scroll::PageUp);
-                        drop(terminal);
-                    }
-                    Act::ScrollPageDown => {
-                        // Move vi mode cursor.
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let scroll_lines = -(terminal.grid.screen_lines() as i32);
+        // Keys of a dead key or Compose sequence carry no text until the
+        // sequence completes, only the composed text is written.
+        if self.composition.press(
+            key.physical_key,
+            &key.logical_key,
+            key.text.as_ref().map(|text| text.as_str()),
+        ) {
+            return;
+        }
 
-                        terminal.vi_mode_cursor =
-                            terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
+        let mut binding_mode = BindingMode::new(&mode);
+        let mut ignore_chars = None;
+        // Bindings of a Latin key still apply with other layouts.
+        let fallback_key =
+            keyboard::binding_fallback(&key.key_without_modifiers(), key.physical_key);
+
+        // Only the bindings following the leader apply to the key pressed
+        // after it, modifiers are part of that key.
+        let leader_pending = self.leader_remaining().is_some();
+        let is_leader = self.is_leader(key, mods, fallback_key.as_ref());
+        if leader_pending {
+            if matches!(
+                key.logical_key,
+                Key::Shift | Key::Control | Key::Alt | Key::Super
+            ) {
+                return;
+            }
+            self.set_leader_pending(false);
+            binding_mode.insert(BindingMode::LEADER);
+        } else if is_leader {
+            self.set_leader_pending(true);
+            return;
+        }
 
-                        terminal.scroll_display(Scroll::PageDown);
-                        drop(terminal);
-                    }
-                    Act::ScrollHalfPageUp => {
-                        // Move vi mode cursor.
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let scroll_lines = terminal.grid.screen_lines() as i32 / 2;
+        // Copy mode bindings are stacked over the normal ones, the keys are
+        // looked up from the top until a table has a binding for them.
+        let tables: &[KeyTable] = if mode.contains(Mode::VI) {
+            &[KeyTable::Copy, KeyTable::Normal]
+        } else {
+            &[KeyTable::Normal]
+        };
+        for table in tables {
+            for i in 0..self.bindings.table(*table).len() {
+                let binding = &self.bindings.table(*table)[i];
+                if leader_pending && !binding.mode.contains(BindingMode::LEADER) {
+                    continue;
+                }
 
-                        terminal.vi_mode_cursor =
-                            terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
+                // When the logical key is some named key, use it, otherwise fallback to
+                // key without modifiers to account for bindings.
+                let logical_key = if matches!(key.logical_key, Key::Character(_)) {
+                    key.key_without_modifiers()
+                } else {
+                    key.logical_key.clone()
+                };
 
-                        terminal.scroll_display(Scroll::Delta(scroll_lines));
-                        drop(terminal);
+                let key = match (&binding.trigger, logical_key) {
+                    (BindingKey::Scancode(_), _) => {
+                        BindingKey::Scancode(key.physical_key)
                     }
-                    Act::ScrollHalfPageDown => {
-                        // Move vi mode cursor.
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let scroll_lines = -(terminal.grid.screen_lines() as i32 / 2);
-
-                        terminal.vi_mode_cursor =
-                            terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
+                    (_, code) => BindingKey::Keycode {
+                        key: code,
+                        location: key.location,
+                    },
+                };
 
-                        terminal.scroll_display(Scroll::Delta(scroll_lines));
-                        drop(terminal);
-                    }
-                    Act::ScrollToTop => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.scroll_display(Scroll::Top);
-
-                        let topmost_line = terminal.grid.topmost_line();
-                        terminal.vi_mode_cursor.pos.row = topmost_line;
-                        terminal.vi_motion(ViMotion::FirstOccupied);
-                        drop(terminal);
-                    }
-                    Act::ScrollToBottom => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.scroll_display(Scroll::Bottom);
-
-                        // Move vi mode cursor.
-                        terminal.vi_mode_cursor.pos.row = terminal.grid.bottommost_line();
-
-                        // Move to beginning twice, to always jump across linewraps.
-                        terminal.vi_motion(ViMotion::FirstOccupied);
-                        terminal.vi_motion(ViMotion::FirstOccupied);
-                        drop(terminal);
-                    }
-                    Act::Scroll(delta) => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.scroll_display(Scroll::Delta(*delta));
-                        drop(terminal);
-                    }
-                    Act::ClearHistory => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.clear_saved_history();
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ToggleFullscreen => self.context_manager.toggle_full_screen(),
-                    Act::Minimize => {
-                        self.context_manager.minimize();
-                    }
-                    Act::Hide => {
-                        self.context_manager.hide();
-                    }
-                    #[cfg(target_os = "macos")]
-                    Act::HideOtherApplications => {
-                        self.context_manager.hide_other_apps();
-                    }
-                    Act::SelectTab(tab_index) => {
-                        self.context_manager.select_tab(*tab_index);
-                    }
-                    Act::SelectLastTab => {
-                        self.context_manager.select_last_tab();
-                    }
-                    Act::SelectNextTab => {
-                        self.clear_selection();
-                        self.context_manager.switch_to_next();
-                        self.render();
-                    }
-                    Act::SelectPrevTab => {
-                        self.clear_selection();
-                        self.context_manager.switch_to_prev();
-                        self.render();
-                    }
-                    Act::ReceiveChar | Act::None => (),
-                    _ => (),
+                let triggered =
+                    binding.is_triggered_by(binding_mode.to_owned(), mods, &key)
+                        || match (&key, &fallback_key) {
+                            (BindingKey::Keycode { location, .. }, Some(fallback)) => {
+                                binding.is_triggered_by(
+                                    binding_mode.to_owned(),
+                                    mods,
+                                    &BindingKey::Keycode {
+                                        key: fallback.clone(),
+                                        location: *location,
+                                    },
+                                )
+                            }
+                            _ => false,
+                        };
+
+                if triggered {
+                    *ignore_chars.get_or_insert(true) &=
+                        binding.action != Act::ReceiveChar;
+
+                    let action = binding.action.clone();
+                    self.run_action(&action);
                 }
             }
+
+            if ignore_chars.is_some() {
+                break;
+            }
         }
 
         if ignore_chars.unwrap_or(false) || mode.contains(Mode::VI) {
             return;
         }
 
+        // Keys without a binding after the leader are dropped, the leader
+        // pressed twice is written.
+        if leader_pending && ignore_chars.is_none() && !is_leader {
+            return;
+        }
+
         let text = key.text_with_all_modifiers().unwrap_or_default();
 
         // We use legacy input when we have associated text with
@@ -769,37 +1073,529 @@ impl Screen {
             self.build_key_sequence(key.to_owned(), mods, mode)
         };
 
-        // Write only when we have something to write.
-        if !bytes.is_empty() {
-            self.scroll_bottom_when_cursor_not_visible();
-            self.clear_selection();
+        self.send_input(bytes);
+    }
 
-            self.ctx_mut().current_mut().messenger.send_bytes(bytes);
+    /// Run the action of a key or mouse binding.
+    fn run_action(&mut self, action: &Act) {
+        // Prompt marks left by the local shell mean nothing to the remote one.
+        let shell_integration = match self.remote_shell_integration {
+            Some(enabled) if self.remote => enabled,
+            _ => self.shell_integration,
+        };
+        if !shell_integration && action.uses_shell_integration() {
+            return;
         }
-    }
 
-    #[inline]
-    pub fn process_mouse_bindings(&mut self, button: MouseButton) {
-        let mode = self.get_mode();
-        let binding_mode = BindingMode::new(&mode);
-        let mouse_mode = self.mouse_mode();
-        let mods = self.modifiers.state();
+        match action {
+            Act::Run(program) => self.exec(program.program(), program.args()),
+            Act::Esc(s) => {
+                let current_context = self.context_manager.current_mut();
+                self.state.set_selection(None);
+                let mut terminal = current_context.terminal.lock();
+                terminal.selection.take();
+                terminal.scroll_display(Scroll::Bottom);
+                drop(terminal);
+                current_context.messenger.send_bytes(s.clone().into_bytes());
+            }
+            Act::SendText(text) => {
+                let bytes = text.clone().into_bytes();
+                self.send_input(bytes);
+            }
+            Act::SendBytes(bytes) => {
+                let bytes = bytes.clone();
+                self.send_input(bytes);
+            }
+            Act::Paste => {
+                self.paste_from_clipboard(ClipboardType::Clipboard);
+            }
+            Act::ClearSelection => {
+                self.clear_selection();
+            }
+            Act::PasteSelection => {
+                self.paste_from_clipboard(ClipboardType::Selection);
+            }
+            Act::Copy => {
+                self.copy_selection(ClipboardType::Clipboard);
+            }
+            Act::SelectLastCommandOutput => {
+                self.select_last_command_output();
+                self.render();
+            }
+            Act::CopyLastCommandOutput => {
+                self.copy_last_command_output();
+            }
+            Act::PageLastCommandOutput => {
+                self.page_last_command_output();
+                self.render();
+            }
+            Act::ViewLastCommandOutput => {
+                self.view_last_command_output();
+            }
+            Act::QuickSelect => {
+                self.open_quick_select();
+            }
+            Act::ClipboardHistory => {
+                let history = crate::clipboard::history();
+                self.quick_select = Some(router::quick_select::QuickSelect::new(history));
+                self.context_manager.open_quick_select();
+            }
+            Act::ContextMenu => {
+                let display_offset = self.display_offset();
+                let cursor = self.ctx().current().terminal.lock().grid.cursor.pos;
+
+                let layout = &self.sugarloaf.layout;
+                let cell_height = layout.scaled_sugarheight / layout.scale_factor;
+                let row = cursor.row.0 + display_offset as i32 + 1;
+                let position = [
+                    layout.margin.x + cursor.col.0 as f32 * layout.sugarwidth,
+                    layout.margin.top_y * 2. + row as f32 * cell_height,
+                ];
+                self.open_context_menu(position, cursor);
+            }
+            Act::ViewScrollback => {
+                let text = self.text(true);
+                self.open_pager("Scrollback", &text);
+            }
+            Act::SearchSelection => {
+                let terminal = self.ctx().current().terminal.lock();
+                let bounds = terminal
+                    .selection
+                    .as_ref()
+                    .and_then(|selection| selection.to_range(&terminal))
+                    .map(|range| (range.start, range.end));
+                drop(terminal);
+                self.search_scrollback("selection", bounds);
+            }
+            Act::SearchLastCommandOutput => {
+                let bounds = self.ctx().current().terminal.lock().last_command_output();
+                self.search_scrollback("last output", bounds);
+            }
+            Act::RerunLastCommand => {
+                let terminal = self.ctx().current().terminal.lock();
+                let command = terminal.last_command();
+                drop(terminal);
 
-        for i in 0..self.mouse_bindings.len() {
-            let mut binding = self.mouse_bindings[i].clone();
+                if let Some(command) = command {
+                    self.ctx_mut()
+                        .current_mut()
+                        .messenger
+                        .send_bytes(format!("{command}\r").into_bytes());
+                }
+            }
+            Act::Screenshot => {
+                self.take_screenshot(false);
+                self.render();
+            }
+            Act::CopyScreenshot => {
+                self.take_screenshot(true);
+                self.render();
+            }
+            Act::ToggleRecording => {
+                self.toggle_recording();
+            }
+            Act::ToggleOutputLog => {
+                self.context_manager.toggle_output_log();
+            }
+            Act::ToggleStats => {
+                self.toggle_stats();
+                self.render();
+            }
+            Act::ShowGlyphAtlas => {
+                self.context_manager.open_glyph_atlas();
+            }
+            Act::CopyAsAnsi => {
+                self.copy_selection_as_ansi();
+            }
+            Act::CopyAsTsv => {
+                self.copy_selection_as_tsv();
+            }
+            Act::ToggleViMode => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.toggle_vi_mode();
+                drop(terminal);
+            }
+            Act::ViMotion(motion) => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                if terminal.mode().contains(Mode::VI) {
+                    terminal.vi_motion(*motion);
+                }
+                drop(terminal);
+                self.render();
+            }
+            Act::Vi(ViAction::CenterAroundViCursor) => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                let display_offset = terminal.display_offset() as i32;
+                let target =
+                    -display_offset + terminal.grid.screen_lines() as i32 / 2 - 1;
+                let line = terminal.vi_mode_cursor.pos.row;
+                let scroll_lines = target - line.0;
+
+                terminal.scroll_display(Scroll::Delta(scroll_lines));
+                drop(terminal);
+            }
+            Act::Vi(ViAction::ToggleNormalSelection) => {
+                self.toggle_selection(SelectionType::Simple, Side::Left);
+                self.render();
+            }
+            Act::Vi(ViAction::ToggleLineSelection) => {
+                self.toggle_selection(SelectionType::Lines, Side::Left);
+                self.render();
+            }
+            Act::Vi(ViAction::ToggleBlockSelection) => {
+                self.toggle_selection(SelectionType::Block, Side::Left);
+                self.render();
+            }
+            Act::Vi(ViAction::ToggleSemanticSelection) => {
+                self.toggle_selection(SelectionType::Semantic, Side::Left);
+                self.render();
+            }
+            Act::ToggleColorFilter(filter) => {
+                let filter = color_filter(*filter);
+                if self.sugarloaf.ctx.color_filter == filter {
+                    self.sugarloaf
+                        .set_color_filter(sugarloaf::context::ColorFilter::None);
+                } else {
+                    self.sugarloaf.set_color_filter(filter);
+                }
+                self.render();
+            }
+            Act::ConfigEditor => {
+                self.context_manager.switch_to_settings();
+            }
+            Act::WindowCreateNew => {
+                self.context_manager.create_new_window();
+            }
+            Act::TabCreateNew => {
+                let redirect = true;
+
+                self.context_manager.add_context(
+                    redirect,
+                    (
+                        self.sugarloaf.layout.width_u32,
+                        self.sugarloaf.layout.height_u32,
+                    ),
+                    (self.sugarloaf.layout.columns, self.sugarloaf.layout.lines),
+                    (
+                        &self.state.get_cursor_state_from_ref(),
+                        self.state.has_blinking_enabled,
+                    ),
+                );
+
+                self.render();
+            }
+            Act::TabCreateWithProfile(name) => {
+                self.context_manager.add_context_with_profile(
+                    name,
+                    (
+                        self.sugarloaf.layout.width_u32,
+                        self.sugarloaf.layout.height_u32,
+                    ),
+                    (self.sugarloaf.layout.columns, self.sugarloaf.layout.lines),
+                    (
+                        &self.state.get_cursor_state_from_ref(),
+                        self.state.has_blinking_enabled,
+                    ),
+                );
+
+                self.render();
+            }
+            Act::TabDuplicate => {
+                self.context_manager.duplicate_current_context(
+                    (
+                        self.sugarloaf.layout.width_u32,
+                        self.sugarloaf.layout.height_u32,
+                    ),
+                    (self.sugarloaf.layout.columns, self.sugarloaf.layout.lines),
+                    (
+                        &self.state.get_cursor_state_from_ref(),
+                        self.state.has_blinking_enabled,
+                    ),
+                );
+
+                self.render();
+            }
+            Act::ToggleMonitorActivity => {
+                self.toggle_monitor(MonitorKind::Activity);
+            }
+            Act::ToggleMonitorSilence => {
+                self.toggle_monitor(MonitorKind::Silence);
+            }
+            Act::TabRename => {
+                let name = self.context_manager.current().name.clone();
+                self.rename_tab = Some(router::rename::RenameTab::new(name));
+                self.context_manager.open_rename_tab();
+            }
+            Act::TabCloseCurrent => {
+                self.clear_selection();
 
-            // Require shift for all modifiers when mouse mode is active.
-            if mouse_mode {
-                binding.mods |= ModifiersState::SHIFT;
+                if self.context_manager.config.is_native {
+                    self.context_manager.close_current_window();
+                } else {
+                    // Kill current context will trigger terminal.exit
+                    // then RioEvent::Exit and eventually try_close_existent_tab
+                    self.context_manager.kill_current_context();
+                }
+            }
+            Act::Quit => {
+                // TODO: Add it in event system
+                std::process::exit(0);
+            }
+            Act::IncreaseFontSize => {
+                self.change_font_size(FontSizeAction::Increase);
+            }
+            Act::DecreaseFontSize => {
+                self.change_font_size(FontSizeAction::Decrease);
             }
+            Act::ResetFontSize => {
+                self.change_font_size(FontSizeAction::Reset);
+            }
+            Act::ScrollPageUp => {
+                self.scroll_smoothly(|terminal| {
+                    // Move vi mode cursor.
+                    let scroll_lines = terminal.grid.screen_lines() as i32;
+                    terminal.vi_mode_cursor =
+                        terminal.vi_mode_cursor.scroll(terminal, scroll_lines);
+                    terminal.scroll_display(Scroll::PageUp);
+                });
+            }
+            Act::ScrollPageDown => {
+                self.scroll_smoothly(|terminal| {
+                    // Move vi mode cursor.
+                    let scroll_lines = -(terminal.grid.screen_lines() as i32);
 
-            if binding.is_triggered_by(binding_mode.to_owned(), mods, &button)
-                && binding.action == Act::PasteSelection
-            {
-                let content = self.clipboard.get(ClipboardType::Selection);
-                self.paste(&content, true);
+                    terminal.vi_mode_cursor =
+                        terminal.vi_mode_cursor.scroll(terminal, scroll_lines);
+
+                    terminal.scroll_display(Scroll::PageDown);
+                });
+            }
+            Act::ScrollHalfPageUp => {
+                self.scroll_smoothly(|terminal| {
+                    // Move vi mode cursor.
+                    let scroll_lines = terminal.grid.screen_lines() as i32 / 2;
+
+                    terminal.vi_mode_cursor =
+                        terminal.vi_mode_cursor.scroll(terminal, scroll_lines);
+
+                    terminal.scroll_display(Scroll::Delta(scroll_lines));
+                });
+            }
+            Act::ScrollHalfPageDown => {
+                self.scroll_smoothly(|terminal| {
+                    // Move vi mode cursor.
+                    let scroll_lines = -(terminal.grid.screen_lines() as i32 / 2);
+
+                    terminal.vi_mode_cursor =
+                        terminal.vi_mode_cursor.scroll(terminal, scroll_lines);
+
+                    terminal.scroll_display(Scroll::Delta(scroll_lines));
+                });
+            }
+            Act::ScrollToTop => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.scroll_display(Scroll::Top);
+
+                let topmost_line = terminal.grid.topmost_line();
+                terminal.vi_mode_cursor.pos.row = topmost_line;
+                terminal.vi_motion(ViMotion::FirstOccupied);
+                drop(terminal);
+            }
+            Act::ScrollToBottom => {
+                self.scroll_to_bottom();
+            }
+            Act::ScrollToPreviousPrompt => {
+                self.scroll_smoothly(|terminal| {
+                    terminal.scroll_to_prompt(false);
+                });
+            }
+            Act::ScrollToNextPrompt => {
+                self.scroll_smoothly(|terminal| {
+                    terminal.scroll_to_prompt(true);
+                });
+            }
+            Act::Scroll(delta) => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.scroll_display(Scroll::Delta(*delta));
+                drop(terminal);
+            }
+            Act::ClearHistory => {
+                self.clear_history();
+                self.render();
+            }
+            Act::ClearScreen => {
+                self.clear_screen();
+                self.render();
+            }
+            Act::ToggleFullscreen => self.context_manager.toggle_full_screen(),
+            Act::Minimize => {
+                self.context_manager.minimize();
+            }
+            Act::Hide => {
+                self.context_manager.hide();
+            }
+            #[cfg(target_os = "macos")]
+            Act::HideOtherApplications => {
+                self.context_manager.hide_other_apps();
+            }
+            Act::SelectTab(tab_index) => {
+                self.context_manager.select_tab(*tab_index);
+            }
+            Act::SelectLastTab => {
+                self.context_manager.select_last_tab();
+            }
+            Act::SelectNextTab => {
+                self.clear_selection();
+                self.context_manager.switch_to_next();
+                self.render();
+            }
+            Act::SelectPrevTab => {
+                self.clear_selection();
+                self.context_manager.switch_to_prev();
+                self.render();
+            }
+            Act::ReceiveChar | Act::None => (),
+            _ => (),
+        }
+    }
+
+    /// Action bound to `key` in a table of the overlays.
+    fn table_action(
+        &self,
+        table: KeyTable,
+        key: &winit::event::KeyEvent,
+    ) -> Option<&Act> {
+        let logical_key = if matches!(key.logical_key, Key::Character(_)) {
+            key.key_without_modifiers()
+        } else {
+            key.logical_key.clone()
+        };
+        self.bindings.action(
+            table,
+            BindingMode::empty(),
+            self.modifiers.state(),
+            &BindingKey::Keycode {
+                key: logical_key,
+                location: key.location,
+            },
+        )
+    }
+
+    /// Whether `key` is the leader key, with the key at the same place on a
+    /// US layout as `fallback`.
+    fn is_leader(
+        &self,
+        key: &winit::event::KeyEvent,
+        mods: ModifiersState,
+        fallback: Option<&Key>,
+    ) -> bool {
+        let Some(leader) = &self.leader else {
+            return false;
+        };
+
+        let logical_key = if matches!(key.logical_key, Key::Character(_)) {
+            key.key_without_modifiers()
+        } else {
+            key.logical_key.clone()
+        };
+        std::iter::once(logical_key)
+            .chain(fallback.cloned())
+            .any(|logical_key| {
+                leader.is_triggered_by(
+                    mods,
+                    &BindingKey::Keycode {
+                        key: logical_key,
+                        location: key.location,
+                    },
+                )
+            })
+    }
+
+    /// Time left to press a key after the leader, `None` if it wasn't pressed
+    /// or timed out.
+    fn leader_remaining(&self) -> Option<Duration> {
+        let timeout = self.leader.as_ref()?.timeout;
+        timeout.checked_sub(self.leader_pressed?.elapsed())
+    }
+
+    fn set_leader_pending(&mut self, pending: bool) {
+        self.leader_pressed = pending.then(Instant::now);
+        self.state.pending_chord = self
+            .leader
+            .as_ref()
+            .filter(|_| pending)
+            .map(|leader| leader.label.clone());
+        self.render();
+    }
+
+    /// Write typed input to the PTY, the way a key press does.
+    fn send_input(&mut self, bytes: Vec<u8>) {
+        // Write only when we have something to write.
+        if !bytes.is_empty() {
+            self.scroll_bottom_when_cursor_not_visible();
+            self.clear_selection();
+
+            self.ctx_mut().current_mut().messenger.send_bytes(bytes);
+
+            if (self.measure_input_latency || self.stats.is_some())
+                && self.pending_input.is_none()
+            {
+                let parsed_bytes =
+                    self.ctx().current().parsed_bytes.load(Ordering::Relaxed);
+                self.pending_input = Some((Instant::now(), parsed_bytes));
+            }
+        }
+    }
+
+    #[inline]
+    /// Run the mouse bindings triggered by `button`, returns `true` if any was.
+    pub fn process_mouse_bindings(&mut self, button: MouseButton) -> bool {
+        let mode = self.get_mode();
+        let binding_mode = BindingMode::new(&mode);
+        let mouse_mode = self.mouse_mode();
+        let mods = self.modifiers.state();
+        let clicks = match self.mouse.click_state {
+            ClickState::None | ClickState::Click => 1,
+            ClickState::DoubleClick => 2,
+            ClickState::TripleClick => 3,
+        };
+
+        let mut triggered = false;
+        for i in 0..self.mouse_bindings.len() {
+            let mut binding = self.mouse_bindings[i].clone();
+
+            // Require shift for all modifiers when mouse mode is active.
+            if mouse_mode {
+                binding.mods |= ModifiersState::SHIFT;
+            }
+
+            let input = MouseTrigger {
+                button,
+                clicks: binding.trigger.clicks.and(Some(clicks)),
+            };
+            if !binding.is_triggered_by(binding_mode.to_owned(), mods, &input) {
+                continue;
+            }
+
+            triggered = true;
+            match binding.action {
+                Act::Mouse(MouseAction::ExpandSelection) => {
+                    let display_offset = self.display_offset();
+                    let point = self.mouse_position(display_offset);
+                    self.update_selection(point, self.mouse.square_side);
+                }
+                Act::ContextMenu => {
+                    let display_offset = self.display_offset();
+                    let point = self.mouse_position(display_offset);
+                    let scale = self.sugarloaf.layout.scale_factor;
+                    let position =
+                        [self.mouse.x as f32 / scale, self.mouse.y as f32 / scale];
+                    self.open_context_menu(position, point);
+                }
+                ref action => self.run_action(action),
             }
         }
+        triggered
     }
 
     /// Whether we should send `ESC` due to `Alt` being pressed.
@@ -1112,6 +1908,352 @@ impl Screen {
         self.clipboard.set(ty, text);
     }
 
+    /// Store the selection as text with ANSI escape sequences.
+    pub fn copy_selection_as_ansi(&mut self) {
+        let terminal = self.ctx().current().terminal.lock();
+        let text = match terminal.selection_to_ansi().filter(|s| !s.is_empty()) {
+            Some(text) => text,
+            None => return,
+        };
+        drop(terminal);
+
+        self.clipboard.set(ClipboardType::Clipboard, text);
+    }
+
+    /// Store the selection as tab separated values, for spreadsheets.
+    pub fn copy_selection_as_tsv(&mut self) {
+        let terminal = self.ctx().current().terminal.lock();
+        let text = match terminal.selection_to_tsv().filter(|s| !s.is_empty()) {
+            Some(text) => text,
+            None => return,
+        };
+        drop(terminal);
+
+        self.clipboard.set(ClipboardType::Clipboard, text);
+    }
+
+    pub fn select_last_command_output(&mut self) {
+        let mut terminal = self.ctx().current().terminal.lock();
+        let range = match terminal.last_command_output() {
+            Some((start, end)) => {
+                let mut selection =
+                    Selection::new(SelectionType::Lines, start, Side::Left);
+                selection.update(end, Side::Right);
+                let range = selection.to_range(&terminal);
+                terminal.selection = Some(selection);
+                range
+            }
+            None => return,
+        };
+        drop(terminal);
+
+        self.state.set_selection(range);
+    }
+
+    pub fn copy_last_command_output(&mut self) {
+        let terminal = self.ctx().current().terminal.lock();
+        let text = match terminal.last_command_output() {
+            Some((start, end)) => terminal.bounds_to_string(start, end),
+            None => return,
+        };
+        drop(terminal);
+
+        self.clipboard.set(ClipboardType::Clipboard, text);
+    }
+
+    /// Write the output of the most recent command into a temporary file
+    /// and open it with `$PAGER` in a new tab.
+    pub fn page_last_command_output(&mut self) {
+        let terminal = self.ctx().current().terminal.lock();
+        let text = match terminal.last_command_output() {
+            Some((start, end)) => terminal.bounds_to_string(start, end),
+            None => return,
+        };
+        drop(terminal);
+
+        let path = match write_private_file("command-output", "txt", text.as_bytes()) {
+            Ok(path) => path,
+            Err(err) => {
+                log::error!("unable to write command output: {err}");
+                return;
+            }
+        };
+
+        let pager = std::env::var("PAGER").unwrap_or(String::from(DEFAULT_PAGER));
+        let mut args: Vec<String> = pager.split_whitespace().map(String::from).collect();
+        if args.is_empty() {
+            args.push(String::from(DEFAULT_PAGER));
+        }
+        let program = args.remove(0);
+        args.push(path.to_string_lossy().to_string());
+
+        let context = self.context_manager.add_context_with_shell(
+            rio_config::Shell { program, args },
+            (
+                self.sugarloaf.layout.width_u32,
+                self.sugarloaf.layout.height_u32,
+            ),
+            (self.sugarloaf.layout.columns, self.sugarloaf.layout.lines),
+            (
+                &self.state.get_cursor_state_from_ref(),
+                self.state.has_blinking_enabled,
+            ),
+        );
+        let file = context::TemporaryFile(path);
+        if let Some(context) = context {
+            // Removed once the pager exits and the tab is closed.
+            context.temporary_file = Some(file);
+        }
+    }
+
+    pub fn view_last_command_output(&mut self) {
+        let terminal = self.ctx().current().terminal.lock();
+        let output = terminal
+            .last_command_output()
+            .map(|(start, end)| terminal.bounds_to_string(start, end));
+        drop(terminal);
+
+        match output {
+            Some(text) => self.open_pager("Command output", &text),
+            None => {
+                let text = self.text(true);
+                self.open_pager("Scrollback", &text);
+            }
+        }
+    }
+
+    /// Open the scrollback in the built-in pager with the search prompt,
+    /// matching only the lines between `bounds`.
+    fn search_scrollback(&mut self, scope: &str, bounds: Option<(Pos, Pos)>) {
+        let Some((start, end)) = bounds else {
+            return;
+        };
+        let terminal = self.ctx().current().terminal.lock();
+        let lines = terminal.text_line(start.row)..terminal.text_line(end.row) + 1;
+        drop(terminal);
+
+        let text = self.text(true);
+        self.open_pager("Scrollback", &text);
+        if let Some(pager) = &mut self.pager {
+            pager.search_in(scope, lines);
+        }
+    }
+
+    fn open_pager(&mut self, title: &str, text: &str) {
+        let mut pager =
+            router::pager::Pager::new(title, text, self.pager_config.syntax_highlighting);
+        pager.set_height(router::pager::height(&self.sugarloaf));
+        self.pager = Some(pager);
+        self.context_manager.open_pager();
+    }
+
+    fn open_quick_select(&mut self) {
+        let terminal = self.ctx().current().terminal.lock();
+        let bottom = terminal.grid.bottommost_line();
+        let top = std::cmp::max(
+            terminal.grid.topmost_line(),
+            bottom - (QUICK_SELECT_LINES - 1),
+        );
+        let text = terminal.bounds_to_string(
+            Pos::new(top, Column(0)),
+            Pos::new(bottom, terminal.grid.last_column()),
+        );
+        drop(terminal);
+
+        let tokens = router::quick_select::tokens(&text, &self.quick_select_patterns);
+        self.quick_select = Some(router::quick_select::QuickSelect::new(tokens));
+        self.context_manager.open_quick_select();
+    }
+
+    /// Open the context menu at `position` in logical pixels, with the link
+    /// at `pos` when there is one.
+    fn open_context_menu(&mut self, position: [f32; 2], pos: Pos) {
+        use router::context_menu::Item;
+
+        let terminal = self.ctx().current().terminal.lock();
+        let link = if pos.row >= terminal.grid.topmost_line()
+            && pos.row <= terminal.grid.bottommost_line()
+            && pos.col <= terminal.grid.last_column()
+        {
+            match terminal.grid[pos.row][pos.col].hyperlink() {
+                Some(hyperlink) => Some(hyperlink.uri().to_string()),
+                None => {
+                    let line = terminal.bounds_to_string(
+                        Pos::new(pos.row, Column(0)),
+                        Pos::new(pos.row, terminal.grid.last_column()),
+                    );
+                    router::context_menu::link_at(&line, pos.col.0)
+                }
+            }
+        } else {
+            None
+        };
+        drop(terminal);
+
+        let mut items = vec![];
+        if !self.selection_is_empty() {
+            items.push(Item::new("Copy", Act::Copy));
+        }
+        items.push(Item::new("Paste", Act::Paste));
+        if let Some(link) = link {
+            let program = router::context_menu::open_link(link);
+            items.push(Item::new("Open link", Act::Run(program)));
+        }
+        items.push(Item::new("Search scrollback", Act::ViewScrollback));
+        items.push(Item::new("Clear scrollback", Act::ClearHistory));
+
+        self.context_menu = Some(router::context_menu::ContextMenu::new(items, position));
+        self.context_manager.open_context_menu();
+    }
+
+    /// Returns false once the context menu is closed, the chosen action is
+    /// run.
+    pub fn context_menu_key(&mut self, key: &winit::keyboard::Key) -> bool {
+        let input = match &mut self.context_menu {
+            Some(context_menu) => context_menu.key(key),
+            None => return false,
+        };
+        self.context_menu_input(input)
+    }
+
+    /// Choose the item of the context menu under the mouse, returns false
+    /// once the menu is closed.
+    pub fn context_menu_click(&mut self) -> bool {
+        let scale = self.sugarloaf.layout.scale_factor;
+        let (x, y) = (self.mouse.x as f32 / scale, self.mouse.y as f32 / scale);
+        let input = match &mut self.context_menu {
+            Some(context_menu) => context_menu.click(x, y),
+            None => return false,
+        };
+        self.context_menu_input(input)
+    }
+
+    /// Select the item of the context menu under the mouse, returns true if
+    /// it changed.
+    pub fn context_menu_hover(&mut self) -> bool {
+        let scale = self.sugarloaf.layout.scale_factor;
+        let (x, y) = (self.mouse.x as f32 / scale, self.mouse.y as f32 / scale);
+        self.context_menu
+            .as_mut()
+            .is_some_and(|context_menu| context_menu.hover(x, y))
+    }
+
+    fn context_menu_input(&mut self, input: router::context_menu::Input) -> bool {
+        match input {
+            router::context_menu::Input::Pending => true,
+            router::context_menu::Input::Cancelled => {
+                self.context_menu = None;
+                false
+            }
+            router::context_menu::Input::Chosen(action) => {
+                self.context_menu = None;
+                self.run_action(&action);
+                false
+            }
+        }
+    }
+
+    /// Returns false once the quick select is closed, the chosen token is
+    /// pasted.
+    pub fn quick_select_key(&mut self, key: &winit::event::KeyEvent) -> bool {
+        let action = self.table_action(KeyTable::Hints, key).cloned();
+        let selection = match &mut self.quick_select {
+            Some(quick_select) => match action {
+                Some(Act::Hint(HintAction::Choose)) => quick_select.choose(),
+                Some(Act::Hint(HintAction::Cancel)) => {
+                    router::quick_select::Selection::Cancelled
+                }
+                Some(Act::Hint(HintAction::Previous)) => {
+                    quick_select.previous();
+                    router::quick_select::Selection::Pending
+                }
+                Some(Act::Hint(HintAction::Next)) => {
+                    quick_select.next();
+                    router::quick_select::Selection::Pending
+                }
+                // Keys bound to other actions are left out.
+                Some(_) => router::quick_select::Selection::Pending,
+                None => quick_select.key(&key.logical_key),
+            },
+            None => return false,
+        };
+
+        match selection {
+            router::quick_select::Selection::Pending => true,
+            router::quick_select::Selection::Cancelled => {
+                self.quick_select = None;
+                false
+            }
+            router::quick_select::Selection::Chosen(token) => {
+                self.quick_select = None;
+                self.paste(&token, true);
+                false
+            }
+        }
+    }
+
+    /// Returns false once the rename prompt is closed.
+    pub fn rename_tab_key(&mut self, key: &winit::keyboard::Key) -> bool {
+        let input = match &mut self.rename_tab {
+            Some(rename_tab) => rename_tab.key(key),
+            None => return false,
+        };
+
+        match input {
+            router::rename::Input::Pending => true,
+            router::rename::Input::Cancelled => {
+                self.rename_tab = None;
+                false
+            }
+            router::rename::Input::Renamed(name) => {
+                self.rename_tab = None;
+                let index = self.context_manager.current_index();
+                self.context_manager.rename_context(index, name);
+                false
+            }
+        }
+    }
+
+    /// Returns false once the pager is closed.
+    pub fn pager_key(&mut self, key: &winit::event::KeyEvent) -> bool {
+        let searching = self
+            .pager
+            .as_ref()
+            .map_or(false, |pager| pager.is_searching());
+        let action = self
+            .table_action(KeyTable::Search, key)
+            .filter(|_| searching)
+            .cloned();
+        let open = match (&mut self.pager, action) {
+            (Some(pager), Some(Act::Search(SearchAction::Confirm))) => {
+                pager.confirm_search();
+                true
+            }
+            (Some(pager), Some(Act::Search(SearchAction::Cancel))) => {
+                pager.cancel_search();
+                true
+            }
+            (Some(_), Some(_)) => true,
+            (Some(pager), None) => pager.key(&key.logical_key),
+            (None, _) => false,
+        };
+        if !open {
+            self.pager = None;
+        }
+        open
+    }
+
+    pub fn scroll_pager(&mut self, lines: isize) {
+        let lines = if self.scroll_config.natural_scrolling {
+            -lines
+        } else {
+            lines
+        };
+        if let Some(pager) = &mut self.pager {
+            pager.scroll(lines);
+        }
+    }
+
     #[inline]
     pub fn clear_selection(&mut self) {
         // Clear the selection on the terminal.
@@ -1184,7 +2326,6 @@ impl Screen {
     }
 
     // TODO: Exec
-    #[cfg(unix)]
     pub fn exec<I, S>(&self, program: &str, args: I)
     where
         I: IntoIterator<Item = S> + Debug + Copy,
@@ -1201,16 +2342,24 @@ impl Screen {
         //     args: vec!["/c".to_string(), "start".to_string(), "".to_string()],
         // });
 
-        let main_fd = *self.ctx().current().main_fd;
-        let shell_pid = &self.ctx().current().shell_pid;
-        match teletypewriter::spawn_daemon(program, args, main_fd, *shell_pid) {
+        #[cfg(unix)]
+        let spawned = {
+            let main_fd = *self.ctx().current().main_fd;
+            let shell_pid = &self.ctx().current().shell_pid;
+            teletypewriter::spawn_daemon(program, args, main_fd, *shell_pid)
+        };
+        #[cfg(windows)]
+        let spawned = teletypewriter::spawn_daemon(program, args);
+        match spawned {
             Ok(_) => log::debug!("Launched {} with args {:?}", program, args),
             Err(_) => log::warn!("Unable to launch {} with args {:?}", program, args),
         }
     }
 
     #[inline]
-    pub fn update_selection_scrolling(&mut self, mouse_y: f64) {
+    /// Scroll when a selection is dragged over the top or bottom edge, faster
+    /// the further it goes. Returns false when the mouse is not on an edge.
+    pub fn update_selection_scrolling(&mut self, mouse_y: f64) -> bool {
         let scale_factor = self.sugarloaf.layout.scale_factor;
         let min_height = (MIN_SELECTION_SCROLLING_HEIGHT * scale_factor) as i32;
         let step = (SELECTION_SCROLLING_STEP * scale_factor) as f64;
@@ -1231,12 +2380,38 @@ impl Screen {
         } else if mouse_y >= start_bottom {
             start_bottom - mouse_y - step
         } else {
-            return;
+            self.mouse.selection_scrolling = None;
+            return false;
         };
 
+        self.mouse.selection_scrolling = Some(mouse_y);
         let mut terminal = self.ctx().current().terminal.lock();
         terminal.scroll_display(Scroll::Delta((delta / step) as i32));
         drop(terminal);
+        true
+    }
+
+    /// Keep scrolling a selection dragged over an edge while the mouse stays
+    /// there, the selection follows the lines scrolled under the mouse.
+    pub fn continue_selection_scrolling(&mut self) -> bool {
+        let dragging = self.mouse.left_button_state == ElementState::Pressed
+            || self.mouse.right_button_state == ElementState::Pressed;
+        let mouse_y = match self.mouse.selection_scrolling {
+            Some(mouse_y) if dragging && !self.selection_is_empty() => mouse_y,
+            _ => {
+                self.mouse.selection_scrolling = None;
+                return false;
+            }
+        };
+
+        if !self.update_selection_scrolling(mouse_y) {
+            return false;
+        }
+
+        let point = self.mouse_position(self.display_offset());
+        let side = self.mouse.square_side;
+        self.update_selection(point, side);
+        true
     }
 
     #[inline]
@@ -1313,6 +2488,46 @@ impl Screen {
         drop(terminal);
     }
 
+    /// Paste the clipboard content applying the configured transforms.
+    ///
+    /// Text with multiple lines or control characters that would be sent
+    /// to a shell prompt is held until the user confirms it.
+    pub fn paste_from_clipboard(&mut self, ty: ClipboardType) {
+        let mut content = self.clipboard.get(ty);
+
+        if self.paste_config.convert_crlf {
+            content = content.replace("\r\n", "\n");
+        }
+
+        if self.paste_config.trim_trailing_newline {
+            let trimmed_len = content.trim_end_matches(['\r', '\n']).len();
+            content.truncate(trimmed_len);
+        }
+
+        if self.paste_config.confirm_unsafe && is_unsafe_paste(&content) {
+            let is_at_prompt = self.ctx().current().terminal.lock().is_at_prompt();
+            if is_at_prompt {
+                self.pending_paste = Some(content);
+                self.context_manager.request_paste_confirmation();
+                return;
+            }
+        }
+
+        self.paste(&content, true);
+    }
+
+    #[inline]
+    pub fn confirm_paste(&mut self) {
+        if let Some(content) = self.pending_paste.take() {
+            self.paste(&content, true);
+        }
+    }
+
+    #[inline]
+    pub fn cancel_paste(&mut self) {
+        self.pending_paste = None;
+    }
+
     #[inline]
     pub fn paste(&mut self, text: &str, bracketed: bool) {
         if bracketed && self.get_mode().contains(Mode::BRACKETED_PASTE) {
@@ -1344,6 +2559,124 @@ impl Screen {
         }
     }
 
+    /// Text of the visible lines, or of the whole history with `scrollback`.
+    pub fn text(&self, scrollback: bool) -> String {
+        let terminal = self.ctx().current().terminal.lock();
+        let (start, end) = if scrollback {
+            (
+                terminal.grid.topmost_line(),
+                terminal.grid.bottommost_line(),
+            )
+        } else {
+            let display_offset = terminal.grid.display_offset() as i32;
+            (
+                Line(-display_offset),
+                Line(terminal.grid.screen_lines() as i32 - 1 - display_offset),
+            )
+        };
+        terminal.bounds_to_string(
+            Pos::new(start, Column(0)),
+            Pos::new(end, terminal.grid.last_column()),
+        )
+    }
+
+    /// Override terminal colors the same way OSC 10, 11 and 12 do.
+    pub fn set_colors(&self, colors: &[(usize, ColorRgb)]) {
+        let mut terminal = self.ctx().current().terminal.lock();
+        for (index, color) in colors {
+            terminal.set_color(*index, *color);
+        }
+    }
+
+    /// Follow a tmux control mode session started in the current tab.
+    pub fn tmux_control(&mut self, control: tmux::Control) {
+        let actions = match (control, &mut self.tmux) {
+            (tmux::Control::Start, None) => {
+                let (client, actions) = tmux::Client::start(
+                    self.sugarloaf.layout.columns,
+                    self.sugarloaf.layout.lines,
+                );
+                self.tmux = Some(tmux::Session {
+                    client,
+                    gateway: Messenger::new(self.ctx().current().messenger.channel()),
+                    parsers: HashMap::new(),
+                });
+                actions
+            }
+            (tmux::Control::Line(line), Some(session)) => {
+                session.client.handle_line(&line)
+            }
+            (tmux::Control::End, Some(session)) => session.client.exit(),
+            _ => return,
+        };
+
+        for action in actions {
+            self.run_tmux_action(action);
+        }
+    }
+
+    fn run_tmux_action(&mut self, action: tmux::Action) {
+        let session = match &mut self.tmux {
+            Some(session) => session,
+            None => return,
+        };
+
+        match action {
+            tmux::Action::Command(command) => {
+                session.gateway.send_write(command.into_bytes());
+            }
+            tmux::Action::OpenPane {
+                pane,
+                columns,
+                lines,
+            } => {
+                let gateway = session.gateway.channel();
+                let cursor_state = self.state.get_cursor_state_from_ref();
+                self.context_manager.add_tmux_context(
+                    pane,
+                    gateway,
+                    (columns, lines),
+                    (&cursor_state, self.state.has_blinking_enabled),
+                );
+            }
+            tmux::Action::ResizePane {
+                pane,
+                columns,
+                lines,
+            } => {
+                if let Some(context) = self.context_manager.tmux_context(pane) {
+                    context
+                        .terminal
+                        .lock()
+                        .resize::<SugarloafLayout>(columns, lines);
+                }
+            }
+            tmux::Action::ClosePane(pane) => {
+                session.parsers.remove(&pane);
+                self.context_manager.close_tmux_context(pane);
+            }
+            tmux::Action::SelectPane(pane) => {
+                self.context_manager.select_tmux_context(pane);
+            }
+            tmux::Action::Rename { pane, title } => {
+                if let Some(context) = self.context_manager.tmux_context_mut(pane) {
+                    context.title = Some(title);
+                }
+            }
+            tmux::Action::Output { pane, data } => {
+                if let Some(context) = self.context_manager.tmux_context(pane) {
+                    let parser = session.parsers.entry(pane).or_default();
+                    let mut terminal = context.terminal.lock();
+                    for byte in data {
+                        parser.advance(&mut *terminal, byte);
+                    }
+                    terminal.publish_mode();
+                }
+            }
+            tmux::Action::Exit => self.tmux = None,
+        }
+    }
+
     #[inline]
     pub fn init(
         &mut self,
@@ -1383,39 +2716,499 @@ impl Screen {
         self.sugarloaf.render();
     }
 
+    #[inline]
+    pub fn render_paste_confirmation(&mut self) {
+        let content = self.pending_paste.to_owned().unwrap_or_default();
+        crate::router::paste::screen(&mut self.sugarloaf, &content);
+        self.sugarloaf.render();
+    }
+
+    #[inline]
+    pub fn render_pager(&mut self) {
+        if let Some(pager) = &mut self.pager {
+            pager.set_height(router::pager::height(&self.sugarloaf));
+            router::pager::screen(&mut self.sugarloaf, pager, &self.state.named_colors);
+        }
+        self.sugarloaf.render();
+    }
+
+    #[inline]
+    pub fn render_quick_select(&mut self) {
+        if let Some(quick_select) = &self.quick_select {
+            router::quick_select::screen(
+                &mut self.sugarloaf,
+                quick_select,
+                &self.state.named_colors,
+            );
+        }
+        self.sugarloaf.render();
+    }
+
+    #[inline]
+    pub fn render_rename_tab(&mut self) {
+        if let Some(rename_tab) = &self.rename_tab {
+            router::rename::screen(
+                &mut self.sugarloaf,
+                rename_tab,
+                &self.state.named_colors,
+            );
+        }
+        self.sugarloaf.render();
+    }
+
+    #[inline]
+    pub fn render_glyph_atlas(&mut self) {
+        router::atlas::screen(
+            &mut self.sugarloaf,
+            &self.state.named_colors,
+            &self.memory,
+        );
+        self.sugarloaf.render();
+    }
+
     #[inline]
     pub fn render_welcome(&mut self) {
         crate::router::welcome::screen(&mut self.sugarloaf);
         self.sugarloaf.render();
     }
 
-    #[inline]
-    pub fn render(&mut self) {
-        let mut terminal = self.ctx().current().terminal.lock();
-        let visible_rows = terminal.visible_rows();
-        let cursor = terminal.cursor();
-        let display_offset = terminal.display_offset();
-        let terminal_has_blinking_enabled = terminal.blinking_cursor;
-        drop(terminal);
+    /// Queue the current terminal content into sugarloaf, returns whether the
+    /// terminal has blinking enabled or `None` when the frame was skipped.
+    fn prepare_render(&mut self, wait: bool) -> Option<bool> {
+        if wait {
+            self.sugarloaf.complete_next_frame();
+        }
+        let context = self.ctx().current();
+        let mut selection_dropped = false;
+        let mut damage = None;
+        let (snapshot, prompt_marks) = match context.terminal.try_lock_unfair() {
+            Some(mut terminal) => {
+                let snapshot = terminal.publish_snapshot();
+                // Marks of the prompts in the scrollback, only shown while
+                // scrolled.
+                let prompt_marks = if snapshot.display_offset > 0 {
+                    let history_size = terminal.grid.history_size();
+                    terminal
+                        .prompt_lines()
+                        .into_iter()
+                        .map(|line| {
+                            let position = (line.0 + history_size as i32) as f32
+                                / (history_size + terminal.grid.screen_lines()) as f32;
+                            (position, line)
+                        })
+                        .collect()
+                } else {
+                    vec![]
+                };
+                // Clearing the screen or the scrollback drops the selection.
+                selection_dropped = terminal.selection.is_none();
+                // Damage of frames drawn from a published snapshot adds up
+                // until the terminal is locked again.
+                if self.show_damage {
+                    damage = Some(terminal.take_damage());
+                }
+                (snapshot, Some(prompt_marks))
+            }
+            // The PTY reader is parsing, draw the last batch it published.
+            None if !wait => match context.snapshot.load() {
+                Some(snapshot) => (snapshot, None),
+                // Nothing published yet, it will request a redraw once done
+                // unless it is holding the terminal for too long.
+                None if self.last_render.elapsed() < MAX_RENDER_SKIP => return None,
+                None => (context.terminal.lock().publish_snapshot(), None),
+            },
+            None => (context.terminal.lock().publish_snapshot(), None),
+        };
+        let terminal_has_blinking_enabled = snapshot.blinking_cursor;
+        // The first frame with output parsed after the input shows its echo.
+        if let Some((written_at, parsed_bytes)) = self.pending_input {
+            if snapshot.parsed_bytes > parsed_bytes {
+                self.pending_input = None;
+                self.input_frame = Some(written_at);
+            } else if written_at.elapsed() > INPUT_ECHO_TIMEOUT {
+                self.pending_input = None;
+            }
+        }
+        self.accessibility
+            .update(&snapshot.rows, snapshot.cursor.pos);
+        if self.has_remote_profile {
+            let remote = self.context_manager.is_remote() || snapshot.remote_host;
+            if remote != self.remote {
+                self.remote = remote;
+                self.context_manager.report_remote_session(remote);
+            }
+        }
+        let palette = if self.remote {
+            self.remote_palette
+                .or(&self.context_manager.current().palette)
+        } else {
+            self.context_manager.current().palette
+        };
+        let background_changed = self.state.set_term_colors(snapshot.colors.or(&palette));
+        if self.state.set_reverse_video(snapshot.reverse_video) || background_changed {
+            let background = self.state.window_background();
+            self.sugarloaf.set_background_color(background);
+        }
+        self.last_render = Instant::now();
         self.context_manager.update_titles();
 
         self.state.set_ime(self.ime.preedit());
+        self.state.new_lines = snapshot.new_lines;
+        if let Some(prompt_marks) = prompt_marks {
+            self.state.prompt_marks = prompt_marks;
+        }
+        if selection_dropped {
+            self.state.set_selection(None);
+        }
+        if let Some(damage) = damage {
+            self.state.flash_damage(damage);
+        }
 
         self.state.prepare_term(
-            visible_rows,
-            cursor,
+            &snapshot.rows,
+            snapshot.cursor.clone(),
             &mut self.sugarloaf,
             &self.context_manager,
-            display_offset as i32,
+            snapshot.display_offset as i32,
             terminal_has_blinking_enabled,
         );
 
+        Some(terminal_has_blinking_enabled)
+    }
+
+    /// Render the terminal into an offscreen texture and save it as a PNG.
+    pub fn save_screenshot(&mut self, path: &str) -> Result<(), String> {
+        self.prepare_render(true);
+        let frame = self
+            .sugarloaf
+            .render_to_image()
+            .ok_or_else(|| String::from("unable to read back the frame"))?;
+
+        image::save_buffer(
+            path,
+            &frame.pixels,
+            frame.width,
+            frame.height,
+            image::ColorType::Rgba8,
+        )
+        .map_err(|err| err.to_string())
+    }
+
+    /// Pixel region of the selection, or of the terminal grid when padding
+    /// is excluded, `None` captures the whole frame.
+    fn screenshot_region(&self, display_offset: usize) -> Option<screenshot::Region> {
+        let layout = &self.sugarloaf.layout;
+        let last_line = layout.lines.saturating_sub(1) as i32;
+        let last_column = layout.columns.saturating_sub(1);
+
+        let (lines, columns) = match self.state.selection_range {
+            Some(range) => {
+                let start =
+                    (range.start.row.0 + display_offset as i32).clamp(0, last_line);
+                let end = (range.end.row.0 + display_offset as i32).clamp(0, last_line);
+                let columns = if range.is_block || start == end {
+                    let first = min(range.start.col.0, range.end.col.0);
+                    let last = max(range.start.col.0, range.end.col.0);
+                    (first.min(last_column), last.min(last_column))
+                } else {
+                    (0, last_column)
+                };
+                ((start as usize, end as usize), columns)
+            }
+            None if !self.screenshot_config.include_padding => {
+                ((0, last_line as usize), (0, last_column))
+            }
+            None => return None,
+        };
+
+        let left = layout.margin.x * layout.scale_factor;
+        let top = layout.margin.top_y * 2. * layout.scale_factor;
+        let (cell_width, cell_height) =
+            (layout.scaled_sugarwidth, layout.scaled_sugarheight);
+
+        Some(screenshot::Region {
+            x: (left + columns.0 as f32 * cell_width) as u32,
+            y: (top + lines.0 as f32 * cell_height) as u32,
+            width: ((columns.1 - columns.0 + 1) as f32 * cell_width).ceil() as u32,
+            height: ((lines.1 - lines.0 + 1) as f32 * cell_height).ceil() as u32,
+        })
+    }
+
+    /// Capture the current frame, or the selected region, into the clipboard
+    /// or a PNG file.
+    pub fn take_screenshot(&mut self, to_clipboard: bool) {
+        let display_offset = self.ctx().current().terminal.lock().display_offset();
+        self.prepare_render(true);
+        let mut frame = match self.sugarloaf.render_to_image() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        if let Some(region) = self.screenshot_region(display_offset) {
+            frame = screenshot::crop(&frame, region);
+        }
+
+        let result = screenshot::encode_png(&frame).and_then(|png| {
+            if to_clipboard {
+                screenshot::copy_png_to_clipboard(png);
+                Ok(())
+            } else {
+                let path =
+                    screenshot::new_path(self.screenshot_config.directory.as_deref());
+                log::info!("saving screenshot to {}", path.display());
+                std::fs::write(&path, png).map_err(|err| err.to_string())
+            }
+        });
+
+        if let Err(err) = result {
+            log::error!("unable to take screenshot: {err}");
+        }
+    }
+
+    /// Start the `kind` monitor on the current tab, or stop it when it is
+    /// already running.
+    fn toggle_monitor(&mut self, kind: MonitorKind) {
+        let monitor = if self.context_manager.current().monitor == Some(kind) {
+            None
+        } else {
+            Some(Monitor::new(
+                kind,
+                Duration::from_secs(self.monitor_config.interval),
+            ))
+        };
+        self.context_manager.set_monitor(monitor);
+    }
+
+    /// A monitor fired, returns whether the user attention is requested.
+    pub fn monitor_alert(&mut self) -> bool {
+        self.context_manager.refresh_titles();
+        self.monitor_config.notify
+    }
+
+    /// Progress to show on the taskbar when it changed since it was last
+    /// shown, after the current tab changed or a tab reported one.
+    pub fn taskbar_progress(&mut self) -> Option<Progress> {
+        let progress = self.context_manager.window_progress();
+        if progress == self.taskbar_progress {
+            return None;
+        }
+        self.taskbar_progress = progress;
+        Some(progress)
+    }
+
+    /// Show the stats overlay, or hide it when shown.
+    pub fn toggle_stats(&mut self) {
+        self.stats = match self.stats {
+            Some(_) => None,
+            None => Some(router::stats::Stats::new(
+                self.context_manager.parsed_bytes(),
+            )),
+        };
+    }
+
+    pub fn toggle_recording(&mut self) {
+        let columns = self.sugarloaf.layout.columns;
+        let lines = self.sugarloaf.layout.lines;
+        let context = self.context_manager.current_mut();
+        if context.recording {
+            context.messenger.stop_recording();
+            context.recording = false;
+            return;
+        }
+
+        let path = recorder::new_path();
+        match Recorder::create(&path, columns, lines) {
+            Ok(recorder) => {
+                log::info!("recording session to {}", path.display());
+                context.messenger.start_recording(recorder);
+                context.recording = true;
+            }
+            Err(err) => log::error!("unable to start recording: {err}"),
+        }
+    }
+
+    #[inline]
+    /// Move the viewport with `scroll`, sliding it to its new place when
+    /// `animation-duration` is set and motion is not reduced.
+    fn scroll_smoothly<F>(&mut self, scroll: F)
+    where
+        F: FnOnce(&mut Crosswords<EventProxy>),
+    {
+        let tab = self.context_manager.current_index();
+        let mut terminal = self.context_manager.current_mut().terminal.lock();
+        // A slide going on ends where it was heading.
+        if let Some((previous_tab, previous)) = self.smooth_scroll.take() {
+            let offset = terminal.grid.display_offset();
+            if previous_tab == tab && offset == previous.offset {
+                terminal
+                    .scroll_display(Scroll::Delta(previous.to as i32 - offset as i32));
+            }
+            self.sugarloaf.set_scroll_offset(0.);
+        }
+
+        let from = terminal.grid.display_offset();
+        scroll(&mut terminal);
+        let to = terminal.grid.display_offset();
+        let duration = Duration::from_millis(self.scroll_config.animation_duration);
+        if from == to || duration.is_zero() || !self.state.animations.smooth_scroll {
+            return;
+        }
+
+        terminal.scroll_display(Scroll::Delta(from as i32 - to as i32));
+        drop(terminal);
+        self.smooth_scroll = Some((
+            tab,
+            smooth_scroll::SmoothScroll::new(
+                from,
+                to,
+                duration,
+                self.scroll_config.animation_easing,
+            ),
+        ));
+    }
+
+    /// Move the sliding viewport to its place for this frame, returns false
+    /// once it stopped.
+    fn advance_smooth_scroll(&mut self) -> bool {
+        let Some((tab, animation)) = &mut self.smooth_scroll else {
+            return false;
+        };
+
+        let now = Instant::now();
+        let same_tab = *tab == self.context_manager.current_index();
+        let mut terminal = self.context_manager.current_mut().terminal.lock();
+        let current = terminal.grid.display_offset();
+        // Scrolled by the mouse, new output or another tab.
+        if !same_tab || current != animation.offset {
+            drop(terminal);
+            self.smooth_scroll = None;
+            self.sugarloaf.set_scroll_offset(0.);
+            return false;
+        }
+
+        let (offset, fraction) = animation.frame(now);
+        if offset != current {
+            terminal.scroll_display(Scroll::Delta(offset as i32 - current as i32));
+        }
+        animation.offset = terminal.grid.display_offset();
+        drop(terminal);
+
+        if animation.is_done(now) {
+            self.smooth_scroll = None;
+            self.sugarloaf.set_scroll_offset(0.);
+            return false;
+        }
+        self.sugarloaf.set_scroll_offset(fraction);
+        true
+    }
+
+    pub fn render(&mut self) {
+        if self.leader_pressed.is_some() && self.leader_remaining().is_none() {
+            self.leader_pressed = None;
+            self.state.pending_chord = None;
+        }
+        let scrolling = self.advance_smooth_scroll();
+
+        let terminal_has_blinking_enabled = match self.prepare_render(false) {
+            Some(terminal_has_blinking_enabled) => terminal_has_blinking_enabled,
+            None => {
+                if let Some(stats) = &mut self.stats {
+                    stats.drop_frame();
+                }
+                return;
+            }
+        };
+
+        if let Some(context_menu) = &mut self.context_menu {
+            router::context_menu::screen(
+                &mut self.sugarloaf,
+                context_menu,
+                &self.state.named_colors,
+            );
+        }
+
+        if let Some(stats) = &mut self.stats {
+            stats.frame(Instant::now(), self.context_manager.parsed_bytes());
+            router::stats::screen(&mut self.sugarloaf, stats, &self.state.named_colors);
+        }
+
         self.sugarloaf.render();
 
+        if let Some(written_at) = self.input_frame.take() {
+            let presented = written_at.elapsed();
+            if let Some(stats) = &mut self.stats {
+                stats.input_latency(presented);
+            }
+            // Wait for the GPU to finish the frame, only done while measuring.
+            if self.measure_input_latency {
+                match self.sugarloaf.last_frame_gpu_time() {
+                    Some(gpu_time) => log::info!(
+                        "input latency: {:.2}ms to present, the frame took {:.2}ms on the GPU",
+                        presented.as_secs_f64() * 1000.0,
+                        gpu_time.as_secs_f64() * 1000.0
+                    ),
+                    None => {
+                        self.sugarloaf.ctx.device.poll(wgpu::Maintain::Wait);
+                        log::info!(
+                            "input latency: {:.2}ms to present, {:.2}ms until the GPU finished",
+                            presented.as_secs_f64() * 1000.0,
+                            written_at.elapsed().as_secs_f64() * 1000.0
+                        );
+                    }
+                }
+            }
+        }
+
+        // Glyphs rasterized in the background and lines left over by a frame
+        // out of time show up in the next frames.
+        if self.sugarloaf.has_pending_glyphs() || self.sugarloaf.has_pending_stacks() {
+            self.context_manager.schedule_render(PENDING_FRAME_INTERVAL);
+            return;
+        }
+
+        if scrolling {
+            self.context_manager.schedule_render(SMOOTH_SCROLL_INTERVAL);
+            return;
+        }
+
+        // Keep the visual bell and the damage tint animating, blinking
+        // resumes once they are over.
+        if let Some(next_frame) = self
+            .state
+            .next_bell_frame()
+            .or_else(|| self.state.next_damage_frame())
+        {
+            self.context_manager
+                .schedule_render(next_frame.as_millis() as u64);
+            return;
+        }
+
         // In this case the configuration of blinking cursor is enabled
         // and the terminal also have instructions of blinking enabled
         if self.state.has_blinking_enabled && terminal_has_blinking_enabled {
-            self.context_manager.schedule_render(800);
+            let interval = if self.low_power {
+                LOW_POWER_BLINK_INTERVAL
+            } else {
+                BLINK_INTERVAL
+            };
+            self.context_manager.schedule_render(interval);
+        }
+
+        if let Some(next_blink) = self.state.next_text_blink() {
+            self.context_manager
+                .schedule_render(next_blink.as_millis() as u64);
+        }
+
+        // Hide the pending chord once the leader times out.
+        if let Some(remaining) = self.leader_remaining() {
+            self.context_manager
+                .schedule_render(remaining.as_millis() as u64 + 1);
+        }
+
+        // Figures of the stats are updated every second.
+        if let Some(stats) = &self.stats {
+            self.context_manager
+                .schedule_render(stats.next_update().as_millis() as u64 + 1);
         }
     }
 
@@ -1515,12 +3308,46 @@ impl Screen {
         self.ctx_mut().current_mut().messenger.send_bytes(msg);
     }
 
+    /// Whether the foreground program is listed in `disabled-programs` of the
+    /// alternate scroll.
+    fn is_alternate_scroll_disabled(&self) -> bool {
+        if self.alternate_scroll.disabled_programs.is_empty() {
+            return false;
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let context = self.ctx().current();
+            let program = teletypewriter::foreground_process_name(
+                *context.main_fd,
+                context.shell_pid,
+            );
+            let program = program.trim();
+            self.alternate_scroll
+                .disabled_programs
+                .iter()
+                .any(|disabled| disabled == program)
+        }
+
+        #[cfg(target_os = "windows")]
+        false
+    }
+
+    /// Scroll by a delta in physical pixels, `precise` for the deltas of
+    /// trackpads and high-resolution wheels.
     #[inline]
-    pub fn scroll(&mut self, new_scroll_x_px: f64, new_scroll_y_px: f64) {
+    pub fn scroll(&mut self, new_scroll_x_px: f64, new_scroll_y_px: f64, precise: bool) {
         let width = self.sugarloaf.layout.width as f64;
-        let height = self.sugarloaf.layout.height as f64;
+        let line_px =
+            (self.sugarloaf.layout.font_size * self.sugarloaf.layout.scale_factor) as f64;
         let mode = self.get_mode();
 
+        let (new_scroll_x_px, new_scroll_y_px) = if self.scroll_config.natural_scrolling {
+            (-new_scroll_x_px, -new_scroll_y_px)
+        } else {
+            (new_scroll_x_px, new_scroll_y_px)
+        };
+
         const MOUSE_WHEEL_UP: u8 = 64;
         const MOUSE_WHEEL_DOWN: u8 = 65;
         const MOUSE_WHEEL_LEFT: u8 = 66;
@@ -1530,21 +3357,18 @@ impl Screen {
             self.mouse.accumulated_scroll.x += new_scroll_x_px;
             self.mouse.accumulated_scroll.y += new_scroll_y_px;
 
-            let code = if new_scroll_y_px > 0. {
+            let code = if self.mouse.accumulated_scroll.y > 0. {
                 MOUSE_WHEEL_UP
             } else {
                 MOUSE_WHEEL_DOWN
             };
-            let lines = (self.mouse.accumulated_scroll.y
-                / (self.sugarloaf.layout.font_size * self.sugarloaf.layout.scale_factor)
-                    as f64)
-                .abs() as usize;
+            let lines = (self.mouse.accumulated_scroll.y / line_px).abs() as usize;
 
             for _ in 0..lines {
                 self.mouse_report(code, ElementState::Pressed);
             }
 
-            let code = if new_scroll_x_px > 0. {
+            let code = if self.mouse.accumulated_scroll.x > 0. {
                 MOUSE_WHEEL_LEFT
             } else {
                 MOUSE_WHEEL_RIGHT
@@ -1557,17 +3381,27 @@ impl Screen {
         } else if mode.contains(Mode::ALT_SCREEN | Mode::ALTERNATE_SCROLL)
             && !self.modifiers.state().shift_key()
         {
+            if self.is_alternate_scroll_disabled() {
+                return;
+            }
+
             self.mouse.accumulated_scroll.x += new_scroll_x_px;
             self.mouse.accumulated_scroll.y += new_scroll_y_px;
 
             // // The chars here are the same as for the respective arrow keys.
-            let line_cmd = if new_scroll_y_px > 0. { b'A' } else { b'B' };
-            let column_cmd = if new_scroll_x_px > 0. { b'D' } else { b'C' };
+            let line_cmd = if self.mouse.accumulated_scroll.y > 0. {
+                b'A'
+            } else {
+                b'B'
+            };
+            let column_cmd = if self.mouse.accumulated_scroll.x > 0. {
+                b'D'
+            } else {
+                b'C'
+            };
 
-            let lines = (self.mouse.accumulated_scroll.y
-                / (self.sugarloaf.layout.font_size * self.sugarloaf.layout.scale_factor)
-                    as f64)
-                .abs() as usize;
+            let lines = (self.mouse.accumulated_scroll.y / line_px).abs() as usize
+                * self.alternate_scroll.arrows;
             let columns = (self.mouse.accumulated_scroll.x / width).abs() as usize;
 
             let mut content = Vec::with_capacity(3 * (lines + columns));
@@ -1588,9 +3422,13 @@ impl Screen {
                 self.ctx_mut().current_mut().messenger.send_bytes(content);
             }
         } else {
-            self.mouse.accumulated_scroll.y += new_scroll_y_px * self.mouse.multiplier;
-            let lines = (self.mouse.accumulated_scroll.y
-                / self.sugarloaf.layout.font_size as f64) as i32;
+            let multiplier = if precise {
+                self.scroll_config.precision_multiplier
+            } else {
+                self.scroll_config.multiplier
+            };
+            self.mouse.accumulated_scroll.y += new_scroll_y_px * multiplier;
+            let lines = (self.mouse.accumulated_scroll.y / line_px) as i32;
 
             if lines != 0 {
                 let mut terminal = self.ctx().current().terminal.lock();
@@ -1599,7 +3437,136 @@ impl Screen {
             }
         }
 
+        // Only whole lines and columns are scrolled, the rest is kept for the
+        // next deltas so small ones add up without scrolling too far.
         self.mouse.accumulated_scroll.x %= width;
-        self.mouse.accumulated_scroll.y %= height;
+        self.mouse.accumulated_scroll.y %= line_px;
+    }
+}
+
+/// Scale factor set for the X11 session when winit reports 1.0, HiDPI screens
+/// without a scale winit can find are drawn tiny otherwise.
+#[cfg(not(any(target_os = "macos", windows)))]
+fn system_scale_factor(
+    display: &winit::window::raw_window_handle::RawDisplayHandle,
+    reported_scale: f64,
+) -> Option<f64> {
+    use winit::window::raw_window_handle::RawDisplayHandle;
+
+    match display {
+        RawDisplayHandle::Xlib(_) | RawDisplayHandle::Xcb(_) if reported_scale == 1.0 => {
+            dpi::x11_scale_factor()
+        }
+        _ => None,
     }
 }
+
+#[cfg(any(target_os = "macos", windows))]
+fn system_scale_factor(
+    _display: &winit::window::raw_window_handle::RawDisplayHandle,
+    _reported_scale: f64,
+) -> Option<f64> {
+    None
+}
+
+#[inline]
+fn color_filter(filter: rio_config::ColorFilter) -> sugarloaf::context::ColorFilter {
+    match filter {
+        rio_config::ColorFilter::None => sugarloaf::context::ColorFilter::None,
+        rio_config::ColorFilter::Protanopia => {
+            sugarloaf::context::ColorFilter::Protanopia
+        }
+        rio_config::ColorFilter::Deuteranopia => {
+            sugarloaf::context::ColorFilter::Deuteranopia
+        }
+        rio_config::ColorFilter::Tritanopia => {
+            sugarloaf::context::ColorFilter::Tritanopia
+        }
+        rio_config::ColorFilter::HighContrast => {
+            sugarloaf::context::ColorFilter::HighContrast
+        }
+    }
+}
+
+/// Output log of the new tabs, the profile Rio was started with can turn it
+/// on for them.
+fn output_log_config(config: &rio_config::Config) -> rio_config::OutputLog {
+    let profile = config
+        .profile
+        .as_ref()
+        .and_then(|name| config.find_profile(name));
+    rio_config::OutputLog {
+        enabled: config.output_log.enabled
+            || profile.is_some_and(|profile| profile.output_log),
+        ..config.output_log.to_owned()
+    }
+}
+
+/// Time the frames on the GPU while measuring the input latency.
+fn set_frame_timing(sugarloaf: &mut Sugarloaf, enabled: bool) {
+    if !sugarloaf.set_frame_timing(enabled) && enabled {
+        log::info!("no GPU timestamps, the input latency is measured on the CPU");
+    }
+}
+
+/// Colors and shell integration the remote profile switches the window to.
+fn remote_profile(config: &rio_config::Config) -> (TermColors, Option<bool>) {
+    match config.find_remote_profile() {
+        Ok(Some(profile)) => (
+            profile
+                .colors
+                .as_ref()
+                .map(TermColors::from_theme)
+                .unwrap_or_default(),
+            profile.shell_integration,
+        ),
+        Ok(None) => (TermColors::default(), None),
+        Err(err) => {
+            log::warn!("unable to load the remote profile: {err}");
+            (TermColors::default(), None)
+        }
+    }
+}
+
+/// Write `contents` to a new file of the runtime directory only the user can
+/// read, named after `name`.
+fn write_private_file(
+    name: &str,
+    extension: &str,
+    contents: &[u8],
+) -> std::io::Result<std::path::PathBuf> {
+    use std::io::Write;
+    #[cfg(unix)]
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let directory = rio_config::paths::runtime_dir();
+    rio_config::paths::create_private_dir(&directory)?;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    // Taken by another pager, or left by a previous instance with the pid.
+    let mut index = 0;
+    loop {
+        let path =
+            directory.join(format!("{name}-{}-{index}.{extension}", std::process::id()));
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(contents)?;
+                return Ok(path);
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => index += 1,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Whether the text has multiple lines or control characters that
+/// could run commands once pasted in a shell prompt.
+#[inline]
+fn is_unsafe_paste(text: &str) -> bool {
+    text.chars()
+        .any(|c| c == '\n' || c == '\r' || (c.is_control() && c != '\t'))
+}
@@ -18,7 +18,6 @@ pub struct AccumulatedScroll {
 
 #[derive(Debug)]
 pub struct Mouse {
-    pub multiplier: f64,
     pub left_button_state: ElementState,
     pub middle_button_state: ElementState,
     pub right_button_state: ElementState,
@@ -29,6 +28,9 @@ pub struct Mouse {
     pub square_side: Side,
     pub lines_scrolled: f32,
     pub inside_text_area: bool,
+    /// Vertical position of a selection drag past the top or bottom edge,
+    /// scrolled on a timer until it comes back.
+    pub selection_scrolling: Option<f64>,
     pub x: usize,
     pub y: usize,
 }
@@ -36,7 +38,6 @@ pub struct Mouse {
 impl Default for Mouse {
     fn default() -> Mouse {
         Mouse {
-            multiplier: 3.0,
             last_click_timestamp: Instant::now(),
             last_click_button: MouseButton::Left,
             left_button_state: ElementState::Released,
@@ -45,6 +46,7 @@ impl Default for Mouse {
             click_state: ClickState::None,
             square_side: Side::Left,
             inside_text_area: Default::default(),
+            selection_scrolling: None,
             lines_scrolled: Default::default(),
             accumulated_scroll: AccumulatedScroll::default(),
             x: Default::default(),
@@ -1,6 +1,8 @@
+use crate::ansi::{Progress, ProgressState};
 use crate::screen::constants::*;
-use rio_config::navigation::NavigationMode;
-use std::collections::HashMap;
+use crate::screen::context::ContextManagerTitles;
+use rio_config::navigation::{format_tab_title, Navigation, NavigationMode};
+use std::collections::{HashMap, HashSet};
 use sugarloaf::components::rect::Rect;
 use sugarloaf::font::FONT_ID_BUILTIN;
 
@@ -35,6 +37,19 @@ pub struct ScreenNavigationColors {
     foreground: [f32; 4],
     active: [f32; 4],
     inactive: [f32; 4],
+    tab_foreground: [f32; 4],
+    tab_active_foreground: [f32; 4],
+    progress: [f32; 4],
+    progress_error: [f32; 4],
+    progress_paused: [f32; 4],
+}
+
+/// Tab bar options of the TopTab and BottomTab modes.
+struct TabBar {
+    hide_if_single: bool,
+    equal_width: bool,
+    min_width: f32,
+    title_format: String,
 }
 
 pub struct ScreenNavigation {
@@ -43,17 +58,19 @@ pub struct ScreenNavigation {
     pub texts: Vec<Text>,
     keys: String,
     current: usize,
+    progress: HashMap<usize, Progress>,
     colors: ScreenNavigationColors,
     width: f32,
     height: f32,
     scale: f32,
     color_automation: HashMap<String, [f32; 4]>,
+    tab_bar: TabBar,
 }
 
 impl ScreenNavigation {
     pub fn new(
-        mode: NavigationMode,
-        colors: [[f32; 4]; 3],
+        navigation: &Navigation,
+        colors: [[f32; 4]; 8],
         color_automation: HashMap<String, [f32; 4]>,
         width: f32,
         height: f32,
@@ -64,20 +81,32 @@ impl ScreenNavigation {
                 inactive: colors[0],
                 active: colors[1],
                 foreground: colors[2],
+                tab_foreground: colors[3],
+                tab_active_foreground: colors[4],
+                progress: colors[5],
+                progress_error: colors[6],
+                progress_paused: colors[7],
             }
         };
 
         ScreenNavigation {
-            mode,
+            mode: navigation.mode,
             rects: vec![],
             texts: vec![],
             keys: String::from(""),
             color_automation,
             current: 0,
+            progress: HashMap::new(),
             colors,
             width,
             height,
             scale,
+            tab_bar: TabBar {
+                hide_if_single: navigation.hide_if_single,
+                equal_width: navigation.equal_tab_width,
+                min_width: navigation.min_tab_width,
+                title_format: navigation.title_format.to_owned(),
+            },
         }
     }
 
@@ -86,8 +115,7 @@ impl ScreenNavigation {
         &mut self,
         dimensions: (f32, f32),
         scale: f32,
-        keys: &str,
-        titles: &HashMap<usize, [String; 2]>,
+        titles: &ContextManagerTitles,
         current: usize,
         len: usize,
     ) {
@@ -108,8 +136,8 @@ impl ScreenNavigation {
             has_changes = true;
         }
 
-        if keys != self.keys {
-            self.keys = keys.to_string();
+        if titles.key != self.keys {
+            self.keys = titles.key.to_string();
             has_changes = true;
         }
 
@@ -118,26 +146,32 @@ impl ScreenNavigation {
             has_changes = true;
         }
 
+        if titles.progress != self.progress {
+            self.progress = titles.progress.clone();
+            has_changes = true;
+        }
+
         if !has_changes {
             return;
         }
 
         self.rects = vec![];
         self.texts = vec![];
+        let (alerts, titles) = (&titles.alerts, &titles.titles);
 
         match self.mode {
             #[cfg(target_os = "macos")]
             NavigationMode::NativeTab => {}
-            NavigationMode::CollapsedTab => self.collapsed_tab(titles, len),
+            NavigationMode::CollapsedTab => self.collapsed_tab(titles, alerts, len),
             #[cfg(not(windows))]
             NavigationMode::Breadcrumb => self.breadcrumb(titles, len),
             NavigationMode::TopTab => {
                 let position_y = 0.0;
-                self.tab(titles, len, position_y, 11.);
+                self.tab(titles, alerts, len, position_y, 11.);
             }
             NavigationMode::BottomTab => {
                 let position_y = (self.height / self.scale) - 20.;
-                self.tab(titles, len, position_y, 9.);
+                self.tab(titles, alerts, len, position_y, 9.);
             }
             // Minimal simply does not do anything
             NavigationMode::Plain => {}
@@ -145,7 +179,12 @@ impl ScreenNavigation {
     }
 
     #[inline]
-    pub fn collapsed_tab(&mut self, titles: &HashMap<usize, [String; 2]>, len: usize) {
+    pub fn collapsed_tab(
+        &mut self,
+        titles: &HashMap<usize, [String; 3]>,
+        alerts: &HashSet<usize>,
+        len: usize,
+    ) {
         if len <= 1 {
             return;
         }
@@ -166,18 +205,26 @@ impl ScreenNavigation {
                 }
             }
 
+            if alerts.contains(&i) {
+                color = self.colors.foreground;
+            }
+
             let renderable = Rect {
                 position: [initial_position, 0.0],
                 color,
                 size: [30.0, size],
             };
-            initial_position -= position_modifier;
             self.rects.push(renderable);
+            if let Some(progress) = self.progress.get(&i) {
+                let bar = self.progress_bar(progress, [initial_position, size], 30.);
+                self.rects.push(bar);
+            }
+            initial_position -= position_modifier;
         }
     }
 
     #[inline]
-    pub fn breadcrumb(&mut self, titles: &HashMap<usize, [String; 2]>, len: usize) {
+    pub fn breadcrumb(&mut self, titles: &HashMap<usize, [String; 3]>, len: usize) {
         let mut initial_position = (self.width / self.scale) - 100.;
         let position_modifier = 80.;
         let mut min_view = 9;
@@ -329,11 +376,16 @@ impl ScreenNavigation {
     #[inline]
     pub fn tab(
         &mut self,
-        titles: &HashMap<usize, [String; 2]>,
+        titles: &HashMap<usize, [String; 3]>,
+        alerts: &HashSet<usize>,
         len: usize,
         position_y: f32,
         text_pos_mod: f32,
     ) {
+        if self.tab_bar.hide_if_single && len <= 1 {
+            return;
+        }
+
         let mut initial_position_x = 0.;
 
         let renderable = Rect {
@@ -347,28 +399,37 @@ impl ScreenNavigation {
         let iter = 0..len;
         let mut tabs = Vec::from_iter(iter);
 
-        let max_tab_width = 150.;
-        let screen_limit = ((self.width / self.scale) / max_tab_width).floor() as usize;
+        let bar_width = self.width / self.scale;
+        let max_tab_width = 150_f32.max(self.tab_bar.min_width);
+        let screen_limit = (bar_width / max_tab_width).floor() as usize;
         if len > screen_limit && self.current > screen_limit {
             tabs = Vec::from_iter(self.current - screen_limit..len);
         }
 
+        let equal_width = if self.tab_bar.equal_width {
+            Some((bar_width / tabs.len() as f32).max(self.tab_bar.min_width))
+        } else {
+            None
+        };
+
         for i in tabs {
             let mut background_color = self.colors.inactive;
-            let mut foreground_color = self.colors.active;
+            let mut foreground_color = self.colors.tab_foreground;
 
             if i == self.current {
-                foreground_color = self.colors.inactive;
+                foreground_color = self.colors.tab_active_foreground;
                 background_color = self.colors.active;
             }
 
-            let mut name = String::from("tab");
+            let mut name = format!("{}.tab", i + 1);
             if let Some(name_idx) = titles.get(&i) {
-                if !name_idx[1].is_empty() {
-                    name = name_idx[1].to_string();
-                } else {
-                    name = name_idx[0].to_string();
-                }
+                name = format_tab_title(
+                    &self.tab_bar.title_format,
+                    i + 1,
+                    &name_idx[0],
+                    &name_idx[1],
+                    &name_idx[2],
+                );
 
                 if let Some(color_overwrite) = self.color_automation.get(&name_idx[0]) {
                     foreground_color = self.colors.inactive;
@@ -376,35 +437,77 @@ impl ScreenNavigation {
                 }
             }
 
-            let mut name_modifier = 100.;
+            // A monitor of the tab fired.
+            if alerts.contains(&i) {
+                name = format!("* {name}");
+            }
 
-            if name.len() >= 20 {
-                name = name[0..20].to_string();
+            let name_len = name.chars().count();
+            let mut name_modifier: f32 = 100.;
+
+            if name_len >= 20 {
+                name = name.chars().take(20).collect();
                 name_modifier += 80.;
-            } else if name.len() >= 15 {
-                name = name[0..15].to_string();
+            } else if name_len >= 15 {
+                name = name.chars().take(15).collect();
                 name_modifier += 40.;
-            } else if name.len() >= 10 {
-                name = name[0..10].to_string();
+            } else if name_len >= 10 {
+                name = name.chars().take(10).collect();
                 name_modifier += 20.;
             }
 
+            // Tabs overlap the next one unless they all share the same width.
+            let (step, width) = match equal_width {
+                Some(width) => (width, width),
+                None => {
+                    let step = name_modifier.max(self.tab_bar.min_width);
+                    (step, step + 150.)
+                }
+            };
+
             let renderable_item = Rect {
                 position: [initial_position_x, position_y],
                 color: background_color,
-                size: [120. + name_modifier + 30., 22.],
+                size: [width, 22.],
             };
 
             self.texts.push(Text::new(
                 (initial_position_x + 4., position_y + text_pos_mod),
-                format!("{}.{}", i + 1, name),
+                name,
                 FONT_ID_BUILTIN,
                 14.,
                 foreground_color,
             ));
 
-            initial_position_x += name_modifier;
             self.rects.push(renderable_item);
+            if let Some(progress) = self.progress.get(&i) {
+                let position = [initial_position_x, position_y + 22. - PROGRESS_HEIGHT];
+                let bar = self.progress_bar(progress, position, step);
+                self.rects.push(bar);
+            }
+            initial_position_x += step;
+        }
+    }
+
+    /// Bar along the bottom of a tab, filled up to the reported value. The
+    /// indeterminate state fills it with a fainter color.
+    fn progress_bar(&self, progress: &Progress, position: [f32; 2], width: f32) -> Rect {
+        let filled = progress.value as f32 / 100.;
+        let (color, filled) = match progress.state {
+            ProgressState::Error => (self.colors.progress_error, filled),
+            ProgressState::Paused => (self.colors.progress_paused, filled),
+            ProgressState::Indeterminate => {
+                let [r, g, b, a] = self.colors.progress;
+                ([r, g, b, a * 0.5], 1.)
+            }
+            ProgressState::Normal | ProgressState::Hidden => {
+                (self.colors.progress, filled)
+            }
+        };
+        Rect {
+            position,
+            color,
+            size: [width * filled, PROGRESS_HEIGHT],
         }
     }
 }
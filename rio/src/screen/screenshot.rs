@@ -0,0 +1,200 @@
+use image::ImageEncoder;
+use std::path::PathBuf;
+use std::process::Command;
+use sugarloaf::FrameImage;
+
+/// Pixel area of a frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Copy `region` out of `frame`, clamped to the frame bounds.
+pub fn crop(frame: &FrameImage, region: Region) -> FrameImage {
+    let x = region.x.min(frame.width);
+    let y = region.y.min(frame.height);
+    let width = region.width.min(frame.width - x);
+    let height = region.height.min(frame.height - y);
+
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for row in y..y + height {
+        let start = ((row * frame.width + x) * 4) as usize;
+        pixels.extend_from_slice(&frame.pixels[start..start + (width * 4) as usize]);
+    }
+
+    FrameImage {
+        width,
+        height,
+        pixels,
+    }
+}
+
+pub fn encode_png(frame: &FrameImage) -> Result<Vec<u8>, String> {
+    let mut png = Vec::new();
+    image::codecs::png::PngEncoder::new(&mut png)
+        .write_image(
+            &frame.pixels,
+            frame.width,
+            frame.height,
+            image::ColorType::Rgba8,
+        )
+        .map_err(|err| err.to_string())?;
+    Ok(png)
+}
+
+/// Path for a new screenshot inside `directory`, by default the pictures
+/// directory or the home directory.
+pub fn new_path(directory: Option<&str>) -> PathBuf {
+    let directory = match directory {
+        Some(directory) => match directory.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+            None => PathBuf::from(directory),
+        },
+        None => dirs::picture_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_default(),
+    };
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    directory.join(format!("rio-screenshot-{timestamp}.png"))
+}
+
+/// Copy PNG data into the system clipboard, the clipboard crate only
+/// handles text so this goes through the platform tools. They run on a
+/// thread of their own, errors are logged.
+pub fn copy_png_to_clipboard(png: Vec<u8>) {
+    crate::performer::spawn_named("screenshot clipboard", move || {
+        if let Err(err) = copy_png(&png) {
+            log::error!("unable to copy the screenshot: {err}");
+        }
+    });
+}
+
+#[cfg(any(target_os = "macos", windows))]
+fn copy_png(png: &[u8]) -> Result<(), String> {
+    let path = super::write_private_file("screenshot", "png", png)
+        .map_err(|err| err.to_string())?;
+    // Removed once the command exits.
+    let file = super::context::TemporaryFile(path);
+
+    // The path is given to the scripts, never made part of their source.
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = Command::new("osascript");
+        command
+            .args([
+                "-e",
+                "on run argv",
+                "-e",
+                "set the clipboard to (read (POSIX file (item 1 of argv)) as «class PNGf»)",
+                "-e",
+                "end run",
+            ])
+            .arg(&file.0);
+        command
+    };
+
+    // Arguments following -Command are joined into the script, the path
+    // goes through the environment instead.
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = Command::new("powershell");
+        command
+            .args([
+                "-NoProfile",
+                "-Command",
+                "Add-Type -AssemblyName System.Windows.Forms, System.Drawing; \
+                 [System.Windows.Forms.Clipboard]::SetImage([System.Drawing.Image]::FromFile($env:RIO_SCREENSHOT))",
+            ])
+            .env("RIO_SCREENSHOT", &file.0);
+        command
+    };
+
+    let status = command.status().map_err(|err| err.to_string())?;
+    if !status.success() {
+        return Err(format!("clipboard command exited with {status}"));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn copy_png(png: &[u8]) -> Result<(), String> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut command = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let mut command = Command::new("wl-copy");
+        command.args(["--type", "image/png"]);
+        command
+    } else {
+        let mut command = Command::new("xclip");
+        command.args(["-selection", "clipboard", "-t", "image/png", "-i"]);
+        command
+    };
+
+    // Both tools fork once the image is read, the forked process keeps
+    // serving the clipboard.
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| err.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(png).map_err(|err| err.to_string())?;
+    }
+    let status = child.wait().map_err(|err| err.to_string())?;
+    if !status.success() {
+        return Err(format!("clipboard command exited with {status}"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crop() {
+        // 3x2 frame where every pixel holds its index.
+        let pixels = (0..6u8).flat_map(|i| [i, i, i, 255]).collect();
+        let frame = FrameImage {
+            width: 3,
+            height: 2,
+            pixels,
+        };
+
+        let cropped = crop(
+            &frame,
+            Region {
+                x: 1,
+                y: 0,
+                width: 2,
+                height: 2,
+            },
+        );
+        assert_eq!(cropped.width, 2);
+        assert_eq!(cropped.height, 2);
+        let indexes: Vec<u8> = cropped.pixels.chunks(4).map(|pixel| pixel[0]).collect();
+        assert_eq!(indexes, vec![1, 2, 4, 5]);
+
+        // Regions out of the frame are clamped.
+        let cropped = crop(
+            &frame,
+            Region {
+                x: 2,
+                y: 1,
+                width: 10,
+                height: 10,
+            },
+        );
+        assert_eq!((cropped.width, cropped.height), (1, 1));
+        assert_eq!(cropped.pixels[0], 5);
+    }
+}
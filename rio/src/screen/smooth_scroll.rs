@@ -0,0 +1,108 @@
+// Viewport sliding from one display offset to another. Each frame shows the
+// scrollback at a whole display offset, the fraction of a line left is drawn
+// by moving the text down with the scroll offset of the renderer.
+
+use rio_config::Easing;
+use std::time::{Duration, Instant};
+
+pub struct SmoothScroll {
+    from: usize,
+    pub to: usize,
+    start: Instant,
+    duration: Duration,
+    easing: Easing,
+    /// Display offset set by the last frame, the viewport was moved by
+    /// something else once the terminal has another one.
+    pub offset: usize,
+}
+
+impl SmoothScroll {
+    pub fn new(
+        from: usize,
+        to: usize,
+        duration: Duration,
+        easing: Easing,
+    ) -> SmoothScroll {
+        SmoothScroll {
+            from,
+            to,
+            start: Instant::now(),
+            duration,
+            easing,
+            offset: from,
+        }
+    }
+
+    /// Display offset and fraction of a line the text is moved down by at
+    /// `now`.
+    pub fn frame(&self, now: Instant) -> (usize, f32) {
+        let progress = if self.duration.is_zero() {
+            1.
+        } else {
+            now.saturating_duration_since(self.start).as_secs_f32()
+                / self.duration.as_secs_f32()
+        };
+        let eased = ease(self.easing, progress.min(1.));
+        let position = self.from as f32 + (self.to as f32 - self.from as f32) * eased;
+        let offset = position.floor();
+        (offset as usize, position - offset)
+    }
+
+    #[inline]
+    pub fn is_done(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.start) >= self.duration
+    }
+}
+
+/// Part of the way covered at `progress`, both going from 0 to 1.
+fn ease(easing: Easing, progress: f32) -> f32 {
+    match easing {
+        Easing::Linear => progress,
+        Easing::EaseOut => 1. - (1. - progress).powi(3),
+        Easing::EaseInOut => {
+            if progress < 0.5 {
+                4. * progress.powi(3)
+            } else {
+                1. - (-2. * progress + 2.).powi(3) / 2.
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ease() {
+        for easing in [Easing::Linear, Easing::EaseOut, Easing::EaseInOut] {
+            assert_eq!(ease(easing, 0.), 0.);
+            assert_eq!(ease(easing, 1.), 1.);
+        }
+        assert_eq!(ease(Easing::Linear, 0.25), 0.25);
+        assert!(ease(Easing::EaseOut, 0.25) > 0.25);
+        assert!(ease(Easing::EaseInOut, 0.25) < 0.25);
+        assert_eq!(ease(Easing::EaseInOut, 0.5), 0.5);
+    }
+
+    #[test]
+    fn test_frame() {
+        let scroll = SmoothScroll::new(0, 10, Duration::from_millis(100), Easing::Linear);
+        assert_eq!(scroll.frame(scroll.start), (0, 0.));
+        let (offset, fraction) = scroll.frame(scroll.start + Duration::from_millis(25));
+        assert_eq!(offset, 2);
+        assert!((fraction - 0.5).abs() < 0.001);
+        assert_eq!(
+            scroll.frame(scroll.start + Duration::from_secs(1)),
+            (10, 0.)
+        );
+        assert!(!scroll.is_done(scroll.start + Duration::from_millis(99)));
+        assert!(scroll.is_done(scroll.start + Duration::from_millis(100)));
+
+        // Going down to the bottom.
+        let scroll = SmoothScroll::new(10, 0, Duration::from_millis(100), Easing::Linear);
+        let (offset, fraction) = scroll.frame(scroll.start + Duration::from_millis(25));
+        assert_eq!(offset, 7);
+        assert!((fraction - 0.5).abs() < 0.001);
+    }
+}
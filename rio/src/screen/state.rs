@@ -3,22 +3,36 @@ use crate::crosswords::grid::row::Row;
 use crate::crosswords::pos;
 use crate::crosswords::pos::CursorState;
 use crate::crosswords::square::{Flags, Square};
+use crate::crosswords::watch::{self, WatchRule};
+use crate::crosswords::{arabic, bidi, TermDamage};
 use crate::ime::Preedit;
+use crate::platform::motion::Animations;
 use crate::screen::navigation::ScreenNavigation;
 use crate::screen::{context, EventProxy};
 use crate::selection::SelectionRange;
+use rio_config::bell::Bell;
 use rio_config::colors::{
     term::{List, TermColors},
-    AnsiColor, ColorArray, Colors, NamedColor,
+    AnsiColor, ColorArray, ColorWGPU, Colors, NamedColor,
 };
-use rio_config::Config;
+use rio_config::{BlinkingText, Config, ReduceMotion, TextAttributes};
 use std::collections::HashMap;
 use std::rc::Rc;
 use std::time::{Duration, Instant};
+use sugarloaf::components::rect::Rect;
 use sugarloaf::core::{Sugar, SugarDecoration, SugarStack, SugarStyle};
 use sugarloaf::Sugarloaf;
 use winit::window::Theme;
 
+/// Duration of each phase of blinking text.
+const TEXT_BLINK_INTERVAL: Duration = Duration::from_millis(800);
+
+/// Time between frames while the visual bell fades out.
+const BELL_FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+/// How long damaged cells stay tinted with `show-damage`.
+const DAMAGE_FLASH_DURATION: Duration = Duration::from_millis(500);
+
 struct Cursor {
     state: CursorState,
     content: char,
@@ -36,9 +50,46 @@ pub struct State {
     cursor: Cursor,
     pub selection_range: Option<SelectionRange>,
     pub has_blinking_enabled: bool,
+    pub animations: Animations,
+    /// Animations of the config, `animations` leaves some out with the low
+    /// power profile.
+    configured_animations: Animations,
+    reduce_motion: ReduceMotion,
+    blinking_cursor: bool,
     pub is_blinking: bool,
+    /// Screen-wide reverse video (DECSCNM).
+    reverse_video: bool,
+    /// Colors set by the terminal with OSC sequences.
+    term_colors: TermColors,
+    has_background_image: bool,
+    blinking_text: BlinkingText,
+    text_attributes: TextAttributes,
+    /// Since when blinking text is on screen, phases are counted from it.
+    text_blink_since: Option<Instant>,
+    text_blink_visible: bool,
+    bell: Bell,
+    /// When the visual bell started, cleared once it faded out.
+    bell_since: Option<Instant>,
+    /// Damage taken by the last frames and when, tinted until it faded out.
+    damage_flashes: Vec<(Instant, TermDamage)>,
     ignore_selection_fg_color: bool,
+    invert_selection: bool,
     dynamic_background: ([f32; 4], wgpu::Color),
+    watch_rules: Vec<WatchRule>,
+    pub bidi: bool,
+    arabic_shaping: bool,
+    /// Lines written below the viewport while it is scrolled up.
+    pub new_lines: usize,
+    /// Bounds of the "new lines" pill (x, y, width, height), in logical
+    /// pixels, while it is shown.
+    pub new_lines_pill: Option<[f32; 4]>,
+    /// Prompts of the scrollback, with their position in it from 0 (top) to
+    /// 1 (bottom).
+    pub prompt_marks: Vec<(f32, pos::Line)>,
+    /// Bounds of the drawn prompt markers, to jump to them on click.
+    pub prompt_mark_bounds: Vec<([f32; 4], pos::Line)>,
+    /// Leader key waiting for the next key of a chord.
+    pub pending_chord: Option<String>,
 }
 
 // TODO: Finish from
@@ -98,20 +149,46 @@ impl State {
             color_automation.insert(rule.program.to_string(), rule.color);
         }
 
+        let animations = Animations::new(config.reduce_motion);
+
         State {
             option_as_alt: config.option_as_alt.to_lowercase(),
             is_ime_enabled: false,
             is_blinking: false,
+            reverse_video: false,
+            term_colors: TermColors::default(),
+            has_background_image: config.background.mode.is_image(),
+            blinking_text: config.blinking_text.to_owned(),
+            text_attributes: config.text_attributes.to_owned(),
+            text_blink_since: None,
+            text_blink_visible: true,
+            bell: config.bell.to_owned(),
+            bell_since: None,
+            damage_flashes: vec![],
             last_typing: None,
-            has_blinking_enabled: config.blinking_cursor,
+            has_blinking_enabled: config.blinking_cursor && animations.blinking_cursor,
+            animations,
+            configured_animations: animations,
+            reduce_motion: config.reduce_motion,
+            blinking_cursor: config.blinking_cursor,
             ignore_selection_fg_color: config.ignore_selection_fg_color,
+            invert_selection: config.selection.invert,
             colors,
             navigation: ScreenNavigation::new(
-                config.navigation.mode,
+                &config.navigation,
                 [
                     named_colors.tabs,
                     named_colors.tabs_active,
                     named_colors.foreground,
+                    named_colors
+                        .tabs_foreground
+                        .unwrap_or(named_colors.tabs_active),
+                    named_colors
+                        .tabs_active_foreground
+                        .unwrap_or(named_colors.tabs),
+                    named_colors.green,
+                    named_colors.red,
+                    named_colors.yellow,
                 ],
                 color_automation,
                 0.0,
@@ -119,6 +196,14 @@ impl State {
                 0.0,
             ),
             font_size: config.fonts.size,
+            watch_rules: watch::compile(&config.watch.rules),
+            bidi: config.bidi,
+            arabic_shaping: config.arabic_shaping,
+            new_lines: 0,
+            new_lines_pill: None,
+            prompt_marks: vec![],
+            prompt_mark_bounds: vec![],
+            pending_chord: None,
             selection_range: None,
             named_colors,
             dynamic_background,
@@ -144,20 +229,22 @@ impl State {
     #[inline]
     fn create_sugar(&self, square: &Square) -> Sugar {
         let flags = square.flags;
+        let (foreground_color, background_color) = self.compute_colors(square);
 
-        let mut foreground_color = self.compute_fg_color(square);
-        let mut background_color = self.compute_bg_color(square);
-
-        let content = if square.c == '\t' || flags.contains(Flags::HIDDEN) {
+        let content = if square.c == '\t'
+            || flags.contains(Flags::HIDDEN)
+            || (flags.contains(Flags::BLINK) && !self.text_blink_visible)
+        {
             ' '
         } else {
             square.c
         };
 
         let mut style: Option<SugarStyle> = None;
+        let bold_font = self.text_attributes.bold_font;
         let is_italic = flags.contains(Flags::ITALIC);
-        let is_bold_italic = flags.contains(Flags::BOLD_ITALIC);
-        let is_bold = flags.contains(Flags::BOLD);
+        let is_bold_italic = bold_font && flags.contains(Flags::BOLD_ITALIC);
+        let is_bold = bold_font && flags.contains(Flags::BOLD);
 
         if is_bold || is_bold_italic || is_italic {
             style = Some(SugarStyle {
@@ -167,22 +254,18 @@ impl State {
             });
         }
 
-        if flags.contains(Flags::INVERSE) {
-            std::mem::swap(&mut background_color, &mut foreground_color);
-        }
-
         let mut decoration = None;
         if flags.contains(Flags::UNDERLINE) {
             decoration = Some(SugarDecoration {
                 relative_position: (0.0, self.font_size - 1.),
                 size: (1.0, 0.005),
-                color: self.named_colors.foreground,
+                color: self.foreground(),
             });
         } else if flags.contains(Flags::STRIKEOUT) {
             decoration = Some(SugarDecoration {
                 relative_position: (0.0, self.font_size / 2.),
                 size: (1.0, 0.025),
-                color: self.named_colors.foreground,
+                color: foreground_color,
             });
         }
 
@@ -201,17 +284,17 @@ impl State {
             CursorShape::Block => Some(SugarDecoration {
                 relative_position: (0.0, 0.0),
                 size: (1.0, 1.0),
-                color: self.named_colors.cursor,
+                color: self.cursor_color(),
             }),
             CursorShape::Underline => Some(SugarDecoration {
                 relative_position: (0.0, self.font_size - 2.5),
                 size: (1.0, 0.08),
-                color: self.named_colors.cursor,
+                color: self.cursor_color(),
             }),
             CursorShape::Beam => Some(SugarDecoration {
                 relative_position: (0.0, 0.0),
                 size: (0.1, 1.0),
-                color: self.named_colors.cursor,
+                color: self.cursor_color(),
             }),
             CursorShape::Hidden => None,
         }
@@ -223,8 +306,8 @@ impl State {
         for _ in 0..columns {
             stack.push(Sugar {
                 content: ' ',
-                foreground_color: self.named_colors.background.0,
-                background_color: self.named_colors.background.0,
+                foreground_color: self.background(),
+                background_color: self.background(),
                 style: None,
                 decoration: None,
             })
@@ -243,6 +326,7 @@ impl State {
     ) -> SugarStack {
         let mut stack: Vec<Sugar> = vec![];
         let columns: usize = row.len();
+        let highlights = watch::highlights(&self.watch_rules, row);
         for column in 0..columns {
             let line = line - display_offset;
             let is_selected = range.contains(pos::Pos::new(line, pos::Column(column)));
@@ -255,27 +339,13 @@ impl State {
             if has_cursor && column == self.cursor.state.pos.col {
                 stack.push(self.create_cursor(square));
             } else if is_selected {
-                let content = if square.c == '\t' || square.flags.contains(Flags::HIDDEN)
-                {
-                    ' '
-                } else {
-                    square.c
-                };
-
-                let selected_sugar = Sugar {
-                    content,
-                    foreground_color: if self.ignore_selection_fg_color {
-                        self.compute_fg_color(square)
-                    } else {
-                        self.named_colors.selection_foreground
-                    },
-                    background_color: self.named_colors.selection_background,
-                    style: None,
-                    decoration: None,
-                };
-                stack.push(selected_sugar);
+                stack.push(self.create_selected_sugar(square));
             } else {
-                stack.push(self.create_sugar(square));
+                let mut sugar = self.create_sugar(square);
+                if let Some(Some(color)) = highlights.get(column) {
+                    sugar.background_color = *color;
+                }
+                stack.push(sugar);
             }
 
             // Render last column and break row
@@ -284,14 +354,64 @@ impl State {
             }
         }
 
-        stack
+        self.shape_arabic(&mut stack);
+        self.reorder_bidi(row, stack)
+    }
+
+    /// Resolve the foreground and background of a square, swapping them for
+    /// inverse squares. The default background of an inverse square is drawn
+    /// opaque even when the window background is an image.
+    #[inline]
+    fn compute_colors(&self, square: &Square) -> (ColorArray, ColorArray) {
+        let fg = self.compute_fg_color(square);
+        if !square.flags.contains(Flags::INVERSE) {
+            return (fg, self.compute_bg_color(square));
+        }
+
+        let bg = match square.bg {
+            AnsiColor::Named(NamedColor::Background) if !self.reverse_video => {
+                self.background()
+            }
+            _ => self.compute_bg_color(square),
+        };
+        (bg, fg)
+    }
+
+    /// Selected squares keep their text attributes and take the selection
+    /// colors, or swap their own colors when `invert` is set.
+    #[inline]
+    fn create_selected_sugar(&self, square: &Square) -> Sugar {
+        let mut sugar = self.create_sugar(square);
+        if self.invert_selection {
+            let background = if sugar.background_color[3] == 0.0 {
+                self.background()
+            } else {
+                sugar.background_color
+            };
+            sugar.background_color = sugar.foreground_color;
+            sugar.foreground_color = background;
+        } else {
+            sugar.background_color = self.named_colors.selection_background;
+            if !self.ignore_selection_fg_color {
+                sugar.foreground_color = self.named_colors.selection_foreground;
+            }
+        }
+        sugar
     }
 
     #[inline]
     fn compute_fg_color(&self, square: &Square) -> ColorArray {
-        match square.fg {
+        let fg = self.style_fg(square);
+        if let Some(color) = self.palette_override(fg) {
+            return color;
+        }
+
+        match fg {
             AnsiColor::Named(NamedColor::Black) => self.named_colors.black,
-            AnsiColor::Named(NamedColor::Background) => self.named_colors.background.0,
+            AnsiColor::Named(NamedColor::Background) if self.reverse_video => {
+                self.foreground()
+            }
+            AnsiColor::Named(NamedColor::Background) => self.background(),
             AnsiColor::Named(NamedColor::Blue) => self.named_colors.blue,
             AnsiColor::Named(NamedColor::LightBlack) => self.named_colors.light_black,
             AnsiColor::Named(NamedColor::LightBlue) => self.named_colors.light_blue,
@@ -304,7 +424,7 @@ impl State {
             AnsiColor::Named(NamedColor::LightRed) => self.named_colors.light_red,
             AnsiColor::Named(NamedColor::LightWhite) => self.named_colors.light_white,
             AnsiColor::Named(NamedColor::LightYellow) => self.named_colors.light_yellow,
-            AnsiColor::Named(NamedColor::Cursor) => self.named_colors.cursor,
+            AnsiColor::Named(NamedColor::Cursor) => self.cursor_color(),
             AnsiColor::Named(NamedColor::Cyan) => self.named_colors.cyan,
             AnsiColor::Named(NamedColor::DimBlack) => self.named_colors.dim_black,
             AnsiColor::Named(NamedColor::DimBlue) => self.named_colors.dim_blue,
@@ -317,7 +437,10 @@ impl State {
             AnsiColor::Named(NamedColor::DimRed) => self.named_colors.dim_red,
             AnsiColor::Named(NamedColor::DimWhite) => self.named_colors.dim_white,
             AnsiColor::Named(NamedColor::DimYellow) => self.named_colors.dim_yellow,
-            AnsiColor::Named(NamedColor::Foreground) => self.named_colors.foreground,
+            AnsiColor::Named(NamedColor::Foreground) if self.reverse_video => {
+                self.background()
+            }
+            AnsiColor::Named(NamedColor::Foreground) => self.foreground(),
             AnsiColor::Named(NamedColor::Green) => self.named_colors.green,
             AnsiColor::Named(NamedColor::Magenta) => self.named_colors.magenta,
             AnsiColor::Named(NamedColor::Red) => self.named_colors.red,
@@ -327,26 +450,64 @@ impl State {
                 if !square.flags.contains(Flags::DIM) {
                     rgb.to_arr()
                 } else {
-                    rgb.to_arr_with_dim()
+                    self.dim(rgb.to_arr())
+                }
+            }
+            AnsiColor::Indexed(index) => match (square.flags & Flags::DIM_BOLD, index) {
+                (Flags::DIM, 8..=15) => self.indexed_color(index as usize - 8),
+                (Flags::DIM, 0..=7) => {
+                    self.indexed_color(NamedColor::DimBlack as usize + index as usize)
                 }
+                (Flags::DIM, _) => self.dim(self.indexed_color(index as usize)),
+                _ => self.indexed_color(index as usize),
+            },
+        }
+    }
+
+    /// Foreground after applying the bold and dim attributes, bold maps the
+    /// first 8 colors to their bright variants when `bold-as-bright` is set
+    /// and dim maps named colors to their dim variants.
+    #[inline]
+    fn style_fg(&self, square: &Square) -> AnsiColor {
+        let bold_as_bright = self.text_attributes.bold_as_bright;
+        match (square.flags & Flags::DIM_BOLD, square.fg) {
+            (Flags::BOLD, AnsiColor::Named(name))
+                if bold_as_bright
+                    && ((name as usize) < 8 || name == NamedColor::Foreground) =>
+            {
+                AnsiColor::Named(name.to_light())
             }
-            AnsiColor::Indexed(index) => {
-                let index = match (square.flags & Flags::DIM_BOLD, index) {
-                    (Flags::DIM, 8..=15) => index as usize - 8,
-                    (Flags::DIM, 0..=7) => NamedColor::DimBlack as usize + index as usize,
-                    _ => index as usize,
-                };
-
-                self.colors[index]
+            (Flags::BOLD, AnsiColor::Indexed(index)) if bold_as_bright && index < 8 => {
+                AnsiColor::Indexed(index + 8)
             }
+            (Flags::DIM, AnsiColor::Named(name)) => AnsiColor::Named(name.to_dim()),
+            (_, fg) => fg,
         }
     }
 
+    #[inline]
+    fn dim(&self, color: ColorArray) -> ColorArray {
+        let multiplier = self.text_attributes.dim_multiplier;
+        [
+            color[0] * multiplier,
+            color[1] * multiplier,
+            color[2] * multiplier,
+            color[3],
+        ]
+    }
+
     #[inline]
     fn compute_bg_color(&self, square: &Square) -> ColorArray {
+        if let Some(color) = self.palette_override(square.bg) {
+            return color;
+        }
+
         match square.bg {
             AnsiColor::Named(NamedColor::Black) => self.named_colors.black,
-            AnsiColor::Named(NamedColor::Background) => self.dynamic_background.0,
+            AnsiColor::Named(NamedColor::Background) if self.reverse_video => {
+                self.foreground()
+            }
+            AnsiColor::Named(NamedColor::Background) => self.dynamic_background(),
             AnsiColor::Named(NamedColor::Blue) => self.named_colors.blue,
             AnsiColor::Named(NamedColor::LightBlack) => self.named_colors.light_black,
             AnsiColor::Named(NamedColor::LightBlue) => self.named_colors.light_blue,
@@ -359,7 +520,7 @@ impl State {
             AnsiColor::Named(NamedColor::LightRed) => self.named_colors.light_red,
             AnsiColor::Named(NamedColor::LightWhite) => self.named_colors.light_white,
             AnsiColor::Named(NamedColor::LightYellow) => self.named_colors.light_yellow,
-            AnsiColor::Named(NamedColor::Cursor) => self.named_colors.cursor,
+            AnsiColor::Named(NamedColor::Cursor) => self.cursor_color(),
             AnsiColor::Named(NamedColor::Cyan) => self.named_colors.cyan,
             AnsiColor::Named(NamedColor::DimBlack) => self.named_colors.dim_black,
             AnsiColor::Named(NamedColor::DimBlue) => self.named_colors.dim_blue,
@@ -372,14 +533,17 @@ impl State {
             AnsiColor::Named(NamedColor::DimRed) => self.named_colors.dim_red,
             AnsiColor::Named(NamedColor::DimWhite) => self.named_colors.dim_white,
             AnsiColor::Named(NamedColor::DimYellow) => self.named_colors.dim_yellow,
-            AnsiColor::Named(NamedColor::Foreground) => self.named_colors.foreground,
+            AnsiColor::Named(NamedColor::Foreground) if self.reverse_video => {
+                self.dynamic_background()
+            }
+            AnsiColor::Named(NamedColor::Foreground) => self.foreground(),
             AnsiColor::Named(NamedColor::Green) => self.named_colors.green,
             AnsiColor::Named(NamedColor::Magenta) => self.named_colors.magenta,
             AnsiColor::Named(NamedColor::Red) => self.named_colors.red,
             AnsiColor::Named(NamedColor::White) => self.named_colors.white,
             AnsiColor::Named(NamedColor::Yellow) => self.named_colors.yellow,
             AnsiColor::Spec(rgb) => rgb.to_arr(),
-            AnsiColor::Indexed(idx) => self.colors[idx as usize],
+            AnsiColor::Indexed(idx) => self.indexed_color(idx as usize),
         }
     }
 
@@ -387,6 +551,7 @@ impl State {
     fn create_sugar_stack(&mut self, row: &Row<Square>, has_cursor: bool) -> SugarStack {
         let mut stack: Vec<Sugar> = vec![];
         let columns: usize = row.len();
+        let highlights = watch::highlights(&self.watch_rules, row);
         for column in 0..columns {
             let square = &row.inner[column];
 
@@ -397,7 +562,11 @@ impl State {
             if has_cursor && column == self.cursor.state.pos.col {
                 stack.push(self.create_cursor(square));
             } else {
-                stack.push(self.create_sugar(square));
+                let mut sugar = self.create_sugar(square);
+                if let Some(Some(color)) = highlights.get(column) {
+                    sugar.background_color = *color;
+                }
+                stack.push(sugar);
             }
 
             // Render last column and break row
@@ -406,7 +575,50 @@ impl State {
             }
         }
 
-        stack
+        self.shape_arabic(&mut stack);
+        self.reorder_bidi(row, stack)
+    }
+
+    #[inline]
+    fn shape_arabic(&self, stack: &mut SugarStack) {
+        if !self.arabic_shaping {
+            return;
+        }
+
+        let mut chars: Vec<char> = stack.iter().map(|sugar| sugar.content).collect();
+        if !arabic::has_arabic(&chars) {
+            return;
+        }
+
+        arabic::shape(&mut chars);
+        for (sugar, c) in stack.iter_mut().zip(chars) {
+            sugar.content = c;
+        }
+    }
+
+    /// Draw the right-to-left runs of the row in visual order. The cursor and
+    /// selection were applied to the squares so they move along with them.
+    #[inline]
+    fn reorder_bidi(&self, row: &Row<Square>, stack: SugarStack) -> SugarStack {
+        if !self.bidi {
+            return stack;
+        }
+
+        let order = match bidi::visual_order(row) {
+            Some(order) => order,
+            None => return stack,
+        };
+        let mut sugars: Vec<Option<Sugar>> = stack.into_iter().map(Some).collect();
+        order
+            .into_iter()
+            .filter_map(|(index, is_rtl)| {
+                let mut sugar = sugars.get_mut(index)?.take()?;
+                if is_rtl {
+                    sugar.content = bidi::mirror(sugar.content);
+                }
+                Some(sugar)
+            })
+            .collect()
     }
 
     #[inline]
@@ -418,17 +630,43 @@ impl State {
             cloned_square.c = self.cursor.content;
         }
 
-        // If IME is enabled or is a block cursor, put background color
-        // when cursor is over the character
+        let mut sugar = self.create_sugar(&cloned_square);
+
+        // If IME is enabled or is a block cursor, draw the character with the
+        // configured cursor text color or the square background (already
+        // resolved for inverse and reverse video)
         if self.is_ime_enabled || self.cursor.state.content == CursorShape::Block {
-            cloned_square.fg = AnsiColor::Named(NamedColor::Background);
+            sugar.foreground_color = if let Some(color) = self.named_colors.cursor_text {
+                color
+            } else if sugar.background_color[3] == 0.0 {
+                self.background()
+            } else {
+                sugar.background_color
+            };
         }
 
-        let mut sugar = self.create_sugar(&cloned_square);
         sugar.decoration = self.cursor_to_decoration();
         sugar
     }
 
+    /// Follow a change of the system preference, the low power profile has
+    /// to be applied again after.
+    pub fn update_reduce_motion(&mut self) {
+        self.configured_animations = Animations::new(self.reduce_motion);
+        self.animations = self.configured_animations;
+        self.has_blinking_enabled =
+            self.blinking_cursor && self.animations.blinking_cursor;
+    }
+
+    /// Leave out the effects of the low power profile, or bring them back.
+    pub fn set_low_power(&mut self, low_power: bool) {
+        self.animations = if low_power {
+            self.configured_animations.low_power()
+        } else {
+            self.configured_animations
+        };
+    }
+
     #[inline]
     pub fn set_ime(&mut self, ime_preedit: Option<&Preedit>) {
         if let Some(preedit) = ime_preedit {
@@ -443,25 +681,430 @@ impl State {
         self.cursor.content = self.cursor.content_ref;
     }
 
+    /// Update the blinking text phase for a frame with `rows`.
+    fn update_text_blink(&mut self, rows: &[Row<Square>]) {
+        let has_blinking_text = self.blinking_text.enabled
+            && rows
+                .iter()
+                .any(|row| row.inner.iter().any(|sq| sq.flags.contains(Flags::BLINK)));
+        if !has_blinking_text {
+            self.text_blink_since = None;
+            self.text_blink_visible = true;
+            return;
+        }
+
+        let since = *self.text_blink_since.get_or_insert_with(Instant::now);
+        self.text_blink_visible = self.text_blink_phase(since.elapsed()).0;
+    }
+
+    /// Whether blinking text is visible after `elapsed` and how long until
+    /// the next phase, `None` once the timeout is over.
+    fn text_blink_phase(&self, elapsed: Duration) -> (bool, Option<Duration>) {
+        let timeout = self.blinking_text.timeout;
+        if timeout > 0 && elapsed >= Duration::from_secs(timeout) {
+            return (true, None);
+        }
+
+        let interval = TEXT_BLINK_INTERVAL.as_millis();
+        let elapsed = elapsed.as_millis();
+        let visible = (elapsed / interval) % 2 == 0;
+        let remaining = interval - elapsed % interval;
+        (visible, Some(Duration::from_millis(remaining as u64)))
+    }
+
+    /// Time until blinking text on screen needs to be redrawn.
+    #[inline]
+    pub fn next_text_blink(&self) -> Option<Duration> {
+        self.text_blink_since
+            .and_then(|since| self.text_blink_phase(since.elapsed()).1)
+    }
+
+    /// Start the visual bell, restarting it if it is already running.
+    #[inline]
+    pub fn ring_bell(&mut self) {
+        if self.bell.duration > 0 {
+            self.bell_since = Some(Instant::now());
+        }
+    }
+
+    /// Opacity of the visual bell `elapsed` after it started.
+    fn bell_intensity(&self, elapsed: Duration) -> f32 {
+        let duration = Duration::from_millis(self.bell.duration);
+        if elapsed >= duration {
+            return 0.0;
+        }
+
+        if !self.animations.bell_fade {
+            return 1.0;
+        }
+        let progress = elapsed.as_secs_f32() / duration.as_secs_f32();
+        1.0 - self.bell.animation.ease(progress)
+    }
+
+    /// Time until the next frame of the visual bell, if it is running.
+    /// Without the fade, the next frame is the one hiding it.
+    #[inline]
+    pub fn next_bell_frame(&self) -> Option<Duration> {
+        let since = self.bell_since?;
+        if self.animations.bell_fade {
+            return Some(BELL_FRAME_INTERVAL);
+        }
+        let duration = Duration::from_millis(self.bell.duration);
+        Some(duration.saturating_sub(since.elapsed()) + Duration::from_millis(1))
+    }
+
+    /// Tint the cells of `damage` in the next frames.
+    pub fn flash_damage(&mut self, damage: TermDamage) {
+        if damage != TermDamage::Partial(vec![]) {
+            self.damage_flashes.push((Instant::now(), damage));
+        }
+    }
+
+    /// Time until the next frame of the damage tint, if one is fading out.
+    #[inline]
+    pub fn next_damage_frame(&self) -> Option<Duration> {
+        (!self.damage_flashes.is_empty()).then_some(BELL_FRAME_INTERVAL)
+    }
+
+    /// Rects over the damaged cells, the older the more transparent. The
+    /// tints that faded out are dropped.
+    fn damage_overlay(&mut self, sugarloaf: &mut Sugarloaf) {
+        self.damage_flashes
+            .retain(|(since, _)| since.elapsed() < DAMAGE_FLASH_DURATION);
+        if self.damage_flashes.is_empty() {
+            return;
+        }
+
+        let layout = &sugarloaf.layout;
+        let cell_width = layout.scaled_sugarwidth / layout.scale_factor;
+        let cell_height = layout.scaled_sugarheight / layout.scale_factor;
+        let left = layout.margin.x;
+        let top = layout.margin.top_y * 2.;
+        let [r, g, b, _] = self.named_colors.magenta;
+
+        let mut rects = vec![];
+        for (since, damage) in self.damage_flashes.iter() {
+            let fade =
+                1.0 - since.elapsed().as_secs_f32() / DAMAGE_FLASH_DURATION.as_secs_f32();
+            let color = [r, g, b, 0.4 * fade];
+            match damage {
+                TermDamage::Full => rects.push(Rect {
+                    position: [left, top],
+                    color,
+                    size: [
+                        layout.columns as f32 * cell_width,
+                        layout.lines as f32 * cell_height,
+                    ],
+                }),
+                TermDamage::Partial(lines) => {
+                    rects.extend(lines.iter().map(|line| Rect {
+                        position: [
+                            left + line.left as f32 * cell_width,
+                            top + line.line as f32 * cell_height,
+                        ],
+                        color,
+                        size: [
+                            (line.right + 1 - line.left) as f32 * cell_width,
+                            cell_height,
+                        ],
+                    }))
+                }
+            }
+        }
+        sugarloaf.pile_rects(rects);
+    }
+
+    /// Returns true when the reverse video state changed.
+    #[inline]
+    pub fn set_reverse_video(&mut self, reverse_video: bool) -> bool {
+        let changed = self.reverse_video != reverse_video;
+        self.reverse_video = reverse_video;
+        changed
+    }
+
+    /// Color used to clear the window, the foreground under reverse video.
+    #[inline]
+    pub fn window_background(&self) -> ColorWGPU {
+        let color = if self.reverse_video {
+            self.foreground()
+        } else {
+            match self.term_colors[NamedColor::Background] {
+                Some(color) => color,
+                None => return self.named_colors.background.1,
+            }
+        };
+
+        let [r, g, b, a] = color;
+        ColorWGPU {
+            r: r as f64,
+            g: g as f64,
+            b: b as f64,
+            a: a as f64,
+        }
+    }
+
+    /// Replace the colors set by the terminal, returns true when the window
+    /// background changed.
+    #[inline]
+    pub fn set_term_colors(&mut self, colors: TermColors) -> bool {
+        let background = NamedColor::Background;
+        let changed = self.term_colors[background] != colors[background];
+        self.term_colors = colors;
+        changed
+    }
+
+    /// Default color for `index`, ignoring the colors set by the terminal.
+    pub fn default_color(&self, index: usize) -> ColorArray {
+        match index {
+            i if i == NamedColor::Foreground as usize => self.named_colors.foreground,
+            i if i == NamedColor::Background as usize => self.named_colors.background.0,
+            i if i == NamedColor::Cursor as usize => self.named_colors.cursor,
+            _ => self.colors[index],
+        }
+    }
+
+    /// Palette entry redefined by OSC 4 for one of the 16 named colors.
+    #[inline]
+    fn palette_override(&self, color: AnsiColor) -> Option<ColorArray> {
+        match color {
+            AnsiColor::Named(name) if (name as usize) < 16 => self.term_colors[name],
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn indexed_color(&self, index: usize) -> ColorArray {
+        self.term_colors[index].unwrap_or(self.colors[index])
+    }
+
+    #[inline]
+    fn foreground(&self) -> ColorArray {
+        self.term_colors[NamedColor::Foreground].unwrap_or(self.named_colors.foreground)
+    }
+
+    #[inline]
+    fn background(&self) -> ColorArray {
+        self.term_colors[NamedColor::Background].unwrap_or(self.named_colors.background.0)
+    }
+
+    /// Background of squares, transparent over a background image.
+    #[inline]
+    fn dynamic_background(&self) -> ColorArray {
+        if self.has_background_image {
+            self.dynamic_background.0
+        } else {
+            self.background()
+        }
+    }
+
+    #[inline]
+    fn cursor_color(&self) -> ColorArray {
+        self.term_colors[NamedColor::Cursor].unwrap_or(self.named_colors.cursor)
+    }
+
     #[inline]
     pub fn set_selection(&mut self, selection_range: Option<SelectionRange>) {
         self.selection_range = selection_range;
     }
 
+    /// Pill in the bottom left corner showing the leader key was pressed.
+    fn chord_indicator(&self, sugarloaf: &mut Sugarloaf) {
+        let Some(chord) = &self.pending_chord else {
+            return;
+        };
+
+        let layout = &sugarloaf.layout;
+        let cell_height = layout.scaled_sugarheight / layout.scale_factor;
+        let top = layout.margin.top_y * 2.;
+        let font_size = layout.font_size;
+        let sugarwidth = layout.sugarwidth;
+
+        let content = format!("{chord} …");
+        let pill_width = (content.chars().count() + 2) as f32 * sugarwidth;
+        let pill_height = cell_height * 1.2;
+        let x = 12.;
+        let y = top + layout.lines as f32 * cell_height - pill_height - 4.;
+        sugarloaf.pile_rects(vec![Rect {
+            position: [x, y],
+            color: self.named_colors.tabs_active,
+            size: [pill_width, pill_height],
+        }]);
+        sugarloaf.text(
+            (x + sugarwidth, y + pill_height / 2.),
+            content,
+            sugarloaf::font::FONT_ID_REGULAR,
+            font_size,
+            self.named_colors.background.0,
+            true,
+        );
+    }
+
+    /// Divider below the last line shown before the viewport was scrolled
+    /// up, and a pill counting the lines written since.
+    fn new_lines_indicator(&mut self, sugarloaf: &mut Sugarloaf, display_offset: i32) {
+        self.new_lines_pill = None;
+        if self.new_lines == 0 {
+            return;
+        }
+
+        let layout = &sugarloaf.layout;
+        let width = layout.width / layout.scale_factor;
+        let cell_height = layout.scaled_sugarheight / layout.scale_factor;
+        let top = layout.margin.top_y * 2.;
+        let lines = layout.lines as i32;
+        let font_size = layout.font_size;
+        let [r, g, b, _] = self.named_colors.foreground;
+
+        // Viewport row of the first new line.
+        let first_new_row = lines - self.new_lines as i32 + display_offset;
+        let mut rects = vec![];
+        if first_new_row > 0 && first_new_row < lines {
+            rects.push(Rect {
+                position: [0., top + first_new_row as f32 * cell_height - 1.],
+                color: [r, g, b, 0.35],
+                size: [width, 1.],
+            });
+        }
+
+        let content = if self.new_lines == 1 {
+            String::from("1 new line")
+        } else {
+            format!("{} new lines", self.new_lines)
+        };
+        let sugarwidth = layout.sugarwidth;
+        let pill_width = (content.chars().count() + 2) as f32 * sugarwidth;
+        let pill_height = cell_height * 1.2;
+        let x = width - pill_width - 12.;
+        let y = top + lines as f32 * cell_height - pill_height - 4.;
+        rects.push(Rect {
+            position: [x, y],
+            color: self.named_colors.tabs_active,
+            size: [pill_width, pill_height],
+        });
+        sugarloaf.pile_rects(rects);
+        sugarloaf.text(
+            (x + sugarwidth, y + pill_height / 2.),
+            content,
+            sugarloaf::font::FONT_ID_REGULAR,
+            font_size,
+            self.named_colors.background.0,
+            true,
+        );
+        self.new_lines_pill = Some([x, y, pill_width, pill_height]);
+    }
+
+    /// Markers along the right edge for the prompts of the scrollback, like
+    /// on a scrollbar.
+    fn prompt_markers(&mut self, sugarloaf: &mut Sugarloaf) {
+        self.prompt_mark_bounds.clear();
+        if self.prompt_marks.is_empty() {
+            return;
+        }
+
+        let layout = &sugarloaf.layout;
+        let width = layout.width / layout.scale_factor;
+        let top = layout.margin.top_y * 2.;
+        let height =
+            layout.lines as f32 * layout.scaled_sugarheight / layout.scale_factor;
+        let (marker_width, marker_height) = (8., 3.);
+
+        let mut rects = Vec::with_capacity(self.prompt_marks.len());
+        for (position, line) in self.prompt_marks.iter() {
+            let bounds = [
+                width - marker_width,
+                top + position * (height - marker_height),
+                marker_width,
+                marker_height,
+            ];
+            rects.push(Rect {
+                position: [bounds[0], bounds[1]],
+                color: self.named_colors.cursor,
+                size: [bounds[2], bounds[3]],
+            });
+            // Easier to hit than it looks.
+            let hit = [bounds[0] - 4., bounds[1] - 3., marker_width + 4., 9.];
+            self.prompt_mark_bounds.push((hit, *line));
+        }
+        sugarloaf.pile_rects(rects);
+    }
+
     #[inline]
     pub fn prepare_term(
         &mut self,
-        rows: Vec<Row<Square>>,
+        rows: &[Row<Square>],
         cursor: CursorState,
         sugarloaf: &mut Sugarloaf,
         context_manager: &context::ContextManager<EventProxy>,
         display_offset: i32,
         terminal_has_blinking_enabled: bool,
+    ) {
+        self.prepare_rows(
+            rows,
+            cursor,
+            sugarloaf,
+            display_offset,
+            terminal_has_blinking_enabled,
+        );
+
+        self.navigation.content(
+            (sugarloaf.layout.width, sugarloaf.layout.height),
+            sugarloaf.layout.scale_factor,
+            &context_manager.titles,
+            context_manager.current_index(),
+            context_manager.len(),
+        );
+
+        sugarloaf.pile_rects(self.navigation.rects.clone());
+        self.new_lines_indicator(sugarloaf, display_offset);
+        self.prompt_markers(sugarloaf);
+        self.chord_indicator(sugarloaf);
+
+        if let Some(since) = self.bell_since {
+            let intensity = self.bell_intensity(since.elapsed());
+            if intensity > 0.0 {
+                let [r, g, b, a] = self.bell.color;
+                let scale = sugarloaf.layout.scale_factor;
+                sugarloaf.pile_rects(vec![Rect {
+                    position: [0.0, 0.0],
+                    color: [r, g, b, a * intensity],
+                    size: [
+                        sugarloaf.layout.width / scale,
+                        sugarloaf.layout.height / scale,
+                    ],
+                }]);
+            } else {
+                self.bell_since = None;
+            }
+        }
+        self.damage_overlay(sugarloaf);
+
+        for text in self.navigation.texts.iter() {
+            sugarloaf.text(
+                text.position,
+                text.content.to_owned(),
+                text.font_id,
+                text.font_size,
+                text.color,
+                true,
+            );
+        }
+    }
+
+    /// Stack the rows of the grid, without the navigation and the overlays
+    /// drawn over them.
+    pub fn prepare_rows(
+        &mut self,
+        rows: &[Row<Square>],
+        cursor: CursorState,
+        sugarloaf: &mut Sugarloaf,
+        display_offset: i32,
+        terminal_has_blinking_enabled: bool,
     ) {
         self.cursor.state = cursor;
         let mut is_cursor_visible = self.cursor.state.is_visible();
 
         self.font_size = sugarloaf.layout.font_size;
+        self.update_text_blink(rows);
         if let Some(active_selection) = self.selection_range {
             for (i, row) in rows.iter().enumerate() {
                 let has_cursor = is_cursor_visible && self.cursor.state.pos.row == i;
@@ -475,8 +1118,14 @@ impl State {
                 sugarloaf.stack(sugar_stack);
             }
         } else {
-            // Only blink cursor if does not contain selection
-            if self.has_blinking_enabled && terminal_has_blinking_enabled {
+            // Only blink cursor if does not contain selection, frames of the
+            // visual bell and of the damage tint would otherwise toggle it on
+            // every render
+            if self.has_blinking_enabled
+                && terminal_has_blinking_enabled
+                && self.bell_since.is_none()
+                && self.damage_flashes.is_empty()
+            {
                 let mut should_blink = true;
                 if let Some(last_typing_time) = self.last_typing {
                     if last_typing_time.elapsed() < Duration::from_secs(1) {
@@ -501,27 +1150,219 @@ impl State {
         let empty_last_line =
             self.create_empty_sugar_stack_from_columns(sugarloaf.layout.columns);
         sugarloaf.stack(empty_last_line);
+    }
+}
 
-        self.navigation.content(
-            (sugarloaf.layout.width, sugarloaf.layout.height),
-            sugarloaf.layout.scale_factor,
-            context_manager.titles.key.as_str(),
-            &context_manager.titles.titles,
-            context_manager.current_index(),
-            context_manager.len(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rio_config::colors::ColorRgb;
+
+    #[test]
+    fn test_inverse_and_reverse_video() {
+        let config = Rc::new(Config::default());
+        let mut state = State::new(&config, None);
+        let foreground = state.named_colors.foreground;
+        let background = state.named_colors.background.0;
+
+        let mut square = Square::default();
+        assert_eq!(state.compute_colors(&square), (foreground, background));
+
+        square.flags.insert(Flags::INVERSE);
+        assert_eq!(state.compute_colors(&square), (background, foreground));
+
+        // Reverse video swaps the defaults, inverse squares swap them back.
+        assert!(state.set_reverse_video(true));
+        assert!(!state.set_reverse_video(true));
+        assert_eq!(state.compute_colors(&square), (foreground, background));
+        square.flags.remove(Flags::INVERSE);
+        assert_eq!(state.compute_colors(&square), (background, foreground));
+
+        // Explicit colors are not affected by reverse video.
+        square.fg = AnsiColor::Named(NamedColor::Red);
+        assert_eq!(state.compute_colors(&square).0, state.named_colors.red);
+    }
+
+    #[test]
+    fn test_text_blink_phase() {
+        let mut config = Config::default();
+        config.blinking_text.timeout = 2;
+        let state = State::new(&Rc::new(config), None);
+
+        let phase = |millis| state.text_blink_phase(Duration::from_millis(millis));
+        assert_eq!(phase(0), (true, Some(Duration::from_millis(800))));
+        assert_eq!(phase(900), (false, Some(Duration::from_millis(700))));
+        assert_eq!(phase(1700), (true, Some(Duration::from_millis(700))));
+        // Blinking stops with the text visible after the timeout.
+        assert_eq!(phase(2000), (true, None));
+        assert_eq!(state.next_text_blink(), None);
+    }
+
+    #[test]
+    fn test_bell_intensity() {
+        let mut config = Config::default();
+        let state = State::new(&Rc::new(config.clone()), None);
+        assert_eq!(state.bell_intensity(Duration::ZERO), 0.0);
+
+        config.bell.duration = 100;
+        config.bell.animation = rio_config::bell::BellAnimation::Linear;
+        config.reduce_motion = rio_config::ReduceMotion::Never;
+        let mut state = State::new(&Rc::new(config.clone()), None);
+        assert_eq!(state.next_bell_frame(), None);
+        state.ring_bell();
+        assert_eq!(state.next_bell_frame(), Some(BELL_FRAME_INTERVAL));
+
+        let intensity = |millis| state.bell_intensity(Duration::from_millis(millis));
+        assert_eq!(intensity(0), 1.0);
+        assert_eq!(intensity(25), 0.75);
+        assert_eq!(intensity(100), 0.0);
+        assert_eq!(intensity(500), 0.0);
+
+        // Shown whole until it ends with reduced motion.
+        config.reduce_motion = rio_config::ReduceMotion::Always;
+        config.blinking_cursor = true;
+        let mut state = State::new(&Rc::new(config), None);
+        assert!(!state.has_blinking_enabled);
+        assert_eq!(state.bell_intensity(Duration::from_millis(75)), 1.0);
+        assert_eq!(state.bell_intensity(Duration::from_millis(100)), 0.0);
+        state.ring_bell();
+        assert!(state.next_bell_frame().unwrap() > BELL_FRAME_INTERVAL);
+    }
+
+    #[test]
+    fn test_flash_damage() {
+        let mut state = State::new(&Rc::new(Config::default()), None);
+        // Frames without damage do not keep animating.
+        state.flash_damage(TermDamage::Partial(vec![]));
+        assert_eq!(state.next_damage_frame(), None);
+        state.flash_damage(TermDamage::Full);
+        assert_eq!(state.next_damage_frame(), Some(BELL_FRAME_INTERVAL));
+    }
+
+    #[test]
+    fn test_term_colors() {
+        let mut state = State::new(&Rc::new(Config::default()), None);
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let mut colors = TermColors::default();
+        colors[NamedColor::Background] = Some(red);
+
+        assert!(state.set_term_colors(colors));
+        assert!(!state.set_term_colors(colors));
+        assert_eq!(state.compute_colors(&Square::default()).1, red);
+        assert_eq!(state.window_background().r, 1.0);
+        // Queries answer with the configured color once reset.
+        assert_eq!(
+            state.default_color(NamedColor::Background as usize),
+            state.named_colors.background.0
         );
+    }
 
-        sugarloaf.pile_rects(self.navigation.rects.clone());
+    #[test]
+    fn test_palette_override() {
+        let mut state = State::new(&Rc::new(Config::default()), None);
+        let green = [0.0, 1.0, 0.0, 1.0];
+        let mut colors = TermColors::default();
+        colors[NamedColor::Red] = Some(green);
+        colors[100] = Some(green);
+        state.set_term_colors(colors);
 
-        for text in self.navigation.texts.iter() {
-            sugarloaf.text(
-                text.position,
-                text.content.to_owned(),
-                text.font_id,
-                text.font_size,
-                text.color,
-                true,
-            );
-        }
+        let mut square = Square {
+            fg: AnsiColor::Named(NamedColor::Red),
+            bg: AnsiColor::Indexed(100),
+            ..Default::default()
+        };
+        assert_eq!(state.compute_colors(&square), (green, green));
+
+        square.fg = AnsiColor::Indexed(101);
+        assert_eq!(state.compute_colors(&square).0, state.colors[101]);
+    }
+
+    #[test]
+    fn test_text_attributes() {
+        let mut config = Config::default();
+        config.text_attributes.bold_as_bright = true;
+        config.text_attributes.dim_multiplier = 0.5;
+        let state = State::new(&Rc::new(config), None);
+
+        let mut square = Square {
+            fg: AnsiColor::Named(NamedColor::Red),
+            ..Default::default()
+        };
+        square.flags.insert(Flags::BOLD);
+        assert_eq!(
+            state.compute_fg_color(&square),
+            state.named_colors.light_red
+        );
+        square.fg = AnsiColor::Indexed(2);
+        assert_eq!(state.compute_fg_color(&square), state.colors[10]);
+        square.fg = AnsiColor::Indexed(9);
+        assert_eq!(state.compute_fg_color(&square), state.colors[9]);
+
+        square.flags = Flags::DIM;
+        square.fg = AnsiColor::Named(NamedColor::Foreground);
+        assert_eq!(
+            state.compute_fg_color(&square),
+            state.named_colors.dim_foreground
+        );
+        square.fg = AnsiColor::Spec(ColorRgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        });
+        assert_eq!(state.compute_fg_color(&square), [0.5, 0.5, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_cursor_colors() {
+        let mut state = State::new(&Rc::new(Config::default()), None);
+        let square = Square::default();
+        let cursor = state.create_cursor(&square);
+        assert_eq!(cursor.foreground_color, state.named_colors.background.0);
+        assert_eq!(
+            cursor.decoration.map(|decoration| decoration.color),
+            Some(state.named_colors.cursor)
+        );
+
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let blue = [0.0, 0.0, 1.0, 1.0];
+        state.named_colors.cursor_text = Some(red);
+        let mut colors = TermColors::default();
+        colors[NamedColor::Cursor] = Some(blue);
+        state.set_term_colors(colors);
+
+        let cursor = state.create_cursor(&square);
+        assert_eq!(cursor.foreground_color, red);
+        assert_eq!(
+            cursor.decoration.map(|decoration| decoration.color),
+            Some(blue)
+        );
+    }
+
+    #[test]
+    fn test_selection_colors() {
+        let mut config = Config::default();
+        let mut state = State::new(&Rc::new(config.clone()), None);
+        let mut square = Square {
+            fg: AnsiColor::Named(NamedColor::Red),
+            ..Default::default()
+        };
+        square.flags.insert(Flags::BOLD);
+
+        let sugar = state.create_selected_sugar(&square);
+        assert_eq!(
+            sugar.foreground_color,
+            state.named_colors.selection_foreground
+        );
+        assert_eq!(
+            sugar.background_color,
+            state.named_colors.selection_background
+        );
+        assert!(sugar.style.map_or(false, |style| style.is_bold));
+
+        config.selection.invert = true;
+        state = State::new(&Rc::new(config), None);
+        let sugar = state.create_selected_sugar(&square);
+        assert_eq!(sugar.foreground_color, state.named_colors.background.0);
+        assert_eq!(sugar.background_color, state.named_colors.red);
     }
 }
@@ -1,3 +1,4 @@
+use rio_config::window::Decorations;
 use rio_config::Config;
 use std::rc::Rc;
 use winit::window::{CursorIcon, Fullscreen, Icon, ImePurpose, Window, WindowBuilder};
@@ -27,7 +28,7 @@ pub fn create_window_builder(
             height: DEFAULT_MINIMUM_WINDOW_HEIGHT,
         })
         .with_resizable(true)
-        .with_decorations(true)
+        .with_decorations(config.window.decorations != Decorations::Disabled)
         .with_window_icon(Some(icon));
 
     #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
@@ -47,11 +48,14 @@ pub fn create_window_builder(
     #[cfg(target_os = "macos")]
     {
         use winit::platform::macos::WindowBuilderExtMacOS;
-        window_builder = window_builder
-            .with_title_hidden(true)
-            .with_titlebar_transparent(true)
-            .with_transparent(true)
-            .with_fullsize_content_view(true);
+        window_builder = window_builder.with_transparent(true);
+
+        if config.window.decorations != Decorations::Enabled {
+            window_builder = window_builder
+                .with_title_hidden(true)
+                .with_titlebar_transparent(true)
+                .with_fullsize_content_view(true);
+        }
 
         if config.navigation.is_native() {
             window_builder = window_builder
@@ -63,7 +67,9 @@ pub fn create_window_builder(
             }
         }
 
-        if config.navigation.macos_hide_window_buttons {
+        if config.navigation.macos_hide_window_buttons
+            || config.window.decorations == Decorations::Buttonless
+        {
             window_builder = window_builder.with_titlebar_buttons_hidden(true);
         }
     }
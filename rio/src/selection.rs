@@ -335,6 +335,12 @@ impl Selection {
             }
         }
 
+        if start == end && term.smart_selection {
+            if let Some(range) = Self::range_shell_token(term, start) {
+                return range;
+            }
+        }
+
         let start = term.semantic_search_left(start);
         let end = term.semantic_search_right(end);
 
@@ -345,6 +351,83 @@ impl Selection {
         }
     }
 
+    /// Find the shell token under `point`, expanding over quoted strings
+    /// and `--long-options` regardless of the configured word separators.
+    fn range_shell_token<T: EventListener>(
+        term: &Crosswords<T>,
+        point: Pos,
+    ) -> Option<SelectionRange> {
+        let line_start = term.row_search_left(point);
+        let line_end = term.row_search_right(point);
+
+        // Collect the whole logical line, ignoring wide char spacers.
+        let spacers = Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER;
+        let mut cells = Vec::new();
+        for row in line_start.row.0..=line_end.row.0 {
+            let row = Line(row);
+            for col in 0..term.grid.columns() {
+                let cell = &term.grid[row][Column(col)];
+                if !cell.flags.intersects(spacers) {
+                    cells.push((Pos::new(row, Column(col)), cell.c));
+                }
+            }
+        }
+
+        let index = cells.iter().rposition(|(pos, _)| *pos <= point)?;
+        if cells[index].1.is_whitespace() {
+            return None;
+        }
+
+        let range = |start: usize, end: usize| SelectionRange {
+            start: cells[start].0,
+            end: cells[end].0,
+            is_block: false,
+        };
+
+        // Quoted strings, following shell escaping rules.
+        let mut quote: Option<(char, usize)> = None;
+        let mut escaped = false;
+        for (i, (_, c)) in cells.iter().enumerate() {
+            if escaped {
+                escaped = false;
+                continue;
+            }
+
+            match (quote, *c) {
+                (Some(('"', _)), '\\') | (None, '\\') => escaped = true,
+                (Some((open, start)), c) if c == open => {
+                    if start <= index && index <= i {
+                        return Some(range(start, i));
+                    }
+                    quote = None;
+                }
+                (None, '"') | (None, '\'') => {
+                    if i > index {
+                        break;
+                    }
+                    quote = Some((*c, i));
+                }
+                _ => (),
+            }
+        }
+
+        // Long options like `--color=always`.
+        let mut start = index;
+        while start > 0 && !cells[start - 1].1.is_whitespace() {
+            start -= 1;
+        }
+        let mut end = index;
+        while end + 1 < cells.len() && !cells[end + 1].1.is_whitespace() {
+            end += 1;
+        }
+
+        if end > start + 1 && cells[start].1 == '-' && cells[start + 1].1 == '-' {
+            return Some(range(start, end));
+        }
+
+        None
+    }
+
     fn range_lines<T: EventListener>(
         term: &Crosswords<T>,
         start: Pos,
@@ -806,4 +889,46 @@ mod tests {
         assert!(!selection.intersects_range(..=Line(2)));
         assert!(!selection.intersects_range(Line(7)..=Line(8)));
     }
+
+    fn term_with_text(text: &str) -> Crosswords<VoidListener> {
+        let mut term = term(1, text.chars().count());
+        for (i, c) in text.chars().enumerate() {
+            term.grid[Line(0)][Column(i)].c = c;
+        }
+        term
+    }
+
+    fn semantic_range(term: &Crosswords<VoidListener>, col: usize) -> (usize, usize) {
+        let location = Pos::new(Line(0), Column(col));
+        let selection = Selection::new(SelectionType::Semantic, location, Side::Left);
+        let range = selection.to_range(term).unwrap();
+        (range.start.col.0, range.end.col.0)
+    }
+
+    #[test]
+    fn semantic_custom_word_separators() {
+        let mut term = term_with_text("foo-bar baz");
+        assert_eq!(semantic_range(&term, 1), (0, 6));
+
+        term.set_semantic_escape_chars(" -");
+        assert_eq!(semantic_range(&term, 1), (0, 2));
+        assert_eq!(semantic_range(&term, 5), (4, 6));
+    }
+
+    #[test]
+    fn semantic_smart_shell_tokens() {
+        let mut term = term_with_text(r#"git commit -m "fix the bug" --author=me"#);
+        term.set_semantic_escape_chars(",│`|:\"' ()[]{}<>\t=-");
+
+        // Without smart selection the quoted string is split into words.
+        assert_eq!(semantic_range(&term, 19), (19, 21));
+        assert_eq!(semantic_range(&term, 32), (30, 35));
+
+        term.smart_selection = true;
+        assert_eq!(semantic_range(&term, 19), (14, 26));
+        assert_eq!(semantic_range(&term, 14), (14, 26));
+        assert_eq!(semantic_range(&term, 32), (28, 38));
+        // Regular words keep using the word separators.
+        assert_eq!(semantic_range(&term, 5), (4, 9));
+    }
 }
@@ -1,30 +1,64 @@
 use crate::clipboard::ClipboardType;
 use crate::event::{ClickState, EventP, EventProxy, RioEvent, RioEventType};
 use crate::ime::Preedit;
+use crate::remote;
+use crate::router::assistant::{AssistantReport, AssistantReportLevel, ErrorReport};
 use crate::router::{RoutePath, RouteWindow, Router};
 use crate::scheduler::{Scheduler, TimerId, Topic};
-use crate::watch::watch;
+use crate::startup;
+use crate::watch::{watch, watch_includes};
 use rio_config::colors::ColorRgb;
+use rio_config::window::Decorations;
 use std::error::Error;
 use std::rc::Rc;
 use std::time::{Duration, Instant};
+use winit::dpi::LogicalSize;
 use winit::event::{
     ElementState, Event, Ime, MouseButton, MouseScrollDelta, StartCause, TouchPhase,
     WindowEvent,
 };
 use winit::event_loop::ControlFlow;
-use winit::event_loop::{DeviceEvents, EventLoop};
+use winit::event_loop::{DeviceEvents, EventLoop, EventLoopWindowTarget};
 #[cfg(target_os = "macos")]
 use winit::platform::macos::EventLoopWindowTargetExtMacOS;
 #[cfg(target_os = "macos")]
 use winit::platform::macos::WindowExtMacOS;
 use winit::platform::run_ondemand::EventLoopExtRunOnDemand;
-use winit::window::{CursorIcon, Fullscreen};
+use winit::window::{CursorIcon, Fullscreen, UserAttentionType};
+
+/// Minimum interval between frames driven by PTY output while the window
+/// is not focused.
+const UNFOCUSED_RENDER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Minimum interval between frames driven by PTY output with the low power
+/// profile.
+const LOW_POWER_RENDER_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Time without resize events after which a deferred resize computes the
+/// new grid.
+const RESIZE_SETTLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Interval between the scrolls of a selection dragged over an edge.
+const SELECTION_SCROLLING_INTERVAL: Duration = Duration::from_millis(15);
 
 pub struct Sequencer {
     config: Rc<rio_config::Config>,
     event_proxy: Option<EventProxy>,
     router: Router,
+    config_watcher: Option<notify::RecommendedWatcher>,
+    // Path of the screenshot taken once the command exits.
+    screenshot: Option<String>,
+    // Windows waiting for an activation token before being created.
+    #[cfg(all(
+        any(feature = "x11", feature = "wayland"),
+        not(any(target_os = "macos", windows))
+    ))]
+    activation_requests: Vec<winit::event_loop::AsyncRequestSerial>,
+    safe_mode: bool,
+    memory: crate::memory::Accountant,
+    #[cfg(all(feature = "wayland-ipc", not(any(target_os = "macos", windows))))]
+    workspaces: crate::platform::compositor::Workspaces,
+    hotkey: Option<crate::platform::hotkey::Hotkey>,
 }
 
 impl Sequencer {
@@ -41,6 +75,182 @@ impl Sequencer {
             config: Rc::new(config),
             event_proxy: None,
             router,
+            config_watcher: None,
+            screenshot: None,
+            #[cfg(all(
+                any(feature = "x11", feature = "wayland"),
+                not(any(target_os = "macos", windows))
+            ))]
+            activation_requests: Vec::new(),
+            safe_mode: false,
+            memory: crate::memory::Accountant::default(),
+            #[cfg(all(feature = "wayland-ipc", not(any(target_os = "macos", windows))))]
+            workspaces: Default::default(),
+            hotkey: None,
+        }
+    }
+
+    /// Keep the default configuration, config changes are ignored until
+    /// Rio is restarted.
+    pub fn set_safe_mode(&mut self, crash_report: Option<String>) {
+        self.safe_mode = true;
+        self.router.propagate_error_to_next_route(ErrorReport {
+            report: AssistantReport::SafeMode(crash_report),
+            level: AssistantReportLevel::Warning,
+        });
+    }
+
+    /// Save a screenshot to `path` when the command exits.
+    pub fn set_screenshot(&mut self, path: String) {
+        self.screenshot = Some(path);
+    }
+
+    /// Grab the global hotkey of the config, releasing the previous one.
+    fn register_hotkey(&mut self) {
+        self.hotkey = None;
+        if let Some(hotkey) = &self.config.global_hotkey {
+            match crate::platform::hotkey::register(
+                hotkey,
+                self.event_proxy.clone().unwrap(),
+            ) {
+                Ok(hotkey) => self.hotkey = Some(hotkey),
+                Err(err) => log::warn!("unable to register the global hotkey: {err}"),
+            }
+        }
+    }
+
+    /// Apply the workspace rules to a window focused on another workspace
+    /// than the last time, when the rules that match differ.
+    #[cfg(all(feature = "wayland-ipc", not(any(target_os = "macos", windows))))]
+    fn follow_workspace(&mut self, window_id: winit::window::WindowId) {
+        let Some((previous, workspace)) = self.workspaces.focus(window_id) else {
+            return;
+        };
+        let rules = |workspace: &crate::platform::compositor::Workspace| {
+            self.config
+                .workspace_rules_for(&workspace.name, &workspace.output)
+        };
+        if previous.as_ref().map(rules).unwrap_or_default() != rules(&workspace) {
+            self.apply_workspace_rules(window_id, &workspace);
+        }
+    }
+
+    #[cfg(all(feature = "wayland-ipc", not(any(target_os = "macos", windows))))]
+    fn apply_workspace_rules(
+        &mut self,
+        window_id: winit::window::WindowId,
+        workspace: &crate::platform::compositor::Workspace,
+    ) {
+        let Some(route) = self.router.routes.get_mut(&window_id) else {
+            return;
+        };
+        let mut config = (*self.config).clone();
+        let rules = config.workspace_rules_for(&workspace.name, &workspace.output);
+        if let Err(err) = config.apply_workspace_rules(&rules) {
+            log::warn!(
+                "unable to apply the rules of workspace {}: {err}",
+                workspace.name
+            );
+        }
+        route.update_config(&config.into(), &self.router.font_database);
+        route.redraw();
+    }
+
+    /// Run a remote control command on the focused window, or on any window
+    /// when none is focused.
+    fn remote_control(
+        &mut self,
+        command: &remote::Command,
+        event_loop: &EventLoopWindowTarget<EventP>,
+    ) -> remote::Response {
+        if let remote::Command::NewWindow = command {
+            self.router.create_window(
+                event_loop,
+                self.event_proxy.clone().unwrap(),
+                &self.config,
+                None,
+            );
+            return remote::Response::ok(None);
+        }
+
+        let route = match self
+            .router
+            .routes
+            .values_mut()
+            .max_by_key(|route| route.window.is_focused)
+        {
+            Some(route) => route,
+            None => return remote::Response::error("no window"),
+        };
+
+        match command {
+            remote::Command::GetText { scrollback } => {
+                remote::Response::ok(Some(route.window.screen.text(*scrollback)))
+            }
+            remote::Command::SendText { text } => {
+                route
+                    .window
+                    .screen
+                    .ctx_mut()
+                    .current_mut()
+                    .messenger
+                    .send_bytes(text.clone().into_bytes());
+                remote::Response::ok(None)
+            }
+            remote::Command::SetColors {
+                foreground,
+                background,
+                cursor,
+            } => match remote::color_changes(foreground, background, cursor) {
+                Ok(colors) => {
+                    route.window.screen.set_colors(&colors);
+                    route.redraw();
+                    remote::Response::ok(None)
+                }
+                Err(message) => remote::Response::error(message),
+            },
+            remote::Command::Resize { columns, lines } => {
+                let (width, height) = route
+                    .window
+                    .screen
+                    .sugarloaf
+                    .layout
+                    .size_for(*columns, *lines);
+                // Some platforms resize right away without a resize event.
+                if let Some(size) = route
+                    .window
+                    .winit_window
+                    .request_inner_size(LogicalSize::new(width, height))
+                {
+                    route.window.screen.resize(size);
+                    route.window.screen.finish_resize();
+                    route.redraw();
+                }
+                remote::Response::ok(None)
+            }
+            remote::Command::RenameTab { tab, name } => {
+                let context_manager = route.window.screen.ctx_mut();
+                let index = tab.unwrap_or_else(|| context_manager.current_index());
+                let name = Some(name.trim().to_string()).filter(|name| !name.is_empty());
+                if context_manager.rename_context(index, name) {
+                    route.redraw();
+                    remote::Response::ok(None)
+                } else {
+                    remote::Response::error(format!("no tab {index}"))
+                }
+            }
+            remote::Command::ClearScreen => {
+                route.window.screen.clear_screen();
+                route.redraw();
+                remote::Response::ok(None)
+            }
+            remote::Command::ClearScrollback => {
+                route.window.screen.clear_history();
+                route.redraw();
+                remote::Response::ok(None)
+            }
+            // Created above, it does not need a window.
+            remote::Command::NewWindow => unreachable!(),
         }
     }
 
@@ -50,12 +260,44 @@ impl Sequencer {
     ) -> Result<(), Box<dyn Error>> {
         let proxy = event_loop.create_proxy();
         self.event_proxy = Some(EventProxy::new(proxy.clone()));
-        let _ = watch(
+        self.config_watcher = watch(
             rio_config::config_dir_path(),
             self.event_proxy.clone().unwrap(),
-        );
+        )
+        .ok();
+        if let Some(watcher) = self.config_watcher.as_mut() {
+            watch_includes(watcher, &self.config.includes);
+        }
         let mut scheduler = Scheduler::new(proxy);
 
+        if self.config.performance == rio_config::Performance::Auto {
+            crate::platform::power::watch(self.event_proxy.clone().unwrap());
+        }
+        if self.config.reduce_motion == rio_config::ReduceMotion::Auto {
+            crate::platform::motion::watch(self.event_proxy.clone().unwrap());
+        }
+
+        #[cfg(all(feature = "wayland-ipc", not(any(target_os = "macos", windows))))]
+        if !self.config.workspace_rules.is_empty() {
+            crate::platform::compositor::listen(self.event_proxy.clone().unwrap());
+        }
+
+        // Exported before the first shell is spawned.
+        #[cfg(unix)]
+        if !self.config.remote_control.allow.is_empty() {
+            let path = remote::socket_path();
+            match remote::listen(
+                path.clone(),
+                self.config.remote_control.allow.clone(),
+                self.event_proxy.clone().unwrap(),
+            ) {
+                Ok(()) => std::env::set_var(remote::SOCKET_ENV, path),
+                Err(err) => {
+                    log::error!("unable to create the remote control socket: {err}")
+                }
+            }
+        }
+
         let window =
             RouteWindow::new(&event_loop, &self.config, &self.router.font_database)
                 .await?;
@@ -74,6 +316,55 @@ impl Sequencer {
                                 if let Some(route) =
                                     self.router.routes.get_mut(&window_id)
                                 {
+                                    // Nothing is visible, the next un-occlusion
+                                    // will redraw with the latest state.
+                                    if route.window.is_occluded {
+                                        return;
+                                    }
+
+                                    // Unfocused windows and the low power
+                                    // profile are coalesced into a lower redraw
+                                    // rate instead of one frame per PTY read.
+                                    let interval = if !route.window.is_focused {
+                                        Some(UNFOCUSED_RENDER_INTERVAL)
+                                    } else if route.window.screen.is_low_latency() {
+                                        // Render right away instead of waiting
+                                        // for the next redraw request.
+                                        if route.path == RoutePath::Terminal {
+                                            route.window.screen.render();
+                                            return;
+                                        }
+                                        None
+                                    } else if route.window.screen.is_low_power() {
+                                        Some(LOW_POWER_RENDER_INTERVAL)
+                                    } else {
+                                        None
+                                    };
+
+                                    if let Some(interval) = interval {
+                                        let timer_id = TimerId::new(Topic::Render, u64::from(window_id));
+                                        if !scheduler.scheduled(timer_id) {
+                                            scheduler.schedule(
+                                                EventP::new(
+                                                    RioEventType::Rio(RioEvent::Render),
+                                                    window_id,
+                                                ),
+                                                interval,
+                                                false,
+                                                timer_id,
+                                            );
+                                        }
+                                        return;
+                                    }
+
+                                    route.redraw();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::Bell) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.window.screen.ring_bell();
                                     route.redraw();
                                 }
                             }
@@ -89,6 +380,54 @@ impl Sequencer {
                                     route.redraw();
                                 }
                             }
+                            RioEventType::Rio(RioEvent::ConfirmPaste) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.path = RoutePath::ConfirmPaste;
+                                    route.redraw();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::OpenPager) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.path = RoutePath::Pager;
+                                    route.redraw();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::OpenQuickSelect) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.path = RoutePath::QuickSelect;
+                                    route.redraw();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::OpenRenameTab) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.path = RoutePath::RenameTab;
+                                    route.redraw();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::OpenContextMenu) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.path = RoutePath::ContextMenu;
+                                    route.redraw();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::OpenGlyphAtlas) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.path = RoutePath::GlyphAtlas;
+                                    route.redraw();
+                                }
+                            }
                             RioEventType::Rio(RioEvent::ReportToAssistant(error)) => {
                                 if let Some(route) =
                                     self.router.routes.get_mut(&window_id)
@@ -96,10 +435,13 @@ impl Sequencer {
                                     route.report_error(&error);
                                 }
                             }
-                            RioEventType::Rio(RioEvent::UpdateConfig) => {
+                            // Safe mode keeps the default configuration.
+                            RioEventType::Rio(RioEvent::UpdateConfig)
+                                if !self.safe_mode =>
+                            {
                                 let mut config_error: Option<rio_config::ConfigError> =
                                     None;
-                                let config = match rio_config::Config::try_load() {
+                                let mut config = match rio_config::Config::try_load() {
                                     Ok(config) => config,
                                     Err(error) => {
                                         config_error = Some(error);
@@ -107,7 +449,39 @@ impl Sequencer {
                                     }
                                 };
 
+                                // Keep the profile selected with --profile.
+                                if let Some(profile) = &self.config.profile {
+                                    if let Err(err) = config.apply_profile(profile) {
+                                        log::warn!("unable to apply profile: {err}");
+                                    }
+                                }
+
+                                crate::logger::set_config_level(
+                                    &config.developer.log_level,
+                                );
+                                let hotkey_changed =
+                                    config.global_hotkey != self.config.global_hotkey;
+                                if let Some(watcher) = self.config_watcher.as_mut() {
+                                    watch_includes(watcher, &config.includes);
+                                }
                                 self.config = config.into();
+                                if hotkey_changed {
+                                    self.register_hotkey();
+                                }
+                                if self.config.performance
+                                    == rio_config::Performance::Auto
+                                {
+                                    crate::platform::power::watch(
+                                        self.event_proxy.clone().unwrap(),
+                                    );
+                                }
+                                if self.config.reduce_motion
+                                    == rio_config::ReduceMotion::Auto
+                                {
+                                    crate::platform::motion::watch(
+                                        self.event_proxy.clone().unwrap(),
+                                    );
+                                }
                                 for (_id, route) in self.router.routes.iter_mut() {
                                     route.update_config(
                                         &self.config,
@@ -122,13 +496,44 @@ impl Sequencer {
 
                                     route.redraw();
                                 }
+
+                                // Windows on a workspace with rules get them back.
+                                #[cfg(all(feature = "wayland-ipc", not(any(target_os = "macos", windows))))]
+                                {
+                                    let windows: Vec<_> = self
+                                        .workspaces
+                                        .windows()
+                                        .filter(|(_, workspace)| {
+                                            !self
+                                                .config
+                                                .workspace_rules_for(
+                                                    &workspace.name,
+                                                    &workspace.output,
+                                                )
+                                                .is_empty()
+                                        })
+                                        .map(|(id, workspace)| (*id, workspace.clone()))
+                                        .collect();
+                                    for (window_id, workspace) in windows {
+                                        self.apply_workspace_rules(window_id, &workspace);
+                                    }
+                                }
                             }
                             RioEventType::Rio(RioEvent::Exit) => {
                                 if let Some(route) =
                                     self.router.routes.get_mut(&window_id)
                                 {
+                                    if let Some(path) = self.screenshot.take() {
+                                        if let Err(err) =
+                                            route.window.screen.save_screenshot(&path)
+                                        {
+                                            eprintln!("unable to save {path}: {err}");
+                                        }
+                                    }
+
                                     if !route.try_close_existent_tab() {
                                         self.router.routes.remove(&window_id);
+                                        scheduler.unschedule_window(u64::from(window_id));
 
                                         if self.router.routes.is_empty() {
                                             *control_flow =
@@ -144,8 +549,40 @@ impl Sequencer {
                                     route.window.screen.render();
                                 }
                             }
+                            RioEventType::Rio(RioEvent::SelectionScrolling) => {
+                                let scrolled = match self.router.routes.get_mut(&window_id)
+                                {
+                                    Some(route) => {
+                                        let scrolled = route
+                                            .window
+                                            .screen
+                                            .continue_selection_scrolling();
+                                        if scrolled {
+                                            route.redraw();
+                                        }
+                                        scrolled
+                                    }
+                                    None => false,
+                                };
+
+                                if !scrolled {
+                                    scheduler.unschedule(TimerId::new(
+                                        Topic::SelectionScrolling,
+                                        0,
+                                    ));
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::FinishResize) => {
+                                // The timer is shared, every window resized
+                                // meanwhile is settled.
+                                for route in self.router.routes.values_mut() {
+                                    if route.window.screen.finish_resize() {
+                                        route.redraw();
+                                    }
+                                }
+                            }
                             RioEventType::Rio(RioEvent::PrepareRender(millis)) => {
-                                let timer_id = TimerId::new(Topic::Render, 0);
+                                let timer_id = TimerId::new(Topic::Render, u64::from(window_id));
                                 let event = EventP::new(
                                     RioEventType::Rio(RioEvent::Render),
                                     window_id,
@@ -244,22 +681,109 @@ impl Sequencer {
                                         .send_bytes(text.into_bytes());
                                 }
                             }
+                            RioEventType::Rio(RioEvent::MonitorAlert) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    if route.window.screen.monitor_alert()
+                                        && !route.window.is_focused
+                                    {
+                                        route.window.winit_window.request_user_attention(
+                                            Some(UserAttentionType::Informational),
+                                        );
+                                    }
+                                    route.redraw();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::RemoteSession(_)) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.redraw();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::PowerSource) => {
+                                for route in self.router.routes.values_mut() {
+                                    route.window.screen.update_power_source();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::ReduceMotion) => {
+                                for route in self.router.routes.values_mut() {
+                                    route.window.screen.update_reduce_motion();
+                                    route.redraw();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::Progress) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.window.screen.ctx_mut().update_progress();
+                                    route.redraw();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::RequestAttention) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    if !route.window.is_focused {
+                                        route.window.winit_window.request_user_attention(
+                                            Some(UserAttentionType::Informational),
+                                        );
+                                    }
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::RunCommand(command)) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    let mut args = command.split_whitespace();
+                                    if let Some(program) = args.next() {
+                                        let args: Vec<&str> = args.collect();
+                                        route.window.screen.exec(program, &args);
+                                    }
+                                }
+                            }
+                            #[cfg(all(feature = "wayland-ipc", not(any(target_os = "macos", windows))))]
+                            RioEventType::Rio(RioEvent::Workspace(workspace)) => {
+                                self.workspaces.current = Some(workspace);
+                                let focused = self
+                                    .router
+                                    .routes
+                                    .iter()
+                                    .find(|(_, route)| route.window.is_focused)
+                                    .map(|(id, _)| *id);
+                                if let Some(window_id) = focused {
+                                    self.follow_workspace(window_id);
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::TmuxControl(control)) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.window.screen.tmux_control(control);
+                                    route.redraw();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::RemoteControl(request)) => {
+                                let response = self.remote_control(
+                                    &request.command,
+                                    event_loop_window_target,
+                                );
+                                request.reply(response);
+                            }
                             RioEventType::Rio(RioEvent::ColorRequest(index, format)) => {
-                                // TODO: colors could be coming terminal as well
-                                // if colors has been declaratively changed
-                                // Rio doesn't cover this case yet.
-                                //
-                                // In the future should try first get
-                                // from Crosrouteords then state colors
-                                // screen.colors()[index] or screen.state.colors[index]
+                                // Colors set by the terminal take precedence
+                                // over the configured ones.
                                 if let Some(route) =
                                     self.router.routes.get_mut(&window_id)
                                 {
-                                    let color = route.window.screen.state.colors[index];
+                                    let screen = &mut route.window.screen;
+                                    let color =
+                                        screen.colors()[index].unwrap_or_else(|| {
+                                            screen.state.default_color(index)
+                                        });
                                     let rgb = ColorRgb::from_color_arr(color);
-                                    route
-                                        .window
-                                        .screen
+                                    screen
                                         .ctx_mut()
                                         .current_mut()
                                         .messenger
@@ -267,10 +791,27 @@ impl Sequencer {
                                 }
                             }
                             RioEventType::Rio(RioEvent::CreateWindow) => {
+                                // Ask for a token so the new window gets the
+                                // focus, it is created once the token is done.
+                                #[cfg(all(
+                                    any(feature = "x11", feature = "wayland"),
+                                    not(any(target_os = "macos", windows))
+                                ))]
+                                if let Some(route) = self.router.routes.get(&window_id) {
+                                    use winit::platform::startup_notify::WindowExtStartupNotify;
+                                    if let Ok(serial) =
+                                        route.window.winit_window.request_activation_token()
+                                    {
+                                        self.activation_requests.push(serial);
+                                        return;
+                                    }
+                                }
+
                                 self.router.create_window(
                                     event_loop_window_target,
                                     self.event_proxy.clone().unwrap(),
                                     &self.config,
+                                    None,
                                 );
                             }
                             #[cfg(target_os = "macos")]
@@ -306,6 +847,7 @@ impl Sequencer {
                                 {
                                     if route.window.winit_window.num_tabs() > 1 {
                                         self.router.routes.remove(&window_id);
+                                        scheduler.unschedule_window(u64::from(window_id));
                                     }
                                 }
                             }
@@ -348,6 +890,24 @@ impl Sequencer {
                                     route.window.winit_window.select_previous_tab();
                                 }
                             }
+                            RioEventType::Rio(RioEvent::ToggleVisibility) => {
+                                let is_focused = self
+                                    .router
+                                    .routes
+                                    .values()
+                                    .any(|route| route.window.is_focused);
+                                for route in self.router.routes.values() {
+                                    route.window.winit_window.set_visible(!is_focused);
+                                }
+
+                                if !is_focused {
+                                    if let Some(route) =
+                                        self.router.routes.values().next()
+                                    {
+                                        route.window.winit_window.focus_window();
+                                    }
+                                }
+                            }
                             #[cfg(target_os = "macos")]
                             RioEventType::Rio(RioEvent::Hide) => {
                                 event_loop_window_target.hide_application();
@@ -382,9 +942,13 @@ impl Sequencer {
                     }
 
                     Event::NewEvents(StartCause::Init) => {
+                        self.register_hotkey();
+
                         #[cfg(target_os = "macos")]
                         {
-                            crate::ui::appkit::create_toolbar();
+                            crate::ui::appkit::create_toolbar(
+                                self.config.navigation.is_native(),
+                            );
                         }
                     }
 
@@ -396,6 +960,7 @@ impl Sequencer {
                         ..
                     } => {
                         self.router.routes.remove(&window_id);
+                        scheduler.unschedule_window(u64::from(window_id));
 
                         if self.router.routes.is_empty() {
                             *control_flow = winit::event_loop::ControlFlow::Exit;
@@ -418,7 +983,9 @@ impl Sequencer {
                         ..
                     } => {
                         if let Some(route) = self.router.routes.get_mut(&window_id) {
-                            if route.path != RoutePath::Terminal {
+                            if route.path != RoutePath::Terminal
+                                && route.path != RoutePath::ContextMenu
+                            {
                                 return;
                             }
 
@@ -438,6 +1005,53 @@ impl Sequencer {
                                 _ => (),
                             }
 
+                            if route.path == RoutePath::ContextMenu {
+                                if state == ElementState::Pressed
+                                    && !route.window.screen.context_menu_click()
+                                {
+                                    route.path = RoutePath::Terminal;
+                                }
+                                route.redraw();
+                                return;
+                            }
+
+                            if state == ElementState::Pressed
+                                && button == MouseButton::Left
+                                && route.window.screen.is_new_lines_pill()
+                            {
+                                route.window.screen.mouse.left_button_state =
+                                    ElementState::Released;
+                                route.window.screen.scroll_to_bottom();
+                                route.redraw();
+                                return;
+                            }
+
+                            if state == ElementState::Pressed
+                                && button == MouseButton::Left
+                                && route.window.screen.click_prompt_mark()
+                            {
+                                route.window.screen.mouse.left_button_state =
+                                    ElementState::Released;
+                                route.redraw();
+                                return;
+                            }
+
+                            if state == ElementState::Pressed
+                                && button == MouseButton::Left
+                                && self.config.window.decorations == Decorations::Disabled
+                                && route.window.screen.is_drag_region()
+                            {
+                                // The window manager takes the drag over and
+                                // may not report the release.
+                                route.window.screen.mouse.left_button_state =
+                                    ElementState::Released;
+                                if let Err(err) = route.window.winit_window.drag_window()
+                                {
+                                    log::warn!("unable to drag window: {err}");
+                                }
+                                return;
+                            }
+
                             #[cfg(target_os = "macos")]
                             {
                                 if route.window.is_macos_deadzone {
@@ -468,11 +1082,6 @@ impl Sequencer {
                                             .window
                                             .screen
                                             .mouse_report(code, ElementState::Pressed);
-
-                                        route
-                                            .window
-                                            .screen
-                                            .process_mouse_bindings(button);
                                     } else {
                                         // Calculate time since the last click to handle double/triple clicks.
                                         let now = Instant::now();
@@ -517,7 +1126,11 @@ impl Sequencer {
                                         let display_offset =
                                             route.window.screen.display_offset();
 
-                                        if let MouseButton::Left = button {
+                                        let bound = route
+                                            .window
+                                            .screen
+                                            .process_mouse_bindings(button);
+                                        if !bound && button == MouseButton::Left {
                                             let point = route
                                                 .window
                                                 .screen
@@ -527,7 +1140,6 @@ impl Sequencer {
 
                                         route.window.winit_window.request_redraw();
                                     }
-                                    // route.screen.process_mouse_bindings(button);
                                 }
                                 ElementState::Released => {
                                     if !route.window.screen.modifiers.state().shift_key()
@@ -568,6 +1180,14 @@ impl Sequencer {
                         ..
                     } => {
                         if let Some(route) = self.router.routes.get_mut(&window_id) {
+                            if route.path == RoutePath::ContextMenu {
+                                route.window.screen.mouse.x = position.x as usize;
+                                route.window.screen.mouse.y = position.y as usize;
+                                if route.window.screen.context_menu_hover() {
+                                    route.redraw();
+                                }
+                            }
+
                             if route.path != RoutePath::Terminal {
                                 route
                                     .window
@@ -642,7 +1262,25 @@ impl Sequencer {
 
                             route.window.winit_window.set_cursor_icon(cursor_icon);
                             if has_selection && (lmb_pressed || rmb_pressed) {
-                                route.window.screen.update_selection_scrolling(y);
+                                let timer_id =
+                                    TimerId::new(Topic::SelectionScrolling, 0);
+                                if route.window.screen.update_selection_scrolling(y) {
+                                    if !scheduler.scheduled(timer_id) {
+                                        scheduler.schedule(
+                                            EventP::new(
+                                                RioEventType::Rio(
+                                                    RioEvent::SelectionScrolling,
+                                                ),
+                                                window_id,
+                                            ),
+                                            SELECTION_SCROLLING_INTERVAL,
+                                            true,
+                                            timer_id,
+                                        );
+                                    }
+                                } else {
+                                    scheduler.unschedule(timer_id);
+                                }
                             }
 
                             let display_offset = route.window.screen.display_offset();
@@ -724,6 +1362,19 @@ impl Sequencer {
                         ..
                     } => {
                         if let Some(route) = self.router.routes.get_mut(&window_id) {
+                            if route.path == RoutePath::Pager {
+                                let lines = match delta {
+                                    MouseScrollDelta::LineDelta(_, lines) => lines * 3.,
+                                    MouseScrollDelta::PixelDelta(lpos) => {
+                                        let layout = &route.window.screen.sugarloaf.layout;
+                                        lpos.y as f32 / (layout.sugarheight * layout.line_height)
+                                    }
+                                };
+                                route.window.screen.scroll_pager(-lines.round() as isize);
+                                route.redraw();
+                                return;
+                            }
+
                             if route.path != RoutePath::Terminal {
                                 return;
                             }
@@ -732,13 +1383,12 @@ impl Sequencer {
                             route.window.winit_window.set_cursor_visible(true);
                             match delta {
                                 MouseScrollDelta::LineDelta(columns, lines) => {
-                                    let new_scroll_px_x = columns
-                                        * route.window.screen.sugarloaf.layout.font_size;
-                                    let new_scroll_px_y = lines
-                                        * route.window.screen.sugarloaf.layout.font_size;
+                                    let layout = &route.window.screen.sugarloaf.layout;
+                                    let line_px = layout.font_size * layout.scale_factor;
                                     route.window.screen.scroll(
-                                        new_scroll_px_x as f64,
-                                        new_scroll_px_y as f64,
+                                        (columns * line_px) as f64,
+                                        (lines * line_px) as f64,
+                                        false,
                                     );
                                 }
                                 MouseScrollDelta::PixelDelta(mut lpos) => {
@@ -760,7 +1410,10 @@ impl Sequencer {
                                                 lpos.x = 0.;
                                             }
 
-                                            route.window.screen.scroll(lpos.x, lpos.y);
+                                            route
+                                                .window
+                                                .screen
+                                                .scroll(lpos.x, lpos.y, true);
                                         }
                                         _ => (),
                                     }
@@ -785,8 +1438,11 @@ impl Sequencer {
                                     && key_event.state == ElementState::Released
                                 {
                                     // Scheduler must be cleaned after leave the terminal route
-                                    scheduler.unschedule(TimerId::new(Topic::Render, 0));
+                                    scheduler.unschedule(TimerId::new(Topic::Render, u64::from(window_id)));
                                     route.window.winit_window.request_redraw();
+                                } else if key_event.state == ElementState::Pressed {
+                                    // Overlays such as the pager change with the keys.
+                                    route.redraw();
                                 }
                                 return;
                             }
@@ -863,11 +1519,41 @@ impl Sequencer {
 
                             let has_regained_focus = !route.window.is_focused && focused;
                             route.window.is_focused = focused;
+                            route.window.screen.accessibility.set_focused(focused);
 
                             if has_regained_focus {
                                 route.redraw();
                             }
                         }
+
+                        #[cfg(all(feature = "wayland-ipc", not(any(target_os = "macos", windows))))]
+                        if focused {
+                            self.follow_workspace(window_id);
+                        }
+                    }
+
+                    #[cfg(all(
+                        any(feature = "x11", feature = "wayland"),
+                        not(any(target_os = "macos", windows))
+                    ))]
+                    Event::WindowEvent {
+                        event:
+                            winit::event::WindowEvent::ActivationTokenDone { serial, token },
+                        ..
+                    } => {
+                        if let Some(index) = self
+                            .activation_requests
+                            .iter()
+                            .position(|request| *request == serial)
+                        {
+                            self.activation_requests.remove(index);
+                            self.router.create_window(
+                                event_loop_window_target,
+                                self.event_proxy.clone().unwrap(),
+                                &self.config,
+                                Some(token),
+                            );
+                        }
                     }
 
                     Event::WindowEvent {
@@ -876,7 +1562,12 @@ impl Sequencer {
                         ..
                     } => {
                         if let Some(route) = self.router.routes.get_mut(&window_id) {
+                            let was_occluded = route.window.is_occluded;
                             route.window.is_occluded = occluded;
+
+                            if was_occluded && !occluded {
+                                route.redraw();
+                            }
                         }
                     }
 
@@ -914,12 +1605,50 @@ impl Sequencer {
                         window_id,
                         ..
                     } => {
-                        if new_size.width == 0 || new_size.height == 0 {
-                            return;
-                        }
-
                         if let Some(route) = self.router.routes.get_mut(&window_id) {
+                            // Some platforms report minimization as a zero sized
+                            // window rather than an occlusion event.
+                            if new_size.width == 0 || new_size.height == 0 {
+                                route.window.is_occluded = true;
+                                return;
+                            }
+
+                            route.window.is_occluded = false;
                             route.window.screen.resize(new_size);
+                            if route.window.screen.has_pending_resize() {
+                                // Restarted on every event until the resize
+                                // settles.
+                                let timer_id = TimerId::new(Topic::Resize, 0);
+                                scheduler.unschedule(timer_id);
+                                scheduler.schedule(
+                                    EventP::new(
+                                        RioEventType::Rio(RioEvent::FinishResize),
+                                        window_id,
+                                    ),
+                                    RESIZE_SETTLE_INTERVAL,
+                                    false,
+                                    timer_id,
+                                );
+                            }
+                            route
+                                .window
+                                .screen
+                                .accessibility
+                                .set_bounds(&route.window.winit_window);
+                        }
+                    }
+
+                    Event::WindowEvent {
+                        event: winit::event::WindowEvent::Moved(_),
+                        window_id,
+                        ..
+                    } => {
+                        if let Some(route) = self.router.routes.get(&window_id) {
+                            route
+                                .window
+                                .screen
+                                .accessibility
+                                .set_bounds(&route.window.winit_window);
                         }
                     }
 
@@ -933,8 +1662,8 @@ impl Sequencer {
                         ..
                     } => {
                         if let Some(route) = self.router.routes.get_mut(&window_id) {
-                            route.window.screen.set_scale(
-                                scale_factor as f32,
+                            route.window.screen.set_reported_scale(
+                                scale_factor,
                                 route.window.winit_window.inner_size(),
                             );
                             route.redraw();
@@ -948,10 +1677,15 @@ impl Sequencer {
                         // TODO: Now we are forcing an exit operation
                         // but it should be revaluated since CloseRequested in MacOs
                         // not necessarily exit the process
+                        // Exiting before drawing, like screenshot runs, is not a crash.
+                        crate::crash::end_startup();
                         std::process::exit(0);
                     }
 
                     Event::AboutToWait => {
+                        self.memory
+                            .check(&self.config.memory, &mut self.router.routes);
+
                         // Update the scheduler after event processing to ensure
                         // the event loop deadline is as accurate as possible.
                         *control_flow = match scheduler.update() {
@@ -962,6 +1696,10 @@ impl Sequencer {
 
                     Event::RedrawRequested(window_id) => {
                         if let Some(route) = self.router.routes.get_mut(&window_id) {
+                            if route.window.is_occluded {
+                                return;
+                            }
+
                             // let start = std::time::Instant::now();
 
                             #[cfg(target_os = "macos")]
@@ -993,14 +1731,39 @@ impl Sequencer {
                                 RoutePath::Welcome => {
                                     route.window.screen.render_welcome();
                                 }
-                                RoutePath::Terminal => {
+                                RoutePath::Terminal | RoutePath::ContextMenu => {
                                     route.window.screen.render();
+                                    if let Some(progress) =
+                                        route.window.screen.taskbar_progress()
+                                    {
+                                        crate::platform::taskbar::set_progress(
+                                            &route.window.winit_window,
+                                            progress,
+                                        );
+                                    }
                                 }
                                 RoutePath::Settings => {
                                     route.window.screen.render_settings(&route.settings);
                                 }
+                                RoutePath::ConfirmPaste => {
+                                    route.window.screen.render_paste_confirmation();
+                                }
+                                RoutePath::Pager => {
+                                    route.window.screen.render_pager();
+                                }
+                                RoutePath::QuickSelect => {
+                                    route.window.screen.render_quick_select();
+                                }
+                                RoutePath::RenameTab => {
+                                    route.window.screen.render_rename_tab();
+                                }
+                                RoutePath::GlyphAtlas => {
+                                    route.window.screen.render_glyph_atlas();
+                                }
                             }
 
+                            startup::first_frame();
+
                             // route.window.screen.render();
                             // let duration = start.elapsed();
                             // println!("Time elapsed in render() is: {:?}", duration);
@@ -0,0 +1,39 @@
+// Startup timing milestones, printed when rio is launched with
+// `--measure-startup`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+static START: OnceLock<Instant> = OnceLock::new();
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static FIRST_FRAME: AtomicBool = AtomicBool::new(false);
+
+/// Starts the startup clock, milestones are only printed when `enabled`.
+pub fn init(enabled: bool) {
+    START.get_or_init(Instant::now);
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Prints the time elapsed since `init` for the given milestone.
+pub fn milestone(name: &str) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    if let Some(start) = START.get() {
+        println!(
+            "[startup] {name}: {:.2}ms",
+            start.elapsed().as_secs_f64() * 1000.0
+        );
+    }
+}
+
+/// Records the first presented frame, subsequent calls are ignored.
+pub fn first_frame() {
+    if !FIRST_FRAME.swap(true, Ordering::Relaxed) {
+        milestone("first frame");
+        // Later crashes are not caused by a config that prevents starting.
+        crate::crash::end_startup();
+    }
+}
@@ -0,0 +1,646 @@
+// Client of the tmux control mode (`tmux -CC`), every tmux pane is shown
+// as a tab fed by the `%output` notifications and the keys typed in it are
+// sent back with `send-keys`.
+//
+// tmux starts the control mode with DCS 1000 p and ends it with ST, the
+// lines in between are notifications and the output of the commands sent by
+// the client, wrapped in `%begin` and `%end` (or `%error`).
+//
+// https://github.com/tmux/tmux/wiki/Control-Mode
+
+use crate::crosswords::{MIN_COLUMNS, MIN_LINES};
+use crate::performer::handler::ParserProcessor;
+use crate::screen::messenger::Messenger;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+/// Keys sent by a single `send-keys` command.
+const SEND_KEYS_CHUNK: usize = 256;
+
+/// Largest pane a layout can declare, the size comes from the remote host and
+/// every pane gets a grid of that size.
+const MAX_PANE_COLUMNS: usize = 2048;
+const MAX_PANE_LINES: usize = 1024;
+
+/// Control mode data read from the PTY.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Control {
+    Start,
+    Line(String),
+    End,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Command to write to the tmux client.
+    Command(String),
+    OpenPane {
+        pane: u32,
+        columns: usize,
+        lines: usize,
+    },
+    ResizePane {
+        pane: u32,
+        columns: usize,
+        lines: usize,
+    },
+    ClosePane(u32),
+    SelectPane(u32),
+    Rename {
+        pane: u32,
+        title: String,
+    },
+    /// Bytes to parse into the pane terminal.
+    Output {
+        pane: u32,
+        data: Vec<u8>,
+    },
+    Exit,
+}
+
+/// Control mode session of a window.
+pub struct Session {
+    pub client: Client,
+    /// Messenger of the tab running tmux.
+    pub gateway: Messenger,
+    /// Parser of each pane output.
+    pub parsers: HashMap<u32, ParserProcessor>,
+}
+
+/// Command sent by the client, answered in order by tmux.
+#[derive(Debug, Clone, PartialEq)]
+enum Pending {
+    ListWindows,
+    CapturePane(u32),
+    CursorPosition(u32),
+    Ignore,
+}
+
+#[derive(Debug)]
+struct Block {
+    /// Answer to a command of this client, the output of the commands run
+    /// when attaching is ignored.
+    from_client: bool,
+    lines: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Client {
+    pending: VecDeque<Pending>,
+    block: Option<Block>,
+    /// Panes of each window, in layout order.
+    windows: HashMap<u32, Vec<u32>>,
+    /// Panes waiting for their history, the output received meanwhile is
+    /// already part of it.
+    capturing: HashSet<u32>,
+    exited: bool,
+}
+
+impl Client {
+    /// Size the tmux windows to the terminal grid and list them.
+    pub fn start(columns: usize, lines: usize) -> (Client, Vec<Action>) {
+        let mut client = Client::default();
+        let actions = vec![
+            client.resize(columns, lines),
+            client.command(
+                Pending::ListWindows,
+                "list-windows -F \"#{window_id} #{window_layout} #{window_name}\"",
+            ),
+        ];
+        (client, actions)
+    }
+
+    /// Command resizing the tmux windows, tmux answers with layout changes.
+    pub fn resize(&mut self, columns: usize, lines: usize) -> Action {
+        self.command(
+            Pending::Ignore,
+            &format!("refresh-client -C {columns}x{lines}"),
+        )
+    }
+
+    fn command(&mut self, pending: Pending, command: &str) -> Action {
+        self.pending.push_back(pending);
+        Action::Command(format!("{command}\n"))
+    }
+
+    pub fn handle_line(&mut self, line: &str) -> Vec<Action> {
+        if self.exited {
+            return vec![];
+        }
+
+        if let Some(block) = &mut self.block {
+            if line.starts_with("%end") || line.starts_with("%error") {
+                let block = self.block.take().unwrap();
+                if !block.from_client {
+                    return vec![];
+                }
+                let pending = self.pending.pop_front().unwrap_or(Pending::Ignore);
+                if line.starts_with("%error") {
+                    log::warn!("tmux command failed: {}", block.lines.join(" "));
+                    if let Pending::CursorPosition(pane) = pending {
+                        self.capturing.remove(&pane);
+                    }
+                    return vec![];
+                }
+                return self.answer(pending, block.lines);
+            }
+
+            block.lines.push(line.to_string());
+            return vec![];
+        }
+
+        let (name, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match name {
+            "%begin" => {
+                // %begin <time> <command number> <flags>
+                let flags = rest.split(' ').nth(2).and_then(|flags| flags.parse().ok());
+                self.block = Some(Block {
+                    from_client: flags.unwrap_or(0u32) & 1 == 1,
+                    lines: vec![],
+                });
+                vec![]
+            }
+            "%output" => {
+                let (pane, data) = rest.split_once(' ').unwrap_or((rest, ""));
+                match parse_id(pane, '%') {
+                    Some(pane) if !self.capturing.contains(&pane) => {
+                        vec![Action::Output {
+                            pane,
+                            data: unescape(data),
+                        }]
+                    }
+                    _ => vec![],
+                }
+            }
+            "%layout-change" => {
+                let mut args = rest.split(' ');
+                match (
+                    args.next().and_then(|window| parse_id(window, '@')),
+                    args.next().and_then(parse_layout),
+                ) {
+                    (Some(window), Some(panes)) => self.update_window(window, panes),
+                    _ => vec![],
+                }
+            }
+            "%window-add" => vec![self.command(
+                Pending::ListWindows,
+                "list-windows -F \"#{window_id} #{window_layout} #{window_name}\"",
+            )],
+            "%window-close" | "%unlinked-window-close" => parse_id(rest, '@')
+                .and_then(|window| self.windows.remove(&window))
+                .unwrap_or_default()
+                .into_iter()
+                .map(Action::ClosePane)
+                .collect(),
+            "%window-renamed" => {
+                let (window, name) = rest.split_once(' ').unwrap_or((rest, ""));
+                self.rename(parse_id(window, '@'), name)
+            }
+            "%window-pane-changed" => rest
+                .split(' ')
+                .nth(1)
+                .and_then(|pane| parse_id(pane, '%'))
+                .map(Action::SelectPane)
+                .into_iter()
+                .collect(),
+            "%session-window-changed" => rest
+                .split(' ')
+                .nth(1)
+                .and_then(|window| parse_id(window, '@'))
+                .and_then(|window| self.windows.get(&window)?.first().copied())
+                .map(Action::SelectPane)
+                .into_iter()
+                .collect(),
+            "%exit" => self.exit(),
+            _ => vec![],
+        }
+    }
+
+    /// The control mode ended, every pane is closed.
+    pub fn exit(&mut self) -> Vec<Action> {
+        if self.exited {
+            return vec![];
+        }
+        self.exited = true;
+        let mut actions: Vec<Action> = self
+            .windows
+            .drain()
+            .flat_map(|(_, panes)| panes)
+            .map(Action::ClosePane)
+            .collect();
+        actions.push(Action::Exit);
+        actions
+    }
+
+    fn answer(&mut self, pending: Pending, lines: Vec<String>) -> Vec<Action> {
+        match pending {
+            Pending::ListWindows => {
+                let mut actions = vec![];
+                for line in lines {
+                    let mut fields = line.splitn(3, ' ');
+                    let window = fields.next().and_then(|window| parse_id(window, '@'));
+                    let panes = fields.next().and_then(parse_layout);
+                    if let (Some(window), Some(panes)) = (window, panes) {
+                        actions.extend(self.update_window(window, panes));
+                        actions.extend(
+                            self.rename(Some(window), fields.next().unwrap_or("")),
+                        );
+                    }
+                }
+                actions
+            }
+            Pending::CapturePane(pane) => {
+                vec![Action::Output {
+                    pane,
+                    data: lines.join("\r\n").into_bytes(),
+                }]
+            }
+            Pending::CursorPosition(pane) => {
+                self.capturing.remove(&pane);
+                let mut position = lines
+                    .first()
+                    .map(|line| line.split(' ').filter_map(|value| value.parse().ok()))
+                    .into_iter()
+                    .flatten();
+                match (position.next(), position.next()) {
+                    (Some(x), Some(y)) => {
+                        let x: usize = x;
+                        let y: usize = y;
+                        vec![Action::Output {
+                            pane,
+                            data: format!("\x1b[{};{}H", y + 1, x + 1).into_bytes(),
+                        }]
+                    }
+                    _ => vec![],
+                }
+            }
+            Pending::Ignore => vec![],
+        }
+    }
+
+    /// Open, resize and close the panes of `window` to match its layout.
+    fn update_window(&mut self, window: u32, panes: Vec<LayoutPane>) -> Vec<Action> {
+        let known: Vec<u32> = self.windows.get(&window).cloned().unwrap_or_default();
+        let mut actions = vec![];
+
+        for closed in known
+            .iter()
+            .filter(|pane| !panes.iter().any(|layout| layout.pane == **pane))
+        {
+            actions.push(Action::ClosePane(*closed));
+        }
+
+        for layout in &panes {
+            let LayoutPane {
+                pane,
+                columns,
+                lines,
+            } = *layout;
+            if known.contains(&pane) {
+                actions.push(Action::ResizePane {
+                    pane,
+                    columns,
+                    lines,
+                });
+                continue;
+            }
+
+            actions.push(Action::OpenPane {
+                pane,
+                columns,
+                lines,
+            });
+            // The history and the screen, with escapes and wrapped lines
+            // joined, then the cursor position.
+            self.capturing.insert(pane);
+            actions.push(self.command(
+                Pending::CapturePane(pane),
+                &format!("capture-pane -p -e -J -S - -t %{pane}"),
+            ));
+            actions.push(self.command(
+                Pending::CursorPosition(pane),
+                &format!("display-message -p -t %{pane} \"#{{cursor_x}} #{{cursor_y}}\""),
+            ));
+        }
+
+        self.windows
+            .insert(window, panes.iter().map(|layout| layout.pane).collect());
+        actions
+    }
+
+    fn rename(&self, window: Option<u32>, name: &str) -> Vec<Action> {
+        window
+            .and_then(|window| self.windows.get(&window))
+            .map(|panes| {
+                panes
+                    .iter()
+                    .map(|pane| Action::Rename {
+                        pane: *pane,
+                        title: name.to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Commands typing `data` into `pane`.
+pub fn send_keys(pane: u32, data: &[u8]) -> String {
+    let keys: Vec<char> = String::from_utf8_lossy(data).chars().collect();
+    let mut commands = String::new();
+    for chunk in keys.chunks(SEND_KEYS_CHUNK) {
+        let _ = write!(commands, "send-keys -t %{pane}");
+        for key in chunk {
+            let _ = write!(commands, " 0x{:x}", *key as u32);
+        }
+        commands.push('\n');
+    }
+    commands
+}
+
+#[inline]
+fn parse_id(id: &str, prefix: char) -> Option<u32> {
+    id.strip_prefix(prefix)?.parse().ok()
+}
+
+/// Output of a pane, tmux writes the characters below 32 and the backslash
+/// as octal escapes.
+fn unescape(data: &str) -> Vec<u8> {
+    let bytes = data.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let octal = bytes.get(index + 1..index + 4).filter(|digits| {
+            bytes[index] == b'\\'
+                && digits.iter().all(|digit| (b'0'..=b'7').contains(digit))
+        });
+        match octal {
+            Some(digits) => {
+                let value = digits
+                    .iter()
+                    .fold(0u32, |value, digit| value * 8 + (digit - b'0') as u32);
+                unescaped.push(value as u8);
+                index += 4;
+            }
+            None => {
+                unescaped.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    unescaped
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutPane {
+    pub pane: u32,
+    pub columns: usize,
+    pub lines: usize,
+}
+
+/// Panes of a window layout like `b25f,80x24,0,0{40x24,0,0,1,39x24,41,0,2}`.
+fn parse_layout(layout: &str) -> Option<Vec<LayoutPane>> {
+    // The layout starts with a checksum.
+    let (_, cells) = layout.split_once(',')?;
+    let mut panes = vec![];
+    let rest = parse_cell(cells, &mut panes)?;
+    if rest.is_empty() {
+        Some(panes)
+    } else {
+        None
+    }
+}
+
+/// Parse `WxH,X,Y` followed by a pane id or by the children between `{}`
+/// (side by side) or `[]` (stacked), returning what follows the cell.
+fn parse_cell<'a>(cell: &'a str, panes: &mut Vec<LayoutPane>) -> Option<&'a str> {
+    let (size, rest) = cell.split_once(',')?;
+    let (columns, lines) = size.split_once('x')?;
+    let (columns, lines) = (columns.parse().ok()?, lines.parse().ok()?);
+    // Layouts with empty or huge cells are dropped.
+    if !(MIN_COLUMNS..=MAX_PANE_COLUMNS).contains(&columns)
+        || !(MIN_LINES..=MAX_PANE_LINES).contains(&lines)
+    {
+        return None;
+    }
+    let (_, rest) = rest.split_once(',')?;
+    let end = rest.find([',', '{', '[', '}', ']']).unwrap_or(rest.len());
+    let rest = &rest[end..];
+
+    if let Some(children) = rest.strip_prefix(['{', '[']) {
+        let mut rest = parse_cell(children, panes)?;
+        while let Some(next) = rest.strip_prefix(',') {
+            rest = parse_cell(next, panes)?;
+        }
+        return rest.strip_prefix(['}', ']']);
+    }
+
+    let rest = rest.strip_prefix(',')?;
+    let end = rest.find([',', '}', ']']).unwrap_or(rest.len());
+    panes.push(LayoutPane {
+        pane: rest[..end].parse().ok()?,
+        columns,
+        lines,
+    });
+    Some(&rest[end..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pane(pane: u32, columns: usize, lines: usize) -> LayoutPane {
+        LayoutPane {
+            pane,
+            columns,
+            lines,
+        }
+    }
+
+    #[test]
+    fn test_parse_layout() {
+        assert_eq!(
+            parse_layout("b25f,80x24,0,0,2"),
+            Some(vec![pane(2, 80, 24)])
+        );
+        assert_eq!(parse_layout("bb62,159x48,0,0{79x48,0,0,79x48,80,0}"), None);
+        assert_eq!(
+            parse_layout(
+                "d6b9,159x48,0,0{79x48,0,0,1,79x48,80,0[79x24,80,0,2,79x23,80,25,3]}"
+            ),
+            Some(vec![pane(1, 79, 48), pane(2, 79, 24), pane(3, 79, 23)])
+        );
+        assert_eq!(parse_layout("80x24"), None);
+
+        // Sizes that would panic or exhaust the memory of the grid.
+        assert_eq!(parse_layout("b25f,0x0,0,0,2"), None);
+        assert_eq!(parse_layout("b25f,1x24,0,0,2"), None);
+        assert_eq!(parse_layout("b25f,99999x99999,0,0,2"), None);
+        assert_eq!(parse_layout("b25f,80x99999999999999999999,0,0,2"), None);
+        assert_eq!(
+            parse_layout("d6b9,159x48,0,0{79x48,0,0,1,79x0,80,0,2}"),
+            None
+        );
+        assert_eq!(
+            parse_layout("b25f,2048x1024,0,0,2"),
+            Some(vec![pane(2, 2048, 1024)])
+        );
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape(r"ls\015\012"), b"ls\r\n".to_vec());
+        assert_eq!(unescape(r"\033[1mbold\134"), b"\x1b[1mbold\\".to_vec());
+        assert_eq!(unescape(r"\01"), b"\\01".to_vec());
+    }
+
+    #[test]
+    fn test_send_keys() {
+        assert_eq!(send_keys(3, b"ls\r"), "send-keys -t %3 0x6c 0x73 0xd\n");
+        assert_eq!(send_keys(1, "é".as_bytes()), "send-keys -t %1 0xe9\n");
+        assert_eq!(send_keys(1, &[b'a'; 300]).lines().count(), 2);
+    }
+
+    #[test]
+    fn test_attach() {
+        let (mut client, actions) = Client::start(80, 24);
+        assert_eq!(
+            actions[0],
+            Action::Command(String::from("refresh-client -C 80x24\n"))
+        );
+
+        // Output of the commands run when attaching.
+        assert!(client.handle_line("%begin 1 10 0").is_empty());
+        assert!(client.handle_line("%end 1 10 0").is_empty());
+        // refresh-client
+        assert!(client.handle_line("%begin 1 11 1").is_empty());
+        assert!(client.handle_line("%end 1 11 1").is_empty());
+
+        // list-windows, the line starting with % is part of the output.
+        client.handle_line("%begin 1 12 1");
+        client.handle_line("@1 b25f,80x24,0,0,2 zsh");
+        let actions = client.handle_line("%end 1 12 1");
+        assert_eq!(
+            actions[0],
+            Action::OpenPane {
+                pane: 2,
+                columns: 80,
+                lines: 24
+            }
+        );
+        assert_eq!(
+            actions[1],
+            Action::Command(String::from("capture-pane -p -e -J -S - -t %2\n"))
+        );
+        assert_eq!(
+            actions[3],
+            Action::Rename {
+                pane: 2,
+                title: String::from("zsh")
+            }
+        );
+
+        // Already part of the captured history.
+        assert!(client.handle_line(r"%output %2 ls\015\012").is_empty());
+
+        client.handle_line("%begin 1 13 1");
+        client.handle_line("%1 $ ls");
+        assert_eq!(
+            client.handle_line("%end 1 13 1"),
+            vec![Action::Output {
+                pane: 2,
+                data: b"%1 $ ls".to_vec()
+            }]
+        );
+        client.handle_line("%begin 1 14 1");
+        client.handle_line("4 0");
+        assert_eq!(
+            client.handle_line("%end 1 14 1"),
+            vec![Action::Output {
+                pane: 2,
+                data: b"\x1b[1;5H".to_vec()
+            }]
+        );
+
+        assert_eq!(
+            client.handle_line(r"%output %2 ls\015\012"),
+            vec![Action::Output {
+                pane: 2,
+                data: b"ls\r\n".to_vec()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_layout_change() {
+        let (mut client, _) = Client::start(80, 24);
+        client.handle_line("%begin 1 1 1");
+        client.handle_line("%end 1 1 1");
+        client.handle_line("%begin 1 2 1");
+        client.handle_line("@1 b25f,80x24,0,0,2 zsh");
+        client.handle_line("%end 1 2 1");
+
+        let actions = client.handle_line(
+            "%layout-change @1 d6b9,80x24,0,0{40x24,0,0,2,39x24,41,0,5} d6b9,80x24,0,0{40x24,0,0,2,39x24,41,0,5} *",
+        );
+        assert_eq!(
+            actions[0],
+            Action::ResizePane {
+                pane: 2,
+                columns: 40,
+                lines: 24
+            }
+        );
+        assert_eq!(
+            actions[1],
+            Action::OpenPane {
+                pane: 5,
+                columns: 39,
+                lines: 24
+            }
+        );
+
+        let actions =
+            client.handle_line("%layout-change @1 b25f,80x24,0,0,5 b25f,80x24,0,0,5 *");
+        assert_eq!(actions[0], Action::ClosePane(2));
+
+        assert_eq!(
+            client.handle_line("%session-window-changed $1 @1"),
+            vec![Action::SelectPane(5)]
+        );
+        assert_eq!(
+            client.handle_line("%exit"),
+            vec![Action::ClosePane(5), Action::Exit]
+        );
+        assert!(client.exit().is_empty());
+    }
+
+    #[test]
+    fn test_control_mode_sequence() {
+        #[derive(Default)]
+        struct Controls(Vec<Control>);
+
+        impl crate::performer::handler::Handler for Controls {
+            fn tmux_control(&mut self, control: Control) {
+                self.0.push(control);
+            }
+        }
+
+        let mut controls = Controls::default();
+        let mut parser = ParserProcessor::new();
+        for byte in b"\x1bP1000p%begin 1 1 0\r\n%end 1 1 0\n%exit\n\x1b\\" {
+            parser.advance(&mut controls, *byte);
+        }
+
+        assert_eq!(
+            controls.0,
+            vec![
+                Control::Start,
+                Control::Line(String::from("%begin 1 1 0")),
+                Control::Line(String::from("%end 1 1 0")),
+                Control::Line(String::from("%exit")),
+                Control::End,
+            ]
+        );
+    }
+}
@@ -3,8 +3,11 @@ pub mod menu;
 pub mod menubar;
 pub mod menuitem;
 
-// use core::ptr::NonNull;
+use core::ffi::c_void;
+use core::ptr::NonNull;
 use objc2::rc::autoreleasepool;
+use objc2::runtime::Sel;
+use objc2::sel;
 
 pub use self::menubar::MenuBar;
 pub use global::InitializedApplication;
@@ -17,7 +20,14 @@ extern "C" {}
 #[link(name = "Foundation", kind = "framework")]
 extern "C" {}
 
-pub fn create_toolbar() {
+/// Action of a menu item sent through the responder chain to the key window.
+fn action(selector: Sel) -> Option<NonNull<c_void>> {
+    NonNull::new(selector.as_ptr() as *mut c_void)
+}
+
+/// Create the application menus, `native_tabs` fills the Window menu with
+/// the NSWindow tab group actions used by the NativeTab navigation.
+pub fn create_toolbar(native_tabs: bool) {
     autoreleasepool(|_pool| {
         let app = unsafe { InitializedApplication::new() };
         // let menubar = app.menubar(pool).unwrap();
@@ -49,8 +59,44 @@ pub fn create_toolbar() {
             // menu.add(NSMenuItem::new("Will be above the window data", "", None));
         });
 
-        let window_menu = menubar.add("Window", |_menu| {
-            // menu.add(NSMenuItem::new("Will be above the window data", "", None));
+        let window_menu = menubar.add("Window", |menu| {
+            menu.add(NSMenuItem::new(
+                "Minimize",
+                "m",
+                action(sel!(performMiniaturize:)),
+            ));
+            menu.add(NSMenuItem::new("Zoom", "", action(sel!(performZoom:))));
+
+            if native_tabs {
+                menu.add(NSMenuItem::new_separator());
+                menu.add(NSMenuItem::new(
+                    "Show Previous Tab",
+                    "",
+                    action(sel!(selectPreviousTab:)),
+                ));
+                menu.add(NSMenuItem::new(
+                    "Show Next Tab",
+                    "",
+                    action(sel!(selectNextTab:)),
+                ));
+                menu.add(NSMenuItem::new(
+                    "Move Tab to New Window",
+                    "",
+                    action(sel!(moveTabToNewWindow:)),
+                ));
+                menu.add(NSMenuItem::new(
+                    "Merge All Windows",
+                    "",
+                    action(sel!(mergeAllWindows:)),
+                ));
+            }
+
+            menu.add(NSMenuItem::new_separator());
+            menu.add(NSMenuItem::new(
+                "Bring All to Front",
+                "",
+                action(sel!(arrangeInFront:)),
+            ));
         });
 
         let help_menu = menubar.add("Help", |_menu| {
@@ -1,6 +1,6 @@
 use crate::event::{EventListener, RioEvent};
 use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use winit::window::WindowId;
 
@@ -12,7 +12,7 @@ pub fn watch<
 >(
     path: P,
     event_proxy: T,
-) -> notify::Result<()> {
+) -> notify::Result<RecommendedWatcher> {
     let (tx, rx) = std::sync::mpsc::channel();
 
     // Automatically select the best implementation for your platform.
@@ -22,15 +22,11 @@ pub fn watch<
         Config::default().with_poll_interval(POLLING_TIMEOUT),
     )?;
 
-    tokio::spawn(async move {
-        // Add a path to be watched. All files and directories at that path and
-        // below will be monitored for changes.
-        if let Err(err_message) =
-            watcher.watch(path.as_ref(), RecursiveMode::NonRecursive)
-        {
-            log::warn!("unable to watch config directory {err_message:?}");
-        };
+    if let Err(err_message) = watcher.watch(path.as_ref(), RecursiveMode::NonRecursive) {
+        log::warn!("unable to watch config directory {err_message:?}");
+    };
 
+    tokio::spawn(async move {
         for res in rx {
             match res {
                 Ok(event) => match event.kind {
@@ -51,5 +47,18 @@ pub fn watch<
         }
     });
 
-    Ok(())
+    Ok(watcher)
+}
+
+/// Also watch the directories of the files included by the config, they may
+/// not exist yet or live outside of the config directory.
+pub fn watch_includes(watcher: &mut RecommendedWatcher, includes: &[PathBuf]) {
+    for directory in includes.iter().filter_map(|include| include.parent()) {
+        if let Err(err_message) = watcher.watch(directory, RecursiveMode::NonRecursive) {
+            log::warn!(
+                "unable to watch included config directory {}: {err_message:?}",
+                directory.display()
+            );
+        }
+    }
 }
@@ -40,6 +40,7 @@ fn bench_sugar_pile(c: &mut Criterion) {
     let mut sugarloaf = futures::executor::block_on(Sugarloaf::new(
         &window,
         wgpu::PowerPreference::LowPower,
+        sugarloaf::context::ColorSpace::Srgb,
         sugarloaf::font::fonts::SugarloafFonts::default(),
         sugarloaf_layout,
         None,
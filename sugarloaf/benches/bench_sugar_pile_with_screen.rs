@@ -42,6 +42,7 @@ fn bench_sugar_pile_with_screen(c: &mut Criterion) {
     let mut sugarloaf = futures::executor::block_on(Sugarloaf::new(
         &window,
         wgpu::PowerPreference::LowPower,
+        sugarloaf::context::ColorSpace::Srgb,
         sugarloaf::font::fonts::SugarloafFonts::default(),
         sugarloaf_layout,
         None,
@@ -43,6 +43,7 @@ async fn main() {
     let mut sugarloaf = Sugarloaf::new(
         &window,
         wgpu::PowerPreference::HighPerformance,
+        sugarloaf::context::ColorSpace::Srgb,
         sugarloaf::font::fonts::SugarloafFonts::default(),
         sugarloaf_layout,
         None,
@@ -48,6 +48,7 @@ async fn main() {
     let mut sugarloaf = Sugarloaf::new(
         &window,
         wgpu::PowerPreference::HighPerformance,
+        sugarloaf::context::ColorSpace::Srgb,
         sugarloaf::font::fonts::SugarloafFonts::default(),
         // "Fira Code".to_string(),
         // "Monaco".to_string(),
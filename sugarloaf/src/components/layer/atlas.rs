@@ -67,6 +67,11 @@ impl Atlas {
         self.layers.len()
     }
 
+    /// Bytes taken by the texture, every layer is allocated in full.
+    pub fn memory(&self) -> usize {
+        self.layers.len() * (SIZE * SIZE * 4) as usize
+    }
+
     pub fn upload(
         &mut self,
         device: &wgpu::Device,
@@ -42,5 +42,5 @@ fn vs_main(input: VertexInput) -> VertexOutput {
 
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(u_texture, u_sampler, input.uv, i32(input.layer));
+    return output_image_color(textureSample(u_texture, u_sampler, input.uv, i32(input.layer)));
 }
\ No newline at end of file
@@ -190,9 +190,9 @@ impl LayerBrush {
 
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("iced_wgpu image shader"),
-            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
-                "image.wgsl"
-            ))),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Owned(
+                context.color_output.shader() + include_str!("image.wgsl"),
+            )),
         });
 
         let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -301,6 +301,11 @@ impl LayerBrush {
         }
     }
 
+    #[inline]
+    pub fn texture_memory(&self) -> usize {
+        self.texture_atlas.memory()
+    }
+
     pub fn dimensions(&self, handle: &image::Handle) -> Size<u32> {
         let mut cache = self.raster_cache.borrow_mut();
         let memory = cache.load(handle);
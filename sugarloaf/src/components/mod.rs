@@ -2,3 +2,4 @@ pub mod core;
 pub mod layer;
 pub mod rect;
 pub mod text;
+pub mod upscale;
@@ -1,5 +1,5 @@
 use crate::components::core::orthographic_projection;
-use crate::context::Context;
+use crate::context::{ColorFilter, Context};
 use crate::Renderable;
 use bytemuck::{Pod, Zeroable};
 use std::{borrow::Cow, mem};
@@ -12,33 +12,34 @@ const MAX_INSTANCES: usize = 10_000;
 struct Uniforms {
     transform: [f32; 16],
     scale: f32,
-    _padding: [f32; 3],
+    color_filtered: u32,
+    _padding: [u32; 2],
+    color_matrix: [f32; 16],
 }
 
 impl Uniforms {
-    fn new(transformation: [f32; 16], scale: f32) -> Uniforms {
+    fn new(transformation: [f32; 16], scale: f32, color_filter: ColorFilter) -> Uniforms {
+        let color_matrix = color_filter.matrix();
         Self {
             transform: transformation,
             scale,
+            color_filtered: color_matrix.is_some() as u32,
             // Ref: https://github.com/iced-rs/iced/blob/bc62013b6cde52174bf4c4286939cf170bfa7760/wgpu/src/quad.rs#LL295C6-L296C68
             // Uniforms must be aligned to their largest member,
             // this uses a mat4x4<f32> which aligns to 16, so align to that
-            _padding: [0.0; 3],
+            _padding: [0; 2],
+            color_matrix: color_matrix.unwrap_or(IDENTITY_MATRIX),
         }
     }
 }
 
+const IDENTITY_MATRIX: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+];
+
 impl Default for Uniforms {
     fn default() -> Self {
-        let identity_matrix: [f32; 16] = [
-            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0,
-            1.0,
-        ];
-        Self {
-            transform: identity_matrix,
-            scale: 1.0,
-            _padding: [0.0; 3],
-        }
+        Self::new(IDENTITY_MATRIX, 1.0, ColorFilter::None)
     }
 }
 
@@ -106,6 +107,7 @@ pub struct RectBrush {
     pipeline: wgpu::RenderPipeline,
     current_transform: [f32; 16],
     scale: f32,
+    color_filter: ColorFilter,
 }
 
 impl Renderable for RectBrush {
@@ -138,7 +140,7 @@ impl Renderable for RectBrush {
                 label: None,
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
@@ -172,7 +174,9 @@ impl Renderable for RectBrush {
 
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("rect.wgsl"))),
+            source: wgpu::ShaderSource::Wgsl(Cow::Owned(
+                context.color_output.shader() + include_str!("rect.wgsl"),
+            )),
         });
 
         let vertex_buffers = [
@@ -233,6 +237,7 @@ impl Renderable for RectBrush {
         // Done
         RectBrush {
             scale: context.scale,
+            color_filter: context.color_filter,
             vertex_buf,
             index_buf,
             index_count: QUAD_INDICES.len(),
@@ -266,28 +271,30 @@ impl Renderable for RectBrush {
         ctx: &mut Context,
     ) {
         let transform: [f32; 16] = orthographic_projection(dimensions.0, dimensions.1);
-        // device.push_error_scope(wgpu::ErrorFilter::Validation);
         let scale = ctx.scale;
-        // let device = &ctx.device;
-        let queue = &mut ctx.queue;
-
-        if transform != self.current_transform || scale != self.scale {
-            let uniforms = Uniforms::new(transform, scale);
-
-            queue.write_buffer(&self.transform, 0, bytemuck::bytes_of(&uniforms));
-
-            // let mut transform_view = staging_belt.write_buffer(
-            //     encoder,
-            //     &self.transform,
-            //     0,
-            //     wgpu::BufferSize::new(mem::size_of::<Uniforms>() as u64).unwrap(),
-            //     device,
-            // );
-
-            // transform_view.copy_from_slice(bytemuck::bytes_of(&uniforms));
+        let color_filter = ctx.color_filter;
+        let device = &ctx.device;
+        let staging_belt = &mut ctx.staging_belt;
+
+        if transform != self.current_transform
+            || scale != self.scale
+            || color_filter != self.color_filter
+        {
+            let uniforms = Uniforms::new(transform, scale, color_filter);
+
+            staging_belt
+                .write_buffer(
+                    encoder,
+                    &self.transform,
+                    0,
+                    wgpu::BufferSize::new(mem::size_of::<Uniforms>() as u64).unwrap(),
+                    device,
+                )
+                .copy_from_slice(bytemuck::bytes_of(&uniforms));
 
             self.current_transform = transform;
             self.scale = scale;
+            self.color_filter = color_filter;
         }
 
         let mut i = 0;
@@ -297,9 +304,19 @@ impl Renderable for RectBrush {
             let end = (i + MAX_INSTANCES).min(total);
             let amount = end - i;
 
-            let instance_bytes = bytemuck::cast_slice(&instances[i..end]);
-
-            queue.write_buffer(&self.instances, 0, instance_bytes);
+            let instance_bytes: &[u8] = bytemuck::cast_slice(&instances[i..end]);
+
+            // Every chunk is copied right before its own render pass,
+            // so chunks don't overwrite each other within the frame.
+            staging_belt
+                .write_buffer(
+                    encoder,
+                    &self.instances,
+                    0,
+                    wgpu::BufferSize::new(instance_bytes.len() as u64).unwrap(),
+                    device,
+                )
+                .copy_from_slice(instance_bytes);
 
             {
                 let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
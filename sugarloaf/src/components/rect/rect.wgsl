@@ -1,6 +1,8 @@
 struct Globals {
     transform: mat4x4<f32>,
     scale: f32,
+    color_filtered: u32,
+    color_matrix: mat4x4<f32>,
 }
 
 @group(0) @binding(0) var<uniform> globals: Globals;
@@ -36,5 +38,9 @@ fn vs_main(
 
 @fragment
 fn fs_main(output: VertexOutput) -> @location(0) vec4<f32> {
-    return output.color;
+    var color: vec4<f32> = output.color;
+    if (globals.color_filtered != 0u) {
+        color = filter_color(color, globals.color_matrix);
+    }
+    return output_color(color);
 }
@@ -1,5 +1,6 @@
 use core::hash::BuildHasher;
 
+use crate::context::ColorOutput;
 use crate::glyph::ab_glyph::Font;
 // use crate::glyph::delegate_glyph_brush_builder_fns;
 use crate::glyph::DefaultSectionHasher;
@@ -11,6 +12,7 @@ pub struct GlyphBrushBuilder<D, F, H = DefaultSectionHasher> {
     inner: crate::glyph::GlyphBrushBuilder<F, H>,
     texture_filter_method: wgpu::FilterMode,
     multisample_state: wgpu::MultisampleState,
+    color_output: ColorOutput,
     depth: D,
 }
 
@@ -20,6 +22,7 @@ impl<F, H> From<crate::glyph::GlyphBrushBuilder<F, H>> for GlyphBrushBuilder<(),
             inner,
             texture_filter_method: wgpu::FilterMode::Linear,
             multisample_state: wgpu::MultisampleState::default(),
+            color_output: ColorOutput::default(),
             depth: (),
         }
     }
@@ -38,6 +41,7 @@ impl GlyphBrushBuilder<(), ()> {
             inner: crate::glyph::GlyphBrushBuilder::using_fonts(fonts),
             texture_filter_method: wgpu::FilterMode::Linear,
             multisample_state: wgpu::MultisampleState::default(),
+            color_output: ColorOutput::default(),
             depth: (),
         }
     }
@@ -86,6 +90,12 @@ impl<F: Font, D, H: BuildHasher> GlyphBrushBuilder<D, F, H> {
         self
     }
 
+    /// Sets the conversion of the colors written to the render target.
+    pub fn color_output(mut self, color_output: ColorOutput) -> Self {
+        self.color_output = color_output;
+        self
+    }
+
     /// Sets the section hasher. `GlyphBrush` cannot handle absolute section
     /// hash collisions so use a good hash algorithm.
     ///
@@ -113,6 +123,7 @@ impl<F: Font, D, H: BuildHasher> GlyphBrushBuilder<D, F, H> {
             inner: self.inner,
             texture_filter_method: self.texture_filter_method,
             multisample_state: self.multisample_state,
+            color_output: self.color_output,
             depth: depth_stencil_state,
         }
     }
@@ -131,6 +142,7 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrushBuilder<(), F, H> {
             self.texture_filter_method,
             self.multisample_state,
             render_format,
+            self.color_output,
             self.inner,
         )
     }
@@ -149,6 +161,7 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrushBuilder<wgpu::DepthStencilState,
             self.texture_filter_method,
             self.multisample_state,
             render_format,
+            self.color_output,
             self.depth,
             self.inner,
         )
@@ -14,6 +14,9 @@ pub struct Region {
 
 use pipeline::{Instance, Pipeline};
 
+/// Smallest side of a texture cache shrunk to fit a memory budget.
+const MIN_SHRUNK_TEXTURE: u32 = 256;
+
 pub use crate::glyph::ab_glyph;
 pub use crate::glyph::{
     BuiltInLineBreaker, Extra, FontId, GlyphCruncher, GlyphPositioner, HorizontalAlign,
@@ -26,7 +29,7 @@ use ab_glyph::{Font, Rect};
 use core::hash::BuildHasher;
 use std::borrow::Cow;
 
-use crate::glyph::{BrushAction, BrushError, DefaultSectionHasher};
+use crate::glyph::{BrushAction, BrushError, CacheStats, DefaultSectionHasher, RowUsage};
 
 /// Object allowing glyph drawing, containing cache state. Manages glyph positioning cacheing,
 /// glyph draw caching & efficient GPU texture cache updating and re-sizing on demand.
@@ -35,6 +38,9 @@ use crate::glyph::{BrushAction, BrushError, DefaultSectionHasher};
 pub struct GlyphBrush<Depth, F = ab_glyph::FontArc, H = DefaultSectionHasher> {
     pipeline: Pipeline<Depth>,
     glyph_brush: crate::glyph::GlyphBrush<Instance, Extra, F, H>,
+    atlas: Option<(Rect, [f32; 4])>,
+    drawn_atlas: Option<(Rect, [f32; 4])>,
+    shrink_to: Option<(u32, u32)>,
 }
 
 impl<Depth, F: Font, H: BuildHasher> GlyphBrush<Depth, F, H> {
@@ -118,6 +124,69 @@ impl<Depth, F: Font, H: BuildHasher> GlyphBrush<Depth, F, H> {
         self.glyph_brush.fonts()
     }
 
+    /// Returns `true` while glyphs are rasterized in the background.
+    #[inline]
+    pub fn has_deferred_glyphs(&self) -> bool {
+        self.glyph_brush.has_deferred_glyphs()
+    }
+
+    /// Turn the rasterization of new glyphs in the background on or off.
+    #[inline]
+    pub fn set_defer_rasterization(&mut self, defer_rasterization: bool) {
+        self.glyph_brush
+            .set_defer_rasterization(defer_rasterization);
+    }
+
+    /// Queues the whole cache texture, stretched over `area`, to be drawn
+    /// with the sections by the next draw.
+    #[inline]
+    pub fn queue_atlas(&mut self, area: Rect, color: [f32; 4]) {
+        self.atlas = Some((area, color));
+    }
+
+    /// Returns the glyph texture cache dimensions, the glyphs it dropped to
+    /// make room and its rows, least recently used first.
+    pub fn atlas_usage(&self) -> ((u32, u32), CacheStats, Vec<RowUsage>) {
+        (
+            self.glyph_brush.texture_dimensions(),
+            self.glyph_brush.texture_cache_stats(),
+            self.glyph_brush.texture_cache_rows(),
+        )
+    }
+
+    /// Bytes taken by the glyph texture cache, a byte per pixel.
+    #[inline]
+    pub fn texture_memory(&self) -> usize {
+        let (width, height) = self.glyph_brush.texture_dimensions();
+        width as usize * height as usize
+    }
+
+    /// Replaces the texture cache by the largest square one within
+    /// `max_bytes` on the next draw, when the glyphs it holds would take at
+    /// most half of it. Returns `false` when the cache is kept as it is.
+    pub fn shrink_texture(&mut self, max_bytes: usize) -> bool {
+        let (width, height) = self.glyph_brush.texture_dimensions();
+        let mut side = MIN_SHRUNK_TEXTURE;
+        while (side as usize * 2).pow(2) <= max_bytes {
+            side *= 2;
+        }
+        if side >= width && side >= height {
+            return false;
+        }
+
+        let used: usize = self
+            .glyph_brush
+            .texture_cache_rows()
+            .iter()
+            .map(|row| row.width as usize * row.height as usize)
+            .sum();
+        if used * 2 > (side as usize).pow(2) {
+            return false;
+        }
+        self.shrink_to = Some((side.min(width), side.min(height)));
+        true
+    }
+
     /// Adds an additional font to the one(s) initially added on build.
     ///
     /// Returns a new [`FontId`](struct.FontId.html) to reference this font.
@@ -136,9 +205,23 @@ where
         device: &wgpu::Device,
         queue: &mut wgpu::Queue,
         encoder: &mut wgpu::CommandEncoder,
+        staging_belt: &mut wgpu::util::StagingBelt,
     ) {
         let pipeline = &mut self.pipeline;
 
+        if let Some((width, height)) = self.shrink_to.take() {
+            pipeline.increase_cache_size(device, width, height);
+            self.glyph_brush.resize_texture(width, height);
+        }
+
+        // The atlas isn't a section, the vertices are built again when it
+        // comes or goes.
+        let atlas = self.atlas.take();
+        if atlas != self.drawn_atlas {
+            self.glyph_brush.invalidate_last_draw();
+            self.drawn_atlas = atlas;
+        }
+
         let mut brush_action;
 
         loop {
@@ -179,7 +262,11 @@ where
 
         match brush_action.unwrap() {
             BrushAction::Draw(mut verts) => {
-                self.pipeline.upload(device, encoder, &mut verts);
+                if let Some((area, color)) = atlas {
+                    verts.push(Instance::atlas(area, color));
+                }
+                self.pipeline
+                    .upload(device, encoder, staging_belt, &mut verts);
             }
             BrushAction::ReDraw => {}
         };
@@ -192,20 +279,24 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<(), F, H> {
         filter_mode: wgpu::FilterMode,
         multisample: wgpu::MultisampleState,
         render_format: wgpu::TextureFormat,
+        color_output: crate::context::ColorOutput,
         raw_builder: crate::glyph::GlyphBrushBuilder<F, H>,
     ) -> Self {
         let glyph_brush = raw_builder.build();
-        let (cache_width, cache_height) = glyph_brush.texture_dimensions();
+        let cache_dimensions = glyph_brush.texture_dimensions();
         GlyphBrush {
             pipeline: Pipeline::<()>::new(
                 device,
                 filter_mode,
                 multisample,
                 render_format,
-                cache_width,
-                cache_height,
+                color_output,
+                cache_dimensions,
             ),
             glyph_brush,
+            atlas: None,
+            drawn_atlas: None,
+            shrink_to: None,
         }
     }
 
@@ -226,12 +317,14 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<(), F, H> {
         encoder: &mut wgpu::CommandEncoder,
         target: &wgpu::TextureView,
     ) -> Result<(), String> {
+        self.pipeline.set_color_filter(context.color_filter);
         let device = &context.device;
         let queue = &mut context.queue;
         self.draw_queued_with_transform(
             device,
             queue,
             encoder,
+            &mut context.staging_belt,
             target,
             orthographic_projection(context.size.width, context.size.height),
         )
@@ -254,10 +347,11 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<(), F, H> {
         device: &wgpu::Device,
         queue: &mut wgpu::Queue,
         encoder: &mut wgpu::CommandEncoder,
+        staging_belt: &mut wgpu::util::StagingBelt,
         target: &wgpu::TextureView,
         transform: [f32; 16],
     ) -> Result<(), String> {
-        self.process_queued(device, queue, encoder);
+        self.process_queued(device, queue, encoder, staging_belt);
         self.pipeline.draw(queue, encoder, target, transform, None);
 
         Ok(())
@@ -277,14 +371,19 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<(), F, H> {
     #[inline]
     pub fn _draw_queued_with_transform_and_scissoring(
         &mut self,
-        device: &wgpu::Device,
-        queue: &mut wgpu::Queue,
-        encoder: &mut wgpu::CommandEncoder,
-        target: &wgpu::TextureView,
+        config: (
+            &wgpu::Device,
+            &mut wgpu::Queue,
+            &mut wgpu::CommandEncoder,
+            &mut wgpu::util::StagingBelt,
+            &wgpu::TextureView,
+        ),
         transform: [f32; 16],
         region: Region,
     ) -> Result<(), String> {
-        self.process_queued(device, queue, encoder);
+        let (device, queue, encoder, staging_belt, target) = config;
+
+        self.process_queued(device, queue, encoder, staging_belt);
         self.pipeline
             .draw(queue, encoder, target, transform, Some(region));
 
@@ -298,22 +397,26 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<wgpu::DepthStencilState, F, H> {
         filter_mode: wgpu::FilterMode,
         multisample: wgpu::MultisampleState,
         render_format: wgpu::TextureFormat,
+        color_output: crate::context::ColorOutput,
         depth_stencil_state: wgpu::DepthStencilState,
         raw_builder: crate::glyph::GlyphBrushBuilder<F, H>,
     ) -> Self {
         let glyph_brush = raw_builder.build();
-        let (cache_width, cache_height) = glyph_brush.texture_dimensions();
+        let cache_dimensions = glyph_brush.texture_dimensions();
         GlyphBrush {
             pipeline: Pipeline::<wgpu::DepthStencilState>::new(
                 device,
                 filter_mode,
                 multisample,
                 render_format,
+                color_output,
                 depth_stencil_state,
-                cache_width,
-                cache_height,
+                cache_dimensions,
             ),
             glyph_brush,
+            atlas: None,
+            drawn_atlas: None,
+            shrink_to: None,
         }
     }
 
@@ -330,18 +433,18 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<wgpu::DepthStencilState, F, H> {
     #[inline]
     pub fn _draw_queued(
         &mut self,
-        device: &wgpu::Device,
-        queue: &mut wgpu::Queue,
-        encoder: &mut wgpu::CommandEncoder,
-        target: &wgpu::TextureView,
+        config: (
+            &wgpu::Device,
+            &mut wgpu::Queue,
+            &mut wgpu::CommandEncoder,
+            &mut wgpu::util::StagingBelt,
+            &wgpu::TextureView,
+        ),
         depth_stencil_attachment: wgpu::RenderPassDepthStencilAttachment,
         w_h: (u32, u32),
     ) -> Result<(), String> {
         self.draw_queued_with_transform(
-            device,
-            queue,
-            encoder,
-            target,
+            config,
             depth_stencil_attachment,
             orthographic_projection(w_h.0, w_h.1),
         )
@@ -362,14 +465,19 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<wgpu::DepthStencilState, F, H> {
     #[allow(dead_code)]
     pub fn draw_queued_with_transform(
         &mut self,
-        device: &wgpu::Device,
-        queue: &mut wgpu::Queue,
-        encoder: &mut wgpu::CommandEncoder,
-        target: &wgpu::TextureView,
+        config: (
+            &wgpu::Device,
+            &mut wgpu::Queue,
+            &mut wgpu::CommandEncoder,
+            &mut wgpu::util::StagingBelt,
+            &wgpu::TextureView,
+        ),
         depth_stencil_attachment: wgpu::RenderPassDepthStencilAttachment,
         transform: [f32; 16],
     ) -> Result<(), String> {
-        self.process_queued(device, queue, encoder);
+        let (device, queue, encoder, staging_belt, target) = config;
+
+        self.process_queued(device, queue, encoder, staging_belt);
         self.pipeline.draw(
             (queue, encoder, target),
             depth_stencil_attachment,
@@ -394,20 +502,20 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<wgpu::DepthStencilState, F, H> {
     #[inline]
     pub fn _draw_queued_with_transform_and_scissoring(
         &mut self,
-        // config: (device, staging_belt, encoder, target),
         config: (
             &wgpu::Device,
             &mut wgpu::Queue,
             &mut wgpu::CommandEncoder,
+            &mut wgpu::util::StagingBelt,
             &wgpu::TextureView,
         ),
         depth_stencil_attachment: wgpu::RenderPassDepthStencilAttachment,
         transform: [f32; 16],
         region: Region,
     ) -> Result<(), String> {
-        let (device, queue, encoder, target) = config;
+        let (device, queue, encoder, staging_belt, target) = config;
 
-        self.process_queued(device, queue, encoder);
+        self.process_queued(device, queue, encoder, staging_belt);
 
         self.pipeline.draw(
             (queue, encoder, target),
@@ -1,6 +1,7 @@
 mod cache;
 
 use crate::components::text::Region;
+use crate::context::{ColorFilter, ColorOutput};
 use cache::Cache;
 use std::borrow::Cow;
 
@@ -34,26 +35,50 @@ pub struct Pipeline<Depth> {
     current_instances: usize,
     supported_instances: usize,
     current_transform: [f32; 16],
+    color_filter: ColorFilter,
+    current_color_filter: ColorFilter,
     depth: PhantomData<Depth>,
 }
 
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+struct Globals {
+    transform: [f32; 16],
+    color_matrix: [f32; 16],
+    color_filtered: u32,
+    // Aligned to the mat4x4<f32> members.
+    _padding: [u32; 3],
+}
+
+impl Globals {
+    fn new(transform: [f32; 16], color_filter: ColorFilter) -> Globals {
+        let color_matrix = color_filter.matrix();
+        Globals {
+            transform,
+            color_matrix: color_matrix.unwrap_or(IDENTITY_MATRIX),
+            color_filtered: color_matrix.is_some() as u32,
+            _padding: [0; 3],
+        }
+    }
+}
+
 impl Pipeline<()> {
     pub fn new(
         device: &wgpu::Device,
         filter_mode: wgpu::FilterMode,
         multisample: wgpu::MultisampleState,
         render_format: wgpu::TextureFormat,
-        cache_width: u32,
-        cache_height: u32,
+        color_output: ColorOutput,
+        cache_dimensions: (u32, u32),
     ) -> Pipeline<()> {
         build(
             device,
             filter_mode,
             multisample,
             render_format,
+            color_output,
             None,
-            cache_width,
-            cache_height,
+            cache_dimensions,
         )
     }
 
@@ -75,18 +100,18 @@ impl Pipeline<wgpu::DepthStencilState> {
         filter_mode: wgpu::FilterMode,
         multisample: wgpu::MultisampleState,
         render_format: wgpu::TextureFormat,
+        color_output: ColorOutput,
         depth_stencil_state: wgpu::DepthStencilState,
-        cache_width: u32,
-        cache_height: u32,
+        cache_dimensions: (u32, u32),
     ) -> Pipeline<wgpu::DepthStencilState> {
         build(
             device,
             filter_mode,
             multisample,
             render_format,
+            color_output,
             Some(depth_stencil_state),
-            cache_width,
-            cache_height,
+            cache_dimensions,
         )
     }
 
@@ -114,6 +139,10 @@ impl Pipeline<wgpu::DepthStencilState> {
 }
 
 impl<Depth> Pipeline<Depth> {
+    pub fn set_color_filter(&mut self, color_filter: ColorFilter) {
+        self.color_filter = color_filter;
+    }
+
     pub fn update_cache(
         &mut self,
         queue: &mut wgpu::Queue,
@@ -145,6 +174,7 @@ impl<Depth> Pipeline<Depth> {
         &mut self,
         device: &wgpu::Device,
         encoder: &mut wgpu::CommandEncoder,
+        staging_belt: &mut wgpu::util::StagingBelt,
         instances: &mut [Instance],
     ) {
         if instances.is_empty() {
@@ -163,24 +193,12 @@ impl<Depth> Pipeline<Depth> {
             self.supported_instances = instances.len();
         }
 
-        let instances_bytes = bytemuck::cast_slice(instances);
-
-        if !instances_bytes.is_empty() {
-            let instances_buffer =
-                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("sugarloaf::text::Pipeline instances"),
-                    contents: instances_bytes,
-                    usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_SRC,
-                });
-
-            encoder.copy_buffer_to_buffer(
-                &instances_buffer,
-                0,
-                &self.instances,
-                0,
-                mem::size_of::<Instance>() as u64 * instances.len() as u64,
-            );
-            // queue.write_buffer(&self.instances, 0, instances_bytes);
+        let instances_bytes: &[u8] = bytemuck::cast_slice(instances);
+
+        if let Some(size) = wgpu::BufferSize::new(instances_bytes.len() as u64) {
+            staging_belt
+                .write_buffer(encoder, &self.instances, 0, size, device)
+                .copy_from_slice(instances_bytes);
         }
 
         self.current_instances = instances.len();
@@ -196,13 +214,14 @@ fn build<D>(
     filter_mode: wgpu::FilterMode,
     multisample: wgpu::MultisampleState,
     render_format: wgpu::TextureFormat,
+    color_output: ColorOutput,
     depth_stencil: Option<wgpu::DepthStencilState>,
-    cache_width: u32,
-    cache_height: u32,
+    cache_dimensions: (u32, u32),
 ) -> Pipeline<D> {
+    let (cache_width, cache_height) = cache_dimensions;
     let transform = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: None,
-        contents: bytemuck::cast_slice(&IDENTITY_MATRIX),
+        contents: bytemuck::bytes_of(&Globals::new(IDENTITY_MATRIX, ColorFilter::None)),
         usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
     });
 
@@ -224,12 +243,12 @@ fn build<D>(
             entries: &[
                 wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
                         min_binding_size: wgpu::BufferSize::new(
-                            mem::size_of::<[f32; 16]>() as u64,
+                            mem::size_of::<Globals>() as u64,
                         ),
                     },
                     count: None,
@@ -271,7 +290,9 @@ fn build<D>(
 
     let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label: Some("Text Shader"),
-        source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("text.wgsl"))),
+        source: wgpu::ShaderSource::Wgsl(Cow::Owned(
+            color_output.shader() + include_str!("text.wgsl"),
+        )),
     });
 
     let raw = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -323,6 +344,8 @@ fn build<D>(
         current_instances: 0,
         supported_instances: Instance::INITIAL_AMOUNT,
         current_transform: [0.0; 16],
+        color_filter: ColorFilter::None,
+        current_color_filter: ColorFilter::None,
         depth: PhantomData,
     }
 }
@@ -339,10 +362,15 @@ fn draw<D>(
     region: Option<Region>,
 ) {
     let (queue, encoder, target) = config;
-    if transform != pipeline.current_transform {
-        queue.write_buffer(&pipeline.transform, 0, bytemuck::cast_slice(&transform));
+    let color_filter = pipeline.color_filter;
+    if transform != pipeline.current_transform
+        || color_filter != pipeline.current_color_filter
+    {
+        let globals = Globals::new(transform, color_filter);
+        queue.write_buffer(&pipeline.transform, 0, bytemuck::bytes_of(&globals));
 
         pipeline.current_transform = transform;
+        pipeline.current_color_filter = color_filter;
     }
 
     let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -413,6 +441,17 @@ pub struct Instance {
 impl Instance {
     const INITIAL_AMOUNT: usize = 50_000;
 
+    /// The whole cache texture stretched over `area`.
+    pub fn atlas(area: Rect, color: [f32; 4]) -> Instance {
+        Instance {
+            left_top: [area.min.x, area.max.y, 0.0],
+            right_bottom: [area.max.x, area.min.y],
+            tex_left_top: [0.0, 1.0],
+            tex_right_bottom: [1.0, 0.0],
+            color,
+        }
+    }
+
     pub fn from_vertex(
         crate::glyph::GlyphVertex {
             mut tex_coords,
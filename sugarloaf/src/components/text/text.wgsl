@@ -1,5 +1,7 @@
 struct Globals {
     transform: mat4x4<f32>,
+    color_matrix: mat4x4<f32>,
+    color_filtered: u32,
 }
 
 @group(0) @binding(0) var<uniform> globals: Globals;
@@ -65,5 +67,9 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
         discard;
     }
 
-    return input.f_color * vec4<f32>(1.0, 1.0, 1.0, alpha);
+    var color: vec4<f32> = input.f_color;
+    if (globals.color_filtered != 0u) {
+        color = filter_color(color, globals.color_matrix);
+    }
+    return output_color(color) * vec4<f32>(1.0, 1.0, 1.0, alpha);
 }
@@ -0,0 +1,197 @@
+use crate::context::Context;
+use std::borrow::Cow;
+
+/// Lowest render scale, below it the text is no longer readable.
+pub const MIN_RENDER_SCALE: f32 = 0.25;
+
+/// Size of the reduced resolution texture for a surface of `size`.
+#[inline]
+pub fn scaled_size(size: (u32, u32), render_scale: f32) -> (u32, u32) {
+    let scale = |value: u32| ((value as f32 * render_scale).round() as u32).max(1);
+    (scale(size.0), scale(size.1))
+}
+
+struct Target {
+    size: (u32, u32),
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+}
+
+/// Frames are drawn into a texture smaller than the surface and stretched
+/// over it by a final pass, so the pipelines shade fewer pixels.
+pub struct UpscaleBrush {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    target: Option<Target>,
+}
+
+impl UpscaleBrush {
+    pub fn new(context: &Context) -> Self {
+        let device = &context.device;
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("upscale::Pipeline source"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float {
+                                filterable: true,
+                            },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(
+                            wgpu::SamplerBindingType::Filtering,
+                        ),
+                        count: None,
+                    },
+                ],
+            });
+
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        // The texture already holds the output colors, they are copied as is.
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("upscale::Pipeline shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("upscale.wgsl"))),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("upscale::Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: context.format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("upscale::Pipeline sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        UpscaleBrush {
+            pipeline,
+            bind_group_layout,
+            sampler,
+            target: None,
+        }
+    }
+
+    /// Texture to draw the frame into, recreated when its size changes.
+    pub fn target(&mut self, context: &Context, size: (u32, u32)) -> &wgpu::TextureView {
+        if self.target.as_ref().map(|target| target.size) != Some(size) {
+            let texture = context.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("upscale::Pipeline target"),
+                size: wgpu::Extent3d {
+                    width: size.0,
+                    height: size.1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: context.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let bind_group =
+                context
+                    .device
+                    .create_bind_group(&wgpu::BindGroupDescriptor {
+                        label: Some("upscale::Pipeline source"),
+                        layout: &self.bind_group_layout,
+                        entries: &[
+                            wgpu::BindGroupEntry {
+                                binding: 0,
+                                resource: wgpu::BindingResource::TextureView(&view),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 1,
+                                resource: wgpu::BindingResource::Sampler(&self.sampler),
+                            },
+                        ],
+                    });
+
+            self.target = Some(Target {
+                size,
+                view,
+                bind_group,
+            });
+        }
+
+        &self.target.as_ref().unwrap().view
+    }
+
+    /// Stretch the target texture over `view`.
+    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let target = match &self.target {
+            Some(target) => target,
+            None => return,
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("upscale::Pipeline render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &target.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scaled_size() {
+        assert_eq!(scaled_size((5120, 2880), 0.75), (3840, 2160));
+        assert_eq!(scaled_size((1001, 3), 0.5), (501, 2));
+        assert_eq!(scaled_size((1, 1), MIN_RENDER_SCALE), (1, 1));
+    }
+}
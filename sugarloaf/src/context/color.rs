@@ -0,0 +1,371 @@
+// Color spaces of the colors given to sugarloaf and of the window surface.
+//
+// Colors are sRGB unless `ColorSpace::DisplayP3` is selected, wide gamut colors
+// then need a surface holding values out of the sRGB range. wgpu configures
+// `Rgba16Float` surfaces as extended linear sRGB, with EDR on Metal, scRGB on
+// DX12 and `EXTENDED_SRGB_LINEAR_EXT` on Vulkan. When the surface has no such
+// format the colors are mapped into the sRGB gamut instead of being clipped.
+//
+// Color filters are matrices applied to linear colors before the output
+// conversion, they live in the uniforms of the pipelines so they can change
+// without building the pipelines again.
+
+/// Color space of the colors given to sugarloaf.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    DisplayP3,
+}
+
+/// Conversion applied to the colors written to the surface.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorOutput {
+    /// Colors are written as they are.
+    #[default]
+    Srgb,
+    /// Display P3 colors on an extended linear sRGB surface.
+    Extended,
+    /// Display P3 colors mapped into the sRGB gamut.
+    GamutMapped,
+}
+
+/// Accessibility filter applied to every color.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorFilter {
+    #[default]
+    None,
+    /// Simulates the lack of red cones.
+    Protanopia,
+    /// Simulates the lack of green cones.
+    Deuteranopia,
+    /// Simulates the lack of blue cones.
+    Tritanopia,
+    /// Pushes colors away from the middle gray.
+    HighContrast,
+}
+
+/// Format holding colors out of the sRGB range.
+pub const EXTENDED_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+// Display P3 to sRGB, both linear with the D65 white point, by rows.
+const P3_TO_SRGB: [[f32; 3]; 3] = [
+    [1.224_940_1, -0.224_940_4, 0.0],
+    [-0.042_056_9, 1.042_057_1, 0.0],
+    [-0.019_637_6, -0.078_636_1, 1.098_273_5],
+];
+
+// Color vision deficiency simulations from Machado, Oliveira and Fernandes
+// (2009) at full severity, by rows.
+const PROTANOPIA: [[f32; 3]; 3] = [
+    [0.152_286, 1.052_583, -0.204_868],
+    [0.114_503, 0.786_281, 0.099_216],
+    [-0.003_882, -0.048_116, 1.051_998],
+];
+const DEUTERANOPIA: [[f32; 3]; 3] = [
+    [0.367_322, 0.860_646, -0.227_968],
+    [0.280_085, 0.672_501, 0.047_413],
+    [-0.011_820, 0.042_940, 0.968_881],
+];
+const TRITANOPIA: [[f32; 3]; 3] = [
+    [1.255_528, -0.076_749, -0.178_779],
+    [-0.078_411, 0.930_809, 0.147_602],
+    [0.004_733, 0.691_367, 0.303_900],
+];
+
+const HIGH_CONTRAST: f32 = 1.6;
+// Linear value of the sRGB middle gray.
+const MIDDLE_GRAY: f32 = 0.214;
+
+const SHADER_FUNCTIONS: &str = "
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, color <= vec3<f32>(0.04045));
+}
+
+fn linear_to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, color <= vec3<f32>(0.0031308));
+}
+
+fn p3_to_srgb(color: vec3<f32>) -> vec3<f32> {
+    return mat3x3<f32>(
+        vec3<f32>(1.2249401, -0.0420569, -0.0196376),
+        vec3<f32>(-0.2249404, 1.0420571, -0.0786361),
+        vec3<f32>(0.0, 0.0, 1.0982735),
+    ) * color;
+}
+
+fn map_into_srgb(color: vec3<f32>) -> vec3<f32> {
+    let luminance = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    var mapped = color;
+    let low = min(min(mapped.r, mapped.g), mapped.b);
+    if (low < 0.0) {
+        mapped = mix(vec3<f32>(luminance), mapped, luminance / (luminance - low));
+    }
+    let high = max(max(mapped.r, mapped.g), mapped.b);
+    if (high > 1.0) {
+        mapped = mapped / high;
+    }
+    return clamp(mapped, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn filter_color(color: vec4<f32>, matrix: mat4x4<f32>) -> vec4<f32> {
+    let filtered = (matrix * vec4<f32>(srgb_to_linear(color.rgb), 1.0)).rgb;
+    let clamped = clamp(filtered, vec3<f32>(0.0), vec3<f32>(1.0));
+    return vec4<f32>(linear_to_srgb(clamped), color.a);
+}
+";
+
+impl ColorFilter {
+    /// Column major matrix of the filter, its last column is an offset.
+    pub fn matrix(self) -> Option<[f32; 16]> {
+        let (rows, offset) = match self {
+            ColorFilter::None => return None,
+            ColorFilter::Protanopia => (PROTANOPIA, 0.0),
+            ColorFilter::Deuteranopia => (DEUTERANOPIA, 0.0),
+            ColorFilter::Tritanopia => (TRITANOPIA, 0.0),
+            ColorFilter::HighContrast => (
+                [
+                    [HIGH_CONTRAST, 0.0, 0.0],
+                    [0.0, HIGH_CONTRAST, 0.0],
+                    [0.0, 0.0, HIGH_CONTRAST],
+                ],
+                MIDDLE_GRAY * (1.0 - HIGH_CONTRAST),
+            ),
+        };
+
+        let mut matrix = [0.0; 16];
+        for (row, values) in rows.iter().enumerate() {
+            for (column, value) in values.iter().enumerate() {
+                matrix[column * 4 + row] = *value;
+            }
+            matrix[12 + row] = offset;
+        }
+        matrix[15] = 1.0;
+        Some(matrix)
+    }
+
+    /// Same filter as `filter_color`, for the colors used outside of the
+    /// shaders like the clear color.
+    pub fn apply(self, color: [f32; 4]) -> [f32; 4] {
+        let Some(matrix) = self.matrix() else {
+            return color;
+        };
+
+        let linear = srgb_to_linear(color);
+        let mut filtered = [0.0; 3];
+        for (row, value) in filtered.iter_mut().enumerate() {
+            *value = matrix[row] * linear[0]
+                + matrix[4 + row] * linear[1]
+                + matrix[8 + row] * linear[2]
+                + matrix[12 + row];
+        }
+
+        let [r, g, b] = filtered.map(|value| linear_to_srgb(value.clamp(0.0, 1.0)));
+        [r, g, b, color[3]]
+    }
+}
+
+impl ColorOutput {
+    pub fn new(color_space: ColorSpace, format: wgpu::TextureFormat) -> Self {
+        match color_space {
+            ColorSpace::Srgb => ColorOutput::Srgb,
+            ColorSpace::DisplayP3 if format == EXTENDED_FORMAT => ColorOutput::Extended,
+            ColorSpace::DisplayP3 => ColorOutput::GamutMapped,
+        }
+    }
+
+    /// WGSL functions prepended to the shaders, `output_color` converts the
+    /// colors given to sugarloaf and `output_image_color` the sRGB images.
+    pub fn shader(self) -> String {
+        let output = match self {
+            ColorOutput::Srgb => {
+                "fn output_color(color: vec4<f32>) -> vec4<f32> { return color; }
+                 fn output_image_color(color: vec4<f32>) -> vec4<f32> { return color; }"
+            }
+            ColorOutput::Extended => {
+                "fn output_color(color: vec4<f32>) -> vec4<f32> {
+                     return vec4<f32>(p3_to_srgb(srgb_to_linear(color.rgb)), color.a);
+                 }
+                 fn output_image_color(color: vec4<f32>) -> vec4<f32> {
+                     return vec4<f32>(srgb_to_linear(color.rgb), color.a);
+                 }"
+            }
+            ColorOutput::GamutMapped => {
+                "fn output_color(color: vec4<f32>) -> vec4<f32> {
+                     let mapped = map_into_srgb(p3_to_srgb(srgb_to_linear(color.rgb)));
+                     return vec4<f32>(linear_to_srgb(mapped), color.a);
+                 }
+                 fn output_image_color(color: vec4<f32>) -> vec4<f32> { return color; }"
+            }
+        };
+
+        format!("{SHADER_FUNCTIONS}\n{output}\n")
+    }
+
+    /// Same conversion as `output_color`, for the colors used outside of the
+    /// shaders like the clear color.
+    pub fn convert(self, color: [f32; 4]) -> [f32; 4] {
+        let rgb = match self {
+            ColorOutput::Srgb => return color,
+            ColorOutput::Extended => p3_to_srgb(srgb_to_linear(color)),
+            ColorOutput::GamutMapped => {
+                let [r, g, b] = map_into_srgb(p3_to_srgb(srgb_to_linear(color)));
+                [linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b)]
+            }
+        };
+
+        [rgb[0], rgb[1], rgb[2], color[3]]
+    }
+}
+
+fn srgb_to_linear(color: [f32; 4]) -> [f32; 3] {
+    let channel = |value: f32| {
+        if value <= 0.04045 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    [channel(color[0]), channel(color[1]), channel(color[2])]
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn p3_to_srgb(color: [f32; 3]) -> [f32; 3] {
+    P3_TO_SRGB.map(|row| row[0] * color[0] + row[1] * color[1] + row[2] * color[2])
+}
+
+fn map_into_srgb(color: [f32; 3]) -> [f32; 3] {
+    let luminance = 0.2126 * color[0] + 0.7152 * color[1] + 0.0722 * color[2];
+    let mut mapped = color;
+
+    // Desaturate towards the gray of the same luminance until it fits.
+    let low = mapped.iter().copied().fold(f32::INFINITY, f32::min);
+    if low < 0.0 {
+        let amount = luminance / (luminance - low);
+        mapped = mapped.map(|value| luminance + (value - luminance) * amount);
+    }
+    let high = mapped.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    if high > 1.0 {
+        mapped = mapped.map(|value| value / high);
+    }
+
+    mapped.map(|value| value.clamp(0.0, 1.0))
+}
+
+/// Clear color of the surface, with the same filter and conversion as the
+/// colors drawn by the shaders.
+pub fn clear_color(
+    color: wgpu::Color,
+    filter: ColorFilter,
+    output: ColorOutput,
+) -> wgpu::Color {
+    let [r, g, b, a] = output.convert(filter.apply([
+        color.r as f32,
+        color.g as f32,
+        color.b as f32,
+        color.a as f32,
+    ]));
+    wgpu::Color {
+        r: r as f64,
+        g: g as f64,
+        b: b as f64,
+        a: a as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(left: [f32; 4], right: [f32; 4]) {
+        for (left, right) in left.iter().zip(right) {
+            assert!((left - right).abs() < 0.001, "{left:?} != {right:?}");
+        }
+    }
+
+    #[test]
+    fn test_color_output() {
+        use wgpu::TextureFormat::{Bgra8Unorm, Rgba16Float};
+
+        assert_eq!(
+            ColorOutput::new(ColorSpace::Srgb, Rgba16Float),
+            ColorOutput::Srgb
+        );
+        assert_eq!(
+            ColorOutput::new(ColorSpace::DisplayP3, Rgba16Float),
+            ColorOutput::Extended
+        );
+        assert_eq!(
+            ColorOutput::new(ColorSpace::DisplayP3, Bgra8Unorm),
+            ColorOutput::GamutMapped
+        );
+    }
+
+    #[test]
+    fn test_convert() {
+        let p3_red = [1.0, 0.0, 0.0, 0.5];
+        assert_eq!(ColorOutput::Srgb.convert(p3_red), p3_red);
+
+        // Out of the sRGB gamut, kept on extended surfaces.
+        assert_close(
+            ColorOutput::Extended.convert(p3_red),
+            [1.224_94, -0.042_06, -0.019_64, 0.5],
+        );
+
+        // Mapped into sRGB, still the most saturated red it can show.
+        let mapped = ColorOutput::GamutMapped.convert(p3_red);
+        assert!(mapped.iter().all(|value| (0.0..=1.0).contains(value)));
+        assert!(mapped[0] > 0.99 && mapped[1] < 0.2 && mapped[2] < 0.2);
+        assert_eq!(mapped[3], 0.5);
+
+        // White and grays are the same in both spaces.
+        let gray = [0.5, 0.5, 0.5, 1.0];
+        assert_close(ColorOutput::GamutMapped.convert(gray), gray);
+        let linear = srgb_to_linear(gray)[0];
+        assert_close(
+            ColorOutput::Extended.convert(gray),
+            [linear, linear, linear, 1.0],
+        );
+    }
+
+    #[test]
+    fn test_color_filter() {
+        assert_eq!(ColorFilter::None.matrix(), None);
+        let color = [0.8, 0.3, 0.1, 0.7];
+        assert_eq!(ColorFilter::None.apply(color), color);
+
+        // Simulations keep grays and white.
+        for filter in [
+            ColorFilter::Protanopia,
+            ColorFilter::Deuteranopia,
+            ColorFilter::Tritanopia,
+        ] {
+            assert_close(filter.apply([1.0, 1.0, 1.0, 1.0]), [1.0, 1.0, 1.0, 1.0]);
+            assert_close(filter.apply([0.5, 0.5, 0.5, 0.2]), [0.5, 0.5, 0.5, 0.2]);
+        }
+
+        // Without green cones both red and green are seen as shades of yellow.
+        for color in [[1.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0]] {
+            let [r, g, _, _] = ColorFilter::Deuteranopia.apply(color);
+            assert!((r - g).abs() < 0.15, "{r} {g}");
+        }
+
+        // Middle gray stays, darker and lighter colors move away from it.
+        let contrast = ColorFilter::HighContrast;
+        assert_close(contrast.apply([0.5, 0.5, 0.5, 1.0]), [0.5, 0.5, 0.5, 1.0]);
+        assert!(contrast.apply([0.3, 0.3, 0.3, 1.0])[0] < 0.3);
+        assert!(contrast.apply([0.7, 0.7, 0.7, 1.0])[0] > 0.7);
+        assert_close(contrast.apply([1.0, 0.0, 1.0, 1.0]), [1.0, 0.0, 1.0, 1.0]);
+    }
+}
@@ -1,41 +1,38 @@
+mod color;
+mod timer;
+
+pub use color::{ColorFilter, ColorOutput, ColorSpace};
+pub use timer::FrameTimer;
+
+/// Size of each staging belt chunk, large enough to hold the instances
+/// of a regular frame so uploads happen from a single chunk.
+const STAGING_BELT_CHUNK_SIZE: wgpu::BufferAddress = 1 << 20;
+
 #[derive(Debug)]
 pub struct Context {
     pub device: wgpu::Device,
-    pub surface: wgpu::Surface,
+    /// None for headless contexts, their frames are only read back.
+    pub surface: Option<wgpu::Surface>,
     pub queue: wgpu::Queue,
     pub format: wgpu::TextureFormat,
     pub size: winit::dpi::PhysicalSize<u32>,
     pub scale: f32,
     pub adapter_info: wgpu::AdapterInfo,
+    pub staging_belt: wgpu::util::StagingBelt,
+    pub present_mode: wgpu::PresentMode,
+    pub color_output: ColorOutput,
+    pub color_filter: ColorFilter,
+    default_present_mode: wgpu::PresentMode,
+    supported_present_modes: Vec<wgpu::PresentMode>,
 }
 
 impl Context {
     pub async fn new(
         winit_window: &winit::window::Window,
         power_preference: wgpu::PowerPreference,
+        color_space: ColorSpace,
     ) -> Context {
-        #[cfg(target_arch = "wasm32")]
-        let default_backend = wgpu::Backends::BROWSER_WEBGPU | wgpu::Backends::GL;
-        #[cfg(not(target_arch = "wasm32"))]
-        let default_backend = wgpu::Backends::all();
-
-        // The backend can be configured using the `WGPU_BACKEND`
-        // environment variable. If the variable is not set, the primary backend
-        // will be used. The following values are allowed:
-        // - `vulkan`
-        // - `metal`
-        // - `dx12`
-        // - `dx11`
-        // - `gl`
-        // - `webgpu`
-        // - `primary`
-        let backend = wgpu::util::backend_bits_from_env().unwrap_or(default_backend);
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: backend,
-            ..Default::default()
-        });
-
-        log::info!("selected instance: {instance:?}");
+        let instance = create_instance();
 
         #[cfg(not(target_arch = "wasm32"))]
         {
@@ -96,34 +93,31 @@ impl Context {
             format = filtered_formats.first().unwrap().to_owned();
         }
 
+        // Wide gamut colors are opt-in, the extended format is taken even where
+        // it is filtered out above.
+        if color_space == ColorSpace::DisplayP3 {
+            if caps.formats.contains(&color::EXTENDED_FORMAT) {
+                format = color::EXTENDED_FORMAT;
+            } else {
+                log::warn!(
+                    "the surface has no extended range format, wide gamut colors are mapped into sRGB"
+                );
+            }
+        }
+        let color_output = ColorOutput::new(color_space, format);
+
         log::info!(
-            "Sugarloaf selected format: {format:?} from {:?}",
+            "Sugarloaf selected format: {format:?} from {:?}, color output {color_output:?}",
             caps.formats
         );
-        let (device, queue) = (async {
-            {
-                if let Ok(result) = adapter
-                    .request_device(&wgpu::DeviceDescriptor::default(), None)
-                    .await
-                {
-                    result
-                } else {
-                    // These downlevel limits will allow the code to run on all possible hardware
-                    adapter
-                        .request_device(
-                            &wgpu::DeviceDescriptor {
-                                label: None,
-                                features: wgpu::Features::empty(),
-                                limits: wgpu::Limits::downlevel_webgl2_defaults(),
-                            },
-                            None,
-                        )
-                        .await
-                        .expect("Request device")
-                }
-            }
-        })
-        .await;
+        let (device, queue) = request_device(&adapter).await;
+
+        // Low power prefers strict vsync so frames are never produced faster
+        // than the display can show them.
+        let present_mode = match power_preference {
+            wgpu::PowerPreference::LowPower => wgpu::PresentMode::Fifo,
+            _ => wgpu::PresentMode::AutoVsync,
+        };
 
         surface.configure(
             &device,
@@ -134,35 +128,177 @@ impl Context {
                 height: size.height,
                 view_formats: vec![],
                 alpha_mode: wgpu::CompositeAlphaMode::Auto,
-                present_mode: wgpu::PresentMode::AutoVsync,
+                present_mode,
             },
         );
 
         Context {
             device,
             queue,
-            surface,
+            surface: Some(surface),
             format,
             size,
             scale: scale as f32,
             adapter_info: adapter.get_info(),
+            staging_belt: wgpu::util::StagingBelt::new(STAGING_BELT_CHUNK_SIZE),
+            present_mode,
+            color_output,
+            color_filter: ColorFilter::None,
+            default_present_mode: present_mode,
+            supported_present_modes: caps.present_modes,
+        }
+    }
+
+    /// Context without a window drawing into `Rgba8Unorm` textures, frames
+    /// are only read back. Returns None when no adapter is available.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn new_headless(
+        size: winit::dpi::PhysicalSize<u32>,
+        scale: f32,
+        power_preference: wgpu::PowerPreference,
+    ) -> Option<Context> {
+        let instance = create_instance();
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference,
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            })
+            .await?;
+
+        log::info!("Selected headless adapter: {:?}", adapter.get_info());
+
+        let format = wgpu::TextureFormat::Rgba8Unorm;
+        let (device, queue) = request_device(&adapter).await;
+        let present_mode = wgpu::PresentMode::AutoVsync;
+
+        Some(Context {
+            device,
+            queue,
+            surface: None,
+            format,
+            size,
+            scale,
+            adapter_info: adapter.get_info(),
+            staging_belt: wgpu::util::StagingBelt::new(STAGING_BELT_CHUNK_SIZE),
+            present_mode,
+            color_output: ColorOutput::new(ColorSpace::Srgb, format),
+            color_filter: ColorFilter::None,
+            default_present_mode: present_mode,
+            supported_present_modes: vec![],
+        })
+    }
+
+    /// Clear color of the surface, filtered and converted like the colors
+    /// drawn by the pipelines.
+    pub fn clear_color(&self, color: wgpu::Color) -> wgpu::Color {
+        color::clear_color(color, self.color_filter, self.color_output)
+    }
+
+    /// Switch to the lowest latency present mode supported by the surface,
+    /// or back to the default one.
+    pub fn set_low_latency(&mut self, enabled: bool) {
+        let present_mode = if enabled {
+            // Mailbox avoids tearing, AutoNoVsync falls back to whatever
+            // the platform supports.
+            [wgpu::PresentMode::Mailbox, wgpu::PresentMode::Immediate]
+                .into_iter()
+                .find(|mode| self.supported_present_modes.contains(mode))
+                .unwrap_or(wgpu::PresentMode::AutoNoVsync)
+        } else {
+            self.default_present_mode
+        };
+
+        if present_mode != self.present_mode {
+            log::info!("switching present mode to {present_mode:?}");
+            self.present_mode = present_mode;
+            self.resize(self.size.width, self.size.height);
         }
     }
 
+    /// Present with strict vsync on low power, low latency keeps its present
+    /// mode until it is turned off.
+    pub fn set_low_power(&mut self, enabled: bool) {
+        let present_mode = if enabled {
+            wgpu::PresentMode::Fifo
+        } else {
+            wgpu::PresentMode::AutoVsync
+        };
+        if present_mode == self.default_present_mode || self.surface.is_none() {
+            return;
+        }
+
+        let low_latency = self.present_mode != self.default_present_mode;
+        self.default_present_mode = present_mode;
+        self.set_low_latency(low_latency);
+    }
+
     pub fn resize(&mut self, width: u32, height: u32) {
         self.size.width = width;
         self.size.height = height;
-        self.surface.configure(
-            &self.device,
-            &wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                format: self.format,
-                width,
-                height,
-                view_formats: vec![],
-                alpha_mode: wgpu::CompositeAlphaMode::Auto,
-                present_mode: wgpu::PresentMode::AutoVsync,
-            },
-        );
+        if let Some(surface) = &self.surface {
+            surface.configure(
+                &self.device,
+                &wgpu::SurfaceConfiguration {
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    format: self.format,
+                    width,
+                    height,
+                    view_formats: vec![],
+                    alpha_mode: wgpu::CompositeAlphaMode::Auto,
+                    present_mode: self.present_mode,
+                },
+            );
+        }
+    }
+}
+
+fn create_instance() -> wgpu::Instance {
+    #[cfg(target_arch = "wasm32")]
+    let default_backend = wgpu::Backends::BROWSER_WEBGPU | wgpu::Backends::GL;
+    #[cfg(not(target_arch = "wasm32"))]
+    let default_backend = wgpu::Backends::all();
+
+    // The backend can be configured using the `WGPU_BACKEND`
+    // environment variable. If the variable is not set, the primary backend
+    // will be used. The following values are allowed:
+    // - `vulkan`
+    // - `metal`
+    // - `dx12`
+    // - `dx11`
+    // - `gl`
+    // - `webgpu`
+    // - `primary`
+    let backend = wgpu::util::backend_bits_from_env().unwrap_or(default_backend);
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: backend,
+        ..Default::default()
+    });
+
+    log::info!("selected instance: {instance:?}");
+    instance
+}
+
+async fn request_device(adapter: &wgpu::Adapter) -> (wgpu::Device, wgpu::Queue) {
+    // Timestamps are only written while frames are measured.
+    let descriptor = wgpu::DeviceDescriptor {
+        features: adapter.features() & wgpu::Features::TIMESTAMP_QUERY,
+        ..Default::default()
+    };
+    if let Ok(result) = adapter.request_device(&descriptor, None).await {
+        result
+    } else {
+        // These downlevel limits will allow the code to run on all possible hardware
+        adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: None,
+                    features: wgpu::Features::empty(),
+                    limits: wgpu::Limits::downlevel_webgl2_defaults(),
+                },
+                None,
+            )
+            .await
+            .expect("Request device")
     }
 }
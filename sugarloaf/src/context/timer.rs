@@ -0,0 +1,145 @@
+// GPU time of a frame, measured with two timestamp queries written around the
+// commands of the frame. Only devices created with `TIMESTAMP_QUERY` can write
+// them (Vulkan and DX12). Reading the time back waits for the frame to finish,
+// so it is only done for the frames being measured.
+
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Size of the two resolved timestamps.
+const TIMESTAMPS_SIZE: wgpu::BufferAddress = 2 * std::mem::size_of::<u64>() as u64;
+
+/// Timestamps written so far, only a frame with both can be read.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Stamps {
+    #[default]
+    None,
+    Started,
+    Resolved,
+}
+
+impl Stamps {
+    /// State after the end of a frame, `None` when it was never started.
+    fn ended(self) -> Option<Stamps> {
+        match self {
+            Stamps::Started => Some(Stamps::Resolved),
+            Stamps::None | Stamps::Resolved => None,
+        }
+    }
+}
+
+pub struct FrameTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    read_buffer: wgpu::Buffer,
+    // Nanoseconds per timestamp tick.
+    period: f32,
+    stamps: Stamps,
+}
+
+impl FrameTimer {
+    /// None when the device cannot write timestamps.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<FrameTimer> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("sugarloaf::timer -> Frame timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: 2,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("sugarloaf::timer -> Resolved timestamps"),
+            size: TIMESTAMPS_SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let read_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("sugarloaf::timer -> Read timestamps"),
+            size: TIMESTAMPS_SIZE,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Some(FrameTimer {
+            query_set,
+            resolve_buffer,
+            read_buffer,
+            period: queue.get_timestamp_period(),
+            stamps: Stamps::None,
+        })
+    }
+
+    /// Write the first timestamp, before any command of the frame.
+    #[inline]
+    pub fn start(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.write_timestamp(&self.query_set, 0);
+        self.stamps = Stamps::Started;
+    }
+
+    /// Write the second timestamp and resolve both, on the encoder given to
+    /// `start`. A frame that was not started is left out.
+    pub fn end(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let Some(stamps) = self.stamps.ended() else {
+            debug_assert!(false, "frame timer ended without being started");
+            return;
+        };
+        encoder.write_timestamp(&self.query_set, 1);
+        encoder.resolve_query_set(&self.query_set, 0..2, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.read_buffer,
+            0,
+            TIMESTAMPS_SIZE,
+        );
+        self.stamps = stamps;
+    }
+
+    /// Wait for the last submitted frame and return the time the GPU spent
+    /// on it, each frame is read once.
+    pub fn read(&mut self, device: &wgpu::Device) -> Option<Duration> {
+        if self.stamps != Stamps::Resolved {
+            return None;
+        }
+        self.stamps = Stamps::None;
+
+        let slice = self.read_buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        if !matches!(receiver.try_recv(), Ok(Ok(()))) {
+            return None;
+        }
+
+        let data = slice.get_mapped_range();
+        let timestamp = |offset: usize| {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&data[offset..offset + 8]);
+            u64::from_le_bytes(bytes)
+        };
+        let ticks = timestamp(8).saturating_sub(timestamp(0));
+        drop(data);
+        self.read_buffer.unmap();
+
+        Some(Duration::from_nanos(
+            (ticks as f64 * self.period as f64) as u64,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stamps_are_paired() {
+        assert_eq!(Stamps::Started.ended(), Some(Stamps::Resolved));
+        // Ending twice, or without starting, would resolve a stale timestamp.
+        assert_eq!(Stamps::None.ended(), None);
+        assert_eq!(Stamps::Resolved.ended(), None);
+    }
+}
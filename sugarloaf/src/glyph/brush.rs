@@ -6,7 +6,7 @@
 mod builder;
 
 pub use self::builder::*;
-use crate::glyph::cache::{CachedBy, DrawCache};
+use crate::glyph::cache::{CacheStats, CachedBy, DrawCache, RowUsage};
 use crate::glyph::calculator::{GlyphCruncher, GlyphedSection};
 use crate::glyph::{
     DefaultSectionHasher, FontId, GlyphChange, GlyphPositioner, Rectangle, Section,
@@ -338,11 +338,42 @@ where
         }
     }
 
+    /// Returns `true` while glyphs are rasterized in the background, another
+    /// frame is needed to show them.
+    #[inline]
+    pub fn has_deferred_glyphs(&self) -> bool {
+        self.texture_cache.has_deferred()
+    }
+
+    /// Draw new glyphs before the frame, waiting for the ones still drawn in
+    /// the background, when `defer_rasterization` is `false`.
+    #[inline]
+    pub fn set_defer_rasterization(&mut self, defer_rasterization: bool) {
+        self.texture_cache
+            .set_defer_rasterization(defer_rasterization);
+    }
+
     /// Returns the logical texture cache pixel dimensions `(width, height)`.
     pub fn texture_dimensions(&self) -> (u32, u32) {
         self.texture_cache.dimensions()
     }
 
+    /// Returns the glyphs the texture cache dropped to make room.
+    pub fn texture_cache_stats(&self) -> CacheStats {
+        self.texture_cache.stats()
+    }
+
+    /// Returns the rows of the texture cache, least recently used first.
+    pub fn texture_cache_rows(&self) -> Vec<RowUsage> {
+        self.texture_cache.rows().collect()
+    }
+
+    /// The next `process_queued` returns `BrushAction::Draw` even if the
+    /// queued sections are the same as the last draw.
+    pub fn invalidate_last_draw(&mut self) {
+        self.last_draw = LastDrawInfo::default();
+    }
+
     fn cleanup_frame(&mut self) {
         if self.cache_glyph_positioning {
             // clear section_buffer & trim calculate_glyph_cache to active sections
@@ -446,13 +477,17 @@ where
     /// # }
     pub fn process_queued<Up, VF>(
         &mut self,
-        update_texture: Up,
+        mut update_texture: Up,
         to_vertex: VF,
     ) -> Result<BrushAction<V>, BrushError>
     where
         Up: FnMut(Rectangle<u32>, &[u8]),
         VF: Fn(GlyphVertex<X>) -> V + Copy,
     {
+        // Glyphs drawn in the background take the place of their blank rects,
+        // the vertices pointing at them stay the same.
+        self.texture_cache.upload_deferred(&mut update_texture);
+
         let draw_info = LastDrawInfo {
             text_state: {
                 let mut s = self.section_hasher.build_hasher();
@@ -493,7 +528,10 @@ where
             }
 
             if some_text {
-                match self.texture_cache.cache_queued(&self.fonts, update_texture) {
+                match self
+                    .texture_cache
+                    .cache_queued(&self.fonts, &mut update_texture)
+                {
                     Ok(CachedBy::Adding) => {}
                     Ok(CachedBy::Reordering) => {
                         for glyphed in self.calculate_glyph_cache.values_mut() {
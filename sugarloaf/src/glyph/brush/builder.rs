@@ -47,7 +47,8 @@ impl GlyphBrushBuilder<()> {
                 .dimensions(256, 256)
                 .scale_tolerance(0.5)
                 .position_tolerance(0.1)
-                .align_4x4(false),
+                .align_4x4(false)
+                .defer_rasterization(true),
         }
     }
 }
@@ -141,6 +142,17 @@ impl<F: Font, H: BuildHasher> GlyphBrushBuilder<F, H> {
         self
     }
 
+    /// Rasterize large batches of new glyphs in the background, they are
+    /// drawn blank until ready.
+    ///
+    /// Defaults to `true`.
+    pub fn defer_rasterization(mut self, defer_rasterization: bool) -> Self {
+        self.draw_cache_builder = self
+            .draw_cache_builder
+            .defer_rasterization(defer_rasterization);
+        self
+    }
+
     /// Align glyphs in texture cache to 4x4 texel boundaries.
     ///
     /// If your backend requires texture updates to be aligned to 4x4 texel
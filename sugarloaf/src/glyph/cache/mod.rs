@@ -163,6 +163,7 @@ pub struct DrawCacheBuilder {
     pad_glyphs: bool,
     align_4x4: bool,
     multithread: bool,
+    defer_rasterization: bool,
 }
 
 impl Default for DrawCacheBuilder {
@@ -174,6 +175,7 @@ impl Default for DrawCacheBuilder {
             pad_glyphs: true,
             align_4x4: false,
             multithread: true,
+            defer_rasterization: false,
         }
     }
 }
@@ -291,6 +293,23 @@ impl DrawCacheBuilder {
         self.multithread = multithread;
         self
     }
+    /// Rasterize large batches of new glyphs on the thread pool instead of
+    /// waiting for them. Their place in the texture is left blank until
+    /// `upload_deferred` uploads them, `has_deferred` tells when some are
+    /// still being drawn. Batches filling an empty cache are never deferred.
+    ///
+    /// # Platform-specific behaviour
+    ///
+    /// This option has no effect on wasm32.
+    ///
+    /// # Example (set to default value)
+    ///
+    /// # use glyph_brush_draw_cache::DrawCache;
+    /// let cache = DrawCache::builder().defer_rasterization(false).build();
+    pub fn defer_rasterization(mut self, defer_rasterization: bool) -> Self {
+        self.defer_rasterization = defer_rasterization;
+        self
+    }
 
     fn validated(self) -> Self {
         assert!(self.scale_tolerance >= 0.0);
@@ -328,6 +347,7 @@ impl DrawCacheBuilder {
             pad_glyphs,
             align_4x4,
             multithread,
+            defer_rasterization,
         } = self.validated();
 
         DrawCache {
@@ -351,6 +371,10 @@ impl DrawCacheBuilder {
             pad_glyphs,
             align_4x4,
             multithread,
+            defer_rasterization,
+            #[cfg(not(target_arch = "wasm32"))]
+            deferred: Deferred::default(),
+            stats: CacheStats::default(),
         }
     }
 
@@ -376,6 +400,7 @@ impl DrawCacheBuilder {
             pad_glyphs,
             align_4x4,
             multithread,
+            defer_rasterization,
         } = self.validated();
 
         cache.width = width;
@@ -385,6 +410,7 @@ impl DrawCacheBuilder {
         cache.pad_glyphs = pad_glyphs;
         cache.align_4x4 = align_4x4;
         cache.multithread = multithread;
+        cache.defer_rasterization = defer_rasterization;
         cache.clear();
     }
 }
@@ -456,6 +482,52 @@ pub struct DrawCache {
     pad_glyphs: bool,
     align_4x4: bool,
     multithread: bool,
+    defer_rasterization: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    deferred: Deferred,
+    stats: CacheStats,
+}
+
+/// Glyphs dropped by a `DrawCache` to make room for new ones, many of them
+/// in a short time means the texture is too small for what is drawn.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Least recently used rows removed.
+    pub evicted_rows: u64,
+    /// Glyphs removed along with those rows.
+    pub evicted_glyphs: u64,
+    /// Times the whole cache was cleared to pack a queue that didn't fit.
+    pub repacks: u64,
+}
+
+/// A row of the cache texture, glyphs are packed from its left.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RowUsage {
+    pub top: u32,
+    pub width: u32,
+    pub height: u32,
+    pub glyphs: usize,
+}
+
+/// Glyphs rasterized on the thread pool, sent back with the texture rect
+/// they were given.
+#[cfg(not(target_arch = "wasm32"))]
+struct Deferred {
+    sender: crossbeam_channel::Sender<(LossyGlyphInfo, Rectangle<u32>, ByteArray2d)>,
+    receiver: crossbeam_channel::Receiver<(LossyGlyphInfo, Rectangle<u32>, ByteArray2d)>,
+    pending: usize,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for Deferred {
+    fn default() -> Deferred {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        Deferred {
+            sender,
+            receiver,
+            pending: 0,
+        }
+    }
 }
 
 impl DrawCache {
@@ -481,6 +553,21 @@ impl DrawCache {
         (self.width, self.height)
     }
 
+    /// Returns the evictions since the cache was built.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Returns the rows of the texture, least recently used first.
+    pub fn rows(&self) -> impl Iterator<Item = RowUsage> + '_ {
+        self.rows.iter().map(|(top, row)| RowUsage {
+            top: *top,
+            width: row.width,
+            height: row.height,
+            glyphs: row.glyphs.len(),
+        })
+    }
+
     /// Queue a glyph for caching by the next call to `cache_queued`. `font_id`
     /// is used to disambiguate glyphs from different fonts. The user should
     /// ensure that `font_id` is unique to the font the glyph is from.
@@ -512,6 +599,7 @@ impl DrawCache {
             pad_glyphs: self.pad_glyphs,
             align_4x4: self.align_4x4,
             multithread: self.multithread,
+            defer_rasterization: self.defer_rasterization,
         }
     }
 
@@ -609,6 +697,7 @@ impl DrawCache {
 
             self.all_glyphs.reserve(uncached_outlined.len());
             let mut draw_and_upload = Vec::with_capacity(uncached_outlined.len());
+            let mut draw_infos = Vec::with_capacity(uncached_outlined.len());
 
             'per_glyph: for (glyph_info, outlined) in uncached_outlined {
                 let bounds = outlined.px_bounds();
@@ -657,6 +746,8 @@ impl DrawCache {
                             if !in_use_rows.contains(self.rows.front().unwrap().0) {
                                 // Remove row
                                 let (top, row) = self.rows.pop_front().unwrap();
+                                self.stats.evicted_rows += 1;
+                                self.stats.evicted_glyphs += row.glyphs.len() as u64;
 
                                 for g in row.glyphs {
                                     self.all_glyphs.remove(&g.glyph_info);
@@ -751,6 +842,7 @@ impl DrawCache {
                 in_use_rows.insert(row_top);
 
                 draw_and_upload.push((aligned_tex_coords, outlined));
+                draw_infos.push(glyph_info);
 
                 self.all_glyphs
                     .insert(glyph_info, (row_top, row.glyphs.len() as u32 - 1));
@@ -789,6 +881,8 @@ impl DrawCache {
                         },
                         &texture_up,
                     );
+                } else if self.should_defer(&draw_and_upload) {
+                    self.defer(draw_infos, draw_and_upload, &mut uploader);
                 } else {
                     self.draw_and_upload(draw_and_upload, &mut uploader);
                 }
@@ -800,12 +894,140 @@ impl DrawCache {
             Ok(CachedBy::Adding)
         } else {
             // clear the cache then try again with optimal packing
+            self.stats.repacks += 1;
             self.clear();
             self.cache_queued(fonts, uploader)
                 .map(|_| CachedBy::Reordering)
         }
     }
 
+    /// Whether a batch is worth drawing in the background rather than before
+    /// the frame.
+    #[inline]
+    fn should_defer(&self, draw_and_upload: &[(Rectangle<u32>, OutlinedGlyph)]) -> bool {
+        // Pixels of a batch above which drawing it would delay the frame,
+        // about a hundred glyphs at 24px.
+        const WORK_MAGNITUDE_TO_DEFER: usize = 65536;
+
+        #[cfg(target_arch = "wasm32")]
+        return false;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let work_magnitude: usize = draw_and_upload
+                .iter()
+                .map(|(rect, _)| rect.width() as usize * rect.height() as usize)
+                .sum();
+            self.defer_rasterization && work_magnitude >= WORK_MAGNITUDE_TO_DEFER
+        }
+    }
+
+    /// Blank the texture rects of a batch and draw it on the thread pool, the
+    /// glyphs are uploaded by `upload_deferred` once drawn.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn defer<U>(
+        &mut self,
+        infos: Vec<LossyGlyphInfo>,
+        draw_and_upload: Vec<(Rectangle<u32>, OutlinedGlyph)>,
+        uploader: &mut U,
+    ) where
+        U: FnMut(Rectangle<u32>, &[u8]),
+    {
+        // The rects may still hold glyphs of evicted rows.
+        for (tex_coords, _) in &draw_and_upload {
+            let blank = vec![0; (tex_coords.width() * tex_coords.height()) as usize];
+            uploader(*tex_coords, &blank);
+        }
+
+        self.deferred.pending += draw_and_upload.len();
+        let threads = rayon::current_num_threads().max(1);
+        let chunk_size = (draw_and_upload.len() + threads - 1) / threads;
+        let mut jobs: Vec<_> = infos.into_iter().zip(draw_and_upload).collect();
+        while !jobs.is_empty() {
+            let chunk: Vec<_> = jobs.drain(..chunk_size.min(jobs.len())).collect();
+            let sender = self.deferred.sender.clone();
+            let pad_glyphs = self.pad_glyphs;
+            rayon::spawn(move || {
+                for (info, (tex_coords, glyph)) in chunk {
+                    let pixels = draw_glyph(tex_coords, &glyph, pad_glyphs);
+                    let _ = sender.send((info, tex_coords, pixels));
+                }
+            });
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn defer<U>(
+        &mut self,
+        _infos: Vec<LossyGlyphInfo>,
+        draw_and_upload: Vec<(Rectangle<u32>, OutlinedGlyph)>,
+        uploader: &mut U,
+    ) where
+        U: FnMut(Rectangle<u32>, &[u8]),
+    {
+        self.draw_and_upload_1_thread(draw_and_upload, uploader)
+    }
+
+    /// Upload the glyphs drawn in the background since the last call, returns
+    /// true when some were uploaded. Glyphs evicted from the cache meanwhile
+    /// are dropped. Once rasterization is no longer deferred it waits for
+    /// all of them.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn upload_deferred<U>(&mut self, mut uploader: U) -> bool
+    where
+        U: FnMut(Rectangle<u32>, &[u8]),
+    {
+        let mut uploaded = false;
+        loop {
+            let received = if self.defer_rasterization || self.deferred.pending == 0 {
+                self.deferred.receiver.try_recv().ok()
+            } else {
+                self.deferred.receiver.recv().ok()
+            };
+            let (info, tex_coords, pixels) = match received {
+                Some(received) => received,
+                None => break,
+            };
+
+            self.deferred.pending = self.deferred.pending.saturating_sub(1);
+            let cached_at = self
+                .all_glyphs
+                .get(&info)
+                .and_then(|(row, index)| self.rows.get(row)?.glyphs.get(*index as usize))
+                .map(|glyph| glyph.tex_coords.min);
+            if cached_at == Some(tex_coords.min) {
+                uploader(tex_coords, pixels.as_slice());
+                uploaded = true;
+            }
+        }
+        uploaded
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn upload_deferred<U>(&mut self, _uploader: U) -> bool
+    where
+        U: FnMut(Rectangle<u32>, &[u8]),
+    {
+        false
+    }
+
+    /// Turn the rasterization in the background on or off, see
+    /// `DrawCacheBuilder::defer_rasterization`.
+    #[inline]
+    pub fn set_defer_rasterization(&mut self, defer_rasterization: bool) {
+        self.defer_rasterization = defer_rasterization;
+    }
+
+    /// Whether glyphs are still being drawn in the background.
+    #[inline]
+    pub fn has_deferred(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.deferred.pending > 0;
+
+        #[cfg(target_arch = "wasm32")]
+        false
+    }
+
     /// Draw using the current thread & the rayon thread pool in a work-stealing manner.
     /// Uploads are called by the current thread only.
     ///
@@ -1071,6 +1293,49 @@ mod test {
         }
     }
 
+    #[test]
+    fn deferred_rasterization() {
+        let font = FontRef::try_from_slice(FONT).unwrap();
+        let glyph = |c: char, x: f32| {
+            font.glyph_id(c)
+                .with_scale_and_position(96.0, point(x, 0.0))
+        };
+
+        let mut cache = DrawCache::builder()
+            .dimensions(1024, 1024)
+            .defer_rasterization(true)
+            .build();
+
+        // Filling an empty cache is never deferred.
+        cache.queue_glyph(0, glyph('a', 0.0));
+        cache.cache_queued(&[&font], |_, _| {}).unwrap();
+        assert!(!cache.has_deferred());
+
+        for (index, c) in "永和九年岁在癸丑暮春之初会于稽山阴兰亭".chars().enumerate()
+        {
+            cache.queue_glyph(0, glyph(c, index as f32 * 96.0));
+        }
+        let mut blanked = 0;
+        cache
+            .cache_queued(&[&font], |_, data| {
+                assert!(data.iter().all(|v| *v == 0));
+                blanked += 1;
+            })
+            .unwrap();
+        assert!(blanked > 0);
+        assert!(cache.has_deferred());
+
+        // Waits for all of them once no longer deferred.
+        cache.set_defer_rasterization(false);
+        let mut drawn = 0;
+        assert!(cache.upload_deferred(|_, data| {
+            assert!(data.iter().any(|v| *v > 0));
+            drawn += 1;
+        }));
+        assert_eq!(drawn, blanked);
+        assert!(!cache.has_deferred());
+    }
+
     #[test]
     fn need_to_check_whole_cache() {
         let font = FontRef::try_from_slice(FONT).unwrap();
@@ -1138,6 +1403,7 @@ mod test {
             pad_glyphs: false,
             align_4x4: false,
             multithread: false,
+            defer_rasterization: true,
         }
         .build();
 
@@ -1149,6 +1415,7 @@ mod test {
         assert!(!to_builder.pad_glyphs);
         assert!(!to_builder.align_4x4);
         assert!(!to_builder.multithread);
+        assert!(to_builder.defer_rasterization);
     }
 
     #[test]
@@ -1239,6 +1506,52 @@ mod test {
         );
     }
 
+    #[test]
+    fn eviction_stats() {
+        let font = FontRef::try_from_slice(FONT).unwrap();
+        let fontmap = &[&font];
+
+        let mut cache = DrawCache::builder()
+            .dimensions(30, 25)
+            .scale_tolerance(0.1)
+            .position_tolerance(0.1)
+            .build();
+        let cache_text = |cache: &mut DrawCache, text: &str| {
+            let glyphs = crate::glyph::layout::Layout::default_single_line()
+                .calculate_glyphs(
+                    fontmap,
+                    &SectionGeometry::default(),
+                    &[SectionText {
+                        text,
+                        scale: 16.0.into(),
+                        ..<_>::default()
+                    }],
+                );
+            for sg in glyphs {
+                cache.queue_glyph(0, sg.glyph);
+            }
+            cache.cache_queued(fontmap, |_, _| {}).unwrap();
+        };
+
+        cache_text(&mut cache, "ABCDEF");
+        assert_eq!(cache.stats(), CacheStats::default());
+        assert_eq!(cache.rows().map(|row| row.glyphs).sum::<usize>(), 6);
+
+        // Nothing is in use, the oldest rows make room.
+        cache_text(&mut cache, "HIK");
+        let stats = cache.stats();
+        assert!(stats.evicted_rows > 0);
+        assert!(stats.evicted_glyphs >= stats.evicted_rows);
+        assert_eq!(stats.repacks, 0);
+
+        // The rows left are in use, everything is packed again.
+        cache_text(&mut cache, "DEFHIK");
+        assert_eq!(cache.stats().repacks, 1);
+        assert!(cache
+            .rows()
+            .all(|row| row.width <= 30 && row.top + row.height <= 25));
+    }
+
     #[test]
     fn align_4x4() {
         // First, test align_4x4 disabled, to confirm non-4x4 alignment
@@ -57,7 +57,9 @@ fn compute(
     margin: Delta<f32>,
     min_cols_lines: (usize, usize),
 ) -> (usize, usize) {
-    let margin_x = ((margin.x) * scale_factor).floor();
+    // Dimensions are physical pixels while the margins and the sugar bounds
+    // are logical, so the grid is the same on any scale factor.
+    let margin_x = margin.x;
     let margin_spaces = (margin.top_y * 2.) + margin.bottom_y;
 
     let mut lines = (dimensions.1 / scale_factor) - margin_spaces;
@@ -71,6 +73,23 @@ fn compute(
     (visible_columns, visible_lines)
 }
 
+// Logical size of a window fitting `columns` and `lines`, the inverse of
+// `compute`. Half a cell is added so the result is not floored to one less
+// column or line.
+#[inline]
+fn size(
+    (columns, lines): (usize, usize),
+    line_height: f32,
+    sugarwidth: f32,
+    sugarheight: f32,
+    margin: Delta<f32>,
+) -> (f32, f32) {
+    let margin_spaces = (margin.top_y * 2.) + margin.bottom_y;
+    let width = margin.x + (columns as f32 + 0.5) * sugarwidth;
+    let height = margin_spaces + (lines as f32 + 0.5) * sugarheight * line_height;
+    (width, height)
+}
+
 impl SugarloafLayout {
     pub fn new(
         width: f32,
@@ -169,6 +188,18 @@ impl SugarloafLayout {
         self
     }
 
+    /// Logical window size needed to show `columns` and `lines`.
+    #[inline]
+    pub fn size_for(&self, columns: usize, lines: usize) -> (f32, f32) {
+        size(
+            (columns, lines),
+            self.line_height,
+            self.sugarwidth,
+            self.sugarheight,
+            self.margin,
+        )
+    }
+
     #[inline]
     pub fn update_columns_per_font_width(&mut self) {
         // SugarStack is a primitive representation of columns data
@@ -233,3 +264,41 @@ impl SugarloafLayout {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_on_scale_factors() {
+        let margin = Delta {
+            x: 10.,
+            top_y: 5.,
+            bottom_y: 0.,
+        };
+
+        // The same window on a standard and on a HiDPI monitor.
+        let standard = compute((800., 600.), 1., 1., 8., 16., margin, (2, 1));
+        let hidpi = compute((1600., 1200.), 2., 1., 8., 16., margin, (2, 1));
+        assert_eq!(standard, (98, 36));
+        assert_eq!(standard, hidpi);
+    }
+
+    #[test]
+    fn test_size_is_inverse_of_compute() {
+        let margin = Delta {
+            x: 10.,
+            top_y: 30.,
+            bottom_y: 5.,
+        };
+
+        for scale_factor in [1., 1.5, 2.] {
+            let (width, height) = size((120, 40), 1.2, 8.4, 17., margin);
+            let dimensions = (width * scale_factor, height * scale_factor);
+            assert_eq!(
+                compute(dimensions, scale_factor, 1.2, 8.4, 17., margin, (2, 1)),
+                (120, 40)
+            );
+        }
+    }
+}
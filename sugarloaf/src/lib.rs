@@ -6,4 +6,6 @@ pub mod glyph;
 pub mod layout;
 mod sugarloaf;
 pub mod tools;
-pub use crate::sugarloaf::{Renderable, Sugarloaf, SugarloafErrors, SugarloafWithErrors};
+pub use crate::sugarloaf::{
+    FrameImage, GlyphAtlas, Renderable, Sugarloaf, SugarloafErrors, SugarloafWithErrors,
+};
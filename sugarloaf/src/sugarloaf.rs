@@ -2,7 +2,8 @@ use crate::components::core::{image::Handle, shapes::Rectangle};
 use crate::components::layer::{self, LayerBrush};
 use crate::components::rect::{Rect, RectBrush};
 use crate::components::text;
-use crate::context::Context;
+use crate::components::upscale::{self, UpscaleBrush};
+use crate::context::{ColorFilter, ColorSpace, Context, FrameTimer};
 use crate::core::{ImageProperties, RepeatedSugar, Sugar, SugarStack};
 use crate::font::fonts::{SugarloafFont, SugarloafFonts};
 #[cfg(not(target_arch = "wasm32"))]
@@ -12,11 +13,14 @@ use crate::font::{
     FONT_ID_BOLD, FONT_ID_BOLD_ITALIC, FONT_ID_EMOJIS, FONT_ID_ICONS, FONT_ID_ITALIC,
     FONT_ID_REGULAR, FONT_ID_SYMBOL, FONT_ID_UNICODE,
 };
-use crate::glyph::{FontId, GlyphCruncher};
+use crate::glyph::{CacheStats, FontId, GlyphCruncher, RowUsage};
 use crate::layout::SugarloafLayout;
 use ab_glyph::{self, Font as GFont, FontArc, PxScale};
 use core::fmt::{Debug, Formatter};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
 use unicode_width::UnicodeWidthChar;
 
 #[cfg(target_arch = "wasm32")]
@@ -41,6 +45,13 @@ pub trait Renderable: 'static + Sized {
     );
 }
 
+/// Sections and rects computed for a stack, positioned at `text_y`.
+struct CachedStack {
+    text_y: f32,
+    sections: Vec<crate::components::text::OwnedSection>,
+    rects: Vec<Rect>,
+}
+
 #[derive(Copy, Clone, PartialEq)]
 pub struct CachedSugar {
     font_id: FontId,
@@ -50,16 +61,221 @@ pub struct CachedSugar {
 
 pub struct Sugarloaf {
     sugar_cache: HashMap<char, CachedSugar>,
+    // Stacks used in the last rendered frame and the ones used in the
+    // current frame, keyed by the stack content hash.
+    stack_cache: HashMap<u64, CachedStack>,
+    next_stack_cache: HashMap<u64, CachedStack>,
     pub ctx: Context,
     pub layout: SugarloafLayout,
     text_brush: text::GlyphBrush<()>,
     rect_brush: RectBrush,
-    layer_brush: LayerBrush,
+    // Only needed for background images, created on first use.
+    layer_brush: Option<LayerBrush>,
+    // Only needed below the full resolution, created on first use.
+    upscale_brush: Option<UpscaleBrush>,
+    render_scale: f32,
     rects: Vec<Rect>,
+    // Drawn after the text of the frame, for menus over the terminal.
+    overlay_rects: Vec<Rect>,
+    overlay_texts: Vec<OverlayText>,
     text_y: f32,
+    // Lines the text is moved down by, a fraction while scrolling smoothly.
+    scroll_offset: f32,
     font_bound: (f32, f32),
     fonts: SugarloafFonts,
     is_text_monospaced: bool,
+    surface_warning: RateLimitedWarning,
+    stack_budget: StackBudget,
+    has_pending_stacks: bool,
+    // Only set while frames are measured and the device writes timestamps.
+    frame_timer: Option<FrameTimer>,
+}
+
+/// Text queued once the rest of the frame is drawn.
+struct OverlayText {
+    pos: (f32, f32),
+    text: String,
+    font_id: usize,
+    scale: f32,
+    color: [f32; 4],
+    single_line: bool,
+}
+
+/// Interval between two logs of the same recoverable error.
+const WARNING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Warning logged at most once per `WARNING_INTERVAL`, the occurrences in
+/// between are counted and reported with the next log.
+#[derive(Default)]
+struct RateLimitedWarning {
+    last: Option<Instant>,
+    skipped: usize,
+}
+
+impl RateLimitedWarning {
+    fn warn(&mut self, message: &str) {
+        if let Some(message) = self.message_at(message, Instant::now()) {
+            log::warn!("{message}");
+        }
+    }
+
+    fn message_at(&mut self, message: &str, now: Instant) -> Option<String> {
+        if let Some(last) = self.last {
+            if now.duration_since(last) < WARNING_INTERVAL {
+                self.skipped += 1;
+                return None;
+            }
+        }
+
+        let skipped = std::mem::take(&mut self.skipped);
+        self.last = Some(now);
+        if skipped == 0 {
+            Some(message.to_string())
+        } else {
+            Some(format!("{message} ({skipped} more since the last warning)"))
+        }
+    }
+}
+
+/// Time given to build the stacks of a frame.
+const STACK_BUDGET: Duration = Duration::from_millis(8);
+
+/// Spreads building the stacks of a frame over several frames when it takes
+/// longer than the budget, like when a big file fills the screen at once.
+/// Lines left over keep what they showed in the last frame and the next
+/// frames carry on from where this one stopped.
+#[derive(Default)]
+struct StackBudget {
+    budget: Duration,
+    /// Build the whole frame, for frames read back right away.
+    complete: bool,
+    started: Option<Instant>,
+    line: usize,
+    /// Key of the stack shown on each line by the last frame and this one.
+    keys: Vec<u64>,
+    next_keys: Vec<u64>,
+    /// Lines above were built by the last frame, changes there wait until
+    /// the lines below are built.
+    resume_from: usize,
+    ran_out_at: Option<usize>,
+    stale_above: bool,
+}
+
+impl StackBudget {
+    fn new(budget: Duration) -> StackBudget {
+        StackBudget {
+            budget,
+            ..StackBudget::default()
+        }
+    }
+
+    /// Key of the last frame's stack to show on the current line instead of
+    /// building a new one, `None` when it has to be built.
+    fn stale_key(&mut self, now: Instant, cached: bool) -> Option<u64> {
+        let started = *self.started.get_or_insert(now);
+        if cached || self.complete {
+            return None;
+        }
+
+        let previous = *self.keys.get(self.line)?;
+        if self.line < self.resume_from {
+            self.stale_above = true;
+            return Some(previous);
+        }
+
+        if now.duration_since(started) > self.budget {
+            self.ran_out_at.get_or_insert(self.line);
+            return Some(previous);
+        }
+
+        None
+    }
+
+    /// Move to the next line, `key` being the stack shown on this one.
+    fn next_line(&mut self, key: u64) {
+        self.next_keys.push(key);
+        self.line += 1;
+    }
+
+    /// End of the frame, returns true when lines are left to build.
+    fn finish(&mut self) -> bool {
+        // Nothing was stacked, like frames of the other routes.
+        if self.line == 0 {
+            return false;
+        }
+
+        self.keys = std::mem::take(&mut self.next_keys);
+        let pending = self.ran_out_at.is_some() || self.stale_above;
+        self.resume_from = self.ran_out_at.take().unwrap_or(0);
+        self.started = None;
+        self.line = 0;
+        self.stale_above = false;
+        self.complete = false;
+        pending
+    }
+}
+
+/// RGBA8 pixels of a rendered frame.
+#[derive(Debug, Clone)]
+pub struct FrameImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl FrameImage {
+    /// Hash of the size and pixels, a frame rendered again by the same
+    /// backend has the same hash.
+    pub fn hash(&self) -> u64 {
+        let mut hasher = twox_hash::XxHash64::with_seed(0);
+        self.width.hash(&mut hasher);
+        self.height.hash(&mut hasher);
+        hasher.write(&self.pixels);
+        hasher.finish()
+    }
+}
+
+/// Glyph texture cache state, for debugging corrupted glyphs or a cache
+/// too small for what is drawn.
+#[derive(Debug, Clone)]
+pub struct GlyphAtlas {
+    pub width: u32,
+    pub height: u32,
+    pub stats: CacheStats,
+    /// Rows of the texture, least recently used first.
+    pub rows: Vec<RowUsage>,
+}
+
+/// Bytes per row of a texture copy, padded to the alignment wgpu requires.
+#[inline]
+fn padded_bytes_per_row(width: u32) -> u32 {
+    let unpadded = width * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    (unpadded + align - 1) / align * align
+}
+
+/// Strip the row padding of a texture copy, converting BGRA to RGBA when
+/// `swap_red_blue` is set.
+fn unpad_rows(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    bytes_per_row: u32,
+    swap_red_blue: bool,
+) -> Vec<u8> {
+    let row_len = (width * 4) as usize;
+    let mut pixels = Vec::with_capacity(row_len * height as usize);
+    for row in data.chunks(bytes_per_row as usize).take(height as usize) {
+        pixels.extend_from_slice(&row[..row_len]);
+    }
+
+    if swap_red_blue {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    pixels
 }
 
 #[derive(Debug)]
@@ -82,11 +298,40 @@ impl Sugarloaf {
     pub async fn new(
         winit_window: &winit::window::Window,
         power_preference: wgpu::PowerPreference,
+        color_space: ColorSpace,
+        fonts: SugarloafFonts,
+        layout: SugarloafLayout,
+        #[allow(unused)] db: Option<&Database>,
+    ) -> Result<Sugarloaf, SugarloafWithErrors> {
+        let mut ctx = Context::new(winit_window, power_preference, color_space).await;
+        // The layout carries the scale factor the text is rasterized at, it
+        // can differ from the one winit reports.
+        ctx.scale = layout.scale_factor;
+        Sugarloaf::with_context(ctx, fonts, layout, db)
+    }
+
+    /// Sugarloaf without a window, frames are read back with
+    /// `render_to_image`, so render regression tests can hash them. Returns
+    /// None when no adapter is available.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn new_headless(
+        power_preference: wgpu::PowerPreference,
+        fonts: SugarloafFonts,
+        layout: SugarloafLayout,
+        db: Option<&Database>,
+    ) -> Option<Result<Sugarloaf, SugarloafWithErrors>> {
+        let size = winit::dpi::PhysicalSize::new(layout.width_u32, layout.height_u32);
+        let ctx =
+            Context::new_headless(size, layout.scale_factor, power_preference).await?;
+        Some(Sugarloaf::with_context(ctx, fonts, layout, db))
+    }
+
+    fn with_context(
+        ctx: Context,
         fonts: SugarloafFonts,
         layout: SugarloafLayout,
         #[allow(unused)] db: Option<&Database>,
     ) -> Result<Sugarloaf, SugarloafWithErrors> {
-        let ctx = Context::new(winit_window, power_preference).await;
         let mut sugarloaf_errors = None;
 
         #[cfg(not(target_arch = "wasm32"))]
@@ -100,23 +345,52 @@ impl Sugarloaf {
             sugarloaf_errors = Some(SugarloafErrors { fonts_not_found });
         }
 
-        let text_brush = text::GlyphBrushBuilder::using_fonts(loaded_fonts)
-            .build(&ctx.device, ctx.format);
-        let rect_brush = RectBrush::init(&ctx);
-        let layer_brush = LayerBrush::new(&ctx);
+        // Text and rect pipelines are compiled in parallel, drivers spend most
+        // of the renderer startup compiling shaders.
+        #[cfg(not(target_arch = "wasm32"))]
+        let (text_brush, rect_brush) = std::thread::scope(|scope| {
+            let device = &ctx.device;
+            let format = ctx.format;
+            let color_output = ctx.color_output;
+            let text_brush = scope.spawn(move || {
+                text::GlyphBrushBuilder::using_fonts(loaded_fonts)
+                    .color_output(color_output)
+                    .build(device, format)
+            });
+            let rect_brush = RectBrush::init(&ctx);
+            (text_brush.join().unwrap(), rect_brush)
+        });
+        #[cfg(target_arch = "wasm32")]
+        let (text_brush, rect_brush) = (
+            text::GlyphBrushBuilder::using_fonts(loaded_fonts)
+                .color_output(ctx.color_output)
+                .build(&ctx.device, ctx.format),
+            RectBrush::init(&ctx),
+        );
 
         let instance = Sugarloaf {
             sugar_cache: HashMap::new(),
-            layer_brush,
+            stack_cache: HashMap::new(),
+            next_stack_cache: HashMap::new(),
+            layer_brush: None,
+            upscale_brush: None,
+            render_scale: 1.0,
             fonts,
             ctx,
             rect_brush,
             rects: vec![],
+            overlay_rects: vec![],
+            overlay_texts: vec![],
             text_brush,
             text_y: 0.0,
+            scroll_offset: 0.0,
             font_bound: (0.0, 0.0),
             layout,
             is_text_monospaced,
+            surface_warning: RateLimitedWarning::default(),
+            stack_budget: StackBudget::new(STACK_BUDGET),
+            has_pending_stacks: false,
+            frame_timer: None,
         };
 
         if let Some(errors) = sugarloaf_errors {
@@ -128,7 +402,10 @@ impl Sugarloaf {
 
     #[allow(unused)]
     pub fn clear(&mut self) {
-        match self.ctx.surface.get_current_texture() {
+        let Some(surface) = &self.ctx.surface else {
+            return;
+        };
+        match surface.get_current_texture() {
             Ok(frame) => {
                 let mut encoder = self.ctx.device.create_command_encoder(
                     &wgpu::CommandEncoderDescriptor { label: None },
@@ -144,7 +421,9 @@ impl Sugarloaf {
                         view,
                         resolve_target: None,
                         ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(self.layout.background_color),
+                            load: wgpu::LoadOp::Clear(
+                                self.ctx.clear_color(self.layout.background_color),
+                            ),
                             store: true,
                         },
                     })],
@@ -157,6 +436,8 @@ impl Sugarloaf {
                 if error == wgpu::SurfaceError::OutOfMemory {
                     panic!("Swapchain error: {error}. Rendering cannot continue.")
                 }
+                self.surface_warning
+                    .warn(&format!("unable to get the next frame: {error}"));
             }
         }
     }
@@ -182,8 +463,11 @@ impl Sugarloaf {
 
             // Clean font cache per instance
             self.sugar_cache = HashMap::new();
+            self.stack_cache.clear();
+            self.next_stack_cache.clear();
 
             let text_brush = text::GlyphBrushBuilder::using_fonts(loaded_fonts)
+                .color_output(self.ctx.color_output)
                 .build(&self.ctx.device, self.ctx.format);
             self.text_brush = text_brush;
             self.fonts = fonts;
@@ -200,6 +484,15 @@ impl Sugarloaf {
         self
     }
 
+    /// Resize the surface and keep the grid of the layout, the frames show
+    /// the previous grid until `layout.update` is called.
+    #[inline]
+    pub fn resize_surface(&mut self, width: u32, height: u32) -> &mut Self {
+        self.ctx.resize(width, height);
+        self.layout.resize(width, height);
+        self
+    }
+
     #[inline]
     pub fn rescale(&mut self, scale: f32) -> &mut Self {
         self.ctx.scale = scale;
@@ -311,10 +604,107 @@ impl Sugarloaf {
         cached_sugar
     }
 
+    /// Hash of the stack content along with the layout values used to
+    /// position it, the vertical position is not part of it.
+    #[inline]
+    fn stack_key(&self, stack: &SugarStack) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for value in [
+            self.ctx.scale,
+            self.layout.line_height,
+            self.layout.sugarwidth,
+            self.layout.sugarheight,
+            self.layout.scaled_sugarwidth,
+            self.layout.scaled_sugarheight,
+            self.layout.style.text_scale,
+            self.layout.style.screen_position.0,
+        ] {
+            value.to_bits().hash(&mut hasher);
+        }
+
+        for sugar in stack {
+            sugar.content.hash(&mut hasher);
+            for value in sugar.foreground_color.iter().chain(&sugar.background_color) {
+                value.to_bits().hash(&mut hasher);
+            }
+            if let Some(style) = &sugar.style {
+                (style.is_italic, style.is_bold, style.is_bold_italic).hash(&mut hasher);
+            }
+            if let Some(decoration) = &sugar.decoration {
+                for value in [
+                    decoration.relative_position.0,
+                    decoration.relative_position.1,
+                    decoration.size.0,
+                    decoration.size.1,
+                ]
+                .iter()
+                .chain(&decoration.color)
+                {
+                    value.to_bits().hash(&mut hasher);
+                }
+            }
+        }
+
+        hasher.finish()
+    }
+
+    /// Queue a stack computed on a previous call, moved to the current line.
+    #[inline]
+    fn stack_from_cache(&mut self, key: u64) -> bool {
+        let cached = match self.next_stack_cache.remove(&key) {
+            Some(cached) => Some(cached),
+            None => self.stack_cache.remove(&key),
+        };
+
+        let Some(mut cached) = cached else {
+            return false;
+        };
+
+        let offset_y = self.text_y - cached.text_y;
+        if offset_y != 0.0 {
+            for section in cached.sections.iter_mut() {
+                section.screen_position.1 += offset_y;
+            }
+            for rect in cached.rects.iter_mut() {
+                rect.position[1] += offset_y / self.ctx.scale;
+            }
+            cached.text_y = self.text_y;
+        }
+
+        for section in cached.sections.iter() {
+            self.text_brush.queue(section);
+        }
+        self.rects.extend_from_slice(&cached.rects);
+        self.next_stack_cache.insert(key, cached);
+        self.text_y += self.layout.scaled_sugarheight;
+        true
+    }
+
     #[inline]
     pub fn stack(&mut self, mut stack: SugarStack) {
+        if self.text_y == 0.0 {
+            self.text_y = self.layout.style.screen_position.1
+                + self.scroll_offset * self.layout.scaled_sugarheight;
+        }
+
+        let key = self.stack_key(&stack);
+        let cached = self.next_stack_cache.contains_key(&key)
+            || self.stack_cache.contains_key(&key);
+        if let Some(stale_key) = self.stack_budget.stale_key(Instant::now(), cached) {
+            if self.stack_from_cache(stale_key) {
+                self.stack_budget.next_line(stale_key);
+                return;
+            }
+        }
+
+        self.stack_budget.next_line(key);
+        if self.stack_from_cache(key) {
+            return;
+        }
+
         let mut x = 0.;
         let mut sections = vec![];
+        let rects_start = self.rects.len();
         let mod_pos_y = self.layout.style.screen_position.1;
         let mod_text_y = self.layout.scaled_sugarheight / 2.;
 
@@ -324,9 +714,6 @@ impl Sugarloaf {
         let mut repeated = RepeatedSugar::new(0);
 
         let text_bound = self.layout.sugarheight * self.ctx.scale;
-        if self.text_y == 0.0 {
-            self.text_y = self.layout.style.screen_position.1;
-        }
 
         let size = stack.len();
         for i in 0..size {
@@ -465,9 +852,18 @@ impl Sugarloaf {
             x += add_pos_x;
         }
 
-        for section in sections {
-            self.text_brush.queue(&section);
+        for section in sections.iter() {
+            self.text_brush.queue(section);
         }
+
+        self.next_stack_cache.insert(
+            key,
+            CachedStack {
+                text_y: self.text_y,
+                sections,
+                rects: self.rects[rects_start..].to_vec(),
+            },
+        );
         self.text_y += self.layout.scaled_sugarheight;
     }
 
@@ -518,6 +914,31 @@ impl Sugarloaf {
         (0., 0.)
     }
 
+    #[inline]
+    pub fn set_color_filter(&mut self, filter: ColorFilter) -> &mut Self {
+        self.ctx.color_filter = filter;
+        self
+    }
+
+    /// Fraction of the surface resolution frames are drawn at, they are
+    /// upscaled when presented. Screenshots always use the full resolution.
+    #[inline]
+    pub fn set_render_scale(&mut self, render_scale: f32) -> &mut Self {
+        self.render_scale = render_scale.clamp(upscale::MIN_RENDER_SCALE, 1.0);
+        if self.render_scale == 1.0 {
+            self.upscale_brush = None;
+        }
+        self
+    }
+
+    /// Move the lines of the next frames down by `lines`, a fraction of a
+    /// line shows the text between two scroll positions.
+    #[inline]
+    pub fn set_scroll_offset(&mut self, lines: f32) -> &mut Self {
+        self.scroll_offset = lines;
+        self
+    }
+
     #[inline]
     pub fn set_background_color(&mut self, color: wgpu::Color) -> &mut Self {
         self.layout.background_color = color;
@@ -551,7 +972,33 @@ impl Sugarloaf {
         self.reset_state();
         self.rects = vec![];
 
-        match self.ctx.surface.get_current_texture() {
+        // Every time a font size change the cached bounds also changes
+        self.sugar_cache = HashMap::new();
+
+        let text_scale = self.layout.style.text_scale;
+        // Bounds are defined in runtime, they don't need a frame so they are
+        // still updated when the surface is outdated, as it usually is right
+        // after the window moves to a monitor with another scale factor.
+        if self.is_text_monospaced {
+            self.font_bound =
+                self.get_font_bounds(' ', FontId(FONT_ID_REGULAR), text_scale);
+        } else {
+            self.font_bound =
+                self.get_font_bounds('-', FontId(FONT_ID_REGULAR), text_scale);
+        }
+
+        self.layout.scaled_sugarwidth = self.font_bound.0;
+        self.layout.scaled_sugarheight = self.font_bound.1;
+
+        self.layout.sugarwidth = self.layout.scaled_sugarwidth / self.ctx.scale;
+        self.layout.sugarheight = self.layout.scaled_sugarheight / self.ctx.scale;
+
+        self.layout.update_columns_per_font_width();
+
+        let Some(surface) = &self.ctx.surface else {
+            return;
+        };
+        match surface.get_current_texture() {
             Ok(frame) => {
                 let mut encoder = self.ctx.device.create_command_encoder(
                     &wgpu::CommandEncoderDescriptor { label: None },
@@ -567,34 +1014,15 @@ impl Sugarloaf {
                         view,
                         resolve_target: None,
                         ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(self.layout.background_color),
+                            load: wgpu::LoadOp::Clear(
+                                self.ctx.clear_color(self.layout.background_color),
+                            ),
                             store: true,
                         },
                     })],
                     depth_stencil_attachment: None,
                 });
 
-                // Every time a font size change the cached bounds also changes
-                self.sugar_cache = HashMap::new();
-
-                let text_scale = self.layout.style.text_scale;
-                // Bounds are defined in runtime
-                if self.is_text_monospaced {
-                    self.font_bound =
-                        self.get_font_bounds(' ', FontId(FONT_ID_REGULAR), text_scale);
-                } else {
-                    self.font_bound =
-                        self.get_font_bounds('-', FontId(FONT_ID_REGULAR), text_scale);
-                }
-
-                self.layout.scaled_sugarwidth = self.font_bound.0;
-                self.layout.scaled_sugarheight = self.font_bound.1;
-
-                self.layout.sugarwidth = self.layout.scaled_sugarwidth / self.ctx.scale;
-                self.layout.sugarheight = self.layout.scaled_sugarheight / self.ctx.scale;
-
-                self.layout.update_columns_per_font_width();
-
                 self.ctx.queue.submit(Some(encoder.finish()));
                 frame.present();
             }
@@ -602,6 +1030,8 @@ impl Sugarloaf {
                 if error == wgpu::SurfaceError::OutOfMemory {
                     panic!("Swapchain error: {error}. Rendering cannot continue.")
                 }
+                self.surface_warning
+                    .warn(&format!("unable to get the next frame: {error}"));
             }
         }
     }
@@ -609,6 +1039,21 @@ impl Sugarloaf {
     #[inline]
     fn reset_state(&mut self) {
         self.text_y = 0.0;
+        self.has_pending_stacks = self.stack_budget.finish();
+    }
+
+    /// Lines kept from the last frame because building the frame ran out of
+    /// time, the next frames build them.
+    #[inline]
+    pub fn has_pending_stacks(&self) -> bool {
+        self.has_pending_stacks
+    }
+
+    /// Build every line of the next frame whatever the time it takes, for
+    /// frames that are read back.
+    #[inline]
+    pub fn complete_next_frame(&mut self) {
+        self.stack_budget.complete = true;
     }
 
     #[inline]
@@ -617,6 +1062,13 @@ impl Sugarloaf {
         self
     }
 
+    /// Rects drawn over the text of the frame.
+    #[inline]
+    pub fn pile_overlay_rects(&mut self, mut instances: Vec<Rect>) -> &mut Self {
+        self.overlay_rects.append(&mut instances);
+        self
+    }
+
     #[inline]
     pub fn text(
         &mut self,
@@ -627,10 +1079,45 @@ impl Sugarloaf {
         color: [f32; 4],
         single_line: bool,
     ) -> &mut Self {
+        self.queue_text(pos, &text_str, font_id_usize, scale, color, single_line);
+        self
+    }
+
+    /// Text drawn over the text and the overlay rects of the frame.
+    #[inline]
+    pub fn overlay_text(
+        &mut self,
+        pos: (f32, f32),
+        text: String,
+        font_id: usize,
+        scale: f32,
+        color: [f32; 4],
+        single_line: bool,
+    ) -> &mut Self {
+        self.overlay_texts.push(OverlayText {
+            pos,
+            text,
+            font_id,
+            scale,
+            color,
+            single_line,
+        });
+        self
+    }
+
+    fn queue_text(
+        &mut self,
+        pos: (f32, f32),
+        text_str: &str,
+        font_id_usize: usize,
+        scale: f32,
+        color: [f32; 4],
+        single_line: bool,
+    ) {
         let font_id = FontId(font_id_usize);
 
         let text = crate::components::text::Text {
-            text: &text_str,
+            text: text_str,
             scale: PxScale::from(scale * self.ctx.scale),
             font_id,
             extra: crate::components::text::Extra { color, z: 0.0 },
@@ -654,14 +1141,262 @@ impl Sugarloaf {
         };
 
         self.text_brush.queue(section);
-        self
+    }
+
+    /// Encode the queued background, rects and text into `view`.
+    fn encode_frame(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+    ) {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("sugarloaf::render -> Clear frame"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(
+                        self.ctx.clear_color(self.layout.background_color),
+                    ),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        if let Some(bg_image) = &self.layout.background_image {
+            let layer_brush = self
+                .layer_brush
+                .get_or_insert_with(|| LayerBrush::new(&self.ctx));
+            layer_brush.prepare_ref(encoder, &mut self.ctx, &[bg_image]);
+            layer_brush.render_with_encoder(0, view, encoder, None);
+        }
+
+        self.rect_brush.render(
+            encoder,
+            view,
+            (self.ctx.size.width, self.ctx.size.height),
+            &self.rects,
+            &mut self.ctx,
+        );
+
+        self.rects.clear();
+
+        let _ = self.text_brush.draw_queued(&mut self.ctx, encoder, view);
+
+        if self.overlay_rects.is_empty() && self.overlay_texts.is_empty() {
+            return;
+        }
+
+        self.rect_brush.render(
+            encoder,
+            view,
+            (self.ctx.size.width, self.ctx.size.height),
+            &self.overlay_rects,
+            &mut self.ctx,
+        );
+        self.overlay_rects.clear();
+
+        for text in std::mem::take(&mut self.overlay_texts) {
+            self.queue_text(
+                text.pos,
+                &text.text,
+                text.font_id,
+                text.scale,
+                text.color,
+                text.single_line,
+            );
+        }
+        let _ = self.text_brush.draw_queued(&mut self.ctx, encoder, view);
+    }
+
+    /// Render the queued frame into an offscreen texture and read it back,
+    /// the window surface is left untouched.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn render_to_image(&mut self) -> Option<FrameImage> {
+        self.reset_state();
+
+        let swap_red_blue = match self.ctx.format {
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => {
+                false
+            }
+            format => {
+                log::error!("unable to read back frames with format {format:?}");
+                self.stack_cache = std::mem::take(&mut self.next_stack_cache);
+                return None;
+            }
+        };
+
+        let (width, height) = (self.ctx.size.width, self.ctx.size.height);
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = self.ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("sugarloaf::render_to_image"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.ctx.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bytes_per_row = padded_bytes_per_row(width);
+        let buffer = self.ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("sugarloaf::render_to_image buffer"),
+            size: (bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        // The image is read right away, it can't have glyphs left blank.
+        self.text_brush.set_defer_rasterization(false);
+        self.encode_frame(&mut encoder, &view);
+        self.text_brush.set_defer_rasterization(true);
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            size,
+        );
+
+        self.ctx.staging_belt.finish();
+        self.ctx.queue.submit(Some(encoder.finish()));
+        self.ctx.staging_belt.recall();
+        self.stack_cache = std::mem::take(&mut self.next_stack_cache);
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.ctx.device.poll(wgpu::Maintain::Wait);
+        if let Err(err) = receiver.recv().ok()? {
+            log::error!("unable to map the frame buffer: {err:?}");
+            return None;
+        }
+
+        let pixels = unpad_rows(
+            &slice.get_mapped_range(),
+            width,
+            height,
+            bytes_per_row,
+            swap_red_blue,
+        );
+        buffer.unmap();
+
+        Some(FrameImage {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    #[inline]
+    pub fn glyph_atlas(&self) -> GlyphAtlas {
+        let ((width, height), stats, rows) = self.text_brush.atlas_usage();
+        GlyphAtlas {
+            width,
+            height,
+            stats,
+            rows,
+        }
+    }
+
+    /// Bytes taken by the glyph texture cache.
+    #[inline]
+    pub fn glyph_atlas_memory(&self) -> usize {
+        self.text_brush.texture_memory()
+    }
+
+    /// Shrinks the glyph texture cache to fit in `max_bytes` if the glyphs it
+    /// holds allow it, the smaller cache is used from the next frame.
+    #[inline]
+    pub fn shrink_glyph_atlas(&mut self, max_bytes: usize) -> bool {
+        self.text_brush.shrink_texture(max_bytes)
+    }
+
+    /// Bytes taken by the image texture atlas.
+    #[inline]
+    pub fn image_memory(&self) -> usize {
+        self.layer_brush
+            .as_ref()
+            .map_or(0, |layer_brush| layer_brush.texture_memory())
+    }
+
+    /// Draw the glyph texture cache over the area at `pos` of `size`, along
+    /// with the text of the next frame.
+    #[inline]
+    pub fn show_glyph_atlas(
+        &mut self,
+        pos: (f32, f32),
+        size: (f32, f32),
+        color: [f32; 4],
+    ) {
+        let scale = self.ctx.scale;
+        let area = ab_glyph::Rect {
+            min: ab_glyph::point(pos.0 * scale, pos.1 * scale),
+            max: ab_glyph::point((pos.0 + size.0) * scale, (pos.1 + size.1) * scale),
+        };
+        self.text_brush.queue_atlas(area, color);
+    }
+
+    /// Write timestamps around the frames, when the device supports them.
+    /// Returns whether frames are timed.
+    pub fn set_frame_timing(&mut self, enabled: bool) -> bool {
+        if !enabled {
+            self.frame_timer = None;
+        } else if self.frame_timer.is_none() {
+            self.frame_timer = FrameTimer::new(&self.ctx.device, &self.ctx.queue);
+        }
+        self.frame_timer.is_some()
+    }
+
+    /// Wait for the last frame and return the time the GPU spent on it, None
+    /// unless frames are timed.
+    pub fn last_frame_gpu_time(&mut self) -> Option<Duration> {
+        self.frame_timer
+            .as_mut()
+            .and_then(|frame_timer| frame_timer.read(&self.ctx.device))
+    }
+
+    /// Glyphs are still rasterized in the background, they are drawn by the
+    /// next frames.
+    #[inline]
+    pub fn has_pending_glyphs(&self) -> bool {
+        self.text_brush.has_deferred_glyphs()
     }
 
     #[inline]
     pub fn render(&mut self) {
+        // Headless frames are drawn offscreen and dropped.
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.ctx.surface.is_none() {
+            self.render_to_image();
+            return;
+        }
+
         self.reset_state();
 
-        match self.ctx.surface.get_current_texture() {
+        let Some(surface) = &self.ctx.surface else {
+            return;
+        };
+        match surface.get_current_texture() {
             Ok(frame) => {
                 let mut encoder = self.ctx.device.create_command_encoder(
                     &wgpu::CommandEncoderDescriptor { label: None },
@@ -671,52 +1406,165 @@ impl Sugarloaf {
                     .texture
                     .create_view(&wgpu::TextureViewDescriptor::default());
 
-                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("sugarloaf::render -> Clear frame"),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view,
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(self.layout.background_color),
-                            store: true,
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                });
-
-                if let Some(bg_image) = &self.layout.background_image {
-                    self.layer_brush.prepare_ref(
+                if let Some(frame_timer) = &mut self.frame_timer {
+                    frame_timer.start(&mut encoder);
+                }
+                if self.render_scale < 1.0 {
+                    let mut upscale_brush = self
+                        .upscale_brush
+                        .take()
+                        .unwrap_or_else(|| UpscaleBrush::new(&self.ctx));
+                    let size = upscale::scaled_size(
+                        (self.ctx.size.width, self.ctx.size.height),
+                        self.render_scale,
+                    );
+                    self.encode_frame(
                         &mut encoder,
-                        &mut self.ctx,
-                        &[bg_image],
+                        upscale_brush.target(&self.ctx, size),
                     );
-
-                    self.layer_brush
-                        .render_with_encoder(0, view, &mut encoder, None);
+                    upscale_brush.render(&mut encoder, view);
+                    self.upscale_brush = Some(upscale_brush);
+                } else {
+                    self.encode_frame(&mut encoder, view);
+                }
+                if let Some(frame_timer) = &mut self.frame_timer {
+                    frame_timer.end(&mut encoder);
                 }
 
-                self.rect_brush.render(
-                    &mut encoder,
-                    view,
-                    (self.ctx.size.width, self.ctx.size.height),
-                    &self.rects,
-                    &mut self.ctx,
-                );
-
-                self.rects = vec![];
-
-                let _ = self
-                    .text_brush
-                    .draw_queued(&mut self.ctx, &mut encoder, view);
-
+                self.ctx.staging_belt.finish();
                 self.ctx.queue.submit(Some(encoder.finish()));
                 frame.present();
+                self.ctx.staging_belt.recall();
             }
             Err(error) => {
                 if error == wgpu::SurfaceError::OutOfMemory {
                     panic!("Swapchain error: {error}. Rendering cannot continue.")
                 }
+                self.surface_warning
+                    .warn(&format!("unable to get the next frame: {error}"));
             }
         }
+
+        // Stacks not used by this frame are dropped.
+        self.stack_cache = std::mem::take(&mut self.next_stack_cache);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_image_hash() {
+        let frame = FrameImage {
+            width: 2,
+            height: 1,
+            pixels: vec![0, 0, 0, 255, 255, 255, 255, 255],
+        };
+        assert_eq!(frame.hash(), frame.clone().hash());
+
+        let mut changed = frame.clone();
+        changed.pixels[4] = 254;
+        assert_ne!(frame.hash(), changed.hash());
+
+        // Same pixels, different shape.
+        let mut reshaped = frame.clone();
+        (reshaped.width, reshaped.height) = (1, 2);
+        assert_ne!(frame.hash(), reshaped.hash());
+    }
+
+    #[test]
+    fn test_unpad_rows() {
+        let width = 2;
+        let bytes_per_row = padded_bytes_per_row(width);
+        assert_eq!(bytes_per_row, wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let mut data = vec![0; (bytes_per_row * 2) as usize];
+        data[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        let second = bytes_per_row as usize;
+        data[second..second + 8].copy_from_slice(&[9, 10, 11, 12, 13, 14, 15, 16]);
+
+        assert_eq!(
+            unpad_rows(&data, width, 2, bytes_per_row, false),
+            vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]
+        );
+        assert_eq!(
+            unpad_rows(&data, width, 2, bytes_per_row, true),
+            vec![3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16]
+        );
+    }
+
+    #[test]
+    fn test_stack_budget() {
+        let start = Instant::now();
+        let late = start + STACK_BUDGET * 2;
+        let mut budget = StackBudget::new(STACK_BUDGET);
+
+        // Nothing shown before, everything is built.
+        for key in [1, 2, 3] {
+            assert_eq!(budget.stale_key(late, false), None);
+            budget.next_line(key);
+        }
+        assert!(!budget.finish());
+
+        // Out of time on the second line, the third keeps its last stack.
+        assert_eq!(budget.stale_key(start, false), None);
+        budget.next_line(4);
+        assert_eq!(budget.stale_key(late, true), None);
+        budget.next_line(2);
+        assert_eq!(budget.stale_key(late, false), Some(3));
+        budget.next_line(3);
+        assert!(budget.finish());
+
+        // The next frame starts from the third line, changes above wait.
+        assert_eq!(budget.stale_key(start, false), Some(4));
+        budget.next_line(4);
+        assert_eq!(budget.stale_key(start, true), None);
+        budget.next_line(5);
+        assert_eq!(budget.stale_key(start, false), None);
+        budget.next_line(6);
+        assert!(budget.finish());
+
+        // Then from the top again.
+        for key in [7, 8, 9] {
+            assert_eq!(budget.stale_key(start, false), None);
+            budget.next_line(key);
+        }
+        assert!(!budget.finish());
+
+        budget.complete = true;
+        assert_eq!(budget.stale_key(start, false), None);
+        budget.next_line(1);
+        assert_eq!(budget.stale_key(late, false), None);
+        budget.next_line(2);
+        assert!(!budget.finish());
+        assert!(!budget.complete);
+    }
+
+    #[test]
+    fn test_rate_limited_warning() {
+        let mut warning = RateLimitedWarning::default();
+        let start = Instant::now();
+
+        assert_eq!(
+            warning.message_at("lost", start),
+            Some(String::from("lost"))
+        );
+        assert_eq!(
+            warning.message_at("lost", start + Duration::from_secs(1)),
+            None
+        );
+        assert_eq!(
+            warning.message_at("lost", start + Duration::from_secs(2)),
+            None
+        );
+        assert_eq!(
+            warning.message_at("lost", start + WARNING_INTERVAL),
+            Some(String::from("lost (2 more since the last warning)"))
+        );
+        assert_eq!(
+            warning.message_at("lost", start + WARNING_INTERVAL * 2),
+            Some(String::from("lost"))
+        );
     }
 }
@@ -0,0 +1,199 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+// Renders a scripted set of frames offscreen and compares the hashes of the
+// pixels read back with the ones recorded in `render_hashes.txt` for the
+// backend and adapter in use, so unintended rendering changes show up without
+// comparing images. Hashes are only stable on the same backend and driver,
+// frames of an adapter without recorded hashes are printed instead.
+//
+// Record the hashes of the current backend again after an intended change:
+//
+//     SUGARLOAF_UPDATE_RENDER_HASHES=1 cargo test --workspace render_hash
+
+use std::collections::BTreeMap;
+use sugarloaf::components::rect::Rect;
+use sugarloaf::core::{Sugar, SugarDecoration, SugarStyle};
+use sugarloaf::font::fonts::SugarloafFonts;
+use sugarloaf::font::loader::Database;
+use sugarloaf::layout::SugarloafLayout;
+use sugarloaf::Sugarloaf;
+
+const HASHES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/render_hashes.txt");
+
+const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+fn sugar(content: char, foreground_color: [f32; 4], background_color: [f32; 4]) -> Sugar {
+    Sugar {
+        content,
+        foreground_color,
+        background_color,
+        style: None,
+        decoration: None,
+    }
+}
+
+fn line(text: &str) -> Vec<Sugar> {
+    text.chars().map(|c| sugar(c, WHITE, BLACK)).collect()
+}
+
+/// Queues the content of a frame.
+type Frame = fn(&mut Sugarloaf);
+
+/// Frames of the script by name.
+fn script() -> Vec<(&'static str, Frame)> {
+    vec![
+        ("text", |sugarloaf| {
+            sugarloaf.stack(line("The quick brown fox jumps over the lazy dog"));
+            sugarloaf.stack(line("0123456789 !@#$%^&*()[]{}<>/\\|~`'\";:,.?"));
+        }),
+        ("colors", |sugarloaf| {
+            let colors = [
+                [0.8, 0.2, 0.2, 1.0],
+                [0.2, 0.8, 0.2, 1.0],
+                [0.2, 0.2, 0.8, 1.0],
+                [0.9, 0.9, 0.2, 1.0],
+            ];
+            let stack = "colored cells"
+                .chars()
+                .enumerate()
+                .map(|(index, c)| sugar(c, colors[index % 4], colors[(index + 1) % 4]))
+                .collect();
+            sugarloaf.stack(stack);
+        }),
+        ("styles", |sugarloaf| {
+            // Bold, italic and bold italic.
+            let styles = [
+                (false, true, false),
+                (true, false, false),
+                (false, false, true),
+            ];
+            for (is_italic, is_bold, is_bold_italic) in styles {
+                let stack = line("styled text")
+                    .into_iter()
+                    .map(|sugar| Sugar {
+                        style: Some(SugarStyle {
+                            is_italic,
+                            is_bold,
+                            is_bold_italic,
+                        }),
+                        decoration: Some(SugarDecoration {
+                            relative_position: (0.0, 0.95),
+                            size: (1.0, 0.005),
+                            color: WHITE,
+                        }),
+                        ..sugar
+                    })
+                    .collect();
+                sugarloaf.stack(stack);
+            }
+        }),
+        ("wide", |sugarloaf| {
+            sugarloaf.stack(line("永和九年 ⌚ 😀 ✓ ±"));
+        }),
+        ("rects", |sugarloaf| {
+            sugarloaf.pile_rects(vec![
+                Rect {
+                    position: [10.0, 10.0],
+                    color: [0.5, 0.5, 1.0, 1.0],
+                    size: [100.0, 40.0],
+                },
+                Rect {
+                    position: [60.0, 30.0],
+                    color: [1.0, 0.5, 0.5, 0.5],
+                    size: [100.0, 40.0],
+                },
+            ]);
+        }),
+    ]
+}
+
+fn read_hashes() -> BTreeMap<(String, String), u64> {
+    let content = std::fs::read_to_string(HASHES).unwrap_or_default();
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let backend = parts.next()?.to_string();
+            let frame = parts.next()?.to_string();
+            let hash = u64::from_str_radix(parts.next()?, 16).ok()?;
+            Some(((backend, frame), hash))
+        })
+        .collect()
+}
+
+fn write_hashes(hashes: &BTreeMap<(String, String), u64>) {
+    let mut content = String::from("# backend frame hash, see render_hash.rs\n");
+    for ((backend, frame), hash) in hashes {
+        content.push_str(&format!("{backend} {frame} {hash:016x}\n"));
+    }
+    std::fs::write(HASHES, content).unwrap();
+}
+
+#[test]
+fn render_hash() {
+    let layout =
+        SugarloafLayout::new(400.0, 200.0, (10.0, 10.0, 0.0), 1.0, 16.0, 1.0, (2, 1));
+    // Only the bundled fonts, the system ones differ between machines.
+    let db = Database::new();
+    let mut sugarloaf = match futures::executor::block_on(Sugarloaf::new_headless(
+        wgpu::PowerPreference::LowPower,
+        SugarloafFonts::default(),
+        layout,
+        Some(&db),
+    )) {
+        Some(Ok(sugarloaf)) => sugarloaf,
+        Some(Err(with_errors)) => with_errors.instance,
+        None => {
+            eprintln!("no adapter available, skipping the render hashes");
+            return;
+        }
+    };
+    sugarloaf.set_background_color(wgpu::Color::BLACK);
+    sugarloaf.calculate_bounds();
+
+    // Drivers of a backend don't rasterize alike, hashes are kept per adapter.
+    let info = &sugarloaf.ctx.adapter_info;
+    let backend = format!("{:?}/{}", info.backend, info.name)
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-");
+    let mut hashes = read_hashes();
+    let update = std::env::var_os("SUGARLOAF_UPDATE_RENDER_HASHES").is_some();
+    let mut mismatches = vec![];
+
+    for (frame, queue) in script() {
+        let mut render = || {
+            sugarloaf.complete_next_frame();
+            queue(&mut sugarloaf);
+            sugarloaf.render_to_image().expect("frame read back").hash()
+        };
+        let hash = render();
+        // The second time the stacks and glyphs come from the caches.
+        assert_eq!(hash, render(), "frame {frame} changed when drawn again");
+
+        let key = (backend.clone(), frame.to_string());
+        if update {
+            hashes.insert(key, hash);
+            continue;
+        }
+        match hashes.get(&key) {
+            Some(expected) if *expected != hash => mismatches.push(format!(
+                "{frame}: expected {expected:016x}, got {hash:016x}"
+            )),
+            Some(_) => {}
+            None => eprintln!("{backend} {frame} {hash:016x}"),
+        }
+    }
+
+    if update {
+        write_hashes(&hashes);
+    }
+    assert!(
+        mismatches.is_empty(),
+        "rendering changed on {backend}:\n{}",
+        mismatches.join("\n")
+    );
+}
@@ -57,6 +57,7 @@ async fn run() {
     let mut sugarloaf = Sugarloaf::new(
         &window,
         wgpu::PowerPreference::HighPerformance,
+        sugarloaf::context::ColorSpace::Srgb,
         sugarloaf::font::fonts::Fonts::default(),
     )
     .await
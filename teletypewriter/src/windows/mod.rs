@@ -32,11 +32,12 @@ pub struct Pty {
 pub fn create_pty(
     shell: &str,
     _args: Vec<String>,
+    env: &[(String, String)],
     working_directory: &Option<String>,
     columns: u16,
     rows: u16,
 ) -> Pty {
-    conpty::new(shell, working_directory, columns, rows)
+    conpty::new(shell, env, working_directory, columns, rows)
         .ok_or_else(|| panic!("failed to spawn conpty"))
         .unwrap()
 }
@@ -229,8 +230,57 @@ fn cmdline(shell: &str) -> String {
         .join(" ")
 }
 
+/// Spawn `program` detached from Rio, without a console of its own.
+pub fn spawn_daemon<I, S>(program: &str, args: I) -> io::Result<()>
+where
+    I: IntoIterator<Item = S> + Copy,
+    S: AsRef<OsStr>,
+{
+    use std::os::windows::process::CommandExt;
+    use std::process::{Command, Stdio};
+    use windows_sys::Win32::System::Threading::{
+        CREATE_NEW_PROCESS_GROUP, DETACHED_PROCESS,
+    };
+
+    Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP)
+        .spawn()
+        .map(|_| ())
+}
+
 /// Converts the string slice into a Windows-standard representation for "W"-
 /// suffixed function variants, which accept UTF-16 encoded string values.
 pub fn win32_string<S: AsRef<OsStr> + ?Sized>(value: &S) -> Vec<u16> {
     OsStr::new(value).encode_wide().chain(once(0)).collect()
 }
+
+/// Environment block of the current process with `env` added, `None` when
+/// there is nothing to add so the child inherits it.
+pub fn environment_block(env: &[(String, String)]) -> Option<Vec<u16>> {
+    if env.is_empty() {
+        return None;
+    }
+
+    let mut vars: Vec<(std::ffi::OsString, std::ffi::OsString)> =
+        std::env::vars_os().collect();
+    for (key, value) in env {
+        vars.retain(|(name, _)| !name.eq_ignore_ascii_case(key));
+        vars.push((key.into(), value.into()));
+    }
+    // Windows expects the variables sorted without regard to case.
+    vars.sort_by_key(|(name, _)| name.to_string_lossy().to_uppercase());
+
+    let mut block = Vec::new();
+    for (name, value) in vars {
+        block.extend(name.encode_wide());
+        block.push('=' as u16);
+        block.extend(value.encode_wide());
+        block.push(0);
+    }
+    block.push(0);
+    Some(block)
+}